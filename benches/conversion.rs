@@ -1,4 +1,5 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use mzpeak::bench_data::{self, BenchManifest};
 use mzpeak::dataset::MzPeakDatasetWriter;
 use mzpeak::metadata::MzPeakMetadata;
 use mzpeak::mzml::converter::{ConversionConfig, MzMLConverter};
@@ -136,6 +137,56 @@ fn bench_conversion(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmark conversion of cached public mzML datasets
+///
+/// Run `mzpeak bench fetch` first; datasets that are not cached are skipped.
+fn bench_public_conversion(c: &mut Criterion) {
+    let Some(cache_dir) = bench_data::cache_dir() else {
+        return;
+    };
+    let manifest = BenchManifest::builtin().expect("Invalid dataset manifest");
+    let cached: Vec<_> = manifest
+        .cached(&cache_dir)
+        .into_iter()
+        .filter(|(_, path)| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("mzml"))
+        })
+        .collect();
+    if cached.is_empty() {
+        return;
+    }
+
+    let mut group = c.benchmark_group("public_mzml_conversion");
+    group.sample_size(10);
+
+    for (dataset, mzml_path) in cached {
+        let bytes = fs::metadata(&mzml_path).expect("Cached dataset vanished").len();
+        group.throughput(Throughput::Bytes(bytes));
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(&dataset.name),
+            &mzml_path,
+            |b, mzml_path| {
+                b.iter_batched(
+                    || TempDir::new().unwrap(),
+                    |temp_dir| {
+                        let output_path = temp_dir.path().join("public.mzpeak");
+                        let converter = MzMLConverter::with_config(ConversionConfig::default());
+                        let _stats = converter
+                            .convert(mzml_path, &output_path)
+                            .expect("Conversion failed");
+                        drop(temp_dir);
+                    },
+                    criterion::BatchSize::PerIteration,
+                );
+            },
+        );
+    }
+
+    group.finish();
+}
+
 /// Benchmark peak writing throughput
 fn bench_peak_writing(c: &mut Criterion) {
     let mut group = c.benchmark_group("peak_writing");
@@ -290,6 +341,7 @@ fn bench_per_peak_overhead(c: &mut Criterion) {
 criterion_group!(
     benches,
     bench_conversion,
+    bench_public_conversion,
     bench_peak_writing,
     bench_peak_writing_arrays,
     bench_per_peak_overhead
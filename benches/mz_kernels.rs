@@ -0,0 +1,145 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use mzpeak::processing::mz_kernels::{self, scalar};
+
+/// Sorted, centroid-like m/z array with matching intensities
+fn centroided_peaks(num_peaks: usize) -> (Vec<f64>, Vec<f32>) {
+    let mz = (0..num_peaks)
+        .map(|i| 150.0 + i as f64 * (1850.0 / num_peaks as f64))
+        .collect();
+    let intensity = (0..num_peaks)
+        .map(|i| 1000.0 + (i % 97) as f32 * 10.0)
+        .collect();
+    (mz, intensity)
+}
+
+const PEAK_COUNTS: [usize; 3] = [1_000, 10_000, 100_000];
+
+/// Benchmark the bit-packed m/z range mask
+fn bench_range_mask(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mz_range_mask");
+
+    for num_peaks in PEAK_COUNTS {
+        let (mz, _) = centroided_peaks(num_peaks);
+        group.throughput(Throughput::Elements(num_peaks as u64));
+
+        group.bench_with_input(BenchmarkId::new("simd", num_peaks), &mz, |b, mz| {
+            b.iter(|| black_box(mz_kernels::mz_range_mask(black_box(mz), 500.0, 800.0)));
+        });
+        group.bench_with_input(BenchmarkId::new("scalar", num_peaks), &mz, |b, mz| {
+            b.iter(|| black_box(scalar::mz_range_mask(black_box(mz), 500.0, 800.0)));
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmark the XIC inner loop (summed intensity in a ppm window)
+fn bench_intensity_in_range(c: &mut Criterion) {
+    let mut group = c.benchmark_group("intensity_in_range");
+
+    for num_peaks in PEAK_COUNTS {
+        let (mz, intensity) = centroided_peaks(num_peaks);
+        let (lower, upper) = (524.2648 * (1.0 - 1e-5), 524.2648 * (1.0 + 1e-5));
+        group.throughput(Throughput::Elements(num_peaks as u64));
+
+        group.bench_function(BenchmarkId::new("simd", num_peaks), |b| {
+            b.iter(|| {
+                black_box(mz_kernels::intensity_in_range(
+                    black_box(&mz),
+                    black_box(&intensity),
+                    lower,
+                    upper,
+                ))
+            });
+        });
+        group.bench_function(BenchmarkId::new("scalar", num_peaks), |b| {
+            b.iter(|| {
+                black_box(scalar::intensity_in_range(
+                    black_box(&mz),
+                    black_box(&intensity),
+                    lower,
+                    upper,
+                ))
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmark nearest-peak lookups in sorted m/z arrays
+fn bench_nearest_index(c: &mut Criterion) {
+    let mut group = c.benchmark_group("nearest_index");
+
+    for num_peaks in PEAK_COUNTS {
+        let (mz, _) = centroided_peaks(num_peaks);
+        let targets: Vec<f64> = (0..256).map(|i| 150.0 + i as f64 * 7.3).collect();
+        group.throughput(Throughput::Elements(targets.len() as u64));
+
+        group.bench_function(BenchmarkId::new("simd", num_peaks), |b| {
+            b.iter(|| {
+                for &target in &targets {
+                    black_box(mz_kernels::nearest_index(black_box(&mz), target));
+                }
+            });
+        });
+        group.bench_function(BenchmarkId::new("scalar", num_peaks), |b| {
+            b.iter(|| {
+                for &target in &targets {
+                    black_box(scalar::nearest_index(black_box(&mz), target));
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmark binning intensities into 1 Da m/z bins
+fn bench_binning(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bin_intensities");
+
+    for num_peaks in PEAK_COUNTS {
+        let (mz, intensity) = centroided_peaks(num_peaks);
+        let mut bins = vec![0.0f64; 1900];
+        group.throughput(Throughput::Elements(num_peaks as u64));
+
+        group.bench_function(BenchmarkId::new("simd", num_peaks), |b| {
+            b.iter(|| {
+                bins.fill(0.0);
+                mz_kernels::bin_intensities(
+                    black_box(&mz),
+                    black_box(&intensity),
+                    100.0,
+                    1.0,
+                    &mut bins,
+                );
+                black_box(&bins);
+            });
+        });
+        group.bench_function(BenchmarkId::new("scalar", num_peaks), |b| {
+            b.iter(|| {
+                bins.fill(0.0);
+                scalar::bin_intensities(
+                    black_box(&mz),
+                    black_box(&intensity),
+                    100.0,
+                    1.0,
+                    &mut bins,
+                );
+                black_box(&bins);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_range_mask,
+    bench_intensity_in_range,
+    bench_nearest_index,
+    bench_binning
+);
+criterion_main!(benches);
@@ -0,0 +1,159 @@
+//! Cookbook: convert an mzML file and query the result
+//!
+//! Converts an mzML file to an mzPeak v2 container, then opens it and runs
+//! the typical first queries: counts from the manifest, row group density,
+//! spectrum lookup by ID and a retention time window.
+//!
+//! Run with:
+//! ```text
+//! cargo run --example convert_and_query -- run.mzML
+//! ```
+//!
+//! Without an argument a small synthetic mzML file is generated first.
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use mzpeak::mzml::converter::{ConversionConfig, MzMLConverter, ScanFilters};
+use mzpeak::reader::{MzPeakReader, RtRange};
+use tempfile::tempdir;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let input = match std::env::args().nth(1) {
+        Some(path) => PathBuf::from(path),
+        None => write_synthetic_mzml(&dir.path().join("synthetic.mzML"), 120)?,
+    };
+    let output = dir.path().join("run.mzpeak");
+
+    // 1. Convert. Every option has a default; here MS3+ scans are skipped.
+    let config = ConversionConfig {
+        scan_filters: ScanFilters {
+            skip_ms_levels: [3, 4].into_iter().collect(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let stats = MzMLConverter::with_config(config).convert(&input, &output)?;
+    println!(
+        "Converted {} spectra ({} MS1, {} MS2) with {} peaks, {:.1}x smaller",
+        stats.spectra_count,
+        stats.ms1_spectra,
+        stats.ms2_spectra,
+        stats.peak_count,
+        stats.compression_ratio
+    );
+
+    // 2. The manifest answers "what is in here" without touching peak data
+    let reader = MzPeakReader::open(&output)?;
+    if let Some(manifest) = reader.manifest()? {
+        println!(
+            "{:?} container: {} spectra, {} peaks, written by {}",
+            manifest.modality, manifest.spectrum_count, manifest.peak_count, manifest.converter
+        );
+    }
+    if let Some(layout) = reader.layout_stats()? {
+        for (index, row_group) in layout.row_groups.iter().enumerate() {
+            println!(
+                "  row group {}: spectra {}-{}, {:.0} peaks/spectrum",
+                index,
+                row_group.first_spectrum_id,
+                row_group.last_spectrum_id,
+                row_group.mean_peaks_per_spectrum()
+            );
+        }
+    }
+
+    // 3. Random access by spectrum ID decodes only that spectrum
+    if let Some(spectrum) = reader.get_spectrum_arrays(0)? {
+        let mz = spectrum.mz_arrays()?;
+        let first_mz = mz.first().and_then(|array| array.values().first());
+        println!(
+            "Spectrum 0: MS{} at {:.1} s, {} peaks, first m/z {:?}",
+            spectrum.ms_level,
+            spectrum.retention_time,
+            spectrum.peak_count(),
+            first_mz
+        );
+    }
+
+    // 4. Select spectra by metadata; each lookup seeks straight to its peaks
+    let window = RtRange::seconds(10.0, 20.0);
+    let spectrum_count = reader
+        .manifest()?
+        .map_or(0, |manifest| manifest.spectrum_count);
+    let mut ms2_in_window = 0;
+    for spectrum_id in 0..spectrum_count as i64 {
+        if let Some(spectrum) = reader.get_spectrum_arrays(spectrum_id)? {
+            if spectrum.ms_level == 2 && window.contains(spectrum.retention_time) {
+                ms2_in_window += 1;
+            }
+        }
+    }
+    println!("{} MS2 spectra between 10 and 20 s", ms2_in_window);
+
+    Ok(())
+}
+
+/// Write an mzML file with `count` spectra, every fifth one MS1
+fn write_synthetic_mzml(path: &Path, count: usize) -> Result<PathBuf, Box<dyn Error>> {
+    let encode = |bytes: Vec<u8>| base64::engine::general_purpose::STANDARD.encode(bytes);
+    let mut mzml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<mzML xmlns="http://psi.hupo.org/ms/mzml" version="1.1.0">
+  <run id="synthetic">
+    <spectrumList count="{}">"#,
+        count
+    );
+    for index in 0..count {
+        let ms_level = if index % 5 == 0 { 1 } else { 2 };
+        let mz: Vec<u8> = (0..50)
+            .flat_map(|i| (200.0 + i as f64 * 15.0 + index as f64 * 0.01).to_le_bytes())
+            .collect();
+        let intensity: Vec<u8> = (0..50)
+            .flat_map(|i| (1000.0 + i as f32 * 40.0).to_le_bytes())
+            .collect();
+        mzml.push_str(&format!(
+            r#"
+      <spectrum index="{index}" id="scan={scan}" defaultArrayLength="50">
+        <cvParam cvRef="MS" accession="MS:1000511" name="ms level" value="{ms_level}"/>
+        <cvParam cvRef="MS" accession="MS:1000130" name="positive scan"/>
+        <scanList count="1">
+          <scan>
+            <cvParam cvRef="MS" accession="MS:1000016" name="scan start time" value="{rt}" unitCvRef="UO" unitAccession="UO:0000010" unitName="second"/>
+          </scan>
+        </scanList>
+        <binaryDataArrayList count="2">
+          <binaryDataArray>
+            <cvParam cvRef="MS" accession="MS:1000523" name="64-bit float"/>
+            <cvParam cvRef="MS" accession="MS:1000576" name="no compression"/>
+            <cvParam cvRef="MS" accession="MS:1000514" name="m/z array"/>
+            <binary>{mz}</binary>
+          </binaryDataArray>
+          <binaryDataArray>
+            <cvParam cvRef="MS" accession="MS:1000521" name="32-bit float"/>
+            <cvParam cvRef="MS" accession="MS:1000576" name="no compression"/>
+            <cvParam cvRef="MS" accession="MS:1000515" name="intensity array"/>
+            <binary>{intensity}</binary>
+          </binaryDataArray>
+        </binaryDataArrayList>
+      </spectrum>"#,
+            index = index,
+            scan = index + 1,
+            ms_level = ms_level,
+            rt = index as f64 * 0.5,
+            mz = encode(mz),
+            intensity = encode(intensity),
+        ));
+    }
+    mzml.push_str(
+        r#"
+    </spectrumList>
+  </run>
+</mzML>
+"#,
+    );
+    std::fs::write(path, mzml)?;
+    Ok(path.to_path_buf())
+}
@@ -0,0 +1,92 @@
+//! Cookbook: read a container stored in S3
+//!
+//! No build reads object stores natively yet (`capabilities().object_store`
+//! is false). Containers in S3, GCS or Azure are read through a filesystem
+//! mount instead, such as mountpoint-s3, s3fs or gcsfuse:
+//!
+//! ```text
+//! mount-s3 my-bucket /mnt/my-bucket
+//! cargo run --example read_remote_s3 -- /mnt/my-bucket/runs/run.mzpeak
+//! ```
+//!
+//! The Parquet tables inside a container are stored uncompressed, so the
+//! reader only issues range reads: the ZIP directory, the manifest, the
+//! Parquet footers and then just the pages a query needs. On a mount every
+//! range read becomes a ranged GET, and the settings below keep their number
+//! low. Without an argument a local container stands in for the mount.
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use mzpeak::capabilities;
+use mzpeak::dataset::MzPeakDatasetWriterV2;
+use mzpeak::reader::{MzPeakReader, ReaderConfig};
+use mzpeak::schema::manifest::Modality;
+use mzpeak::writer::{PeakArraysV2, SpectrumMetadata, SpectrumV2};
+use tempfile::tempdir;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    println!(
+        "native object store support: {}",
+        capabilities().object_store
+    );
+
+    let dir = tempdir()?;
+    let path = match std::env::args().nth(1) {
+        Some(path) => PathBuf::from(path),
+        None => write_local_container(&dir.path().join("stand_in.mzpeak"))?,
+    };
+
+    let config = ReaderConfig {
+        // Fewer, larger requests: decode bigger batches per read
+        batch_size: 256 * 1024,
+        // Hide request latency by fetching row groups ahead during scans
+        prefetch_row_groups: 4,
+        ..Default::default()
+    };
+
+    let started = Instant::now();
+    let reader = MzPeakReader::open_with_config(&path, config)?;
+    println!("opened {} in {:?}", path.display(), started.elapsed());
+
+    // Small JSON entries: one or two requests each
+    if let Some(manifest) = reader.manifest()? {
+        println!(
+            "{} spectra, {} peaks, {:?}",
+            manifest.spectrum_count, manifest.peak_count, manifest.modality
+        );
+    }
+    if let Some(layout) = reader.layout_stats()? {
+        println!(
+            "{} peak row groups of up to {} peaks",
+            layout.row_groups.len(),
+            layout.row_group_size
+        );
+    }
+
+    // A lookup reads the spectra table once, then only the pages holding the
+    // requested spectrum
+    let started = Instant::now();
+    if let Some(spectrum) = reader.get_spectrum_arrays(0)? {
+        println!(
+            "spectrum 0: {} peaks, fetched in {:?}",
+            spectrum.peak_count(),
+            started.elapsed()
+        );
+    }
+    Ok(())
+}
+
+/// Write a small container to stand in for a mounted one
+fn write_local_container(path: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    let mut writer = MzPeakDatasetWriterV2::new(path, Modality::LcMs, None)?;
+    for id in 0..100u32 {
+        let mz: Vec<f64> = (0..200).map(|i| 100.0 + f64::from(i) * 5.0).collect();
+        let intensity = vec![1.0e4; mz.len()];
+        let metadata = SpectrumMetadata::new_ms1(id, Some(id as i32 + 1), id as f32, 1, 200);
+        writer.write_spectrum(&SpectrumV2::new(metadata, PeakArraysV2::new(mz, intensity)))?;
+    }
+    writer.close()?;
+    Ok(path.to_path_buf())
+}
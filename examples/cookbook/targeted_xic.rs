@@ -0,0 +1,103 @@
+//! Cookbook: targeted extracted ion chromatograms
+//!
+//! Extracts one chromatogram per target m/z from the MS1 spectra of an mzPeak
+//! file and reports the apex and area of each, then pulls the raw peaks
+//! around the first target with an m/z range query.
+//!
+//! Run with:
+//! ```text
+//! cargo run --example targeted_xic -- run.mzpeak.parquet 445.1200 524.2648
+//! ```
+//!
+//! Without arguments a synthetic LC run with two eluting analytes is written
+//! first.
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use mzpeak::metadata::MzPeakMetadata;
+use mzpeak::processing::MassTolerance;
+use mzpeak::reader::MzPeakReader;
+use mzpeak::writer::{MzPeakWriter, PeakArrays, SpectrumArrays, WriterConfig};
+use tempfile::tempdir;
+
+/// Analytes of the synthetic run: m/z, apex retention time (s), height
+const SYNTHETIC_ANALYTES: [(f64, f32, f32); 2] = [(445.1200, 60.0, 5.0e6), (524.2648, 95.0, 2.0e6)];
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (path, targets) = match args.split_first() {
+        Some((path, targets)) => (
+            PathBuf::from(path),
+            targets
+                .iter()
+                .map(|target| target.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+        None => (
+            write_synthetic_run(&dir.path().join("synthetic.mzpeak.parquet"))?,
+            SYNTHETIC_ANALYTES.iter().map(|(mz, _, _)| *mz).collect(),
+        ),
+    };
+
+    let reader = MzPeakReader::open(&path)?;
+    let tolerance = MassTolerance::Ppm(10.0);
+    for xic in reader.extract_xics(&targets, tolerance)? {
+        let apex = xic
+            .intensities
+            .iter()
+            .zip(&xic.retention_times)
+            .max_by(|a, b| a.0.total_cmp(b.0));
+        // Trapezoidal area over retention time
+        let area: f64 = xic
+            .retention_times
+            .windows(2)
+            .zip(xic.intensities.windows(2))
+            .map(|(rt, intensity)| f64::from(rt[1] - rt[0]) * (intensity[0] + intensity[1]) / 2.0)
+            .sum();
+        match apex {
+            Some((height, rt)) if *height > 0.0 => println!(
+                "m/z {:.4} [{:.4}, {:.4}]: apex {:.3e} at {:.1} s, area {:.3e}",
+                xic.target_mz, xic.lower_mz, xic.upper_mz, height, rt, area
+            ),
+            _ => println!("m/z {:.4}: not detected", xic.target_mz),
+        }
+    }
+
+    // Raw peaks inside a window; page statistics skip pages outside it
+    if let Some(&target) = targets.first() {
+        let window = tolerance.window(target);
+        let batches = reader.peaks_in_mz_range(target - window, target + window)?;
+        let rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+        println!("{} peaks within {:.4} of m/z {:.4}", rows, window, target);
+    }
+
+    Ok(())
+}
+
+/// Write two minutes of MS1 spectra with Gaussian elution profiles
+fn write_synthetic_run(path: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    let metadata = MzPeakMetadata::new();
+    let mut writer = MzPeakWriter::new_file(path, &metadata, WriterConfig::default())?;
+    for scan in 0..120 {
+        let rt = scan as f32;
+        let mut mz: Vec<f64> = (0..40).map(|i| 300.0 + i as f64 * 12.5).collect();
+        let mut intensity = vec![1.0e3; mz.len()];
+        for (analyte_mz, apex, height) in SYNTHETIC_ANALYTES {
+            mz.push(analyte_mz);
+            intensity.push(height * (-((rt - apex) / 6.0).powi(2) / 2.0).exp());
+        }
+        let mut order: Vec<usize> = (0..mz.len()).collect();
+        order.sort_by(|&a, &b| mz[a].total_cmp(&mz[b]));
+        let peaks = PeakArrays::new(
+            order.iter().map(|&i| mz[i]).collect(),
+            order.iter().map(|&i| intensity[i]).collect(),
+        );
+        let mut spectrum = SpectrumArrays::new_ms1(scan, scan + 1, rt, 1, peaks);
+        spectrum.compute_statistics();
+        writer.write_spectrum_arrays(&spectrum)?;
+    }
+    writer.finish()?;
+    Ok(path.to_path_buf())
+}
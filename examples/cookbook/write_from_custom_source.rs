@@ -0,0 +1,127 @@
+//! Cookbook: write mzPeak from your own data source
+//!
+//! Instruments and in-house formats without a built-in converter can still
+//! produce mzPeak: map each scan onto the ingestion contract
+//! ([`IngestSpectrum`]), let [`IngestSpectrumConverter`] check it, and stream
+//! the result into a v2 container. The container is validated at the end.
+//!
+//! Run with:
+//! ```text
+//! cargo run --example write_from_custom_source
+//! ```
+
+use std::error::Error;
+
+use mzpeak::dataset::MzPeakDatasetWriterV2;
+use mzpeak::ingest::{IngestSpectrum, IngestSpectrumConverter};
+use mzpeak::metadata::{MzPeakMetadata, SourceFileInfo, VendorHints};
+use mzpeak::reader::MzPeakReader;
+use mzpeak::schema::manifest::Modality;
+use mzpeak::validator::validate_mzpeak_file;
+use mzpeak::writer::{PeakArrays, SpectrumV2};
+use tempfile::tempdir;
+
+/// A scan as an in-house acquisition system might report it
+struct LabScan {
+    scan: u32,
+    time_minutes: f64,
+    precursor: Option<(f64, i16)>,
+    /// (m/z, counts) pairs, not necessarily sorted
+    centroids: Vec<(f64, u32)>,
+}
+
+/// Stand-in for a reader of the in-house format
+fn lab_scans() -> impl Iterator<Item = LabScan> {
+    (0..60u32).map(|scan| {
+        let precursor = (scan % 4 != 0).then(|| (400.0 + f64::from(scan % 4) * 50.0, 2));
+        let centroids = (0..30)
+            .rev()
+            .map(|i| (150.0 + f64::from(i) * 20.0, 500 + i * scan))
+            .collect();
+        LabScan {
+            scan: scan + 1,
+            time_minutes: f64::from(scan) * 0.05,
+            precursor,
+            centroids,
+        }
+    })
+}
+
+/// Map a scan onto the ingestion contract: seconds, sorted m/z, f32 intensity
+fn to_ingest(spectrum_id: i64, mut scan: LabScan) -> IngestSpectrum {
+    scan.centroids.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let (mz, intensity) = scan
+        .centroids
+        .iter()
+        .map(|&(mz, counts)| (mz, counts as f32))
+        .unzip();
+    IngestSpectrum {
+        spectrum_id,
+        scan_number: i64::from(scan.scan),
+        ms_level: if scan.precursor.is_some() { 2 } else { 1 },
+        retention_time: (scan.time_minutes * 60.0) as f32,
+        polarity: 1,
+        precursor_mz: scan.precursor.map(|(mz, _)| mz),
+        precursor_charge: scan.precursor.map(|(_, charge)| charge),
+        precursor_intensity: None,
+        isolation_window_lower: scan.precursor.map(|_| 1.0),
+        isolation_window_upper: scan.precursor.map(|_| 1.0),
+        collision_energy: scan.precursor.map(|_| 27.0),
+        total_ion_current: None,
+        base_peak_mz: None,
+        base_peak_intensity: None,
+        injection_time: None,
+        pixel_x: None,
+        pixel_y: None,
+        pixel_z: None,
+        peaks: PeakArrays::new(mz, intensity),
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().join("lab_run.mzpeak");
+
+    // Provenance travels with the data
+    let mut metadata = MzPeakMetadata::new();
+    metadata.source_file = Some(SourceFileInfo::new("lab_run.lab"));
+    let hints = VendorHints::new("Example Lab")
+        .with_format("lab")
+        .with_conversion_path(vec!["lab".to_string(), "mzpeak".to_string()]);
+
+    let mut writer = MzPeakDatasetWriterV2::new(&path, Modality::LcMs, Some(hints))?;
+    writer.set_metadata(metadata);
+
+    // The converter rejects contract violations (unequal array lengths,
+    // gaps in spectrum IDs, invalid MS levels or polarities)
+    let mut contract = IngestSpectrumConverter::new();
+    for (spectrum_id, scan) in lab_scans().enumerate() {
+        let mut spectrum = contract.convert(to_ingest(spectrum_id as i64, scan))?;
+        spectrum.compute_statistics();
+        writer.write_spectrum(&SpectrumV2::try_from_spectrum_arrays(spectrum)?)?;
+    }
+    let stats = writer.close()?;
+    println!(
+        "Wrote {} spectra and {} peaks ({} bytes)",
+        stats.spectra_stats.spectra_written,
+        stats.peaks_stats.peaks_written,
+        stats.total_size_bytes
+    );
+
+    let report = validate_mzpeak_file(&path)?;
+    println!(
+        "Validation: {} passed, {} warnings, {} failed",
+        report.success_count(),
+        report.warning_count(),
+        report.failure_count()
+    );
+
+    let reader = MzPeakReader::open(&path)?;
+    if let Some(spectrum) = reader.get_spectrum_arrays(1)? {
+        println!(
+            "Spectrum 1: MS{}, precursor {:?}, charge {:?}",
+            spectrum.ms_level, spectrum.precursor_mz, spectrum.precursor_charge
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,156 @@
+//! Plugin: processing steps in a shared library
+//!
+//! A plugin written in Rust against the C ABI of `mzpeak::plugin`. It offers
+//! two steps:
+//!
+//! - `denoise`: drops peaks below 1% of the base peak
+//! - `top_n`: keeps the N most intense peaks (`MZPEAK_TOP_N`, default 150)
+//!
+//! Build and use with:
+//! ```text
+//! cargo build --release --example denoise_plugin
+//! mzpeak convert run.mzML run.mzpeak \
+//!     --plugin target/release/examples/libdenoise_plugin.so --step denoise
+//! ```
+//!
+//! Plugins in C or C++ include `include/mzpeak_plugin.h` instead.
+
+use std::os::raw::c_char;
+use std::ptr;
+
+use arrow::array::{Array, AsArray, BooleanArray, StructArray, UInt32Array};
+use arrow::compute::{filter_record_batch, take_record_batch};
+use arrow::datatypes::Float32Type;
+use arrow::error::ArrowError;
+use arrow::ffi::{from_ffi, to_ffi, FFI_ArrowArray, FFI_ArrowSchema};
+use arrow::record_batch::RecordBatch;
+use mzpeak::plugin::abi::{
+    MzPeakPlugin, MzPeakPluginStep, MzPeakSpectrumInfo, MZPEAK_PLUGIN_ABI_VERSION, MZPEAK_PLUGIN_OK,
+};
+
+/// Registration records hold raw pointers to static data
+struct Static<T>(T);
+
+// SAFETY: the records are immutable and only point at static data
+unsafe impl<T> Sync for Static<T> {}
+
+static STEPS: Static<[MzPeakPluginStep; 2]> = Static([
+    MzPeakPluginStep {
+        name: b"denoise\0".as_ptr().cast(),
+        description: b"drop peaks below 1% of the base peak\0".as_ptr().cast(),
+        process: Some(denoise),
+    },
+    MzPeakPluginStep {
+        name: b"top_n\0".as_ptr().cast(),
+        description: b"keep the MZPEAK_TOP_N most intense peaks\0"
+            .as_ptr()
+            .cast(),
+        process: Some(top_n),
+    },
+]);
+
+static PLUGIN: Static<MzPeakPlugin> = Static(MzPeakPlugin {
+    abi_version: MZPEAK_PLUGIN_ABI_VERSION,
+    name: b"denoise-example\0".as_ptr().cast(),
+    step_count: 2,
+    steps: &STEPS.0 as *const [MzPeakPluginStep; 2] as *const MzPeakPluginStep,
+});
+
+/// Entry point looked up by the host
+#[no_mangle]
+pub extern "C" fn mzpeak_plugin_register() -> *const MzPeakPlugin {
+    &PLUGIN.0
+}
+
+unsafe extern "C" fn denoise(
+    _info: *const MzPeakSpectrumInfo,
+    input: *mut FFI_ArrowArray,
+    input_schema: *const FFI_ArrowSchema,
+    output: *mut FFI_ArrowArray,
+    output_schema: *mut FFI_ArrowSchema,
+    error: *mut c_char,
+    error_len: usize,
+) -> i32 {
+    let step = |peaks: RecordBatch| {
+        let intensity = peaks.column(1).as_primitive::<Float32Type>();
+        let base_peak = intensity.values().iter().copied().fold(0.0f32, f32::max);
+        let keep: BooleanArray = intensity
+            .values()
+            .iter()
+            .map(|&value| Some(value >= base_peak * 0.01))
+            .collect();
+        filter_record_batch(&peaks, &keep)
+    };
+    run(
+        step,
+        input,
+        input_schema,
+        output,
+        output_schema,
+        error,
+        error_len,
+    )
+}
+
+unsafe extern "C" fn top_n(
+    _info: *const MzPeakSpectrumInfo,
+    input: *mut FFI_ArrowArray,
+    input_schema: *const FFI_ArrowSchema,
+    output: *mut FFI_ArrowArray,
+    output_schema: *mut FFI_ArrowSchema,
+    error: *mut c_char,
+    error_len: usize,
+) -> i32 {
+    let step = |peaks: RecordBatch| {
+        let n = std::env::var("MZPEAK_TOP_N")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(150);
+        let intensity = peaks.column(1).as_primitive::<Float32Type>().values();
+        let mut order: Vec<u32> = (0..intensity.len() as u32).collect();
+        order.sort_by(|&a, &b| intensity[b as usize].total_cmp(&intensity[a as usize]));
+        order.truncate(n);
+        // Back into m/z order
+        order.sort_unstable();
+        take_record_batch(&peaks, &UInt32Array::from(order))
+    };
+    run(
+        step,
+        input,
+        input_schema,
+        output,
+        output_schema,
+        error,
+        error_len,
+    )
+}
+
+/// Import the peaks, run `step` and export the result or the error message
+unsafe fn run(
+    step: impl FnOnce(RecordBatch) -> Result<RecordBatch, ArrowError>,
+    input: *mut FFI_ArrowArray,
+    input_schema: *const FFI_ArrowSchema,
+    output: *mut FFI_ArrowArray,
+    output_schema: *mut FFI_ArrowSchema,
+    error: *mut c_char,
+    error_len: usize,
+) -> i32 {
+    let result = from_ffi(FFI_ArrowArray::from_raw(input), &*input_schema)
+        .map(|data| RecordBatch::from(StructArray::from(data)))
+        .and_then(step)
+        .and_then(|peaks| to_ffi(&StructArray::from(peaks).into_data()));
+    match result {
+        Ok((array, schema)) => {
+            ptr::write(output, array);
+            ptr::write(output_schema, schema);
+            MZPEAK_PLUGIN_OK
+        }
+        Err(message) => {
+            let message = message.to_string();
+            let len = message.len().min(error_len.saturating_sub(1));
+            ptr::copy_nonoverlapping(message.as_ptr().cast(), error, len);
+            *error.add(len) = 0;
+            1
+        }
+    }
+}
@@ -0,0 +1,114 @@
+/*
+ * mzPeak processing step plugins, ABI version 1
+ *
+ * A plugin is a shared library exporting mzpeak_plugin_register(). Each step
+ * receives the peaks of one spectrum as an Arrow C Data Interface struct
+ * array and returns the processed peaks the same way. See the documentation
+ * of the Rust module `mzpeak::plugin` for the full contract.
+ *
+ *   cc -shared -fPIC -o libmyfilter.so myfilter.c
+ *   mzpeak convert run.mzML run.mzpeak --plugin ./libmyfilter.so --step denoise
+ */
+
+#ifndef MZPEAK_PLUGIN_H
+#define MZPEAK_PLUGIN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define MZPEAK_PLUGIN_ABI_VERSION 1
+#define MZPEAK_PLUGIN_OK 0
+
+/* Arrow C Data Interface, https://arrow.apache.org/docs/format/CDataInterface.html */
+#ifndef ARROW_C_DATA_INTERFACE
+#define ARROW_C_DATA_INTERFACE
+
+#define ARROW_FLAG_DICTIONARY_ORDERED 1
+#define ARROW_FLAG_NULLABLE 2
+#define ARROW_FLAG_MAP_KEYS_SORTED 4
+
+struct ArrowSchema {
+  const char* format;
+  const char* name;
+  const char* metadata;
+  int64_t flags;
+  int64_t n_children;
+  struct ArrowSchema** children;
+  struct ArrowSchema* dictionary;
+  void (*release)(struct ArrowSchema*);
+  void* private_data;
+};
+
+struct ArrowArray {
+  int64_t length;
+  int64_t null_count;
+  int64_t offset;
+  int64_t n_buffers;
+  int64_t n_children;
+  const void** buffers;
+  struct ArrowArray** children;
+  struct ArrowArray* dictionary;
+  void (*release)(struct ArrowArray*);
+  void* private_data;
+};
+
+#endif /* ARROW_C_DATA_INTERFACE */
+
+/* Spectrum metadata passed alongside the peak arrays */
+typedef struct MzPeakSpectrumInfo {
+  int64_t spectrum_id;    /* spectrum ID in the output file */
+  float retention_time;   /* seconds */
+  int16_t ms_level;       /* 1, 2, ... */
+  int8_t polarity;        /* 1 positive, -1 negative, 0 unknown */
+  double precursor_mz;    /* NaN without a precursor */
+} MzPeakSpectrumInfo;
+
+/*
+ * Process the peaks of one spectrum.
+ *
+ * input: struct array with columns "mz" (float64), "intensity" (float32) and,
+ *   for ion mobility data, a nullable "ion_mobility" (float64). The step may
+ *   move it out (copy the struct and set input->release to NULL); otherwise
+ *   the host releases it after the call. input_schema is borrowed.
+ * output, output_schema: on success, either export a struct array with the
+ *   same columns (ownership passes to the host) or leave them untouched to
+ *   keep the peaks unchanged. "mz" must stay sorted.
+ * error: buffer for a NUL-terminated message of at most error_len bytes.
+ *
+ * Returns MZPEAK_PLUGIN_OK, or any other value to fail the conversion. May be
+ * called from several threads at once.
+ */
+typedef int32_t (*MzPeakProcessFn)(const MzPeakSpectrumInfo* info,
+                                   struct ArrowArray* input,
+                                   const struct ArrowSchema* input_schema,
+                                   struct ArrowArray* output,
+                                   struct ArrowSchema* output_schema,
+                                   char* error,
+                                   size_t error_len);
+
+typedef struct MzPeakPluginStep {
+  const char* name;        /* UTF-8, used with --step */
+  const char* description; /* UTF-8, may be NULL */
+  MzPeakProcessFn process;
+} MzPeakPluginStep;
+
+/* Must stay valid while the library is loaded */
+typedef struct MzPeakPlugin {
+  uint32_t abi_version; /* MZPEAK_PLUGIN_ABI_VERSION */
+  const char* name;
+  size_t step_count;
+  const MzPeakPluginStep* steps;
+} MzPeakPlugin;
+
+/* The entry point every plugin exports */
+const MzPeakPlugin* mzpeak_plugin_register(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* MZPEAK_PLUGIN_H */
@@ -346,6 +346,11 @@ class FileSummary:
     def format_version(self) -> str:
         """Format version string."""
         ...
+    
+    @property
+    def spectra_by_polarity(self) -> Dict[int, int]:
+        """Number of spectra per polarity (1 positive, -1 negative, 0 unknown)."""
+        ...
 
 class FileMetadata:
     """Metadata from an mzPeak file."""
@@ -589,6 +594,26 @@ class ConversionStats:
     def compression_ratio(self) -> float:
         """Compression ratio achieved."""
         ...
+    
+    @property
+    def stage_timings(self) -> Dict[str, float]:
+        """Wall-clock seconds per stage (parse, decode, transform, write, package, total)."""
+        ...
+    
+    @property
+    def peaks_per_second(self) -> float:
+        """Average throughput in peaks per second."""
+        ...
+    
+    @property
+    def max_peaks_per_second(self) -> float:
+        """Highest throughput over any one-second window, in peaks per second."""
+        ...
+    
+    @property
+    def peak_memory_bytes(self) -> Optional[int]:
+        """Peak resident memory of the process in bytes (None if not measurable)."""
+        ...
 
 # Reader classes
 class SpectrumIterator:
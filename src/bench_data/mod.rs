@@ -0,0 +1,306 @@
+//! # Public Benchmark Datasets
+//!
+//! Development support for reproducible performance numbers. A manifest lists
+//! public mzML/RAW files (e.g. from PRIDE) with their SHA-256 checksums;
+//! [`fetch`] downloads them into a local cache and verifies each file before
+//! it is used. The criterion suite picks up cached files through
+//! [`BenchManifest::cached`], and `mzpeak bench fetch` populates the cache
+//! from the command line.
+//!
+//! The built-in manifest is `benches/datasets.toml`. Downloads go through the
+//! system `curl`, so no HTTP client is linked into the library.
+//!
+//! The cache lives in `$MZPEAK_BENCH_DIR`, or `~/.mzpeak/bench-data` when the
+//! variable is not set.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use mzpeak::bench_data::{self, BenchManifest};
+//!
+//! let manifest = BenchManifest::builtin()?;
+//! let dir = bench_data::cache_dir().expect("no home directory");
+//! for dataset in &manifest.datasets {
+//!     let outcome = bench_data::fetch(dataset, &dir)?;
+//!     println!("{}: {:?}", dataset.name, outcome);
+//! }
+//! # Ok::<(), mzpeak::bench_data::BenchDataError>(())
+//! ```
+
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+mod sha256;
+
+#[cfg(test)]
+mod tests;
+
+pub use sha256::{sha256_hex, Sha256};
+
+/// Environment variable overriding the dataset cache directory
+pub const BENCH_DIR_ENV: &str = "MZPEAK_BENCH_DIR";
+
+/// Manifest compiled into the library
+const BUILTIN_MANIFEST: &str = include_str!("../../benches/datasets.toml");
+
+/// Errors that can occur while fetching benchmark datasets
+#[derive(Debug, thiserror::Error)]
+pub enum BenchDataError {
+    /// I/O error
+    #[error("I/O error: {0}")]
+    IoError(#[from] io::Error),
+
+    /// Manifest could not be parsed
+    #[error("Invalid manifest: {0}")]
+    ManifestError(#[from] toml::de::Error),
+
+    /// Manifest entry is unusable
+    #[error("Invalid dataset '{name}': {reason}")]
+    InvalidDataset {
+        /// Dataset name
+        name: String,
+        /// What is wrong with the entry
+        reason: String,
+    },
+
+    /// Download failed
+    #[error("Failed to download '{name}': {reason}")]
+    DownloadFailed {
+        /// Dataset name
+        name: String,
+        /// Downloader error
+        reason: String,
+    },
+
+    /// Downloaded file does not match the manifest checksum
+    #[error("Checksum mismatch for '{name}': expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        /// Dataset name
+        name: String,
+        /// SHA-256 from the manifest
+        expected: String,
+        /// SHA-256 of the downloaded file
+        actual: String,
+    },
+}
+
+/// One public file used for benchmarking
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchDataset {
+    /// Short unique name, used for selection and as the cache sub-directory
+    pub name: String,
+    /// Download URL
+    pub url: String,
+    /// Expected SHA-256 of the file (lowercase hex)
+    pub sha256: String,
+    /// File name in the cache (defaults to the last URL path segment)
+    #[serde(default)]
+    pub file_name: Option<String>,
+    /// Size in bytes, shown before downloading
+    #[serde(default)]
+    pub size: Option<u64>,
+    /// Source repository accession (e.g. a PRIDE PXD identifier)
+    #[serde(default)]
+    pub accession: Option<String>,
+    /// What the file is representative of
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl BenchDataset {
+    /// File name in the cache
+    pub fn file_name(&self) -> Result<String, BenchDataError> {
+        let name = match &self.file_name {
+            Some(name) => name.clone(),
+            None => self
+                .url
+                .split(['?', '#'])
+                .next()
+                .and_then(|path| path.rsplit('/').next())
+                .unwrap_or_default()
+                .to_string(),
+        };
+        if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+            return Err(self.invalid(format!("cannot derive a file name from '{}'", self.url)));
+        }
+        Ok(name)
+    }
+
+    /// Path of the file inside `cache_dir`
+    pub fn cache_path(&self, cache_dir: &Path) -> Result<PathBuf, BenchDataError> {
+        Ok(cache_dir.join(&self.name).join(self.file_name()?))
+    }
+
+    fn validate(&self) -> Result<(), BenchDataError> {
+        if self.name.is_empty() || self.name.contains(['/', '\\']) || self.name.starts_with('.') {
+            return Err(self.invalid("name must be a plain directory name".to_string()));
+        }
+        if self.sha256.len() != 64 || !self.sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(self.invalid("sha256 must be 64 hex digits".to_string()));
+        }
+        self.file_name().map(|_| ())
+    }
+
+    fn invalid(&self, reason: String) -> BenchDataError {
+        BenchDataError::InvalidDataset {
+            name: self.name.clone(),
+            reason,
+        }
+    }
+}
+
+/// List of benchmark datasets
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchManifest {
+    /// Datasets in manifest order
+    #[serde(default, rename = "dataset")]
+    pub datasets: Vec<BenchDataset>,
+}
+
+impl BenchManifest {
+    /// Parse and validate a TOML manifest
+    pub fn parse(text: &str) -> Result<Self, BenchDataError> {
+        let manifest: Self = toml::from_str(text)?;
+        for (i, dataset) in manifest.datasets.iter().enumerate() {
+            dataset.validate()?;
+            if manifest.datasets[..i].iter().any(|d| d.name == dataset.name) {
+                return Err(dataset.invalid("duplicate name".to_string()));
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Load a manifest from disk
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, BenchDataError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Manifest shipped with mzPeak (`benches/datasets.toml`)
+    pub fn builtin() -> Result<Self, BenchDataError> {
+        Self::parse(BUILTIN_MANIFEST)
+    }
+
+    /// Look up a dataset by name
+    pub fn get(&self, name: &str) -> Option<&BenchDataset> {
+        self.datasets.iter().find(|d| d.name == name)
+    }
+
+    /// Datasets already present in `cache_dir`, with their paths
+    ///
+    /// Only checks that the files exist; [`fetch`] verifies checksums.
+    pub fn cached(&self, cache_dir: &Path) -> Vec<(&BenchDataset, PathBuf)> {
+        self.datasets
+            .iter()
+            .filter_map(|dataset| {
+                let path = dataset.cache_path(cache_dir).ok()?;
+                path.is_file().then_some((dataset, path))
+            })
+            .collect()
+    }
+}
+
+/// Result of fetching one dataset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchOutcome {
+    /// A verified copy was already cached
+    Cached(PathBuf),
+    /// The file was downloaded and verified
+    Downloaded(PathBuf),
+}
+
+impl FetchOutcome {
+    /// Path of the verified file
+    pub fn path(&self) -> &Path {
+        match self {
+            FetchOutcome::Cached(path) | FetchOutcome::Downloaded(path) => path,
+        }
+    }
+}
+
+/// Dataset cache directory
+///
+/// `$MZPEAK_BENCH_DIR` if set, otherwise `~/.mzpeak/bench-data`.
+pub fn cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(BENCH_DIR_ENV) {
+        return Some(PathBuf::from(dir));
+    }
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".mzpeak").join("bench-data"))
+}
+
+/// Download `dataset` into `cache_dir` with `curl` unless a verified copy exists
+pub fn fetch(dataset: &BenchDataset, cache_dir: &Path) -> Result<FetchOutcome, BenchDataError> {
+    fetch_with(dataset, cache_dir, curl_download)
+}
+
+/// [`fetch`] with a custom downloader writing `url` to the given path
+pub fn fetch_with<F>(
+    dataset: &BenchDataset,
+    cache_dir: &Path,
+    download: F,
+) -> Result<FetchOutcome, BenchDataError>
+where
+    F: FnOnce(&str, &Path) -> io::Result<()>,
+{
+    dataset.validate()?;
+    let path = dataset.cache_path(cache_dir)?;
+
+    if path.is_file() {
+        if sha256_hex(File::open(&path)?)? == dataset.sha256.to_ascii_lowercase() {
+            return Ok(FetchOutcome::Cached(path));
+        }
+        log::warn!("Cached {} is corrupt, downloading again", path.display());
+    }
+
+    let dir = path.parent().expect("cache path has a parent");
+    fs::create_dir_all(dir)?;
+    // Download next to the target so that the final rename is atomic
+    let partial = tempfile::Builder::new()
+        .prefix(".download-")
+        .tempfile_in(dir)?;
+    download(&dataset.url, partial.path()).map_err(|e| BenchDataError::DownloadFailed {
+        name: dataset.name.clone(),
+        reason: e.to_string(),
+    })?;
+
+    let actual = sha256_hex(File::open(partial.path())?)?;
+    if actual != dataset.sha256.to_ascii_lowercase() {
+        return Err(BenchDataError::ChecksumMismatch {
+            name: dataset.name.clone(),
+            expected: dataset.sha256.clone(),
+            actual,
+        });
+    }
+    partial.persist(&path).map_err(|e| e.error)?;
+    Ok(FetchOutcome::Downloaded(path))
+}
+
+/// Download `url` to `dest` with the system `curl`
+fn curl_download(url: &str, dest: &Path) -> io::Result<()> {
+    let output = Command::new("curl")
+        .args(["--fail", "--location", "--silent", "--show-error"])
+        .args(["--retry", "3"])
+        .arg("--output")
+        .arg(dest)
+        .arg(url)
+        .output()
+        .map_err(|e| {
+            if e.kind() == io::ErrorKind::NotFound {
+                io::Error::new(e.kind(), "curl not found; install curl to fetch datasets")
+            } else {
+                e
+            }
+        })?;
+    if !output.status.success() {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use super::*;
+use std::io::Write;
+use tempfile::TempDir;
+
+const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+fn dataset(sha256: &str) -> BenchDataset {
+    BenchDataset {
+        name: "tiny".to_string(),
+        url: "https://example.org/data/tiny.mzML?download=1".to_string(),
+        sha256: sha256.to_string(),
+        file_name: None,
+        size: None,
+        accession: None,
+        description: None,
+    }
+}
+
+#[test]
+fn test_sha256_known_vectors() {
+    assert_eq!(
+        sha256_hex(&b""[..]).unwrap(),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(sha256_hex(&b"abc"[..]).unwrap(), ABC_SHA256);
+    assert_eq!(
+        sha256_hex(&b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"[..]).unwrap(),
+        "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+    );
+
+    // Chunked updates across block boundaries match a single update
+    let data: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+    let mut hasher = Sha256::new();
+    for chunk in data.chunks(37) {
+        hasher.update(chunk);
+    }
+    assert_eq!(hasher.finalize_hex(), sha256_hex(&data[..]).unwrap());
+}
+
+#[test]
+fn test_manifest_parse() {
+    let manifest = BenchManifest::parse(&format!(
+        r#"
+[[dataset]]
+name = "tiny"
+url = "https://example.org/data/tiny.mzML"
+sha256 = "{ABC_SHA256}"
+accession = "PXD000000"
+"#
+    ))
+    .unwrap();
+    assert_eq!(manifest.datasets.len(), 1);
+    let tiny = manifest.get("tiny").unwrap();
+    assert_eq!(tiny.file_name().unwrap(), "tiny.mzML");
+    assert_eq!(tiny.accession.as_deref(), Some("PXD000000"));
+
+    // The shipped manifest must always parse
+    BenchManifest::builtin().unwrap();
+
+    let bad = BenchManifest::parse(
+        r#"
+[[dataset]]
+name = "../escape"
+url = "https://example.org/x.mzML"
+sha256 = "00"
+"#,
+    );
+    assert!(matches!(bad, Err(BenchDataError::InvalidDataset { .. })));
+}
+
+#[test]
+fn test_fetch_verifies_and_caches() {
+    let dir = TempDir::new().unwrap();
+    let write_abc = |_: &str, dest: &Path| File::create(dest)?.write_all(b"abc");
+
+    let tiny = dataset(ABC_SHA256);
+    let outcome = fetch_with(&tiny, dir.path(), write_abc).unwrap();
+    assert!(matches!(outcome, FetchOutcome::Downloaded(_)));
+    assert_eq!(outcome.path(), dir.path().join("tiny").join("tiny.mzML"));
+    assert_eq!(tiny.cache_path(dir.path()).unwrap(), outcome.path());
+
+    // A verified copy is not downloaded again
+    let cached = fetch_with(&tiny, dir.path(), |_: &str, _: &Path| {
+        panic!("cached dataset downloaded again")
+    })
+    .unwrap();
+    assert_eq!(cached, FetchOutcome::Cached(outcome.path().to_path_buf()));
+
+    let manifest = BenchManifest {
+        datasets: vec![tiny],
+    };
+    assert_eq!(manifest.cached(dir.path()).len(), 1);
+}
+
+#[test]
+fn test_fetch_rejects_checksum_mismatch() {
+    let dir = TempDir::new().unwrap();
+    let tiny = dataset(&"0".repeat(64));
+
+    let result = fetch_with(&tiny, dir.path(), |_: &str, dest: &Path| {
+        File::create(dest)?.write_all(b"abc")
+    });
+    match result {
+        Err(BenchDataError::ChecksumMismatch { actual, .. }) => assert_eq!(actual, ABC_SHA256),
+        other => panic!("expected checksum mismatch, got {:?}", other),
+    }
+    // Neither the target nor the partial download is left behind
+    assert_eq!(fs::read_dir(dir.path().join("tiny")).unwrap().count(), 0);
+}
@@ -0,0 +1,95 @@
+//! Build capabilities
+//!
+//! Which input formats and backends are compiled into this build. Pipeline
+//! managers can check a deployment before queuing vendor-specific jobs, either
+//! through [`capabilities()`] or with `mzpeak --capabilities`, which prints the
+//! same struct as JSON:
+//!
+//! ```text
+//! {"version":"0.1.0","format_version":"1.2.0","schema_version":"2.2","container":true,"writer":true,"mzml":true,...}
+//! ```
+//!
+//! These are compile-time facts. Whether the machine also provides the
+//! runtime requirements (e.g. .NET 8 for Thermo) is reported by
+//! [`crate::environment::check_environment`].
+
+use serde::Serialize;
+
+use crate::schema::{MZPEAK_FORMAT_VERSION, MZPEAK_V2_SCHEMA_VERSION};
+
+/// Features and backends supported by this build
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Capabilities {
+    /// Crate version
+    pub version: &'static str,
+    /// mzPeak v1 format version written
+    pub format_version: &'static str,
+    /// mzPeak v2 container schema version written
+    pub schema_version: &'static str,
+    /// Reading `.mzpeak` ZIP containers (`container` feature)
+    pub container: bool,
+    /// Writers and dataset containers (`writer` feature); reader-only
+    /// builds leave them out
+    pub writer: bool,
+    /// mzML input (`mzml` feature)
+    pub mzml: bool,
+    /// Thermo RAW input (`thermo` feature)
+    pub thermo: bool,
+    /// Bruker TDF input (`tdf` feature)
+    pub tdf: bool,
+    /// Parallel SIMD mzML decoding (`mzml-parallel` feature)
+    pub parallel_decode: bool,
+    /// Python bindings (`python` feature)
+    pub python: bool,
+    /// Async query API on a tokio runtime (`datafusion` feature)
+    #[serde(rename = "async")]
+    pub async_runtime: bool,
+    /// SQL and Substrait queries over containers (`datafusion` feature)
+    pub sql: bool,
+    /// Reading containers directly from object stores (S3, GCS, Azure);
+    /// no build supports this yet
+    pub object_store: bool,
+    /// io_uring read backend (`uring` feature on Linux)
+    pub io_uring: bool,
+    /// Processing step plugins loaded through the C ABI (`plugins` feature)
+    pub plugins: bool,
+}
+
+/// Features and backends supported by this build
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        format_version: MZPEAK_FORMAT_VERSION,
+        schema_version: MZPEAK_V2_SCHEMA_VERSION,
+        container: cfg!(feature = "container"),
+        writer: cfg!(feature = "writer"),
+        mzml: cfg!(feature = "mzml"),
+        thermo: cfg!(feature = "thermo"),
+        tdf: cfg!(feature = "tdf"),
+        parallel_decode: cfg!(feature = "mzml-parallel"),
+        python: cfg!(feature = "python"),
+        async_runtime: cfg!(feature = "datafusion"),
+        sql: cfg!(feature = "datafusion"),
+        object_store: false,
+        io_uring: cfg!(all(feature = "uring", target_os = "linux")),
+        plugins: cfg!(feature = "plugins"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_json() -> Result<(), Box<dyn std::error::Error>> {
+        let json = serde_json::to_value(capabilities())?;
+        assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(json["format_version"], MZPEAK_FORMAT_VERSION);
+        assert_eq!(json["container"], cfg!(feature = "container"));
+        assert_eq!(json["writer"], cfg!(feature = "writer"));
+        assert_eq!(json["mzml"], cfg!(feature = "mzml"));
+        assert_eq!(json["async"], cfg!(feature = "datafusion"));
+        assert_eq!(json["object_store"], false);
+        Ok(())
+    }
+}
@@ -19,8 +19,10 @@ use parquet::arrow::ArrowWriter;
 use parquet::basic::{Compression, ZstdLevel};
 use parquet::file::properties::{EnabledStatistics, WriterProperties};
 use parquet::format::KeyValue;
+use serde::{Deserialize, Serialize};
 
 use crate::metadata::MzPeakMetadata;
+use crate::schema::column_metadata::append_column_key_values;
 use crate::schema::{chromatogram_columns, create_chromatogram_schema_arc};
 
 /// Errors that can occur during chromatogram writing
@@ -207,7 +209,8 @@ impl<W: Write + Send> ChromatogramWriter<W> {
         let parquet_metadata = metadata.to_parquet_metadata()?;
         let props = config.to_writer_properties(&parquet_metadata);
 
-        let arrow_writer = ArrowWriter::try_new(writer, schema.clone(), Some(props))?;
+        let mut arrow_writer = ArrowWriter::try_new(writer, schema.clone(), Some(props))?;
+        append_column_key_values(&mut arrow_writer, &schema);
 
         Ok(Self {
             writer: arrow_writer,
@@ -322,7 +325,8 @@ impl<W: Write + Send> ChromatogramWriter<W> {
 }
 
 /// Statistics from a completed chromatogram write operation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ChromatogramWriterStats {
     /// Number of chromatograms written to the file
     pub chromatograms_written: usize,
@@ -0,0 +1,49 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+use mzpeak::dataset::{attach_file, detach_file, AttachOptions};
+use mzpeak::schema::AttachmentKind;
+
+/// Attach files to an mzPeak dataset
+pub fn attach(
+    file: PathBuf,
+    attachments: Vec<PathBuf>,
+    kind: Option<AttachmentKind>,
+    name: Option<String>,
+    description: Option<String>,
+) -> Result<()> {
+    if !file.exists() {
+        anyhow::bail!("File does not exist: {}", file.display());
+    }
+    if name.is_some() && attachments.len() > 1 {
+        anyhow::bail!("--name can only be used with a single attachment");
+    }
+
+    let options = AttachOptions {
+        name,
+        kind,
+        description,
+    };
+    for path in attachments {
+        let attachment = attach_file(&file, &path, &options)
+            .with_context(|| format!("Failed to attach {}", path.display()))?;
+        println!(
+            "Attached {} ({:?}, {}, {} bytes)",
+            attachment.name, attachment.kind, attachment.media_type, attachment.size_bytes
+        );
+    }
+    Ok(())
+}
+
+/// Remove attachments from an mzPeak dataset
+pub fn detach(file: PathBuf, names: Vec<String>) -> Result<()> {
+    if !file.exists() {
+        anyhow::bail!("File does not exist: {}", file.display());
+    }
+    for name in names {
+        let attachment = detach_file(&file, &name)
+            .with_context(|| format!("Failed to detach {}", name))?;
+        println!("Detached {}", attachment.name);
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+use mzpeak::bench_data::{self, BenchManifest, FetchOutcome};
+
+/// Download public benchmark datasets into the cache
+pub fn fetch(
+    manifest: Option<PathBuf>,
+    dir: Option<PathBuf>,
+    names: Vec<String>,
+    list: bool,
+) -> Result<()> {
+    let manifest = match &manifest {
+        Some(path) => BenchManifest::load(path)
+            .with_context(|| format!("Failed to load manifest {}", path.display()))?,
+        None => BenchManifest::builtin().context("Built-in dataset manifest is invalid")?,
+    };
+    let dir = dir
+        .or_else(bench_data::cache_dir)
+        .context("Cannot determine cache directory; pass --dir or set MZPEAK_BENCH_DIR")?;
+
+    if manifest.datasets.is_empty() {
+        println!("The manifest lists no datasets; add entries or pass --manifest");
+        return Ok(());
+    }
+
+    let selected = if names.is_empty() {
+        manifest.datasets.iter().collect::<Vec<_>>()
+    } else {
+        names
+            .iter()
+            .map(|name| {
+                manifest
+                    .get(name)
+                    .with_context(|| format!("Unknown dataset: {}", name))
+            })
+            .collect::<Result<Vec<_>>>()?
+    };
+
+    if list {
+        for dataset in selected {
+            let path = dataset.cache_path(&dir)?;
+            let status = if path.is_file() { "cached" } else { "missing" };
+            println!(
+                "{:<24} {:<8} {}",
+                dataset.name,
+                status,
+                dataset.description.as_deref().unwrap_or("")
+            );
+        }
+        return Ok(());
+    }
+
+    for dataset in selected {
+        if let Some(size) = dataset.size {
+            println!(
+                "Fetching {} ({:.1} MB)...",
+                dataset.name,
+                size as f64 / 1_000_000.0
+            );
+        } else {
+            println!("Fetching {}...", dataset.name);
+        }
+        match bench_data::fetch(dataset, &dir)? {
+            FetchOutcome::Cached(path) => println!("  Already cached: {}", path.display()),
+            FetchOutcome::Downloaded(path) => println!("  Verified: {}", path.display()),
+        }
+    }
+    Ok(())
+}
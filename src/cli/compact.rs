@@ -0,0 +1,35 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+use mzpeak::dataset::{compact_dataset, CompactOptions};
+
+/// Rewrite a dataset into optimally sized row groups and drop orphaned entries
+pub fn run(file: PathBuf, row_group_size: usize) -> Result<()> {
+    if !file.exists() {
+        anyhow::bail!("File does not exist: {}", file.display());
+    }
+
+    let options = CompactOptions { row_group_size };
+    let report = compact_dataset(&file, &options)
+        .with_context(|| format!("Failed to compact {}", file.display()))?;
+
+    for table in &report.tables {
+        println!(
+            "{}: {} rows, {} -> {} row groups, {} -> {} bytes",
+            table.path,
+            table.rows,
+            table.row_groups_before,
+            table.row_groups_after,
+            table.bytes_before,
+            table.bytes_after
+        );
+    }
+    for entry in &report.removed_entries {
+        println!("Removed orphaned entry {}", entry);
+    }
+    println!(
+        "Verified {} entries; size {} -> {} bytes",
+        report.verified_entries, report.size_before, report.size_after
+    );
+    Ok(())
+}
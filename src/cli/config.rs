@@ -10,11 +10,33 @@
 //! batch_size = 2000
 //! parallel = true
 //! legacy = false
+//! temp_dir = "D:/mzpeak-tmp"   # staging directory, overrides MZPEAK_TMPDIR
+//! stage_locally = true         # write to temp_dir, copy to the destination when done
+//! spectrum_checksums = true    # XXH3 content checksum per spectrum (v2 only)
+//! output_template = "{stem}_{date}_{profile}.mzpeak"
+//! on_collision = "version-suffix"   # or "error" (default), "overwrite"
+//! ```
+//!
+//! Named profiles bundle a complete set of writer and conversion settings and
+//! are selected with `--profile <name>`. Unset keys fall back to the built-in
+//! profile named by `base` (default: `balanced`):
+//!
+//! ```toml
+//! [profiles.mylab-archive]
+//! description = "Long-term storage on the lab NAS"
+//! base = "max-compression"
+//! compression_level = 19
+//! row_group_size = 500000
+//! max_peaks_per_file = 0   # never rotate
 //! ```
 
 use anyhow::{Context, Result};
-use serde::Deserialize;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::output::CollisionPolicy;
+use super::profile::Profile;
 
 /// Root configuration structure for mzpeak.toml files.
 #[derive(Debug, Default, Deserialize)]
@@ -22,6 +44,95 @@ pub struct Config {
     /// Conversion-specific settings.
     #[serde(default)]
     pub conversion: ConversionConfig,
+
+    /// User-defined profiles, keyed by name.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ProfileDefinition>,
+}
+
+/// Compression codec of a profile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionCodec {
+    /// ZSTD at `compression_level`
+    Zstd,
+    /// Snappy
+    Snappy,
+    /// No compression
+    None,
+}
+
+/// A `[profiles.<name>]` table. Every key is optional.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileDefinition {
+    /// Free-text description shown by `mzpeak profiles list`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Built-in profile supplying unset keys (default: balanced).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+
+    /// Compression codec.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compression: Option<CompressionCodec>,
+
+    /// ZSTD compression level (1-22).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compression_level: Option<i32>,
+
+    /// Number of peaks per Parquet row group.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row_group_size: Option<usize>,
+
+    /// Parquet data page size in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_page_size: Option<usize>,
+
+    /// Whether to write column statistics.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub write_statistics: Option<bool>,
+
+    /// Dictionary page size limit in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dictionary_page_size_limit: Option<usize>,
+
+    /// Peaks per file before rotating (0 = never rotate).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_peaks_per_file: Option<usize>,
+
+    /// BYTE_STREAM_SPLIT encoding for floating-point columns.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub use_byte_stream_split: Option<bool>,
+
+    /// Batches buffered by the async writer pipeline.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub async_buffer_capacity: Option<usize>,
+
+    /// Number of spectra to process per batch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<usize>,
+
+    /// Preserve the original 32/64-bit precision of binary arrays.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preserve_precision: Option<bool>,
+
+    /// Convert chromatograms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_chromatograms: Option<bool>,
+
+    /// Spectra between progress reports.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress_interval: Option<usize>,
+
+    /// Enable parallel decoding (requires parallel-decode feature).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel: Option<bool>,
+
+    /// Use legacy single-file .mzpeak.parquet format.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub legacy: Option<bool>,
 }
 
 /// Configuration for the convert command.
@@ -41,6 +152,21 @@ pub struct ConversionConfig {
 
     /// Use legacy single-file .mzpeak.parquet format.
     pub legacy: Option<bool>,
+
+    /// Directory for staging temp files (overrides `MZPEAK_TMPDIR`).
+    pub temp_dir: Option<PathBuf>,
+
+    /// Write output locally and copy it to the destination when done.
+    pub stage_locally: Option<bool>,
+
+    /// Store a content checksum per spectrum in v2 containers.
+    pub spectrum_checksums: Option<bool>,
+
+    /// Template for output names when no output path is given.
+    pub output_template: Option<String>,
+
+    /// What to do when the output already exists.
+    pub on_collision: Option<CollisionPolicy>,
 }
 
 impl Config {
@@ -54,7 +180,13 @@ impl Config {
 
     /// Parse configuration from a TOML string.
     pub fn from_str(content: &str) -> Result<Self> {
-        toml::from_str(content).context("Failed to parse TOML configuration")
+        let config: Self = toml::from_str(content).context("Failed to parse TOML configuration")?;
+        for name in config.profiles.keys() {
+            if name.parse::<Profile>().is_ok() {
+                anyhow::bail!("Profile '{}' shadows a built-in profile", name);
+            }
+        }
+        Ok(config)
     }
 }
 
@@ -63,7 +195,7 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_parse_config() {
+    fn test_parse_config() -> Result<(), Box<dyn std::error::Error>> {
         let toml = r#"
             [conversion]
             compression_level = 15
@@ -71,31 +203,73 @@ mod tests {
             batch_size = 2000
             parallel = true
             legacy = false
+            temp_dir = "D:/scratch"
+            spectrum_checksums = true
+            output_template = "{stem}_{date}.mzpeak"
+            on_collision = "version-suffix"
         "#;
 
-        let config = Config::from_str(toml).unwrap();
+        let config = Config::from_str(toml)?;
         assert_eq!(config.conversion.compression_level, Some(15));
         assert_eq!(config.conversion.row_group_size, Some(200_000));
         assert_eq!(config.conversion.batch_size, Some(2_000));
         assert_eq!(config.conversion.parallel, Some(true));
         assert_eq!(config.conversion.legacy, Some(false));
+        assert_eq!(config.conversion.temp_dir, Some(PathBuf::from("D:/scratch")));
+        assert_eq!(config.conversion.spectrum_checksums, Some(true));
+        assert_eq!(
+            config.conversion.output_template.as_deref(),
+            Some("{stem}_{date}.mzpeak")
+        );
+        assert_eq!(
+            config.conversion.on_collision,
+            Some(CollisionPolicy::VersionSuffix)
+        );
+        Ok(())
     }
 
     #[test]
-    fn test_partial_config() {
+    fn test_partial_config() -> Result<(), Box<dyn std::error::Error>> {
         let toml = r#"
             [conversion]
             compression_level = 10
         "#;
 
-        let config = Config::from_str(toml).unwrap();
+        let config = Config::from_str(toml)?;
         assert_eq!(config.conversion.compression_level, Some(10));
         assert_eq!(config.conversion.row_group_size, None);
+        Ok(())
     }
 
     #[test]
-    fn test_empty_config() {
-        let config = Config::from_str("").unwrap();
+    fn test_empty_config() -> Result<(), Box<dyn std::error::Error>> {
+        let config = Config::from_str("")?;
         assert_eq!(config.conversion.compression_level, None);
+        assert!(config.profiles.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_profile_definitions() -> Result<(), Box<dyn std::error::Error>> {
+        let toml = r#"
+            [profiles.mylab-archive]
+            description = "NAS archive"
+            base = "max-compression"
+            compression = "zstd"
+            compression_level = 19
+            max_peaks_per_file = 0
+        "#;
+
+        let config = Config::from_str(toml)?;
+        let profile = &config.profiles["mylab-archive"];
+        assert_eq!(profile.base.as_deref(), Some("max-compression"));
+        assert_eq!(profile.compression, Some(CompressionCodec::Zstd));
+        assert_eq!(profile.compression_level, Some(19));
+        assert_eq!(profile.max_peaks_per_file, Some(0));
+        assert_eq!(profile.row_group_size, None);
+
+        assert!(Config::from_str("[profiles.x]\nrow_group = 5").is_err());
+        assert!(Config::from_str("[profiles.fast]\nbatch_size = 5").is_err());
+        Ok(())
     }
 }
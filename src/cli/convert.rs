@@ -2,24 +2,52 @@ use anyhow::{Context, Result};
 use log::info;
 #[cfg(not(feature = "mzml-parallel"))]
 use log::warn;
-use std::path::PathBuf;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use super::config::Config;
-use super::profile::Profile;
-use mzpeak::mzml::{ConversionConfig, MzMLConverter, OutputFormat};
+use super::output::OutputNaming;
+use super::profile::ProfileSettings;
+use super::progress::ProgressDisplay;
+use mzpeak::ingest::SpectrumSteps;
+use mzpeak::mzml::{
+    ConversionConfig, ConversionStats, MzMLConverter, OutputFormat, ScanFilters, Stage,
+};
+use mzpeak::plugin::Plugin;
 use mzpeak::schema::manifest::Modality;
-use mzpeak::writer::{CompressionType, WriterConfig};
+use mzpeak::writer::{CompressionType, WriteProgress};
+
+/// Conversion report written by `--report`
+#[derive(Serialize)]
+struct ConversionReport<'a> {
+    mzpeak_version: &'a str,
+    input: &'a Path,
+    output: &'a Path,
+    profile: &'a str,
+    parallel: bool,
+    stats: &'a ConversionStats,
+}
 
 /// Convert mzML file to mzPeak format
 #[allow(clippy::too_many_arguments)]
 pub fn run(
     input: PathBuf,
     output: Option<PathBuf>,
-    profile: Profile,
+    naming: OutputNaming,
+    profile: String,
     config_path: Option<PathBuf>,
     legacy: bool,
+    stage_locally: bool,
+    spectrum_checksums: bool,
+    report: Option<&Path>,
     parallel: bool,
     modality: Option<Modality>,
+    repair_rt_order: bool,
+    rt_jitter_tolerance: Option<f32>,
+    scan_filters: ScanFilters,
+    plugins: &[PathBuf],
+    steps: &[String],
     cli_compression_level: Option<i32>,
     cli_row_group_size: Option<usize>,
     cli_batch_size: Option<usize>,
@@ -35,89 +63,112 @@ pub fn run(
     } else {
         None
     };
+    let profile = ProfileSettings::resolve(&profile, file_config.as_ref())?;
 
-    // Resolve settings with priority: CLI > config file > profile defaults
-    let compression_level = cli_compression_level
-        .or(file_config.as_ref().and_then(|c| c.conversion.compression_level))
-        .unwrap_or_else(|| profile.compression_level());
+    // Resolve settings with priority: CLI > config file > profile
+    let mut writer_config = profile.writer.clone();
+    if let Some(level) =
+        cli_compression_level.or(file_config.as_ref().and_then(|c| c.conversion.compression_level))
+    {
+        writer_config.compression = CompressionType::Zstd(level);
+    }
 
-    let row_group_size = cli_row_group_size
-        .or(file_config.as_ref().and_then(|c| c.conversion.row_group_size))
-        .unwrap_or_else(|| profile.row_group_size());
+    if let Some(row_group_size) =
+        cli_row_group_size.or(file_config.as_ref().and_then(|c| c.conversion.row_group_size))
+    {
+        writer_config.row_group_size = row_group_size;
+    }
+
+    if let Some(temp_dir) = file_config.as_ref().and_then(|c| c.conversion.temp_dir.clone()) {
+        writer_config.temp_dir = Some(temp_dir);
+    }
+    writer_config.stage_locally = stage_locally
+        || file_config
+            .as_ref()
+            .and_then(|c| c.conversion.stage_locally)
+            .unwrap_or(false);
+    writer_config.spectrum_checksums = spectrum_checksums
+        || file_config
+            .as_ref()
+            .and_then(|c| c.conversion.spectrum_checksums)
+            .unwrap_or(false);
+    let progress = Arc::new(WriteProgress::new());
+    writer_config.progress = Some(progress.clone());
 
     let batch_size = cli_batch_size
         .or(file_config.as_ref().and_then(|c| c.conversion.batch_size))
-        .unwrap_or_else(|| profile.batch_size());
+        .unwrap_or(profile.batch_size);
 
     let use_parallel = parallel
         || file_config
             .as_ref()
             .and_then(|c| c.conversion.parallel)
-            .unwrap_or(false);
+            .unwrap_or(profile.parallel);
 
     let use_legacy = legacy
         || file_config
             .as_ref()
             .and_then(|c| c.conversion.legacy)
-            .unwrap_or(false);
+            .unwrap_or(profile.legacy);
 
-    // Determine output path (default to .mzpeak container format or .mzpeak.parquet if legacy)
-    let output = output.unwrap_or_else(|| {
-        let stem = input.file_stem().unwrap_or_default().to_string_lossy();
-        let stem = stem
-            .trim_end_matches(".mzML")
-            .trim_end_matches(".mzml")
-            .trim_end_matches(".imzML")
-            .trim_end_matches(".imzml");
-        if use_legacy {
-            input.with_file_name(format!("{}.mzpeak.parquet", stem))
-        } else {
-            input.with_file_name(format!("{}.mzpeak", stem))
-        }
-    });
+    // Explicit path, template or default name, after the collision policy
+    let output = naming.resolve(output, &input, &profile.name, use_legacy, file_config.as_ref())?;
 
     info!("mzPeak Converter - mzML/imzML to mzPeak");
     info!("==================================");
     info!("Input:  {}", input.display());
     info!("Output: {}", output.display());
-    info!("Profile: {}", profile);
-    if config_path.is_some() {
-        info!("Config file: {}", config_path.as_ref().unwrap().display());
+    info!("Profile: {}", profile.name);
+    if let Some(config_path) = &config_path {
+        info!("Config file: {}", config_path.display());
     }
     if use_legacy {
         info!("Format: Legacy single-file .mzpeak.parquet (v1)");
     } else {
         info!("Format: Container .mzpeak (v2)");
     }
-    info!("Compression level: {}", compression_level);
-    info!("Row group size: {}", row_group_size);
+    info!("Compression: {:?}", writer_config.compression);
+    info!("Row group size: {}", writer_config.row_group_size);
     info!("Batch size: {}", batch_size);
+    if writer_config.stage_locally {
+        info!("Staging output locally");
+    }
+    if writer_config.spectrum_checksums {
+        info!("Spectrum checksums: enabled");
+    }
     if use_parallel {
         info!("Parallel decode: enabled");
     }
 
+    let spectrum_steps = load_steps(plugins, steps)?;
+
     // Create converter with configuration
-    let writer_config = WriterConfig {
-        compression: CompressionType::Zstd(compression_level),
-        row_group_size,
+    let mut config = ConversionConfig {
+        writer_config,
+        batch_size,
+        preserve_precision: profile.preserve_precision,
+        include_chromatograms: profile.include_chromatograms,
+        progress_interval: profile.progress_interval,
+        output_format: if use_legacy {
+            OutputFormat::V1Parquet
+        } else {
+            OutputFormat::V2Container
+        },
+        modality,
         ..Default::default()
     };
-
-    let mut config = ConversionConfig::default();
-    config.writer_config = writer_config;
-    config.batch_size = batch_size;
-
-    config.output_format = if use_legacy {
-        OutputFormat::V1Parquet
-    } else {
-        OutputFormat::V2Container
-    };
-    config.modality = modality;
+    config.rt_order.repair = repair_rt_order;
+    if let Some(tolerance) = rt_jitter_tolerance {
+        config.rt_order.jitter_tolerance = tolerance;
+    }
+    config.scan_filters = scan_filters;
+    config.spectrum_steps = spectrum_steps;
 
     let converter = MzMLConverter::with_config(config);
 
     // Run conversion
     info!("Starting conversion...");
+    let display = ProgressDisplay::start(progress);
     let stats = {
         #[cfg(feature = "mzml-parallel")]
         {
@@ -138,10 +189,21 @@ pub fn run(
         }
     };
 
+    drop(display);
+
     // Print results
     info!("Conversion complete!");
     info!("  Spectra converted: {}", stats.spectra_count);
     info!("  Total peaks: {}", stats.peak_count);
+    if stats.skipped_spectra.total() > 0 {
+        info!("  Spectra skipped by filters: {}", stats.skipped_spectra.total());
+    }
+    if stats.rt_jitter_spectra > 0 || stats.rt_order_breaks > 0 {
+        info!(
+            "  Retention time order: {} jittered spectra, {} breaks, {} re-sorted",
+            stats.rt_jitter_spectra, stats.rt_order_breaks, stats.rt_reordered_spectra
+        );
+    }
 
     let file_size = std::fs::metadata(&output).map(|m| m.len()).unwrap_or(0);
     info!(
@@ -154,6 +216,31 @@ pub fn run(
         info!("  Compression ratio: {:.1}x", stats.compression_ratio);
     }
 
+    info!(
+        "  Time: {:.2} s ({:.0} peaks/s, peak {:.0} peaks/s)",
+        stats.timings.total, stats.peaks_per_second, stats.max_peaks_per_second
+    );
+    for stage in Stage::ALL {
+        info!("    {:<10} {:>8.3} s", stage.name(), stats.timings.get(stage));
+    }
+    if let Some(bytes) = stats.peak_memory_bytes {
+        info!("  Peak memory: {:.1} MB", bytes as f64 / 1024.0 / 1024.0);
+    }
+
+    if let Some(report) = report {
+        let json = serde_json::to_string_pretty(&ConversionReport {
+            mzpeak_version: env!("CARGO_PKG_VERSION"),
+            input: &input,
+            output: &output,
+            profile: &profile.name,
+            parallel: use_parallel,
+            stats: &stats,
+        })?;
+        std::fs::write(report, json)
+            .with_context(|| format!("Failed to write report {}", report.display()))?;
+        info!("  Report: {}", report.display());
+    }
+
     info!("\nFile can be read with any Parquet-compatible tool:");
     info!(
         "  - Python: pyarrow.parquet.read_table('{}').to_pandas()",
@@ -167,3 +254,40 @@ pub fn run(
 
     Ok(())
 }
+
+/// Load the plugin libraries and pick the requested steps, searching the
+/// plugins in command-line order
+fn load_steps(plugins: &[PathBuf], steps: &[String]) -> Result<SpectrumSteps> {
+    let plugins = plugins
+        .iter()
+        .map(|path| {
+            let plugin = Plugin::load(path)?;
+            info!("Plugin: {} ({})", plugin.name(), path.display());
+            Ok(plugin)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut spectrum_steps = SpectrumSteps::default();
+    for name in steps {
+        let step = plugins
+            .iter()
+            .find_map(|plugin| plugin.step(name).ok())
+            .with_context(|| {
+                let available: Vec<&str> = plugins
+                    .iter()
+                    .flat_map(|plugin| plugin.steps().iter().map(|step| step.name()))
+                    .collect();
+                format!(
+                    "No loaded plugin provides step '{}' (available: {})",
+                    name,
+                    available.join(", ")
+                )
+            })?;
+        match step.description() {
+            Some(description) => info!("Step: {} - {}", step.name(), description),
+            None => info!("Step: {}", step.name()),
+        }
+        spectrum_steps.push(step);
+    }
+    Ok(spectrum_steps)
+}
@@ -1,19 +1,28 @@
 use anyhow::{Context, Result};
 use log::{info, warn};
+use std::fs::File;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use super::config::Config;
-use super::profile::Profile;
+use super::output::OutputNaming;
+use super::profile::ProfileSettings;
+use super::progress::ProgressDisplay;
 use mzpeak::controlled_vocabulary::ms_terms;
 use mzpeak::dataset::{DatasetWriterV2Config, MzPeakDatasetWriterV2};
 use mzpeak::ingest::IngestSpectrumConverter;
-use mzpeak::metadata::{InstrumentConfig, MzPeakMetadata, SourceFileInfo, VendorHints};
-use mzpeak::thermo::{ThermoConverter, ThermoStreamer};
+use mzpeak::metadata::{
+    InstrumentConfig, MzPeakMetadata, ProcessingHistory, ProcessingStep, SourceFileInfo,
+    VendorHints,
+};
+use mzpeak::scan_diagnostics_writer::ScanDiagnostics;
 use mzpeak::schema::manifest::Modality;
+use mzpeak::thermo::{ThermoConversionConfig, ThermoConverter, ThermoSpectrumMode, ThermoStreamer};
 use mzpeak::writer::{
     CompressionType, MzPeakWriter, PeaksWriterV2Config, SpectraWriterConfig, SpectrumArrays,
-    SpectrumV2, WriterConfig,
+    SpectrumV2, WriteProgress, WriterConfig,
 };
+use thermorawfilereader::RawSpectrum;
 
 #[derive(Default)]
 struct ThermoConversionStats {
@@ -33,12 +42,16 @@ struct ThermoConversionStats {
 pub fn run(
     input: PathBuf,
     output: Option<PathBuf>,
-    profile: Profile,
+    naming: OutputNaming,
+    profile: String,
     config_path: Option<PathBuf>,
     legacy: bool,
+    stage_locally: bool,
+    spectrum_checksums: bool,
     cli_compression_level: Option<i32>,
     cli_row_group_size: Option<usize>,
     cli_batch_size: Option<usize>,
+    spectrum_mode: ThermoSpectrumMode,
 ) -> Result<()> {
     if !input.exists() {
         anyhow::bail!("Input file does not exist: {}", input.display());
@@ -49,48 +62,73 @@ pub fn run(
     } else {
         None
     };
+    let profile = ProfileSettings::resolve(&profile, file_config.as_ref())?;
 
-    let compression_level = cli_compression_level
-        .or(file_config.as_ref().and_then(|c| c.conversion.compression_level))
-        .unwrap_or_else(|| profile.compression_level());
+    let mut writer_config = profile.writer.clone();
+    if let Some(level) =
+        cli_compression_level.or(file_config.as_ref().and_then(|c| c.conversion.compression_level))
+    {
+        writer_config.compression = CompressionType::Zstd(level);
+    }
 
-    let row_group_size = cli_row_group_size
-        .or(file_config.as_ref().and_then(|c| c.conversion.row_group_size))
-        .unwrap_or_else(|| profile.row_group_size());
+    if let Some(row_group_size) =
+        cli_row_group_size.or(file_config.as_ref().and_then(|c| c.conversion.row_group_size))
+    {
+        writer_config.row_group_size = row_group_size;
+    }
+
+    if let Some(temp_dir) = file_config
+        .as_ref()
+        .and_then(|c| c.conversion.temp_dir.clone())
+    {
+        writer_config.temp_dir = Some(temp_dir);
+    }
+    writer_config.stage_locally = stage_locally
+        || file_config
+            .as_ref()
+            .and_then(|c| c.conversion.stage_locally)
+            .unwrap_or(false);
+    writer_config.spectrum_checksums = spectrum_checksums
+        || file_config
+            .as_ref()
+            .and_then(|c| c.conversion.spectrum_checksums)
+            .unwrap_or(false);
+    let progress = Arc::new(WriteProgress::new());
+    writer_config.progress = Some(progress.clone());
 
     let batch_size = cli_batch_size
         .or(file_config.as_ref().and_then(|c| c.conversion.batch_size))
-        .unwrap_or_else(|| profile.batch_size())
+        .unwrap_or(profile.batch_size)
         .max(1);
 
     let use_legacy = legacy
         || file_config
             .as_ref()
             .and_then(|c| c.conversion.legacy)
-            .unwrap_or(false);
+            .unwrap_or(profile.legacy);
 
     let parallel_requested = file_config
         .as_ref()
         .and_then(|c| c.conversion.parallel)
-        .unwrap_or(false);
+        .unwrap_or(profile.parallel);
     if parallel_requested {
         warn!("Parallel decoding is not supported for Thermo RAW conversion.");
     }
 
-    let output = output.unwrap_or_else(|| {
-        let stem = input.file_stem().unwrap_or_default().to_string_lossy();
-        if use_legacy {
-            input.with_file_name(format!("{}.mzpeak.parquet", stem))
-        } else {
-            input.with_file_name(format!("{}.mzpeak", stem))
-        }
-    });
+    let output = naming.resolve(output, &input, &profile.name, use_legacy, file_config.as_ref())?;
 
     info!("mzPeak Converter - Thermo RAW to mzPeak");
     info!("=======================================");
+    // With both representations, profile data goes to a sibling container
+    let profile_output =
+        (spectrum_mode == ThermoSpectrumMode::Both).then(|| profile_output_path(&output));
+
     info!("Input:  {}", input.display());
     info!("Output: {}", output.display());
-    info!("Profile: {}", profile);
+    if let Some(path) = profile_output.as_ref() {
+        info!("Profile output: {}", path.display());
+    }
+    info!("Profile: {}", profile.name);
     if config_path.is_some() {
         info!("Config file: {}", config_path.as_ref().unwrap().display());
     }
@@ -99,15 +137,13 @@ pub fn run(
     } else {
         info!("Format: Container .mzpeak (v2)");
     }
-    info!("Compression level: {}", compression_level);
-    info!("Row group size: {}", row_group_size);
+    info!("Compression: {:?}", writer_config.compression);
+    info!("Row group size: {}", writer_config.row_group_size);
     info!("Batch size: {}", batch_size);
-
-    let writer_config = WriterConfig {
-        compression: CompressionType::Zstd(compression_level),
-        row_group_size,
-        ..Default::default()
-    };
+    if writer_config.stage_locally {
+        info!("Staging output locally");
+    }
+    info!("Spectrum mode: {}", spectrum_mode);
 
     let mut streamer = ThermoStreamer::new(&input, batch_size)
         .context("Failed to open Thermo RAW file")?;
@@ -122,176 +158,113 @@ pub fn run(
         info!("Instrument: {}", model);
     }
 
-    let metadata = build_metadata(&input, instrument_model.as_deref());
-
-    if use_legacy {
-        let mut writer = MzPeakWriter::new_file(&output, &metadata, writer_config)
-            .context("Failed to create legacy mzPeak writer")?;
-
-        let mut stats = ThermoConversionStats {
-            source_file_size: std::fs::metadata(&input).map(|m| m.len()).unwrap_or(0),
-            ..Default::default()
+    let mut sinks = Vec::new();
+    for (representation, path) in [
+        (ThermoSpectrumMode::Centroid, &output),
+        (
+            ThermoSpectrumMode::Profile,
+            profile_output.as_ref().unwrap_or(&output),
+        ),
+    ] {
+        let included = match representation {
+            ThermoSpectrumMode::Profile => spectrum_mode.includes_profile(),
+            _ => spectrum_mode.includes_centroid(),
         };
-
-        let mut batch: Vec<SpectrumArrays> = Vec::with_capacity(batch_size);
-        let mut ingest_converter = IngestSpectrumConverter::new();
-        let converter = ThermoConverter::new();
-        let mut spectrum_id: i64 = 0;
-
-        info!("Starting conversion...");
-
-        while let Some(raw_batch) = streamer
-            .next_batch()
-            .context("Failed to read Thermo RAW spectra batch")?
-        {
-            for raw_spectrum in raw_batch {
-                let scan_number = raw_spectrum.index() + 1;
-                let ingest = converter
-                    .convert_spectrum(raw_spectrum, spectrum_id)
-                    .with_context(|| format!("Failed to convert scan {}", scan_number))?;
-                spectrum_id += 1;
-
-                let spectrum = ingest_converter
-                    .convert(ingest)
-                    .with_context(|| format!("Ingest contract failed at scan {}", scan_number))?;
-
-                stats.spectra_count += 1;
-                stats.peak_count += spectrum.peak_count();
-                match spectrum.ms_level {
-                    1 => stats.ms1_spectra += 1,
-                    2 => stats.ms2_spectra += 1,
-                    _ => stats.msn_spectra += 1,
-                }
-
-                batch.push(spectrum);
-
-                if batch.len() >= batch_size {
-                    writer
-                        .write_spectra_owned(batch)
-                        .context("Failed to write spectra batch")?;
-                    batch = Vec::with_capacity(batch_size);
-                }
-
-                if stats.spectra_count % PROGRESS_INTERVAL == 0 && total_spectra > 0 {
-                    let processed = streamer.position();
-                    let pct = (processed as f64 / total_spectra as f64) * 100.0;
-                    info!(
-                        "Progress: {}/{} spectra ({:.1}%)",
-                        processed, total_spectra, pct
-                    );
-                }
-            }
-        }
-
-        if !batch.is_empty() {
-            writer
-                .write_spectra_owned(batch)
-                .context("Failed to write final spectra batch")?;
-        }
-
-        let writer_stats = writer.finish().context("Failed to finalize mzPeak file")?;
-        info!("Writer finalized: {}", writer_stats);
-
-        stats.output_file_size = std::fs::metadata(&output).map(|m| m.len()).unwrap_or(0);
-        if stats.output_file_size > 0 {
-            stats.compression_ratio =
-                stats.source_file_size as f64 / stats.output_file_size as f64;
+        if included {
+            let metadata = build_metadata(&input, instrument_model.as_deref(), representation);
+            sinks.push(ThermoSink::create(
+                path,
+                metadata,
+                &writer_config,
+                use_legacy,
+                batch_size,
+            )?);
         }
-
-        info!("Conversion complete!");
-        info!(
-            "  Spectra: {} (MS1: {}, MS2: {}, MSn: {})",
-            stats.spectra_count, stats.ms1_spectra, stats.ms2_spectra, stats.msn_spectra
-        );
-        info!("  Peaks: {}", stats.peak_count);
-        info!("  Input size: {} bytes", stats.source_file_size);
-        info!(
-            "  Output size: {} bytes ({:.2} MB)",
-            stats.output_file_size,
-            stats.output_file_size as f64 / 1024.0 / 1024.0
-        );
-        if stats.compression_ratio > 0.0 {
-            info!("  Compression ratio: {:.1}x", stats.compression_ratio);
-        }
-
-        info!("\nFile can be read with any Parquet-compatible tool:");
-        info!(
-            "  - Python: pyarrow.parquet.read_table('{}').to_pandas()",
-            output.display()
-        );
-        info!("  - R: arrow::read_parquet('{}')", output.display());
-        info!(
-            "  - DuckDB: SELECT * FROM read_parquet('{}')",
-            output.display()
-        );
-
-        return Ok(());
     }
 
-    let vendor_hints = metadata.vendor_hints.clone();
-    let dataset_config = DatasetWriterV2Config {
-        spectra_config: SpectraWriterConfig {
-            compression: writer_config.compression,
-            ..Default::default()
-        },
-        peaks_config: PeaksWriterV2Config {
-            compression: writer_config.compression,
-            row_group_size: writer_config.row_group_size,
-            ..Default::default()
-        },
-    };
-    let mut writer = MzPeakDatasetWriterV2::with_config(
-        &output,
-        Modality::LcMs,
-        vendor_hints,
-        dataset_config,
-    )
-    .context("Failed to create mzPeak v2 dataset writer")?;
-    writer.set_metadata(metadata);
-
     let mut stats = ThermoConversionStats {
         source_file_size: std::fs::metadata(&input).map(|m| m.len()).unwrap_or(0),
         ..Default::default()
     };
 
-    let mut ingest_converter = IngestSpectrumConverter::new();
-    let converter = ThermoConverter::new();
+    // The contract checks spectrum ID contiguity, so each output gets its own
+    let mut ingest_converters: Vec<IngestSpectrumConverter> =
+        sinks.iter().map(|_| IngestSpectrumConverter::new()).collect();
+    let converter = ThermoConverter::with_config(ThermoConversionConfig { spectrum_mode });
+    streamer.set_centroid_spectra(converter.centroid_spectra());
     let mut spectrum_id: i64 = 0;
 
     const PROGRESS_INTERVAL: usize = 1000;
 
+    if use_legacy {
+        info!("Trailer extras are only kept in .mzpeak containers; skipping them");
+    }
+
     info!("Starting conversion...");
+    if total_spectra > 0 {
+        progress.set_expected_spectra((total_spectra * sinks.len()) as u64);
+    }
+    let display = ProgressDisplay::start(progress);
+
+    loop {
+        // Each scan yields one raw spectrum per sink, in sink order
+        let raw_batch: Vec<Vec<RawSpectrum>> = if spectrum_mode == ThermoSpectrumMode::Both {
+            match streamer
+                .next_dual_batch()
+                .context("Failed to read Thermo RAW spectra batch")?
+            {
+                Some(pairs) => pairs
+                    .into_iter()
+                    .map(|(centroid, profile)| vec![centroid, profile])
+                    .collect(),
+                None => break,
+            }
+        } else {
+            match streamer
+                .next_batch()
+                .context("Failed to read Thermo RAW spectra batch")?
+            {
+                Some(batch) => batch.into_iter().map(|raw| vec![raw]).collect(),
+                None => break,
+            }
+        };
 
-    while let Some(raw_batch) = streamer
-        .next_batch()
-        .context("Failed to read Thermo RAW spectra batch")?
-    {
-        for raw_spectrum in raw_batch {
-            let scan_number = raw_spectrum.index() + 1;
-            let ingest = converter
-                .convert_spectrum(raw_spectrum, spectrum_id)
-                .with_context(|| format!("Failed to convert scan {}", scan_number))?;
-            spectrum_id += 1;
+        for raw_spectra in raw_batch {
+            let scan_number = raw_spectra[0].index() + 1;
+            let ms_level = raw_spectra[0].ms_level();
+            let diagnostics = converter
+                .convert_trailer(streamer.trailer_extras(raw_spectra[0].index()), spectrum_id);
+            for sink in sinks.iter_mut() {
+                sink.write_diagnostics(&diagnostics).with_context(|| {
+                    format!("Failed to write diagnostics of scan {}", scan_number)
+                })?;
+            }
 
-            let spectrum = ingest_converter
-                .convert(ingest)
-                .with_context(|| format!("Ingest contract failed at scan {}", scan_number))?;
+            for ((raw_spectrum, sink), ingest_converter) in raw_spectra
+                .into_iter()
+                .zip(sinks.iter_mut())
+                .zip(ingest_converters.iter_mut())
+            {
+                let ingest = converter
+                    .convert_spectrum(raw_spectrum, spectrum_id)
+                    .with_context(|| format!("Failed to convert scan {}", scan_number))?;
+                let spectrum = ingest_converter
+                    .convert(ingest)
+                    .with_context(|| format!("Ingest contract failed at scan {}", scan_number))?;
 
-            let spectrum_v2 = SpectrumV2::try_from_spectrum_arrays(spectrum)
-                .with_context(|| format!("v2 conversion failed at scan {}", scan_number))?;
+                stats.peak_count += spectrum.peak_count();
+                sink.write(spectrum)
+                    .with_context(|| format!("Failed to write scan {}", scan_number))?;
+            }
+            spectrum_id += 1;
 
             stats.spectra_count += 1;
-            stats.peak_count += spectrum_v2.peaks.len();
-            match spectrum_v2.metadata.ms_level {
+            match ms_level {
                 1 => stats.ms1_spectra += 1,
                 2 => stats.ms2_spectra += 1,
                 _ => stats.msn_spectra += 1,
             }
 
-            writer
-                .write_spectrum(&spectrum_v2)
-                .context("Failed to write spectrum")?;
-
             if stats.spectra_count % PROGRESS_INTERVAL == 0 && total_spectra > 0 {
                 let processed = streamer.position();
                 let pct = (processed as f64 / total_spectra as f64) * 100.0;
@@ -303,10 +276,14 @@ pub fn run(
         }
     }
 
-    let dataset_stats = writer.close().context("Failed to finalize dataset")?;
-    info!("Dataset finalized: {}", dataset_stats);
+    drop(display);
 
-    stats.output_file_size = std::fs::metadata(&output).map(|m| m.len()).unwrap_or(0);
+    let mut outputs = Vec::with_capacity(sinks.len());
+    for sink in sinks {
+        let path = sink.finish()?;
+        stats.output_file_size += std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        outputs.push(path);
+    }
     if stats.output_file_size > 0 {
         stats.compression_ratio = stats.source_file_size as f64 / stats.output_file_size as f64;
     }
@@ -317,7 +294,9 @@ pub fn run(
         stats.spectra_count, stats.ms1_spectra, stats.ms2_spectra, stats.msn_spectra
     );
     info!("  Peaks: {}", stats.peak_count);
-    info!("  Chromatograms: {}", stats.chromatograms_converted);
+    if !use_legacy {
+        info!("  Chromatograms: {}", stats.chromatograms_converted);
+    }
     info!("  Input size: {} bytes", stats.source_file_size);
     info!(
         "  Output size: {} bytes ({:.2} MB)",
@@ -329,20 +308,162 @@ pub fn run(
     }
 
     info!("\nFile can be read with any Parquet-compatible tool:");
-    info!(
-        "  - Python: pyarrow.parquet.read_table('{}').to_pandas()",
-        output.display()
-    );
-    info!("  - R: arrow::read_parquet('{}')", output.display());
-    info!(
-        "  - DuckDB: SELECT * FROM read_parquet('{}')",
-        output.display()
-    );
+    for path in &outputs {
+        info!(
+            "  - Python: pyarrow.parquet.read_table('{}').to_pandas()",
+            path.display()
+        );
+        info!("  - R: arrow::read_parquet('{}')", path.display());
+        info!(
+            "  - DuckDB: SELECT * FROM read_parquet('{}')",
+            path.display()
+        );
+    }
 
     Ok(())
 }
 
-fn build_metadata(input: &Path, instrument_model: Option<&str>) -> MzPeakMetadata {
+/// Destination of one converted spectrum stream (centroid or profile).
+enum ThermoSink {
+    Legacy {
+        path: PathBuf,
+        writer: MzPeakWriter<File>,
+        batch: Vec<SpectrumArrays>,
+        batch_size: usize,
+    },
+    Container {
+        path: PathBuf,
+        writer: MzPeakDatasetWriterV2,
+    },
+}
+
+impl ThermoSink {
+    fn create(
+        path: &Path,
+        metadata: MzPeakMetadata,
+        writer_config: &WriterConfig,
+        legacy: bool,
+        batch_size: usize,
+    ) -> Result<Self> {
+        if legacy {
+            let writer = MzPeakWriter::new_file(path, &metadata, writer_config.clone())
+                .context("Failed to create legacy mzPeak writer")?;
+            return Ok(Self::Legacy {
+                path: path.to_path_buf(),
+                writer,
+                batch: Vec::with_capacity(batch_size),
+                batch_size,
+            });
+        }
+
+        let vendor_hints = metadata.vendor_hints.clone();
+        let dataset_config = DatasetWriterV2Config {
+            spectra_config: SpectraWriterConfig {
+                compression: writer_config.compression,
+                spectrum_checksums: writer_config.spectrum_checksums,
+                ..Default::default()
+            },
+            peaks_config: PeaksWriterV2Config {
+                compression: writer_config.compression,
+                row_group_size: writer_config.row_group_size,
+                progress: writer_config.progress.clone(),
+                ..Default::default()
+            },
+            temp_dir: writer_config.temp_dir.clone(),
+            stage_locally: writer_config.stage_locally,
+            ..Default::default()
+        };
+        let mut writer =
+            MzPeakDatasetWriterV2::with_config(path, Modality::LcMs, vendor_hints, dataset_config)
+                .context("Failed to create mzPeak v2 dataset writer")?;
+        writer.set_metadata(metadata);
+        Ok(Self::Container {
+            path: path.to_path_buf(),
+            writer,
+        })
+    }
+
+    fn write(&mut self, spectrum: SpectrumArrays) -> Result<()> {
+        match self {
+            Self::Legacy {
+                writer,
+                batch,
+                batch_size,
+                ..
+            } => {
+                batch.push(spectrum);
+                if batch.len() >= *batch_size {
+                    writer
+                        .write_spectra_drain(batch)
+                        .context("Failed to write spectra batch")?;
+                }
+            }
+            Self::Container { writer, .. } => {
+                let spectrum_v2 = SpectrumV2::try_from_spectrum_arrays(spectrum)
+                    .context("v2 conversion failed")?;
+                writer
+                    .write_spectrum(&spectrum_v2)
+                    .context("Failed to write spectrum")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Store a scan's trailer extras; the legacy format has no table for them.
+    fn write_diagnostics(&mut self, diagnostics: &ScanDiagnostics) -> Result<()> {
+        if let Self::Container { writer, .. } = self {
+            writer
+                .write_scan_diagnostics(diagnostics)
+                .context("Failed to write scan diagnostics")?;
+        }
+        Ok(())
+    }
+
+    /// Flush and close the output, returning its path.
+    fn finish(self) -> Result<PathBuf> {
+        match self {
+            Self::Legacy {
+                path,
+                mut writer,
+                mut batch,
+                ..
+            } => {
+                if !batch.is_empty() {
+                    writer
+                        .write_spectra_drain(&mut batch)
+                        .context("Failed to write final spectra batch")?;
+                }
+                let writer_stats = writer.finish().context("Failed to finalize mzPeak file")?;
+                info!("Writer finalized ({}): {}", path.display(), writer_stats);
+                Ok(path)
+            }
+            Self::Container { path, writer } => {
+                let dataset_stats = writer.close().context("Failed to finalize dataset")?;
+                info!("Dataset finalized ({}): {}", path.display(), dataset_stats);
+                Ok(path)
+            }
+        }
+    }
+}
+
+/// Sibling path for the profile stream: `run.mzpeak` -> `run.profile.mzpeak`.
+fn profile_output_path(output: &Path) -> PathBuf {
+    let name = output.file_name().unwrap_or_default().to_string_lossy();
+    let profile_name = [".mzpeak.parquet", ".mzpeak"]
+        .iter()
+        .find_map(|ext| {
+            name.strip_suffix(ext)
+                .map(|stem| format!("{}.profile{}", stem, ext))
+        })
+        .unwrap_or_else(|| format!("{}.profile", name));
+    output.with_file_name(profile_name)
+}
+
+fn build_metadata(
+    input: &Path,
+    instrument_model: Option<&str>,
+    representation: ThermoSpectrumMode,
+) -> MzPeakMetadata {
     let mut metadata = MzPeakMetadata::new();
 
     let mut source = SourceFileInfo::new(
@@ -372,6 +493,28 @@ fn build_metadata(input: &Path, instrument_model: Option<&str>) -> MzPeakMetadat
         metadata.instrument = Some(instrument);
     }
 
+    // Record which peak representation this output holds
+    let mut step = ProcessingStep {
+        order: 1,
+        software: "mzpeak-rs".to_string(),
+        version: Some(env!("CARGO_PKG_VERSION").to_string()),
+        processing_type: "Conversion to mzPeak".to_string(),
+        timestamp: Some(chrono::Utc::now().to_rfc3339()),
+        parameters: Default::default(),
+        cv_params: Default::default(),
+        unknown_fields: Default::default(),
+    };
+    step.parameters.insert(
+        "spectrum_mode".to_string(),
+        representation.as_str().to_string(),
+    );
+    if representation == ThermoSpectrumMode::Centroid {
+        step.cv_params.add(ms_terms::peak_picking());
+    }
+    let mut history = ProcessingHistory::new();
+    history.add_step(step);
+    metadata.processing_history = Some(history);
+
     metadata
 }
 
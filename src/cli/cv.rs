@@ -0,0 +1,53 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+use mzpeak::controlled_vocabulary::{install_cv_files, instrument_db, psi_ms, store};
+
+/// Install CV files from disk into the override directory
+pub fn update(
+    obo: Option<PathBuf>,
+    instruments: Option<PathBuf>,
+    dir: Option<PathBuf>,
+) -> Result<()> {
+    if obo.is_none() && instruments.is_none() {
+        anyhow::bail!("Nothing to update: pass --obo and/or --instruments");
+    }
+    let dir = dir
+        .or_else(store::override_dir)
+        .context("Cannot determine CV directory; pass --dir or set MZPEAK_CV_DIR")?;
+
+    let report = install_cv_files(obo.as_deref(), instruments.as_deref(), &dir)
+        .context("Failed to install CV files")?;
+
+    println!("Installed CV files into {}", dir.display());
+    if let Some(terms) = report.ontology_terms {
+        println!(
+            "  {}: {} terms (version {})",
+            store::PSI_MS_FILE_NAME,
+            terms,
+            report.ontology_version.as_deref().unwrap_or("unknown")
+        );
+    }
+    if let Some(models) = report.instrument_models {
+        println!("  {}: {} models", store::INSTRUMENT_DB_FILE_NAME, models);
+    }
+    Ok(())
+}
+
+/// Show the CV data in use
+pub fn info() -> Result<()> {
+    let ontology = psi_ms();
+    println!("PSI-MS ontology:");
+    println!(
+        "  Version: {}",
+        ontology.data_version().unwrap_or("unknown")
+    );
+    println!("  Terms: {}", ontology.len());
+    println!("Instrument database:");
+    println!("  Models: {}", instrument_db().len());
+    match store::override_dir() {
+        Some(dir) => println!("Override directory: {}", dir.display()),
+        None => println!("Override directory: <none>"),
+    }
+    Ok(())
+}
@@ -133,6 +133,7 @@ fn build_demo_metadata() -> Result<MzPeakMetadata> {
             resolution: None,
             resolution_mz: None,
             cv_params: Default::default(),
+            unknown_fields: Default::default(),
         },
         MassAnalyzerConfig {
             analyzer_type: "orbitrap".to_string(),
@@ -140,6 +141,7 @@ fn build_demo_metadata() -> Result<MzPeakMetadata> {
             resolution: Some(120000.0),
             resolution_mz: Some(200.0),
             cv_params: Default::default(),
+            unknown_fields: Default::default(),
         },
     ];
 
@@ -162,6 +164,7 @@ fn build_demo_metadata() -> Result<MzPeakMetadata> {
         particle_size_um: Some(2.0),
         pore_size_angstrom: Some(100.0),
         stationary_phase: Some("C18".to_string()),
+        unknown_fields: Default::default(),
     });
 
     lc.mobile_phases = vec![
@@ -169,11 +172,13 @@ fn build_demo_metadata() -> Result<MzPeakMetadata> {
             channel: "A".to_string(),
             composition: "0.1% formic acid in water".to_string(),
             ph: Some(2.7),
+            unknown_fields: Default::default(),
         },
         MobilePhase {
             channel: "B".to_string(),
             composition: "0.1% formic acid in 80% acetonitrile".to_string(),
             ph: None,
+            unknown_fields: Default::default(),
         },
     ];
 
@@ -183,38 +188,46 @@ fn build_demo_metadata() -> Result<MzPeakMetadata> {
                 time_min: 0.0,
                 percent_b: 2.0,
                 flow_rate_ul_min: Some(300.0),
+                unknown_fields: Default::default(),
             },
             GradientStep {
                 time_min: 5.0,
                 percent_b: 2.0,
                 flow_rate_ul_min: Some(300.0),
+                unknown_fields: Default::default(),
             },
             GradientStep {
                 time_min: 90.0,
                 percent_b: 35.0,
                 flow_rate_ul_min: Some(300.0),
+                unknown_fields: Default::default(),
             },
             GradientStep {
                 time_min: 100.0,
                 percent_b: 95.0,
                 flow_rate_ul_min: Some(300.0),
+                unknown_fields: Default::default(),
             },
             GradientStep {
                 time_min: 105.0,
                 percent_b: 95.0,
                 flow_rate_ul_min: Some(300.0),
+                unknown_fields: Default::default(),
             },
             GradientStep {
                 time_min: 106.0,
                 percent_b: 2.0,
                 flow_rate_ul_min: Some(300.0),
+                unknown_fields: Default::default(),
             },
             GradientStep {
                 time_min: 120.0,
                 percent_b: 2.0,
                 flow_rate_ul_min: Some(300.0),
+                unknown_fields: Default::default(),
             },
         ],
+        unknown_fields: Default::default(),
     });
 
     metadata.lc_config = Some(lc);
@@ -267,6 +280,7 @@ fn build_demo_metadata() -> Result<MzPeakMetadata> {
                 base + gradient_effect + noise
             })
             .collect(),
+        unknown_fields: Default::default(),
     }];
 
     // Vendor-specific parameters
@@ -300,6 +314,7 @@ fn build_demo_metadata() -> Result<MzPeakMetadata> {
         timestamp: Some(chrono::Utc::now().to_rfc3339()),
         parameters: std::collections::HashMap::new(),
         cv_params: Default::default(),
+        unknown_fields: Default::default(),
     });
     metadata.processing_history = Some(history);
 
@@ -307,9 +322,13 @@ fn build_demo_metadata() -> Result<MzPeakMetadata> {
 }
 
 /// Generate a mock LC-MS run with realistic data patterns
+///
+/// Cycles alternate between positive and negative mode, as in a
+/// polarity-switching method, so the demo file covers both polarities.
 fn generate_mock_lcms_run() -> Vec<SpectrumArrays> {
     let mut spectra = Vec::new();
     let mut spectrum_id: i64 = 0;
+    let mut cycle = 0;
 
     let run_duration_sec = 120.0 * 60.0;
     let cycle_time = 3.0;
@@ -317,13 +336,15 @@ fn generate_mock_lcms_run() -> Vec<SpectrumArrays> {
     let mut current_time = 0.0;
 
     while current_time < run_duration_sec {
+        let polarity: i8 = if cycle % 2 == 0 { 1 } else { -1 };
+
         // MS1 survey scan
         let ms1_peaks = generate_ms1_peaks(current_time, run_duration_sec);
         let mut ms1_spectrum = SpectrumArrays::new_ms1(
             spectrum_id,
             spectrum_id + 1,
             current_time as f32,
-            1,
+            polarity,
             ms1_peaks,
         );
         ms1_spectrum.injection_time = Some(50.0);
@@ -342,7 +363,7 @@ fn generate_mock_lcms_run() -> Vec<SpectrumArrays> {
                 spectrum_id,
                 spectrum_id + 1,
                 current_time as f32,
-                1,
+                polarity,
                 precursor_mz,
                 ms2_peaks,
             );
@@ -358,6 +379,7 @@ fn generate_mock_lcms_run() -> Vec<SpectrumArrays> {
         }
 
         current_time += cycle_time;
+        cycle += 1;
     }
 
     spectra
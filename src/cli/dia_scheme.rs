@@ -0,0 +1,49 @@
+use anyhow::{Context, Result};
+use log::info;
+use std::path::PathBuf;
+
+use mzpeak::processing::DiaScheme;
+use mzpeak::reader::MzPeakReader;
+
+use super::DiaSchemeFormat;
+
+/// Detect the DIA isolation scheme and export it as JSON or SVG
+pub fn run(file: PathBuf, format: DiaSchemeFormat, output: Option<PathBuf>) -> Result<()> {
+    if !file.exists() {
+        anyhow::bail!("File does not exist: {}", file.display());
+    }
+
+    let reader = MzPeakReader::open(&file).context("Failed to open mzPeak file")?;
+    let scheme = DiaScheme::detect(&reader)
+        .context("Failed to detect DIA scheme")?
+        .with_context(|| {
+            format!(
+                "No DIA scheme found in {} (no repeating MS2 isolation windows)",
+                file.display()
+            )
+        })?;
+
+    info!(
+        "Detected {} windows ({} overlaps, {} gaps) over {} cycles",
+        scheme.windows.len(),
+        scheme.overlaps.len(),
+        scheme.gaps.len(),
+        scheme.cycle_time.num_cycles
+    );
+
+    let rendered = match format {
+        DiaSchemeFormat::Json => scheme.to_json()?,
+        DiaSchemeFormat::Svg => scheme.to_svg(),
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, rendered)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            info!("Wrote DIA scheme to {}", path.display());
+        }
+        None => print!("{}", rendered),
+    }
+
+    Ok(())
+}
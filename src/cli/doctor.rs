@@ -0,0 +1,31 @@
+use anyhow::Result;
+
+use mzpeak::environment::{check_environment, CheckStatus};
+
+/// Check optional runtime requirements and print their status
+pub fn run() -> Result<()> {
+    let checks = check_environment();
+    println!(
+        "mzpeak {} ({})",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS
+    );
+    for check in &checks {
+        println!("{}", check);
+    }
+
+    let missing = checks
+        .iter()
+        .filter(|check| check.status != CheckStatus::Ok)
+        .count();
+    if missing == 0 {
+        println!("\nAll optional capabilities are available.");
+    } else {
+        println!(
+            "\n{} of {} optional capabilities are degraded or unavailable.",
+            missing,
+            checks.len()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use anyhow::{Context, Result};
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+
+use mzpeak::mzml::export::{export_mzml, export_mzml_to_writer, MzMLWriterConfig};
+use mzpeak::mzml::BinaryCompression;
+use mzpeak::reader::{MzPeakReader, RtRange};
+
+/// Export an mzPeak dataset to (indexed) mzML
+pub fn run(
+    input: PathBuf,
+    output: PathBuf,
+    no_index: bool,
+    no_chromatograms: bool,
+    uncompressed: bool,
+    rt_range: Option<RtRange>,
+) -> Result<()> {
+    if !input.exists() {
+        anyhow::bail!("File does not exist: {}", input.display());
+    }
+
+    let reader = MzPeakReader::open(&input)
+        .with_context(|| format!("Failed to open {}", input.display()))?;
+    let config = MzMLWriterConfig {
+        indexed: !no_index,
+        compression: if uncompressed {
+            BinaryCompression::None
+        } else {
+            BinaryCompression::Zlib
+        },
+        include_chromatograms: !no_chromatograms,
+        run_id: input
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().trim_end_matches(".mzpeak").to_string())
+            .unwrap_or_else(|| "run".to_string()),
+        rt_range,
+        ..Default::default()
+    };
+
+    if output.as_os_str() == "-" {
+        let stdout = BufWriter::new(io::stdout().lock());
+        export_mzml_to_writer(&reader, stdout, &config).context("Failed to export mzML")?;
+        return Ok(());
+    }
+
+    let stats = export_mzml(&reader, &output, &config)
+        .with_context(|| format!("Failed to export {}", output.display()))?;
+    println!(
+        "Wrote {} spectra and {} chromatograms to {} ({} bytes{})",
+        stats.spectra_written,
+        stats.chromatograms_written,
+        output.display(),
+        stats.bytes_written,
+        if config.indexed { ", indexed" } else { "" }
+    );
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use anyhow::{Context, Result};
+use log::info;
+use std::fs::File;
+use std::io::BufWriter;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+use mzpeak::processing::{
+    build_inclusion_list, detect_features, write_inclusion_list_csv, FeatureDetectionConfig,
+    InclusionListConfig,
+};
+use mzpeak::reader::MzPeakReader;
+
+/// Detect MS1 features and export the most intense ones as an inclusion list
+pub fn run(
+    file: PathBuf,
+    output: Option<PathBuf>,
+    top: usize,
+    charges: RangeInclusive<i16>,
+    rt_window: Option<f32>,
+    collision_energy: Option<f32>,
+    ppm: f64,
+) -> Result<()> {
+    if !file.exists() {
+        anyhow::bail!("File does not exist: {}", file.display());
+    }
+
+    let reader = MzPeakReader::open(&file).context("Failed to open mzPeak file")?;
+    let detection = FeatureDetectionConfig {
+        mass_tolerance_ppm: ppm,
+        ..Default::default()
+    };
+    let features = detect_features(&reader, &detection).context("Feature detection failed")?;
+    info!("Detected {} MS1 features", features.len());
+
+    let config = InclusionListConfig {
+        top_n: top,
+        charges,
+        rt_window_seconds: rt_window,
+        collision_energy,
+    };
+    let entries = build_inclusion_list(&features, &config);
+    info!("Selected {} inclusion list entries", entries.len());
+
+    match output {
+        Some(path) => {
+            let file = File::create(&path)
+                .with_context(|| format!("Failed to create {}", path.display()))?;
+            write_inclusion_list_csv(&entries, BufWriter::new(file))?;
+            info!("Wrote inclusion list to {}", path.display());
+        }
+        None => write_inclusion_list_csv(&entries, std::io::stdout().lock())?,
+    }
+
+    Ok(())
+}
+
+/// Parse a charge range such as `2-4` or a single charge such as `3`
+pub fn parse_charge_range(value: &str) -> Result<RangeInclusive<i16>, String> {
+    let (min, max) = match value.split_once('-') {
+        Some((min, max)) => (min.trim(), max.trim()),
+        None => (value.trim(), value.trim()),
+    };
+    let min: i16 = min
+        .parse()
+        .map_err(|_| format!("invalid charge '{}'", min))?;
+    let max: i16 = max
+        .parse()
+        .map_err(|_| format!("invalid charge '{}'", max))?;
+    if min < 1 || max < min {
+        return Err(format!("invalid charge range '{}'", value));
+    }
+    Ok(min..=max)
+}
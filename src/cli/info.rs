@@ -5,7 +5,9 @@ use std::path::PathBuf;
 /// Display information about an mzPeak file
 pub fn run(file: PathBuf) -> Result<()> {
     use std::fs::File;
+    use std::io::Read;
     use mzpeak::reader::ZipEntryChunkReader;
+    use mzpeak::schema::Manifest;
     use zip::ZipArchive;
 
     if !file.exists() {
@@ -40,6 +42,38 @@ pub fn run(file: PathBuf) -> Result<()> {
             print_parquet_info("spectra/spectra.parquet", &spectra_reader);
         }
 
+        if is_v2 {
+            let mut manifest_json = String::new();
+            archive
+                .by_name("manifest.json")?
+                .read_to_string(&mut manifest_json)?;
+            let manifest: Manifest =
+                serde_json::from_str(&manifest_json).context("Failed to parse manifest.json")?;
+            if !manifest.attachments.is_empty() {
+                println!("Attachments:");
+                for attachment in &manifest.attachments {
+                    println!(
+                        "  {} ({:?}, {}, {} bytes)",
+                        attachment.name, attachment.kind, attachment.media_type, attachment.size_bytes
+                    );
+                }
+            }
+            if let Some(layout) = &manifest.layout_stats {
+                println!("Peak row groups:");
+                for (index, row_group) in layout.row_groups.iter().enumerate() {
+                    println!(
+                        "  {}: {} peaks, spectra {}-{}, {:.1} peaks/spectrum, max intensity {:.3e}",
+                        index,
+                        row_group.peak_count,
+                        row_group.first_spectrum_id,
+                        row_group.last_spectrum_id,
+                        row_group.mean_peaks_per_spectrum(),
+                        row_group.max_intensity().unwrap_or(0.0)
+                    );
+                }
+            }
+        }
+
         return Ok(());
     }
 
@@ -1,20 +1,45 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
-use mzpeak::schema::manifest::Modality;
+use mzpeak::processing::ReporterPlex;
+use mzpeak::reader::{parse_retention_time, RtRange};
+use mzpeak::schema::manifest::{AttachmentKind, Modality};
+#[cfg(feature = "mzml")]
+use mzpeak::mzml::ScanFilters;
+#[cfg(feature = "mzml")]
+use regex::Regex;
 
 #[cfg(feature = "mzml")]
 mod convert;
 #[cfg(feature = "thermo")]
 mod convert_thermo;
+mod attach;
+mod bench;
+mod compact;
+mod cv;
 mod demo;
+mod dia_scheme;
+mod doctor;
+#[cfg(feature = "mzml")]
+mod export_mzml;
+mod inclusion_list;
 mod info;
+mod precursors;
+mod profiles;
+#[cfg(any(feature = "mzml", feature = "thermo"))]
+mod progress;
+mod reporters;
+mod sample;
+mod serve;
 mod validate;
 
 mod config;
+mod output;
 mod profile;
 
-pub use profile::Profile;
+#[cfg(any(feature = "mzml", feature = "thermo"))]
+use output::{CollisionPolicy, OutputNaming};
 
 /// mzPeak - Modern Mass Spectrometry Data Format Converter
 #[derive(Parser)]
@@ -25,20 +50,12 @@ pub struct Cli {
     #[arg(short, long, action = clap::ArgAction::Count)]
     verbose: u8,
 
-    #[command(subcommand)]
-    command: Commands,
-}
+    /// Print the features and backends of this build as JSON and exit
+    #[arg(long)]
+    capabilities: bool,
 
-/// Conversion profile for optimizing speed vs compression.
-#[derive(Clone, Copy, Debug, Default, ValueEnum)]
-pub enum ProfileArg {
-    /// Prioritize speed over compression
-    Fast,
-    /// Balance between speed and compression
-    #[default]
-    Balanced,
-    /// Maximum compression, slower conversion
-    MaxCompression,
+    #[command(subcommand)]
+    command: Option<Commands>,
 }
 
 /// Data modality override for v2 containers.
@@ -65,17 +82,102 @@ impl From<ModalityArg> for Modality {
     }
 }
 
-impl From<ProfileArg> for Profile {
-    fn from(arg: ProfileArg) -> Self {
+/// Peak representation to convert from Thermo RAW files.
+#[cfg(feature = "thermo")]
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum SpectrumModeArg {
+    /// Centroid profile scans (peak lists only)
+    #[default]
+    Centroid,
+    /// Keep spectra as acquired
+    Profile,
+    /// Write both; profile data goes to a sibling `.profile.mzpeak` output
+    Both,
+}
+
+#[cfg(feature = "thermo")]
+impl From<SpectrumModeArg> for mzpeak::thermo::ThermoSpectrumMode {
+    fn from(arg: SpectrumModeArg) -> Self {
+        match arg {
+            SpectrumModeArg::Centroid => Self::Centroid,
+            SpectrumModeArg::Profile => Self::Profile,
+            SpectrumModeArg::Both => Self::Both,
+        }
+    }
+}
+
+/// Isobaric labeling reagent set for reporter ion extraction.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum PlexArg {
+    /// iTRAQ 4-plex
+    Itraq4,
+    /// iTRAQ 8-plex
+    Itraq8,
+    /// TMT 6-plex
+    Tmt6,
+    /// TMT 10-plex
+    Tmt10,
+    /// TMT 11-plex
+    Tmt11,
+    /// TMTpro 16-plex
+    Tmt16,
+    /// TMTpro 18-plex
+    Tmt18,
+}
+
+impl From<PlexArg> for ReporterPlex {
+    fn from(arg: PlexArg) -> Self {
         match arg {
-            ProfileArg::Fast => Profile::Fast,
-            ProfileArg::Balanced => Profile::Balanced,
-            ProfileArg::MaxCompression => Profile::MaxCompression,
+            PlexArg::Itraq4 => ReporterPlex::Itraq4,
+            PlexArg::Itraq8 => ReporterPlex::Itraq8,
+            PlexArg::Tmt6 => ReporterPlex::Tmt6,
+            PlexArg::Tmt10 => ReporterPlex::Tmt10,
+            PlexArg::Tmt11 => ReporterPlex::Tmt11,
+            PlexArg::Tmt16 => ReporterPlex::Tmt16,
+            PlexArg::Tmt18 => ReporterPlex::Tmt18,
         }
     }
 }
 
+/// Category of an attached file.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum AttachmentKindArg {
+    /// Acquisition or LC method export
+    Method,
+    /// Screenshot or other image
+    Image,
+    /// Report, notes or other document
+    Document,
+    /// Processing or acquisition script
+    Script,
+    /// Anything else
+    Other,
+}
+
+impl From<AttachmentKindArg> for AttachmentKind {
+    fn from(arg: AttachmentKindArg) -> Self {
+        match arg {
+            AttachmentKindArg::Method => AttachmentKind::Method,
+            AttachmentKindArg::Image => AttachmentKind::Image,
+            AttachmentKindArg::Document => AttachmentKind::Document,
+            AttachmentKindArg::Script => AttachmentKind::Script,
+            AttachmentKindArg::Other => AttachmentKind::Other,
+        }
+    }
+}
+
+/// Export format for the detected DIA scheme.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum DiaSchemeFormat {
+    /// Window layout, overlaps and cycle time statistics as JSON
+    #[default]
+    Json,
+    /// Window layout drawing as SVG
+    Svg,
+}
+
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)] // Parsed once per invocation; boxing buys nothing
 enum Commands {
     /// Convert mzML file to mzPeak format
     #[cfg(feature = "mzml")]
@@ -88,9 +190,17 @@ enum Commands {
         #[arg(value_name = "OUTPUT")]
         output: Option<PathBuf>,
 
-        /// Conversion profile (fast, balanced, max-compression)
-        #[arg(short = 'p', long, default_value = "balanced", value_enum)]
-        profile: ProfileArg,
+        /// Name the output from a template: {stem}, {date}, {time}, {profile}
+        #[arg(long, value_name = "TEMPLATE", conflicts_with = "output")]
+        output_template: Option<String>,
+
+        /// What to do when the output already exists [default: error]
+        #[arg(long, value_enum, value_name = "POLICY")]
+        on_collision: Option<CollisionPolicy>,
+
+        /// Conversion profile: fast, balanced, max-compression, or a profile from --config
+        #[arg(short = 'p', long, value_name = "NAME", default_value = "balanced")]
+        profile: String,
 
         /// Load settings from a TOML config file
         #[arg(long, value_name = "FILE")]
@@ -100,6 +210,18 @@ enum Commands {
         #[arg(long)]
         legacy: bool,
 
+        /// Write locally and copy to OUTPUT when done (for network shares)
+        #[arg(long)]
+        stage_locally: bool,
+
+        /// Store an XXH3 content checksum per spectrum (v2 containers only)
+        #[arg(long)]
+        spectrum_checksums: bool,
+
+        /// Write conversion statistics and stage timings to this JSON file
+        #[arg(long, value_name = "FILE")]
+        report: Option<PathBuf>,
+
         /// Enable parallel decoding (requires the mzml-parallel feature)
         #[arg(long, default_value_t = false)]
         parallel: bool,
@@ -108,6 +230,38 @@ enum Commands {
         #[arg(long, value_enum)]
         modality: Option<ModalityArg>,
 
+        /// Re-sort spectra with slightly out-of-order retention times (v2 containers only)
+        #[arg(long)]
+        repair_rt_order: bool,
+
+        /// Largest retention time decrease treated as benign jitter
+        #[arg(long, value_name = "SECONDS")]
+        rt_jitter_tolerance: Option<f32>,
+
+        /// Skip spectra of this MS level (repeatable)
+        #[arg(long, value_name = "LEVEL")]
+        skip_ms_level: Vec<i16>,
+
+        /// Skip spectra whose native ID matches this regex (e.g. lock-mass scans)
+        #[arg(long, value_name = "REGEX")]
+        skip_native_id: Option<Regex>,
+
+        /// Skip spectra whose vendor filter string matches this regex
+        #[arg(long, value_name = "REGEX")]
+        skip_filter: Option<Regex>,
+
+        /// Only convert spectra in this retention time range (e.g. 2-58min)
+        #[arg(long, value_name = "RANGE")]
+        rt: Option<RtRange>,
+
+        /// Load processing steps from this plugin library (repeatable)
+        #[arg(long, value_name = "LIBRARY", requires = "step")]
+        plugin: Vec<PathBuf>,
+
+        /// Run this plugin step on every spectrum (repeatable, in order)
+        #[arg(long, value_name = "NAME", requires = "plugin")]
+        step: Vec<String>,
+
         // === Advanced tuning flags (hidden from --help) ===
         /// Compression level for ZSTD (1-22, default: profile-dependent)
         #[arg(short = 'c', long, hide = true)]
@@ -133,9 +287,17 @@ enum Commands {
         #[arg(value_name = "OUTPUT")]
         output: Option<PathBuf>,
 
-        /// Conversion profile (fast, balanced, max-compression)
-        #[arg(short = 'p', long, default_value = "balanced", value_enum)]
-        profile: ProfileArg,
+        /// Name the output from a template: {stem}, {date}, {time}, {profile}
+        #[arg(long, value_name = "TEMPLATE", conflicts_with = "output")]
+        output_template: Option<String>,
+
+        /// What to do when the output already exists [default: error]
+        #[arg(long, value_enum, value_name = "POLICY")]
+        on_collision: Option<CollisionPolicy>,
+
+        /// Conversion profile: fast, balanced, max-compression, or a profile from --config
+        #[arg(short = 'p', long, value_name = "NAME", default_value = "balanced")]
+        profile: String,
 
         /// Load settings from a TOML config file
         #[arg(long, value_name = "FILE")]
@@ -145,6 +307,18 @@ enum Commands {
         #[arg(long)]
         legacy: bool,
 
+        /// Write locally and copy to OUTPUT when done (for network shares)
+        #[arg(long)]
+        stage_locally: bool,
+
+        /// Store an XXH3 content checksum per spectrum (v2 containers only)
+        #[arg(long)]
+        spectrum_checksums: bool,
+
+        /// Peak representation: centroid, profile, or both
+        #[arg(long, default_value = "centroid", value_enum)]
+        spectrum_mode: SpectrumModeArg,
+
         // === Advanced tuning flags (hidden from --help) ===
         /// Compression level for ZSTD (1-22, default: profile-dependent)
         #[arg(short = 'c', long, hide = true)]
@@ -159,6 +333,34 @@ enum Commands {
         batch_size: Option<usize>,
     },
 
+    /// Export an mzPeak file to mzML (indexedmzML by default)
+    #[cfg(feature = "mzml")]
+    ExportMzml {
+        /// Input mzPeak file path
+        #[arg(value_name = "INPUT")]
+        input: PathBuf,
+
+        /// Output mzML file path ("-" for stdout)
+        #[arg(value_name = "OUTPUT")]
+        output: PathBuf,
+
+        /// Write plain mzML without the indexedmzML wrapper
+        #[arg(long)]
+        no_index: bool,
+
+        /// Skip the chromatogram list
+        #[arg(long)]
+        no_chromatograms: bool,
+
+        /// Write binary arrays without zlib compression
+        #[arg(long)]
+        uncompressed: bool,
+
+        /// Only export spectra in this retention time range (e.g. 10-20min, 600-1200s)
+        #[arg(long, value_name = "RANGE")]
+        rt: Option<RtRange>,
+    },
+
     /// Generate demo LC-MS data for testing
     Demo {
         /// Output mzPeak file path
@@ -183,6 +385,263 @@ enum Commands {
         #[arg(value_name = "FILE")]
         file: PathBuf,
     },
+
+    /// Export the DIA isolation scheme (window layout, overlaps, cycle time)
+    DiaScheme {
+        /// Input mzPeak file path
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Export format (json, svg)
+        #[arg(short = 'f', long, default_value = "json", value_enum)]
+        format: DiaSchemeFormat,
+
+        /// Output file path (defaults to stdout)
+        #[arg(short = 'o', long, value_name = "OUTPUT")]
+        output: Option<PathBuf>,
+    },
+
+    /// Generate a targeted inclusion list from detected MS1 features
+    InclusionList {
+        /// Input mzPeak file path
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Output CSV path (defaults to stdout)
+        #[arg(short = 'o', long, value_name = "OUTPUT")]
+        output: Option<PathBuf>,
+
+        /// Number of features to include (most intense first)
+        #[arg(long, default_value = "2000")]
+        top: usize,
+
+        /// Charge states to include (e.g. 2-4)
+        #[arg(long, default_value = "2-4", value_parser = inclusion_list::parse_charge_range)]
+        charge: std::ops::RangeInclusive<i16>,
+
+        /// Fixed RT window width around the apex, e.g. 30s or 0.5min; bare
+        /// numbers are seconds (defaults to feature bounds)
+        #[arg(long, value_name = "TIME", value_parser = parse_retention_time)]
+        rt_window: Option<f32>,
+
+        /// Collision energy written to every entry
+        #[arg(long)]
+        collision_energy: Option<f32>,
+
+        /// Mass tolerance for feature detection (ppm)
+        #[arg(long, default_value = "10")]
+        ppm: f64,
+    },
+
+    /// Extract TMT/iTRAQ reporter ion intensities from MS2/MS3 spectra
+    Reporters {
+        /// Input mzPeak file path
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Reagent set
+        #[arg(long, value_enum)]
+        plex: PlexArg,
+
+        /// Output Parquet path
+        #[arg(short = 'o', long, default_value = "reporters.parquet")]
+        output: PathBuf,
+
+        /// Match tolerance around each reporter m/z (ppm)
+        #[arg(long, default_value = "20")]
+        ppm: f64,
+
+        /// Isotope impurity matrix CSV (one row of fractions per channel)
+        #[arg(long, value_name = "CSV")]
+        impurities: Option<PathBuf>,
+    },
+
+    /// Correct monoisotopic precursor m/z and charge from the MS1 isotope envelope
+    CorrectPrecursors {
+        /// Input mzPeak file path
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Output Parquet path
+        #[arg(short = 'o', long, default_value = "precursors.parquet")]
+        output: PathBuf,
+
+        /// Match tolerance for isotope peaks (ppm)
+        #[arg(long, default_value = "10")]
+        ppm: f64,
+
+        /// Highest charge state considered
+        #[arg(long, default_value = "6")]
+        max_charge: i16,
+
+        /// Most isotope steps the monoisotopic peak may lie below the selected one
+        #[arg(long, default_value = "2")]
+        max_isotope_error: usize,
+    },
+
+    /// Write a seeded random subset of the spectra to a new mzPeak dataset
+    Sample {
+        /// Input mzPeak file path
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Output dataset path (`.mzpeak` for a container, otherwise a directory)
+        #[arg(short = 'o', long, default_value = "sample.mzpeak")]
+        output: PathBuf,
+
+        /// Number of spectra to sample
+        #[arg(short = 'n', long, default_value = "100")]
+        count: usize,
+
+        /// Random seed; the same seed always selects the same spectra
+        #[arg(long, default_value = "0")]
+        seed: u64,
+
+        /// Only sample spectra of this MS level
+        #[arg(long)]
+        ms_level: Option<i16>,
+    },
+
+    /// Store files (method PDFs, screenshots, scripts) inside a v2 dataset
+    Attach {
+        /// mzPeak dataset to modify
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Files to attach
+        #[arg(value_name = "ATTACHMENT", required = true)]
+        attachments: Vec<PathBuf>,
+
+        /// Attachment kind (guessed from the extension by default)
+        #[arg(long, value_enum)]
+        kind: Option<AttachmentKindArg>,
+
+        /// Name inside the dataset (single attachment only)
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Free-text description
+        #[arg(long)]
+        description: Option<String>,
+    },
+
+    /// Remove attached files from a v2 dataset
+    Detach {
+        /// mzPeak dataset to modify
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Names of the attachments to remove
+        #[arg(value_name = "NAME", required = true)]
+        names: Vec<String>,
+    },
+
+    /// Rewrite a dataset into optimally sized row groups and drop orphaned entries
+    Compact {
+        /// mzPeak dataset to compact in place
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Target rows per row group
+        #[arg(long, default_value = "100000")]
+        row_group_size: usize,
+    },
+
+    /// Serve a JSON/Arrow API and a browser viewer for quick QC
+    Serve {
+        /// mzPeak file to serve
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Listen address (`:8080` is loopback only; use `0.0.0.0:8080` for all interfaces)
+        #[arg(long, value_name = "ADDRESS", default_value = "127.0.0.1:8080")]
+        http: String,
+    },
+
+    /// Inspect built-in and user-defined conversion profiles
+    Profiles {
+        #[command(subcommand)]
+        command: ProfilesCommands,
+    },
+
+    /// Manage the PSI-MS CV and instrument database
+    Cv {
+        #[command(subcommand)]
+        command: CvCommands,
+    },
+
+    /// Check the environment for optional runtime requirements
+    Doctor,
+
+    /// Benchmark support tools
+    Bench {
+        #[command(subcommand)]
+        command: BenchCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProfilesCommands {
+    /// List available profiles
+    List {
+        /// Config file with [profiles.<name>] tables
+        #[arg(long, value_name = "FILE")]
+        config: Option<PathBuf>,
+    },
+
+    /// Show the effective settings of a profile
+    Show {
+        /// Profile name
+        #[arg(value_name = "NAME")]
+        name: String,
+
+        /// Config file with [profiles.<name>] tables
+        #[arg(long, value_name = "FILE")]
+        config: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum CvCommands {
+    /// Install CV files from disk, replacing the embedded copies
+    Update {
+        /// PSI-MS ontology file (psi-ms.obo)
+        #[arg(long, value_name = "FILE")]
+        obo: Option<PathBuf>,
+
+        /// Instrument database file (tab-separated)
+        #[arg(long, value_name = "FILE")]
+        instruments: Option<PathBuf>,
+
+        /// Target directory (defaults to $MZPEAK_CV_DIR or ~/.mzpeak/cv)
+        #[arg(long, value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
+
+    /// Show the CV versions in use
+    Info,
+}
+
+#[derive(Subcommand)]
+enum BenchCommands {
+    /// Download public benchmark datasets and verify their checksums
+    Fetch {
+        /// Dataset names to fetch (defaults to all)
+        #[arg(value_name = "NAME")]
+        names: Vec<String>,
+
+        /// Dataset manifest (defaults to the built-in benches/datasets.toml)
+        #[arg(long, value_name = "FILE")]
+        manifest: Option<PathBuf>,
+
+        /// Cache directory (defaults to $MZPEAK_BENCH_DIR or ~/.mzpeak/bench-data)
+        #[arg(long, value_name = "DIR")]
+        dir: Option<PathBuf>,
+
+        /// List datasets and their cache status without downloading
+        #[arg(long)]
+        list: bool,
+    },
 }
 
 impl Cli {
@@ -201,27 +660,67 @@ pub fn init_logging(verbosity: u8) {
 }
 
 pub fn dispatch(cli: Cli) -> Result<()> {
-    match cli.command {
+    if cli.capabilities {
+        println!("{}", serde_json::to_string_pretty(&mzpeak::capabilities())?);
+        return Ok(());
+    }
+    let Some(command) = cli.command else {
+        Cli::command()
+            .error(ErrorKind::MissingSubcommand, "a subcommand is required")
+            .exit()
+    };
+    match command {
         #[cfg(feature = "mzml")]
         Commands::Convert {
             input,
             output,
+            output_template,
+            on_collision,
             profile,
             config,
             legacy,
+            stage_locally,
+            spectrum_checksums,
+            report,
             parallel,
             modality,
+            repair_rt_order,
+            rt_jitter_tolerance,
+            skip_ms_level,
+            skip_native_id,
+            skip_filter,
+            rt,
+            plugin,
+            step,
             compression_level,
             row_group_size,
             batch_size,
         } => convert::run(
             input,
             output,
-            Profile::from(profile),
+            OutputNaming {
+                template: output_template,
+                on_collision,
+            },
+            profile,
             config,
             legacy,
+            stage_locally,
+            spectrum_checksums,
+            report.as_deref(),
             parallel,
             modality.map(Modality::from),
+            repair_rt_order,
+            rt_jitter_tolerance,
+            ScanFilters {
+                skip_ms_levels: skip_ms_level.into_iter().collect(),
+                skip_native_id,
+                skip_filter_string: skip_filter,
+                min_retention_time: rt.map(|rt| f64::from(rt.start_seconds())),
+                max_retention_time: rt.map(|rt| f64::from(rt.end_seconds())),
+            },
+            &plugin,
+            &step,
             compression_level,
             row_group_size,
             batch_size,
@@ -230,27 +729,117 @@ pub fn dispatch(cli: Cli) -> Result<()> {
         Commands::ConvertThermo {
             input,
             output,
+            output_template,
+            on_collision,
             profile,
             config,
             legacy,
+            stage_locally,
+            spectrum_checksums,
+            spectrum_mode,
             compression_level,
             row_group_size,
             batch_size,
         } => convert_thermo::run(
             input,
             output,
-            Profile::from(profile),
+            OutputNaming {
+                template: output_template,
+                on_collision,
+            },
+            profile,
             config,
             legacy,
+            stage_locally,
+            spectrum_checksums,
             compression_level,
             row_group_size,
             batch_size,
+            spectrum_mode.into(),
         ),
+        #[cfg(feature = "mzml")]
+        Commands::ExportMzml {
+            input,
+            output,
+            no_index,
+            no_chromatograms,
+            uncompressed,
+            rt,
+        } => export_mzml::run(input, output, no_index, no_chromatograms, uncompressed, rt),
         Commands::Demo {
             output,
             compression_level,
         } => demo::run(output, compression_level),
         Commands::Info { file } => info::run(file),
         Commands::Validate { file } => validate::run(file),
+        Commands::DiaScheme {
+            file,
+            format,
+            output,
+        } => dia_scheme::run(file, format, output),
+        Commands::InclusionList {
+            file,
+            output,
+            top,
+            charge,
+            rt_window,
+            collision_energy,
+            ppm,
+        } => inclusion_list::run(file, output, top, charge, rt_window, collision_energy, ppm),
+        Commands::Reporters {
+            file,
+            plex,
+            output,
+            ppm,
+            impurities,
+        } => reporters::run(file, output, ReporterPlex::from(plex), ppm, impurities),
+        Commands::CorrectPrecursors {
+            file,
+            output,
+            ppm,
+            max_charge,
+            max_isotope_error,
+        } => precursors::run(file, output, ppm, max_charge, max_isotope_error),
+        Commands::Sample {
+            file,
+            output,
+            count,
+            seed,
+            ms_level,
+        } => sample::run(file, output, count, seed, ms_level),
+        Commands::Attach {
+            file,
+            attachments,
+            kind,
+            name,
+            description,
+        } => attach::attach(file, attachments, kind.map(AttachmentKind::from), name, description),
+        Commands::Detach { file, names } => attach::detach(file, names),
+        Commands::Compact {
+            file,
+            row_group_size,
+        } => compact::run(file, row_group_size),
+        Commands::Serve { file, http } => serve::run(file, http),
+        Commands::Profiles { command } => match command {
+            ProfilesCommands::List { config } => profiles::list(config),
+            ProfilesCommands::Show { name, config } => profiles::show(name, config),
+        },
+        Commands::Cv { command } => match command {
+            CvCommands::Update {
+                obo,
+                instruments,
+                dir,
+            } => cv::update(obo, instruments, dir),
+            CvCommands::Info => cv::info(),
+        },
+        Commands::Doctor => doctor::run(),
+        Commands::Bench { command } => match command {
+            BenchCommands::Fetch {
+                names,
+                manifest,
+                dir,
+                list,
+            } => bench::fetch(manifest, dir, names, list),
+        },
     }
 }
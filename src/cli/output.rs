@@ -0,0 +1,247 @@
+//! Output naming for the convert commands.
+//!
+//! Without an explicit OUTPUT, the output is named after the input, or after
+//! `--output-template` when given:
+//!
+//! ```text
+//! mzpeak convert run.mzML --output-template "{stem}_{date}_{profile}.mzpeak"
+//! # -> run_2024-01-15_balanced.mzpeak next to run.mzML
+//! ```
+//!
+//! `--on-collision` decides what happens when the output already exists:
+//! fail before converting (the default), replace it, or pick the next free
+//! `_v2`, `_v3`, ... name. Both can also be set in the `[conversion]` table of
+//! a config file.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use log::warn;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use super::config::Config;
+
+/// Placeholders understood by output templates.
+const PLACEHOLDERS: [&str; 4] = ["stem", "date", "time", "profile"];
+
+/// Extensions kept together when adding a version suffix.
+const EXTENSIONS: [&str; 3] = [".mzpeak.parquet", ".mzpeak", ".parquet"];
+
+/// What to do when the output path already exists.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CollisionPolicy {
+    /// Fail before converting
+    #[default]
+    Error,
+    /// Delete the existing output and convert again
+    Overwrite,
+    /// Append `_v2`, `_v3`, ... until the name is free
+    VersionSuffix,
+}
+
+/// Output naming options of a convert command.
+#[derive(Clone, Debug, Default)]
+pub struct OutputNaming {
+    /// Template for the output file name (`--output-template`)
+    pub template: Option<String>,
+    /// Collision policy (`--on-collision`)
+    pub on_collision: Option<CollisionPolicy>,
+}
+
+impl OutputNaming {
+    /// Final output path of a conversion.
+    ///
+    /// Priority: explicit OUTPUT > CLI template > config template > default
+    /// name. The collision policy (CLI > config > error) is applied last;
+    /// with `Overwrite` the existing output is deleted here.
+    pub fn resolve(
+        &self,
+        output: Option<PathBuf>,
+        input: &Path,
+        profile: &str,
+        legacy: bool,
+        config: Option<&Config>,
+    ) -> Result<PathBuf> {
+        let template = self
+            .template
+            .clone()
+            .or_else(|| config.and_then(|c| c.conversion.output_template.clone()));
+        let output = match (output, template) {
+            (Some(output), _) => output,
+            (None, Some(template)) => {
+                let name = render_template(&template, input_stem(input), profile, Local::now())?;
+                input.with_file_name(name)
+            }
+            (None, None) => default_output(input, legacy),
+        };
+        let policy = self
+            .on_collision
+            .or_else(|| config.and_then(|c| c.conversion.on_collision))
+            .unwrap_or_default();
+        apply_collision_policy(output, policy)
+    }
+}
+
+/// Input file name without its extension (including `.mzML`/`.imzML`).
+fn input_stem(input: &Path) -> &str {
+    let stem = input
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    stem.trim_end_matches(".mzML")
+        .trim_end_matches(".mzml")
+        .trim_end_matches(".imzML")
+        .trim_end_matches(".imzml")
+}
+
+/// Output next to the input: `.mzpeak` container, or `.mzpeak.parquet` if legacy.
+pub fn default_output(input: &Path, legacy: bool) -> PathBuf {
+    let stem = input_stem(input);
+    if legacy {
+        input.with_file_name(format!("{}.mzpeak.parquet", stem))
+    } else {
+        input.with_file_name(format!("{}.mzpeak", stem))
+    }
+}
+
+/// Expand the `{placeholder}`s of an output template.
+fn render_template(
+    template: &str,
+    stem: &str,
+    profile: &str,
+    now: DateTime<Local>,
+) -> Result<String> {
+    let mut name = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        name.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .with_context(|| format!("Unclosed '{{' in output template '{}'", template))?;
+        match &rest[start + 1..start + end] {
+            "stem" => name.push_str(stem),
+            "date" => name.push_str(&now.format("%Y-%m-%d").to_string()),
+            "time" => name.push_str(&now.format("%H%M%S").to_string()),
+            "profile" => name.push_str(profile),
+            other => anyhow::bail!(
+                "Unknown placeholder '{{{}}}' in output template (available: {})",
+                other,
+                PLACEHOLDERS.map(|p| format!("{{{}}}", p)).join(", ")
+            ),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    name.push_str(rest);
+    if name.is_empty() {
+        anyhow::bail!("Output template '{}' produces an empty name", template);
+    }
+    Ok(name)
+}
+
+fn apply_collision_policy(output: PathBuf, policy: CollisionPolicy) -> Result<PathBuf> {
+    if !output.exists() {
+        return Ok(output);
+    }
+    match policy {
+        CollisionPolicy::Error => anyhow::bail!(
+            "Output already exists: {} (use --on-collision overwrite or version-suffix)",
+            output.display()
+        ),
+        CollisionPolicy::Overwrite => {
+            warn!("Overwriting existing output {}", output.display());
+            if output.is_dir() {
+                std::fs::remove_dir_all(&output)
+            } else {
+                std::fs::remove_file(&output)
+            }
+            .with_context(|| format!("Failed to remove {}", output.display()))?;
+            Ok(output)
+        }
+        CollisionPolicy::VersionSuffix => {
+            let name = output
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default()
+                .to_string();
+            let (base, extension) = EXTENSIONS
+                .iter()
+                .find_map(|ext| name.strip_suffix(ext).map(|base| (base, *ext)))
+                .unwrap_or((name.as_str(), ""));
+            (2..)
+                .map(|version| output.with_file_name(format!("{}_v{}{}", base, version, extension)))
+                .find(|candidate| !candidate.exists())
+                .context("No free versioned output name")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_render_template() -> Result<()> {
+        let now = Local
+            .with_ymd_and_hms(2024, 1, 15, 10, 30, 5)
+            .single()
+            .context("valid time")?;
+        assert_eq!(
+            render_template("{stem}_{date}_{profile}.mzpeak", "run01", "fast", now)?,
+            "run01_2024-01-15_fast.mzpeak"
+        );
+        assert_eq!(
+            render_template("{stem}-{time}.mzpeak", "run01", "fast", now)?,
+            "run01-103005.mzpeak"
+        );
+        assert!(render_template("{stem}_{user}.mzpeak", "run01", "fast", now).is_err());
+        assert!(render_template("{stem.mzpeak", "run01", "fast", now).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_collision_policies() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let output = dir.path().join("run.mzpeak");
+        std::fs::write(&output, b"old")?;
+
+        assert!(apply_collision_policy(output.clone(), CollisionPolicy::Error).is_err());
+
+        let versioned = apply_collision_policy(output.clone(), CollisionPolicy::VersionSuffix)?;
+        assert_eq!(versioned, dir.path().join("run_v2.mzpeak"));
+        std::fs::write(&versioned, b"old")?;
+        assert_eq!(
+            apply_collision_policy(output.clone(), CollisionPolicy::VersionSuffix)?,
+            dir.path().join("run_v3.mzpeak")
+        );
+
+        let legacy = dir.path().join("run.mzpeak.parquet");
+        std::fs::write(&legacy, b"old")?;
+        assert_eq!(
+            apply_collision_policy(legacy, CollisionPolicy::VersionSuffix)?,
+            dir.path().join("run_v2.mzpeak.parquet")
+        );
+
+        assert_eq!(
+            apply_collision_policy(output.clone(), CollisionPolicy::Overwrite)?,
+            output
+        );
+        assert!(!output.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_output_strips_mzml_extension() {
+        let input = Path::new("/data/run01.mzML");
+        assert_eq!(
+            default_output(input, false),
+            Path::new("/data/run01.mzpeak")
+        );
+        assert_eq!(
+            default_output(Path::new("/data/run01.mzML.gz"), true),
+            Path::new("/data/run01.mzpeak.parquet")
+        );
+    }
+}
@@ -0,0 +1,42 @@
+use anyhow::{Context, Result};
+use log::info;
+use std::path::PathBuf;
+
+use mzpeak::processing::{PrecursorCorrectionConfig, PrecursorCorrectionTable};
+use mzpeak::reader::MzPeakReader;
+
+/// Recalculate precursor m/z and charge into a precursors.parquet overlay table
+pub fn run(
+    file: PathBuf,
+    output: PathBuf,
+    ppm: f64,
+    max_charge: i16,
+    max_isotope_error: usize,
+) -> Result<()> {
+    if !file.exists() {
+        anyhow::bail!("File does not exist: {}", file.display());
+    }
+
+    let config = PrecursorCorrectionConfig {
+        tolerance_ppm: ppm,
+        max_charge,
+        max_isotope_error,
+        ..Default::default()
+    };
+
+    let reader = MzPeakReader::open(&file).context("Failed to open mzPeak file")?;
+    let table = PrecursorCorrectionTable::compute(&reader, &config)
+        .context("Failed to recalculate precursors")?;
+    info!(
+        "Corrected {} of {} precursors",
+        table.num_corrected(),
+        table.rows.len()
+    );
+
+    table
+        .write_parquet(&output)
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+    info!("Wrote precursor table to {}", output.display());
+
+    Ok(())
+}
@@ -1,11 +1,18 @@
 //! Conversion profiles for common use cases.
 //!
 //! Profiles provide sensible defaults for compression and performance tuning,
-//! hiding low-level Parquet settings from end users.
+//! hiding low-level Parquet settings from end users. Besides the built-in
+//! profiles, config files may define named profiles (see [`super::config`]);
+//! [`ProfileSettings::resolve`] turns either kind into a complete snapshot.
 
+use anyhow::Result;
 use std::fmt;
 use std::str::FromStr;
 
+use mzpeak::writer::{CompressionType, WriterConfig};
+
+use super::config::{CompressionCodec, Config, ProfileDefinition};
+
 /// Conversion profiles for common use cases.
 ///
 /// Each profile pre-configures compression level, row group size, and batch size
@@ -96,6 +103,178 @@ impl FromStr for Profile {
     }
 }
 
+/// Complete writer and conversion settings of a built-in or user profile.
+#[derive(Clone, Debug)]
+pub struct ProfileSettings {
+    /// Profile name as passed to `--profile`.
+    pub name: String,
+    /// Description from the config file (`None` for built-ins).
+    pub description: Option<String>,
+    /// Whether this is a built-in profile.
+    pub builtin: bool,
+    /// Parquet writer settings.
+    pub writer: WriterConfig,
+    /// Number of spectra to process per batch.
+    pub batch_size: usize,
+    /// Preserve the original 32/64-bit precision of binary arrays.
+    pub preserve_precision: bool,
+    /// Convert chromatograms.
+    pub include_chromatograms: bool,
+    /// Spectra between progress reports.
+    pub progress_interval: usize,
+    /// Enable parallel decoding.
+    pub parallel: bool,
+    /// Use legacy single-file .mzpeak.parquet format.
+    pub legacy: bool,
+}
+
+impl ProfileSettings {
+    /// Settings of a built-in profile.
+    pub fn builtin(profile: Profile) -> Self {
+        Self {
+            name: profile.to_string(),
+            description: None,
+            builtin: true,
+            writer: WriterConfig {
+                compression: CompressionType::Zstd(profile.compression_level()),
+                row_group_size: profile.row_group_size(),
+                ..Default::default()
+            },
+            batch_size: profile.batch_size(),
+            preserve_precision: true,
+            include_chromatograms: true,
+            progress_interval: 1000,
+            parallel: false,
+            legacy: false,
+        }
+    }
+
+    /// Resolve a profile by name, looking at user profiles in `config` first.
+    pub fn resolve(name: &str, config: Option<&Config>) -> Result<Self> {
+        if let Some(definition) = config.and_then(|c| c.profiles.get(name)) {
+            return Self::from_definition(name, definition);
+        }
+        name.parse::<Profile>().map(Self::builtin).map_err(|_| {
+            let mut available: Vec<&str> = Profile::variants().to_vec();
+            if let Some(config) = config {
+                available.extend(config.profiles.keys().map(String::as_str));
+            }
+            anyhow::anyhow!(
+                "Unknown profile '{}'. Available profiles: {}",
+                name,
+                available.join(", ")
+            )
+        })
+    }
+
+    /// Apply a config-file profile on top of its base profile.
+    pub fn from_definition(name: &str, definition: &ProfileDefinition) -> Result<Self> {
+        let base = match definition.base.as_deref() {
+            Some(base) => base
+                .parse::<Profile>()
+                .map_err(|e| anyhow::anyhow!("Invalid base for profile '{}': {}", name, e))?,
+            None => Profile::default(),
+        };
+        let mut settings = Self::builtin(base);
+        settings.name = name.to_string();
+        settings.description = definition.description.clone();
+        settings.builtin = false;
+
+        let writer = &mut settings.writer;
+        let level = definition.compression_level.or(match writer.compression {
+            CompressionType::Zstd(level) => Some(level),
+            _ => None,
+        });
+        writer.compression = match (definition.compression, level) {
+            (Some(CompressionCodec::Snappy), _) => CompressionType::Snappy,
+            (Some(CompressionCodec::None), _) => CompressionType::Uncompressed,
+            (Some(CompressionCodec::Zstd), level) | (None, level @ Some(_)) => {
+                CompressionType::Zstd(level.unwrap_or(3))
+            }
+            (None, None) => writer.compression,
+        };
+        if let CompressionType::Zstd(level) = writer.compression {
+            if !(1..=22).contains(&level) {
+                anyhow::bail!(
+                    "Profile '{}': compression_level must be between 1 and 22, got {}",
+                    name,
+                    level
+                );
+            }
+        }
+        if let Some(value) = definition.row_group_size {
+            writer.row_group_size = value;
+        }
+        if let Some(value) = definition.data_page_size {
+            writer.data_page_size = value;
+        }
+        if let Some(value) = definition.write_statistics {
+            writer.write_statistics = value;
+        }
+        if let Some(value) = definition.dictionary_page_size_limit {
+            writer.dictionary_page_size_limit = value;
+        }
+        if let Some(value) = definition.max_peaks_per_file {
+            writer.max_peaks_per_file = (value > 0).then_some(value);
+        }
+        if let Some(value) = definition.use_byte_stream_split {
+            writer.use_byte_stream_split = value;
+        }
+        if let Some(value) = definition.async_buffer_capacity {
+            writer.async_buffer_capacity = value;
+        }
+
+        if let Some(value) = definition.batch_size {
+            settings.batch_size = value;
+        }
+        if let Some(value) = definition.preserve_precision {
+            settings.preserve_precision = value;
+        }
+        if let Some(value) = definition.include_chromatograms {
+            settings.include_chromatograms = value;
+        }
+        if let Some(value) = definition.progress_interval {
+            settings.progress_interval = value;
+        }
+        if let Some(value) = definition.parallel {
+            settings.parallel = value;
+        }
+        if let Some(value) = definition.legacy {
+            settings.legacy = value;
+        }
+        Ok(settings)
+    }
+
+    /// Every setting as a fully populated definition, for display.
+    pub fn to_definition(&self) -> ProfileDefinition {
+        let writer = &self.writer;
+        let (compression, compression_level) = match writer.compression {
+            CompressionType::Zstd(level) => (CompressionCodec::Zstd, Some(level)),
+            CompressionType::Snappy => (CompressionCodec::Snappy, None),
+            CompressionType::Uncompressed => (CompressionCodec::None, None),
+        };
+        ProfileDefinition {
+            description: self.description.clone(),
+            base: None,
+            compression: Some(compression),
+            compression_level,
+            row_group_size: Some(writer.row_group_size),
+            data_page_size: Some(writer.data_page_size),
+            write_statistics: Some(writer.write_statistics),
+            dictionary_page_size_limit: Some(writer.dictionary_page_size_limit),
+            max_peaks_per_file: Some(writer.max_peaks_per_file.unwrap_or(0)),
+            use_byte_stream_split: Some(writer.use_byte_stream_split),
+            async_buffer_capacity: Some(writer.async_buffer_capacity),
+            batch_size: Some(self.batch_size),
+            preserve_precision: Some(self.preserve_precision),
+            include_chromatograms: Some(self.include_chromatograms),
+            progress_interval: Some(self.progress_interval),
+            parallel: Some(self.parallel),
+            legacy: Some(self.legacy),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -110,13 +289,48 @@ mod tests {
     }
 
     #[test]
-    fn test_profile_from_str() {
-        assert_eq!(Profile::from_str("fast").unwrap(), Profile::Fast);
-        assert_eq!(Profile::from_str("BALANCED").unwrap(), Profile::Balanced);
+    fn test_profile_from_str() -> Result<(), Box<dyn std::error::Error>> {
+        assert_eq!(Profile::from_str("fast")?, Profile::Fast);
+        assert_eq!(Profile::from_str("BALANCED")?, Profile::Balanced);
         assert_eq!(
-            Profile::from_str("max-compression").unwrap(),
+            Profile::from_str("max-compression")?,
             Profile::MaxCompression
         );
         assert!(Profile::from_str("invalid").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_user_profile() -> Result<(), Box<dyn std::error::Error>> {
+        let config = Config::from_str(
+            r#"
+            [profiles.mylab-archive]
+            description = "NAS archive"
+            base = "max-compression"
+            compression_level = 19
+            max_peaks_per_file = 0
+            legacy = true
+        "#,
+        )?;
+
+        let settings = ProfileSettings::resolve("mylab-archive", Some(&config))?;
+        assert!(!settings.builtin);
+        assert_eq!(settings.writer.compression, CompressionType::Zstd(19));
+        // Unset keys come from the base profile
+        assert_eq!(settings.writer.row_group_size, 200_000);
+        assert_eq!(settings.batch_size, 2_000);
+        assert_eq!(settings.writer.max_peaks_per_file, None);
+        assert!(settings.legacy);
+
+        // The displayed snapshot resolves to the same settings
+        let snapshot = settings.to_definition();
+        let reparsed = ProfileSettings::from_definition("mylab-archive", &snapshot)?;
+        assert_eq!(reparsed.to_definition(), snapshot);
+
+        let fast = ProfileSettings::resolve("fast", Some(&config))?;
+        assert!(fast.builtin);
+        assert_eq!(fast.writer.compression, CompressionType::Zstd(1));
+        assert!(ProfileSettings::resolve("missing", Some(&config)).is_err());
+        Ok(())
     }
 }
@@ -0,0 +1,94 @@
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+use mzpeak::writer::CompressionType;
+
+use super::config::Config;
+use super::profile::{Profile, ProfileSettings};
+
+/// List built-in and config-file profiles
+pub fn list(config_path: Option<PathBuf>) -> Result<()> {
+    let config = load_config(config_path)?;
+
+    let mut profiles: Vec<ProfileSettings> = Profile::variants()
+        .iter()
+        .map(|name| ProfileSettings::resolve(name, None))
+        .collect::<Result<_>>()?;
+    if let Some(config) = config.as_ref() {
+        for name in config.profiles.keys() {
+            profiles.push(ProfileSettings::resolve(name, Some(config))?);
+        }
+    }
+
+    let width = profiles
+        .iter()
+        .map(|p| p.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("NAME".len());
+    println!("{:<width$}  {:<8}  SETTINGS", "NAME", "SOURCE");
+    for profile in &profiles {
+        let source = if profile.builtin {
+            "built-in"
+        } else {
+            "config"
+        };
+        println!(
+            "{:<width$}  {:<8}  {}",
+            profile.name,
+            source,
+            summary(profile)
+        );
+        if let Some(description) = profile.description.as_deref() {
+            println!("{:<width$}  {:<8}  {}", "", "", description);
+        }
+    }
+    Ok(())
+}
+
+/// Print the effective settings of one profile as a `[profiles.<name>]` table
+pub fn show(name: String, config_path: Option<PathBuf>) -> Result<()> {
+    let config = load_config(config_path)?;
+    let settings = ProfileSettings::resolve(&name, config.as_ref())?;
+
+    let table = toml::to_string(&settings.to_definition())
+        .context("Failed to serialize profile settings")?;
+    if settings.builtin {
+        println!("# built-in profile");
+    }
+    println!("[profiles.{}]", toml_key(&settings.name));
+    print!("{}", table);
+    Ok(())
+}
+
+fn load_config(config_path: Option<PathBuf>) -> Result<Option<Config>> {
+    config_path.map(|path| Config::from_file(&path)).transpose()
+}
+
+fn summary(profile: &ProfileSettings) -> String {
+    let compression = match profile.writer.compression {
+        CompressionType::Zstd(level) => format!("zstd {}", level),
+        CompressionType::Snappy => "snappy".to_string(),
+        CompressionType::Uncompressed => "uncompressed".to_string(),
+    };
+    format!(
+        "{}, {} peaks/row group, {} spectra/batch{}",
+        compression,
+        profile.writer.row_group_size,
+        profile.batch_size,
+        if profile.legacy { ", legacy" } else { "" }
+    )
+}
+
+/// Quote a table key unless it is a bare TOML key
+fn toml_key(name: &str) -> String {
+    if !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        name.to_string()
+    } else {
+        format!("{:?}", name)
+    }
+}
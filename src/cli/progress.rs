@@ -0,0 +1,71 @@
+use log::info;
+use std::io::{IsTerminal, Write};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use mzpeak::writer::WriteProgress;
+
+/// Redraw interval on a terminal
+const TERMINAL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Log interval when stderr is not a terminal
+const LOG_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Live throughput display fed from a writer's progress counters.
+///
+/// On a terminal the status line on stderr is redrawn every second;
+/// otherwise a status line is logged every 30 seconds. Stops when dropped.
+pub struct ProgressDisplay {
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ProgressDisplay {
+    /// Start rendering `progress` on a background thread
+    pub fn start(progress: Arc<WriteProgress>) -> Self {
+        let terminal = std::io::stderr().is_terminal();
+        let interval = if terminal {
+            TERMINAL_INTERVAL
+        } else {
+            LOG_INTERVAL
+        };
+        let (stop, stopped) = mpsc::channel::<()>();
+
+        let handle = std::thread::spawn(move || loop {
+            match stopped.recv_timeout(interval) {
+                Err(RecvTimeoutError::Timeout) => {
+                    let snapshot = progress.snapshot();
+                    if terminal {
+                        let mut stderr = std::io::stderr().lock();
+                        let _ = write!(stderr, "\r\x1b[2K{}", snapshot);
+                        let _ = stderr.flush();
+                    } else {
+                        info!("{}", snapshot);
+                    }
+                }
+                _ => {
+                    if terminal {
+                        eprint!("\r\x1b[2K");
+                    }
+                    break;
+                }
+            }
+        });
+
+        Self {
+            stop: Some(stop),
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for ProgressDisplay {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use anyhow::{Context, Result};
+use log::info;
+use std::path::{Path, PathBuf};
+
+use mzpeak::processing::{ReporterConfig, ReporterPlex, ReporterTable};
+use mzpeak::reader::MzPeakReader;
+
+/// Extract reporter ion intensities into a reporters.parquet table
+pub fn run(
+    file: PathBuf,
+    output: PathBuf,
+    plex: ReporterPlex,
+    ppm: f64,
+    impurities: Option<PathBuf>,
+) -> Result<()> {
+    if !file.exists() {
+        anyhow::bail!("File does not exist: {}", file.display());
+    }
+
+    let mut config = ReporterConfig::new(plex);
+    config.tolerance_ppm = ppm;
+    if let Some(path) = impurities {
+        config.impurity_matrix = Some(read_impurity_matrix(&path)?);
+    }
+
+    let reader = MzPeakReader::open(&file).context("Failed to open mzPeak file")?;
+    let table = ReporterTable::extract(&reader, &config)
+        .context("Failed to extract reporter ions")?;
+    info!(
+        "Extracted {} channels from {} spectra",
+        plex.num_channels(),
+        table.rows.len()
+    );
+
+    table
+        .write_parquet(&output)
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+    info!("Wrote reporter ion table to {}", output.display());
+
+    Ok(())
+}
+
+/// Read an impurity matrix from a CSV file (one row of fractions per channel)
+///
+/// Empty lines and lines starting with `#` are ignored.
+fn read_impurity_matrix(path: &Path) -> Result<Vec<Vec<f64>>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            line.split(',')
+                .map(|value| {
+                    value
+                        .trim()
+                        .parse::<f64>()
+                        .with_context(|| format!("Invalid impurity value '{}'", value.trim()))
+                })
+                .collect()
+        })
+        .collect()
+}
@@ -0,0 +1,56 @@
+use anyhow::{Context, Result};
+use log::info;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use mzpeak::dataset::MzPeakDatasetWriter;
+use mzpeak::metadata::{MzPeakMetadata, ProcessingHistory, ProcessingStep};
+use mzpeak::reader::MzPeakReader;
+use mzpeak::writer::WriterConfig;
+
+/// Write a seeded random subset of the spectra to a new mzPeak dataset
+pub fn run(
+    file: PathBuf,
+    output: PathBuf,
+    count: usize,
+    seed: u64,
+    ms_level: Option<i16>,
+) -> Result<()> {
+    if !file.exists() {
+        anyhow::bail!("File does not exist: {}", file.display());
+    }
+
+    let reader = MzPeakReader::open(&file).context("Failed to open mzPeak file")?;
+    let sample = reader
+        .sample_spectra(count, seed, ms_level)
+        .context("Failed to sample spectra")?;
+
+    let mut metadata = reader
+        .metadata()
+        .mzpeak_metadata
+        .clone()
+        .unwrap_or_else(MzPeakMetadata::new);
+    let mut history = ProcessingHistory {
+        steps: reader.processing_history()?,
+        ..Default::default()
+    };
+    let mut parameters = HashMap::from([
+        ("count".to_string(), count.to_string()),
+        ("seed".to_string(), seed.to_string()),
+    ]);
+    if let Some(level) = ms_level {
+        parameters.insert("ms_level".to_string(), level.to_string());
+    }
+    history.append(ProcessingStep::mzpeak("Random sampling", parameters));
+    metadata.processing_history = Some(history);
+
+    let mut writer = MzPeakDatasetWriter::new(&output, &metadata, WriterConfig::default())
+        .with_context(|| format!("Failed to create {}", output.display()))?;
+    for spectrum in &sample {
+        writer.write_spectrum_arrays(&spectrum.to_owned()?)?;
+    }
+    writer.close().context("Failed to finalize output")?;
+
+    info!("Wrote {} sampled spectra to {}", sample.len(), output.display());
+    Ok(())
+}
@@ -0,0 +1,589 @@
+use anyhow::{Context, Result};
+use log::{info, warn};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use arrow::array::{ArrayRef, Float32Array, Float64Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::ipc::writer::StreamWriter;
+use arrow::record_batch::RecordBatch;
+use serde_json::{json, Value};
+
+use mzpeak::processing::MassTolerance;
+use mzpeak::reader::{MzPeakReader, ReaderError};
+
+/// Single-page viewer served at `/`
+const VIEWER_HTML: &str = include_str!("viewer.html");
+
+/// Largest request head accepted, in bytes
+const MAX_REQUEST_HEAD: usize = 16 * 1024;
+
+/// Socket read and write timeout, so a stalled client cannot block the
+/// single-threaded server
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Per-spectrum entry of the spectrum index
+struct SpectrumEntry {
+    id: i64,
+    ms_level: i16,
+    retention_time: f32,
+    precursor_mz: Option<f64>,
+    total_ion_current: f64,
+    num_peaks: usize,
+}
+
+/// Spectrum list and m/z range, built by one streaming pass over the peaks
+struct SpectrumIndex {
+    spectra: Vec<SpectrumEntry>,
+    mz_range: Option<(f64, f64)>,
+}
+
+/// Dataset opened by the server
+///
+/// The spectrum index behind `/api/summary`, `/api/spectra` and `/api/tic` is
+/// built on the first request that needs it, so startup does not read the
+/// peaks.
+struct Dataset {
+    file: PathBuf,
+    reader: MzPeakReader,
+    index: OnceLock<Result<SpectrumIndex, String>>,
+}
+
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn json(value: Value) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: json!({ "error": message.into() }).to_string().into_bytes(),
+        }
+    }
+}
+
+/// Serve a minimal JSON/Arrow API and an embedded viewer for one dataset
+pub fn run(file: PathBuf, http: String) -> Result<()> {
+    if !file.exists() {
+        anyhow::bail!("File does not exist: {}", file.display());
+    }
+
+    let dataset = Dataset::open(file)?;
+    let address = parse_address(&http)?;
+    let listener = TcpListener::bind(address.as_str())
+        .with_context(|| format!("Failed to bind {}", address))?;
+
+    info!(
+        "Serving {} on http://{}",
+        dataset.file.display(),
+        listener.local_addr()?
+    );
+    println!("Viewer: http://{}/", listener.local_addr()?);
+    println!("Press Ctrl+C to stop");
+
+    // Requests are handled one at a time; the server is meant for a single
+    // person doing quick QC, not for concurrent load
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = handle_connection(&dataset, stream) {
+                    warn!("Request failed: {}", e);
+                }
+            }
+            Err(e) => warn!("Failed to accept connection: {}", e),
+        }
+    }
+    Ok(())
+}
+
+/// `:8080` listens on loopback only; `host:port` is used as given, so
+/// `0.0.0.0:8080` exposes the server on all interfaces
+fn parse_address(http: &str) -> Result<String> {
+    let address = match http.strip_prefix(':') {
+        Some(port) => format!("127.0.0.1:{}", port),
+        None => http.to_string(),
+    };
+    address
+        .to_socket_addrs()
+        .with_context(|| format!("Invalid listen address: {}", http))?;
+    Ok(address)
+}
+
+impl SpectrumIndex {
+    fn build(reader: &MzPeakReader) -> Result<Self, ReaderError> {
+        let mut spectra = Vec::new();
+        let mut mz_range: Option<(f64, f64)> = None;
+        for spectrum in reader.iter_spectra_arrays_streaming()? {
+            let spectrum = spectrum?;
+            for array in spectrum.mz_arrays()? {
+                for &mz in array.values() {
+                    let (min, max) = mz_range.get_or_insert((mz, mz));
+                    *min = min.min(mz);
+                    *max = max.max(mz);
+                }
+            }
+            let total_ion_current = match spectrum.total_ion_current {
+                Some(tic) => tic,
+                None => spectrum
+                    .intensity_arrays()?
+                    .iter()
+                    .flat_map(|array| array.values().iter())
+                    .map(|&intensity| f64::from(intensity))
+                    .sum(),
+            };
+            spectra.push(SpectrumEntry {
+                id: spectrum.spectrum_id,
+                ms_level: spectrum.ms_level,
+                retention_time: spectrum.retention_time,
+                precursor_mz: spectrum.precursor_mz,
+                total_ion_current,
+                num_peaks: spectrum.peak_count(),
+            });
+        }
+        Ok(Self { spectra, mz_range })
+    }
+}
+
+impl Dataset {
+    fn open(file: PathBuf) -> Result<Self> {
+        let reader = MzPeakReader::open(&file).context("Failed to open mzPeak file")?;
+        Ok(Self {
+            file,
+            reader,
+            index: OnceLock::new(),
+        })
+    }
+
+    fn index(&self) -> Result<&SpectrumIndex, Response> {
+        self.index
+            .get_or_init(|| SpectrumIndex::build(&self.reader).map_err(|e| e.to_string()))
+            .as_ref()
+            .map_err(|e| Response::error(500, e.clone()))
+    }
+
+    fn route(&self, path: &str, query: &HashMap<String, String>) -> Response {
+        let result = match path {
+            "/" | "/index.html" => {
+                return Response {
+                    status: 200,
+                    content_type: "text/html; charset=utf-8",
+                    body: VIEWER_HTML.as_bytes().to_vec(),
+                }
+            }
+            "/api/summary" => self.summary_json(),
+            "/api/spectra" => self.spectra_json(query),
+            "/api/tic" => self.tic_json(),
+            "/api/xic" => self.xic_json(query),
+            _ => match path.strip_prefix("/api/spectrum/") {
+                Some(id) => self.spectrum(id, query),
+                None => return Response::error(404, format!("Not found: {}", path)),
+            },
+        };
+        result.unwrap_or_else(|response| response)
+    }
+
+    fn summary_json(&self) -> Result<Response, Response> {
+        let index = self.index()?;
+        let metadata = self.reader.metadata();
+        let count = |ms_level: i16| {
+            index
+                .spectra
+                .iter()
+                .filter(|s| s.ms_level == ms_level)
+                .count()
+        };
+        let rt_range = index.spectra.iter().map(|s| s.retention_time).fold(
+            None,
+            |range: Option<(f32, f32)>, rt| match range {
+                Some((min, max)) => Some((min.min(rt), max.max(rt))),
+                None => Some((rt, rt)),
+            },
+        );
+        Ok(Response::json(json!({
+            "file": self.file.display().to_string(),
+            "format_version": metadata.format_version,
+            "total_peaks": metadata.total_rows,
+            "num_spectra": index.spectra.len(),
+            "num_ms1_spectra": count(1),
+            "num_ms2_spectra": count(2),
+            "rt_range": rt_range.map(|(min, max)| [min, max]),
+            "mz_range": index.mz_range.map(|(min, max)| [min, max]),
+        })))
+    }
+
+    fn spectra_json(&self, query: &HashMap<String, String>) -> Result<Response, Response> {
+        let ms_level = optional_param::<i16>(query, "ms_level")?;
+        let spectra: Vec<Value> = self
+            .index()?
+            .spectra
+            .iter()
+            .filter(|s| ms_level.is_none() || ms_level == Some(s.ms_level))
+            .map(|s| {
+                json!({
+                    "id": s.id,
+                    "ms_level": s.ms_level,
+                    "rt": s.retention_time,
+                    "precursor_mz": s.precursor_mz,
+                    "tic": s.total_ion_current,
+                    "peaks": s.num_peaks,
+                })
+            })
+            .collect();
+        Ok(Response::json(Value::Array(spectra)))
+    }
+
+    fn tic_json(&self) -> Result<Response, Response> {
+        let (rt, intensity): (Vec<f32>, Vec<f64>) = self
+            .index()?
+            .spectra
+            .iter()
+            .filter(|s| s.ms_level == 1)
+            .map(|s| (s.retention_time, s.total_ion_current))
+            .unzip();
+        Ok(Response::json(json!({ "rt": rt, "intensity": intensity })))
+    }
+
+    fn xic_json(&self, query: &HashMap<String, String>) -> Result<Response, Response> {
+        let mz = optional_param::<f64>(query, "mz")?
+            .ok_or_else(|| Response::error(400, "Missing parameter: mz"))?;
+        let tolerance = match optional_param::<f64>(query, "da")? {
+            Some(da) => MassTolerance::Da(da),
+            None => MassTolerance::Ppm(optional_param(query, "ppm")?.unwrap_or(10.0)),
+        };
+        let xic = self
+            .reader
+            .extract_xics(&[mz], tolerance)
+            .map_err(|e| Response::error(500, e.to_string()))?
+            .remove(0);
+        Ok(Response::json(json!({
+            "mz": xic.target_mz,
+            "lower_mz": xic.lower_mz,
+            "upper_mz": xic.upper_mz,
+            "rt": xic.retention_times,
+            "intensity": xic.intensities,
+        })))
+    }
+
+    fn spectrum(&self, id: &str, query: &HashMap<String, String>) -> Result<Response, Response> {
+        let id: i64 = id
+            .parse()
+            .map_err(|_| Response::error(400, format!("Invalid spectrum id: {}", id)))?;
+        let spectrum = self
+            .reader
+            .get_spectrum_arrays(id)
+            .map_err(|e| Response::error(500, e.to_string()))?
+            .ok_or_else(|| Response::error(404, format!("Spectrum {} not found", id)))?;
+        let to_500 = |e: mzpeak::reader::ReaderError| Response::error(500, e.to_string());
+        let mz: Vec<f64> = spectrum
+            .mz_arrays()
+            .map_err(to_500)?
+            .iter()
+            .flat_map(|array| array.values().iter().copied())
+            .collect();
+        let intensity: Vec<f32> = spectrum
+            .intensity_arrays()
+            .map_err(to_500)?
+            .iter()
+            .flat_map(|array| array.values().iter().copied())
+            .collect();
+
+        match query.get("format").map(String::as_str) {
+            None | Some("json") => Ok(Response::json(json!({
+                "id": spectrum.spectrum_id,
+                "scan_number": spectrum.scan_number,
+                "ms_level": spectrum.ms_level,
+                "rt": spectrum.retention_time,
+                "polarity": spectrum.polarity,
+                "precursor_mz": spectrum.precursor_mz,
+                "precursor_charge": spectrum.precursor_charge,
+                "mz": mz,
+                "intensity": intensity,
+            }))),
+            Some("arrow") => arrow_stream(mz, intensity)
+                .map(|body| Response {
+                    status: 200,
+                    content_type: "application/vnd.apache.arrow.stream",
+                    body,
+                })
+                .map_err(|e| Response::error(500, e.to_string())),
+            Some(other) => Err(Response::error(400, format!("Unknown format: {}", other))),
+        }
+    }
+}
+
+/// Encode a spectrum as an Arrow IPC stream with `mz` and `intensity` columns
+fn arrow_stream(mz: Vec<f64>, intensity: Vec<f32>) -> Result<Vec<u8>> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("mz", DataType::Float64, false),
+        Field::new("intensity", DataType::Float32, false),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Float64Array::from(mz)),
+        Arc::new(Float32Array::from(intensity)),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+    let mut body = Vec::new();
+    let mut writer = StreamWriter::try_new(&mut body, &schema)?;
+    writer.write(&batch)?;
+    writer.finish()?;
+    drop(writer);
+    Ok(body)
+}
+
+fn optional_param<T: std::str::FromStr>(
+    query: &HashMap<String, String>,
+    name: &str,
+) -> Result<Option<T>, Response> {
+    query
+        .get(name)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| Response::error(400, format!("Invalid {}: {}", name, value)))
+        })
+        .transpose()
+}
+
+fn handle_connection(dataset: &Dataset, stream: TcpStream) -> Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    // The limit bounds every line too, so a head without newlines cannot
+    // grow the buffer
+    let mut reader = BufReader::new((&stream).take(MAX_REQUEST_HEAD as u64));
+
+    let mut head = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        if !line.ends_with('\n') {
+            if reader.get_ref().limit() == 0 {
+                return write_response(
+                    &stream,
+                    &Response::error(431, "Request header too large"),
+                    true,
+                );
+            }
+            // Connection closed before the end of the head
+            break;
+        }
+        if line == "\r\n" || line == "\n" {
+            break;
+        }
+        head.push(line);
+    }
+
+    let request_line = head.first().map(|line| line.trim_end()).unwrap_or_default();
+    let mut parts = request_line.split(' ');
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let response = match method {
+        "GET" | "HEAD" => {
+            let (path, query) = split_target(target);
+            dataset.route(path, &query)
+        }
+        _ => Response::error(405, format!("Method not allowed: {}", method)),
+    };
+    info!("{} {} {}", method, target, response.status);
+    write_response(&stream, &response, method != "HEAD")
+}
+
+fn write_response(mut stream: &TcpStream, response: &Response, include_body: bool) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason_phrase(response.status),
+        response.content_type,
+        response.body.len()
+    )?;
+    if include_body {
+        stream.write_all(&response.body)?;
+    }
+    stream.flush()?;
+    Ok(())
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        431 => "Request Header Fields Too Large",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Split a request target into its path and decoded query parameters
+fn split_target(target: &str) -> (&str, HashMap<String, String>) {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let params = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect();
+    (path, params)
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                match (hex_value(bytes[i + 1]), hex_value(bytes[i + 2])) {
+                    (Some(high), Some(low)) => {
+                        decoded.push(high << 4 | low);
+                        i += 2;
+                    }
+                    _ => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|digit| digit as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_target_decodes_query() {
+        let (path, query) = split_target("/api/xic?mz=445.12&ppm=5&note=a%20b+c");
+        assert_eq!(path, "/api/xic");
+        assert_eq!(query["mz"], "445.12");
+        assert_eq!(query["ppm"], "5");
+        assert_eq!(query["note"], "a b c");
+
+        let (path, query) = split_target("/api/tic");
+        assert_eq!(path, "/api/tic");
+        assert!(query.is_empty());
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%4"), "%zz%4");
+    }
+
+    #[test]
+    fn test_parse_address() -> Result<(), Box<dyn std::error::Error>> {
+        assert_eq!(parse_address(":8080")?, "127.0.0.1:8080");
+        assert_eq!(parse_address("0.0.0.0:8080")?, "0.0.0.0:8080");
+        assert_eq!(parse_address("127.0.0.1:9000")?, "127.0.0.1:9000");
+        assert!(parse_address("not an address").is_err());
+        Ok(())
+    }
+
+    /// Two MS1 spectra at 60 and 61 s with peaks at 445.12 and 500.0
+    fn test_dataset(dir: &std::path::Path) -> Result<Dataset, Box<dyn std::error::Error>> {
+        use mzpeak::metadata::MzPeakMetadata;
+        use mzpeak::writer::{MzPeakWriter, PeakArrays, SpectrumArrays, WriterConfig};
+
+        let path = dir.join("serve.mzpeak.parquet");
+        let mut writer =
+            MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), WriterConfig::default())?;
+        for (id, rt) in [(0, 60.0), (1, 61.0)] {
+            let peaks = PeakArrays::new(vec![445.12, 500.0], vec![100.0, 50.0]);
+            writer.write_spectrum_arrays(&SpectrumArrays::new_ms1(id, id + 1, rt, 1, peaks))?;
+        }
+        writer.finish()?;
+        Ok(Dataset::open(path)?)
+    }
+
+    #[test]
+    fn test_request_head_limit() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let dataset = test_dataset(dir.path())?;
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+
+        // A head of exactly the limit with no blank line after it
+        let mut head = String::from("GET /api/tic HTTP/1.1\r\n");
+        let padding = MAX_REQUEST_HEAD - head.len() - "X-Pad: \r\n".len();
+        head.push_str(&format!("X-Pad: {}\r\n", "a".repeat(padding)));
+        let client = std::thread::spawn(move || -> std::io::Result<String> {
+            let mut stream = TcpStream::connect(address)?;
+            stream.write_all(head.as_bytes())?;
+            let mut response = String::new();
+            stream.read_to_string(&mut response)?;
+            Ok(response)
+        });
+
+        let (stream, _) = listener.accept()?;
+        handle_connection(&dataset, stream)?;
+        let response = client.join().map_err(|_| "client panicked")??;
+        assert!(response.starts_with("HTTP/1.1 431 "), "{}", response);
+        Ok(())
+    }
+
+    #[test]
+    fn test_routes() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let dataset = test_dataset(dir.path())?;
+        let get = |target: &str| {
+            let (path, query) = split_target(target);
+            let response = dataset.route(path, &query);
+            let body = serde_json::from_slice(&response.body).unwrap_or(Value::Null);
+            (response.status, body)
+        };
+
+        assert!(dataset.index.get().is_none());
+        let (status, summary) = get("/api/summary");
+        assert_eq!(status, 200);
+        assert_eq!(summary["num_spectra"], 2);
+        assert_eq!(summary["total_peaks"], 4);
+        assert_eq!(summary["rt_range"], json!([60.0, 61.0]));
+        assert_eq!(summary["mz_range"], json!([445.12, 500.0]));
+
+        let (_, tic) = get("/api/tic");
+        assert_eq!(tic["intensity"], json!([150.0, 150.0]));
+
+        let (_, xic) = get("/api/xic?mz=445.12&ppm=10");
+        assert_eq!(xic["intensity"], json!([100.0, 100.0]));
+        assert_eq!(get("/api/xic").0, 400);
+
+        let (_, spectrum) = get("/api/spectrum/1");
+        assert_eq!(spectrum["mz"], json!([445.12, 500.0]));
+        assert_eq!(get("/api/spectrum/7").0, 404);
+        assert_eq!(get("/api/spectrum/abc").0, 400);
+        assert_eq!(get("/nope").0, 404);
+
+        let (path, query) = split_target("/api/spectrum/0?format=arrow");
+        let response = dataset.route(path, &query);
+        let batches: Vec<RecordBatch> =
+            arrow::ipc::reader::StreamReader::try_new(&response.body[..], None)?
+                .collect::<Result<_, _>>()?;
+        assert_eq!(batches[0].num_rows(), 2);
+
+        let (path, query) = split_target("/");
+        assert!(dataset
+            .route(path, &query)
+            .content_type
+            .starts_with("text/html"));
+        Ok(())
+    }
+}
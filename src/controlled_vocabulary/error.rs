@@ -0,0 +1,11 @@
+/// Errors that can occur while loading controlled vocabulary data
+#[derive(Debug, thiserror::Error)]
+pub enum CvError {
+    /// I/O error
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    /// Malformed OBO or instrument database content
+    #[error("Parse error: {0}")]
+    ParseError(String),
+}
@@ -0,0 +1,103 @@
+//! Instrument model database
+//!
+//! A tab-separated table mapping instrument model names to their PSI-MS
+//! accession, vendor and mass analyzer configuration. Columns are `model`,
+//! `accession`, `vendor` and `mass_analyzers` (`;`-separated, in ion path
+//! order, e.g. `quadrupole;orbitrap`); lines starting with `#` are comments.
+
+use super::CvError;
+
+/// One instrument model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstrumentModel {
+    /// Model name as reported by vendor software
+    pub name: String,
+    /// PSI-MS accession of the model
+    pub accession: String,
+    /// Vendor name
+    pub vendor: String,
+    /// Mass analyzers in ion path order
+    pub mass_analyzers: Vec<String>,
+}
+
+/// Instrument model lookup table
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentDb {
+    models: Vec<InstrumentModel>,
+}
+
+impl InstrumentDb {
+    /// Parse the tab-separated database; `#` lines are comments
+    pub fn parse(text: &str) -> Result<Self, CvError> {
+        let mut models = Vec::new();
+        for (line_no, line) in text.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+            if fields.len() < 3 {
+                return Err(CvError::ParseError(format!(
+                    "instrument database line {}: expected at least 3 tab-separated fields",
+                    line_no + 1
+                )));
+            }
+            models.push(InstrumentModel {
+                name: fields[0].to_string(),
+                accession: fields[1].to_string(),
+                vendor: fields[2].to_string(),
+                mass_analyzers: fields
+                    .get(3)
+                    .map(|v| {
+                        v.split(';')
+                            .map(str::trim)
+                            .filter(|a| !a.is_empty())
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            });
+        }
+        if models.is_empty() {
+            return Err(CvError::ParseError(
+                "instrument database has no entries".to_string(),
+            ));
+        }
+        Ok(Self { models })
+    }
+
+    /// Look up a model by name
+    ///
+    /// Matching ignores case, whitespace, `-` and `_`, so "Q-Exactive HF" finds
+    /// "Q Exactive HF".
+    pub fn lookup(&self, name: &str) -> Option<&InstrumentModel> {
+        let key = normalize(name);
+        self.models.iter().find(|m| normalize(&m.name) == key)
+    }
+
+    /// Look up a model by PSI-MS accession
+    pub fn by_accession(&self, accession: &str) -> Option<&InstrumentModel> {
+        self.models.iter().find(|m| m.accession == accession)
+    }
+
+    /// Iterate over all models
+    pub fn iter(&self) -> impl Iterator<Item = &InstrumentModel> {
+        self.models.iter()
+    }
+
+    /// Number of models
+    pub fn len(&self) -> usize {
+        self.models.len()
+    }
+
+    /// Whether the database has no models
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
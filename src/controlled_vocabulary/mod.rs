@@ -4,6 +4,21 @@
 //! Controlled Vocabulary (CV) terms. Using CV terms ensures global interoperability
 //! as specified in the mzPeak whitepaper.
 //!
+//! ## Ontology Lookup
+//!
+//! [`psi_ms()`] and [`instrument_db()`] give process-wide access to the PSI-MS
+//! ontology and an instrument model database. Compressed copies are embedded in
+//! the binary for offline use and can be overridden on disk (see [`store`]).
+//!
+//! ```rust,no_run
+//! use mzpeak::controlled_vocabulary::{instrument_db, psi_ms};
+//!
+//! assert_eq!(psi_ms().name("MS:1000040"), Some("m/z"));
+//! if let Some(model) = instrument_db().lookup("Q Exactive HF") {
+//!     println!("{} ({})", model.accession, model.mass_analyzers.join(", "));
+//! }
+//! ```
+//!
 //! ## Reference
 //! - OBO file: <https://raw.githubusercontent.com/HUPO-PSI/psi-ms-CV/master/psi-ms.obo>
 //! - Documentation: <https://github.com/HUPO-PSI/psi-ms-CV>
@@ -12,6 +27,16 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
 
+mod error;
+pub mod instruments;
+pub mod obo;
+pub mod store;
+
+pub use error::CvError;
+pub use instruments::{InstrumentDb, InstrumentModel};
+pub use obo::{OboTerm, Ontology};
+pub use store::{install_cv_files, instrument_db, psi_ms, CvUpdateReport};
+
 /// A controlled vocabulary term with its accession and name
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct CvTerm {
@@ -436,4 +461,62 @@ mod tests {
         assert_eq!(list.len(), 2);
         assert!(list.get("MS:1000511").is_some());
     }
+
+    #[test]
+    fn test_embedded_psi_ms() {
+        let ontology = store::embedded_psi_ms();
+        assert_eq!(ontology.name("MS:1000040"), Some("m/z"));
+        assert_eq!(ontology.cv_term("UO:0000010"), Some(unit_terms::second()));
+        // HCD -> CID -> dissociation method
+        assert!(ontology.is_a("MS:1000422", "MS:1000044"));
+        assert!(!ontology.is_a("MS:1000044", "MS:1000422"));
+        assert!(ontology
+            .descendants("MS:1000031")
+            .iter()
+            .any(|t| t.name == "Q Exactive"));
+        // Memoized accessor returns the same instance
+        assert!(std::ptr::eq(psi_ms(), psi_ms()));
+    }
+
+    #[test]
+    fn test_obo_parser() {
+        let text = "format-version: 1.2\ndata-version: 4.1.0\n\n[Term]\nid: X:1\nname: root\n\n[Term]\nid: X:2\nname: child\ndef: \"A child.\" [PSI:MS]\nis_a: X:1 ! root\nis_obsolete: true\n\n[Typedef]\nid: part_of\nname: part_of\n";
+        let ontology = Ontology::parse(text).unwrap();
+        assert_eq!(ontology.data_version(), Some("4.1.0"));
+        assert_eq!(ontology.len(), 2);
+        let child = ontology.get("X:2").unwrap();
+        assert_eq!(child.definition.as_deref(), Some("A child."));
+        assert_eq!(child.parents, vec!["X:1".to_string()]);
+        assert!(child.is_obsolete);
+        assert!(ontology.descendants("X:1").is_empty());
+        assert!(Ontology::parse("format-version: 1.2\n").is_err());
+    }
+
+    #[test]
+    fn test_instrument_db_lookup() {
+        let db = store::embedded_instrument_db();
+        let model = db.lookup("q-exactive hf").unwrap();
+        assert_eq!(model.accession, "MS:1002523");
+        assert_eq!(model.mass_analyzers, vec!["quadrupole", "orbitrap"]);
+        assert_eq!(db.by_accession("MS:1003005").unwrap().name, "timsTOF Pro");
+        assert!(db.lookup("unknown instrument").is_none());
+    }
+
+    #[test]
+    fn test_install_cv_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let obo = dir.path().join("full.obo");
+        std::fs::write(&obo, "data-version: 9.9\n[Term]\nid: MS:1\nname: one\n").unwrap();
+        let broken = dir.path().join("broken.tsv");
+        std::fs::write(&broken, "only-one-field\n").unwrap();
+        let target = dir.path().join("cv");
+
+        assert!(install_cv_files(None, Some(&broken), &target).is_err());
+        assert!(!target.join(store::INSTRUMENT_DB_FILE_NAME).exists());
+
+        let report = install_cv_files(Some(&obo), None, &target).unwrap();
+        assert_eq!(report.ontology_terms, Some(1));
+        assert_eq!(report.ontology_version.as_deref(), Some("9.9"));
+        assert!(target.join(store::PSI_MS_FILE_NAME).exists());
+    }
 }
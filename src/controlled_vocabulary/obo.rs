@@ -0,0 +1,169 @@
+//! Minimal OBO 1.2 parser
+//!
+//! Only `[Term]` stanzas are read; for each term the id, name, definition,
+//! `is_a` parents and obsolete flag are kept. Other stanzas (`[Typedef]`,
+//! `[Instance]`) and tags are skipped.
+
+use std::collections::HashMap;
+
+use super::{CvError, CvTerm};
+
+/// One ontology term
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OboTerm {
+    /// Accession (e.g. "MS:1000040")
+    pub id: String,
+    /// Term name
+    pub name: String,
+    /// Definition text without references
+    pub definition: Option<String>,
+    /// Accessions of direct `is_a` parents
+    pub parents: Vec<String>,
+    /// Whether the term is marked obsolete
+    pub is_obsolete: bool,
+}
+
+/// Parsed ontology indexed by accession
+#[derive(Debug, Clone, Default)]
+pub struct Ontology {
+    data_version: Option<String>,
+    terms: HashMap<String, OboTerm>,
+}
+
+impl Ontology {
+    /// Parse OBO text
+    pub fn parse(text: &str) -> Result<Self, CvError> {
+        let mut ontology = Ontology::default();
+        let mut current: Option<OboTerm> = None;
+        let mut in_header = true;
+
+        for (line_no, raw) in text.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with('!') {
+                continue;
+            }
+            if line.starts_with('[') {
+                ontology.finish_term(current.take(), line_no)?;
+                in_header = false;
+                if line == "[Term]" {
+                    current = Some(OboTerm {
+                        id: String::new(),
+                        name: String::new(),
+                        definition: None,
+                        parents: Vec::new(),
+                        is_obsolete: false,
+                    });
+                }
+                continue;
+            }
+
+            let Some((tag, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match current.as_mut() {
+                None => {
+                    if in_header && tag == "data-version" {
+                        ontology.data_version = Some(value.to_string());
+                    }
+                }
+                Some(term) => match tag {
+                    "id" => term.id = value.to_string(),
+                    "name" => term.name = value.to_string(),
+                    "def" => term.definition = quoted(value).map(str::to_string),
+                    "is_a" => {
+                        if let Some(parent) = value.split_whitespace().next() {
+                            term.parents.push(parent.to_string());
+                        }
+                    }
+                    "is_obsolete" => term.is_obsolete = value == "true",
+                    _ => {}
+                },
+            }
+        }
+        ontology.finish_term(current.take(), text.lines().count())?;
+
+        if ontology.terms.is_empty() {
+            return Err(CvError::ParseError("no [Term] stanzas found".to_string()));
+        }
+        Ok(ontology)
+    }
+
+    fn finish_term(&mut self, term: Option<OboTerm>, line_no: usize) -> Result<(), CvError> {
+        if let Some(term) = term {
+            if term.id.is_empty() {
+                return Err(CvError::ParseError(format!(
+                    "[Term] without id before line {}",
+                    line_no + 1
+                )));
+            }
+            self.terms.insert(term.id.clone(), term);
+        }
+        Ok(())
+    }
+
+    /// `data-version` header value
+    pub fn data_version(&self) -> Option<&str> {
+        self.data_version.as_deref()
+    }
+
+    /// Look up a term by accession
+    pub fn get(&self, accession: &str) -> Option<&OboTerm> {
+        self.terms.get(accession)
+    }
+
+    /// Name of a term
+    pub fn name(&self, accession: &str) -> Option<&str> {
+        self.get(accession).map(|t| t.name.as_str())
+    }
+
+    /// Build a [`CvTerm`] for an accession
+    pub fn cv_term(&self, accession: &str) -> Option<CvTerm> {
+        self.get(accession).map(|t| CvTerm::new(&t.id, &t.name))
+    }
+
+    /// Whether `accession` is `ancestor` or transitively derives from it via `is_a`
+    pub fn is_a(&self, accession: &str, ancestor: &str) -> bool {
+        let mut stack = vec![accession];
+        let mut visited = std::collections::HashSet::new();
+        while let Some(id) = stack.pop() {
+            if id == ancestor {
+                return true;
+            }
+            if visited.insert(id) {
+                if let Some(term) = self.get(id) {
+                    stack.extend(term.parents.iter().map(String::as_str));
+                }
+            }
+        }
+        false
+    }
+
+    /// All non-obsolete terms deriving from `ancestor` (excluding itself)
+    pub fn descendants(&self, ancestor: &str) -> Vec<&OboTerm> {
+        let mut terms: Vec<&OboTerm> = self
+            .terms
+            .values()
+            .filter(|t| !t.is_obsolete && t.id != ancestor && self.is_a(&t.id, ancestor))
+            .collect();
+        terms.sort_by(|a, b| a.id.cmp(&b.id));
+        terms
+    }
+
+    /// Number of terms
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Whether the ontology has no terms
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+}
+
+/// Text between the first pair of double quotes
+fn quoted(value: &str) -> Option<&str> {
+    let start = value.find('"')? + 1;
+    let end = start + value[start..].find('"')?;
+    Some(&value[start..end])
+}
@@ -0,0 +1,132 @@
+//! Embedded CV data with an on-disk override
+//!
+//! Trimmed copies of the PSI-MS ontology and the instrument model database
+//! are compiled into the binary so that offline instrument PCs can resolve
+//! accessions without network access. A full PSI-MS release can be
+//! installed into the override directory (see [`override_dir`]) with
+//! [`install_cv_files`] or `mzpeak cv update --obo psi-ms.obo`.
+//!
+//! Each source is parsed once on first use and memoized for the lifetime of the
+//! process; files installed later are picked up by the next process.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use super::instruments::InstrumentDb;
+use super::obo::Ontology;
+use super::CvError;
+
+/// Environment variable overriding the CV data directory
+pub const CV_DIR_ENV: &str = "MZPEAK_CV_DIR";
+
+/// File name of the PSI-MS ontology in the override directory
+pub const PSI_MS_FILE_NAME: &str = "psi-ms.obo";
+
+/// File name of the instrument database in the override directory
+pub const INSTRUMENT_DB_FILE_NAME: &str = "instruments.tsv";
+
+static EMBEDDED_PSI_MS: &str = include_str!("data/psi-ms.obo");
+static EMBEDDED_INSTRUMENT_DB: &str = include_str!("data/instruments.tsv");
+
+static PSI_MS: OnceLock<Ontology> = OnceLock::new();
+static INSTRUMENT_DB: OnceLock<InstrumentDb> = OnceLock::new();
+
+/// Directory searched for CV files overriding the embedded copies
+///
+/// `$MZPEAK_CV_DIR` if set, otherwise `~/.mzpeak/cv`.
+pub fn override_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(CV_DIR_ENV) {
+        return Some(PathBuf::from(dir));
+    }
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join(".mzpeak").join("cv"))
+}
+
+/// Process-wide PSI-MS ontology (override file if valid, else embedded copy)
+pub fn psi_ms() -> &'static Ontology {
+    PSI_MS.get_or_init(|| {
+        load_override(PSI_MS_FILE_NAME, Ontology::parse).unwrap_or_else(embedded_psi_ms)
+    })
+}
+
+/// Process-wide instrument model database (override file if valid, else embedded copy)
+pub fn instrument_db() -> &'static InstrumentDb {
+    INSTRUMENT_DB.get_or_init(|| {
+        load_override(INSTRUMENT_DB_FILE_NAME, InstrumentDb::parse)
+            .unwrap_or_else(embedded_instrument_db)
+    })
+}
+
+/// Parse the PSI-MS ontology compiled into the binary
+pub fn embedded_psi_ms() -> Ontology {
+    Ontology::parse(EMBEDDED_PSI_MS).expect("embedded psi-ms.obo is valid")
+}
+
+/// Parse the instrument database compiled into the binary
+pub fn embedded_instrument_db() -> InstrumentDb {
+    InstrumentDb::parse(EMBEDDED_INSTRUMENT_DB).expect("embedded instruments.tsv is valid")
+}
+
+/// Summary of an installed CV update
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CvUpdateReport {
+    /// Number of terms in the installed ontology
+    pub ontology_terms: Option<usize>,
+    /// `data-version` of the installed ontology
+    pub ontology_version: Option<String>,
+    /// Number of models in the installed instrument database
+    pub instrument_models: Option<usize>,
+}
+
+/// Validate and copy CV files into `dir`
+///
+/// Files are parsed before being installed so that a truncated download never
+/// replaces a working copy.
+pub fn install_cv_files(
+    obo: Option<&Path>,
+    instruments: Option<&Path>,
+    dir: &Path,
+) -> Result<CvUpdateReport, CvError> {
+    let mut report = CvUpdateReport::default();
+    let mut installs = Vec::new();
+
+    if let Some(path) = obo {
+        let text = std::fs::read_to_string(path)?;
+        let ontology = Ontology::parse(&text)?;
+        report.ontology_terms = Some(ontology.len());
+        report.ontology_version = ontology.data_version().map(str::to_string);
+        installs.push((PSI_MS_FILE_NAME, text));
+    }
+    if let Some(path) = instruments {
+        let text = std::fs::read_to_string(path)?;
+        report.instrument_models = Some(InstrumentDb::parse(&text)?.len());
+        installs.push((INSTRUMENT_DB_FILE_NAME, text));
+    }
+
+    std::fs::create_dir_all(dir)?;
+    for (name, text) in installs {
+        // Write next to the target and rename so readers never see a partial file
+        let target = dir.join(name);
+        let staging = dir.join(format!("{}.tmp", name));
+        std::fs::write(&staging, text)?;
+        std::fs::rename(&staging, &target)?;
+    }
+    Ok(report)
+}
+
+fn load_override<T>(file_name: &str, parse: fn(&str) -> Result<T, CvError>) -> Option<T> {
+    let path = override_dir()?.join(file_name);
+    let text = std::fs::read_to_string(&path).ok()?;
+    match parse(&text) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            log::warn!(
+                "Ignoring invalid CV override {}: {}; using embedded copy",
+                path.display(),
+                e
+            );
+            None
+        }
+    }
+}
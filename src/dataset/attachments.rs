@@ -0,0 +1,261 @@
+//! Container-level attachments
+//!
+//! Arbitrary files (method exports, instrument screenshots, scripts) can be
+//! stored next to the data under `attachments/`, so a run travels as a single
+//! artifact instead of a folder of strays. Every attachment is registered in
+//! `manifest.json` with its kind, media type, size and CRC-32.
+//!
+//! ZIP containers are rewritten into a temporary file next to the original
+//! and atomically renamed over it; existing entries are raw-copied without
+//! recompression. Directory bundles are edited in place.
+//!
+//! Attachments require a v2 dataset (one with a `manifest.json`).
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use mzpeak::dataset::{attach_file, detach_file, AttachOptions};
+//!
+//! let attachment = attach_file("run.mzpeak", "method.pdf", &AttachOptions::default())?;
+//! println!("attached {} ({:?})", attachment.name, attachment.kind);
+//!
+//! detach_file("run.mzpeak", "method.pdf")?;
+//! # Ok::<(), mzpeak::dataset::DatasetError>(())
+//! ```
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Seek, Write};
+use std::path::Path;
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::DatasetError;
+use crate::schema::manifest::{Attachment, AttachmentKind, Manifest, ATTACHMENTS_DIR};
+
+/// Name of the manifest entry
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Options for adding an attachment
+#[derive(Debug, Clone, Default)]
+pub struct AttachOptions {
+    /// Name inside the container (defaults to the file name)
+    pub name: Option<String>,
+    /// Attachment kind (guessed from the extension if not set)
+    pub kind: Option<AttachmentKind>,
+    /// Free-text description
+    pub description: Option<String>,
+}
+
+/// Attach a file to an existing dataset
+///
+/// Fails if an attachment with the same name already exists.
+pub fn attach_file<P: AsRef<Path>, Q: AsRef<Path>>(
+    dataset: P,
+    file: Q,
+    options: &AttachOptions,
+) -> Result<Attachment, DatasetError> {
+    let dataset = dataset.as_ref();
+    let file = file.as_ref();
+
+    let mut manifest = read_manifest(dataset)?;
+    let attachment = describe_attachment(file, options)?;
+    if manifest.attachments.iter().any(|a| a.name == attachment.name) {
+        return Err(DatasetError::AttachmentError(format!(
+            "'{}' is already attached; detach it first",
+            attachment.name
+        )));
+    }
+    manifest.attachments.push(attachment.clone());
+
+    if dataset.is_dir() {
+        let dir = dataset.join(ATTACHMENTS_DIR);
+        fs::create_dir_all(&dir)?;
+        fs::copy(file, dir.join(&attachment.name))?;
+        write_manifest_file(dataset, &manifest)?;
+    } else {
+        rewrite_container(dataset, &manifest, None, Some((&attachment, file)))?;
+    }
+
+    Ok(attachment)
+}
+
+/// Remove an attachment from a dataset, returning its registry entry
+pub fn detach_file<P: AsRef<Path>>(dataset: P, name: &str) -> Result<Attachment, DatasetError> {
+    let dataset = dataset.as_ref();
+
+    let mut manifest = read_manifest(dataset)?;
+    let index = manifest
+        .attachments
+        .iter()
+        .position(|a| a.name == name)
+        .ok_or_else(|| DatasetError::AttachmentError(format!("No attachment named '{}'", name)))?;
+    let attachment = manifest.attachments.remove(index);
+
+    if dataset.is_dir() {
+        let path = dataset.join(attachment.entry_path());
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        write_manifest_file(dataset, &manifest)?;
+    } else {
+        let entry_path = attachment.entry_path();
+        rewrite_container(dataset, &manifest, Some(&entry_path), None)?;
+    }
+
+    Ok(attachment)
+}
+
+/// Build the registry entry for a file on disk
+pub(super) fn describe_attachment(
+    file: &Path,
+    options: &AttachOptions,
+) -> Result<Attachment, DatasetError> {
+    let name = match &options.name {
+        Some(name) => name.clone(),
+        None => file
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(str::to_string)
+            .ok_or_else(|| {
+                DatasetError::AttachmentError(format!("Invalid file name: {}", file.display()))
+            })?,
+    };
+    validate_name(&name)?;
+
+    let extension = Path::new(&name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("");
+
+    let mut reader = BufReader::new(File::open(file)?);
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut size_bytes = 0u64;
+    loop {
+        let n = reader.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+        size_bytes += n as u64;
+    }
+
+    Ok(Attachment {
+        kind: options
+            .kind
+            .unwrap_or_else(|| AttachmentKind::from_extension(extension)),
+        media_type: AttachmentKind::media_type(extension).to_string(),
+        name,
+        size_bytes,
+        crc32: hasher.finalize(),
+        added: chrono::Utc::now().to_rfc3339(),
+        description: options.description.clone(),
+    })
+}
+
+/// Copy an attachment into a ZIP container
+pub(super) fn write_attachment_entry<W: Write + Seek>(
+    zip_writer: &mut ZipWriter<W>,
+    attachment: &Attachment,
+    file: &Path,
+) -> Result<(), DatasetError> {
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .unix_permissions(0o644);
+    zip_writer.start_file(attachment.entry_path(), options)?;
+    std::io::copy(&mut BufReader::new(File::open(file)?), zip_writer)?;
+    Ok(())
+}
+
+/// Attachment names must be plain file names
+fn validate_name(name: &str) -> Result<(), DatasetError> {
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        return Err(DatasetError::AttachmentError(format!(
+            "Invalid attachment name '{}': must be a plain file name",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Read the manifest of a directory bundle or ZIP container
+fn read_manifest(dataset: &Path) -> Result<Manifest, DatasetError> {
+    let missing = || {
+        DatasetError::AttachmentError(format!(
+            "{} has no manifest.json; attachments require a v2 dataset",
+            dataset.display()
+        ))
+    };
+
+    let content = if dataset.is_dir() {
+        let path = dataset.join(MANIFEST_FILE_NAME);
+        if !path.exists() {
+            return Err(missing());
+        }
+        fs::read_to_string(path)?
+    } else {
+        let mut archive = ZipArchive::new(BufReader::new(File::open(dataset)?))?;
+        let mut entry = archive.by_name(MANIFEST_FILE_NAME).map_err(|_| missing())?;
+        let mut content = String::new();
+        entry.read_to_string(&mut content)?;
+        content
+    };
+
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Write the manifest of a directory bundle
+fn write_manifest_file(dataset: &Path, manifest: &Manifest) -> Result<(), DatasetError> {
+    fs::write(
+        dataset.join(MANIFEST_FILE_NAME),
+        serde_json::to_string_pretty(manifest)?,
+    )?;
+    Ok(())
+}
+
+/// Rewrite a ZIP container with an updated manifest
+///
+/// `remove` names an entry to drop and `add` an attachment to append.
+fn rewrite_container(
+    path: &Path,
+    manifest: &Manifest,
+    remove: Option<&str>,
+    add: Option<(&Attachment, &Path)>,
+) -> Result<(), DatasetError> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let temp = tempfile::NamedTempFile::new_in(dir)?;
+    let mut zip_writer = ZipWriter::new(BufWriter::new(temp.as_file().try_clone()?));
+
+    let manifest_json = serde_json::to_string_pretty(manifest)?;
+    for i in 0..archive.len() {
+        let entry = archive.by_index_raw(i)?;
+        let name = entry.name().to_string();
+        if name == MANIFEST_FILE_NAME {
+            drop(entry);
+            let options = SimpleFileOptions::default()
+                .compression_method(CompressionMethod::Deflated)
+                .unix_permissions(0o644);
+            zip_writer.start_file(MANIFEST_FILE_NAME, options)?;
+            zip_writer.write_all(manifest_json.as_bytes())?;
+        } else if Some(name.as_str()) != remove {
+            zip_writer.raw_copy_file(entry)?;
+        }
+    }
+
+    if let Some((attachment, file)) = add {
+        write_attachment_entry(&mut zip_writer, attachment, file)?;
+    }
+
+    let mut inner = zip_writer.finish()?;
+    inner.flush()?;
+    drop(inner);
+
+    temp.persist(path).map_err(|e| DatasetError::IoError(e.error))?;
+    Ok(())
+}
@@ -0,0 +1,615 @@
+//! Dataset compaction
+//!
+//! Editing a dataset in place (attaching and detaching files, rewriting
+//! tables with small batches) leaves it with undersized row groups and stray
+//! entries. [`compact_dataset`] rewrites every Parquet table into row groups
+//! of a target size and drops orphaned entries:
+//!
+//! - files under `attachments/` that are not registered in the manifest
+//! - superseded generations of the manifest kept next to `manifest.json`
+//!   (`manifest.json.1`, `manifest.json.bak`, `manifest.2.json`)
+//! - leftover temporary files (`.tmp*`) from interrupted writes
+//!
+//! Registered attachments are never rewritten, even when they are Parquet
+//! files. Entries the compactor does not know about are kept verbatim, so
+//! data written by newer versions is never lost. A "Compaction" step is
+//! appended to the processing history in table footers and `metadata.json`.
+//!
+//! The rewritten tables keep the writer version, sorting columns, footer
+//! key-value metadata and, per column, the compression codec, encodings,
+//! dictionary use, statistics level (none, chunk or page) and bloom filters
+//! of the originals. Parquet does not record compression levels or page size
+//! limits, so ZSTD columns are recompressed at the default level and pages
+//! use the default size limits.
+//!
+//! Nothing is replaced until the whole result has been verified: every ZIP
+//! entry is read back against its CRC-32, every table must have the same
+//! number of rows, and every attachment must match its registered checksum.
+//! Directory bundles stage each rewritten file next to its original, and
+//! only move the staged files into place and delete orphans once all of
+//! them have passed.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use mzpeak::dataset::{compact_dataset, CompactOptions};
+//!
+//! let report = compact_dataset("run.mzpeak", &CompactOptions::default())?;
+//! println!("{} -> {} bytes", report.size_before, report.size_after);
+//! # Ok::<(), mzpeak::dataset::DatasetError>(())
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arrow::datatypes::Schema;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, Encoding};
+use parquet::file::metadata::{KeyValue, ParquetMetaData};
+use parquet::file::properties::{EnabledStatistics, WriterProperties, WriterVersion};
+use parquet::file::reader::{ChunkReader, FileReader, SerializedFileReader};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::paths::resolve_temp_dir;
+use super::DatasetError;
+use crate::metadata::{ProcessingHistory, ProcessingStep};
+use crate::reader::ZipEntryChunkReader;
+use crate::schema::manifest::{Manifest, ATTACHMENTS_DIR};
+use crate::schema::KEY_PROCESSING_HISTORY;
+
+/// Human-readable dataset metadata entry
+const METADATA_JSON: &str = "metadata.json";
+
+/// Dataset manifest entry
+const MANIFEST_JSON: &str = "manifest.json";
+
+/// Options for [`compact_dataset`]
+#[derive(Debug, Clone)]
+pub struct CompactOptions {
+    /// Target number of rows per row group
+    pub row_group_size: usize,
+}
+
+impl Default for CompactOptions {
+    fn default() -> Self {
+        Self {
+            row_group_size: crate::writer::WriterConfig::default().row_group_size,
+        }
+    }
+}
+
+/// Result of compacting one Parquet table
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableCompaction {
+    /// Path of the table inside the dataset
+    pub path: String,
+    /// Number of rows (unchanged by compaction)
+    pub rows: i64,
+    /// Row groups before compaction
+    pub row_groups_before: usize,
+    /// Row groups after compaction
+    pub row_groups_after: usize,
+    /// Size in bytes before compaction
+    pub bytes_before: u64,
+    /// Size in bytes after compaction
+    pub bytes_after: u64,
+}
+
+/// Summary of a compaction run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompactReport {
+    /// Rewritten tables
+    pub tables: Vec<TableCompaction>,
+    /// Orphaned entries that were removed
+    pub removed_entries: Vec<String>,
+    /// Number of entries whose checksums were verified
+    pub verified_entries: usize,
+    /// Total dataset size before compaction (bytes)
+    pub size_before: u64,
+    /// Total dataset size after compaction (bytes)
+    pub size_after: u64,
+}
+
+/// Compact a ZIP container or directory bundle in place
+pub fn compact_dataset<P: AsRef<Path>>(
+    path: P,
+    options: &CompactOptions,
+) -> Result<CompactReport, DatasetError> {
+    if options.row_group_size == 0 {
+        return Err(DatasetError::InvalidPath(
+            "row_group_size must be greater than zero".to_string(),
+        ));
+    }
+    let path = path.as_ref();
+    let step = ProcessingStep::mzpeak(
+        "Compaction",
+        HashMap::from([(
+            "row_group_size".to_string(),
+            options.row_group_size.to_string(),
+        )]),
+    );
+    if path.is_dir() {
+        compact_directory(path, options, &step)
+    } else if path.is_file() {
+        compact_container(path, options, &step)
+    } else {
+        Err(DatasetError::InvalidPath(format!(
+            "Dataset does not exist: {}",
+            path.display()
+        )))
+    }
+}
+
+/// Whether an entry should be dropped during compaction
+fn is_orphan(name: &str, registered: &[String]) -> bool {
+    let file_name = name.trim_end_matches('/').rsplit('/').next().unwrap_or(name);
+    if file_name.starts_with(".tmp") || is_superseded_manifest(name) {
+        return true;
+    }
+    match name.strip_prefix(ATTACHMENTS_DIR) {
+        Some(rest) if !rest.is_empty() && !name.ends_with('/') => {
+            !registered.iter().any(|r| r == rest)
+        }
+        _ => false,
+    }
+}
+
+/// Whether a root entry is an earlier generation of `manifest.json`
+fn is_superseded_manifest(name: &str) -> bool {
+    name != MANIFEST_JSON
+        && !name.contains('/')
+        && (name.starts_with("manifest.json.")
+            || (name.starts_with("manifest.") && name.ends_with(".json")))
+}
+
+/// Whether an entry is a table to rewrite (attachments are kept as they are)
+fn is_table(name: &str) -> bool {
+    name.ends_with(".parquet") && !name.starts_with(ATTACHMENTS_DIR)
+}
+
+fn registered_attachments(manifest: Option<&Manifest>) -> Vec<String> {
+    manifest
+        .map(|m| m.attachments.iter().map(|a| a.name.clone()).collect())
+        .unwrap_or_default()
+}
+
+fn compact_container(
+    path: &Path,
+    options: &CompactOptions,
+    step: &ProcessingStep,
+) -> Result<CompactReport, DatasetError> {
+    let mut report = CompactReport {
+        size_before: fs::metadata(path)?.len(),
+        ..Default::default()
+    };
+
+    let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+    let manifest = match archive.by_name(MANIFEST_JSON) {
+        Ok(mut entry) => {
+            let mut content = String::new();
+            entry.read_to_string(&mut content)?;
+            Some(serde_json::from_str::<Manifest>(&content)?)
+        }
+        Err(_) => None,
+    };
+    let registered = registered_attachments(manifest.as_ref());
+
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let temp = tempfile::NamedTempFile::new_in(dir)?;
+    let mut zip_writer = ZipWriter::new(BufWriter::new(temp.as_file().try_clone()?));
+    let stored = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .unix_permissions(0o644);
+
+    let mut expected_rows = HashMap::new();
+    for i in 0..archive.len() {
+        let entry = archive.by_index_raw(i)?;
+        let name = entry.name().to_string();
+
+        if is_orphan(&name, &registered) {
+            report.removed_entries.push(name);
+            continue;
+        }
+
+        if is_table(&name) && entry.compression() == CompressionMethod::Stored {
+            drop(entry);
+            let input = ZipEntryChunkReader::new(path, &name)
+                .map_err(|e| DatasetError::InvalidPath(e.to_string()))?;
+            let bytes_before = input.entry_size();
+            let mut table_file = tempfile::tempfile_in(resolve_temp_dir(None)?)?;
+            let (rows, before, after) =
+                rewrite_parquet(input, &mut table_file, options.row_group_size, step)?;
+            let bytes_after = table_file.metadata()?.len();
+
+            zip_writer.start_file(name.as_str(), stored)?;
+            io::copy(&mut BufReader::new(reopen(table_file)?), &mut zip_writer)?;
+
+            expected_rows.insert(name.clone(), rows);
+            report.tables.push(TableCompaction {
+                path: name,
+                rows,
+                row_groups_before: before,
+                row_groups_after: after,
+                bytes_before,
+                bytes_after,
+            });
+        } else if name == METADATA_JSON {
+            let method = entry.compression();
+            drop(entry);
+            let mut content = String::new();
+            archive.by_index(i)?.read_to_string(&mut content)?;
+            let entry_options = SimpleFileOptions::default()
+                .compression_method(method)
+                .unix_permissions(0o644);
+            zip_writer.start_file(name.as_str(), entry_options)?;
+            zip_writer.write_all(append_to_metadata_json(&content, step)?.as_bytes())?;
+        } else {
+            zip_writer.raw_copy_file(entry)?;
+        }
+    }
+
+    let mut inner = zip_writer.finish()?;
+    inner.flush()?;
+    drop(inner);
+
+    report.verified_entries = verify_container(temp.path(), manifest.as_ref(), &expected_rows)?;
+    temp.persist(path).map_err(|e| DatasetError::IoError(e.error))?;
+    report.size_after = fs::metadata(path)?.len();
+    Ok(report)
+}
+
+fn compact_directory(
+    path: &Path,
+    options: &CompactOptions,
+    step: &ProcessingStep,
+) -> Result<CompactReport, DatasetError> {
+    let manifest_path = path.join(MANIFEST_JSON);
+    let manifest = if manifest_path.exists() {
+        Some(serde_json::from_str::<Manifest>(&fs::read_to_string(&manifest_path)?)?)
+    } else {
+        None
+    };
+    let registered = registered_attachments(manifest.as_ref());
+
+    let mut files = Vec::new();
+    collect_files(path, &mut files)?;
+    files.sort();
+
+    let mut report = CompactReport::default();
+    // Rewritten files are staged next to their originals and orphans are only
+    // collected, so a failure anywhere leaves the dataset untouched
+    let mut staged = Vec::new();
+    let mut orphans = Vec::new();
+    for file in files {
+        let size = fs::metadata(&file)?.len();
+        report.size_before += size;
+        let name = relative_name(path, &file);
+
+        if is_orphan(&name, &registered) {
+            orphans.push((file, name));
+            continue;
+        }
+
+        if is_table(&name) {
+            let parent = file.parent().unwrap_or(path);
+            let mut temp = tempfile::NamedTempFile::new_in(parent)?;
+            let (rows, before, after) = rewrite_parquet(
+                File::open(&file)?,
+                temp.as_file_mut(),
+                options.row_group_size,
+                step,
+            )?;
+            check_rows(&name, rows, parquet_rows(File::open(temp.path())?)?)?;
+            report.verified_entries += 1;
+
+            report.tables.push(TableCompaction {
+                path: name,
+                rows,
+                row_groups_before: before,
+                row_groups_after: after,
+                bytes_before: size,
+                bytes_after: temp.as_file().metadata()?.len(),
+            });
+            staged.push((temp, file));
+        } else if name == METADATA_JSON {
+            let updated = append_to_metadata_json(&fs::read_to_string(&file)?, step)?;
+            let mut temp = tempfile::NamedTempFile::new_in(file.parent().unwrap_or(path))?;
+            temp.write_all(updated.as_bytes())?;
+            staged.push((temp, file));
+        }
+    }
+
+    if let Some(manifest) = &manifest {
+        for attachment in &manifest.attachments {
+            let mut file = File::open(path.join(attachment.entry_path()))?;
+            let crc32 = crc32_of(&mut file)?;
+            check_attachment_crc(&attachment.name, attachment.crc32, crc32)?;
+            report.verified_entries += 1;
+        }
+    }
+
+    for (temp, file) in staged {
+        temp.persist(&file).map_err(|e| DatasetError::IoError(e.error))?;
+    }
+    for (file, name) in orphans {
+        fs::remove_file(&file)?;
+        report.removed_entries.push(name);
+    }
+
+    let mut files = Vec::new();
+    collect_files(path, &mut files)?;
+    for file in files {
+        report.size_after += fs::metadata(file)?.len();
+    }
+    Ok(report)
+}
+
+/// Rewrite a Parquet file with a new row-group size
+///
+/// Returns `(rows, row_groups_before, row_groups_after)`.
+fn rewrite_parquet<R: ChunkReader + 'static, W: Write + Send>(
+    input: R,
+    output: W,
+    row_group_size: usize,
+    step: &ProcessingStep,
+) -> Result<(i64, usize, usize), DatasetError> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(input)?.with_batch_size(8192);
+    let metadata = builder.metadata().clone();
+    let props = writer_properties(&metadata, row_group_size, step)?;
+
+    // The reader merges the footer entries into the schema metadata; they are
+    // carried over by the writer properties, so keep them out of the embedded
+    // Arrow schema instead of storing each one twice
+    let footer_keys: HashSet<&str> = metadata
+        .file_metadata()
+        .key_value_metadata()
+        .map(|kv| kv.iter().map(|kv| kv.key.as_str()).collect())
+        .unwrap_or_default();
+    let schema_metadata = builder
+        .schema()
+        .metadata()
+        .iter()
+        .filter(|(key, _)| !footer_keys.contains(key.as_str()))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    let schema = Arc::new(Schema::new_with_metadata(
+        builder.schema().fields().clone(),
+        schema_metadata,
+    ));
+
+    let mut writer = ArrowWriter::try_new(output, schema, Some(props))?;
+    for batch in builder.build()? {
+        writer.write(&batch?)?;
+    }
+    let written = writer.close()?;
+
+    Ok((
+        metadata.file_metadata().num_rows(),
+        metadata.num_row_groups(),
+        written.row_groups.len(),
+    ))
+}
+
+/// Writer properties reproducing the column layout of an existing file
+///
+/// A processing history in the footer gets `step` appended.
+fn writer_properties(
+    metadata: &ParquetMetaData,
+    row_group_size: usize,
+    step: &ProcessingStep,
+) -> Result<WriterProperties, DatasetError> {
+    let mut builder = WriterProperties::builder().set_max_row_group_size(row_group_size);
+
+    let mut key_value_metadata: Vec<KeyValue> = metadata
+        .file_metadata()
+        .key_value_metadata()
+        .map(|kv| {
+            kv.iter()
+                .filter(|kv| kv.key != "ARROW:schema")
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default();
+    for kv in &mut key_value_metadata {
+        if let (KEY_PROCESSING_HISTORY, Some(json)) = (kv.key.as_str(), &kv.value) {
+            let mut history = ProcessingHistory::from_json(json)?;
+            history.append(step.clone());
+            kv.value = Some(history.to_json()?);
+        }
+    }
+    if !key_value_metadata.is_empty() {
+        builder = builder.set_key_value_metadata(Some(key_value_metadata));
+    }
+
+    if metadata.file_metadata().version() >= 2 {
+        builder = builder.set_writer_version(WriterVersion::PARQUET_2_0);
+    }
+    if let Some(sorting) = metadata.row_groups().first().and_then(|rg| rg.sorting_columns()) {
+        builder = builder.set_sorting_columns(Some(sorting.clone()));
+    }
+
+    // Settings are merged over all row groups: a column is dictionary
+    // encoded, has statistics or a bloom filter if any of its chunks does
+    for (i, column) in metadata.file_metadata().schema_descr().columns().iter().enumerate() {
+        let path = column.path().clone();
+        let chunks: Vec<_> = metadata.row_groups().iter().map(|rg| rg.column(i)).collect();
+        if chunks.is_empty() {
+            continue;
+        }
+
+        let compression = chunks
+            .iter()
+            .map(|chunk| chunk.compression())
+            .find(|compression| *compression != Compression::UNCOMPRESSED)
+            .unwrap_or(Compression::UNCOMPRESSED);
+        builder = builder.set_column_compression(path.clone(), compression);
+
+        let has_encoding = |encoding: Encoding| {
+            chunks
+                .iter()
+                .any(|chunk| chunk.encodings().contains(&encoding))
+        };
+        if has_encoding(Encoding::BYTE_STREAM_SPLIT) {
+            builder = builder
+                .set_column_dictionary_enabled(path.clone(), false)
+                .set_column_encoding(path.clone(), Encoding::BYTE_STREAM_SPLIT);
+        } else {
+            let dictionary =
+                has_encoding(Encoding::RLE_DICTIONARY) || has_encoding(Encoding::PLAIN_DICTIONARY);
+            builder = builder.set_column_dictionary_enabled(path.clone(), dictionary);
+        }
+
+        let statistics = if chunks.iter().any(|chunk| chunk.column_index_offset().is_some()) {
+            EnabledStatistics::Page
+        } else if chunks.iter().any(|chunk| chunk.statistics().is_some()) {
+            EnabledStatistics::Chunk
+        } else {
+            EnabledStatistics::None
+        };
+        builder = builder.set_column_statistics_enabled(path.clone(), statistics);
+
+        if chunks.iter().any(|chunk| chunk.bloom_filter_offset().is_some()) {
+            builder = builder.set_column_bloom_filter_enabled(path, true);
+        }
+    }
+
+    Ok(builder.build())
+}
+
+/// Append `step` to the processing history of a `metadata.json` document
+///
+/// Works on the raw JSON so every other field, including the header written
+/// by the original converter, is kept.
+fn append_to_metadata_json(json: &str, step: &ProcessingStep) -> Result<String, DatasetError> {
+    let mut document: serde_json::Value = serde_json::from_str(json)?;
+    let Some(fields) = document.as_object_mut() else {
+        return Ok(json.to_string());
+    };
+    let mut history = match fields.remove("processing_history") {
+        Some(value) if !value.is_null() => serde_json::from_value::<ProcessingHistory>(value)?,
+        _ => ProcessingHistory::new(),
+    };
+    history.append(step.clone());
+    fields.insert("processing_history".to_string(), serde_json::to_value(&history)?);
+    Ok(serde_json::to_string_pretty(&document)?)
+}
+
+/// Re-read every entry of a compacted container and check it
+///
+/// Entries are streamed, never buffered whole: tables can be several GB.
+fn verify_container(
+    path: &Path,
+    manifest: Option<&Manifest>,
+    expected_rows: &HashMap<String, i64>,
+) -> Result<usize, DatasetError> {
+    let mut archive = ZipArchive::new(BufReader::new(File::open(path)?))?;
+    let mut verified = 0;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        // The ZIP reader checks the CRC-32 once the entry is fully read
+        let crc32 = crc32_of(&mut entry)
+            .map_err(|e| DatasetError::VerificationFailed(format!("{}: {}", name, e)))?;
+        drop(entry);
+
+        if let Some(&rows) = expected_rows.get(&name) {
+            let input = ZipEntryChunkReader::new(path, &name)
+                .map_err(|e| DatasetError::VerificationFailed(format!("{}: {}", name, e)))?;
+            check_rows(&name, rows, parquet_rows(input)?)?;
+        } else if let Some(attachment) = name
+            .strip_prefix(ATTACHMENTS_DIR)
+            .and_then(|n| manifest?.attachments.iter().find(|a| a.name == n))
+        {
+            check_attachment_crc(&attachment.name, attachment.crc32, crc32)?;
+        }
+        verified += 1;
+    }
+
+    Ok(verified)
+}
+
+/// CRC-32 of everything `reader` yields, read in chunks
+fn crc32_of<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut hasher = Crc32Writer(crc32fast::Hasher::new());
+    io::copy(reader, &mut hasher)?;
+    Ok(hasher.0.finalize())
+}
+
+/// Sink that only hashes the bytes written to it
+struct Crc32Writer(crc32fast::Hasher);
+
+impl Write for Crc32Writer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn check_attachment_crc(name: &str, expected: u32, actual: u32) -> Result<(), DatasetError> {
+    if actual != expected {
+        return Err(DatasetError::VerificationFailed(format!(
+            "attachment '{}' does not match its registered CRC-32",
+            name
+        )));
+    }
+    Ok(())
+}
+
+fn check_rows(name: &str, expected: i64, written: i64) -> Result<(), DatasetError> {
+    if written != expected {
+        return Err(DatasetError::VerificationFailed(format!(
+            "{}: expected {} rows after compaction, found {}",
+            name, expected, written
+        )));
+    }
+    Ok(())
+}
+
+fn parquet_rows<R: ChunkReader + 'static>(input: R) -> Result<i64, DatasetError> {
+    let reader = SerializedFileReader::new(input)?;
+    Ok(reader.metadata().file_metadata().num_rows())
+}
+
+fn reopen(mut file: File) -> std::io::Result<File> {
+    use std::io::{Seek, SeekFrom};
+    file.seek(SeekFrom::Start(0))?;
+    Ok(file)
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Path relative to the dataset root with `/` separators
+fn relative_name(root: &Path, file: &Path) -> String {
+    file.strip_prefix(root)
+        .unwrap_or(file)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -19,6 +19,14 @@ pub enum DatasetError {
     #[error("JSON serialization error: {0}")]
     SerdeJsonError(#[from] serde_json::Error),
 
+    /// Error reading or writing a Parquet table
+    #[error("Parquet error: {0}")]
+    ParquetError(#[from] parquet::errors::ParquetError),
+
+    /// Error processing Arrow record batches
+    #[error("Arrow error: {0}")]
+    ArrowError(#[from] arrow::error::ArrowError),
+
     /// Error from the ZIP container library
     #[error("ZIP error: {0}")]
     ZipError(#[from] zip::result::ZipError),
@@ -31,14 +39,35 @@ pub enum DatasetError {
     #[error("Mobilogram writer error: {0}")]
     MobilogramWriterError(String),
 
+    /// Error from the scan diagnostics writer
+    #[error("Scan diagnostics writer error: {0}")]
+    ScanDiagnosticsWriterError(String),
+
     /// Invalid or malformed dataset path
     #[error("Invalid dataset path: {0}")]
     InvalidPath(String),
 
+    /// Destination directory cannot be written (read-only or missing permissions)
+    #[error("Cannot write to {path}: {reason}")]
+    DestinationNotWritable {
+        /// Directory that failed the write probe
+        path: String,
+        /// Short cause, e.g. "read-only filesystem"
+        reason: String,
+    },
+
     /// Dataset already exists at the specified location
     #[error("Dataset already exists: {0}")]
     AlreadyExists(String),
 
+    /// A rewritten dataset failed its post-write checks
+    #[error("Verification failed: {0}")]
+    VerificationFailed(String),
+
+    /// Attachment could not be added, found or removed
+    #[error("Attachment error: {0}")]
+    AttachmentError(String),
+
     /// Dataset was not properly initialized before use
     #[error("Dataset not properly initialized")]
     NotInitialized,
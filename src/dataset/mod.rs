@@ -25,7 +25,8 @@
 //! ├── manifest.json               # Schema version and modality declaration
 //! ├── metadata.json               # Human-readable metadata
 //! ├── spectra/spectra.parquet     # Spectrum-level metadata (one row per spectrum)
-//! └── peaks/peaks.parquet         # Peak-level data (one row per peak)
+//! ├── peaks/peaks.parquet         # Peak-level data (one row per peak)
+//! └── attachments/                # Optional user files (method PDFs, screenshots, ...)
 //! ```
 //!
 //! ## Performance Notes
@@ -34,6 +35,20 @@
 //! 1. Parquet files already handle their own internal compression (ZSTD/Snappy)
 //! 2. Storing uncompressed allows readers to seek directly to byte offsets
 //!
+//! ## Paths and Temp Files
+//!
+//! On Windows, output paths are converted to extended-length form so deep
+//! directory-mode datasets and UNC shares are not limited by `MAX_PATH`.
+//! Tables are staged in `$MZPEAK_TMPDIR` (or the system temp directory)
+//! unless [`WriterConfig::temp_dir`](crate::writer::WriterConfig::temp_dir) /
+//! [`DatasetWriterV2Config::temp_dir`] point elsewhere.
+//!
+//! Writers probe the destination when created and fail with
+//! [`DatasetError::DestinationNotWritable`] on read-only filesystems. With
+//! `stage_locally` set, datasets are written to the temp directory and
+//! atomically moved to the destination on close, which keeps unreliable
+//! network shares out of the write path.
+//!
 //! ## Usage (v2.0 - recommended)
 //!
 //! ```rust,ignore
@@ -68,16 +83,26 @@
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 
+mod attachments;
+mod compact;
 mod error;
+mod paths;
+mod staging;
 mod stats;
+mod table_worker;
 mod types;
 mod writer_impl;
 mod writer_v2;
+mod zip_pipeline;
 
 #[cfg(test)]
 mod tests;
 
+pub use attachments::{attach_file, detach_file, AttachOptions};
+pub use compact::{compact_dataset, CompactOptions, CompactReport, TableCompaction};
 pub use error::DatasetError;
+pub use paths::{default_temp_dir, normalize_path, TMPDIR_ENV};
+pub use staging::{check_destination, detect_network_filesystem};
 pub use stats::DatasetStats;
 pub use types::OutputMode;
 pub use writer_impl::MzPeakDatasetWriter;
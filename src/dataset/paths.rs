@@ -0,0 +1,178 @@
+//! Output path normalization and temp file placement
+//!
+//! Directory-mode datasets nest several levels below the output path, which
+//! easily exceeds the 260-character `MAX_PATH` limit of Win32 APIs on deep
+//! instrument share paths. On Windows, writers therefore rewrite their output
+//! path to the extended-length form (`\\?\C:\...`, `\\?\UNC\server\share\...`)
+//! before touching the filesystem. Other platforms use paths unchanged.
+//!
+//! Container writers stage Parquet tables in temp files before packaging them.
+//! Instrument PCs often have a small system drive, so the staging directory
+//! can be moved with [`TMPDIR_ENV`] or per writer via
+//! [`WriterConfig::temp_dir`](crate::writer::WriterConfig::temp_dir).
+
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding the directory for staging temp files
+pub const TMPDIR_ENV: &str = "MZPEAK_TMPDIR";
+
+/// Directory for staging temp files.
+///
+/// `$MZPEAK_TMPDIR` if set, otherwise the system temp directory.
+pub fn default_temp_dir() -> PathBuf {
+    std::env::var_os(TMPDIR_ENV)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+}
+
+/// Resolve the staging directory for a writer, creating it if needed.
+pub(crate) fn resolve_temp_dir(configured: Option<&Path>) -> std::io::Result<PathBuf> {
+    let dir = configured
+        .map(Path::to_path_buf)
+        .unwrap_or_else(default_temp_dir);
+    let dir = normalize_path(&dir);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Normalize an output path for the current platform.
+///
+/// On Windows the path is made absolute, `.`/`..` components are resolved and
+/// the result is converted to its extended-length form, lifting the `MAX_PATH`
+/// limit. Paths that already use a `\\?\` or `\\.\` prefix, or that cannot be
+/// represented as UTF-8, are only made absolute. Elsewhere this is a no-op.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        let absolute = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            match std::env::current_dir() {
+                Ok(cwd) => cwd.join(path),
+                Err(_) => return path.to_path_buf(),
+            }
+        };
+        absolute
+            .to_str()
+            .and_then(extended_length_path)
+            .map(PathBuf::from)
+            .unwrap_or(absolute)
+    }
+    #[cfg(not(windows))]
+    {
+        path.to_path_buf()
+    }
+}
+
+/// Extended-length form of an absolute Windows path.
+///
+/// Returns `None` for relative, drive-relative and already prefixed paths.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn extended_length_path(path: &str) -> Option<String> {
+    if path.starts_with(r"\\?\") || path.starts_with(r"\\.\") {
+        return None;
+    }
+    // Verbatim paths bypass Win32 normalization, so separators must be backslashes
+    let path = path.replace('/', "\\");
+
+    let (mut normalized, rest) = if let Some(unc) = path.strip_prefix(r"\\") {
+        let mut parts = unc.splitn(3, '\\');
+        let server = parts.next().filter(|part| !part.is_empty())?;
+        let share = parts.next().filter(|part| !part.is_empty())?;
+        (
+            format!(r"\\?\UNC\{}\{}", server, share),
+            parts.next().unwrap_or(""),
+        )
+    } else {
+        let bytes = path.as_bytes();
+        let is_drive_absolute = bytes.len() >= 3
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && bytes[2] == b'\\';
+        if !is_drive_absolute {
+            return None;
+        }
+        (format!(r"\\?\{}", &path[..2]), &path[3..])
+    };
+
+    let mut components: Vec<&str> = Vec::new();
+    for component in rest.split('\\') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+
+    if components.is_empty() && !normalized.contains(r"\UNC\") {
+        // Drive root: `\\?\C:` alone would name the drive, not its root
+        normalized.push('\\');
+    }
+    for component in components {
+        normalized.push('\\');
+        normalized.push_str(component);
+    }
+    Some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extended_length_drive_paths() {
+        assert_eq!(
+            extended_length_path(r"C:\data\run.mzpeak").as_deref(),
+            Some(r"\\?\C:\data\run.mzpeak")
+        );
+        assert_eq!(
+            extended_length_path("D:/instrument/./batch/../run.mzpeak").as_deref(),
+            Some(r"\\?\D:\instrument\run.mzpeak")
+        );
+        assert_eq!(extended_length_path(r"C:\").as_deref(), Some(r"\\?\C:\"));
+
+        let deep = format!(r"C:\{}\peaks\peaks.parquet", "a".repeat(300));
+        let extended = extended_length_path(&deep).unwrap();
+        assert!(extended.starts_with(r"\\?\C:\aaa"));
+        assert!(extended.ends_with(r"\peaks\peaks.parquet"));
+    }
+
+    #[test]
+    fn test_extended_length_unc_paths() {
+        assert_eq!(
+            extended_length_path(r"\\nas\ms-data\2024\run.mzpeak").as_deref(),
+            Some(r"\\?\UNC\nas\ms-data\2024\run.mzpeak")
+        );
+        assert_eq!(
+            extended_length_path(r"\\nas\ms-data").as_deref(),
+            Some(r"\\?\UNC\nas\ms-data")
+        );
+        assert_eq!(extended_length_path(r"\\nas"), None);
+    }
+
+    #[test]
+    fn test_extended_length_passthrough() {
+        assert_eq!(extended_length_path(r"\\?\C:\data"), None);
+        assert_eq!(extended_length_path(r"\\.\pipe\name"), None);
+        assert_eq!(extended_length_path(r"data\run.mzpeak"), None);
+        assert_eq!(extended_length_path("C:run.mzpeak"), None);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_normalize_path_is_noop() {
+        let path = Path::new("out/./run.mzpeak");
+        assert_eq!(normalize_path(path), path);
+    }
+
+    #[test]
+    fn test_resolve_temp_dir_creates_configured_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("scratch").join("mzpeak");
+        let resolved = resolve_temp_dir(Some(&dir)).unwrap();
+        assert!(resolved.is_dir());
+    }
+}
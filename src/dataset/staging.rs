@@ -0,0 +1,325 @@
+//! Destination checks and local staging for read-only and network filesystems
+//!
+//! A destination that cannot be written used to surface as a Parquet or ZIP
+//! error once the first buffers were flushed, possibly hours into a
+//! conversion. Writers now probe the destination directory when they are
+//! created and fail with [`DatasetError::DestinationNotWritable`].
+//!
+//! Network shares that drop connections mid-write are handled by staging:
+//! with `stage_locally` enabled the dataset is written to the local temp
+//! directory and copied to its destination on close. The copy is written
+//! under a hidden temporary name next to the destination and renamed into
+//! place, so readers never observe a partial dataset.
+
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use tempfile::TempDir;
+
+use super::error::DatasetError;
+use super::paths::resolve_temp_dir;
+
+/// Filesystem types treated as network filesystems
+const NETWORK_FS_TYPES: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "9p",
+    "afs",
+    "ceph",
+    "glusterfs",
+    "lustre",
+    "gpfs",
+    "beegfs",
+    "davfs",
+    "fuse.sshfs",
+    "fuse.rclone",
+    "fuse.s3fs",
+];
+
+/// Check that a dataset can be created at `path`.
+///
+/// Creates the parent directory if needed and writes a probe file into it.
+pub fn check_destination(path: &Path) -> Result<(), DatasetError> {
+    let parent = destination_dir(path);
+    fs::create_dir_all(parent).map_err(|e| not_writable(parent, e))?;
+    tempfile::Builder::new()
+        .prefix(".mzpeak-probe")
+        .tempfile_in(parent)
+        .map_err(|e| not_writable(parent, e))?;
+    Ok(())
+}
+
+/// Check the destination and decide where a writer creates its output.
+///
+/// Returns the staging location when `stage_locally` is set; otherwise the
+/// writer writes to `destination` directly.
+pub(crate) fn prepare_output(
+    destination: &Path,
+    temp_dir: Option<&Path>,
+    stage_locally: bool,
+) -> Result<Option<StagedOutput>, DatasetError> {
+    check_destination(destination)?;
+    if stage_locally {
+        let temp_dir = resolve_temp_dir(temp_dir)?;
+        return Ok(Some(StagedOutput::new(destination, &temp_dir)?));
+    }
+    if let Some(fs_type) = detect_network_filesystem(destination) {
+        log::warn!(
+            "{} is on a network filesystem ({}); consider stage_locally to write locally and copy on close",
+            destination.display(),
+            fs_type
+        );
+    }
+    Ok(None)
+}
+
+/// Filesystem type of `path` if it lives on a network filesystem.
+///
+/// Detection is best effort: UNC paths on Windows and the mount table on
+/// Linux. Returns `None` when the filesystem is local or unknown.
+pub fn detect_network_filesystem(path: &Path) -> Option<String> {
+    #[cfg(windows)]
+    {
+        let path = path.to_string_lossy();
+        if path.starts_with(r"\\?\UNC\") || (path.starts_with(r"\\") && !path.starts_with(r"\\?\"))
+        {
+            return Some("UNC share".to_string());
+        }
+        None
+    }
+    #[cfg(target_os = "linux")]
+    {
+        let dir = fs::canonicalize(destination_dir(path)).ok()?;
+        let mounts = fs::read_to_string("/proc/self/mounts").ok()?;
+        network_fs_type(&mounts, &dir).map(str::to_string)
+    }
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+/// Network filesystem type of the mount containing `path`, from a
+/// `/proc/mounts`-formatted table.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn network_fs_type<'a>(mounts: &'a str, path: &Path) -> Option<&'a str> {
+    let mut best: Option<(PathBuf, &str)> = None;
+    for line in mounts.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(_source), Some(mount_point), Some(fs_type)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let mount_point = PathBuf::from(mount_point.replace("\\040", " "));
+        let longer = match best.as_ref() {
+            Some((current, _)) => mount_point.as_os_str().len() >= current.as_os_str().len(),
+            None => true,
+        };
+        if path.starts_with(&mount_point) && longer {
+            best = Some((mount_point, fs_type));
+        }
+    }
+    best.map(|(_, fs_type)| fs_type)
+        .filter(|fs_type| NETWORK_FS_TYPES.contains(fs_type))
+}
+
+/// Dataset written to a local staging directory and moved to its
+/// destination on [`StagedOutput::commit`].
+pub(crate) struct StagedOutput {
+    /// Removed (with any leftovers) when the output is dropped
+    staging_dir: TempDir,
+    staged_path: PathBuf,
+    destination: PathBuf,
+}
+
+impl StagedOutput {
+    /// Reserve a staging location in `temp_dir` for `destination`.
+    pub(crate) fn new(destination: &Path, temp_dir: &Path) -> io::Result<Self> {
+        let staging_dir = tempfile::Builder::new()
+            .prefix(".mzpeak-stage")
+            .tempdir_in(temp_dir)?;
+        let name = destination
+            .file_name()
+            .unwrap_or_else(|| "dataset".as_ref());
+        let staged_path = staging_dir.path().join(name);
+        Ok(Self {
+            staging_dir,
+            staged_path,
+            destination: destination.to_path_buf(),
+        })
+    }
+
+    /// Path the writer should write to.
+    pub(crate) fn path(&self) -> &Path {
+        &self.staged_path
+    }
+
+    /// Copy the staged dataset next to its destination and rename it into place.
+    pub(crate) fn commit(self) -> Result<(), DatasetError> {
+        if self.destination.exists() {
+            return Err(DatasetError::AlreadyExists(
+                self.destination.to_string_lossy().to_string(),
+            ));
+        }
+        let parent = destination_dir(&self.destination);
+        let builder = {
+            let mut builder = tempfile::Builder::new();
+            builder.prefix(".mzpeak-partial");
+            builder
+        };
+
+        if self.staged_path.is_dir() {
+            let partial = builder
+                .tempdir_in(parent)
+                .map_err(|e| not_writable(parent, e))?;
+            copy_dir(&self.staged_path, partial.path())?;
+            fs::rename(partial.path(), &self.destination)?;
+        } else {
+            let mut partial = builder
+                .tempfile_in(parent)
+                .map_err(|e| not_writable(parent, e))?;
+            io::copy(&mut File::open(&self.staged_path)?, partial.as_file_mut())?;
+            partial.as_file().sync_all()?;
+            partial
+                .persist_noclobber(&self.destination)
+                .map_err(|e| match e.error.kind() {
+                    io::ErrorKind::AlreadyExists => {
+                        DatasetError::AlreadyExists(self.destination.to_string_lossy().to_string())
+                    }
+                    _ => DatasetError::IoError(e.error),
+                })?;
+        }
+
+        drop(self.staging_dir);
+        Ok(())
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            fs::create_dir(&target)?;
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+fn destination_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+fn not_writable(path: &Path, error: io::Error) -> DatasetError {
+    let reason = if is_read_only_error(&error) {
+        "read-only filesystem".to_string()
+    } else if error.kind() == io::ErrorKind::PermissionDenied {
+        "permission denied".to_string()
+    } else {
+        error.to_string()
+    };
+    DatasetError::DestinationNotWritable {
+        path: path.to_string_lossy().to_string(),
+        reason,
+    }
+}
+
+fn is_read_only_error(error: &io::Error) -> bool {
+    // EROFS on Linux and macOS, ERROR_WRITE_PROTECT on Windows
+    #[cfg(unix)]
+    const READ_ONLY: i32 = 30;
+    #[cfg(windows)]
+    const READ_ONLY: i32 = 19;
+    #[cfg(not(any(unix, windows)))]
+    const READ_ONLY: i32 = -1;
+    error.raw_os_error() == Some(READ_ONLY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTS: &str = "\
+/dev/sda1 / ext4 rw,relatime 0 0
+nas:/export/ms /mnt/ms nfs4 rw,relatime 0 0
+//nas/raw\\040data /mnt/raw\\040data cifs rw 0 0
+/dev/sdb1 /mnt/ms/local ext4 rw 0 0
+";
+
+    #[test]
+    fn test_network_fs_type_longest_mount_wins() {
+        assert_eq!(
+            network_fs_type(MOUNTS, Path::new("/mnt/ms/run1")),
+            Some("nfs4")
+        );
+        assert_eq!(
+            network_fs_type(MOUNTS, Path::new("/mnt/ms/local/run1")),
+            None
+        );
+        assert_eq!(
+            network_fs_type(MOUNTS, Path::new("/mnt/raw data/x")),
+            Some("cifs")
+        );
+        assert_eq!(network_fs_type(MOUNTS, Path::new("/home/user")), None);
+        // Mount points match whole components only
+        assert_eq!(network_fs_type(MOUNTS, Path::new("/mnt/msx")), None);
+    }
+
+    #[test]
+    fn test_read_only_error_is_reported() {
+        #[cfg(unix)]
+        let error = io::Error::from_raw_os_error(30);
+        #[cfg(windows)]
+        let error = io::Error::from_raw_os_error(19);
+        match not_writable(Path::new("/data"), error) {
+            DatasetError::DestinationNotWritable { path, reason } => {
+                assert_eq!(path, "/data");
+                assert_eq!(reason, "read-only filesystem");
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+    }
+
+    #[test]
+    fn test_staged_file_commit() {
+        let root = tempfile::tempdir().unwrap();
+        let destination = root.path().join("out").join("run.mzpeak");
+        fs::create_dir(root.path().join("out")).unwrap();
+
+        let staged = StagedOutput::new(&destination, root.path()).unwrap();
+        fs::write(staged.path(), b"dataset").unwrap();
+        assert!(!destination.exists());
+        staged.commit().unwrap();
+
+        assert_eq!(fs::read(&destination).unwrap(), b"dataset");
+        // Only the committed file remains next to the destination
+        assert_eq!(fs::read_dir(root.path().join("out")).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_staged_commit_refuses_existing_destination() {
+        let root = tempfile::tempdir().unwrap();
+        let destination = root.path().join("run.mzpeak");
+        fs::write(&destination, b"existing").unwrap();
+
+        let staged = StagedOutput::new(&destination, root.path()).unwrap();
+        fs::write(staged.path(), b"dataset").unwrap();
+        assert!(matches!(
+            staged.commit(),
+            Err(DatasetError::AlreadyExists(_))
+        ));
+        assert_eq!(fs::read(&destination).unwrap(), b"existing");
+    }
+}
@@ -1,11 +1,14 @@
 use std::fmt;
 
+use serde::{Deserialize, Serialize};
+
 use crate::chromatogram_writer::ChromatogramWriterStats;
 use crate::mobilogram_writer::MobilogramWriterStats;
 use crate::writer::WriterStats;
 
 /// Statistics from a completed dataset write operation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DatasetStats {
     /// Statistics from the peak writer
     pub peak_stats: WriterStats,
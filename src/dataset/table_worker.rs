@@ -0,0 +1,175 @@
+//! Table writers on worker threads
+//!
+//! Each table of a v2 container has its own Parquet writer. A threaded
+//! [`TableWriter`] moves that writer onto a dedicated thread fed through a
+//! bounded queue, so encoding one table (the small spectra table) never
+//! stalls the other (the much larger peaks stream). Rows are handed off in
+//! batches to keep channel traffic low; the queue bound caps the memory held
+//! by rows waiting to be encoded.
+
+use std::thread::JoinHandle;
+
+use crossbeam_channel::{bounded, Sender};
+
+use super::error::DatasetError;
+
+/// Rows collected before a batch is handed to the worker
+const TABLE_BATCH_SIZE: usize = 64;
+
+/// Number of batches that may be queued before the producer blocks
+const TABLE_QUEUE_CAPACITY: usize = 8;
+
+/// Encodes one row into a table writer
+pub(crate) type WriteRow<T, W> = fn(&mut W, T) -> Result<(), DatasetError>;
+
+/// Writer of one table, either on the calling thread or on a worker
+pub(crate) enum TableWriter<T, W> {
+    /// Rows are encoded on the calling thread
+    Inline {
+        /// Table writer
+        writer: W,
+        /// Row encoder
+        write: WriteRow<T, W>,
+    },
+    /// Rows are encoded on a dedicated thread
+    Threaded(TableWorker<T, W>),
+}
+
+/// Producer side of a table writer running on its own thread
+pub(crate) struct TableWorker<T, W> {
+    sender: Option<Sender<Vec<T>>>,
+    handle: Option<JoinHandle<Result<W, DatasetError>>>,
+    batch: Vec<T>,
+}
+
+impl<T: Send + 'static, W: Send + 'static> TableWriter<T, W> {
+    /// Encode rows on the calling thread
+    pub(crate) fn inline(writer: W, write: WriteRow<T, W>) -> Self {
+        Self::Inline { writer, write }
+    }
+
+    /// Move the writer onto a thread named `name`
+    pub(crate) fn spawn(name: &str, mut writer: W, write: WriteRow<T, W>) -> std::io::Result<Self> {
+        let (sender, receiver) = bounded::<Vec<T>>(TABLE_QUEUE_CAPACITY);
+        let handle = std::thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || {
+                for batch in receiver {
+                    for row in batch {
+                        write(&mut writer, row)?;
+                    }
+                }
+                Ok(writer)
+            })?;
+        Ok(Self::Threaded(TableWorker {
+            sender: Some(sender),
+            handle: Some(handle),
+            batch: Vec::with_capacity(TABLE_BATCH_SIZE),
+        }))
+    }
+
+    /// Write one row
+    ///
+    /// A threaded writer reports an encoding error on the first write after
+    /// the worker stopped.
+    pub(crate) fn write(&mut self, row: T) -> Result<(), DatasetError> {
+        match self {
+            Self::Inline { writer, write } => write(writer, row),
+            Self::Threaded(worker) => {
+                worker.batch.push(row);
+                if worker.batch.len() >= TABLE_BATCH_SIZE {
+                    worker.send_batch()?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Write the remaining rows and take the writer back
+    pub(crate) fn finish(self) -> Result<W, DatasetError> {
+        match self {
+            Self::Inline { writer, .. } => Ok(writer),
+            Self::Threaded(mut worker) => {
+                let sent = worker.send_batch();
+                worker.sender = None;
+                // A send error only means the worker stopped; report its error first
+                let writer = worker.join()?;
+                sent?;
+                Ok(writer)
+            }
+        }
+    }
+}
+
+impl<T, W> TableWorker<T, W> {
+    fn send_batch(&mut self) -> Result<(), DatasetError> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+        let batch = std::mem::replace(&mut self.batch, Vec::with_capacity(TABLE_BATCH_SIZE));
+        let sender = self.sender.as_ref().ok_or(DatasetError::NotInitialized)?;
+        if sender.send(batch).is_ok() {
+            return Ok(());
+        }
+        self.sender = None;
+        match self.join() {
+            Err(e) => Err(e),
+            Ok(_) => Err(DatasetError::NotInitialized),
+        }
+    }
+
+    fn join(&mut self) -> Result<W, DatasetError> {
+        let handle = self.handle.take().ok_or(DatasetError::NotInitialized)?;
+        handle.join().map_err(|_| {
+            DatasetError::IoError(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "table writer thread panicked",
+            ))
+        })?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push(rows: &mut Vec<u32>, row: u32) -> Result<(), DatasetError> {
+        if row == 1000 {
+            return Err(DatasetError::InvalidPath("row 1000".to_string()));
+        }
+        rows.push(row);
+        Ok(())
+    }
+
+    #[test]
+    fn test_threaded_writer_keeps_row_order() -> Result<(), DatasetError> {
+        let mut writer = TableWriter::spawn("test-table", Vec::new(), push)?;
+        for row in 0..500 {
+            writer.write(row)?;
+        }
+        let rows = writer.finish()?;
+        assert_eq!(rows, (0..500).collect::<Vec<_>>());
+        Ok(())
+    }
+
+    #[test]
+    fn test_threaded_writer_reports_worker_error() -> Result<(), DatasetError> {
+        let mut writer = TableWriter::spawn("test-table", Vec::new(), push)?;
+        let mut result = Ok(());
+        for row in 990..2000 {
+            result = writer.write(row);
+            if result.is_err() {
+                break;
+            }
+        }
+        let error = match result {
+            Err(e) => e,
+            Ok(()) => match writer.finish() {
+                Err(e) => e,
+                Ok(_) => return Err(DatasetError::NotInitialized),
+            },
+        };
+        assert!(error.to_string().contains("row 1000"), "{}", error);
+        Ok(())
+    }
+}
@@ -136,8 +136,105 @@ fn test_directory_mode_peaks_file_created() {
     assert!(peaks_file.metadata().unwrap().len() > 0);
 }
 
+#[test]
+fn test_directory_mode_deep_path() {
+    let dir = tempdir().unwrap();
+    // Deeper than Win32 MAX_PATH once the table paths are appended
+    let mut dataset_path = dir.path().to_path_buf();
+    for i in 0..6 {
+        dataset_path.push(format!("{}_{}", "instrument_share_level", i).repeat(2));
+    }
+    dataset_path.push("run_dir");
+    assert!(dataset_path.as_os_str().len() > 260);
+
+    let metadata = MzPeakMetadata::new();
+    let mut dataset =
+        MzPeakDatasetWriter::new_directory(&dataset_path, &metadata, WriterConfig::default())
+            .unwrap();
+    dataset
+        .write_spectrum_arrays(&make_ms1_spectrum(0, 1, 60.0, &[(400.0, 10000.0)]))
+        .unwrap();
+    dataset.close().unwrap();
+
+    assert!(dataset_path.join("peaks").join("peaks.parquet").exists());
+}
+
 // ==================== Container Mode Tests ====================
 
+#[test]
+fn test_container_mode_configured_temp_dir() {
+    let dir = tempdir().unwrap();
+    let temp_dir = dir.path().join("staging").join("tmp");
+    let dataset_path = dir.path().join("staged.mzpeak");
+
+    let config = WriterConfig {
+        temp_dir: Some(temp_dir.clone()),
+        ..Default::default()
+    };
+    let mut dataset =
+        MzPeakDatasetWriter::new_container(&dataset_path, &MzPeakMetadata::new(), config).unwrap();
+    // Tables are staged in the configured directory until close
+    assert!(fs::read_dir(&temp_dir).unwrap().count() >= 3);
+
+    dataset
+        .write_spectrum_arrays(&make_ms1_spectrum(0, 1, 60.0, &[(400.0, 10000.0)]))
+        .unwrap();
+    dataset.close().unwrap();
+
+    assert!(dataset_path.is_file());
+    assert_eq!(fs::read_dir(&temp_dir).unwrap().count(), 0);
+}
+
+#[test]
+fn test_stage_locally_moves_dataset_on_close() {
+    let dir = tempdir().unwrap();
+    let temp_dir = dir.path().join("local");
+    let share = dir.path().join("share");
+    let config = WriterConfig {
+        temp_dir: Some(temp_dir.clone()),
+        stage_locally: true,
+        ..Default::default()
+    };
+
+    for dataset_path in [share.join("staged.mzpeak"), share.join("staged_dir")] {
+        let mut dataset =
+            MzPeakDatasetWriter::new(&dataset_path, &MzPeakMetadata::new(), config.clone())
+                .unwrap();
+        dataset
+            .write_spectrum_arrays(&make_ms1_spectrum(0, 1, 60.0, &[(400.0, 10000.0)]))
+            .unwrap();
+        // Nothing appears at the destination until the dataset is complete
+        assert!(!dataset_path.exists());
+
+        let stats = dataset.close().unwrap();
+        assert!(dataset_path.exists());
+        assert!(stats.total_size_bytes > 0);
+    }
+
+    // No partial copies next to the outputs, no leftovers in the staging area
+    assert_eq!(fs::read_dir(&share).unwrap().count(), 2);
+    assert_eq!(fs::read_dir(&temp_dir).unwrap().count(), 0);
+    assert!(share.join("staged_dir").join("peaks").join("peaks.parquet").exists());
+}
+
+#[test]
+fn test_destination_not_writable() {
+    let dir = tempdir().unwrap();
+    // A file where the parent directory should be
+    let blocker = dir.path().join("blocker");
+    fs::write(&blocker, b"").unwrap();
+
+    let result = MzPeakDatasetWriter::new_container(
+        blocker.join("run.mzpeak"),
+        &MzPeakMetadata::new(),
+        WriterConfig::default(),
+    );
+    assert!(matches!(
+        result,
+        Err(DatasetError::DestinationNotWritable { .. })
+    ));
+}
+
 #[test]
 fn test_container_mode_creation() {
     let dir = tempdir().unwrap();
@@ -435,3 +532,629 @@ fn test_write_multiple_spectra_container() {
     let stats = dataset.close().unwrap();
     assert!(stats.total_size_bytes > 0);
 }
+
+// ==================== Scan Diagnostics Tests ====================
+
+#[test]
+fn test_writer_v2_scan_diagnostics_roundtrip() {
+    use crate::reader::MzPeakReader;
+    use crate::scan_diagnostics_writer::ScanDiagnostics;
+    use crate::schema::manifest::Modality;
+    use crate::writer::{PeakArraysV2, SpectrumMetadata};
+
+    let dir = tempdir().unwrap();
+    let dataset_path = dir.path().join("diagnostics.mzpeak");
+    let mut writer = MzPeakDatasetWriterV2::new(&dataset_path, Modality::LcMs, None).unwrap();
+    for spectrum_id in 0..2 {
+        let metadata = SpectrumMetadata::new_ms1(spectrum_id, Some(spectrum_id as i32 + 1), 60.0, 1, 1);
+        let peaks = PeakArraysV2::new(vec![100.0], vec![1000.0]);
+        writer.write_spectrum_v2(&metadata, &peaks).unwrap();
+
+        let mut diagnostics = ScanDiagnostics::new(i64::from(spectrum_id));
+        diagnostics.push("Ion Injection Time (ms)", format!("{}.5", 10 + spectrum_id));
+        diagnostics.push("Master Scan Number", "0");
+        writer.write_scan_diagnostics(&diagnostics).unwrap();
+    }
+    writer.close().unwrap();
+
+    let reader = MzPeakReader::open(&dataset_path).unwrap();
+    let scans = reader.read_scan_diagnostics().unwrap();
+    assert_eq!(scans.len(), 2);
+    assert_eq!(scans[1].spectrum_id, 1);
+    assert_eq!(scans[1].get_numeric("Ion Injection Time (ms)"), Some(11.5));
+    assert_eq!(scans[0].get("Master Scan Number"), Some("0"));
+
+    // Datasets without diagnostics read as empty
+    let plain_path = dir.path().join("plain.mzpeak");
+    write_v2_container(&plain_path, &[]);
+    let reader = MzPeakReader::open(&plain_path).unwrap();
+    assert!(reader.read_scan_diagnostics().unwrap().is_empty());
+}
+
+// ==================== Attachment Tests ====================
+
+fn write_v2_container(path: &std::path::Path, attachments: &[&std::path::Path]) {
+    use crate::schema::manifest::Modality;
+    use crate::writer::{PeakArraysV2, SpectrumMetadata};
+
+    let mut writer = MzPeakDatasetWriterV2::new(path, Modality::LcMs, None).unwrap();
+    for attachment in attachments {
+        writer.add_attachment(attachment, &AttachOptions::default()).unwrap();
+    }
+    let metadata = SpectrumMetadata::new_ms1(0, Some(1), 60.0, 1, 2);
+    let peaks = PeakArraysV2::new(vec![100.0, 200.0], vec![1000.0, 500.0]);
+    writer.write_spectrum_v2(&metadata, &peaks).unwrap();
+    writer.close().unwrap();
+}
+
+#[test]
+fn test_writer_v2_attachments_readable() {
+    use crate::reader::MzPeakReader;
+    use crate::schema::manifest::AttachmentKind;
+
+    let dir = tempdir().unwrap();
+    let method = dir.path().join("method.pdf");
+    fs::write(&method, b"%PDF-1.7 method export").unwrap();
+    let dataset_path = dir.path().join("attached.mzpeak");
+    write_v2_container(&dataset_path, &[&method]);
+
+    let reader = MzPeakReader::open(&dataset_path).unwrap();
+    let attachments = reader.attachments().unwrap();
+    assert_eq!(attachments.len(), 1);
+    assert_eq!(attachments[0].name, "method.pdf");
+    assert_eq!(attachments[0].kind, AttachmentKind::Document);
+    assert_eq!(attachments[0].media_type, "application/pdf");
+    assert_eq!(attachments[0].size_bytes, 22);
+
+    let content = reader.read_attachment("method.pdf").unwrap().unwrap();
+    assert_eq!(content, b"%PDF-1.7 method export");
+    assert!(reader.read_attachment("missing.pdf").unwrap().is_none());
+}
+
+#[test]
+fn test_attach_and_detach_container() {
+    use crate::reader::MzPeakReader;
+    use crate::schema::manifest::AttachmentKind;
+
+    let dir = tempdir().unwrap();
+    let dataset_path = dir.path().join("edit.mzpeak");
+    write_v2_container(&dataset_path, &[]);
+
+    let script = dir.path().join("process.py");
+    fs::write(&script, "print('hello')\n").unwrap();
+    let options = AttachOptions {
+        description: Some("Post-processing script".to_string()),
+        ..Default::default()
+    };
+    let attachment = attach_file(&dataset_path, &script, &options).unwrap();
+    assert_eq!(attachment.kind, AttachmentKind::Script);
+
+    // Duplicate names are rejected
+    assert!(matches!(
+        attach_file(&dataset_path, &script, &options),
+        Err(DatasetError::AttachmentError(_))
+    ));
+
+    let reader = MzPeakReader::open(&dataset_path).unwrap();
+    let attachments = reader.attachments().unwrap();
+    assert_eq!(attachments.len(), 1);
+    assert_eq!(attachments[0].description.as_deref(), Some("Post-processing script"));
+    assert_eq!(
+        reader.read_attachment("process.py").unwrap().unwrap(),
+        b"print('hello')\n"
+    );
+    // Existing data entries survive the rewrite
+    assert_eq!(reader.total_peaks(), 2);
+    drop(reader);
+
+    let removed = detach_file(&dataset_path, "process.py").unwrap();
+    assert_eq!(removed.name, "process.py");
+
+    let mut archive = zip::ZipArchive::new(File::open(&dataset_path).unwrap()).unwrap();
+    assert!(archive.by_name("attachments/process.py").is_err());
+    assert_eq!(archive.by_index(0).unwrap().name(), "mimetype");
+    drop(archive);
+
+    let reader = MzPeakReader::open(&dataset_path).unwrap();
+    assert!(reader.attachments().unwrap().is_empty());
+    assert!(matches!(
+        detach_file(&dataset_path, "process.py"),
+        Err(DatasetError::AttachmentError(_))
+    ));
+}
+
+#[test]
+fn test_attach_rejects_v1_container_and_bad_names() {
+    let dir = tempdir().unwrap();
+    let dataset_path = dir.path().join("v1.mzpeak");
+    let mut dataset = MzPeakDatasetWriter::new_container(
+        &dataset_path,
+        &MzPeakMetadata::new(),
+        WriterConfig::default(),
+    )
+    .unwrap();
+    dataset
+        .write_spectrum_arrays(&make_ms1_spectrum(0, 1, 0.0, &[(400.0, 1.0)]))
+        .unwrap();
+    dataset.close().unwrap();
+
+    let notes = dir.path().join("notes.txt");
+    fs::write(&notes, "notes").unwrap();
+    assert!(matches!(
+        attach_file(&dataset_path, &notes, &AttachOptions::default()),
+        Err(DatasetError::AttachmentError(_))
+    ));
+
+    let v2_path = dir.path().join("v2.mzpeak");
+    write_v2_container(&v2_path, &[]);
+    let options = AttachOptions {
+        name: Some("../escape.txt".to_string()),
+        ..Default::default()
+    };
+    assert!(matches!(
+        attach_file(&v2_path, &notes, &options),
+        Err(DatasetError::AttachmentError(_))
+    ));
+}
+
+// ==================== Compaction Tests ====================
+
+fn small_row_group_config() -> WriterConfig {
+    WriterConfig {
+        row_group_size: 4,
+        ..WriterConfig::default()
+    }
+}
+
+fn peaks_row_groups(path: &std::path::Path) -> usize {
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+    let reader = SerializedFileReader::new(File::open(path).unwrap()).unwrap();
+    reader.metadata().num_row_groups()
+}
+
+#[test]
+fn test_compact_container_merges_row_groups() {
+    use crate::reader::MzPeakReader;
+
+    let dir = tempdir().unwrap();
+    let dataset_path = dir.path().join("fragmented.mzpeak");
+    let mut dataset = MzPeakDatasetWriter::new_container(
+        &dataset_path,
+        &MzPeakMetadata::new(),
+        small_row_group_config(),
+    )
+    .unwrap();
+    let spectra: Vec<_> = (0..20)
+        .map(|i| make_ms1_spectrum(i, i + 1, i as f32, &[(400.0, 1.0), (500.0, 2.0)]))
+        .collect();
+    dataset.write_spectra_arrays(&spectra).unwrap();
+    dataset.close().unwrap();
+
+    let before = MzPeakReader::open(&dataset_path).unwrap();
+    let format_version = before.metadata().format_version.clone();
+    assert!(before.metadata().num_row_groups > 1);
+    drop(before);
+
+    let report = compact_dataset(&dataset_path, &CompactOptions::default()).unwrap();
+    assert_eq!(report.tables.len(), 1);
+    assert_eq!(report.tables[0].path, "peaks/peaks.parquet");
+    assert_eq!(report.tables[0].rows, 40);
+    assert_eq!(report.tables[0].row_groups_after, 1);
+    assert!(report.tables[0].row_groups_before > 1);
+    assert!(report.removed_entries.is_empty());
+    assert!(report.verified_entries >= 3);
+
+    let after = MzPeakReader::open(&dataset_path).unwrap();
+    assert_eq!(after.metadata().num_row_groups, 1);
+    assert_eq!(after.metadata().format_version, format_version);
+    assert_eq!(after.total_peaks(), 40);
+    assert_eq!(after.iter_spectra_arrays().unwrap().len(), 20);
+
+    let mut archive = zip::ZipArchive::new(File::open(&dataset_path).unwrap()).unwrap();
+    assert_eq!(archive.by_index(0).unwrap().name(), "mimetype");
+    assert_eq!(
+        archive.by_name("peaks/peaks.parquet").unwrap().compression(),
+        zip::CompressionMethod::Stored
+    );
+}
+
+#[test]
+fn test_compact_directory_removes_orphans() {
+    let dir = tempdir().unwrap();
+    let dataset_path = dir.path().join("fragmented_dir");
+    let mut dataset = MzPeakDatasetWriter::new_directory(
+        &dataset_path,
+        &MzPeakMetadata::new(),
+        small_row_group_config(),
+    )
+    .unwrap();
+    let spectra: Vec<_> = (0..10)
+        .map(|i| make_ms1_spectrum(i, i + 1, i as f32, &[(400.0, 1.0)]))
+        .collect();
+    dataset.write_spectra_arrays(&spectra).unwrap();
+    dataset.close().unwrap();
+
+    let peaks_path = dataset_path.join("peaks/peaks.parquet");
+    assert!(peaks_row_groups(&peaks_path) > 1);
+
+    fs::create_dir_all(dataset_path.join("attachments")).unwrap();
+    fs::write(dataset_path.join("attachments/stray.pdf"), b"stray").unwrap();
+    fs::write(dataset_path.join("peaks/.tmpA1b2C3"), b"partial").unwrap();
+    fs::create_dir_all(dataset_path.join("extra")).unwrap();
+    fs::write(dataset_path.join("extra/future.json"), b"{}").unwrap();
+
+    let report = compact_dataset(&dataset_path, &CompactOptions::default()).unwrap();
+    let mut removed = report.removed_entries.clone();
+    removed.sort();
+    assert_eq!(removed, vec!["attachments/stray.pdf", "peaks/.tmpA1b2C3"]);
+    assert!(!dataset_path.join("attachments/stray.pdf").exists());
+    assert!(dataset_path.join("extra/future.json").exists());
+
+    assert_eq!(peaks_row_groups(&peaks_path), 1);
+    let peaks = report
+        .tables
+        .iter()
+        .find(|t| t.path == "peaks/peaks.parquet")
+        .unwrap();
+    assert_eq!(peaks.rows, 10);
+    assert!(report.size_after < report.size_before);
+}
+
+#[test]
+fn test_compact_keeps_registered_attachments() {
+    use crate::reader::MzPeakReader;
+
+    let dir = tempdir().unwrap();
+    let notes = dir.path().join("notes.txt");
+    fs::write(&notes, "run notes").unwrap();
+    let dataset_path = dir.path().join("with_attachment.mzpeak");
+    write_v2_container(&dataset_path, &[&notes]);
+
+    let report = compact_dataset(&dataset_path, &CompactOptions::default()).unwrap();
+    assert!(report.removed_entries.is_empty());
+    assert_eq!(report.tables.len(), 2);
+
+    let reader = MzPeakReader::open(&dataset_path).unwrap();
+    assert_eq!(
+        reader.read_attachment("notes.txt").unwrap().unwrap(),
+        b"run notes"
+    );
+}
+
+/// Directory bundle with a manifest and a fragmented peaks table
+fn write_compactable_directory(path: &std::path::Path) {
+    use crate::schema::manifest::{Manifest, Modality};
+
+    let mut dataset =
+        MzPeakDatasetWriter::new_directory(path, &MzPeakMetadata::new(), small_row_group_config())
+            .unwrap();
+    let spectra: Vec<_> = (0..10)
+        .map(|i| make_ms1_spectrum(i, i + 1, i as f32, &[(400.0, 1.0)]))
+        .collect();
+    dataset.write_spectra_arrays(&spectra).unwrap();
+    dataset.close().unwrap();
+
+    let manifest = Manifest::new(
+        Modality::LcMs,
+        false,
+        10,
+        10,
+        "2024-01-01T00:00:00Z".to_string(),
+        "test".to_string(),
+    );
+    fs::write(
+        path.join("manifest.json"),
+        serde_json::to_string(&manifest).unwrap(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_compact_leaves_parquet_attachments_untouched() {
+    use crate::reader::MzPeakReader;
+
+    let dir = tempdir().unwrap();
+    let dataset_path = dir.path().join("library_dir");
+    write_compactable_directory(&dataset_path);
+
+    // A fragmented Parquet file that compaction would otherwise rewrite
+    let library = dir.path().join("library.parquet");
+    fs::copy(dataset_path.join("peaks/peaks.parquet"), &library).unwrap();
+    let original = fs::read(&library).unwrap();
+    attach_file(&dataset_path, &library, &AttachOptions::default()).unwrap();
+
+    let report = compact_dataset(&dataset_path, &CompactOptions::default()).unwrap();
+    assert!(report
+        .tables
+        .iter()
+        .all(|t| !t.path.starts_with("attachments/")));
+    assert_eq!(
+        fs::read(dataset_path.join("attachments/library.parquet")).unwrap(),
+        original
+    );
+    assert_eq!(peaks_row_groups(&dataset_path.join("peaks/peaks.parquet")), 1);
+
+    let container_path = dir.path().join("library.mzpeak");
+    write_v2_container(&container_path, &[&library]);
+    let report = compact_dataset(&container_path, &CompactOptions::default()).unwrap();
+    assert_eq!(report.tables.len(), 2);
+    let reader = MzPeakReader::open(&container_path).unwrap();
+    assert_eq!(
+        reader.read_attachment("library.parquet").unwrap().unwrap(),
+        original
+    );
+}
+
+#[test]
+fn test_compact_directory_failure_leaves_dataset_untouched() {
+    use crate::schema::manifest::Manifest;
+
+    let dir = tempdir().unwrap();
+    let dataset_path = dir.path().join("corrupt_dir");
+    write_compactable_directory(&dataset_path);
+    let notes = dir.path().join("notes.txt");
+    fs::write(&notes, "run notes").unwrap();
+    attach_file(&dataset_path, &notes, &AttachOptions::default()).unwrap();
+    fs::write(dataset_path.join("attachments/notes.txt"), "edited").unwrap();
+    fs::write(dataset_path.join("peaks/.tmpA1b2C3"), b"partial").unwrap();
+
+    let peaks_path = dataset_path.join("peaks/peaks.parquet");
+    let peaks = fs::read(&peaks_path).unwrap();
+    let manifest: Manifest =
+        serde_json::from_str(&fs::read_to_string(dataset_path.join("manifest.json")).unwrap())
+            .unwrap();
+    assert_eq!(manifest.attachments.len(), 1);
+
+    let result = compact_dataset(&dataset_path, &CompactOptions::default());
+    assert!(matches!(result, Err(DatasetError::VerificationFailed(_))));
+    assert_eq!(fs::read(&peaks_path).unwrap(), peaks);
+    assert!(dataset_path.join("peaks/.tmpA1b2C3").exists());
+
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dataset_path.join("peaks")).unwrap() {
+        files.push(entry.unwrap().file_name().into_string().unwrap());
+    }
+    files.sort();
+    assert_eq!(files, vec![".tmpA1b2C3", "peaks.parquet"]);
+}
+
+#[test]
+fn test_compact_drops_superseded_manifests() {
+    let dir = tempdir().unwrap();
+    let dataset_path = dir.path().join("generations_dir");
+    write_compactable_directory(&dataset_path);
+    let manifest = fs::read(dataset_path.join("manifest.json")).unwrap();
+    fs::write(dataset_path.join("manifest.json.1"), &manifest).unwrap();
+    fs::write(dataset_path.join("manifest.2.json"), &manifest).unwrap();
+    fs::create_dir_all(dataset_path.join("extra")).unwrap();
+    fs::write(dataset_path.join("extra/manifest.json.1"), &manifest).unwrap();
+
+    let report = compact_dataset(&dataset_path, &CompactOptions::default()).unwrap();
+    let mut removed = report.removed_entries.clone();
+    removed.sort();
+    assert_eq!(removed, vec!["manifest.2.json", "manifest.json.1"]);
+    assert!(dataset_path.join("manifest.json").exists());
+    assert!(dataset_path.join("extra/manifest.json.1").exists());
+}
+
+#[test]
+fn test_compact_appends_processing_history() {
+    use crate::metadata::{ProcessingHistory, ProcessingStep};
+    use crate::reader::MzPeakReader;
+    use std::collections::HashMap;
+
+    let mut history = ProcessingHistory::new();
+    history.append(ProcessingStep::mzpeak("Conversion", HashMap::new()));
+    let mut metadata = MzPeakMetadata::new();
+    metadata.processing_history = Some(history);
+
+    let dir = tempdir().unwrap();
+    let dataset_path = dir.path().join("history.mzpeak");
+    let mut dataset =
+        MzPeakDatasetWriter::new_container(&dataset_path, &metadata, small_row_group_config())
+            .unwrap();
+    let spectra: Vec<_> = (0..10)
+        .map(|i| make_ms1_spectrum(i, i + 1, i as f32, &[(400.0, 1.0)]))
+        .collect();
+    dataset.write_spectra_arrays(&spectra).unwrap();
+    dataset.close().unwrap();
+
+    compact_dataset(&dataset_path, &CompactOptions::default()).unwrap();
+
+    let reader = MzPeakReader::open(&dataset_path).unwrap();
+    let steps = reader.processing_history().unwrap();
+    let types: Vec<_> = steps.iter().map(|s| s.processing_type.as_str()).collect();
+    assert_eq!(types, vec!["Conversion", "Compaction"]);
+    assert_eq!(steps[1].order, 2);
+    assert_eq!(
+        steps[1].parameters["row_group_size"],
+        CompactOptions::default().row_group_size.to_string()
+    );
+
+    let json = reader.read_metadata_json().unwrap().unwrap();
+    assert_eq!(json.processing_history.unwrap().steps.len(), 2);
+}
+
+// ==================== Pipelined Container Tests ====================
+
+#[test]
+fn test_pipelined_container_matches_staged() {
+    use crate::reader::MzPeakReader;
+    use crate::schema::manifest::Modality;
+    use crate::writer::{PeakArraysV2, SpectrumMetadata};
+
+    let dir = tempdir().unwrap();
+    let write = |name: &str, pipeline_peaks: bool| {
+        let path = dir.path().join(name);
+        // The staged variant also encodes both tables on the calling thread
+        let config = DatasetWriterV2Config {
+            pipeline_peaks,
+            parallel_tables: pipeline_peaks,
+            ..Default::default()
+        };
+        let mut writer = MzPeakDatasetWriterV2::with_config(&path, Modality::LcMs, None, config).unwrap();
+        for i in 0..50u32 {
+            let metadata = SpectrumMetadata::new_ms1(i, Some(i as i32 + 1), i as f32, 1, 3);
+            let peaks = PeakArraysV2::new(
+                vec![100.0 + i as f64, 200.0, 300.0],
+                vec![1.0, 2.0, i as f32],
+            );
+            writer.write_spectrum_v2(&metadata, &peaks).unwrap();
+        }
+        let stats = writer.close().unwrap();
+        (path, stats)
+    };
+
+    let (pipelined, pipelined_stats) = write("pipelined.mzpeak", true);
+    let (staged, staged_stats) = write("staged.mzpeak", false);
+    assert_eq!(
+        pipelined_stats.peaks_stats.file_size_bytes,
+        staged_stats.peaks_stats.file_size_bytes
+    );
+
+    // Every entry passes its CRC check and mimetype stays first
+    let read_entries = |path: &std::path::Path| {
+        let mut archive = zip::ZipArchive::new(File::open(path).unwrap()).unwrap();
+        assert_eq!(archive.by_index(0).unwrap().name(), "mimetype");
+        let mut entries = std::collections::BTreeMap::new();
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).unwrap();
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content).unwrap();
+            entries.insert(entry.name().to_string(), (entry.compression(), content));
+        }
+        entries
+    };
+    let pipelined_entries = read_entries(&pipelined);
+    let staged_entries = read_entries(&staged);
+    assert_eq!(
+        pipelined_entries.keys().collect::<Vec<_>>(),
+        staged_entries.keys().collect::<Vec<_>>()
+    );
+    // Footer key-value order is not deterministic, so compare sizes only
+    assert_eq!(
+        pipelined_entries["peaks/peaks.parquet"].1.len(),
+        staged_entries["peaks/peaks.parquet"].1.len()
+    );
+    assert_eq!(
+        pipelined_entries["peaks/peaks.parquet"].0,
+        zip::CompressionMethod::Stored
+    );
+
+    let pipelined = MzPeakReader::open(&pipelined).unwrap();
+    let staged = MzPeakReader::open(&staged).unwrap();
+    assert_eq!(pipelined.total_peaks(), 150);
+    assert_eq!(pipelined.total_peaks(), staged.total_peaks());
+}
+
+#[test]
+fn test_writer_v2_stats_snapshot() {
+    use crate::schema::manifest::Modality;
+    use crate::writer::{PeakArraysV2, SpectrumMetadata};
+
+    let dir = tempdir().unwrap();
+    let dataset_path = dir.path().join("progress.mzpeak");
+    let mut writer = MzPeakDatasetWriterV2::new(&dataset_path, Modality::LcMs, None).unwrap();
+    writer.progress().unwrap().set_expected_spectra(4);
+
+    let peaks = PeakArraysV2::new(vec![100.0, 200.0], vec![1000.0, 500.0]);
+    writer
+        .write_spectrum_v2(&SpectrumMetadata::new_ms1(0, Some(1), 60.0, 1, 2), &peaks)
+        .unwrap();
+    // Spectra without peaks still count towards completion
+    writer
+        .write_spectrum_v2(
+            &SpectrumMetadata::new_ms1(1, Some(2), 61.5, 1, 0),
+            &PeakArraysV2::new(vec![], vec![]),
+        )
+        .unwrap();
+
+    assert_eq!(writer.stats_snapshot().unwrap().current_rt, Some(61.5));
+
+    // Queued rows are counted once the table workers have encoded them
+    let progress = writer.progress().unwrap();
+    writer.close().unwrap();
+    let snapshot = progress.snapshot();
+    assert_eq!(snapshot.spectra_written, 2);
+    assert_eq!(snapshot.peaks_written, 2);
+    assert_eq!(snapshot.current_rt, Some(61.5));
+    assert_eq!(snapshot.fraction_complete, Some(0.5));
+}
+
+#[test]
+fn test_writer_v2_stats_json() {
+    use crate::schema::manifest::Modality;
+    use crate::writer::{PeakArraysV2, SpectraWriterStats, SpectrumMetadata};
+
+    let dir = tempdir().unwrap();
+    let dataset_path = dir.path().join("stats.mzpeak");
+    let mut writer = MzPeakDatasetWriterV2::new(&dataset_path, Modality::LcMs, None).unwrap();
+    let peaks = PeakArraysV2::new(vec![100.0, 200.0], vec![1000.0, 500.0]);
+    writer
+        .write_spectrum_v2(&SpectrumMetadata::new_ms1(0, Some(1), 60.0, 1, 2), &peaks)
+        .unwrap();
+    let stats = writer.close().unwrap();
+
+    let json = serde_json::to_value(&stats).unwrap();
+    assert_eq!(json["spectra_stats"]["spectra_written"], 1);
+    assert_eq!(json["peaks_stats"]["peaks_written"], 2);
+    assert_eq!(json["total_size_bytes"], stats.total_size_bytes);
+    let parsed: DatasetV2Stats = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, stats);
+
+    // Fields added in later releases default when reading older reports
+    let parsed: DatasetV2Stats = serde_json::from_str(r#"{"total_size_bytes": 10}"#).unwrap();
+    assert_eq!(parsed.total_size_bytes, 10);
+    assert_eq!(parsed.spectra_stats, SpectraWriterStats::default());
+}
+
+#[test]
+fn test_writer_v2_layout_stats() {
+    use crate::reader::MzPeakReader;
+    use crate::schema::manifest::Modality;
+    use crate::writer::{PeakArraysV2, PeaksWriterV2Config, SpectrumMetadata};
+
+    let dir = tempdir().unwrap();
+    let dataset_path = dir.path().join("layout.mzpeak");
+    let config = DatasetWriterV2Config {
+        peaks_config: PeaksWriterV2Config {
+            row_group_size: 20,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut writer =
+        MzPeakDatasetWriterV2::with_config(&dataset_path, Modality::LcMs, None, config).unwrap();
+    for id in 0..25u32 {
+        let mz = (0..7).map(|i| 100.0 + i as f64).collect();
+        let intensity = (0..7).map(|i| (id * 7 + i) as f32).collect();
+        let metadata = SpectrumMetadata::new_ms1(id, Some(id as i32), id as f32, 1, 7);
+        writer
+            .write_spectrum_v2(&metadata, &PeakArraysV2::new(mz, intensity))
+            .unwrap();
+    }
+    let stats = writer.close().unwrap();
+    assert_eq!(stats.peaks_stats.row_groups_written, 9);
+
+    let reader = MzPeakReader::open(&dataset_path).unwrap();
+    let layout = reader.layout_stats().unwrap().expect("layout stats");
+    assert_eq!(layout.row_group_size, 20);
+    assert_eq!(layout.row_groups.len(), reader.metadata().num_row_groups);
+    assert_eq!(layout.peak_count(), 175);
+
+    let first = &layout.row_groups[0];
+    assert_eq!(first.peak_count, 20);
+    assert_eq!((first.first_spectrum_id, first.last_spectrum_id), (0, 2));
+    // Two whole spectra (bin 2) and 6 peaks of spectrum 2 (bin 2)
+    assert_eq!(first.peaks_per_spectrum, vec![0, 0, 3]);
+    assert_eq!(first.intensity_deciles.first(), Some(&0.0));
+    assert_eq!(first.max_intensity(), Some(19.0));
+    assert_eq!(layout.row_groups[8].peak_count, 15);
+
+    // Regrouping the peaks table invalidates the summaries
+    let options = CompactOptions { row_group_size: 100 };
+    compact_dataset(&dataset_path, &options).unwrap();
+    let reader = MzPeakReader::open(&dataset_path).unwrap();
+    assert!(reader.manifest().unwrap().unwrap().layout_stats.is_some());
+    assert!(reader.layout_stats().unwrap().is_none());
+}
@@ -1,7 +1,9 @@
 use std::fs::{self, File};
 use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use arrow::record_batch::RecordBatch;
 use tempfile::NamedTempFile;
 use zip::write::SimpleFileOptions;
 use zip::CompressionMethod;
@@ -13,11 +15,15 @@ use crate::chromatogram_writer::{
 use crate::mobilogram_writer::{
     Mobilogram, MobilogramWriter, MobilogramWriterConfig, MobilogramWriterStats,
 };
-use crate::metadata::MzPeakMetadata;
+use crate::metadata::{ImagingMetadata, MzPeakMetadata};
 use crate::schema::MZPEAK_MIMETYPE;
-use crate::writer::{MzPeakWriter, SpectrumArrays, WriterConfig, WriterStats};
+use crate::writer::{
+    MzPeakWriter, SpectrumArrays, StatsSnapshot, WriteProgress, WriterConfig, WriterStats,
+};
 
 use super::error::DatasetError;
+use super::paths::{normalize_path, resolve_temp_dir};
+use super::staging::{prepare_output, StagedOutput};
 use super::stats::DatasetStats;
 use super::types::OutputMode;
 
@@ -38,8 +44,8 @@ struct ParquetTempFile {
 }
 
 impl ParquetTempFile {
-    fn new() -> std::io::Result<Self> {
-        let temp_file = NamedTempFile::new()?;
+    fn new_in(dir: &Path) -> std::io::Result<Self> {
+        let temp_file = NamedTempFile::new_in(dir)?;
         // Clone the file handle for writing
         let file = temp_file.reopen()?;
         let writer = BufWriter::new(file);
@@ -87,6 +93,7 @@ impl Seek for ParquetTempFile {
 }
 
 /// Internal sink abstraction for writing to either directory or container
+#[allow(clippy::large_enum_variant)] // Constructed once per dataset; boxing buys nothing
 enum DatasetSink {
     /// Directory mode: writes directly to files
     Directory {
@@ -121,6 +128,9 @@ pub struct MzPeakDatasetWriter {
     /// Copy of metadata for JSON export
     metadata: MzPeakMetadata,
 
+    /// Local staging location, moved to the destination on close
+    staged: Option<StagedOutput>,
+
     /// Flag indicating if the dataset is finalized
     finalized: bool,
 }
@@ -148,7 +158,7 @@ impl MzPeakDatasetWriter {
         metadata: &MzPeakMetadata,
         config: WriterConfig,
     ) -> Result<Self, DatasetError> {
-        let path = path.as_ref();
+        let path = &normalize_path(path.as_ref());
 
         // Determine mode: Container if .mzpeak extension and not an existing directory
         let use_container = path
@@ -170,26 +180,24 @@ impl MzPeakDatasetWriter {
         metadata: &MzPeakMetadata,
         config: WriterConfig,
     ) -> Result<Self, DatasetError> {
-        let output_path = path.as_ref().to_path_buf();
-
         // Validate path
-        if output_path.to_string_lossy().is_empty() {
+        if path.as_ref().as_os_str().is_empty() {
             return Err(DatasetError::InvalidPath("Empty path".to_string()));
         }
+        let output_path = normalize_path(path.as_ref());
 
         // Check if file already exists
         if output_path.exists() {
             return Err(DatasetError::AlreadyExists(
-                output_path.to_string_lossy().to_string(),
+                path.as_ref().to_string_lossy().to_string(),
             ));
         }
 
-        // Create parent directories if needed
-        if let Some(parent) = output_path.parent() {
-            if !parent.as_os_str().is_empty() && !parent.exists() {
-                fs::create_dir_all(parent)?;
-            }
-        }
+        // Fail early on read-only destinations; optionally write locally first
+        let staged = prepare_output(&output_path, config.temp_dir.as_deref(), config.stage_locally)?;
+        let output_path = staged
+            .as_ref()
+            .map_or(output_path, |staged| staged.path().to_path_buf());
 
         // Create ZIP file
         let file = File::create(&output_path)?;
@@ -204,17 +212,18 @@ impl MzPeakDatasetWriter {
         zip_writer.write_all(MZPEAK_MIMETYPE.as_bytes())?;
 
         // Initialize peak writer to temp file (bounded memory - Issue 000 fix)
-        let peak_buffer = ParquetTempFile::new()?;
+        let temp_dir = resolve_temp_dir(config.temp_dir.as_deref())?;
+        let peak_buffer = ParquetTempFile::new_in(&temp_dir)?;
         let peak_writer = MzPeakWriter::new(peak_buffer, metadata, config.clone())?;
 
         // Initialize chromatogram writer to temp file
-        let chrom_buffer = ParquetTempFile::new()?;
+        let chrom_buffer = ParquetTempFile::new_in(&temp_dir)?;
         let chrom_config = ChromatogramWriterConfig::default();
         let chrom_writer = ChromatogramWriter::new(chrom_buffer, metadata, chrom_config)
             .map_err(|e| DatasetError::ChromatogramWriterError(e.to_string()))?;
 
         // Initialize mobilogram writer to temp file
-        let mob_buffer = ParquetTempFile::new()?;
+        let mob_buffer = ParquetTempFile::new_in(&temp_dir)?;
         let mob_config = MobilogramWriterConfig::default();
         let mob_writer = MobilogramWriter::new(mob_buffer, metadata, mob_config)
             .map_err(|e| DatasetError::MobilogramWriterError(e.to_string()))?;
@@ -229,6 +238,7 @@ impl MzPeakDatasetWriter {
             },
             mode: OutputMode::Container,
             metadata: metadata.clone(),
+            staged,
             finalized: false,
         })
     }
@@ -239,20 +249,26 @@ impl MzPeakDatasetWriter {
         metadata: &MzPeakMetadata,
         config: WriterConfig,
     ) -> Result<Self, DatasetError> {
-        let root_path = path.as_ref().to_path_buf();
-
         // Validate path
-        if root_path.to_string_lossy().is_empty() {
+        if path.as_ref().as_os_str().is_empty() {
             return Err(DatasetError::InvalidPath("Empty path".to_string()));
         }
+        // Nested table paths can exceed MAX_PATH on Windows
+        let root_path = normalize_path(path.as_ref());
 
         // Check if dataset already exists
         if root_path.exists() {
             return Err(DatasetError::AlreadyExists(
-                root_path.to_string_lossy().to_string(),
+                path.as_ref().to_string_lossy().to_string(),
             ));
         }
 
+        // Fail early on read-only destinations; optionally write locally first
+        let staged = prepare_output(&root_path, config.temp_dir.as_deref(), config.stage_locally)?;
+        let root_path = staged
+            .as_ref()
+            .map_or(root_path, |staged| staged.path().to_path_buf());
+
         // Create root directory
         fs::create_dir_all(&root_path)?;
 
@@ -290,6 +306,7 @@ impl MzPeakDatasetWriter {
             },
             mode: OutputMode::Directory,
             metadata: metadata.clone(),
+            staged,
             finalized: false,
         })
     }
@@ -368,7 +385,17 @@ impl MzPeakDatasetWriter {
     /// Write multiple spectra by transferring ownership of their peak arrays.
     pub fn write_spectra_owned(
         &mut self,
-        spectra: Vec<SpectrumArrays>,
+        mut spectra: Vec<SpectrumArrays>,
+    ) -> Result<(), DatasetError> {
+        self.write_spectra_drain(&mut spectra)
+    }
+
+    /// Write and remove all spectra from `spectra`, keeping its capacity.
+    ///
+    /// See [`MzPeakWriter::write_spectra_drain`].
+    pub fn write_spectra_drain(
+        &mut self,
+        spectra: &mut Vec<SpectrumArrays>,
     ) -> Result<(), DatasetError> {
         if self.finalized {
             return Err(DatasetError::NotInitialized);
@@ -377,11 +404,32 @@ impl MzPeakDatasetWriter {
         match &mut self.sink {
             DatasetSink::Directory { peak_writer, .. } => {
                 let writer = peak_writer.as_mut().ok_or(DatasetError::NotInitialized)?;
-                writer.write_spectra_owned(spectra)?;
+                writer.write_spectra_drain(spectra)?;
+            }
+            DatasetSink::Container { peak_writer, .. } => {
+                let writer = peak_writer.as_mut().ok_or(DatasetError::NotInitialized)?;
+                writer.write_spectra_drain(spectra)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a record batch that is already in the mzPeak peaks schema.
+    ///
+    /// See [`MzPeakWriter::write_record_batch`] for the accepted schema.
+    pub fn write_record_batch(&mut self, batch: RecordBatch) -> Result<(), DatasetError> {
+        if self.finalized {
+            return Err(DatasetError::NotInitialized);
+        }
+
+        match &mut self.sink {
+            DatasetSink::Directory { peak_writer, .. } => {
+                let writer = peak_writer.as_mut().ok_or(DatasetError::NotInitialized)?;
+                writer.write_record_batch(batch)?;
             }
             DatasetSink::Container { peak_writer, .. } => {
                 let writer = peak_writer.as_mut().ok_or(DatasetError::NotInitialized)?;
-                writer.write_spectra_owned(spectra)?;
+                writer.write_record_batch(batch)?;
             }
         }
         Ok(())
@@ -533,63 +581,32 @@ impl MzPeakDatasetWriter {
         }
     }
 
-    /// Build the metadata JSON content
-    fn build_metadata_json(&self) -> Result<String, DatasetError> {
-        let mut json_map = serde_json::Map::new();
-
-        json_map.insert(
-            "format_version".to_string(),
-            serde_json::Value::String(crate::schema::MZPEAK_FORMAT_VERSION.to_string()),
-        );
-
-        json_map.insert(
-            "created".to_string(),
-            serde_json::Value::String(chrono::Utc::now().to_rfc3339()),
-        );
-
-        json_map.insert(
-            "converter".to_string(),
-            serde_json::Value::String(format!("mzpeak-rs v{}", env!("CARGO_PKG_VERSION"))),
-        );
-
-        // Add SDRF metadata
-        if let Some(ref sdrf) = self.metadata.sdrf {
-            let sdrf_json = serde_json::to_value(sdrf)?;
-            json_map.insert("sdrf".to_string(), sdrf_json);
-        }
-
-        // Add instrument config
-        if let Some(ref instrument) = self.metadata.instrument {
-            let instrument_json = serde_json::to_value(instrument)?;
-            json_map.insert("instrument".to_string(), instrument_json);
-        }
-
-        // Add LC config
-        if let Some(ref lc) = self.metadata.lc_config {
-            let lc_json = serde_json::to_value(lc)?;
-            json_map.insert("lc_config".to_string(), lc_json);
-        }
-
-        // Add run parameters
-        if let Some(ref run) = self.metadata.run_parameters {
-            let run_json = serde_json::to_value(run)?;
-            json_map.insert("run_parameters".to_string(), run_json);
+    /// Live progress counters of the peak writer, readable from other threads
+    pub fn progress(&self) -> Option<Arc<WriteProgress>> {
+        match &self.sink {
+            DatasetSink::Directory { peak_writer, .. } => peak_writer.as_ref().map(|w| w.progress()),
+            DatasetSink::Container { peak_writer, .. } => peak_writer.as_ref().map(|w| w.progress()),
         }
+    }
 
-        // Add source file info
-        if let Some(ref source) = self.metadata.source_file {
-            let source_json = serde_json::to_value(source)?;
-            json_map.insert("source_file".to_string(), source_json);
-        }
+    /// Running statistics of the peak writer (see [`MzPeakWriter::stats_snapshot`])
+    pub fn stats_snapshot(&self) -> Option<StatsSnapshot> {
+        self.progress().map(|progress| progress.snapshot())
+    }
 
-        // Add processing history
-        if let Some(ref history) = self.metadata.processing_history {
-            let history_json = serde_json::to_value(history)?;
-            json_map.insert("processing_history".to_string(), history_json);
-        }
+    /// Set the pixel grid calibration written to `metadata.json`
+    ///
+    /// For calibrations only known after the spectra have been written; the
+    /// Parquet footers keep the metadata passed on creation.
+    pub fn set_imaging_metadata(&mut self, imaging: ImagingMetadata) {
+        self.metadata.imaging = Some(imaging);
+    }
 
-        let json_value = serde_json::Value::Object(json_map);
-        Ok(serde_json::to_string_pretty(&json_value)?)
+    /// Build the metadata JSON content
+    fn build_metadata_json(&self) -> Result<String, DatasetError> {
+        Ok(self
+            .metadata
+            .to_metadata_json(crate::schema::MZPEAK_FORMAT_VERSION)?)
     }
 
     /// Close the dataset and finalize all writers
@@ -798,6 +815,9 @@ impl MzPeakDatasetWriter {
             }
         };
 
+        if let Some(staged) = self.staged.take() {
+            staged.commit()?;
+        }
         self.finalized = true;
 
         let chromatograms_written = chromatogram_stats
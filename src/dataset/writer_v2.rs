@@ -11,9 +11,20 @@
 //! ├── manifest.json               # Schema version and modality declaration
 //! ├── metadata.json               # Human-readable metadata (Deflate compressed)
 //! ├── spectra/spectra.parquet     # Spectrum-level metadata (one row per spectrum)
-//! └── peaks/peaks.parquet         # Peak-level data (one row per peak)
+//! ├── peaks/peaks.parquet         # Peak-level data (one row per peak)
+//! ├── diagnostics/scan_diagnostics.parquet  # Optional per-scan key-value diagnostics
+//! └── attachments/                # Optional user files registered in the manifest
 //! ```
 //!
+//! By default `peaks.parquet` is streamed into the container by a background
+//! ZIP writer thread while it is encoded (see
+//! [`DatasetWriterV2Config::pipeline_peaks`]), so it is stored right after
+//! `mimetype` and closing the writer only appends the small entries.
+//!
+//! The spectra and peaks tables are encoded on worker threads of their own
+//! (see [`DatasetWriterV2Config::parallel_tables`]), so the spectrum
+//! metadata never stalls the peaks stream.
+//!
 //! ## Design Rationale
 //!
 //! The v2.0 schema separates spectrum metadata from peak data:
@@ -50,34 +61,42 @@
 use std::fs::{self, File};
 use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use serde::{Deserialize, Serialize};
 use tempfile::NamedTempFile;
 use zip::write::SimpleFileOptions;
 use zip::CompressionMethod;
 use zip::ZipWriter;
 
 use crate::metadata::{MzPeakMetadata, VendorHints};
-use crate::schema::manifest::{Manifest, Modality};
+use crate::scan_diagnostics_writer::{
+    ScanDiagnostics, ScanDiagnosticsWriter, ScanDiagnosticsWriterConfig, SCAN_DIAGNOSTICS_PATH,
+};
+use crate::schema::manifest::{Attachment, LayoutStats, Manifest, Modality};
+use crate::schema::MZPEAK_V2_SCHEMA_VERSION;
 use crate::writer::{
-    PeakArraysV2, PeaksWriterV2, PeaksWriterV2Config, PeaksWriterV2Stats, SpectraWriter,
-    SpectraWriterConfig, SpectraWriterStats, SpectrumMetadata, SpectrumV2,
+    spectrum_checksum, PeakArraysV2, PeaksWriterV2, PeaksWriterV2Config, PeaksWriterV2Stats,
+    SpectraWriter, SpectraWriterConfig, SpectraWriterStats, SpectrumMetadata, SpectrumV2,
+    StatsSnapshot, WriteProgress,
 };
 
+use super::attachments::{describe_attachment, write_attachment_entry, AttachOptions};
 use super::error::DatasetError;
+use super::paths::{normalize_path, resolve_temp_dir};
+use super::staging::{prepare_output, StagedOutput};
+use super::table_worker::TableWriter;
+use super::zip_pipeline::{PipelineWriter, ZipEntryPipeline, DEFAULT_PIPELINE_CHUNK_SIZE};
 
-// =============================================================================
-// v2.0 Mimetype
-// =============================================================================
-
-/// MIME type for mzPeak v2.0 container format
-pub const MZPEAK_V2_MIMETYPE: &str = "application/vnd.mzpeak+v2";
+pub use crate::schema::MZPEAK_V2_MIMETYPE;
 
 // =============================================================================
 // Statistics
 // =============================================================================
 
 /// Statistics from a completed v2.0 dataset write operation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DatasetV2Stats {
     /// Statistics from the spectra writer
     pub spectra_stats: SpectraWriterStats,
@@ -110,8 +129,8 @@ struct ParquetTempFile {
 }
 
 impl ParquetTempFile {
-    fn new() -> std::io::Result<Self> {
-        let temp_file = NamedTempFile::new()?;
+    fn new_in(dir: &Path) -> std::io::Result<Self> {
+        let temp_file = NamedTempFile::new_in(dir)?;
         let file = temp_file.reopen()?;
         let writer = BufWriter::new(file);
         Ok(Self { temp_file, writer })
@@ -140,6 +159,31 @@ impl Write for ParquetTempFile {
     }
 }
 
+/// Destination of the peaks table
+///
+/// Either staged in a temp file and copied into the container on close, or
+/// streamed straight into its ZIP entry by a [`ZipEntryPipeline`].
+enum PeaksOutput {
+    TempFile(ParquetTempFile),
+    Pipeline(PipelineWriter),
+}
+
+impl Write for PeaksOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            PeaksOutput::TempFile(temp_file) => temp_file.write(buf),
+            PeaksOutput::Pipeline(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            PeaksOutput::TempFile(temp_file) => temp_file.flush(),
+            PeaksOutput::Pipeline(writer) => writer.flush(),
+        }
+    }
+}
+
 impl Seek for ParquetTempFile {
     fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
         self.writer.flush()?;
@@ -158,6 +202,19 @@ pub struct DatasetWriterV2Config {
     pub spectra_config: SpectraWriterConfig,
     /// Configuration for the peaks writer
     pub peaks_config: PeaksWriterV2Config,
+    /// Stream peaks.parquet into the container on a background thread while
+    /// it is being written, instead of staging it in a temp file and copying
+    /// it on close. Removes the final packaging pass on large runs.
+    pub pipeline_peaks: bool,
+    /// Encode the spectra and peaks tables on separate worker threads fed
+    /// through bounded queues, instead of on the calling thread.
+    pub parallel_tables: bool,
+    /// Directory for staged temp files. `None` uses `$MZPEAK_TMPDIR` or the
+    /// system temp directory.
+    pub temp_dir: Option<PathBuf>,
+    /// Write the container to `temp_dir` and copy it to the destination on
+    /// close (see [`WriterConfig::stage_locally`](crate::writer::WriterConfig::stage_locally)).
+    pub stage_locally: bool,
 }
 
 impl Default for DatasetWriterV2Config {
@@ -165,6 +222,10 @@ impl Default for DatasetWriterV2Config {
         Self {
             spectra_config: SpectraWriterConfig::default(),
             peaks_config: PeaksWriterV2Config::default(),
+            pipeline_peaks: true,
+            parallel_tables: true,
+            temp_dir: None,
+            stage_locally: false,
         }
     }
 }
@@ -185,14 +246,30 @@ pub struct MzPeakDatasetWriterV2 {
     /// Output path for the container
     output_path: PathBuf,
 
-    /// ZIP writer for the container
-    zip_writer: ZipWriter<BufWriter<File>>,
+    /// ZIP writer for the container (moved to the pipeline thread while
+    /// peaks are streamed)
+    zip_writer: Option<ZipWriter<BufWriter<File>>>,
+
+    /// Background writer streaming peaks.parquet into its ZIP entry
+    peaks_pipeline: Option<ZipEntryPipeline<BufWriter<File>>>,
 
     /// Spectra writer (writes to temp file)
-    spectra_writer: Option<SpectraWriter<ParquetTempFile>>,
+    spectra_writer: Option<TableWriter<SpectrumRow, SpectraWriter<ParquetTempFile>>>,
+
+    /// Peaks writer (writes to temp file or the pipeline)
+    peaks_writer: Option<TableWriter<PeaksRow, PeaksWriterV2<PeaksOutput>>>,
+
+    /// Progress counters of the peaks writer
+    progress: Arc<WriteProgress>,
+
+    /// Scan diagnostics writer, created on first use (writes to temp file)
+    diagnostics_writer: Option<ScanDiagnosticsWriter<ParquetTempFile>>,
+
+    /// Directory for staged temp files
+    temp_dir: PathBuf,
 
-    /// Peaks writer (writes to temp file)
-    peaks_writer: Option<PeaksWriterV2<ParquetTempFile>>,
+    /// Local staging location, moved to `output_path` on close
+    staged: Option<StagedOutput>,
 
     /// Data modality
     modality: Modality,
@@ -203,9 +280,15 @@ pub struct MzPeakDatasetWriterV2 {
     /// Vendor hints for provenance
     vendor_hints: Option<VendorHints>,
 
+    /// Files to store under attachments/ on close
+    attachments: Vec<(Attachment, PathBuf)>,
+
     /// Whether precursor info has been written
     has_precursor_info: bool,
 
+    /// Whether to compute the `spectrum_checksum` column
+    spectrum_checksums: bool,
+
     /// Current peak offset (byte position in peaks file)
     current_peak_offset: u64,
 
@@ -246,29 +329,24 @@ impl MzPeakDatasetWriterV2 {
         vendor_hints: Option<VendorHints>,
         config: DatasetWriterV2Config,
     ) -> Result<Self, DatasetError> {
-        let output_path = path.as_ref().to_path_buf();
-
         // Validate path
-        if output_path.to_string_lossy().is_empty() {
+        if path.as_ref().as_os_str().is_empty() {
             return Err(DatasetError::InvalidPath("Empty path".to_string()));
         }
+        let output_path = normalize_path(path.as_ref());
 
         // Check if file already exists
         if output_path.exists() {
             return Err(DatasetError::AlreadyExists(
-                output_path.to_string_lossy().to_string(),
+                path.as_ref().to_string_lossy().to_string(),
             ));
         }
 
-        // Create parent directories if needed
-        if let Some(parent) = output_path.parent() {
-            if !parent.as_os_str().is_empty() && !parent.exists() {
-                fs::create_dir_all(parent)?;
-            }
-        }
+        // Fail early on read-only destinations; optionally write locally first
+        let staged = prepare_output(&output_path, config.temp_dir.as_deref(), config.stage_locally)?;
 
         // Create ZIP file
-        let file = File::create(&output_path)?;
+        let file = File::create(staged.as_ref().map_or(output_path.as_path(), StagedOutput::path))?;
         let buf_writer = BufWriter::new(file);
         let mut zip_writer = ZipWriter::new(buf_writer);
 
@@ -279,24 +357,57 @@ impl MzPeakDatasetWriterV2 {
         zip_writer.start_file("mimetype", options)?;
         zip_writer.write_all(MZPEAK_V2_MIMETYPE.as_bytes())?;
 
+        let spectrum_checksums = config.spectra_config.spectrum_checksums;
+
         // Initialize spectra writer to temp file
-        let spectra_buffer = ParquetTempFile::new()?;
+        let temp_dir = resolve_temp_dir(config.temp_dir.as_deref())?;
+        let spectra_buffer = ParquetTempFile::new_in(&temp_dir)?;
         let spectra_writer = SpectraWriter::new(spectra_buffer, &config.spectra_config)?;
 
-        // Initialize peaks writer to temp file
+        // Initialize peaks writer, streaming into the container if pipelined
+        let (zip_writer, peaks_pipeline, peaks_output) = if config.pipeline_peaks {
+            let (pipeline, writer) = ZipEntryPipeline::start(
+                zip_writer,
+                "peaks/peaks.parquet",
+                DEFAULT_PIPELINE_CHUNK_SIZE,
+            )?;
+            (None, Some(pipeline), PeaksOutput::Pipeline(writer))
+        } else {
+            let temp_file = ParquetTempFile::new_in(&temp_dir)?;
+            (Some(zip_writer), None, PeaksOutput::TempFile(temp_file))
+        };
         let has_ion_mobility = modality.has_ion_mobility();
-        let peaks_buffer = ParquetTempFile::new()?;
-        let peaks_writer = PeaksWriterV2::new(peaks_buffer, &config.peaks_config, has_ion_mobility)?;
+        let peaks_writer = PeaksWriterV2::new(peaks_output, &config.peaks_config, has_ion_mobility)?;
+        let progress = peaks_writer.progress();
+
+        let (spectra_writer, peaks_writer) = if config.parallel_tables {
+            (
+                TableWriter::spawn("mzpeak-spectra", spectra_writer, write_spectrum_row)?,
+                TableWriter::spawn("mzpeak-peaks", peaks_writer, write_peaks_row)?,
+            )
+        } else {
+            (
+                TableWriter::inline(spectra_writer, write_spectrum_row),
+                TableWriter::inline(peaks_writer, write_peaks_row),
+            )
+        };
 
         Ok(Self {
             output_path,
             zip_writer,
+            peaks_pipeline,
             spectra_writer: Some(spectra_writer),
             peaks_writer: Some(peaks_writer),
+            progress,
+            diagnostics_writer: None,
+            temp_dir,
+            staged,
             modality,
             metadata: None,
             vendor_hints,
+            attachments: Vec::new(),
             has_precursor_info: false,
+            spectrum_checksums,
             current_peak_offset: 0,
             peaks_written: 0,
             spectra_written: 0,
@@ -334,14 +445,23 @@ impl MzPeakDatasetWriterV2 {
             .spectra_writer
             .as_mut()
             .ok_or(DatasetError::NotInitialized)?;
-        spectra_writer.write_spectrum_metadata_with_offset(metadata, self.current_peak_offset)?;
+        let checksum = self
+            .spectrum_checksums
+            .then(|| spectrum_checksum(metadata, &peaks.mz, &peaks.intensity));
+        spectra_writer.write((metadata.clone(), self.current_peak_offset, checksum))?;
 
         // Write peaks
         let peaks_writer = self
             .peaks_writer
             .as_mut()
             .ok_or(DatasetError::NotInitialized)?;
-        peaks_writer.write_peaks(metadata.spectrum_id, peaks)?;
+        if peaks.is_empty() {
+            // The peaks writer only counts spectra that have peaks
+            self.progress.record(1, 0, 0);
+        } else {
+            peaks_writer.write((metadata.spectrum_id, peaks.clone()))?;
+        }
+        self.progress.set_current_rt(metadata.retention_time);
 
         // Update offset tracking
         // Note: We track row count, not byte offset. The peak_offset column
@@ -366,18 +486,86 @@ impl MzPeakDatasetWriterV2 {
         Ok(())
     }
 
+    /// Write instrument diagnostics for one scan.
+    ///
+    /// Stored as key-value rows in `diagnostics/scan_diagnostics.parquet`,
+    /// which is only created once diagnostics are written.
+    pub fn write_scan_diagnostics(
+        &mut self,
+        diagnostics: &ScanDiagnostics,
+    ) -> Result<(), DatasetError> {
+        if self.finalized {
+            return Err(DatasetError::NotInitialized);
+        }
+
+        if self.diagnostics_writer.is_none() {
+            let empty = MzPeakMetadata::default();
+            let metadata = self.metadata.as_ref().unwrap_or(&empty);
+            self.diagnostics_writer = Some(
+                ScanDiagnosticsWriter::new(
+                    ParquetTempFile::new_in(&self.temp_dir)?,
+                    metadata,
+                    ScanDiagnosticsWriterConfig::default(),
+                )
+                .map_err(|e| DatasetError::ScanDiagnosticsWriterError(e.to_string()))?,
+            );
+        }
+        if let Some(writer) = self.diagnostics_writer.as_mut() {
+            writer
+                .write_scan(diagnostics)
+                .map_err(|e| DatasetError::ScanDiagnosticsWriterError(e.to_string()))?;
+        }
+        Ok(())
+    }
+
     /// Get current statistics (without closing).
     pub fn stats(&self) -> (u64, u64) {
         (self.spectra_written, self.peaks_written)
     }
 
+    /// Live progress counters, readable from other threads while writing
+    ///
+    /// With [`DatasetWriterV2Config::parallel_tables`] the spectrum and peak
+    /// counts trail the written spectra by the rows still queued for the
+    /// peaks worker.
+    pub fn progress(&self) -> Option<Arc<WriteProgress>> {
+        Some(self.progress.clone())
+    }
+
+    /// Running statistics: throughput, current retention time and, when the
+    /// expected size is known, estimated completion
+    pub fn stats_snapshot(&self) -> Option<StatsSnapshot> {
+        Some(self.progress.snapshot())
+    }
+
     /// Get the data modality.
     pub fn modality(&self) -> Modality {
         self.modality
     }
 
+    /// Attach a file (method export, screenshot, script) to the container.
+    ///
+    /// The file is registered in the manifest and copied under `attachments/`
+    /// when the dataset is closed, so it must still exist at that point.
+    pub fn add_attachment<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        options: &AttachOptions,
+    ) -> Result<Attachment, DatasetError> {
+        let path = path.as_ref();
+        let attachment = describe_attachment(path, options)?;
+        if self.attachments.iter().any(|(a, _)| a.name == attachment.name) {
+            return Err(DatasetError::AttachmentError(format!(
+                "'{}' is already attached",
+                attachment.name
+            )));
+        }
+        self.attachments.push((attachment.clone(), path.to_path_buf()));
+        Ok(attachment)
+    }
+
     /// Build the manifest JSON content.
-    fn build_manifest(&self) -> Manifest {
+    fn build_manifest(&self, layout_stats: Option<LayoutStats>) -> Manifest {
         let created = chrono::Utc::now().to_rfc3339();
         let converter = format!("mzpeak-rs v{}", env!("CARGO_PKG_VERSION"));
 
@@ -391,69 +579,20 @@ impl MzPeakDatasetWriterV2 {
         );
 
         manifest.vendor_hints = self.vendor_hints.clone();
+        if self.modality.has_imaging() {
+            manifest.imaging = self.metadata.as_ref().and_then(|m| m.imaging.clone());
+        }
+        manifest.attachments = self.attachments.iter().map(|(a, _)| a.clone()).collect();
+        manifest.layout_stats = layout_stats;
 
         manifest
     }
 
     /// Build the metadata JSON content.
     fn build_metadata_json(&self) -> Result<String, DatasetError> {
-        let mut json_map = serde_json::Map::new();
-
-        json_map.insert(
-            "format_version".to_string(),
-            serde_json::Value::String("2.0".to_string()),
-        );
-
-        json_map.insert(
-            "created".to_string(),
-            serde_json::Value::String(chrono::Utc::now().to_rfc3339()),
-        );
-
-        json_map.insert(
-            "converter".to_string(),
-            serde_json::Value::String(format!("mzpeak-rs v{}", env!("CARGO_PKG_VERSION"))),
-        );
-
-        // Add optional MzPeakMetadata fields if present
-        if let Some(ref metadata) = self.metadata {
-            if let Some(ref sdrf) = metadata.sdrf {
-                let sdrf_json = serde_json::to_value(sdrf)?;
-                json_map.insert("sdrf".to_string(), sdrf_json);
-            }
-
-            if let Some(ref instrument) = metadata.instrument {
-                let instrument_json = serde_json::to_value(instrument)?;
-                json_map.insert("instrument".to_string(), instrument_json);
-            }
-
-            if let Some(ref lc) = metadata.lc_config {
-                let lc_json = serde_json::to_value(lc)?;
-                json_map.insert("lc_config".to_string(), lc_json);
-            }
-
-            if let Some(ref run) = metadata.run_parameters {
-                let run_json = serde_json::to_value(run)?;
-                json_map.insert("run_parameters".to_string(), run_json);
-            }
-
-            if let Some(ref source) = metadata.source_file {
-                let source_json = serde_json::to_value(source)?;
-                json_map.insert("source_file".to_string(), source_json);
-            }
-
-            if let Some(ref history) = metadata.processing_history {
-                let history_json = serde_json::to_value(history)?;
-                json_map.insert("processing_history".to_string(), history_json);
-            }
-
-            if let Some(ref hints) = metadata.vendor_hints {
-                let hints_json = serde_json::to_value(hints)?;
-                json_map.insert("vendor_hints".to_string(), hints_json);
-            }
-        }
-
-        let json_value = serde_json::Value::Object(json_map);
-        Ok(serde_json::to_string_pretty(&json_value)?)
+        let empty = MzPeakMetadata::default();
+        let metadata = self.metadata.as_ref().unwrap_or(&empty);
+        Ok(metadata.to_metadata_json(MZPEAK_V2_SCHEMA_VERSION)?)
     }
 
     /// Close the dataset and finalize all writers.
@@ -471,16 +610,13 @@ impl MzPeakDatasetWriterV2 {
             return Err(DatasetError::NotInitialized);
         }
 
-        // Build JSON content before consuming writers
-        let manifest = self.build_manifest();
-        let manifest_json = serde_json::to_string_pretty(&manifest)?;
         let metadata_json = self.build_metadata_json()?;
 
         // Finalize spectra writer
         let spectra_stats;
         let spectra_reader;
         if let Some(writer) = self.spectra_writer.take() {
-            let temp_file = writer.finish_into_inner()?;
+            let temp_file = writer.finish()?.finish_into_inner()?;
             let (size, reader) = temp_file.into_reader()?;
             spectra_stats = SpectraWriterStats {
                 spectra_written: self.spectra_written,
@@ -492,47 +628,78 @@ impl MzPeakDatasetWriterV2 {
             return Err(DatasetError::NotInitialized);
         }
 
-        // Finalize peaks writer
-        let peaks_stats;
-        let peaks_reader;
-        if let Some(writer) = self.peaks_writer.take() {
-            let temp_file = writer.finish_into_inner()?;
-            let (size, reader) = temp_file.into_reader()?;
-            peaks_stats = PeaksWriterV2Stats {
-                peaks_written: self.peaks_written,
-                spectra_written: self.spectra_written,
-                row_groups_written: 0,
-                file_size_bytes: size,
+        // Finalize peaks writer; a pipelined entry is already in the container
+        let peaks_writer = self
+            .peaks_writer
+            .take()
+            .ok_or(DatasetError::NotInitialized)?
+            .finish()?;
+        let layout_stats = peaks_writer.layout_stats();
+        let row_groups_written = layout_stats.as_ref().map_or(0, |layout| layout.row_groups.len());
+        let manifest = self.build_manifest(layout_stats);
+        let manifest_json = serde_json::to_string_pretty(&manifest)?;
+        let peaks_output = peaks_writer.finish_into_inner()?;
+        let (mut zip_writer, peaks_reader, peaks_size) =
+            match (peaks_output, self.peaks_pipeline.take()) {
+                (PeaksOutput::Pipeline(writer), Some(pipeline)) => {
+                    let (zip_writer, entry) = pipeline.finish(writer)?;
+                    (zip_writer, None, entry.bytes)
+                }
+                (PeaksOutput::TempFile(temp_file), None) => {
+                    let (size, reader) = temp_file.into_reader()?;
+                    let zip_writer = self.zip_writer.take().ok_or(DatasetError::NotInitialized)?;
+                    (zip_writer, Some(reader), size)
+                }
+                _ => return Err(DatasetError::NotInitialized),
             };
-            peaks_reader = reader;
-        } else {
-            return Err(DatasetError::NotInitialized);
-        }
+        let peaks_stats = PeaksWriterV2Stats {
+            peaks_written: self.peaks_written,
+            spectra_written: self.spectra_written,
+            row_groups_written,
+            file_size_bytes: peaks_size,
+        };
 
         // Write manifest.json (Deflate compressed)
         let options = SimpleFileOptions::default()
             .compression_method(CompressionMethod::Deflated)
             .unix_permissions(0o644);
-        self.zip_writer.start_file("manifest.json", options)?;
-        self.zip_writer.write_all(manifest_json.as_bytes())?;
+        zip_writer.start_file("manifest.json", options)?;
+        zip_writer.write_all(manifest_json.as_bytes())?;
 
         // Write metadata.json (Deflate compressed)
-        self.zip_writer.start_file("metadata.json", options)?;
-        self.zip_writer.write_all(metadata_json.as_bytes())?;
+        zip_writer.start_file("metadata.json", options)?;
+        zip_writer.write_all(metadata_json.as_bytes())?;
 
         // Write spectra/spectra.parquet (MUST be uncompressed/Stored for seekability)
         let options = SimpleFileOptions::default()
             .compression_method(CompressionMethod::Stored)
             .unix_permissions(0o644);
-        self.zip_writer.start_file("spectra/spectra.parquet", options)?;
-        stream_copy_to_zip(spectra_reader, &mut self.zip_writer)?;
+        zip_writer.start_file("spectra/spectra.parquet", options)?;
+        stream_copy_to_zip(spectra_reader, &mut zip_writer)?;
 
         // Write peaks/peaks.parquet (MUST be uncompressed/Stored for seekability)
-        self.zip_writer.start_file("peaks/peaks.parquet", options)?;
-        stream_copy_to_zip(peaks_reader, &mut self.zip_writer)?;
+        if let Some(peaks_reader) = peaks_reader {
+            zip_writer.start_file("peaks/peaks.parquet", options)?;
+            stream_copy_to_zip(peaks_reader, &mut zip_writer)?;
+        }
+
+        // Write diagnostics/scan_diagnostics.parquet (Stored, like the other tables)
+        if let Some(writer) = self.diagnostics_writer.take() {
+            let (_, diagnostics_reader) = writer
+                .finish_into_inner()
+                .map_err(|e| DatasetError::ScanDiagnosticsWriterError(e.to_string()))?
+                .into_reader()?;
+            zip_writer.start_file(SCAN_DIAGNOSTICS_PATH, options)?;
+            stream_copy_to_zip(diagnostics_reader, &mut zip_writer)?;
+        }
+
+        // Write attachments (Deflate compressed)
+        for (attachment, path) in &self.attachments {
+            write_attachment_entry(&mut zip_writer, attachment, path)?;
+        }
 
         // Finalize the ZIP archive
-        let inner = self.zip_writer.finish()?;
+        let inner = zip_writer.finish()?;
         inner.into_inner().map_err(|e| {
             DatasetError::IoError(std::io::Error::new(
                 std::io::ErrorKind::Other,
@@ -540,6 +707,10 @@ impl MzPeakDatasetWriterV2 {
             ))
         })?;
 
+        if let Some(staged) = self.staged.take() {
+            staged.commit()?;
+        }
+
         // Get final file size
         let total_size = fs::metadata(&self.output_path)?.len();
 
@@ -558,6 +729,33 @@ impl MzPeakDatasetWriterV2 {
     }
 }
 
+/// Row of the spectra table: metadata, the spectrum's first peak row and its
+/// content checksum if enabled
+type SpectrumRow = (SpectrumMetadata, u64, Option<u64>);
+
+/// Rows of the peaks table for one spectrum
+type PeaksRow = (u32, PeakArraysV2);
+
+fn write_spectrum_row(
+    writer: &mut SpectraWriter<ParquetTempFile>,
+    (metadata, peak_offset, checksum): SpectrumRow,
+) -> Result<(), DatasetError> {
+    match checksum {
+        Some(checksum) => {
+            writer.write_spectrum_metadata_with_checksum(&metadata, peak_offset, checksum)?
+        }
+        None => writer.write_spectrum_metadata_with_offset(&metadata, peak_offset)?,
+    }
+    Ok(())
+}
+
+fn write_peaks_row(
+    writer: &mut PeaksWriterV2<PeaksOutput>,
+    (spectrum_id, peaks): PeaksRow,
+) -> Result<(), DatasetError> {
+    Ok(writer.write_peaks(spectrum_id, &peaks)?)
+}
+
 /// Copy data from a reader to a ZIP writer with bounded memory.
 const STREAM_COPY_BUFFER_SIZE: usize = 64 * 1024;
 
@@ -704,6 +902,7 @@ mod tests {
             original_format: Some("RAW".to_string()),
             instrument_model: Some("Q Exactive HF".to_string()),
             conversion_path: vec!["RAW".to_string(), "mzML".to_string(), "mzpeak".to_string()],
+            unknown_fields: Default::default(),
         };
 
         let mut writer = MzPeakDatasetWriterV2::new(&output_path, Modality::LcMs, Some(vendor_hints))
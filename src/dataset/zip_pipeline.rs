@@ -0,0 +1,162 @@
+//! Pipelined ZIP entry writing
+//!
+//! Streams bytes produced on one thread into a stored (uncompressed) ZIP entry
+//! on a background thread. The producer hands off fixed-size chunks over a
+//! bounded channel, so Parquet encoding overlaps with disk writes and the
+//! container needs no final copy from a temporary file. The CRC-32 and size
+//! of the entry are accumulated as chunks arrive.
+
+use std::io::{Seek, Write};
+use std::thread::JoinHandle;
+
+use crossbeam_channel::{bounded, Sender};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Default size of the chunks handed to the ZIP writer thread
+pub(crate) const DEFAULT_PIPELINE_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
+/// Number of chunks that may be queued before the producer blocks
+const PIPELINE_CAPACITY: usize = 4;
+
+/// Size and checksum of a pipelined entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PipelinedEntryStats {
+    /// Bytes written to the entry
+    pub bytes: u64,
+    /// CRC-32 of the entry content
+    pub crc32: u32,
+}
+
+type PipelineResult<W> = std::io::Result<(ZipWriter<W>, PipelinedEntryStats)>;
+
+/// Background thread writing one ZIP entry
+pub(crate) struct ZipEntryPipeline<W: Write + Seek + Send + 'static> {
+    handle: JoinHandle<PipelineResult<W>>,
+}
+
+/// Producer side of a [`ZipEntryPipeline`]
+pub(crate) struct PipelineWriter {
+    sender: Option<Sender<Vec<u8>>>,
+    buffer: Vec<u8>,
+    chunk_size: usize,
+}
+
+impl<W: Write + Seek + Send + 'static> ZipEntryPipeline<W> {
+    /// Start a stored entry and move the ZIP writer onto a background thread
+    pub(crate) fn start(
+        mut zip_writer: ZipWriter<W>,
+        entry_name: &str,
+        chunk_size: usize,
+    ) -> zip::result::ZipResult<(Self, PipelineWriter)> {
+        // Entry size is unknown up front, so always allow ZIP64 sizes
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Stored)
+            .large_file(true)
+            .unix_permissions(0o644);
+        zip_writer.start_file(entry_name, options)?;
+
+        let (sender, receiver) = bounded::<Vec<u8>>(PIPELINE_CAPACITY);
+        let handle = std::thread::spawn(move || {
+            let mut hasher = crc32fast::Hasher::new();
+            let mut bytes = 0u64;
+            for chunk in receiver {
+                zip_writer.write_all(&chunk)?;
+                hasher.update(&chunk);
+                bytes += chunk.len() as u64;
+            }
+            let stats = PipelinedEntryStats {
+                bytes,
+                crc32: hasher.finalize(),
+            };
+            Ok((zip_writer, stats))
+        });
+
+        let writer = PipelineWriter {
+            sender: Some(sender),
+            buffer: Vec::with_capacity(chunk_size),
+            chunk_size: chunk_size.max(1),
+        };
+        Ok((Self { handle }, writer))
+    }
+
+    /// Flush the remaining bytes and take the ZIP writer back
+    pub(crate) fn finish(self, mut writer: PipelineWriter) -> PipelineResult<W> {
+        let flushed = writer.send_buffer();
+        writer.sender = None;
+        let result = self.handle.join().map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::Other, "ZIP writer thread panicked")
+        })?;
+        // A send error only means the writer thread stopped; report its error first
+        let (zip_writer, stats) = result?;
+        flushed?;
+        Ok((zip_writer, stats))
+    }
+}
+
+impl PipelineWriter {
+    fn send_buffer(&mut self) -> std::io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(self.chunk_size));
+        match &self.sender {
+            Some(sender) => sender.send(chunk).map_err(|_| {
+                std::io::Error::new(
+                    std::io::ErrorKind::BrokenPipe,
+                    "ZIP writer thread stopped",
+                )
+            }),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "pipeline already finished",
+            )),
+        }
+    }
+}
+
+impl Write for PipelineWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= self.chunk_size {
+            self.send_buffer()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        // Chunks are handed off when full or on finish; flushing a partial
+        // chunk would only add channel traffic.
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Read};
+
+    #[test]
+    fn test_pipeline_writes_entry_with_crc() {
+        let zip_writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let (pipeline, mut writer) =
+            ZipEntryPipeline::start(zip_writer, "peaks/peaks.parquet", 7).unwrap();
+
+        let payload: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+        for chunk in payload.chunks(13) {
+            writer.write_all(chunk).unwrap();
+        }
+        let (zip_writer, stats) = pipeline.finish(writer).unwrap();
+        assert_eq!(stats.bytes, 1000);
+        assert_eq!(stats.crc32, crc32fast::hash(&payload));
+
+        let cursor = zip_writer.finish().unwrap();
+        let mut archive = zip::ZipArchive::new(cursor).unwrap();
+        let mut entry = archive.by_name("peaks/peaks.parquet").unwrap();
+        assert_eq!(entry.compression(), CompressionMethod::Stored);
+        assert_eq!(entry.crc32(), stats.crc32);
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content).unwrap();
+        assert_eq!(content, payload);
+    }
+}
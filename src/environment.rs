@@ -0,0 +1,355 @@
+//! Runtime environment checks
+//!
+//! Some capabilities depend on the machine as well as on the build: Thermo
+//! RAW reading needs an x86 CPU and the .NET 8 runtime, the SIMD m/z kernels
+//! need AVX2, and io_uring reads need a kernel that allows them. Each of
+//! these falls back or fails with an actionable message at runtime; this
+//! module reports the same conditions up front (`mzpeak doctor`).
+//!
+//! ```rust
+//! use mzpeak::environment::{check_environment, CheckStatus};
+//!
+//! for check in check_environment() {
+//!     if check.status != CheckStatus::Ok {
+//!         println!("{}", check);
+//!     }
+//! }
+//! ```
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// .NET runtime major version required by Thermo's RawFileReader
+pub const REQUIRED_DOTNET_MAJOR: u32 = 8;
+
+/// Download page of the .NET runtime required for Thermo RAW files
+pub const DOTNET_DOWNLOAD_URL: &str = "https://dotnet.microsoft.com/download/dotnet/8.0";
+
+/// Outcome of an environment check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// Fully available
+    Ok,
+    /// Works through a slower fallback
+    Degraded,
+    /// Not usable in this build or on this machine
+    Unavailable,
+}
+
+/// One capability and whether this machine provides it
+#[derive(Debug, Clone)]
+pub struct EnvironmentCheck {
+    /// Capability checked
+    pub name: &'static str,
+    /// Outcome
+    pub status: CheckStatus,
+    /// What was found
+    pub detail: String,
+    /// How to enable the capability, if it is not fully available
+    pub hint: Option<String>,
+}
+
+impl EnvironmentCheck {
+    fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+impl fmt::Display for EnvironmentCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self.status {
+            CheckStatus::Ok => "✓",
+            CheckStatus::Degraded => "⚠",
+            CheckStatus::Unavailable => "✗",
+        };
+        write!(f, "[{}] {}: {}", symbol, self.name, self.detail)?;
+        if let Some(hint) = &self.hint {
+            write!(f, "\n    -> {}", hint)?;
+        }
+        Ok(())
+    }
+}
+
+/// An installed .NET runtime
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DotnetRuntime {
+    /// Runtime version, e.g. `8.0.11`
+    pub version: String,
+    /// Installation root (the directory containing `shared/`)
+    pub root: PathBuf,
+}
+
+/// Newest installed .NET runtime (`Microsoft.NETCore.App`) of a major version
+///
+/// Looks in `DOTNET_ROOT`, next to a `dotnet` executable on `PATH`, in
+/// `~/.dotnet` and in the platform's default installation directories.
+pub fn find_dotnet_runtime(major: u32) -> Option<DotnetRuntime> {
+    newest_runtime(&dotnet_roots(), major)
+}
+
+/// Candidate .NET installation roots, most specific first
+fn dotnet_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    for var in ["DOTNET_ROOT", "DOTNET_ROOT_X64"] {
+        if let Some(root) = std::env::var_os(var) {
+            roots.push(PathBuf::from(root));
+        }
+    }
+    if let Some(path) = std::env::var_os("PATH") {
+        for dir in std::env::split_paths(&path) {
+            let executable = dir.join(if cfg!(windows) {
+                "dotnet.exe"
+            } else {
+                "dotnet"
+            });
+            if let Ok(resolved) = executable.canonicalize() {
+                roots.extend(resolved.parent().map(Path::to_path_buf));
+            }
+        }
+    }
+    if let Some(home) = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) {
+        roots.push(PathBuf::from(home).join(".dotnet"));
+    }
+    let defaults: &[&str] = if cfg!(windows) {
+        &[r"C:\Program Files\dotnet", r"C:\Program Files (x86)\dotnet"]
+    } else if cfg!(target_os = "macos") {
+        &["/usr/local/share/dotnet"]
+    } else {
+        &[
+            "/usr/share/dotnet",
+            "/usr/lib/dotnet",
+            "/usr/lib64/dotnet",
+            "/opt/dotnet",
+        ]
+    };
+    roots.extend(defaults.iter().map(PathBuf::from));
+    roots
+}
+
+/// Newest runtime of `major` under any of `roots`
+fn newest_runtime(roots: &[PathBuf], major: u32) -> Option<DotnetRuntime> {
+    let prefix = format!("{}.", major);
+    roots
+        .iter()
+        .filter_map(|root| {
+            let entries =
+                std::fs::read_dir(root.join("shared").join("Microsoft.NETCore.App")).ok()?;
+            entries
+                .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                .filter(|version| version.starts_with(&prefix))
+                .max_by_key(|version| version_key(version))
+                .map(|version| DotnetRuntime {
+                    version,
+                    root: root.clone(),
+                })
+        })
+        .max_by_key(|runtime| version_key(&runtime.version))
+}
+
+/// Numeric sort key of a dotted version (`8.0.11` > `8.0.4`)
+fn version_key(version: &str) -> Vec<u64> {
+    version
+        .split(['.', '-'])
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+/// Whether the AVX2 m/z kernels are used on this CPU
+pub fn has_avx2() -> bool {
+    #[cfg(target_arch = "x86_64")]
+    {
+        std::is_x86_feature_detected!("avx2")
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        false
+    }
+}
+
+/// Actionable message for a missing .NET runtime, `None` if one is installed
+///
+/// Used to explain RAW file open failures instead of passing on the loader's
+/// error.
+pub fn missing_dotnet_message() -> Option<String> {
+    if find_dotnet_runtime(REQUIRED_DOTNET_MAJOR).is_some() {
+        return None;
+    }
+    Some(format!(
+        "the .NET {} runtime was not found; install it from {} or set DOTNET_ROOT \
+         to its location (run `mzpeak doctor` to check)",
+        REQUIRED_DOTNET_MAJOR, DOTNET_DOWNLOAD_URL
+    ))
+}
+
+/// Check every optional runtime requirement
+pub fn check_environment() -> Vec<EnvironmentCheck> {
+    vec![
+        check_simd(),
+        check_thermo(),
+        check_tdf(),
+        check_parallel_decode(),
+        check_io_uring(),
+    ]
+}
+
+fn check_simd() -> EnvironmentCheck {
+    const NAME: &str = "SIMD m/z kernels";
+    if has_avx2() {
+        EnvironmentCheck::new(NAME, CheckStatus::Ok, "AVX2 available")
+    } else {
+        EnvironmentCheck::new(
+            NAME,
+            CheckStatus::Degraded,
+            format!(
+                "no AVX2 on this {} CPU; scalar kernels are used",
+                std::env::consts::ARCH
+            ),
+        )
+        .with_hint("m/z range queries and XIC extraction run slower but give the same results")
+    }
+}
+
+fn check_thermo() -> EnvironmentCheck {
+    const NAME: &str = "Thermo RAW";
+    if !cfg!(feature = "thermo") {
+        return EnvironmentCheck::new(
+            NAME,
+            CheckStatus::Unavailable,
+            "not compiled into this build",
+        )
+        .with_hint("rebuild with `--features thermo`");
+    }
+    if !cfg!(any(target_arch = "x86", target_arch = "x86_64")) {
+        return EnvironmentCheck::new(
+            NAME,
+            CheckStatus::Unavailable,
+            format!(
+                "Thermo's RawFileReader does not support {} CPUs",
+                std::env::consts::ARCH
+            ),
+        )
+        .with_hint("convert on an x86_64 machine, or export mzML with ThermoRawFileParser");
+    }
+    match find_dotnet_runtime(REQUIRED_DOTNET_MAJOR) {
+        Some(runtime) => EnvironmentCheck::new(
+            NAME,
+            CheckStatus::Ok,
+            format!(".NET {} in {}", runtime.version, runtime.root.display()),
+        ),
+        None => EnvironmentCheck::new(
+            NAME,
+            CheckStatus::Unavailable,
+            format!(".NET {} runtime not found", REQUIRED_DOTNET_MAJOR),
+        )
+        .with_hint(format!(
+            "install it from {} or set DOTNET_ROOT to its location",
+            DOTNET_DOWNLOAD_URL
+        )),
+    }
+}
+
+fn check_tdf() -> EnvironmentCheck {
+    const NAME: &str = "Bruker TDF";
+    if cfg!(feature = "tdf") {
+        EnvironmentCheck::new(
+            NAME,
+            CheckStatus::Ok,
+            "built-in reader, no Bruker SDK or system SQLite needed",
+        )
+    } else {
+        EnvironmentCheck::new(
+            NAME,
+            CheckStatus::Unavailable,
+            "not compiled into this build",
+        )
+        .with_hint("rebuild with `--features tdf`")
+    }
+}
+
+fn check_parallel_decode() -> EnvironmentCheck {
+    const NAME: &str = "Parallel mzML decoding";
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    if !cfg!(feature = "mzml-parallel") {
+        EnvironmentCheck::new(
+            NAME,
+            CheckStatus::Unavailable,
+            "not compiled into this build",
+        )
+        .with_hint("rebuild with `--features mzml-parallel`")
+    } else if threads > 1 {
+        EnvironmentCheck::new(NAME, CheckStatus::Ok, format!("{} threads", threads))
+    } else {
+        EnvironmentCheck::new(
+            NAME,
+            CheckStatus::Degraded,
+            "a single CPU thread is available",
+        )
+    }
+}
+
+fn check_io_uring() -> EnvironmentCheck {
+    const NAME: &str = "io_uring reads";
+    #[cfg(all(feature = "uring", target_os = "linux"))]
+    {
+        if crate::reader::uring_available() {
+            EnvironmentCheck::new(NAME, CheckStatus::Ok, "available")
+        } else {
+            EnvironmentCheck::new(
+                NAME,
+                CheckStatus::Degraded,
+                "refused by the kernel or sandbox; positioned reads are used",
+            )
+            .with_hint("check `kernel.io_uring_disabled` and the container's seccomp profile")
+        }
+    }
+    #[cfg(not(all(feature = "uring", target_os = "linux")))]
+    {
+        EnvironmentCheck::new(
+            NAME,
+            CheckStatus::Unavailable,
+            "not compiled into this build; positioned reads are used",
+        )
+        .with_hint("rebuild with `--features uring` on Linux")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newest_runtime_by_numeric_version() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let shared = dir.path().join("shared").join("Microsoft.NETCore.App");
+        for version in ["6.0.30", "8.0.4", "8.0.11", "9.0.0"] {
+            std::fs::create_dir_all(shared.join(version))?;
+        }
+        let roots = [dir.path().join("missing"), dir.path().to_path_buf()];
+
+        let runtime = newest_runtime(&roots, 8).ok_or("runtime 8")?;
+        assert_eq!(runtime.version, "8.0.11");
+        assert_eq!(runtime.root, dir.path());
+        assert!(newest_runtime(&roots, 7).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_environment_reports_every_capability() {
+        let checks = check_environment();
+        assert_eq!(checks.len(), 5);
+        for check in &checks {
+            assert!(!check.detail.is_empty());
+            assert!(check.to_string().contains(check.name));
+        }
+    }
+}
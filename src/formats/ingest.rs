@@ -1,5 +1,9 @@
 //! Thin-waist ingestion contract types and validation.
 
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+
 use crate::writer::{OptionalColumnBuf, PeakArrays, SpectrumArrays, WriterError};
 
 /// Errors returned when the ingestion contract is violated.
@@ -8,6 +12,14 @@ pub enum IngestError {
     /// Contract violation with a human-readable message.
     #[error("ingest contract violation: {0}")]
     ContractViolation(String),
+    /// A processing step failed or broke the peak arrays.
+    #[error("processing step '{step}' failed: {message}")]
+    StepFailed {
+        /// Name of the step.
+        step: String,
+        /// Reason reported by the step.
+        message: String,
+    },
 }
 
 impl IngestError {
@@ -22,11 +34,126 @@ impl From<IngestError> for WriterError {
     }
 }
 
+/// Default largest retention time decrease treated as benign jitter, in seconds
+pub const DEFAULT_RT_JITTER_TOLERANCE: f32 = 0.5;
+
+/// Retention time ordering policy for a spectrum stream
+///
+/// Some vendor streams emit spectra whose retention times are slightly out of
+/// order. A decrease of at most `jitter_tolerance` seconds below the latest
+/// retention time is benign jitter; a larger decrease breaks the ordering.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RtOrderConfig {
+    /// Largest retention time decrease treated as jitter, in seconds
+    pub jitter_tolerance: f32,
+    /// Stable re-sort jittered spectra by retention time before writing
+    pub repair: bool,
+    /// Reject streams whose retention time decreases by more than the tolerance
+    pub strict: bool,
+}
+
+impl Default for RtOrderConfig {
+    fn default() -> Self {
+        Self {
+            jitter_tolerance: DEFAULT_RT_JITTER_TOLERANCE,
+            repair: false,
+            strict: false,
+        }
+    }
+}
+
+/// Position of a spectrum's retention time relative to its predecessors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RtStep {
+    /// At or after the latest retention time
+    InOrder,
+    /// Before the latest retention time, within the jitter tolerance
+    Jitter,
+    /// Before the latest retention time by more than the jitter tolerance
+    Break,
+}
+
+/// Classifies the retention times of a spectrum stream
+///
+/// Retention times are compared with the latest (largest) one seen since the
+/// last break, so a run of jittered spectra is measured against the same
+/// reference. A break starts a new reference.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RtOrderTracker {
+    tolerance: f32,
+    latest_rt: Option<f32>,
+    jitter_count: usize,
+    break_count: usize,
+    max_jitter: f32,
+}
+
+impl Default for RtOrderTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_RT_JITTER_TOLERANCE)
+    }
+}
+
+impl RtOrderTracker {
+    /// Create a tracker treating decreases up to `tolerance` seconds as jitter
+    pub fn new(tolerance: f32) -> Self {
+        Self {
+            tolerance: tolerance.max(0.0),
+            latest_rt: None,
+            jitter_count: 0,
+            break_count: 0,
+            max_jitter: 0.0,
+        }
+    }
+
+    /// Classify the next retention time of the stream
+    pub fn observe(&mut self, rt: f32) -> RtStep {
+        let latest = match self.latest_rt {
+            Some(latest) if rt < latest => latest,
+            _ => {
+                self.latest_rt = Some(rt);
+                return RtStep::InOrder;
+            }
+        };
+        let decrease = latest - rt;
+        if decrease <= self.tolerance {
+            self.jitter_count += 1;
+            self.max_jitter = self.max_jitter.max(decrease);
+            RtStep::Jitter
+        } else {
+            self.break_count += 1;
+            self.latest_rt = Some(rt);
+            RtStep::Break
+        }
+    }
+
+    /// Jitter tolerance in seconds
+    pub fn tolerance(&self) -> f32 {
+        self.tolerance
+    }
+
+    /// Number of spectra within the tolerance before the latest retention time
+    pub fn jitter_count(&self) -> usize {
+        self.jitter_count
+    }
+
+    /// Number of retention time decreases beyond the tolerance
+    pub fn break_count(&self) -> usize {
+        self.break_count
+    }
+
+    /// Largest jitter seen, in seconds
+    pub fn max_jitter(&self) -> f32 {
+        self.max_jitter
+    }
+}
+
 /// Thin-waist ingestion contract for a single spectrum.
 ///
 /// Invariants:
 /// - Peak arrays have identical lengths.
 /// - Spectrum IDs are contiguous in stream order (checked by `IngestSpectrumConverter`).
+/// - Retention times never decrease by more than the jitter tolerance when
+///   strict retention time ordering is requested (checked by `IngestSpectrumConverter`).
 /// - Units match the contract (RT seconds, m/z in Th, ion mobility in ms when provided).
 #[derive(Debug, Clone)]
 pub struct IngestSpectrum {
@@ -143,22 +270,108 @@ impl IngestSpectrum {
     }
 }
 
+/// Processing applied to every spectrum after the contract checks, e.g. a
+/// denoising filter loaded from a plugin.
+///
+/// Steps may run on several conversion threads at once.
+pub trait SpectrumStep: Send + Sync {
+    /// Name reported in errors.
+    fn name(&self) -> &str;
+
+    /// Modify `spectrum` in place.
+    fn process(
+        &self,
+        spectrum: &mut SpectrumArrays,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Ordered list of processing steps run by an [`IngestSpectrumConverter`].
+#[derive(Clone, Default)]
+pub struct SpectrumSteps(Vec<Arc<dyn SpectrumStep>>);
+
+impl SpectrumSteps {
+    /// Append a step.
+    pub fn push(&mut self, step: impl SpectrumStep + 'static) {
+        self.0.push(Arc::new(step));
+    }
+
+    /// Number of steps.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether no steps are registered.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Names of the steps, in run order.
+    pub fn names(&self) -> Vec<&str> {
+        self.0.iter().map(|step| step.name()).collect()
+    }
+
+    /// Run every step in order; the peak arrays are re-validated after each.
+    pub fn process(&self, spectrum: &mut SpectrumArrays) -> Result<(), IngestError> {
+        for step in &self.0 {
+            let failed = |message: String| IngestError::StepFailed {
+                step: step.name().to_string(),
+                message,
+            };
+            step.process(spectrum)
+                .map_err(|error| failed(error.to_string()))?;
+            spectrum.peaks.validate().map_err(failed)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for SpectrumSteps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
 /// Stateful converter from `IngestSpectrum` to `SpectrumArrays` with contract enforcement.
 #[derive(Debug, Default)]
 pub struct IngestSpectrumConverter {
     next_spectrum_id: Option<i64>,
+    rt_order: RtOrderTracker,
+    strict_rt_order: bool,
+    steps: SpectrumSteps,
 }
 
 impl IngestSpectrumConverter {
     /// Create a new contract-enforcing converter.
     pub fn new() -> Self {
-        Self { next_spectrum_id: None }
+        Self::default()
+    }
+
+    /// Create a converter tracking retention time order with the given policy.
+    pub fn with_rt_order(config: &RtOrderConfig) -> Self {
+        Self {
+            next_spectrum_id: None,
+            rt_order: RtOrderTracker::new(config.jitter_tolerance),
+            strict_rt_order: config.strict,
+            steps: SpectrumSteps::default(),
+        }
+    }
+
+    /// Run `steps` on every converted spectrum.
+    pub fn with_steps(mut self, steps: SpectrumSteps) -> Self {
+        self.steps = steps;
+        self
+    }
+
+    /// Retention time order of the spectra converted so far.
+    pub fn rt_order(&self) -> &RtOrderTracker {
+        &self.rt_order
     }
 
     /// Convert an ingestion spectrum into `SpectrumArrays`, enforcing contract invariants.
     pub fn convert(&mut self, ingest: IngestSpectrum) -> Result<SpectrumArrays, IngestError> {
         ingest.validate_contract()?;
         self.validate_ordering(ingest.spectrum_id)?;
+        self.validate_rt_order(ingest.spectrum_id, ingest.retention_time)?;
 
         let IngestSpectrum {
             spectrum_id,
@@ -204,6 +417,14 @@ impl IngestSpectrumConverter {
             peaks,
         };
 
+        // Steps change the peaks, so source-reported statistics no longer apply
+        if !self.steps.is_empty() {
+            self.steps.process(&mut spectrum)?;
+            spectrum.total_ion_current = None;
+            spectrum.base_peak_mz = None;
+            spectrum.base_peak_intensity = None;
+        }
+
         // Compute statistics only if not already provided (avoid redundant pass)
         if spectrum.total_ion_current.is_none() {
             spectrum.compute_statistics();
@@ -224,4 +445,91 @@ impl IngestSpectrumConverter {
         self.next_spectrum_id = Some(spectrum_id + 1);
         Ok(())
     }
+
+    fn validate_rt_order(&mut self, spectrum_id: i64, retention_time: f32) -> Result<(), IngestError> {
+        if self.rt_order.observe(retention_time) == RtStep::Break && self.strict_rt_order {
+            return Err(IngestError::violation(format!(
+                "retention_time of spectrum {spectrum_id} decreases by more than {} s to {retention_time}",
+                self.rt_order.tolerance()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Stable re-sort stage restoring retention time order of jittered spectra
+///
+/// Spectra are held until no later spectrum within the jitter tolerance can
+/// precede them, so the buffer spans at most `jitter_tolerance` seconds of
+/// the run. Spectra with equal retention times keep their stream order. A
+/// decrease beyond the tolerance cannot be repaired in a bounded window: the
+/// buffer is flushed and ordering restarts from the new retention time.
+///
+/// Released spectra are renumbered so spectrum IDs stay contiguous in the
+/// written order; scan numbers keep the native identifiers.
+#[derive(Debug, Default)]
+pub struct RtReorderBuffer {
+    tracker: RtOrderTracker,
+    pending: VecDeque<SpectrumArrays>,
+    next_spectrum_id: Option<i64>,
+    reordered_count: usize,
+}
+
+impl RtReorderBuffer {
+    /// Create a re-sort stage for decreases up to `tolerance` seconds
+    pub fn new(tolerance: f32) -> Self {
+        Self {
+            tracker: RtOrderTracker::new(tolerance),
+            ..Self::default()
+        }
+    }
+
+    /// Add the next spectrum of the stream, appending released spectra to `ready`
+    pub fn push(&mut self, spectrum: SpectrumArrays, ready: &mut Vec<SpectrumArrays>) {
+        let rt = spectrum.retention_time;
+        if self.tracker.observe(rt) == RtStep::Break {
+            self.release_all(ready);
+        }
+        let index = self.pending.partition_point(|pending| pending.retention_time <= rt);
+        self.pending.insert(index, spectrum);
+
+        if let Some(latest) = self.tracker.latest_rt {
+            let horizon = latest - self.tracker.tolerance();
+            while self
+                .pending
+                .front()
+                .is_some_and(|pending| pending.retention_time < horizon)
+            {
+                if let Some(spectrum) = self.pending.pop_front() {
+                    self.release(spectrum, ready);
+                }
+            }
+        }
+    }
+
+    /// Release all held spectra at the end of the stream
+    pub fn finish(&mut self, ready: &mut Vec<SpectrumArrays>) {
+        self.release_all(ready);
+    }
+
+    /// Number of released spectra whose position in the stream changed
+    pub fn reordered_count(&self) -> usize {
+        self.reordered_count
+    }
+
+    fn release_all(&mut self, ready: &mut Vec<SpectrumArrays>) {
+        while let Some(spectrum) = self.pending.pop_front() {
+            self.release(spectrum, ready);
+        }
+    }
+
+    fn release(&mut self, mut spectrum: SpectrumArrays, ready: &mut Vec<SpectrumArrays>) {
+        let spectrum_id = self.next_spectrum_id.unwrap_or(spectrum.spectrum_id);
+        if spectrum.spectrum_id != spectrum_id {
+            self.reordered_count += 1;
+            spectrum.spectrum_id = spectrum_id;
+        }
+        self.next_spectrum_id = Some(spectrum_id + 1);
+        ready.push(spectrum);
+    }
 }
@@ -3,13 +3,15 @@ use std::path::Path;
 use log::{info, warn};
 
 use super::{ConversionError, MzMLConverter};
-use super::super::cv_params::MS_CV_ACCESSIONS;
+use super::super::cv_params::{
+    extract_cv_f64, extract_cv_value, CvParam, IMS_CV_ACCESSIONS, MS_CV_ACCESSIONS,
+};
 use super::super::models::{ChromatogramType, ComponentType, MzMLChromatogram, MzMLFileMetadata};
 use super::super::streamer::MzMLStreamer;
 use crate::dataset::MzPeakDatasetWriter;
 use crate::metadata::{
-    InstrumentConfig, MassAnalyzerConfig, MzPeakMetadata, ProcessingHistory, ProcessingStep,
-    RunParameters, SdrfMetadata, SourceFileInfo,
+    AxisOrientation, ImagingMetadata, InstrumentConfig, MassAnalyzerConfig, MzPeakMetadata,
+    ProcessingHistory, ProcessingStep, RunParameters, SdrfMetadata, SourceFileInfo,
 };
 use crate::writer::WriterError;
 
@@ -216,6 +218,7 @@ impl MzMLConverter {
         // Run parameters
         let mut run_params = RunParameters::new();
         run_params.start_time = mzml.run_start_time.clone();
+        run_params.normalize_timestamps(self.config.timestamp_zone);
         run_params.method_name = mzml.run_id.clone();
 
         // Extract software info
@@ -230,6 +233,9 @@ impl MzMLConverter {
 
         metadata.run_parameters = Some(run_params);
 
+        // Image geometry (imzML scan settings)
+        metadata.imaging = imaging_from_scan_settings(&mzml.scan_settings);
+
         // Processing history
         let mut history = ProcessingHistory::new();
 
@@ -253,6 +259,7 @@ impl MzMLConverter {
                     timestamp: None,
                     parameters: params,
                     cv_params: Default::default(),
+                    unknown_fields: Default::default(),
                 });
             }
         }
@@ -266,6 +273,7 @@ impl MzMLConverter {
             timestamp: Some(chrono::Utc::now().to_rfc3339()),
             parameters: std::collections::HashMap::new(),
             cv_params: Default::default(),
+            unknown_fields: Default::default(),
         });
 
         metadata.processing_history = Some(history);
@@ -287,3 +295,30 @@ impl MzMLConverter {
         Ok(metadata)
     }
 }
+
+/// Pixel grid calibration from imzML scan settings, `None` without any
+///
+/// imzML places pixel (1, 1) at the top left of the image.
+fn imaging_from_scan_settings(scan_settings: &[CvParam]) -> Option<ImagingMetadata> {
+    let value = |accession| extract_cv_f64(scan_settings, accession);
+    let count = |accession| extract_cv_value(scan_settings, accession)?.parse().ok();
+    let imaging = ImagingMetadata {
+        grid_width: count(IMS_CV_ACCESSIONS::MAX_COUNT_PIXELS_X),
+        grid_height: count(IMS_CV_ACCESSIONS::MAX_COUNT_PIXELS_Y),
+        pixel_size_x_um: value(IMS_CV_ACCESSIONS::PIXEL_SIZE_X),
+        pixel_size_y_um: value(IMS_CV_ACCESSIONS::PIXEL_SIZE_Y),
+        origin_x_um: value(IMS_CV_ACCESSIONS::ABSOLUTE_POSITION_OFFSET_X),
+        origin_y_um: value(IMS_CV_ACCESSIONS::ABSOLUTE_POSITION_OFFSET_Y),
+        axis_orientation: None,
+        unknown_fields: Default::default(),
+    };
+    let any_set = imaging.grid_width.is_some()
+        || imaging.grid_height.is_some()
+        || imaging.pixel_size_x_um.is_some()
+        || imaging.pixel_size_y_um.is_some();
+    any_set.then_some(ImagingMetadata {
+        axis_orientation: Some(AxisOrientation::YDown),
+        ..imaging
+    })
+}
+
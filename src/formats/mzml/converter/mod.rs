@@ -3,7 +3,11 @@
 //! This module provides the high-level conversion pipeline from mzML files
 //! to the mzPeak Parquet format, preserving all metadata and numerical precision.
 
+use serde::{Deserialize, Serialize};
+
 use super::streamer::MzMLError;
+use crate::ingest::{IngestSpectrumConverter, RtOrderConfig, RtReorderBuffer, SpectrumSteps};
+use crate::metadata::TimestampZone;
 use crate::writer::{WriterConfig, WriterError};
 use crate::schema::manifest::Modality;
 
@@ -191,6 +195,21 @@ pub struct ConversionConfig {
 
     /// Optional modality override for v2 containers (auto-detect when None)
     pub modality: Option<Modality>,
+
+    /// Zone assumed for run start/end times without a UTC offset; run times
+    /// are stored as RFC 3339
+    pub timestamp_zone: TimestampZone,
+
+    /// Retention time ordering policy; repair is only supported for v2
+    /// container output
+    pub rt_order: RtOrderConfig,
+
+    /// Spectra to skip, e.g. lock-mass or calibration scans
+    pub scan_filters: ScanFilters,
+
+    /// Processing steps run on every admitted spectrum before it is written,
+    /// e.g. steps loaded from plugins
+    pub spectrum_steps: SpectrumSteps,
 }
 
 impl Default for ConversionConfig {
@@ -207,6 +226,10 @@ impl Default for ConversionConfig {
             progress_interval: 1000,
             output_format: OutputFormat::V2Container,
             modality: None,
+            timestamp_zone: TimestampZone::default(),
+            rt_order: RtOrderConfig::default(),
+            scan_filters: ScanFilters::default(),
+            spectrum_steps: SpectrumSteps::default(),
         }
     }
 }
@@ -228,6 +251,10 @@ impl ConversionConfig {
             progress_interval: 1000,
             output_format: OutputFormat::V2Container,
             modality: None,
+            timestamp_zone: TimestampZone::default(),
+            rt_order: RtOrderConfig::default(),
+            scan_filters: ScanFilters::default(),
+            spectrum_steps: SpectrumSteps::default(),
         }
     }
 
@@ -246,6 +273,10 @@ impl ConversionConfig {
             progress_interval: 1000,
             output_format: OutputFormat::V2Container,
             modality: None,
+            timestamp_zone: TimestampZone::default(),
+            rt_order: RtOrderConfig::default(),
+            scan_filters: ScanFilters::default(),
+            spectrum_steps: SpectrumSteps::default(),
         }
     }
 
@@ -264,6 +295,10 @@ impl ConversionConfig {
             progress_interval: 1000,
             output_format: OutputFormat::V2Container,
             modality: None,
+            timestamp_zone: TimestampZone::default(),
+            rt_order: RtOrderConfig::default(),
+            scan_filters: ScanFilters::default(),
+            spectrum_steps: SpectrumSteps::default(),
         }
     }
 
@@ -274,7 +309,8 @@ impl ConversionConfig {
 }
 
 /// Statistics from a conversion
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ConversionStats {
     /// Total spectra converted
     pub spectra_count: usize,
@@ -294,6 +330,22 @@ pub struct ConversionStats {
     pub output_file_size: u64,
     /// Compression ratio (source/output)
     pub compression_ratio: f64,
+    /// Spectra within the jitter tolerance before an earlier retention time
+    pub rt_jitter_spectra: usize,
+    /// Retention time decreases beyond the jitter tolerance
+    pub rt_order_breaks: usize,
+    /// Spectra moved by retention time repair
+    pub rt_reordered_spectra: usize,
+    /// Spectra skipped by the scan filters
+    pub skipped_spectra: SkippedSpectra,
+    /// Wall-clock time per conversion stage
+    pub timings: StageTimings,
+    /// Average throughput in peaks per second
+    pub peaks_per_second: f64,
+    /// Highest throughput over any one-second window, in peaks per second
+    pub max_peaks_per_second: f64,
+    /// Peak resident memory of the process in bytes, where measurable
+    pub peak_memory_bytes: Option<u64>,
 }
 
 /// Converter from mzML to mzPeak format
@@ -319,6 +371,79 @@ impl MzMLConverter {
         self.config.batch_size = batch_size;
         self
     }
+
+    /// Pass the spectrum count from the mzML index on to the shared
+    /// progress counters, if any, for completion estimates
+    fn report_expected_count(&self, expected_count: Option<usize>) {
+        if let (Some(progress), Some(count)) =
+            (self.config.writer_config.progress.as_ref(), expected_count)
+        {
+            progress.set_expected_spectra(count as u64);
+        }
+    }
+
+    /// Ingest converter enforcing the configured retention time policy and
+    /// running the configured processing steps
+    fn ingest_converter(&self) -> IngestSpectrumConverter {
+        IngestSpectrumConverter::with_rt_order(&self.config.rt_order)
+            .with_steps(self.config.spectrum_steps.clone())
+    }
+
+    /// Retention time re-sort stage, when repair is requested
+    fn rt_repair_stage(&self) -> Option<RtReorderBuffer> {
+        let rt_order = &self.config.rt_order;
+        rt_order
+            .repair
+            .then(|| RtReorderBuffer::new(rt_order.jitter_tolerance))
+    }
+
+    /// Reject retention time repair for outputs written in stream order
+    fn reject_rt_repair(&self, output: &str) -> Result<(), ConversionError> {
+        if self.config.rt_order.repair {
+            return Err(ConversionError::WriterError(WriterError::InvalidData(format!(
+                "retention time repair is not supported for {output}"
+            ))));
+        }
+        Ok(())
+    }
+}
+
+impl ConversionStats {
+    /// Record the spectra skipped by the scan filters
+    fn record_skipped(&mut self, selection: &scan_filter::ScanSelection<'_>) {
+        self.skipped_spectra = selection.skipped().clone();
+        if self.skipped_spectra.total() > 0 {
+            log::info!(
+                "Skipped {} spectra (MS level: {}, native ID: {}, filter string: {}, retention time: {})",
+                self.skipped_spectra.total(),
+                self.skipped_spectra.ms_level,
+                self.skipped_spectra.native_id,
+                self.skipped_spectra.filter_string,
+                self.skipped_spectra.retention_time
+            );
+        }
+    }
+
+    /// Record the retention time order seen by the ingest converter
+    fn record_rt_order(
+        &mut self,
+        ingest_converter: &IngestSpectrumConverter,
+        rt_repair: Option<&RtReorderBuffer>,
+    ) {
+        let rt_order = ingest_converter.rt_order();
+        self.rt_jitter_spectra = rt_order.jitter_count();
+        self.rt_order_breaks = rt_order.break_count();
+        self.rt_reordered_spectra = rt_repair.map_or(0, RtReorderBuffer::reordered_count);
+        if rt_order.jitter_count() > 0 || rt_order.break_count() > 0 {
+            log::warn!(
+                "Retention time order: {} jittered spectra (up to {:.3} s), {} breaks, {} spectra re-sorted",
+                self.rt_jitter_spectra,
+                rt_order.max_jitter(),
+                self.rt_order_breaks,
+                self.rt_reordered_spectra
+            );
+        }
+    }
 }
 
 impl Default for MzMLConverter {
@@ -328,9 +453,14 @@ impl Default for MzMLConverter {
 }
 
 mod metadata;
+mod perf;
+mod scan_filter;
 mod sequential;
 mod spectrum;
 
+pub use perf::{peak_memory_bytes, Stage, StageTimings};
+pub use scan_filter::{ScanFilters, SkipReason, SkippedSpectra};
+
 #[cfg(feature = "parallel-decode")]
 mod parallel;
 
@@ -7,11 +7,13 @@ use super::{ConversionError, ConversionStats, MzMLConverter, OutputFormat};
 use super::super::models::RawMzMLSpectrum;
 use super::super::streamer::MzMLStreamer;
 use crate::dataset::{DatasetWriterV2Config, MzPeakDatasetWriter, MzPeakDatasetWriterV2};
-use crate::ingest::IngestSpectrumConverter;
+use crate::ingest::{IngestSpectrumConverter, RtReorderBuffer};
 use crate::schema::manifest::Modality;
 use crate::writer::{
     PeaksWriterV2Config, SpectraWriterConfig, SpectrumArrays, SpectrumV2, WriterError,
 };
+use super::perf::{Stage, StageClock};
+use super::scan_filter::ScanSelection;
 use super::spectrum::DecodedRawSpectrum;
 
 impl MzMLConverter {
@@ -54,12 +56,14 @@ impl MzMLConverter {
     ) -> Result<ConversionStats, ConversionError> {
         let input_path = input_path.as_ref();
         let output_path = output_path.as_ref();
+        let mut clock = StageClock::start();
 
         info!(
             "Converting {} to {} (parallel mode)",
             input_path.display(),
             output_path.display()
         );
+        self.reject_rt_repair("legacy v1 output")?;
 
         // Get source file size
         let source_file_size = std::fs::metadata(input_path)?.len();
@@ -73,7 +77,7 @@ impl MzMLConverter {
         };
 
         // Read metadata first
-        let mzml_metadata = streamer.read_metadata()?;
+        let mzml_metadata = clock.time(Stage::Parse, || streamer.read_metadata())?;
         info!("mzML version: {:?}", mzml_metadata.version);
 
         // Convert mzML metadata to mzPeak metadata
@@ -95,13 +99,15 @@ impl MzMLConverter {
         let parallel_batch_size = self.config.parallel_batch_size;
         let mut raw_batch: Vec<RawMzMLSpectrum> = Vec::with_capacity(parallel_batch_size);
         let expected_count = streamer.spectrum_count();
+        self.report_expected_count(expected_count);
 
         // Accumulate TIC and BPC data during spectrum processing
         let mut tic_times: Vec<f64> = Vec::new();
         let mut tic_intensities: Vec<f32> = Vec::new();
         let mut bpc_times: Vec<f64> = Vec::new();
         let mut bpc_intensities: Vec<f32> = Vec::new();
-        let mut ingest_converter = IngestSpectrumConverter::new();
+        let mut ingest_converter = self.ingest_converter();
+        let mut selection = ScanSelection::new(&self.config.scan_filters);
 
         info!(
             "Converting {} spectra (parallel, batch_size={})...",
@@ -112,29 +118,34 @@ impl MzMLConverter {
         );
 
         // Phase 1: Collect raw spectra in batches
-        while let Some(raw_spectrum) = streamer.next_raw_spectrum()? {
+        while let Some(raw_spectrum) = clock.time(Stage::Parse, || streamer.next_raw_spectrum())? {
+            if !selection.admit(&raw_spectrum) {
+                continue;
+            }
             raw_batch.push(raw_spectrum);
 
             if raw_batch.len() >= parallel_batch_size {
                 // Phase 2: Parallel decode this batch
-                let decoded_batch: Vec<_> = raw_batch
-                    .par_drain(..)
-                    .map(|raw| self.build_ingest_spectrum_raw(raw))
-                    .collect::<Result<_, _>>()?;
+                let decoded_batch = clock.time(Stage::Decode, || {
+                    self.decode_batch(&mut raw_batch, &mut selection)
+                })?;
 
                 // Process decoded spectra
-                let write_batch = self.process_decoded_batch(
-                    decoded_batch,
-                    &mut stats,
-                    &mut tic_times,
-                    &mut tic_intensities,
-                    &mut bpc_times,
-                    &mut bpc_intensities,
-                    &mut ingest_converter,
-                )?;
+                let write_batch = clock.time(Stage::Transform, || {
+                    self.process_decoded_batch(
+                        decoded_batch,
+                        &mut stats,
+                        &mut tic_times,
+                        &mut tic_intensities,
+                        &mut bpc_times,
+                        &mut bpc_intensities,
+                        &mut ingest_converter,
+                    )
+                })?;
 
                 // Write to output
-                writer.write_spectra_owned(write_batch)?;
+                clock.time(Stage::Write, || writer.write_spectra_owned(write_batch))?;
+                clock.progress(stats.peak_count);
 
                 // Progress update
                 if stats.spectra_count % self.config.progress_interval == 0 {
@@ -153,22 +164,23 @@ impl MzMLConverter {
 
         // Process remaining spectra
         if !raw_batch.is_empty() {
-            let decoded_batch: Vec<_> = raw_batch
-                .par_drain(..)
-                .map(|raw| self.build_ingest_spectrum_raw(raw))
-                .collect::<Result<_, _>>()?;
-
-            let write_batch = self.process_decoded_batch(
-                decoded_batch,
-                &mut stats,
-                &mut tic_times,
-                &mut tic_intensities,
-                &mut bpc_times,
-                &mut bpc_intensities,
-                &mut ingest_converter,
-            )?;
-
-            writer.write_spectra_owned(write_batch)?;
+            let decoded_batch = clock.time(Stage::Decode, || {
+                self.decode_batch(&mut raw_batch, &mut selection)
+            })?;
+
+            let write_batch = clock.time(Stage::Transform, || {
+                self.process_decoded_batch(
+                    decoded_batch,
+                    &mut stats,
+                    &mut tic_times,
+                    &mut tic_intensities,
+                    &mut bpc_times,
+                    &mut bpc_intensities,
+                    &mut ingest_converter,
+                )
+            })?;
+
+            clock.time(Stage::Write, || writer.write_spectra_owned(write_batch))?;
         }
 
         // Finalize spectrum writer
@@ -179,7 +191,9 @@ impl MzMLConverter {
             info!("Processing chromatograms...");
 
             // First, try to read chromatograms from mzML
-            let chrom_count = self.stream_chromatograms(&mut streamer, &mut writer)?;
+            let chrom_count = clock.time(Stage::Write, || {
+                self.stream_chromatograms(&mut streamer, &mut writer)
+            })?;
             stats.chromatograms_converted = chrom_count;
 
             // If no chromatograms were found and we have MS1 spectra, generate TIC/BPC
@@ -193,8 +207,8 @@ impl MzMLConverter {
                     tic_times,
                     tic_intensities,
                 ) {
-                    writer
-                        .write_chromatogram(&tic_chrom)
+                    clock
+                        .time(Stage::Write, || writer.write_chromatogram(&tic_chrom))
                         .map_err(|e| ConversionError::WriterError(WriterError::InvalidData(e.to_string())))?;
                     stats.chromatograms_converted += 1;
                 }
@@ -206,8 +220,8 @@ impl MzMLConverter {
                     bpc_times,
                     bpc_intensities,
                 ) {
-                    writer
-                        .write_chromatogram(&bpc_chrom)
+                    clock
+                        .time(Stage::Write, || writer.write_chromatogram(&bpc_chrom))
                         .map_err(|e| ConversionError::WriterError(WriterError::InvalidData(e.to_string())))?;
                     stats.chromatograms_converted += 1;
                 }
@@ -218,8 +232,11 @@ impl MzMLConverter {
             info!("  Chromatograms: {}", stats.chromatograms_converted);
         }
 
+        stats.record_rt_order(&ingest_converter, None);
+        stats.record_skipped(&selection);
+
         // Close dataset (finalizes both peaks and chromatograms)
-        let dataset_stats = writer.close()?;
+        let dataset_stats = clock.time(Stage::Package, || writer.close())?;
         info!("Dataset finalized: {}", dataset_stats);
 
         // Get output file size
@@ -227,6 +244,7 @@ impl MzMLConverter {
         if stats.output_file_size > 0 {
             stats.compression_ratio = stats.source_file_size as f64 / stats.output_file_size as f64;
         }
+        clock.finish(&mut stats);
 
         info!("Conversion complete (parallel):");
         info!(
@@ -237,6 +255,7 @@ impl MzMLConverter {
         info!("  Input size: {} bytes", stats.source_file_size);
         info!("  Output size: {} bytes", stats.output_file_size);
         info!("  Compression ratio: {:.2}x", stats.compression_ratio);
+        info!("  Stage timings: {}", stats.timings);
 
         Ok(stats)
     }
@@ -248,6 +267,7 @@ impl MzMLConverter {
     ) -> Result<ConversionStats, ConversionError> {
         let input_path = input_path.as_ref();
         let output_path = output_path.as_ref();
+        let mut clock = StageClock::start();
 
         info!(
             "Converting {} to {} (parallel v2 container)",
@@ -263,12 +283,12 @@ impl MzMLConverter {
             MzMLStreamer::open_with_buffer_size(input_path, buffer_size)?
         };
 
-        let mzml_metadata = streamer.read_metadata()?;
+        let mzml_metadata = clock.time(Stage::Parse, || streamer.read_metadata())?;
         info!("mzML version: {:?}", mzml_metadata.version);
 
         let mzpeak_metadata = self.convert_metadata(mzml_metadata, input_path)?;
 
-        let mut pending_raw = streamer.next_raw_spectrum()?;
+        let mut pending_raw = clock.time(Stage::Parse, || streamer.next_raw_spectrum())?;
         let mut has_imaging = is_imzml_path(input_path);
         let mut has_ion_mobility = false;
         if let Some(ref raw) = pending_raw {
@@ -286,13 +306,18 @@ impl MzMLConverter {
         let dataset_config = DatasetWriterV2Config {
             spectra_config: SpectraWriterConfig {
                 compression: self.config.writer_config.compression,
+                spectrum_checksums: self.config.writer_config.spectrum_checksums,
                 ..Default::default()
             },
             peaks_config: PeaksWriterV2Config {
                 compression: self.config.writer_config.compression,
                 row_group_size: self.config.writer_config.row_group_size,
+                progress: self.config.writer_config.progress.clone(),
                 ..Default::default()
             },
+            temp_dir: self.config.writer_config.temp_dir.clone(),
+            stage_locally: self.config.writer_config.stage_locally,
+            ..Default::default()
         };
 
         let vendor_hints = mzpeak_metadata.vendor_hints.clone();
@@ -308,7 +333,10 @@ impl MzMLConverter {
         let parallel_batch_size = self.config.parallel_batch_size;
         let mut raw_batch: Vec<RawMzMLSpectrum> = Vec::with_capacity(parallel_batch_size);
         let expected_count = streamer.spectrum_count();
-        let mut ingest_converter = IngestSpectrumConverter::new();
+        self.report_expected_count(expected_count);
+        let mut ingest_converter = self.ingest_converter();
+        let mut selection = ScanSelection::new(&self.config.scan_filters);
+        let mut rt_repair = self.rt_repair_stage();
 
         info!(
             "Converting {} spectra (parallel, batch_size={})...",
@@ -318,54 +346,74 @@ impl MzMLConverter {
             parallel_batch_size
         );
 
-        if let Some(raw) = pending_raw.take() {
+        if let Some(raw) = pending_raw.take().filter(|raw| selection.admit(raw)) {
             raw_batch.push(raw);
         }
 
-        while let Some(raw_spectrum) = streamer.next_raw_spectrum()? {
+        while let Some(raw_spectrum) = clock.time(Stage::Parse, || streamer.next_raw_spectrum())? {
+            if !selection.admit(&raw_spectrum) {
+                continue;
+            }
             raw_batch.push(raw_spectrum);
 
             if raw_batch.len() >= parallel_batch_size {
-                let decoded_batch: Vec<_> = raw_batch
-                    .par_drain(..)
-                    .map(|raw| self.build_ingest_spectrum_raw(raw))
-                    .collect::<Result<_, _>>()?;
+                let decoded_batch = clock.time(Stage::Decode, || {
+                    self.decode_batch(&mut raw_batch, &mut selection)
+                })?;
+
+                let write_batch = clock.time(Stage::Transform, || {
+                    self.process_decoded_batch_v2(
+                        decoded_batch,
+                        &mut stats,
+                        &mut ingest_converter,
+                        &mut rt_repair,
+                        modality,
+                    )
+                })?;
+
+                clock.time(Stage::Write, || writer.write_spectra(&write_batch))?;
+                clock.progress(stats.peak_count);
+                log_progress(&stats, expected_count, self.config.progress_interval);
+            }
+        }
+
+        if !raw_batch.is_empty() {
+            let decoded_batch = clock.time(Stage::Decode, || {
+                self.decode_batch(&mut raw_batch, &mut selection)
+            })?;
 
-                let write_batch = self.process_decoded_batch_v2(
+            let write_batch = clock.time(Stage::Transform, || {
+                self.process_decoded_batch_v2(
                     decoded_batch,
                     &mut stats,
                     &mut ingest_converter,
+                    &mut rt_repair,
                     modality,
-                )?;
+                )
+            })?;
 
-                writer.write_spectra(&write_batch)?;
-                log_progress(&stats, expected_count, self.config.progress_interval);
-            }
+            clock.time(Stage::Write, || writer.write_spectra(&write_batch))?;
         }
 
-        if !raw_batch.is_empty() {
-            let decoded_batch: Vec<_> = raw_batch
-                .par_drain(..)
-                .map(|raw| self.build_ingest_spectrum_raw(raw))
-                .collect::<Result<_, _>>()?;
-
-            let write_batch = self.process_decoded_batch_v2(
-                decoded_batch,
-                &mut stats,
-                &mut ingest_converter,
-                modality,
-            )?;
-
-            writer.write_spectra(&write_batch)?;
+        if let Some(rt_repair) = rt_repair.as_mut() {
+            let mut ready = Vec::new();
+            let write_batch = clock.time(Stage::Transform, || {
+                rt_repair.finish(&mut ready);
+                self.build_spectra_v2(ready, &mut stats, modality)
+            })?;
+            clock.time(Stage::Write, || writer.write_spectra(&write_batch))?;
         }
+        stats.record_rt_order(&ingest_converter, rt_repair.as_ref());
+        stats.record_skipped(&selection);
 
-        let dataset_stats = writer.close()?;
+        let dataset_stats = clock.time(Stage::Package, || writer.close())?;
         info!("Dataset finalized: {}", dataset_stats);
 
         stats.output_file_size = std::fs::metadata(output_path)?.len();
         if stats.output_file_size > 0 {
             stats.compression_ratio = stats.source_file_size as f64 / stats.output_file_size as f64;
         }
+        clock.finish(&mut stats);
 
         info!("Conversion complete (parallel v2):");
         info!(
@@ -376,10 +424,28 @@ impl MzMLConverter {
         info!("  Input size: {} bytes", stats.source_file_size);
         info!("  Output size: {} bytes", stats.output_file_size);
         info!("  Compression ratio: {:.2}x", stats.compression_ratio);
+        info!("  Stage timings: {}", stats.timings);
 
         Ok(stats)
     }
 
+    /// Decode a batch of admitted raw spectra in parallel, numbering them in
+    /// stream order
+    fn decode_batch(
+        &self,
+        raw_batch: &mut Vec<RawMzMLSpectrum>,
+        selection: &mut ScanSelection<'_>,
+    ) -> Result<Vec<DecodedRawSpectrum>, ConversionError> {
+        let mut decoded_batch: Vec<DecodedRawSpectrum> = raw_batch
+            .par_drain(..)
+            .map(|raw| self.build_ingest_spectrum_raw(raw))
+            .collect::<Result<_, _>>()?;
+        for decoded in &mut decoded_batch {
+            selection.renumber(&mut decoded.ingest);
+        }
+        Ok(decoded_batch)
+    }
+
     /// Process a batch of decoded spectra, updating stats and accumulating TIC/BPC
     fn process_decoded_batch(
         &self,
@@ -441,9 +507,10 @@ impl MzMLConverter {
         decoded_batch: Vec<DecodedRawSpectrum>,
         stats: &mut ConversionStats,
         ingest_converter: &mut IngestSpectrumConverter,
+        rt_repair: &mut Option<RtReorderBuffer>,
         modality: Modality,
     ) -> Result<Vec<SpectrumV2>, ConversionError> {
-        let mut write_batch = Vec::with_capacity(decoded_batch.len());
+        let mut ready = Vec::with_capacity(decoded_batch.len());
 
         for decoded in decoded_batch {
             let DecodedRawSpectrum { ingest, .. } = decoded;
@@ -451,6 +518,25 @@ impl MzMLConverter {
                 .convert(ingest)
                 .map_err(WriterError::from)?;
 
+            match rt_repair {
+                Some(rt_repair) => rt_repair.push(spectrum, &mut ready),
+                None => ready.push(spectrum),
+            }
+        }
+
+        self.build_spectra_v2(ready, stats, modality)
+    }
+
+    /// Convert spectra released by the ingest stages to v2 rows
+    fn build_spectra_v2(
+        &self,
+        ready: Vec<SpectrumArrays>,
+        stats: &mut ConversionStats,
+        modality: Modality,
+    ) -> Result<Vec<SpectrumV2>, ConversionError> {
+        let mut write_batch = Vec::with_capacity(ready.len());
+
+        for spectrum in ready {
             let spectrum_v2 = SpectrumV2::try_from_spectrum_arrays(spectrum)
                 .map_err(ConversionError::WriterError)?;
 
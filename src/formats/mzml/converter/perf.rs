@@ -0,0 +1,238 @@
+//! Conversion performance: per-stage timing, throughput and memory
+//!
+//! The converters time every stage of the pipeline on the wall clock and
+//! record the result in [`ConversionStats`], so regressions between releases
+//! show up in the CLI summary and in `mzpeak convert --report` output.
+//!
+//! | Stage | Covers |
+//! |-------|--------|
+//! | parse | XML parsing of the mzML/imzML stream, including its metadata |
+//! | decode | Base64 and zlib decoding of the binary arrays |
+//! | transform | Ingest validation, processing steps and retention time repair |
+//! | write | Encoding and writing spectra and chromatograms |
+//! | package | Finalizing the output tables and container |
+//!
+//! Time outside these stages (opening files, scan filters, statistics) only
+//! counts towards the total. With parallel decoding, `decode` is the wall
+//! clock spent waiting for each decoded batch.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::ConversionStats;
+
+/// Shortest window used for the highest throughput
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(1);
+
+/// Stage of the conversion pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// XML parsing
+    Parse,
+    /// Binary array decoding
+    Decode,
+    /// Ingest validation and processing steps
+    Transform,
+    /// Writing spectra and chromatograms
+    Write,
+    /// Finalizing the output
+    Package,
+}
+
+impl Stage {
+    /// All stages in pipeline order
+    pub const ALL: [Stage; 5] = [
+        Stage::Parse,
+        Stage::Decode,
+        Stage::Transform,
+        Stage::Write,
+        Stage::Package,
+    ];
+
+    /// Lowercase stage name
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Parse => "parse",
+            Stage::Decode => "decode",
+            Stage::Transform => "transform",
+            Stage::Write => "write",
+            Stage::Package => "package",
+        }
+    }
+}
+
+/// Wall-clock seconds spent in each stage of a conversion
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StageTimings {
+    /// XML parsing
+    pub parse: f64,
+    /// Binary array decoding
+    pub decode: f64,
+    /// Ingest validation and processing steps
+    pub transform: f64,
+    /// Writing spectra and chromatograms
+    pub write: f64,
+    /// Finalizing the output
+    pub package: f64,
+    /// Whole conversion
+    pub total: f64,
+}
+
+impl StageTimings {
+    /// Seconds spent in `stage`
+    pub fn get(&self, stage: Stage) -> f64 {
+        match stage {
+            Stage::Parse => self.parse,
+            Stage::Decode => self.decode,
+            Stage::Transform => self.transform,
+            Stage::Write => self.write,
+            Stage::Package => self.package,
+        }
+    }
+
+    fn get_mut(&mut self, stage: Stage) -> &mut f64 {
+        match stage {
+            Stage::Parse => &mut self.parse,
+            Stage::Decode => &mut self.decode,
+            Stage::Transform => &mut self.transform,
+            Stage::Write => &mut self.write,
+            Stage::Package => &mut self.package,
+        }
+    }
+}
+
+impl fmt::Display for StageTimings {
+    /// `parse 1.20 s (40%), decode ...`, shares of the total
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, stage) in Stage::ALL.into_iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            let seconds = self.get(stage);
+            let share = if self.total > 0.0 {
+                seconds / self.total * 100.0
+            } else {
+                0.0
+            };
+            write!(f, "{} {:.2} s ({:.0}%)", stage.name(), seconds, share)?;
+        }
+        Ok(())
+    }
+}
+
+/// Stage clock of one conversion
+pub(super) struct StageClock {
+    started: Instant,
+    timings: StageTimings,
+    window_started: Instant,
+    /// Running peak count when the current window started
+    window_peaks: usize,
+    max_peaks_per_second: f64,
+}
+
+impl StageClock {
+    /// Start the clock at the beginning of a conversion
+    pub(super) fn start() -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            timings: StageTimings::default(),
+            window_started: now,
+            window_peaks: 0,
+            max_peaks_per_second: 0.0,
+        }
+    }
+
+    /// Run `f`, adding its wall-clock time to `stage`
+    pub(super) fn time<T>(&mut self, stage: Stage, f: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let result = f();
+        *self.timings.get_mut(stage) += started.elapsed().as_secs_f64();
+        result
+    }
+
+    /// Update the throughput windows with the running peak count
+    pub(super) fn progress(&mut self, peak_count: usize) {
+        let elapsed = self.window_started.elapsed();
+        if elapsed >= THROUGHPUT_WINDOW {
+            let peaks = peak_count.saturating_sub(self.window_peaks);
+            let rate = peaks as f64 / elapsed.as_secs_f64();
+            self.max_peaks_per_second = self.max_peaks_per_second.max(rate);
+            self.window_started = Instant::now();
+            self.window_peaks = peak_count;
+        }
+    }
+
+    /// Stop the clock and record timings, throughput and memory in `stats`
+    pub(super) fn finish(mut self, stats: &mut ConversionStats) {
+        self.timings.total = self.started.elapsed().as_secs_f64();
+        if self.timings.total > 0.0 {
+            stats.peaks_per_second = stats.peak_count as f64 / self.timings.total;
+        }
+        // Conversions shorter than one window only have the average
+        stats.max_peaks_per_second = self.max_peaks_per_second.max(stats.peaks_per_second);
+        stats.peak_memory_bytes = peak_memory_bytes();
+        stats.timings = self.timings;
+    }
+}
+
+/// Peak resident memory of this process in bytes
+///
+/// Read from `VmHWM` in `/proc/self/status`; `None` where the platform does
+/// not report it. This is the high-water mark of the whole process, not just
+/// of one conversion.
+pub fn peak_memory_bytes() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+        let kib: u64 = line
+            .trim_start_matches("VmHWM:")
+            .trim()
+            .trim_end_matches("kB")
+            .trim()
+            .parse()
+            .ok()?;
+        Some(kib * 1024)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage_clock_records_stats() {
+        let mut clock = StageClock::start();
+        let value = clock.time(Stage::Decode, || {
+            std::thread::sleep(Duration::from_millis(5));
+            42
+        });
+        assert_eq!(value, 42);
+        clock.progress(1000);
+
+        let mut stats = ConversionStats {
+            peak_count: 1000,
+            ..Default::default()
+        };
+        clock.finish(&mut stats);
+        assert!(stats.timings.decode >= 0.005);
+        assert_eq!(stats.timings.parse, 0.0);
+        assert!(stats.timings.total >= stats.timings.decode);
+        assert!(stats.peaks_per_second > 0.0);
+        assert!(stats.max_peaks_per_second >= stats.peaks_per_second);
+        #[cfg(target_os = "linux")]
+        assert!(stats.peak_memory_bytes.is_some_and(|bytes| bytes > 0));
+        assert!(stats
+            .timings
+            .to_string()
+            .starts_with("parse 0.00 s (0%), decode"));
+    }
+}
@@ -0,0 +1,176 @@
+//! Scan filtering at convert time
+//!
+//! Filters drop spectra before their binary arrays are decoded, so lock-mass,
+//! calibration or out-of-window scans cost neither decode time nor space in
+//! the output. Kept spectra are renumbered to keep spectrum IDs contiguous;
+//! scan numbers keep the native identifiers.
+
+use std::collections::BTreeSet;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::super::models::RawMzMLSpectrum;
+use crate::ingest::IngestSpectrum;
+
+/// Spectra to exclude from a conversion
+///
+/// A spectrum is skipped when any filter matches. The default skips nothing.
+///
+/// # Example
+///
+/// ```rust
+/// use mzpeak::mzml::converter::{ConversionConfig, ScanFilters};
+/// use regex::Regex;
+///
+/// let config = ConversionConfig {
+///     scan_filters: ScanFilters {
+///         // Waters lock-mass reference scans are written as function 3
+///         skip_native_id: Some(Regex::new(r"function=3\b").unwrap()),
+///         min_retention_time: Some(120.0),
+///         ..Default::default()
+///     },
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ScanFilters {
+    /// MS levels to skip
+    pub skip_ms_levels: BTreeSet<i16>,
+    /// Skip spectra whose native ID matches
+    pub skip_native_id: Option<Regex>,
+    /// Skip spectra whose vendor filter string matches
+    pub skip_filter_string: Option<Regex>,
+    /// Skip spectra before this retention time, in seconds
+    pub min_retention_time: Option<f64>,
+    /// Skip spectra after this retention time, in seconds
+    pub max_retention_time: Option<f64>,
+}
+
+/// Reason a spectrum was skipped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// MS level in `skip_ms_levels`
+    MsLevel,
+    /// Native ID matched `skip_native_id`
+    NativeId,
+    /// Filter string matched `skip_filter_string`
+    FilterString,
+    /// Retention time outside the retention time limits
+    RetentionTime,
+}
+
+impl ScanFilters {
+    /// Whether any filter is set
+    pub fn is_empty(&self) -> bool {
+        self.skip_ms_levels.is_empty()
+            && self.skip_native_id.is_none()
+            && self.skip_filter_string.is_none()
+            && self.min_retention_time.is_none()
+            && self.max_retention_time.is_none()
+    }
+
+    /// Reason to skip a spectrum, `None` to keep it
+    ///
+    /// Spectra without a retention time or filter string are never skipped
+    /// by the corresponding filter.
+    pub fn skip_reason(&self, spectrum: &RawMzMLSpectrum) -> Option<SkipReason> {
+        if self.skip_ms_levels.contains(&spectrum.ms_level) {
+            return Some(SkipReason::MsLevel);
+        }
+        if let Some(pattern) = &self.skip_native_id {
+            if pattern.is_match(&spectrum.id) {
+                return Some(SkipReason::NativeId);
+            }
+        }
+        if let (Some(pattern), Some(filter_string)) =
+            (&self.skip_filter_string, &spectrum.filter_string)
+        {
+            if pattern.is_match(filter_string) {
+                return Some(SkipReason::FilterString);
+            }
+        }
+        if let Some(rt) = spectrum.retention_time {
+            let too_early = self.min_retention_time.is_some_and(|min| rt < min);
+            let too_late = self.max_retention_time.is_some_and(|max| rt > max);
+            if too_early || too_late {
+                return Some(SkipReason::RetentionTime);
+            }
+        }
+        None
+    }
+}
+
+/// Number of spectra skipped by each scan filter
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkippedSpectra {
+    /// Skipped by MS level
+    pub ms_level: usize,
+    /// Skipped by native ID
+    pub native_id: usize,
+    /// Skipped by filter string
+    pub filter_string: usize,
+    /// Skipped by retention time limits
+    pub retention_time: usize,
+}
+
+impl SkippedSpectra {
+    /// Total number of skipped spectra
+    pub fn total(&self) -> usize {
+        self.ms_level + self.native_id + self.filter_string + self.retention_time
+    }
+
+    fn record(&mut self, reason: SkipReason) {
+        match reason {
+            SkipReason::MsLevel => self.ms_level += 1,
+            SkipReason::NativeId => self.native_id += 1,
+            SkipReason::FilterString => self.filter_string += 1,
+            SkipReason::RetentionTime => self.retention_time += 1,
+        }
+    }
+}
+
+/// Applies scan filters to one conversion
+#[derive(Debug)]
+pub(crate) struct ScanSelection<'a> {
+    filters: &'a ScanFilters,
+    skipped: SkippedSpectra,
+    next_spectrum_id: Option<i64>,
+}
+
+impl<'a> ScanSelection<'a> {
+    pub(crate) fn new(filters: &'a ScanFilters) -> Self {
+        Self {
+            filters,
+            skipped: SkippedSpectra::default(),
+            next_spectrum_id: None,
+        }
+    }
+
+    /// Whether to convert a spectrum; skipped spectra are counted
+    pub(crate) fn admit(&mut self, spectrum: &RawMzMLSpectrum) -> bool {
+        // Numbering starts at the first spectrum of the stream, kept or not
+        self.next_spectrum_id.get_or_insert(spectrum.index);
+        match self.filters.skip_reason(spectrum) {
+            Some(reason) => {
+                self.skipped.record(reason);
+                false
+            }
+            None => true,
+        }
+    }
+
+    /// Renumber an admitted spectrum, in stream order, to close the gaps
+    /// left by skipped spectra
+    pub(crate) fn renumber(&mut self, ingest: &mut IngestSpectrum) {
+        let spectrum_id = self.next_spectrum_id.unwrap_or(ingest.spectrum_id);
+        ingest.spectrum_id = spectrum_id;
+        self.next_spectrum_id = Some(spectrum_id + 1);
+    }
+
+    /// Counts of skipped spectra
+    pub(crate) fn skipped(&self) -> &SkippedSpectra {
+        &self.skipped
+    }
+}
@@ -3,11 +3,13 @@ use std::path::Path;
 use log::info;
 
 use super::{ConversionError, ConversionStats, MzMLConverter, OutputFormat};
+use super::perf::{Stage, StageClock};
+use super::scan_filter::ScanSelection;
 use super::spectrum::DecodedRawSpectrum;
 use super::super::models::RawMzMLSpectrum;
 use super::super::streamer::MzMLStreamer;
 use crate::dataset::{DatasetWriterV2Config, MzPeakDatasetWriter, MzPeakDatasetWriterV2};
-use crate::ingest::IngestSpectrumConverter;
+use crate::ingest::{IngestSpectrumConverter, RtReorderBuffer};
 use crate::schema::manifest::Modality;
 use crate::writer::{
     PeaksWriterV2Config, RollingWriter, SpectraWriterConfig, SpectrumArrays, SpectrumV2,
@@ -34,8 +36,10 @@ impl MzMLConverter {
     ) -> Result<ConversionStats, ConversionError> {
         let input_path = input_path.as_ref();
         let output_path = output_path.as_ref();
+        let mut clock = StageClock::start();
 
         info!("Converting {} to {}", input_path.display(), output_path.display());
+        self.reject_rt_repair("legacy v1 output")?;
 
         // Get source file size
         let source_file_size = std::fs::metadata(input_path)?.len();
@@ -49,7 +53,7 @@ impl MzMLConverter {
         };
 
         // Read metadata first
-        let mzml_metadata = streamer.read_metadata()?;
+        let mzml_metadata = clock.time(Stage::Parse, || streamer.read_metadata())?;
         info!("mzML version: {:?}", mzml_metadata.version);
 
         // Convert mzML metadata to mzPeak metadata
@@ -69,8 +73,10 @@ impl MzMLConverter {
         };
 
         let mut batch: Vec<SpectrumArrays> = Vec::with_capacity(self.config.batch_size);
-        let mut ingest_converter = IngestSpectrumConverter::new();
+        let mut ingest_converter = self.ingest_converter();
+        let mut selection = ScanSelection::new(&self.config.scan_filters);
         let expected_count = streamer.spectrum_count();
+        self.report_expected_count(expected_count);
 
         // Accumulate TIC and BPC data during spectrum processing
         let mut tic_times: Vec<f64> = Vec::new();
@@ -85,15 +91,21 @@ impl MzMLConverter {
                 .unwrap_or_else(|| "unknown".to_string())
         );
 
-        while let Some(raw_spectrum) = streamer.next_raw_spectrum()? {
+        while let Some(raw_spectrum) = clock.time(Stage::Parse, || streamer.next_raw_spectrum())? {
+            if !selection.admit(&raw_spectrum) {
+                continue;
+            }
             let DecodedRawSpectrum {
-                ingest,
+                mut ingest,
                 retention_time,
                 total_ion_current,
                 base_peak_intensity,
-            } = self.build_ingest_spectrum_raw(raw_spectrum)?;
-            let spectrum = ingest_converter
-                .convert(ingest)
+            } = clock.time(Stage::Decode, || {
+                self.build_ingest_spectrum_raw(raw_spectrum)
+            })?;
+            selection.renumber(&mut ingest);
+            let spectrum = clock
+                .time(Stage::Transform, || ingest_converter.convert(ingest))
                 .map_err(WriterError::from)?;
 
             // Update statistics
@@ -126,8 +138,9 @@ impl MzMLConverter {
 
             // Write batch if full
             if batch.len() >= self.config.batch_size {
-                writer.write_spectra_owned(batch)?;
-                batch = Vec::with_capacity(self.config.batch_size);
+                // Draining keeps the batch capacity for the next round
+                clock.time(Stage::Write, || writer.write_spectra_drain(&mut batch))?;
+                clock.progress(stats.peak_count);
 
                 // Progress update
                 if stats.spectra_count % self.config.progress_interval == 0 {
@@ -146,7 +159,7 @@ impl MzMLConverter {
 
         // Write remaining spectra
         if !batch.is_empty() {
-            writer.write_spectra_owned(batch)?;
+            clock.time(Stage::Write, || writer.write_spectra_drain(&mut batch))?;
         }
 
         // Finalize spectrum writer first
@@ -157,7 +170,9 @@ impl MzMLConverter {
             info!("Processing chromatograms...");
 
             // First, try to read chromatograms from mzML
-            let chrom_count = self.stream_chromatograms(&mut streamer, &mut writer)?;
+            let chrom_count = clock.time(Stage::Write, || {
+                self.stream_chromatograms(&mut streamer, &mut writer)
+            })?;
             stats.chromatograms_converted = chrom_count;
 
             // If no chromatograms were found in mzML and we have MS1 spectra, generate TIC/BPC
@@ -171,8 +186,8 @@ impl MzMLConverter {
                     tic_times,
                     tic_intensities,
                 ) {
-                    writer
-                        .write_chromatogram(&tic_chrom)
+                    clock
+                        .time(Stage::Write, || writer.write_chromatogram(&tic_chrom))
                         .map_err(|e| ConversionError::WriterError(WriterError::InvalidData(e.to_string())))?;
                     stats.chromatograms_converted += 1;
                 }
@@ -184,8 +199,8 @@ impl MzMLConverter {
                     bpc_times,
                     bpc_intensities,
                 ) {
-                    writer
-                        .write_chromatogram(&bpc_chrom)
+                    clock
+                        .time(Stage::Write, || writer.write_chromatogram(&bpc_chrom))
                         .map_err(|e| ConversionError::WriterError(WriterError::InvalidData(e.to_string())))?;
                     stats.chromatograms_converted += 1;
                 }
@@ -196,8 +211,11 @@ impl MzMLConverter {
             info!("  Chromatograms: {}", stats.chromatograms_converted);
         }
 
+        stats.record_rt_order(&ingest_converter, None);
+        stats.record_skipped(&selection);
+
         // Close dataset (finalizes both peaks and chromatograms)
-        let dataset_stats = writer.close()?;
+        let dataset_stats = clock.time(Stage::Package, || writer.close())?;
         info!("Dataset finalized: {}", dataset_stats);
 
         // Get output file size
@@ -205,6 +223,7 @@ impl MzMLConverter {
         if stats.output_file_size > 0 {
             stats.compression_ratio = stats.source_file_size as f64 / stats.output_file_size as f64;
         }
+        clock.finish(&mut stats);
 
         info!("Conversion complete:");
         info!(
@@ -215,6 +234,7 @@ impl MzMLConverter {
         info!("  Input size: {} bytes", stats.source_file_size);
         info!("  Output size: {} bytes", stats.output_file_size);
         info!("  Compression ratio: {:.2}x", stats.compression_ratio);
+        info!("  Stage timings: {}", stats.timings);
 
         Ok(stats)
     }
@@ -226,6 +246,7 @@ impl MzMLConverter {
     ) -> Result<ConversionStats, ConversionError> {
         let input_path = input_path.as_ref();
         let output_path = output_path.as_ref();
+        let mut clock = StageClock::start();
 
         info!(
             "Converting {} to {} (v2 container)",
@@ -241,12 +262,12 @@ impl MzMLConverter {
             MzMLStreamer::open_with_buffer_size(input_path, buffer_size)?
         };
 
-        let mzml_metadata = streamer.read_metadata()?;
+        let mzml_metadata = clock.time(Stage::Parse, || streamer.read_metadata())?;
         info!("mzML version: {:?}", mzml_metadata.version);
 
         let mzpeak_metadata = self.convert_metadata(mzml_metadata, input_path)?;
 
-        let mut pending_raw = streamer.next_raw_spectrum()?;
+        let mut pending_raw = clock.time(Stage::Parse, || streamer.next_raw_spectrum())?;
         let mut has_imaging = is_imzml_path(input_path);
         let mut has_ion_mobility = false;
         if let Some(ref raw) = pending_raw {
@@ -264,13 +285,18 @@ impl MzMLConverter {
         let dataset_config = DatasetWriterV2Config {
             spectra_config: SpectraWriterConfig {
                 compression: self.config.writer_config.compression,
+                spectrum_checksums: self.config.writer_config.spectrum_checksums,
                 ..Default::default()
             },
             peaks_config: PeaksWriterV2Config {
                 compression: self.config.writer_config.compression,
                 row_group_size: self.config.writer_config.row_group_size,
+                progress: self.config.writer_config.progress.clone(),
                 ..Default::default()
             },
+            temp_dir: self.config.writer_config.temp_dir.clone(),
+            stage_locally: self.config.writer_config.stage_locally,
+            ..Default::default()
         };
 
         let vendor_hints = mzpeak_metadata.vendor_hints.clone();
@@ -283,8 +309,12 @@ impl MzMLConverter {
             ..Default::default()
         };
 
-        let mut ingest_converter = IngestSpectrumConverter::new();
+        let mut ingest_converter = self.ingest_converter();
+        let mut selection = ScanSelection::new(&self.config.scan_filters);
+        let mut rt_repair = self.rt_repair_stage();
+        let mut ready: Vec<SpectrumArrays> = Vec::new();
         let expected_count = streamer.spectrum_count();
+        self.report_expected_count(expected_count);
 
         info!(
             "Converting {} spectra...",
@@ -293,29 +323,71 @@ impl MzMLConverter {
                 .unwrap_or_else(|| "unknown".to_string())
         );
 
-        if let Some(raw) = pending_raw.take() {
-            let spectrum_v2 =
-                self.build_spectrum_v2_from_raw(raw, &mut ingest_converter, modality)?;
-            writer.write_spectrum(&spectrum_v2)?;
-            update_v2_stats(&mut stats, &spectrum_v2);
-            log_progress(&stats, expected_count, self.config.progress_interval);
+        if let Some(raw) = pending_raw.take().filter(|raw| selection.admit(raw)) {
+            let spectrum = self.build_spectrum_from_raw(
+                raw,
+                &mut selection,
+                &mut ingest_converter,
+                &mut clock,
+            )?;
+            clock.time(Stage::Transform, || {
+                push_ready(&mut rt_repair, spectrum, &mut ready)
+            });
+            self.write_ready_v2(
+                &mut writer,
+                &mut ready,
+                &mut stats,
+                modality,
+                expected_count,
+                &mut clock,
+            )?;
+        }
+
+        while let Some(raw_spectrum) = clock.time(Stage::Parse, || streamer.next_raw_spectrum())? {
+            if !selection.admit(&raw_spectrum) {
+                continue;
+            }
+            let spectrum = self.build_spectrum_from_raw(
+                raw_spectrum,
+                &mut selection,
+                &mut ingest_converter,
+                &mut clock,
+            )?;
+            clock.time(Stage::Transform, || {
+                push_ready(&mut rt_repair, spectrum, &mut ready)
+            });
+            self.write_ready_v2(
+                &mut writer,
+                &mut ready,
+                &mut stats,
+                modality,
+                expected_count,
+                &mut clock,
+            )?;
         }
 
-        while let Some(raw_spectrum) = streamer.next_raw_spectrum()? {
-            let spectrum_v2 =
-                self.build_spectrum_v2_from_raw(raw_spectrum, &mut ingest_converter, modality)?;
-            writer.write_spectrum(&spectrum_v2)?;
-            update_v2_stats(&mut stats, &spectrum_v2);
-            log_progress(&stats, expected_count, self.config.progress_interval);
+        if let Some(rt_repair) = rt_repair.as_mut() {
+            clock.time(Stage::Transform, || rt_repair.finish(&mut ready));
+            self.write_ready_v2(
+                &mut writer,
+                &mut ready,
+                &mut stats,
+                modality,
+                expected_count,
+                &mut clock,
+            )?;
         }
+        stats.record_rt_order(&ingest_converter, rt_repair.as_ref());
+        stats.record_skipped(&selection);
 
-        let dataset_stats = writer.close()?;
+        let dataset_stats = clock.time(Stage::Package, || writer.close())?;
         info!("Dataset finalized: {}", dataset_stats);
 
         stats.output_file_size = std::fs::metadata(output_path)?.len();
         if stats.output_file_size > 0 {
             stats.compression_ratio = stats.source_file_size as f64 / stats.output_file_size as f64;
         }
+        clock.finish(&mut stats);
 
         info!("Conversion complete:");
         info!(
@@ -326,27 +398,60 @@ impl MzMLConverter {
         info!("  Input size: {} bytes", stats.source_file_size);
         info!("  Output size: {} bytes", stats.output_file_size);
         info!("  Compression ratio: {:.2}x", stats.compression_ratio);
+        info!("  Stage timings: {}", stats.timings);
 
         Ok(stats)
     }
 
-    fn build_spectrum_v2_from_raw(
+    fn build_spectrum_from_raw(
         &self,
         raw_spectrum: RawMzMLSpectrum,
+        selection: &mut ScanSelection<'_>,
         ingest_converter: &mut IngestSpectrumConverter,
-        modality: Modality,
-    ) -> Result<SpectrumV2, ConversionError> {
+        clock: &mut StageClock,
+    ) -> Result<SpectrumArrays, ConversionError> {
         let DecodedRawSpectrum {
-            ingest,
+            mut ingest,
             retention_time: _,
             total_ion_current: _,
             base_peak_intensity: _,
-        } = self.build_ingest_spectrum_raw(raw_spectrum)?;
+        } = clock.time(Stage::Decode, || {
+            self.build_ingest_spectrum_raw(raw_spectrum)
+        })?;
+        selection.renumber(&mut ingest);
+
+        Ok(clock
+            .time(Stage::Transform, || ingest_converter.convert(ingest))
+            .map_err(WriterError::from)?)
+    }
 
-        let spectrum = ingest_converter
-            .convert(ingest)
-            .map_err(WriterError::from)?;
+    /// Write the spectra released by the ingest stages
+    fn write_ready_v2(
+        &self,
+        writer: &mut MzPeakDatasetWriterV2,
+        ready: &mut Vec<SpectrumArrays>,
+        stats: &mut ConversionStats,
+        modality: Modality,
+        expected_count: Option<usize>,
+        clock: &mut StageClock,
+    ) -> Result<(), ConversionError> {
+        for spectrum in ready.drain(..) {
+            let spectrum_v2 = clock.time(Stage::Transform, || {
+                self.build_spectrum_v2(spectrum, modality)
+            })?;
+            clock.time(Stage::Write, || writer.write_spectrum(&spectrum_v2))?;
+            update_v2_stats(stats, &spectrum_v2);
+            clock.progress(stats.peak_count);
+            log_progress(stats, expected_count, self.config.progress_interval);
+        }
+        Ok(())
+    }
 
+    fn build_spectrum_v2(
+        &self,
+        spectrum: SpectrumArrays,
+        modality: Modality,
+    ) -> Result<SpectrumV2, ConversionError> {
         let spectrum_v2 =
             SpectrumV2::try_from_spectrum_arrays(spectrum).map_err(ConversionError::WriterError)?;
 
@@ -394,12 +499,14 @@ impl MzMLConverter {
 
         let input_path = input_path.as_ref();
         let output_path = output_path.as_ref();
+        let mut clock = StageClock::start();
 
         info!(
             "Converting {} to {} (with sharding)",
             input_path.display(),
             output_path.display()
         );
+        self.reject_rt_repair("sharded output")?;
 
         // Get source file size
         let source_file_size = std::fs::metadata(input_path)?.len();
@@ -409,7 +516,7 @@ impl MzMLConverter {
         let mut streamer = MzMLStreamer::open_with_buffer_size(input_path, buffer_size)?;
 
         // Read metadata first
-        let mzml_metadata = streamer.read_metadata()?;
+        let mzml_metadata = clock.time(Stage::Parse, || streamer.read_metadata())?;
         info!("mzML version: {:?}", mzml_metadata.version);
 
         // Convert mzML metadata to mzPeak metadata
@@ -426,8 +533,10 @@ impl MzMLConverter {
         };
 
         let mut batch: Vec<SpectrumArrays> = Vec::with_capacity(self.config.batch_size);
-        let mut ingest_converter = IngestSpectrumConverter::new();
+        let mut ingest_converter = self.ingest_converter();
+        let mut selection = ScanSelection::new(&self.config.scan_filters);
         let expected_count = streamer.spectrum_count();
+        self.report_expected_count(expected_count);
 
         info!(
             "Converting {} spectra...",
@@ -436,10 +545,16 @@ impl MzMLConverter {
                 .unwrap_or_else(|| "unknown".to_string())
         );
 
-        while let Some(raw_spectrum) = streamer.next_raw_spectrum()? {
-            let DecodedRawSpectrum { ingest, .. } = self.build_ingest_spectrum_raw(raw_spectrum)?;
-            let spectrum = ingest_converter
-                .convert(ingest)
+        while let Some(raw_spectrum) = clock.time(Stage::Parse, || streamer.next_raw_spectrum())? {
+            if !selection.admit(&raw_spectrum) {
+                continue;
+            }
+            let DecodedRawSpectrum { mut ingest, .. } = clock.time(Stage::Decode, || {
+                self.build_ingest_spectrum_raw(raw_spectrum)
+            })?;
+            selection.renumber(&mut ingest);
+            let spectrum = clock
+                .time(Stage::Transform, || ingest_converter.convert(ingest))
                 .map_err(WriterError::from)?;
 
             // Update statistics
@@ -456,8 +571,9 @@ impl MzMLConverter {
 
             // Write batch if full
             if batch.len() >= self.config.batch_size {
-                writer.write_spectra_owned(batch)?;
-                batch = Vec::with_capacity(self.config.batch_size);
+                // Draining keeps the batch capacity for the next round
+                clock.time(Stage::Write, || writer.write_spectra_drain(&mut batch))?;
+                clock.progress(stats.peak_count);
 
                 // Progress update
                 if stats.spectra_count % self.config.progress_interval == 0 {
@@ -476,11 +592,14 @@ impl MzMLConverter {
 
         // Write remaining spectra
         if !batch.is_empty() {
-            writer.write_spectra_owned(batch)?;
+            clock.time(Stage::Write, || writer.write_spectra_drain(&mut batch))?;
         }
 
+        stats.record_rt_order(&ingest_converter, None);
+        stats.record_skipped(&selection);
+
         // Finalize
-        let writer_stats = writer.finish()?;
+        let writer_stats = clock.time(Stage::Package, || writer.finish())?;
         info!("{}", writer_stats);
 
         // Calculate total output size from all parts
@@ -493,6 +612,7 @@ impl MzMLConverter {
         if stats.output_file_size > 0 {
             stats.compression_ratio = stats.source_file_size as f64 / stats.output_file_size as f64;
         }
+        clock.finish(&mut stats);
 
         info!("Conversion complete:");
         info!(
@@ -506,11 +626,24 @@ impl MzMLConverter {
             stats.output_file_size, writer_stats.files_written
         );
         info!("  Compression ratio: {:.2}x", stats.compression_ratio);
+        info!("  Stage timings: {}", stats.timings);
 
         Ok(stats)
     }
 }
 
+/// Pass a converted spectrum through the retention time re-sort stage, if any
+fn push_ready(
+    rt_repair: &mut Option<RtReorderBuffer>,
+    spectrum: SpectrumArrays,
+    ready: &mut Vec<SpectrumArrays>,
+) {
+    match rt_repair {
+        Some(rt_repair) => rt_repair.push(spectrum, ready),
+        None => ready.push(spectrum),
+    }
+}
+
 fn update_v2_stats(stats: &mut ConversionStats, spectrum: &SpectrumV2) {
     stats.spectra_count += 1;
     stats.peak_count += spectrum.peaks.len();
@@ -123,3 +123,104 @@ fn test_chromatogram_conversion() {
     assert_eq!(chrom.intensity_array.len(), 3);
     assert_eq!(chrom.time_array, vec![0.0, 1.0, 2.0]);
 }
+
+#[test]
+fn test_rt_jitter_repair() {
+    use crate::ingest::{RtOrderConfig, RtReorderBuffer};
+
+    let retention_times = [10.0, 10.3, 10.1, 10.5, 10.4, 30.0, 5.0, 6.0];
+    let converter = MzMLConverter::new();
+    let rt_order = RtOrderConfig {
+        repair: true,
+        ..RtOrderConfig::default()
+    };
+    let mut contract = IngestSpectrumConverter::with_rt_order(&rt_order);
+    let mut repair = RtReorderBuffer::new(rt_order.jitter_tolerance);
+    let mut ready = Vec::new();
+    for (index, rt) in retention_times.iter().enumerate() {
+        let ingest = converter.build_ingest_spectrum(MzMLSpectrum {
+            index: index as i64,
+            id: format!("scan={}", index + 1),
+            ms_level: 1,
+            retention_time: Some(*rt),
+            mz_array: vec![100.0],
+            intensity_array: vec![1000.0],
+            ..Default::default()
+        });
+        let spectrum = contract
+            .convert(ingest)
+            .expect("jittered spectra should satisfy ingest contract");
+        repair.push(spectrum, &mut ready);
+    }
+    repair.finish(&mut ready);
+
+    assert_eq!(contract.rt_order().jitter_count(), 2);
+    assert_eq!(contract.rt_order().break_count(), 1);
+
+    // Jitter is re-sorted; the break restarts ordering instead of being repaired
+    let scans: Vec<i64> = ready.iter().map(|s| s.scan_number).collect();
+    assert_eq!(scans, vec![1, 3, 2, 5, 4, 6, 7, 8]);
+    let ids: Vec<i64> = ready.iter().map(|s| s.spectrum_id).collect();
+    assert_eq!(ids, (0..8).collect::<Vec<_>>());
+    assert_eq!(repair.reordered_count(), 4);
+
+    let mut strict = IngestSpectrumConverter::with_rt_order(&RtOrderConfig {
+        strict: true,
+        ..RtOrderConfig::default()
+    });
+    for (index, rt) in [60.0, 59.8, 30.0].iter().enumerate() {
+        let ingest = converter.build_ingest_spectrum(MzMLSpectrum {
+            index: index as i64,
+            id: format!("scan={}", index + 1),
+            ms_level: 1,
+            retention_time: Some(*rt),
+            mz_array: vec![100.0],
+            intensity_array: vec![1000.0],
+            ..Default::default()
+        });
+        let result = strict.convert(ingest);
+        assert_eq!(result.is_err(), index == 2);
+    }
+}
+
+#[test]
+fn test_scan_filter_skip_reasons() {
+    use super::ScanFilters;
+    use super::SkipReason;
+    use regex::Regex;
+
+    let filters = ScanFilters {
+        skip_ms_levels: [3].into_iter().collect(),
+        skip_native_id: Some(Regex::new(r"function=3\b").expect("valid regex")),
+        skip_filter_string: Some(Regex::new(r"^FTMS \+ p ESI Full lock").expect("valid regex")),
+        min_retention_time: Some(60.0),
+        max_retention_time: Some(600.0),
+    };
+    assert!(!filters.is_empty());
+    assert!(ScanFilters::default().is_empty());
+
+    let spectrum = |id: &str, ms_level: i16, rt: Option<f64>, filter: Option<&str>| RawMzMLSpectrum {
+        id: id.to_string(),
+        ms_level,
+        retention_time: rt,
+        filter_string: filter.map(str::to_string),
+        ..Default::default()
+    };
+    let kept = spectrum("function=1 process=0 scan=5", 1, Some(120.0), None);
+    assert_eq!(filters.skip_reason(&kept), None);
+    let cases = [
+        (spectrum("function=1 scan=6", 3, Some(120.0), None), SkipReason::MsLevel),
+        (spectrum("function=3 scan=7", 1, Some(120.0), None), SkipReason::NativeId),
+        (
+            spectrum("scan=8", 1, Some(120.0), Some("FTMS + p ESI Full lock ms")),
+            SkipReason::FilterString,
+        ),
+        (spectrum("scan=9", 1, Some(30.0), None), SkipReason::RetentionTime),
+        (spectrum("scan=10", 1, Some(601.0), None), SkipReason::RetentionTime),
+    ];
+    for (spectrum, reason) in cases {
+        assert_eq!(filters.skip_reason(&spectrum), Some(reason), "{}", spectrum.id);
+    }
+    // Without a retention time the limits cannot apply
+    assert_eq!(filters.skip_reason(&spectrum("scan=11", 1, None, None)), None);
+}
@@ -300,6 +300,24 @@ pub mod IMS_CV_ACCESSIONS {
     /// Position z (pixel coordinate)
     pub const POSITION_Z: &str = "IMS:1000052";
 
+    /// Max count of pixels x (scan settings)
+    pub const MAX_COUNT_PIXELS_X: &str = "IMS:1000042";
+
+    /// Max count of pixels y (scan settings)
+    pub const MAX_COUNT_PIXELS_Y: &str = "IMS:1000043";
+
+    /// Pixel size x in micrometers (scan settings)
+    pub const PIXEL_SIZE_X: &str = "IMS:1000046";
+
+    /// Pixel size y in micrometers (scan settings)
+    pub const PIXEL_SIZE_Y: &str = "IMS:1000047";
+
+    /// Absolute position offset x in micrometers (scan settings)
+    pub const ABSOLUTE_POSITION_OFFSET_X: &str = "IMS:1000053";
+
+    /// Absolute position offset y in micrometers (scan settings)
+    pub const ABSOLUTE_POSITION_OFFSET_Y: &str = "IMS:1000054";
+
     /// External array length (imzML external binary data)
     pub const EXTERNAL_ARRAY_LENGTH: &str = "IMS:1000102";
 
@@ -316,7 +334,6 @@ pub fn extract_cv_value(cv_params: &[CvParam], accession: &str) -> Option<String
 }
 
 /// Extract a CV parameter as f64 from a list by accession
-#[allow(dead_code)]
 pub fn extract_cv_f64(cv_params: &[CvParam], accession: &str) -> Option<f64> {
     cv_params
         .iter()
@@ -0,0 +1,792 @@
+//! # mzML Export
+//!
+//! Streaming writer that turns mzPeak spectra and chromatograms back into
+//! mzML 1.1, for tools and viewers that only read the XML format.
+//!
+//! By default the document is wrapped in `indexedmzML`: the writer records
+//! the byte offset of every `<spectrum>` and `<chromatogram>` element as it
+//! goes and appends the `indexList`, `indexListOffset` and SHA-1
+//! `fileChecksum` at the end, so viewers can seek without scanning the file.
+//! The writer never seeks, so indexed output also works on pipes; set
+//! [`MzMLWriterConfig::indexed`] to `false` for a plain `<mzML>` stream.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use mzpeak::mzml::export::{export_mzml, MzMLWriterConfig};
+//! use mzpeak::reader::MzPeakReader;
+//!
+//! let reader = MzPeakReader::open("data.mzpeak")?;
+//! let stats = export_mzml(&reader, "data.mzML", &MzMLWriterConfig::default())?;
+//! println!(
+//!     "Wrote {} spectra and {} chromatograms",
+//!     stats.spectra_written, stats.chromatograms_written
+//! );
+//! # Ok::<(), mzpeak::mzml::export::MzMLExportError>(())
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use base64::prelude::*;
+use flate2::write::ZlibEncoder;
+use quick_xml::escape::escape;
+
+use crate::chromatogram_writer::Chromatogram;
+use crate::mzml::BinaryCompression as CompressionType;
+use crate::mzml::cv_params::{IMS_CV_ACCESSIONS, MS_CV_ACCESSIONS};
+use crate::mzml::models::IndexEntry;
+use crate::reader::{MzPeakReader, ReaderError, RtRange};
+use crate::schema::{columns, Unit};
+use crate::writer::{OptionalColumnBuf, SpectrumArrays};
+
+mod sha1;
+
+#[cfg(test)]
+mod tests;
+
+use sha1::Sha1;
+
+/// Errors that can occur while exporting mzML
+#[derive(Debug, thiserror::Error)]
+pub enum MzMLExportError {
+    /// I/O error
+    #[error("I/O error: {0}")]
+    IoError(#[from] io::Error),
+
+    /// Error reading the mzPeak source
+    #[error("Reader error: {0}")]
+    ReaderError(#[from] ReaderError),
+
+    /// Writer methods called out of order or with inconsistent counts
+    #[error("Invalid export state: {0}")]
+    InvalidState(String),
+}
+
+/// Configuration for [`MzMLWriter`]
+#[derive(Debug, Clone)]
+pub struct MzMLWriterConfig {
+    /// Wrap the document in `indexedmzML` with spectrum/chromatogram offsets
+    pub indexed: bool,
+    /// Compression of the binary data arrays (`None` or `Zlib`)
+    pub compression: CompressionType,
+    /// Write the chromatogram list
+    pub include_chromatograms: bool,
+    /// `id` of the `<run>` element
+    pub run_id: String,
+    /// Instrument model name for the instrument configuration
+    pub instrument_model: Option<String>,
+    /// Unit of the ion mobility array (drift time in ms or 1/K0)
+    pub ion_mobility_unit: Unit,
+    /// Only export spectra inside this retention time range; chromatograms
+    /// are written whole
+    pub rt_range: Option<RtRange>,
+}
+
+impl Default for MzMLWriterConfig {
+    fn default() -> Self {
+        Self {
+            indexed: true,
+            compression: CompressionType::Zlib,
+            include_chromatograms: true,
+            run_id: "run".to_string(),
+            instrument_model: None,
+            ion_mobility_unit: Unit::Millisecond,
+            rt_range: None,
+        }
+    }
+}
+
+/// Statistics from an mzML export
+#[derive(Debug, Clone, Default)]
+pub struct MzMLExportStats {
+    /// Number of spectra written
+    pub spectra_written: usize,
+    /// Number of chromatograms written
+    pub chromatograms_written: usize,
+    /// Total bytes written
+    pub bytes_written: u64,
+    /// Spectrum index entries (empty for non-indexed output)
+    pub spectrum_index: Vec<IndexEntry>,
+    /// Chromatogram index entries (empty for non-indexed output)
+    pub chromatogram_index: Vec<IndexEntry>,
+}
+
+/// Tracks the byte position of the output and hashes it for the checksum
+struct CountingWriter<W: Write> {
+    inner: W,
+    position: u64,
+    hasher: Option<Sha1>,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.position += n as u64;
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(&buf[..n]);
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Spectra,
+    Chromatograms,
+    Done,
+}
+
+/// Streaming mzML writer
+///
+/// Call [`write_spectrum`](Self::write_spectrum) exactly `spectrum_count`
+/// times, then optionally [`write_chromatograms`](Self::write_chromatograms),
+/// then [`finish`](Self::finish).
+pub struct MzMLWriter<W: Write> {
+    out: CountingWriter<W>,
+    config: MzMLWriterConfig,
+    section: Section,
+    spectrum_count: usize,
+    stats: MzMLExportStats,
+}
+
+impl<W: Write> MzMLWriter<W> {
+    /// Write the document header and open the spectrum list
+    pub fn new(
+        inner: W,
+        config: MzMLWriterConfig,
+        spectrum_count: usize,
+    ) -> Result<Self, MzMLExportError> {
+        if !matches!(config.compression, CompressionType::None | CompressionType::Zlib) {
+            return Err(MzMLExportError::InvalidState(format!(
+                "unsupported export compression {:?}",
+                config.compression
+            )));
+        }
+        let mut writer = Self {
+            out: CountingWriter {
+                inner,
+                position: 0,
+                hasher: config.indexed.then(Sha1::new),
+            },
+            config,
+            section: Section::Spectra,
+            spectrum_count,
+            stats: MzMLExportStats::default(),
+        };
+        writer.write_header()?;
+        Ok(writer)
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        let out = &mut self.out;
+        writeln!(out, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+        if self.config.indexed {
+            writeln!(
+                out,
+                r#"<indexedmzML xmlns="http://psi.hupo.org/ms/mzml" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://psi.hupo.org/ms/mzml http://psidev.info/files/ms/mzML/xsd/mzML1.1.2_idx.xsd">"#
+            )?;
+        }
+        writeln!(
+            out,
+            r#"<mzML xmlns="http://psi.hupo.org/ms/mzml" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://psi.hupo.org/ms/mzml http://psidev.info/files/ms/mzML/xsd/mzML1.1.0.xsd" version="1.1.0">"#
+        )?;
+        writeln!(out, r#"  <cvList count="3">"#)?;
+        writeln!(
+            out,
+            r#"    <cv id="MS" fullName="Proteomics Standards Initiative Mass Spectrometry Ontology" URI="https://raw.githubusercontent.com/HUPO-PSI/psi-ms-CV/master/psi-ms.obo"/>"#
+        )?;
+        writeln!(
+            out,
+            r#"    <cv id="UO" fullName="Unit Ontology" URI="https://raw.githubusercontent.com/bio-ontology-research-group/unit-ontology/master/unit.obo"/>"#
+        )?;
+        writeln!(
+            out,
+            r#"    <cv id="IMS" fullName="Imaging MS Ontology" URI="https://raw.githubusercontent.com/imzML/imzML/master/imagingMS.obo"/>"#
+        )?;
+        writeln!(out, r#"  </cvList>"#)?;
+        writeln!(out, r#"  <fileDescription>"#)?;
+        writeln!(out, r#"    <fileContent>"#)?;
+        writeln!(
+            out,
+            r#"      <cvParam cvRef="MS" accession="MS:1000524" name="data file content" value=""/>"#
+        )?;
+        writeln!(out, r#"    </fileContent>"#)?;
+        writeln!(out, r#"  </fileDescription>"#)?;
+        writeln!(out, r#"  <softwareList count="1">"#)?;
+        writeln!(
+            out,
+            r#"    <software id="mzpeak" version="{}">"#,
+            env!("CARGO_PKG_VERSION")
+        )?;
+        writeln!(
+            out,
+            r#"      <cvParam cvRef="MS" accession="MS:1000799" name="custom unreleased software tool" value="mzpeak"/>"#
+        )?;
+        writeln!(out, r#"    </software>"#)?;
+        writeln!(out, r#"  </softwareList>"#)?;
+        writeln!(out, r#"  <instrumentConfigurationList count="1">"#)?;
+        writeln!(out, r#"    <instrumentConfiguration id="IC1">"#)?;
+        writeln!(
+            out,
+            r#"      <cvParam cvRef="MS" accession="MS:1000031" name="instrument model" value="{}"/>"#,
+            escape(self.config.instrument_model.as_deref().unwrap_or(""))
+        )?;
+        writeln!(out, r#"    </instrumentConfiguration>"#)?;
+        writeln!(out, r#"  </instrumentConfigurationList>"#)?;
+        writeln!(out, r#"  <dataProcessingList count="1">"#)?;
+        writeln!(out, r#"    <dataProcessing id="mzpeak_export">"#)?;
+        writeln!(out, r#"      <processingMethod order="0" softwareRef="mzpeak">"#)?;
+        writeln!(
+            out,
+            r#"        <cvParam cvRef="MS" accession="MS:1000544" name="Conversion to mzML" value=""/>"#
+        )?;
+        writeln!(out, r#"      </processingMethod>"#)?;
+        writeln!(out, r#"    </dataProcessing>"#)?;
+        writeln!(out, r#"  </dataProcessingList>"#)?;
+        writeln!(
+            out,
+            r#"  <run id="{}" defaultInstrumentConfigurationRef="IC1">"#,
+            escape(&self.config.run_id)
+        )?;
+        writeln!(
+            out,
+            r#"    <spectrumList count="{}" defaultDataProcessingRef="mzpeak_export">"#,
+            self.spectrum_count
+        )
+    }
+
+    /// Write one spectrum
+    pub fn write_spectrum(&mut self, spectrum: &SpectrumArrays) -> Result<(), MzMLExportError> {
+        if self.section != Section::Spectra {
+            return Err(MzMLExportError::InvalidState(
+                "spectra must be written before chromatograms".to_string(),
+            ));
+        }
+        if self.stats.spectra_written == self.spectrum_count {
+            return Err(MzMLExportError::InvalidState(format!(
+                "more spectra than the declared count of {}",
+                self.spectrum_count
+            )));
+        }
+
+        let id = format!("scan={}", spectrum.scan_number);
+        if self.config.indexed {
+            self.stats.spectrum_index.push(IndexEntry {
+                id: id.clone(),
+                offset: self.out.position + 6,
+            });
+        }
+
+        let peaks = &spectrum.peaks;
+        let out = &mut self.out;
+        writeln!(
+            out,
+            r#"      <spectrum index="{}" id="{}" defaultArrayLength="{}">"#,
+            self.stats.spectra_written,
+            id,
+            peaks.mz.len()
+        )?;
+        write_cv(out, 8, MS_CV_ACCESSIONS::MS_LEVEL, "ms level", spectrum.ms_level)?;
+        if spectrum.ms_level == 1 {
+            write_cv(out, 8, "MS:1000579", "MS1 spectrum", "")?;
+        } else {
+            write_cv(out, 8, "MS:1000580", "MSn spectrum", "")?;
+        }
+        match spectrum.polarity {
+            1 => write_cv(out, 8, MS_CV_ACCESSIONS::POSITIVE_SCAN, "positive scan", "")?,
+            -1 => write_cv(out, 8, MS_CV_ACCESSIONS::NEGATIVE_SCAN, "negative scan", "")?,
+            _ => {}
+        }
+        if let Some(tic) = spectrum.total_ion_current {
+            write_cv(out, 8, MS_CV_ACCESSIONS::TOTAL_ION_CURRENT, "total ion current", tic)?;
+        }
+        if let Some(mz) = spectrum.base_peak_mz {
+            write_cv_unit(out, 8, MS_CV_ACCESSIONS::BASE_PEAK_MZ, "base peak m/z", mz, Unit::MassToCharge)?;
+        }
+        if let Some(intensity) = spectrum.base_peak_intensity {
+            write_cv_unit(
+                out,
+                8,
+                MS_CV_ACCESSIONS::BASE_PEAK_INTENSITY,
+                "base peak intensity",
+                intensity,
+                Unit::DetectorCounts,
+            )?;
+        }
+
+        writeln!(out, r#"        <scanList count="1">"#)?;
+        write_cv(out, 10, "MS:1000795", "no combination", "")?;
+        writeln!(out, r#"          <scan>"#)?;
+        write_cv_unit(
+            out,
+            12,
+            MS_CV_ACCESSIONS::SCAN_START_TIME,
+            "scan start time",
+            spectrum.retention_time,
+            Unit::Second,
+        )?;
+        if let Some(injection_time) = spectrum.injection_time {
+            write_cv_unit(
+                out,
+                12,
+                MS_CV_ACCESSIONS::ION_INJECTION_TIME,
+                "ion injection time",
+                injection_time,
+                Unit::Millisecond,
+            )?;
+        }
+        for (accession, name, value) in [
+            (IMS_CV_ACCESSIONS::POSITION_X, "position x", spectrum.pixel_x),
+            (IMS_CV_ACCESSIONS::POSITION_Y, "position y", spectrum.pixel_y),
+            (IMS_CV_ACCESSIONS::POSITION_Z, "position z", spectrum.pixel_z),
+        ] {
+            if let Some(value) = value {
+                writeln!(
+                    out,
+                    r#"            <cvParam cvRef="IMS" accession="{}" name="{}" value="{}"/>"#,
+                    accession, name, value
+                )?;
+            }
+        }
+        writeln!(out, r#"          </scan>"#)?;
+        writeln!(out, r#"        </scanList>"#)?;
+
+        if let Some(precursor_mz) = spectrum.precursor_mz {
+            write_precursor(out, spectrum, precursor_mz)?;
+        }
+
+        let mut arrays = vec![
+            encode_f64(&peaks.mz, self.config.compression)?,
+            encode_f32(&peaks.intensity, self.config.compression)?,
+        ];
+        if let OptionalColumnBuf::AllPresent(ion_mobility) = &peaks.ion_mobility {
+            arrays.push(encode_f64(ion_mobility, self.config.compression)?);
+        }
+        let kinds = [
+            (MS_CV_ACCESSIONS::MZ_ARRAY, "m/z array", Unit::MassToCharge),
+            (MS_CV_ACCESSIONS::INTENSITY_ARRAY, "intensity array", Unit::DetectorCounts),
+            (
+                MS_CV_ACCESSIONS::ION_MOBILITY_ARRAY,
+                "ion mobility array",
+                self.config.ion_mobility_unit,
+            ),
+        ];
+        writeln!(out, r#"        <binaryDataArrayList count="{}">"#, arrays.len())?;
+        for (encoded, (accession, name, unit)) in arrays.iter().zip(kinds) {
+            write_binary_array(out, encoded, accession, name, unit, self.config.compression)?;
+        }
+        writeln!(out, r#"        </binaryDataArrayList>"#)?;
+        writeln!(out, r#"      </spectrum>"#)?;
+
+        self.stats.spectra_written += 1;
+        Ok(())
+    }
+
+    /// Close the spectrum list and write the chromatogram list
+    ///
+    /// Ignored when [`MzMLWriterConfig::include_chromatograms`] is `false`.
+    pub fn write_chromatograms(
+        &mut self,
+        chromatograms: &[Chromatogram],
+    ) -> Result<(), MzMLExportError> {
+        if self.section != Section::Spectra {
+            return Err(MzMLExportError::InvalidState(
+                "chromatograms can only be written once".to_string(),
+            ));
+        }
+        self.close_spectrum_list()?;
+        self.section = Section::Chromatograms;
+        if !self.config.include_chromatograms || chromatograms.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(
+            self.out,
+            r#"    <chromatogramList count="{}" defaultDataProcessingRef="mzpeak_export">"#,
+            chromatograms.len()
+        )?;
+        for (index, chromatogram) in chromatograms.iter().enumerate() {
+            let id = escape(&chromatogram.chromatogram_id).into_owned();
+            if self.config.indexed {
+                self.stats.chromatogram_index.push(IndexEntry {
+                    id: chromatogram.chromatogram_id.clone(),
+                    offset: self.out.position + 6,
+                });
+            }
+
+            let out = &mut self.out;
+            writeln!(
+                out,
+                r#"      <chromatogram index="{}" id="{}" defaultArrayLength="{}">"#,
+                index,
+                id,
+                chromatogram.time_array.len()
+            )?;
+            let (accession, name) = chromatogram_type_term(&chromatogram.chromatogram_type);
+            write_cv(out, 8, accession, name, "")?;
+            writeln!(out, r#"        <binaryDataArrayList count="2">"#)?;
+            let time = encode_f64(&chromatogram.time_array, self.config.compression)?;
+            write_binary_array(
+                out,
+                &time,
+                MS_CV_ACCESSIONS::TIME_ARRAY,
+                "time array",
+                Unit::Second,
+                self.config.compression,
+            )?;
+            let intensity = encode_f32(&chromatogram.intensity_array, self.config.compression)?;
+            write_binary_array(
+                out,
+                &intensity,
+                MS_CV_ACCESSIONS::INTENSITY_ARRAY,
+                "intensity array",
+                Unit::DetectorCounts,
+                self.config.compression,
+            )?;
+            writeln!(out, r#"        </binaryDataArrayList>"#)?;
+            writeln!(out, r#"      </chromatogram>"#)?;
+            self.stats.chromatograms_written += 1;
+        }
+        writeln!(self.out, r#"    </chromatogramList>"#)?;
+        Ok(())
+    }
+
+    fn close_spectrum_list(&mut self) -> Result<(), MzMLExportError> {
+        if self.stats.spectra_written != self.spectrum_count {
+            return Err(MzMLExportError::InvalidState(format!(
+                "declared {} spectra but wrote {}",
+                self.spectrum_count, self.stats.spectra_written
+            )));
+        }
+        writeln!(self.out, r#"    </spectrumList>"#)?;
+        Ok(())
+    }
+
+    /// Close the document, append the index and return the inner writer
+    pub fn finish(mut self) -> Result<(W, MzMLExportStats), MzMLExportError> {
+        match self.section {
+            Section::Spectra => self.close_spectrum_list()?,
+            Section::Chromatograms => {}
+            Section::Done => unreachable!("finish consumes the writer"),
+        }
+        self.section = Section::Done;
+
+        writeln!(self.out, r#"  </run>"#)?;
+        writeln!(self.out, r#"</mzML>"#)?;
+        if self.config.indexed {
+            self.write_index()?;
+        }
+        self.out.flush()?;
+
+        self.stats.bytes_written = self.out.position;
+        Ok((self.out.inner, self.stats))
+    }
+
+    fn write_index(&mut self) -> io::Result<()> {
+        let index_list_offset = self.out.position;
+        let lists: Vec<(&str, &[IndexEntry])> = [
+            ("spectrum", self.stats.spectrum_index.as_slice()),
+            ("chromatogram", self.stats.chromatogram_index.as_slice()),
+        ]
+        .into_iter()
+        .filter(|(_, entries)| !entries.is_empty())
+        .collect();
+
+        let out = &mut self.out;
+        writeln!(out, r#"<indexList count="{}">"#, lists.len())?;
+        for (name, entries) in lists {
+            writeln!(out, r#"  <index name="{}">"#, name)?;
+            for entry in entries {
+                writeln!(
+                    out,
+                    r#"    <offset idRef="{}">{}</offset>"#,
+                    escape(&entry.id),
+                    entry.offset
+                )?;
+            }
+            writeln!(out, r#"  </index>"#)?;
+        }
+        writeln!(out, r#"</indexList>"#)?;
+        writeln!(out, r#"<indexListOffset>{}</indexListOffset>"#, index_list_offset)?;
+        // The checksum covers everything up to and including this tag
+        write!(out, r#"<fileChecksum>"#)?;
+        let checksum = out.hasher.take().expect("indexed output is hashed").finalize_hex();
+        writeln!(out, r#"{}</fileChecksum>"#, checksum)?;
+        writeln!(out, r#"</indexedmzML>"#)
+    }
+}
+
+/// Export a whole mzPeak dataset to an mzML file
+pub fn export_mzml<P: AsRef<Path>>(
+    reader: &MzPeakReader,
+    output: P,
+    config: &MzMLWriterConfig,
+) -> Result<MzMLExportStats, MzMLExportError> {
+    let file = BufWriter::new(File::create(output)?);
+    let (_, stats) = export_mzml_to_writer(reader, file, config)?;
+    Ok(stats)
+}
+
+/// Export a whole mzPeak dataset as mzML into any writer (e.g. stdout)
+///
+/// The instrument model and ion mobility unit are taken from the dataset
+/// when the config leaves them at their defaults.
+pub fn export_mzml_to_writer<W: Write>(
+    reader: &MzPeakReader,
+    inner: W,
+    config: &MzMLWriterConfig,
+) -> Result<(W, MzMLExportStats), MzMLExportError> {
+    let mut config = config.clone();
+    let metadata = reader.metadata();
+    if config.instrument_model.is_none() {
+        config.instrument_model = metadata
+            .mzpeak_metadata
+            .as_ref()
+            .and_then(|m| m.instrument.as_ref())
+            .and_then(|instrument| instrument.model.clone());
+    }
+    if let Some(unit) = metadata.column_unit(columns::ION_MOBILITY) {
+        config.ion_mobility_unit = unit;
+    }
+
+    let rt_range = config.rt_range;
+    let in_range = |retention_time: f32| match rt_range {
+        Some(range) => range.contains(retention_time),
+        None => true,
+    };
+
+    // The spectrum count precedes the spectra; v2 manifests record it
+    let spectrum_count = match reader.manifest()? {
+        Some(manifest) if rt_range.is_none() => manifest.spectrum_count as usize,
+        _ => {
+            let mut count = 0;
+            for spectrum in reader.iter_spectra_arrays_streaming()? {
+                if in_range(spectrum?.retention_time) {
+                    count += 1;
+                }
+            }
+            count
+        }
+    };
+
+    let mut writer = MzMLWriter::new(inner, config, spectrum_count)?;
+    for spectrum in reader.iter_spectra_arrays_streaming()? {
+        let spectrum = spectrum?;
+        if in_range(spectrum.retention_time) {
+            writer.write_spectrum(&spectrum.to_owned()?)?;
+        }
+    }
+    writer.write_chromatograms(&reader.read_chromatograms()?)?;
+    writer.finish()
+}
+
+fn write_cv<W: Write, V: std::fmt::Display>(
+    out: &mut W,
+    indent: usize,
+    accession: &str,
+    name: &str,
+    value: V,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        r#"{:indent$}<cvParam cvRef="MS" accession="{}" name="{}" value="{}"/>"#,
+        "",
+        accession,
+        name,
+        value,
+        indent = indent
+    )
+}
+
+fn write_cv_unit<W: Write, V: std::fmt::Display>(
+    out: &mut W,
+    indent: usize,
+    accession: &str,
+    name: &str,
+    value: V,
+    unit: Unit,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        r#"{:indent$}<cvParam cvRef="MS" accession="{}" name="{}" value="{}" unitCvRef="{}" unitAccession="{}" unitName="{}"/>"#,
+        "",
+        accession,
+        name,
+        value,
+        unit_cv_ref(unit),
+        unit.accession(),
+        unit.name(),
+        indent = indent
+    )
+}
+
+fn unit_cv_ref(unit: Unit) -> &'static str {
+    unit.accession().split(':').next().unwrap_or("MS")
+}
+
+fn write_precursor<W: Write>(
+    out: &mut W,
+    spectrum: &SpectrumArrays,
+    precursor_mz: f64,
+) -> io::Result<()> {
+    writeln!(out, r#"        <precursorList count="1">"#)?;
+    writeln!(out, r#"          <precursor>"#)?;
+    if spectrum.isolation_window_lower.is_some() || spectrum.isolation_window_upper.is_some() {
+        writeln!(out, r#"            <isolationWindow>"#)?;
+        write_cv_unit(
+            out,
+            14,
+            MS_CV_ACCESSIONS::ISOLATION_WINDOW_TARGET_MZ,
+            "isolation window target m/z",
+            precursor_mz,
+            Unit::MassToCharge,
+        )?;
+        if let Some(lower) = spectrum.isolation_window_lower {
+            write_cv_unit(
+                out,
+                14,
+                MS_CV_ACCESSIONS::ISOLATION_WINDOW_LOWER_OFFSET,
+                "isolation window lower offset",
+                lower,
+                Unit::MassToCharge,
+            )?;
+        }
+        if let Some(upper) = spectrum.isolation_window_upper {
+            write_cv_unit(
+                out,
+                14,
+                MS_CV_ACCESSIONS::ISOLATION_WINDOW_UPPER_OFFSET,
+                "isolation window upper offset",
+                upper,
+                Unit::MassToCharge,
+            )?;
+        }
+        writeln!(out, r#"            </isolationWindow>"#)?;
+    }
+    writeln!(out, r#"            <selectedIonList count="1">"#)?;
+    writeln!(out, r#"              <selectedIon>"#)?;
+    write_cv_unit(
+        out,
+        16,
+        MS_CV_ACCESSIONS::SELECTED_ION_MZ,
+        "selected ion m/z",
+        precursor_mz,
+        Unit::MassToCharge,
+    )?;
+    if let Some(charge) = spectrum.precursor_charge {
+        write_cv(out, 16, MS_CV_ACCESSIONS::CHARGE_STATE, "charge state", charge)?;
+    }
+    if let Some(intensity) = spectrum.precursor_intensity {
+        write_cv_unit(
+            out,
+            16,
+            MS_CV_ACCESSIONS::PEAK_INTENSITY,
+            "peak intensity",
+            intensity,
+            Unit::DetectorCounts,
+        )?;
+    }
+    writeln!(out, r#"              </selectedIon>"#)?;
+    writeln!(out, r#"            </selectedIonList>"#)?;
+    writeln!(out, r#"            <activation>"#)?;
+    if let Some(energy) = spectrum.collision_energy {
+        write_cv_unit(
+            out,
+            14,
+            MS_CV_ACCESSIONS::COLLISION_ENERGY,
+            "collision energy",
+            energy,
+            Unit::Electronvolt,
+        )?;
+    }
+    writeln!(out, r#"            </activation>"#)?;
+    writeln!(out, r#"          </precursor>"#)?;
+    writeln!(out, r#"        </precursorList>"#)
+}
+
+/// Base64-encoded array data with its precision accession
+struct EncodedArray {
+    data: String,
+    precision: (&'static str, &'static str),
+}
+
+fn encode_f64(values: &[f64], compression: CompressionType) -> io::Result<EncodedArray> {
+    let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    Ok(EncodedArray {
+        data: encode_bytes(&bytes, compression)?,
+        precision: (MS_CV_ACCESSIONS::FLOAT_64_BIT, "64-bit float"),
+    })
+}
+
+fn encode_f32(values: &[f32], compression: CompressionType) -> io::Result<EncodedArray> {
+    let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    Ok(EncodedArray {
+        data: encode_bytes(&bytes, compression)?,
+        precision: (MS_CV_ACCESSIONS::FLOAT_32_BIT, "32-bit float"),
+    })
+}
+
+fn encode_bytes(bytes: &[u8], compression: CompressionType) -> io::Result<String> {
+    match compression {
+        CompressionType::Zlib => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(bytes)?;
+            Ok(BASE64_STANDARD.encode(encoder.finish()?))
+        }
+        _ => Ok(BASE64_STANDARD.encode(bytes)),
+    }
+}
+
+fn write_binary_array<W: Write>(
+    out: &mut W,
+    encoded: &EncodedArray,
+    accession: &str,
+    name: &str,
+    unit: Unit,
+    compression: CompressionType,
+) -> io::Result<()> {
+    writeln!(
+        out,
+        r#"          <binaryDataArray encodedLength="{}">"#,
+        encoded.data.len()
+    )?;
+    write_cv(out, 12, encoded.precision.0, encoded.precision.1, "")?;
+    match compression {
+        CompressionType::Zlib => {
+            write_cv(out, 12, MS_CV_ACCESSIONS::ZLIB_COMPRESSION, "zlib compression", "")?
+        }
+        _ => write_cv(out, 12, MS_CV_ACCESSIONS::NO_COMPRESSION, "no compression", "")?,
+    }
+    write_cv_unit(out, 12, accession, name, "", unit)?;
+    writeln!(out, r#"            <binary>{}</binary>"#, encoded.data)?;
+    writeln!(out, r#"          </binaryDataArray>"#)
+}
+
+/// CV term of an mzPeak chromatogram type string
+fn chromatogram_type_term(chromatogram_type: &str) -> (&'static str, &'static str) {
+    match chromatogram_type {
+        "TIC" => (MS_CV_ACCESSIONS::TIC_CHROMATOGRAM, "total ion current chromatogram"),
+        "BPC" => (MS_CV_ACCESSIONS::BPC_CHROMATOGRAM, "basepeak chromatogram"),
+        "SIM" => (
+            MS_CV_ACCESSIONS::SIM_CHROMATOGRAM,
+            "selected ion monitoring chromatogram",
+        ),
+        "SRM" => (
+            MS_CV_ACCESSIONS::SRM_CHROMATOGRAM,
+            "selected reaction monitoring chromatogram",
+        ),
+        "XIC" => (
+            MS_CV_ACCESSIONS::XIC_CHROMATOGRAM,
+            "selected ion current chromatogram",
+        ),
+        "Absorption" => ("MS:1000812", "absorption chromatogram"),
+        "Emission" => ("MS:1000813", "emission chromatogram"),
+        _ => ("MS:1000626", "chromatogram type"),
+    }
+}
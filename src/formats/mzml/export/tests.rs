@@ -0,0 +1,183 @@
+use super::*;
+use crate::metadata::MzPeakMetadata;
+use crate::mzml::MzMLStreamer;
+use crate::writer::{MzPeakWriter, PeakArrays, WriterConfig};
+
+fn test_spectra() -> Vec<SpectrumArrays> {
+    let ms1 = SpectrumArrays::new_ms1(
+        0,
+        1,
+        60.0,
+        1,
+        PeakArrays::new(vec![400.0, 500.25, 600.5], vec![1000.0, 2000.0, 3000.0]),
+    );
+    let mut ms2 = SpectrumArrays::new_ms2(
+        1,
+        2,
+        61.5,
+        1,
+        500.25,
+        PeakArrays::new(vec![150.1, 250.2], vec![10.0, 20.0]),
+    );
+    ms2.precursor_charge = Some(2);
+    ms2.isolation_window_lower = Some(1.0);
+    ms2.isolation_window_upper = Some(1.0);
+    ms2.collision_energy = Some(30.0);
+    vec![ms1, ms2]
+}
+
+fn test_chromatograms() -> Vec<Chromatogram> {
+    vec![Chromatogram::new(
+        "TIC".to_string(),
+        "TIC".to_string(),
+        vec![60.0, 61.5],
+        vec![6000.0, 30.0],
+    )
+    .unwrap()]
+}
+
+fn write_mzml(config: MzMLWriterConfig) -> (Vec<u8>, MzMLExportStats) {
+    let spectra = test_spectra();
+    let mut writer = MzMLWriter::new(Vec::new(), config, spectra.len()).unwrap();
+    for spectrum in &spectra {
+        writer.write_spectrum(spectrum).unwrap();
+    }
+    writer.write_chromatograms(&test_chromatograms()).unwrap();
+    writer.finish().unwrap()
+}
+
+#[test]
+fn test_sha1_known_vectors() {
+    let digest = |data: &[u8]| {
+        let mut hasher = Sha1::new();
+        hasher.update(data);
+        hasher.finalize_hex()
+    };
+    assert_eq!(digest(b""), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+    assert_eq!(digest(b"abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+    assert_eq!(
+        digest(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+        "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+    );
+}
+
+#[test]
+fn test_export_roundtrip_with_chromatograms() {
+    let (bytes, stats) = write_mzml(MzMLWriterConfig::default());
+    assert_eq!(stats.spectra_written, 2);
+    assert_eq!(stats.chromatograms_written, 1);
+    assert_eq!(stats.bytes_written, bytes.len() as u64);
+
+    let mut streamer = MzMLStreamer::new(&bytes[..]).unwrap();
+    let ms1 = streamer.next_spectrum().unwrap().unwrap();
+    assert_eq!(ms1.id, "scan=1");
+    assert_eq!(ms1.ms_level, 1);
+    assert_eq!(ms1.mz_array, vec![400.0, 500.25, 600.5]);
+    assert_eq!(ms1.intensity_array, vec![1000.0, 2000.0, 3000.0]);
+    assert_eq!(ms1.retention_time, Some(60.0));
+
+    let ms2 = streamer.next_spectrum().unwrap().unwrap();
+    assert_eq!(ms2.ms_level, 2);
+    assert_eq!(ms2.precursors[0].selected_ion_mz, Some(500.25));
+    assert_eq!(ms2.precursors[0].selected_ion_charge, Some(2));
+    assert_eq!(ms2.precursors[0].collision_energy, Some(30.0));
+    assert!(streamer.next_spectrum().unwrap().is_none());
+
+    let tic = streamer.next_chromatogram().unwrap().unwrap();
+    assert_eq!(tic.id, "TIC");
+    assert_eq!(tic.chromatogram_type, crate::mzml::ChromatogramType::TIC);
+    assert_eq!(tic.time_array, vec![60.0, 61.5]);
+    assert_eq!(tic.intensity_array, vec![6000.0, 30.0]);
+}
+
+#[test]
+fn test_export_index_offsets_and_checksum() {
+    let (bytes, stats) = write_mzml(MzMLWriterConfig::default());
+    let text = String::from_utf8(bytes.clone()).unwrap();
+
+    for entry in &stats.spectrum_index {
+        assert!(text[entry.offset as usize..].starts_with(&format!(
+            r#"<spectrum index="{}" id="{}""#,
+            stats.spectrum_index.iter().position(|e| e.id == entry.id).unwrap(),
+            entry.id
+        )));
+    }
+    let tic = &stats.chromatogram_index[0];
+    assert!(text[tic.offset as usize..].starts_with(r#"<chromatogram index="0" id="TIC""#));
+
+    // The index written to the file matches the one reported
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("export.mzML");
+    std::fs::write(&path, &bytes).unwrap();
+    let streamer = MzMLStreamer::open_indexed(&path).unwrap();
+    let index = streamer.index();
+    let offset = index.index_list_offset.unwrap() as usize;
+    assert!(text[offset..].starts_with("<indexList"));
+    assert_eq!(index.spectrum_count(), 2);
+    assert_eq!(index.chromatogram_count(), 1);
+    assert_eq!(index.spectrum_index[1].offset, stats.spectrum_index[1].offset);
+
+    // SHA-1 over everything up to and including <fileChecksum>
+    let tag = "<fileChecksum>";
+    let end = text.find(tag).unwrap() + tag.len();
+    let mut hasher = Sha1::new();
+    hasher.update(&bytes[..end]);
+    assert!(text[end..].starts_with(&hasher.finalize_hex()));
+}
+
+#[test]
+fn test_export_without_index() {
+    let config = MzMLWriterConfig {
+        indexed: false,
+        compression: CompressionType::None,
+        ..Default::default()
+    };
+    let (bytes, stats) = write_mzml(config);
+    let text = String::from_utf8(bytes.clone()).unwrap();
+    assert!(!text.contains("indexedmzML"));
+    assert!(!text.contains("<indexList"));
+    assert!(text.contains("no compression"));
+    assert!(stats.spectrum_index.is_empty());
+
+    let mut streamer = MzMLStreamer::new(&bytes[..]).unwrap();
+    assert_eq!(
+        streamer.next_spectrum().unwrap().unwrap().mz_array,
+        vec![400.0, 500.25, 600.5]
+    );
+}
+
+#[test]
+fn test_export_rejects_wrong_spectrum_count() {
+    let mut writer = MzMLWriter::new(Vec::new(), MzMLWriterConfig::default(), 3).unwrap();
+    for spectrum in &test_spectra() {
+        writer.write_spectrum(spectrum).unwrap();
+    }
+    assert!(matches!(
+        writer.write_chromatograms(&[]),
+        Err(MzMLExportError::InvalidState(_))
+    ));
+}
+
+#[test]
+fn test_export_mzml_from_reader() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("source.mzpeak.parquet");
+    let mut writer =
+        MzPeakWriter::new_file(&source, &MzPeakMetadata::new(), WriterConfig::default()).unwrap();
+    for spectrum in &test_spectra() {
+        writer.write_spectrum_arrays(spectrum).unwrap();
+    }
+    writer.finish().unwrap();
+
+    let reader = MzPeakReader::open(&source).unwrap();
+    let output = dir.path().join("export.mzML");
+    let stats = export_mzml(&reader, &output, &MzMLWriterConfig::default()).unwrap();
+    assert_eq!(stats.spectra_written, 2);
+    assert_eq!(stats.chromatograms_written, 0);
+
+    let mut streamer = MzMLStreamer::open_indexed(&output).unwrap();
+    assert_eq!(streamer.index().spectrum_count(), 2);
+    assert_eq!(streamer.spectrum_count(), Some(2));
+    let ms2 = streamer.next_spectrum().and_then(|_| streamer.next_spectrum());
+    assert_eq!(ms2.unwrap().unwrap().mz_array, vec![150.1, 250.2]);
+}
@@ -34,9 +34,12 @@
 //!         │               └── binary (base64 data)
 //!         └── chromatogramList (optional)
 //! ```
+//!
+//! The reverse direction, mzPeak to (indexed) mzML, lives in [`export`].
 
 mod binary;
 mod cv_params;
+pub mod export;
 mod external;
 mod models;
 mod streamer;
@@ -50,4 +53,4 @@ pub use cv_params::{CvParam, extract_cv_value, IMS_CV_ACCESSIONS, MS_CV_ACCESSIO
 pub(crate) use external::ExternalBinaryReader;
 pub use models::*;
 pub use streamer::{MzMLStreamer, MzMLError, SpectrumIterator, RawSpectrumIterator, DEFAULT_INPUT_BUFFER_SIZE};
-pub use converter::{MzMLConverter, ConversionConfig, ConversionStats, OutputFormat, StreamingConfig};
+pub use converter::{MzMLConverter, ConversionConfig, ConversionStats, OutputFormat, ScanFilters, SkippedSpectra, Stage, StageTimings, StreamingConfig};
@@ -37,6 +37,9 @@ pub struct MzMLFileMetadata {
 
     /// Sample information
     pub samples: Vec<Sample>,
+
+    /// CV parameters of the scan settings (imzML image geometry)
+    pub scan_settings: Vec<CvParam>,
 }
 
 /// Source file information from mzML
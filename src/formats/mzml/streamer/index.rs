@@ -145,6 +145,9 @@ impl<R: BufRead> MzMLStreamer<R> {
     fn parse_index_data(data: &[u8], offset: u64) -> Result<MzMLIndex, MzMLError> {
         let mut reader = Reader::from_reader(data);
         reader.config_mut().trim_text(true);
+        // The slice starts mid-document, so the closing </indexedmzML> has no
+        // matching start tag
+        reader.config_mut().allow_unmatched_ends = true;
 
         let mut buf = Vec::new();
         let mut index = MzMLIndex {
@@ -32,6 +32,9 @@ impl<R: BufRead> MzMLStreamer<R> {
                     b"sampleList" => {
                         self.parse_sample_list()?;
                     }
+                    b"scanSettingsList" => {
+                        self.parse_scan_settings_list()?;
+                    }
                     b"run" => {
                         self.metadata.run_id = get_attribute(e, "id")?;
                         self.metadata.run_start_time = get_attribute(e, "startTimeStamp")?;
@@ -495,6 +498,38 @@ impl<R: BufRead> MzMLStreamer<R> {
         Ok(())
     }
 
+    /// Parse scanSettingsList element, collecting the CV parameters of all
+    /// scan settings
+    fn parse_scan_settings_list(&mut self) -> Result<(), MzMLError> {
+        let mut depth = 1;
+        let mut buf = Vec::new();
+
+        loop {
+            match self.reader.read_event_into(&mut buf) {
+                Ok(Event::Start(_)) => {
+                    depth += 1;
+                }
+                Ok(Event::Empty(ref e)) => {
+                    if e.name().as_ref() == b"cvParam" {
+                        let cv = parse_cv_param(e)?;
+                        self.metadata.scan_settings.push(cv);
+                    }
+                }
+                Ok(Event::End(ref e)) => {
+                    depth -= 1;
+                    if e.name().as_ref() == b"scanSettingsList" && depth == 0 {
+                        break;
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(MzMLError::XmlError(e)),
+                _ => {}
+            }
+            buf.clear();
+        }
+        Ok(())
+    }
+
     /// Parse sample element content
     fn parse_sample_content(&mut self, id: String, name: Option<String>) -> Result<Sample, MzMLError> {
         let mut sample = Sample {
@@ -50,6 +50,19 @@ fn test_parse_minimal_mzml() {
     assert!((spectrum.mz_array[1] - 200.0).abs() < 0.001);
 }
 
+#[test]
+fn test_parse_negative_scan() {
+    let negative = MINIMAL_MZML.replace(
+        r#"accession="MS:1000130" name="positive scan""#,
+        r#"accession="MS:1000129" name="negative scan""#,
+    );
+    let reader = std::io::Cursor::new(negative);
+    let mut streamer = MzMLStreamer::new(BufReader::new(reader)).unwrap();
+
+    let spectrum = streamer.next_spectrum().unwrap().unwrap();
+    assert_eq!(spectrum.polarity, -1);
+}
+
 #[test]
 fn test_scan_number_extraction() {
     let spectrum = crate::mzml::models::MzMLSpectrum {
@@ -1,7 +1,7 @@
 //! Conversion from Bruker TDF format to mzpeak thin-waist contract.
 
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[cfg(feature = "parallel-decode")]
 use rayon::prelude::*;
@@ -9,9 +9,9 @@ use timsrust::converters::{ConvertableDomain, Scan2ImConverter, Tof2MzConverter}
 use timsrust::readers::PrecursorReader;
 use timsrust::{MSLevel, Precursor};
 
-use crate::dataset::{DatasetWriterV2Config, MzPeakDatasetWriterV2};
+use crate::dataset::{DatasetWriterV2Config, MzPeakDatasetWriter, MzPeakDatasetWriterV2};
 use crate::ingest::{IngestSpectrum, IngestSpectrumConverter};
-use crate::metadata::{MzPeakMetadata, SourceFileInfo, VendorHints};
+use crate::metadata::{ImagingMetadata, MzPeakMetadata, SourceFileInfo, VendorHints};
 use crate::readers::{RawTdfFrame, TdfStreamer};
 use crate::schema::manifest::Modality;
 use crate::writer::{
@@ -21,12 +21,34 @@ use crate::writer::{
 
 use super::error::TdfError;
 
+/// How the ion mobility axis of TDF frames is written.
+///
+/// Many downstream tools cannot consume ion mobility yet. Collapsing sums all
+/// peaks of a frame that share a TOF index (and therefore an m/z) across the
+/// mobility scans, yielding a conventional 3D spectrum per frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TdfMobilityMode {
+    /// Keep full 4D peaks with per-peak ion mobility (default)
+    #[default]
+    Full,
+    /// Collapse mobility into summed 3D spectra
+    Collapsed,
+    /// Write the full data and a collapsed copy next to it
+    /// (see [`collapsed_output_path`])
+    Both,
+}
+
 /// Configuration for TDF to SpectrumArrays conversion.
 pub struct TdfConversionConfig {
     /// Whether to include extended metadata (e.g., TIC/base peak prepopulation).
     pub include_extended_metadata: bool,
     /// Batch size for streaming + parallel decode.
     pub batch_size: usize,
+    /// Whether to keep or collapse the ion mobility axis.
+    ///
+    /// [`TdfMobilityMode::Both`] only affects the dataset/container writers;
+    /// in-memory conversion and streaming yield the full data.
+    pub mobility_mode: TdfMobilityMode,
 }
 
 impl Default for TdfConversionConfig {
@@ -34,6 +56,7 @@ impl Default for TdfConversionConfig {
         Self {
             include_extended_metadata: true,
             batch_size: 256,
+            mobility_mode: TdfMobilityMode::Full,
         }
     }
 }
@@ -61,12 +84,141 @@ struct DecoderContext {
     precursors_by_frame: HashMap<usize, Vec<Precursor>>,
 }
 
+impl TdfConversionStats {
+    fn record(&mut self, ms_level: i16, peak_count: usize, has_pixel: bool) {
+        self.spectra_read += 1;
+        self.peaks_total += peak_count;
+        match ms_level {
+            1 => self.ms1_count += 1,
+            2 => self.ms2_count += 1,
+            _ => {}
+        }
+        if has_pixel {
+            self.imaging_frames += 1;
+        }
+    }
+}
+
 /// Raw frame plus assigned spectrum ID for ordering enforcement.
 struct IndexedRawFrame {
     spectrum_id: i64,
     frame: RawTdfFrame,
 }
 
+/// Streaming conversion of a TDF dataset, one frame batch at a time.
+///
+/// Only `batch_size` frames are held (raw and decoded) at any point, so
+/// memory use is bounded by the batch size rather than the run length.
+/// Spectrum IDs are contiguous from 0 across batches.
+///
+/// ```no_run
+/// # #[cfg(feature = "tdf")]
+/// # {
+/// use mzpeak::tdf::TdfConverter;
+///
+/// for batch in TdfConverter::new().stream("sample.d")? {
+///     for spectrum in batch? {
+///         // Write or aggregate the spectrum, then drop it
+///         let _ = spectrum.peak_count();
+///     }
+/// }
+/// # }
+/// # Ok::<(), mzpeak::tdf::TdfError>(())
+/// ```
+pub struct TdfSpectrumBatches {
+    streamer: TdfStreamer,
+    ctx: DecoderContext,
+    ingest_converter: IngestSpectrumConverter,
+    next_spectrum_id: i64,
+    collapse: bool,
+    /// `(pixel_x, pixel_y, stage_x_um, stage_y_um)` of the MALDI frames read
+    stage_positions: Vec<(i32, i32, f64, f64)>,
+}
+
+impl TdfSpectrumBatches {
+    /// Total frame count in the dataset.
+    pub fn frame_count(&self) -> usize {
+        self.streamer.len()
+    }
+
+    /// Whether this dataset contains MALDI imaging frames.
+    pub fn is_maldi(&self) -> bool {
+        self.streamer.is_maldi()
+    }
+
+    /// Pixel grid calibration derived from the stage positions of the MALDI
+    /// frames read so far.
+    ///
+    /// Complete once every batch has been read; `None` for LC data.
+    pub fn imaging_metadata(&self) -> Option<ImagingMetadata> {
+        ImagingMetadata::from_stage_positions(&self.stage_positions)
+    }
+
+    /// Decode the next batch of frames.
+    ///
+    /// Returns `Ok(None)` when all frames have been read.
+    pub fn next_batch(&mut self) -> Result<Option<Vec<SpectrumArrays>>, TdfError> {
+        let raw_batch = match self.streamer.next_batch()? {
+            Some(batch) => batch,
+            None => return Ok(None),
+        };
+
+        for maldi in raw_batch.iter().filter_map(|frame| frame.maldi_info.as_ref()) {
+            if let (Some(x_um), Some(y_um)) = (maldi.position_x_um, maldi.position_y_um) {
+                self.stage_positions
+                    .push((maldi.pixel_x as i32, maldi.pixel_y as i32, x_um, y_um));
+            }
+        }
+
+        let mut indexed: Vec<IndexedRawFrame> = Vec::with_capacity(raw_batch.len());
+        for frame in raw_batch.into_iter() {
+            indexed.push(IndexedRawFrame {
+                spectrum_id: self.next_spectrum_id,
+                frame,
+            });
+            self.next_spectrum_id += 1;
+        }
+
+        let ctx = &self.ctx;
+
+        // Parallel decode if available
+        #[cfg(feature = "parallel-decode")]
+        let decoded: Vec<IngestSpectrum> = indexed
+            .into_par_iter()
+            .map(|raw| decode_raw_frame(raw, ctx))
+            .collect::<Result<_, _>>()?;
+
+        #[cfg(not(feature = "parallel-decode"))]
+        let decoded: Vec<IngestSpectrum> = indexed
+            .into_iter()
+            .map(|raw| decode_raw_frame(raw, ctx))
+            .collect::<Result<_, _>>()?;
+
+        let mut spectra = Vec::with_capacity(decoded.len());
+        for ingest in decoded {
+            let spectrum = self
+                .ingest_converter
+                .convert(ingest)
+                .map_err(|e| TdfError::PeakConversionError(format!("{e}")))?;
+            if self.collapse {
+                spectra.push(collapse_mobility(spectrum));
+            } else {
+                spectra.push(spectrum);
+            }
+        }
+
+        Ok(Some(spectra))
+    }
+}
+
+impl Iterator for TdfSpectrumBatches {
+    type Item = Result<Vec<SpectrumArrays>, TdfError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_batch().transpose()
+    }
+}
+
 /// Converter from Bruker TDF format to mzpeak SpectrumArrays.
 pub struct TdfConverter {
     config: TdfConversionConfig,
@@ -90,25 +242,25 @@ impl TdfConverter {
     }
 
     /// Convert a Bruker TDF dataset to SpectrumArrays.
+    ///
+    /// The whole run is held in memory. For large datasets use
+    /// [`stream`](Self::stream), [`convert_to_dataset`](Self::convert_to_dataset)
+    /// or [`convert_to_v2_container`](Self::convert_to_v2_container), which
+    /// keep only one frame batch in memory.
     pub fn convert<P: AsRef<Path>>(&self, path: P) -> Result<Vec<SpectrumArrays>, TdfError> {
-        let path = path.as_ref();
-
-        // Validate path
-        if !path.exists() {
-            return Err(TdfError::InvalidPath(format!(
-                "Path does not exist: {}",
-                path.display()
-            )));
+        let mut spectra: Vec<SpectrumArrays> = Vec::new();
+        for batch in self.stream(path)? {
+            spectra.extend(batch?);
         }
+        Ok(spectra)
+    }
 
-        if !path.is_dir() {
-            return Err(TdfError::InvalidPath(format!(
-                "Not a directory: {}",
-                path.display()
-            )));
-        }
+    /// Open a Bruker TDF dataset for batch-wise streaming conversion.
+    pub fn stream<P: AsRef<Path>>(&self, path: P) -> Result<TdfSpectrumBatches, TdfError> {
+        let path = path.as_ref();
+        validate_input_path(path)?;
 
-        let mut streamer = TdfStreamer::new(path, self.config.batch_size)?;
+        let streamer = TdfStreamer::new(path, self.config.batch_size)?;
         let (tof_to_mz, scan_to_im, _rt_conv) = streamer.converters();
 
         // Build precursor lookup (best-effort; absence is tolerated)
@@ -124,45 +276,86 @@ impl TdfConverter {
             precursors_by_frame,
         };
 
-        let mut ingest_converter = IngestSpectrumConverter::new();
-        let mut next_spectrum_id: i64 = 0;
-        let mut spectra: Vec<SpectrumArrays> = Vec::new();
+        Ok(TdfSpectrumBatches {
+            streamer,
+            ctx,
+            ingest_converter: IngestSpectrumConverter::new(),
+            next_spectrum_id: 0,
+            collapse: self.config.mobility_mode == TdfMobilityMode::Collapsed,
+            stage_positions: Vec::new(),
+        })
+    }
 
-        while let Some(raw_batch) = streamer.next_batch()? {
-            let mut indexed: Vec<IndexedRawFrame> = Vec::with_capacity(raw_batch.len());
-            for frame in raw_batch.into_iter() {
-                indexed.push(IndexedRawFrame {
-                    spectrum_id: next_spectrum_id,
-                    frame,
-                });
-                next_spectrum_id += 1;
+    /// Convert a Bruker TDF dataset to a v1 mzPeak dataset with bounded memory.
+    ///
+    /// Like the mzML path, frames are decoded and written one batch at a time
+    /// through [`MzPeakDatasetWriter`], which picks container or directory
+    /// mode from the output path. With [`TdfMobilityMode::Both`] the collapsed
+    /// copy is written to [`collapsed_output_path`]; the returned statistics
+    /// describe the primary output.
+    pub fn convert_to_dataset<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        input_path: P,
+        output_path: Q,
+        writer_config: WriterConfig,
+    ) -> Result<TdfConversionStats, TdfError> {
+        let input_path = input_path.as_ref();
+        let output_path = output_path.as_ref();
+        let mut batches = self.stream(input_path)?;
+
+        let metadata = build_metadata(input_path);
+        let create_writer = |path: &Path| {
+            MzPeakDatasetWriter::new(path, &metadata, writer_config.clone())
+                .map_err(|e| TdfError::ReadError(format!("Failed to create writer: {e}")))
+        };
+        let mut writer = create_writer(output_path)?;
+        let mut collapsed_writer = match self.config.mobility_mode {
+            TdfMobilityMode::Both => Some(create_writer(&collapsed_output_path(output_path))?),
+            _ => None,
+        };
+
+        let mut stats = TdfConversionStats::default();
+        while let Some(mut batch) = batches.next_batch()? {
+            for spectrum in &batch {
+                stats.record(
+                    spectrum.ms_level,
+                    spectrum.peak_count(),
+                    spectrum.pixel_x.is_some() && spectrum.pixel_y.is_some(),
+                );
             }
+            if let Some(collapsed_writer) = collapsed_writer.as_mut() {
+                let mut collapsed: Vec<SpectrumArrays> =
+                    batch.iter().cloned().map(collapse_mobility).collect();
+                collapsed_writer
+                    .write_spectra_drain(&mut collapsed)
+                    .map_err(|e| TdfError::ReadError(format!("Failed to write spectra: {e}")))?;
+            }
+            // Draining keeps the batch capacity for the next round
+            writer
+                .write_spectra_drain(&mut batch)
+                .map_err(|e| TdfError::ReadError(format!("Failed to write spectra: {e}")))?;
+        }
 
-            // Parallel decode if available
-            #[cfg(feature = "parallel-decode")]
-            let decoded: Vec<IngestSpectrum> = indexed
-                .into_par_iter()
-                .map(|raw| decode_raw_frame(raw, &ctx))
-                .collect::<Result<_, _>>()?;
-
-            #[cfg(not(feature = "parallel-decode"))]
-            let decoded: Vec<IngestSpectrum> = indexed
-                .into_iter()
-                .map(|raw| decode_raw_frame(raw, &ctx))
-                .collect::<Result<_, _>>()?;
-
-            for ingest in decoded {
-                let spec = ingest_converter
-                    .convert(ingest)
-                    .map_err(|e| TdfError::PeakConversionError(format!("{e}")))?;
-                spectra.push(spec);
+        let imaging = batches.imaging_metadata();
+        for mut writer in std::iter::once(writer).chain(collapsed_writer) {
+            if let Some(imaging) = &imaging {
+                writer.set_imaging_metadata(imaging.clone());
             }
+            writer
+                .close()
+                .map_err(|e| TdfError::ReadError(format!("Failed to finalize dataset: {e}")))?;
         }
 
-        Ok(spectra)
+        Ok(stats)
     }
 
     /// Convert a Bruker TDF dataset directly to an mzPeak v2.0 container.
+    ///
+    /// Frames are decoded and written one batch at a time. Collapsed output
+    /// is declared without ion mobility in the manifest. With
+    /// [`TdfMobilityMode::Both`] the collapsed copy is written to
+    /// [`collapsed_output_path`]; the returned statistics describe the
+    /// primary output.
     pub fn convert_to_v2_container<P: AsRef<Path>, Q: AsRef<Path>>(
         &self,
         input_path: P,
@@ -172,135 +365,62 @@ impl TdfConverter {
         let input_path = input_path.as_ref();
         let output_path = output_path.as_ref();
 
-        // Validate path
-        if !input_path.exists() {
-            return Err(TdfError::InvalidPath(format!(
-                "Path does not exist: {}",
-                input_path.display()
-            )));
-        }
-
-        if !input_path.is_dir() {
-            return Err(TdfError::InvalidPath(format!(
-                "Not a directory: {}",
-                input_path.display()
-            )));
-        }
-
-        let mut streamer = TdfStreamer::new(input_path, self.config.batch_size)?;
-        let (tof_to_mz, scan_to_im, _rt_conv) = streamer.converters();
-
-        let precursors_by_frame = PrecursorReader::new(input_path)
-            .ok()
-            .map(|reader| build_precursor_map(&reader))
-            .unwrap_or_default();
-
-        let ctx = DecoderContext {
-            tof_to_mz: *tof_to_mz,
-            scan_to_im: *scan_to_im,
-            include_extended_metadata: self.config.include_extended_metadata,
-            precursors_by_frame,
-        };
+        let mut batches = self.stream(input_path)?;
 
-        let modality = Modality::from_flags(true, streamer.is_maldi());
+        let is_maldi = batches.is_maldi();
+        let full_modality = Modality::from_flags(true, is_maldi);
+        let collapsed_modality = Modality::from_flags(false, is_maldi);
         let metadata = build_metadata(input_path);
-        let vendor_hints = metadata.vendor_hints.clone();
 
-        let dataset_config = DatasetWriterV2Config {
-            spectra_config: SpectraWriterConfig {
-                compression: writer_config.compression,
-                ..Default::default()
-            },
-            peaks_config: PeaksWriterV2Config {
-                compression: writer_config.compression,
-                row_group_size: writer_config.row_group_size,
-                ..Default::default()
-            },
+        let (mut writer, modality) = match self.config.mobility_mode {
+            TdfMobilityMode::Collapsed => (
+                create_v2_writer(output_path, collapsed_modality, &metadata, &writer_config)?,
+                collapsed_modality,
+            ),
+            _ => (
+                create_v2_writer(output_path, full_modality, &metadata, &writer_config)?,
+                full_modality,
+            ),
+        };
+        let mut collapsed_writer = match self.config.mobility_mode {
+            TdfMobilityMode::Both => Some(create_v2_writer(
+                &collapsed_output_path(output_path),
+                collapsed_modality,
+                &metadata,
+                &writer_config,
+            )?),
+            _ => None,
         };
-
-        let mut writer =
-            MzPeakDatasetWriterV2::with_config(output_path, modality, vendor_hints, dataset_config)
-                .map_err(|e| TdfError::ReadError(format!("Failed to create writer: {e}")))?;
-        writer.set_metadata(metadata);
 
         let mut stats = TdfConversionStats::default();
-        let mut ingest_converter = IngestSpectrumConverter::new();
-        let mut next_spectrum_id: i64 = 0;
-
-        while let Some(raw_batch) = streamer.next_batch()? {
-            let mut indexed: Vec<IndexedRawFrame> = Vec::with_capacity(raw_batch.len());
-            for frame in raw_batch.into_iter() {
-                indexed.push(IndexedRawFrame {
-                    spectrum_id: next_spectrum_id,
-                    frame,
-                });
-                next_spectrum_id += 1;
-            }
-
-            #[cfg(feature = "parallel-decode")]
-            let decoded: Vec<IngestSpectrum> = indexed
-                .into_par_iter()
-                .map(|raw| decode_raw_frame(raw, &ctx))
-                .collect::<Result<_, _>>()?;
-
-            #[cfg(not(feature = "parallel-decode"))]
-            let decoded: Vec<IngestSpectrum> = indexed
-                .into_iter()
-                .map(|raw| decode_raw_frame(raw, &ctx))
-                .collect::<Result<_, _>>()?;
-
-            for ingest in decoded {
-                let spectrum = ingest_converter
-                    .convert(ingest)
-                    .map_err(|e| TdfError::PeakConversionError(format!("{e}")))?;
-                let spectrum_v2 = SpectrumV2::try_from_spectrum_arrays(spectrum)
-                    .map_err(|e| TdfError::PeakConversionError(format!("{e}")))?;
-
-                if spectrum_v2.peaks.ion_mobility.is_none() {
-                    return Err(TdfError::PeakConversionError(
-                        "ion mobility missing for TDF spectrum".to_string(),
-                    ));
-                }
-
-                if modality.has_imaging() {
-                    if spectrum_v2.metadata.pixel_x.is_none()
-                        || spectrum_v2.metadata.pixel_y.is_none()
-                    {
-                        return Err(TdfError::PeakConversionError(
-                            "pixel coordinates missing for MALDI imaging dataset".to_string(),
-                        ));
-                    }
-                } else if spectrum_v2.metadata.pixel_x.is_some()
-                    || spectrum_v2.metadata.pixel_y.is_some()
-                    || spectrum_v2.metadata.pixel_z.is_some()
-                {
-                    return Err(TdfError::PeakConversionError(
-                        "imaging coordinates present for non-imaging dataset".to_string(),
-                    ));
-                }
 
-                writer
-                    .write_spectrum(&spectrum_v2)
-                    .map_err(|e| TdfError::ReadError(format!("Failed to write spectrum: {e}")))?;
-
-                stats.spectra_read += 1;
-                stats.peaks_total += spectrum_v2.peaks.len();
-                match spectrum_v2.metadata.ms_level {
-                    1 => stats.ms1_count += 1,
-                    2 => stats.ms2_count += 1,
-                    _ => {}
-                }
-                if spectrum_v2.metadata.pixel_x.is_some()
-                    && spectrum_v2.metadata.pixel_y.is_some()
-                {
-                    stats.imaging_frames += 1;
+        while let Some(batch) = batches.next_batch()? {
+            for spectrum in batch {
+                stats.record(
+                    spectrum.ms_level,
+                    spectrum.peak_count(),
+                    spectrum.pixel_x.is_some() && spectrum.pixel_y.is_some(),
+                );
+                if let Some(collapsed_writer) = collapsed_writer.as_mut() {
+                    let collapsed = collapse_mobility(spectrum.clone());
+                    write_v2_spectrum(collapsed_writer, collapsed, collapsed_modality)?;
                 }
+                write_v2_spectrum(&mut writer, spectrum, modality)?;
             }
         }
 
-        writer
-            .close()
-            .map_err(|e| TdfError::ReadError(format!("Failed to finalize dataset: {e}")))?;
+        let imaging = batches.imaging_metadata();
+        for mut writer in std::iter::once(writer).chain(collapsed_writer) {
+            if let Some(imaging) = &imaging {
+                writer.set_metadata(MzPeakMetadata {
+                    imaging: Some(imaging.clone()),
+                    ..metadata.clone()
+                });
+            }
+            writer
+                .close()
+                .map_err(|e| TdfError::ReadError(format!("Failed to finalize dataset: {e}")))?;
+        }
 
         Ok(stats)
     }
@@ -312,6 +432,140 @@ impl Default for TdfConverter {
     }
 }
 
+/// Sibling path for the collapsed copy written by [`TdfMobilityMode::Both`]:
+/// `run.mzpeak` becomes `run.collapsed.mzpeak`, a directory `run` becomes
+/// `run.collapsed`.
+pub fn collapsed_output_path(output: &Path) -> PathBuf {
+    let name = output.file_name().unwrap_or_default().to_string_lossy();
+    let collapsed_name = [".mzpeak.parquet", ".mzpeak"]
+        .iter()
+        .find_map(|ext| {
+            name.strip_suffix(ext)
+                .map(|stem| format!("{stem}.collapsed{ext}"))
+        })
+        .unwrap_or_else(|| format!("{name}.collapsed"));
+    output.with_file_name(collapsed_name)
+}
+
+/// Collapse the ion mobility axis of a spectrum.
+///
+/// Peaks with identical m/z (the same TOF index in different mobility
+/// scans) are summed; the result is sorted by m/z and carries no ion
+/// mobility. Precomputed base peak statistics are recomputed.
+pub fn collapse_mobility(mut spectrum: SpectrumArrays) -> SpectrumArrays {
+    let peaks = &spectrum.peaks;
+    let mut order: Vec<usize> = (0..peaks.mz.len()).collect();
+    order.sort_unstable_by(|&a, &b| peaks.mz[a].total_cmp(&peaks.mz[b]));
+
+    let mut mz: Vec<f64> = Vec::with_capacity(order.len());
+    let mut intensity: Vec<f32> = Vec::with_capacity(order.len());
+    for idx in order {
+        let (peak_mz, peak_intensity) = (peaks.mz[idx], peaks.intensity[idx]);
+        match (mz.last(), intensity.last_mut()) {
+            (Some(&last_mz), Some(last_intensity)) if last_mz == peak_mz => {
+                *last_intensity += peak_intensity;
+            }
+            _ => {
+                mz.push(peak_mz);
+                intensity.push(peak_intensity);
+            }
+        }
+    }
+
+    spectrum.peaks = PeakArrays::new(mz, intensity);
+    if spectrum.base_peak_intensity.is_some() {
+        spectrum.compute_statistics();
+    }
+    spectrum
+}
+
+fn create_v2_writer(
+    output_path: &Path,
+    modality: Modality,
+    metadata: &MzPeakMetadata,
+    writer_config: &WriterConfig,
+) -> Result<MzPeakDatasetWriterV2, TdfError> {
+    let dataset_config = DatasetWriterV2Config {
+        spectra_config: SpectraWriterConfig {
+            compression: writer_config.compression,
+            spectrum_checksums: writer_config.spectrum_checksums,
+            ..Default::default()
+        },
+        peaks_config: PeaksWriterV2Config {
+            compression: writer_config.compression,
+            row_group_size: writer_config.row_group_size,
+            progress: writer_config.progress.clone(),
+            ..Default::default()
+        },
+        temp_dir: writer_config.temp_dir.clone(),
+        stage_locally: writer_config.stage_locally,
+        ..Default::default()
+    };
+
+    let mut writer = MzPeakDatasetWriterV2::with_config(
+        output_path,
+        modality,
+        metadata.vendor_hints.clone(),
+        dataset_config,
+    )
+    .map_err(|e| TdfError::ReadError(format!("Failed to create writer: {e}")))?;
+    writer.set_metadata(metadata.clone());
+    Ok(writer)
+}
+
+/// Check a spectrum against the container modality and write it.
+fn write_v2_spectrum(
+    writer: &mut MzPeakDatasetWriterV2,
+    spectrum: SpectrumArrays,
+    modality: Modality,
+) -> Result<(), TdfError> {
+    let spectrum_v2 = SpectrumV2::try_from_spectrum_arrays(spectrum)
+        .map_err(|e| TdfError::PeakConversionError(format!("{e}")))?;
+
+    if modality.has_ion_mobility() && spectrum_v2.peaks.ion_mobility.is_none() {
+        return Err(TdfError::PeakConversionError(
+            "ion mobility missing for TDF spectrum".to_string(),
+        ));
+    }
+
+    if modality.has_imaging() {
+        if spectrum_v2.metadata.pixel_x.is_none() || spectrum_v2.metadata.pixel_y.is_none() {
+            return Err(TdfError::PeakConversionError(
+                "pixel coordinates missing for MALDI imaging dataset".to_string(),
+            ));
+        }
+    } else if spectrum_v2.metadata.pixel_x.is_some()
+        || spectrum_v2.metadata.pixel_y.is_some()
+        || spectrum_v2.metadata.pixel_z.is_some()
+    {
+        return Err(TdfError::PeakConversionError(
+            "imaging coordinates present for non-imaging dataset".to_string(),
+        ));
+    }
+
+    writer
+        .write_spectrum(&spectrum_v2)
+        .map_err(|e| TdfError::ReadError(format!("Failed to write spectrum: {e}")))
+}
+
+fn validate_input_path(path: &Path) -> Result<(), TdfError> {
+    if !path.exists() {
+        return Err(TdfError::InvalidPath(format!(
+            "Path does not exist: {}",
+            path.display()
+        )));
+    }
+
+    if !path.is_dir() {
+        return Err(TdfError::InvalidPath(format!(
+            "Not a directory: {}",
+            path.display()
+        )));
+    }
+
+    Ok(())
+}
+
 fn decode_raw_frame(raw: IndexedRawFrame, ctx: &DecoderContext) -> Result<IngestSpectrum, TdfError> {
     let IndexedRawFrame { spectrum_id, frame } = raw;
 
@@ -589,6 +843,19 @@ mod tests {
         assert_eq!(ingest.collision_energy, Some(27.5_f32));
     }
 
+    #[test]
+    fn conversion_stats_record() {
+        let mut stats = TdfConversionStats::default();
+        stats.record(1, 10, false);
+        stats.record(2, 5, false);
+        stats.record(1, 7, true);
+        assert_eq!(stats.spectra_read, 3);
+        assert_eq!(stats.peaks_total, 22);
+        assert_eq!(stats.ms1_count, 2);
+        assert_eq!(stats.ms2_count, 1);
+        assert_eq!(stats.imaging_frames, 1);
+    }
+
     #[test]
     fn decode_maldi_pixels_mapped() {
         let ctx = dummy_ctx(false);
@@ -622,4 +889,39 @@ mod tests {
         assert_eq!(ingest.pixel_x, Some(5));
         assert_eq!(ingest.pixel_y, Some(7));
     }
+
+    #[test]
+    fn collapse_mobility_sums_matching_mz() {
+        let peaks = PeakArrays {
+            mz: vec![200.0, 100.0, 200.0, 150.0, 100.0],
+            intensity: vec![1.0, 2.0, 3.0, 4.0, 5.0],
+            ion_mobility: OptionalColumnBuf::AllPresent(vec![0.8, 0.8, 0.9, 0.9, 1.0]),
+        };
+        let mut spectrum = SpectrumArrays::new_ms1(0, 1, 10.0, 1, peaks);
+        spectrum.compute_statistics();
+
+        let collapsed = collapse_mobility(spectrum);
+        assert_eq!(collapsed.peaks.mz, vec![100.0, 150.0, 200.0]);
+        assert_eq!(collapsed.peaks.intensity, vec![7.0, 4.0, 4.0]);
+        assert!(collapsed.peaks.ion_mobility.is_all_null());
+        assert_eq!(collapsed.base_peak_mz, Some(100.0));
+        assert_eq!(collapsed.base_peak_intensity, Some(7.0));
+        assert_eq!(collapsed.total_ion_current, Some(15.0));
+    }
+
+    #[test]
+    fn collapsed_output_path_inserts_suffix() {
+        assert_eq!(
+            collapsed_output_path(Path::new("out/run.mzpeak")),
+            PathBuf::from("out/run.collapsed.mzpeak")
+        );
+        assert_eq!(
+            collapsed_output_path(Path::new("run.mzpeak.parquet")),
+            PathBuf::from("run.collapsed.mzpeak.parquet")
+        );
+        assert_eq!(
+            collapsed_output_path(Path::new("out/run")),
+            PathBuf::from("out/run.collapsed")
+        );
+    }
 }
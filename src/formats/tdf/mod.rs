@@ -47,6 +47,22 @@
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 //!
+//! # Large Datasets
+//!
+//! [`TdfConverter::convert`] returns the whole run at once. For runs of
+//! hundreds of gigabytes, use [`TdfConverter::stream`] to iterate over decoded
+//! frame batches, or [`TdfConverter::convert_to_dataset`] /
+//! [`TdfConverter::convert_to_v2_container`] to write them incrementally;
+//! these hold at most `batch_size` frames in memory.
+//!
+//! # Ion Mobility
+//!
+//! By default every peak keeps its 1/K₀ value. Set
+//! [`TdfConversionConfig::mobility_mode`] to [`TdfMobilityMode::Collapsed`] to
+//! sum each frame over the mobility axis into a conventional 3D spectrum, or to
+//! [`TdfMobilityMode::Both`] to write the full output plus a collapsed copy
+//! at [`collapsed_output_path`].
+//!
 //! # Thin-Waist Mapping
 //!
 //! TDF fields are mapped to the thin-waist contract as follows:
@@ -78,6 +94,9 @@ pub mod converter;
 pub mod error;
 pub mod parallel_converter;
 
-pub use converter::TdfConverter;
+pub use converter::{
+    collapse_mobility, collapsed_output_path, TdfConversionConfig, TdfConversionStats,
+    TdfConverter, TdfMobilityMode, TdfSpectrumBatches,
+};
 pub use error::TdfError;
 pub use parallel_converter::{ParallelConversionConfig, ParallelConversionStats, ParallelTdfConverter};
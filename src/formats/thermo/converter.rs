@@ -1,28 +1,70 @@
 //! Converter from Thermo RAW spectra to thin-waist IngestSpectrum.
 
 use crate::ingest::IngestSpectrum;
+use crate::scan_diagnostics_writer::ScanDiagnostics;
 use crate::thermo::ThermoError;
 use crate::writer::{OptionalColumnBuf, PeakArrays};
 
 use thermorawfilereader::schema::Polarity;
 use thermorawfilereader::RawSpectrum;
 
-/// Configuration for Thermo RAW spectrum conversion.
-#[derive(Debug, Clone)]
-pub struct ThermoConversionConfig {
-    /// Whether to centroid profile spectra during conversion.
-    /// If true, the thermorawfilereader will centroid profile data.
-    pub centroid_spectra: bool,
+/// Which peak representation to take from a Thermo RAW file.
+///
+/// Thermo instruments record some scan types (typically Orbitrap MS1) in
+/// profile mode and others (often ion trap MSn) already centroided.
+/// RawFileReader centroids profile scans on request and passes centroid scans
+/// through unchanged, so:
+///
+/// - `Centroid` yields peak lists for every scan.
+/// - `Profile` yields the acquired profile data, and the acquired peak list for
+///   scans that have no profile signal.
+/// - `Both` yields each scan twice, once per representation, so that both can
+///   be stored side by side with matching spectrum IDs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ThermoSpectrumMode {
+    /// Centroid profile scans with RawFileReader (default)
+    #[default]
+    Centroid,
+    /// Keep spectra as acquired
+    Profile,
+    /// Produce both a centroided and an as-acquired stream
+    Both,
 }
 
-impl Default for ThermoConversionConfig {
-    fn default() -> Self {
-        Self {
-            centroid_spectra: true,
+impl ThermoSpectrumMode {
+    /// Whether the centroided stream is produced.
+    pub fn includes_centroid(&self) -> bool {
+        matches!(self, Self::Centroid | Self::Both)
+    }
+
+    /// Whether the as-acquired (profile) stream is produced.
+    pub fn includes_profile(&self) -> bool {
+        matches!(self, Self::Profile | Self::Both)
+    }
+
+    /// Lowercase name, as used on the command line and in processing history.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Centroid => "centroid",
+            Self::Profile => "profile",
+            Self::Both => "both",
         }
     }
 }
 
+impl std::fmt::Display for ThermoSpectrumMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Configuration for Thermo RAW spectrum conversion.
+#[derive(Debug, Clone, Default)]
+pub struct ThermoConversionConfig {
+    /// Which peak representation(s) to convert.
+    pub spectrum_mode: ThermoSpectrumMode,
+}
+
 /// Converter from Thermo RAW spectra to thin-waist `IngestSpectrum`.
 #[derive(Debug, Clone, Default)]
 pub struct ThermoConverter {
@@ -40,9 +82,14 @@ impl ThermoConverter {
         Self { config }
     }
 
-    /// Whether centroiding is enabled.
+    /// Whether centroided spectra are produced.
     pub fn centroid_spectra(&self) -> bool {
-        self.config.centroid_spectra
+        self.config.spectrum_mode.includes_centroid()
+    }
+
+    /// Selected peak representation(s).
+    pub fn spectrum_mode(&self) -> ThermoSpectrumMode {
+        self.config.spectrum_mode
     }
 
     /// Convert a Thermo RawSpectrum to IngestSpectrum.
@@ -166,6 +213,25 @@ impl ThermoConverter {
             peaks,
         })
     }
+
+    /// Convert a scan's trailer extras to generic key-value diagnostics.
+    ///
+    /// Thermo labels end with a colon ("FT Resolution:"); it is dropped along
+    /// with surrounding whitespace, and entries with an empty label (section
+    /// separators in the trailer) are skipped. Values are only trimmed.
+    pub fn convert_trailer<I>(&self, trailer: I, spectrum_id: i64) -> ScanDiagnostics
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let mut diagnostics = ScanDiagnostics::new(spectrum_id);
+        for (label, value) in trailer {
+            let key = label.trim().trim_end_matches(':').trim_end();
+            if !key.is_empty() {
+                diagnostics.push(key, value.trim());
+            }
+        }
+        diagnostics
+    }
 }
 
 #[cfg(test)]
@@ -181,9 +247,38 @@ mod tests {
     #[test]
     fn test_custom_config() {
         let config = ThermoConversionConfig {
-            centroid_spectra: false,
+            spectrum_mode: ThermoSpectrumMode::Profile,
         };
         let converter = ThermoConverter::with_config(config);
         assert!(!converter.centroid_spectra());
+        assert_eq!(converter.spectrum_mode(), ThermoSpectrumMode::Profile);
+    }
+
+    #[test]
+    fn test_convert_trailer() {
+        let trailer = vec![
+            ("Ion Injection Time (ms):".to_string(), "22.500".to_string()),
+            ("FT Resolution:".to_string(), "120000".to_string()),
+            ("=== Mass Calibration: ===".to_string(), String::new()),
+            (":".to_string(), String::new()),
+            ("Elapsed Scan Time (sec): ".to_string(), " 0.26 ".to_string()),
+        ];
+        let diagnostics = ThermoConverter::new().convert_trailer(trailer, 7);
+        assert_eq!(diagnostics.spectrum_id, 7);
+        assert_eq!(diagnostics.len(), 4);
+        assert_eq!(diagnostics.get("Ion Injection Time (ms)"), Some("22.500"));
+        assert_eq!(diagnostics.get_numeric("FT Resolution"), Some(120000.0));
+        assert_eq!(diagnostics.get("Elapsed Scan Time (sec)"), Some("0.26"));
+    }
+
+    #[test]
+    fn test_spectrum_mode_streams() {
+        assert!(ThermoSpectrumMode::Centroid.includes_centroid());
+        assert!(!ThermoSpectrumMode::Centroid.includes_profile());
+        assert!(!ThermoSpectrumMode::Profile.includes_centroid());
+        assert!(ThermoSpectrumMode::Profile.includes_profile());
+        assert!(ThermoSpectrumMode::Both.includes_centroid());
+        assert!(ThermoSpectrumMode::Both.includes_profile());
+        assert_eq!(ThermoSpectrumMode::Both.to_string(), "both");
     }
 }
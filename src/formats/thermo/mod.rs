@@ -39,11 +39,29 @@
 //! }
 //! # Ok::<(), mzpeak::thermo::ThermoError>(())
 //! ```
+//!
+//! # Centroid and Profile Data
+//!
+//! By default profile scans are centroided by RawFileReader. Select
+//! [`ThermoSpectrumMode::Profile`] to keep data as acquired, or
+//! [`ThermoSpectrumMode::Both`] to convert both representations; the CLI then
+//! writes the profile stream to a sibling `<name>.profile.mzpeak` container
+//! with the same spectrum IDs, and [`ThermoStreamer::next_dual_batch`] yields
+//! the matching pairs.
+//!
+//! # Instrument Diagnostics
+//!
+//! Per-scan trailer extras ("Ion Injection Time (ms)", "FT Resolution",
+//! "Elapsed Scan Time (sec)", ...) are read with
+//! [`ThermoStreamer::trailer_extras`] and converted by
+//! [`ThermoConverter::convert_trailer`] into generic key-value
+//! [`ScanDiagnostics`](crate::scan_diagnostics_writer::ScanDiagnostics), which
+//! v2 containers store in `diagnostics/scan_diagnostics.parquet`.
 
 pub mod error;
 pub mod converter;
 pub mod streamer;
 
 pub use error::ThermoError;
-pub use converter::ThermoConverter;
+pub use converter::{ThermoConversionConfig, ThermoConverter, ThermoSpectrumMode};
 pub use streamer::ThermoStreamer;
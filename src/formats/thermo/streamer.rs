@@ -49,6 +49,7 @@ fn check_platform_support() -> Result<(), ThermoError> {
 /// ```
 pub struct ThermoStreamer {
     reader: RawFileReader,
+    centroid_spectra: bool,
     next_index: usize,
     batch_size: usize,
     total_spectra: usize,
@@ -87,12 +88,16 @@ impl ThermoStreamer {
         }
 
         let mut reader = RawFileReader::open(path).map_err(|e| {
-            ThermoError::OpenError(format!("{}: {}", path.display(), e))
+            // A missing runtime surfaces as an opaque loader error
+            match crate::environment::missing_dotnet_message() {
+                Some(message) => ThermoError::RuntimeError(format!("{}: {}", message, e)),
+                None => ThermoError::OpenError(format!("{}: {}", path.display(), e)),
+            }
         })?;
 
         // Enable signal loading (peak data)
         reader.set_signal_loading(true);
-        // Enable centroiding for profile spectra
+        // Enable centroiding for profile spectra (see `set_centroid_spectra`)
         reader.set_centroid_spectra(true);
 
         let total_spectra = reader.len();
@@ -100,6 +105,7 @@ impl ThermoStreamer {
 
         Ok(Self {
             reader,
+            centroid_spectra: true,
             next_index: 0,
             batch_size,
             total_spectra,
@@ -126,6 +132,19 @@ impl ThermoStreamer {
         self.next_index = 0;
     }
 
+    /// Whether profile spectra are centroided by RawFileReader (default: true).
+    pub fn centroid_spectra(&self) -> bool {
+        self.centroid_spectra
+    }
+
+    /// Choose between centroided and as-acquired peak data for `next_batch`.
+    ///
+    /// Scans acquired in centroid mode are returned unchanged either way.
+    pub fn set_centroid_spectra(&mut self, centroid: bool) {
+        self.reader.set_centroid_spectra(centroid);
+        self.centroid_spectra = centroid;
+    }
+
     /// Get a reference to the underlying RawFileReader for metadata access.
     pub fn reader(&self) -> &RawFileReader {
         &self.reader
@@ -163,6 +182,62 @@ impl ThermoStreamer {
         Ok(Some(batch))
     }
 
+    /// Fetch the next batch as `(centroided, as_acquired)` pairs.
+    ///
+    /// Each scan is read twice, toggling RawFileReader centroiding in between,
+    /// so both representations share the same scan. Scans for which either
+    /// read fails are skipped. The `set_centroid_spectra` setting is restored
+    /// afterwards.
+    ///
+    /// # Errors
+    /// Returns `ThermoError::ReadError` if spectrum reading fails.
+    pub fn next_dual_batch(
+        &mut self,
+    ) -> Result<Option<Vec<(RawSpectrum, RawSpectrum)>>, ThermoError> {
+        if self.next_index >= self.total_spectra {
+            return Ok(None);
+        }
+
+        let end = (self.next_index + self.batch_size).min(self.total_spectra);
+        let mut batch = Vec::with_capacity(end - self.next_index);
+
+        for idx in self.next_index..end {
+            self.reader.set_centroid_spectra(true);
+            let centroid = self.reader.get(idx);
+            self.reader.set_centroid_spectra(false);
+            let profile = self.reader.get(idx);
+            match (centroid, profile) {
+                (Some(centroid), Some(profile)) => batch.push((centroid, profile)),
+                _ => {
+                    eprintln!(
+                        "⚠️  Skipping spectrum {} (read returned None)",
+                        idx + 1
+                    );
+                }
+            }
+        }
+        self.reader.set_centroid_spectra(self.centroid_spectra);
+
+        self.next_index = end;
+        Ok(Some(batch))
+    }
+
+    /// Trailer extra values ("Ion Injection Time (ms):", "FT Resolution:", ...)
+    /// reported for the scan at `index`, as `(label, value)` text pairs.
+    ///
+    /// Returns an empty vector if the scan has no trailer.
+    pub fn trailer_extras(&self, index: usize) -> Vec<(String, String)> {
+        self.reader
+            .get_raw_trailers_for(index)
+            .map(|trailers| {
+                trailers
+                    .iter()
+                    .map(|entry| (entry.label.to_string(), entry.value.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Get instrument model information.
     pub fn instrument_model(&self) -> String {
         let model = self.reader.instrument_model();
@@ -179,6 +254,7 @@ impl std::fmt::Debug for ThermoStreamer {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ThermoStreamer")
             .field("total_spectra", &self.total_spectra)
+            .field("centroid_spectra", &self.centroid_spectra)
             .field("next_index", &self.next_index)
             .field("batch_size", &self.batch_size)
             .finish()
@@ -161,6 +161,8 @@
 //! - [`metadata`]: SDRF parsing and technical metadata structures
 //! - [`writer`]: Streaming Parquet writer with RLE optimization
 //! - [`controlled_vocabulary`]: HUPO-PSI MS controlled vocabulary terms
+//! - [`study`]: Study-level QC aggregation and drift monitoring
+//! - [`processing`]: Reader-driven analyses (DIA scheme detection, ...)
 //!
 //! ## Format Specification
 //!
@@ -216,16 +218,30 @@
 // Allow some patterns common in scientific code
 #![allow(clippy::too_many_arguments)]
 
+pub mod capabilities;
 pub mod controlled_vocabulary;
 pub mod chromatogram_writer;
+#[cfg(feature = "writer")]
 pub mod dataset;
+pub mod environment;
 pub mod metadata;
 pub mod mobilogram_writer;
+#[cfg(feature = "plugins")]
+pub mod plugin;
+pub mod processing;
 pub mod reader;
+pub mod scan_diagnostics_writer;
 pub mod schema;
+#[cfg(feature = "writer")]
+pub mod study;
 pub mod validator;
 pub mod writer;
 
+// Development support: public benchmark datasets for the criterion suite
+#[cfg(feature = "cli")]
+#[doc(hidden)]
+pub mod bench_data;
+
 // Format-specific modules
 mod formats;
 
@@ -245,6 +261,8 @@ pub use formats::thermo;
 /// Common ingestion interface for format converters.
 pub use formats::ingest;
 
+pub use capabilities::{capabilities, Capabilities};
+
 // Python bindings module (only compiled with the "python" feature)
 #[cfg(feature = "python")]
 mod python;
@@ -258,6 +276,7 @@ pub mod prelude {
         Mobilogram, MobilogramWriter, MobilogramWriterConfig, MobilogramWriterStats,
     };
     pub use crate::controlled_vocabulary::{ms_terms, unit_terms, CvParamList, CvTerm};
+    #[cfg(feature = "writer")]
     pub use crate::dataset::{DatasetError, DatasetStats, MzPeakDatasetWriter, OutputMode};
     pub use crate::metadata::{
         InstrumentConfig, LcConfig, MzPeakMetadata, RunParameters, SdrfMetadata, SourceFileInfo,
@@ -267,10 +286,11 @@ pub mod prelude {
         MZPEAK_FORMAT_VERSION, MZPEAK_MIMETYPE,
     };
     pub use crate::validator::{validate_mzpeak_file, ValidationReport};
-    pub use crate::writer::{
-        CompressionType, MzPeakWriter, OptionalColumnBuf, PeakArrays, SpectrumArrays, WriterConfig, WriterStats,
-    };
+    pub use crate::writer::{OptionalColumnBuf, PeakArrays, SpectrumArrays};
+    #[cfg(feature = "writer")]
+    pub use crate::writer::{CompressionType, MzPeakWriter, WriterConfig, WriterStats};
     pub use crate::reader::{
-        FileSummary, FileMetadata, MzPeakReader, ReaderConfig, ReaderError,
+        FileSummary, FileMetadata, InMemorySpectrumStore, MzPeakReader, ReaderConfig, ReaderError,
+        SpectrumStore,
     };
 }
@@ -2,10 +2,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::controlled_vocabulary::{CvParamList, CvTerm};
 
-use super::MetadataError;
+use super::{MetadataError, UnknownFields};
 
 /// Instrument configuration metadata
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct InstrumentConfig {
     /// Instrument model name (CV: MS:1000031)
     pub model: Option<String>,
@@ -30,10 +31,15 @@ pub struct InstrumentConfig {
 
     /// Additional CV parameters
     pub cv_params: CvParamList,
+
+    /// Fields not known to this version, preserved verbatim
+    #[serde(flatten)]
+    pub unknown_fields: UnknownFields,
 }
 
 /// Mass analyzer configuration
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MassAnalyzerConfig {
     /// Analyzer type (e.g., "orbitrap", "quadrupole", "ion trap")
     pub analyzer_type: String,
@@ -49,6 +55,10 @@ pub struct MassAnalyzerConfig {
 
     /// CV parameters specific to this analyzer
     pub cv_params: CvParamList,
+
+    /// Fields not known to this version, preserved verbatim
+    #[serde(flatten)]
+    pub unknown_fields: UnknownFields,
 }
 
 impl InstrumentConfig {
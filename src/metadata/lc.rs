@@ -2,10 +2,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::controlled_vocabulary::CvParamList;
 
-use super::MetadataError;
+use super::{MetadataError, UnknownFields};
 
 /// Liquid Chromatography configuration
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct LcConfig {
     /// LC system model
     pub system_model: Option<String>,
@@ -30,10 +31,15 @@ pub struct LcConfig {
 
     /// Additional CV parameters
     pub cv_params: CvParamList,
+
+    /// Fields not known to this version, preserved verbatim
+    #[serde(flatten)]
+    pub unknown_fields: UnknownFields,
 }
 
 /// Information about an LC column
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ColumnInfo {
     /// Column name/model
     pub name: Option<String>,
@@ -55,10 +61,15 @@ pub struct ColumnInfo {
 
     /// Stationary phase type
     pub stationary_phase: Option<String>,
+
+    /// Fields not known to this version, preserved verbatim
+    #[serde(flatten)]
+    pub unknown_fields: UnknownFields,
 }
 
 /// Mobile phase solvent configuration
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MobilePhase {
     /// Channel identifier (A, B, C, D)
     pub channel: String,
@@ -68,13 +79,22 @@ pub struct MobilePhase {
 
     /// pH (if applicable)
     pub ph: Option<f64>,
+
+    /// Fields not known to this version, preserved verbatim
+    #[serde(flatten)]
+    pub unknown_fields: UnknownFields,
 }
 
 /// LC gradient program definition
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GradientProgram {
     /// Gradient steps as (time_min, %B)
     pub steps: Vec<GradientStep>,
+
+    /// Fields not known to this version, preserved verbatim
+    #[serde(flatten)]
+    pub unknown_fields: UnknownFields,
 }
 
 /// A single step in an LC gradient program
@@ -88,6 +108,10 @@ pub struct GradientStep {
 
     /// Flow rate at this step (if variable)
     pub flow_rate_ul_min: Option<f64>,
+
+    /// Fields not known to this version, preserved verbatim
+    #[serde(flatten)]
+    pub unknown_fields: UnknownFields,
 }
 
 impl LcConfig {
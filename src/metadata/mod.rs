@@ -21,6 +21,14 @@
 //!
 //! 3. **Run Parameters**: Technical details like pump pressures, temperatures,
 //!    and other diagnostic data that vendors typically store but converters lose
+//!
+//! ## Forward Compatibility
+//!
+//! Every metadata struct carries an `unknown_fields` map. Fields written by a
+//! newer version of mzPeak that this version does not know about are captured
+//! there on deserialization and written back verbatim, so read-modify-write
+//! cycles (e.g. editing `metadata.json`) never drop them. Missing fields fall
+//! back to their defaults instead of failing the whole document.
 
 mod error;
 mod instrument;
@@ -30,6 +38,7 @@ mod processing;
 mod run;
 mod sdrf;
 mod source;
+mod timestamp;
 mod traces;
 
 #[cfg(test)]
@@ -38,9 +47,13 @@ mod tests;
 pub use error::MetadataError;
 pub use instrument::{InstrumentConfig, MassAnalyzerConfig};
 pub use lc::{ColumnInfo, GradientProgram, GradientStep, LcConfig, MobilePhase};
-pub use mzpeak::{ImagingMetadata, MzPeakMetadata, VendorHints};
+pub use mzpeak::{AxisOrientation, ImagingMetadata, MzPeakMetadata, VendorHints};
 pub use processing::{ProcessingHistory, ProcessingStep};
 pub use run::RunParameters;
 pub use sdrf::SdrfMetadata;
 pub use source::SourceFileInfo;
+pub use timestamp::{normalize_timestamp, parse_timestamp, ParsedTimestamp, TimestampZone};
 pub use traces::{PressureTrace, TemperatureTrace};
+
+/// JSON fields not known to this version, preserved verbatim on round-trip
+pub type UnknownFields = serde_json::Map<String, serde_json::Value>;
@@ -8,7 +8,10 @@ use super::processing::ProcessingHistory;
 use super::run::RunParameters;
 use super::sdrf::SdrfMetadata;
 use super::source::SourceFileInfo;
-use super::MetadataError;
+use super::{MetadataError, UnknownFields};
+
+/// Header fields of `metadata.json` regenerated on every write
+const METADATA_JSON_HEADER_KEYS: [&str; 3] = ["format_version", "created", "converter"];
 
 /// Vendor hints for files converted via intermediate formats (e.g., mzML).
 ///
@@ -16,6 +19,7 @@ use super::MetadataError;
 /// information may be lost or obscured. This struct preserves hints about the
 /// original vendor source to enable better downstream processing decisions.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct VendorHints {
     /// Original vendor name (e.g., "Waters", "Sciex", "Agilent")
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -32,6 +36,10 @@ pub struct VendorHints {
     /// Conversion path taken (e.g., ["waters_raw", "mzML", "mzpeak"])
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub conversion_path: Vec<String>,
+
+    /// Fields not known to this version, preserved verbatim
+    #[serde(flatten)]
+    pub unknown_fields: UnknownFields,
 }
 
 impl VendorHints {
@@ -87,23 +95,30 @@ impl VendorHints {
 
 /// Complete metadata container for an mzPeak file
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MzPeakMetadata {
     /// SDRF experimental metadata
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sdrf: Option<SdrfMetadata>,
 
     /// Instrument configuration
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub instrument: Option<InstrumentConfig>,
 
     /// LC configuration
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub lc_config: Option<LcConfig>,
 
     /// Run-level technical parameters
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub run_parameters: Option<RunParameters>,
 
     /// Source file information
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub source_file: Option<SourceFileInfo>,
 
     /// Processing history
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub processing_history: Option<ProcessingHistory>,
 
     /// SHA-256 checksum of the original raw file (top-level for quick access)
@@ -117,10 +132,21 @@ pub struct MzPeakMetadata {
     /// Vendor hints for files converted via intermediate formats (e.g., mzML)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vendor_hints: Option<VendorHints>,
+
+    /// Fields not known to this version, preserved verbatim
+    #[serde(flatten)]
+    pub unknown_fields: UnknownFields,
 }
 
 /// MALDI/imaging grid metadata for spatial indexing.
+///
+/// Maps the integer pixel coordinates of the spectra (`pixel_x`, `pixel_y`)
+/// to physical positions in micrometers: pixel `(x, y)` lies
+/// `(x - 1) * pixel_size_x_um` and `(y - 1) * pixel_size_y_um` along the image
+/// axes from the origin, the stage position of pixel (1, 1) (see
+/// [`ImagingMetadata::position_um`]).
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ImagingMetadata {
     /// Width of the pixel grid (X dimension, zero-indexed + 1)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -134,9 +160,79 @@ pub struct ImagingMetadata {
     /// Pixel size along Y in micrometers
     #[serde(skip_serializing_if = "Option::is_none")]
     pub pixel_size_y_um: Option<f64>,
+    /// Stage X coordinate of pixel (1, 1) in micrometers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin_x_um: Option<f64>,
+    /// Stage Y coordinate of pixel (1, 1) in micrometers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin_y_um: Option<f64>,
+    /// Direction of the Y axis when the image is displayed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub axis_orientation: Option<AxisOrientation>,
+
+    /// Fields not known to this version, preserved verbatim
+    #[serde(flatten)]
+    pub unknown_fields: UnknownFields,
+}
+
+/// Direction of the imaging Y axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AxisOrientation {
+    /// Row 1 is the top of the image (imzML convention)
+    YDown,
+    /// Row 1 is the bottom of the image (Cartesian stage coordinates)
+    YUp,
 }
 
 impl ImagingMetadata {
+    /// Position of pixel `(x, y)` in micrometers
+    ///
+    /// Returns `None` without pixel sizes. A missing origin is taken as
+    /// `(0, 0)`, giving positions relative to pixel (1, 1).
+    pub fn position_um(&self, x: i32, y: i32) -> Option<(f64, f64)> {
+        let (size_x, size_y) = (self.pixel_size_x_um?, self.pixel_size_y_um?);
+        Some((
+            self.origin_x_um.unwrap_or(0.0) + f64::from(x - 1) * size_x,
+            self.origin_y_um.unwrap_or(0.0) + f64::from(y - 1) * size_y,
+        ))
+    }
+
+    /// Derive the grid calibration from pixels with known stage positions
+    ///
+    /// `positions` holds `(pixel_x, pixel_y, stage_x_um, stage_y_um)`. Pixel
+    /// sizes are the absolute stage steps between the two most distant pixels
+    /// along each axis, so the positions must lie on a regular grid. The axis
+    /// orientation is [`AxisOrientation::YUp`] when the stage Y coordinate
+    /// grows with the pixel row. Returns `None` for fewer than two distinct
+    /// pixels per axis.
+    pub fn from_stage_positions(positions: &[(i32, i32, f64, f64)]) -> Option<Self> {
+        let min_x = positions.iter().min_by_key(|p| p.0)?;
+        let max_x = positions.iter().max_by_key(|p| p.0)?;
+        let min_y = positions.iter().min_by_key(|p| p.1)?;
+        let max_y = positions.iter().max_by_key(|p| p.1)?;
+        if max_x.0 == min_x.0 || max_y.1 == min_y.1 {
+            return None;
+        }
+
+        let step_x = (max_x.2 - min_x.2) / f64::from(max_x.0 - min_x.0);
+        let step_y = (max_y.3 - min_y.3) / f64::from(max_y.1 - min_y.1);
+        Some(Self {
+            grid_width: u32::try_from(max_x.0 - min_x.0 + 1).ok(),
+            grid_height: u32::try_from(max_y.1 - min_y.1 + 1).ok(),
+            pixel_size_x_um: Some(step_x.abs()),
+            pixel_size_y_um: Some(step_y.abs()),
+            origin_x_um: Some(min_x.2 - f64::from(min_x.0 - 1) * step_x),
+            origin_y_um: Some(min_y.3 - f64::from(min_y.1 - 1) * step_y),
+            axis_orientation: Some(if step_y > 0.0 {
+                AxisOrientation::YUp
+            } else {
+                AxisOrientation::YDown
+            }),
+            unknown_fields: UnknownFields::new(),
+        })
+    }
+
     /// Serialize imaging metadata to JSON for Parquet footer storage.
     pub fn to_json(&self) -> Result<String, MetadataError> {
         Ok(serde_json::to_string(self)?)
@@ -258,6 +354,45 @@ impl MzPeakMetadata {
         Ok(result)
     }
 
+    /// Serialize to the `metadata.json` document of a dataset bundle
+    ///
+    /// The writer-generated header fields (`format_version`, `created`,
+    /// `converter`) are always refreshed; unknown fields captured from an
+    /// earlier read are written back unchanged.
+    pub fn to_metadata_json(&self, format_version: &str) -> Result<String, MetadataError> {
+        let mut json_map = match serde_json::to_value(self)? {
+            serde_json::Value::Object(map) => map,
+            _ => UnknownFields::new(),
+        };
+
+        json_map.insert(
+            "format_version".to_string(),
+            serde_json::Value::String(format_version.to_string()),
+        );
+        json_map.insert(
+            "created".to_string(),
+            serde_json::Value::String(chrono::Utc::now().to_rfc3339()),
+        );
+        json_map.insert(
+            "converter".to_string(),
+            serde_json::Value::String(format!("mzpeak-rs v{}", env!("CARGO_PKG_VERSION"))),
+        );
+
+        Ok(serde_json::to_string_pretty(&serde_json::Value::Object(json_map))?)
+    }
+
+    /// Parse the `metadata.json` document of a dataset bundle
+    ///
+    /// Fields this version does not recognize are kept in `unknown_fields` (at
+    /// every nesting level) so that writing the metadata back preserves them.
+    pub fn from_metadata_json(json: &str) -> Result<Self, MetadataError> {
+        let mut metadata: Self = serde_json::from_str(json)?;
+        for key in METADATA_JSON_HEADER_KEYS {
+            metadata.unknown_fields.remove(key);
+        }
+        Ok(metadata)
+    }
+
     /// Set vendor hints for this metadata
     pub fn with_vendor_hints(mut self, hints: VendorHints) -> Self {
         self.vendor_hints = Some(hints);
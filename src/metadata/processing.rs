@@ -3,13 +3,18 @@ use std::collections::HashMap;
 
 use crate::controlled_vocabulary::CvParamList;
 
-use super::MetadataError;
+use super::{MetadataError, UnknownFields};
 
 /// Data processing history for audit trail
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ProcessingHistory {
     /// List of processing steps applied
     pub steps: Vec<ProcessingStep>,
+
+    /// Fields not known to this version, preserved verbatim
+    #[serde(flatten)]
+    pub unknown_fields: UnknownFields,
 }
 
 /// A single data processing step in the processing history
@@ -31,10 +36,34 @@ pub struct ProcessingStep {
     pub timestamp: Option<String>,
 
     /// Processing parameters
+    #[serde(default)]
     pub parameters: HashMap<String, String>,
 
     /// CV parameters describing the processing
+    #[serde(default)]
     pub cv_params: CvParamList,
+
+    /// Fields not known to this version, preserved verbatim
+    #[serde(flatten)]
+    pub unknown_fields: UnknownFields,
+}
+
+impl ProcessingStep {
+    /// Step performed by this library, timestamped now
+    ///
+    /// The order is assigned by [`ProcessingHistory::append`].
+    pub fn mzpeak(processing_type: impl Into<String>, parameters: HashMap<String, String>) -> Self {
+        Self {
+            order: 0,
+            software: "mzpeak-rs".to_string(),
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            processing_type: processing_type.into(),
+            timestamp: Some(chrono::Utc::now().to_rfc3339()),
+            parameters,
+            cv_params: Default::default(),
+            unknown_fields: Default::default(),
+        }
+    }
 }
 
 impl ProcessingHistory {
@@ -48,6 +77,12 @@ impl ProcessingHistory {
         self.steps.push(step);
     }
 
+    /// Add a step after the existing ones, numbering it accordingly
+    pub fn append(&mut self, mut step: ProcessingStep) {
+        step.order = self.steps.iter().map(|s| s.order).max().unwrap_or(0) + 1;
+        self.steps.push(step);
+    }
+
     /// Serialize to JSON for Parquet footer storage
     pub fn to_json(&self) -> Result<String, MetadataError> {
         Ok(serde_json::to_string(self)?)
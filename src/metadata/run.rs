@@ -4,13 +4,14 @@ use std::collections::HashMap;
 use crate::controlled_vocabulary::{CvParamList, CvTerm};
 
 use super::traces::{PressureTrace, TemperatureTrace};
-use super::MetadataError;
+use super::{MetadataError, UnknownFields};
 
 /// Technical run parameters - lossless storage of vendor-specific data
 ///
 /// This is a critical differentiator for mzPeak. Unlike mzML converters that
 /// discard technical metadata, mzPeak preserves all available vendor data.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RunParameters {
     /// Run start timestamp (ISO 8601)
     pub start_time: Option<String>,
@@ -74,6 +75,10 @@ pub struct RunParameters {
 
     /// CV parameters
     pub cv_params: CvParamList,
+
+    /// Fields not known to this version, preserved verbatim
+    #[serde(flatten)]
+    pub unknown_fields: UnknownFields,
 }
 
 impl RunParameters {
@@ -4,12 +4,13 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::Path;
 
-use super::MetadataError;
+use super::{MetadataError, UnknownFields};
 
 /// SDRF-Proteomics metadata following the community standard
 ///
 /// Reference: <https://github.com/bigbio/proteomics-sample-metadata>
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SdrfMetadata {
     /// Source file name (required)
     pub source_name: String,
@@ -58,6 +59,10 @@ pub struct SdrfMetadata {
 
     /// Additional custom attributes
     pub custom_attributes: HashMap<String, String>,
+
+    /// Fields not known to this version, preserved verbatim
+    #[serde(flatten)]
+    pub unknown_fields: UnknownFields,
 }
 
 impl SdrfMetadata {
@@ -1,9 +1,10 @@
 use serde::{Deserialize, Serialize};
 
-use super::MetadataError;
+use super::{MetadataError, UnknownFields};
 
 /// Source file information for provenance tracking
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SourceFileInfo {
     /// Original file name
     pub name: String,
@@ -25,6 +26,10 @@ pub struct SourceFileInfo {
 
     /// Vendor file version/format version
     pub format_version: Option<String>,
+
+    /// Fields not known to this version, preserved verbatim
+    #[serde(flatten)]
+    pub unknown_fields: UnknownFields,
 }
 
 impl SourceFileInfo {
@@ -43,3 +43,162 @@ fn test_run_parameters() {
         Some(&"SomeValue".to_string())
     );
 }
+
+/// metadata.json as a hypothetical newer writer would produce it
+const FUTURE_METADATA_JSON: &str = r#"{
+  "format_version": "9.0",
+  "created": "2030-01-01T00:00:00Z",
+  "converter": "mzpeak-rs v9.0.0",
+  "future_section": {"nested": [1, 2, {"deep": true}]},
+  "instrument": {
+    "model": "Orbitrap Astral",
+    "mass_analyzers": [
+      {"analyzer_type": "orbitrap", "order": 1, "future_analyzer_field": 42}
+    ],
+    "future_instrument_field": "kept"
+  },
+  "lc_config": {
+    "gradient": {"steps": [{"time_min": 0.0, "percent_b": 2.0, "future_step_field": null}]}
+  },
+  "processing_history": {
+    "steps": [{"order": 1, "software": "mzpeak-rs", "processing_type": "conversion", "future_step_field": 1.5}]
+  }
+}"#;
+
+/// Remove the regenerated header fields so documents can be compared
+fn without_header(json: &str) -> serde_json::Value {
+    let mut value: serde_json::Value = serde_json::from_str(json).unwrap();
+    let map = value.as_object_mut().unwrap();
+    for key in ["format_version", "created", "converter"] {
+        map.remove(key);
+    }
+    value
+}
+
+#[test]
+fn test_metadata_json_tolerates_missing_fields() {
+    let metadata = MzPeakMetadata::from_metadata_json(FUTURE_METADATA_JSON).unwrap();
+
+    let instrument = metadata.instrument.as_ref().unwrap();
+    assert_eq!(instrument.model.as_deref(), Some("Orbitrap Astral"));
+    assert!(instrument.cv_params.is_empty());
+    assert_eq!(instrument.mass_analyzers[0].order, 1);
+
+    let step = &metadata.processing_history.as_ref().unwrap().steps[0];
+    assert!(step.parameters.is_empty());
+    assert!(metadata.sdrf.is_none());
+}
+
+#[test]
+fn test_metadata_json_roundtrip_preserves_unknown_fields() {
+    let metadata = MzPeakMetadata::from_metadata_json(FUTURE_METADATA_JSON).unwrap();
+    assert!(!metadata.unknown_fields.contains_key("format_version"));
+
+    let written = metadata.to_metadata_json("2.0").unwrap();
+    let expected = without_header(FUTURE_METADATA_JSON);
+    let actual = without_header(&written);
+
+    // Known fields missing from the input are filled with their defaults,
+    // so compare only the fields that were present in the original.
+    fn assert_subset(expected: &serde_json::Value, actual: &serde_json::Value, path: &str) {
+        match (expected, actual) {
+            (serde_json::Value::Object(e), serde_json::Value::Object(a)) => {
+                for (key, value) in e {
+                    let child = format!("{}/{}", path, key);
+                    let other = a.get(key).unwrap_or_else(|| panic!("lost field {}", child));
+                    assert_subset(value, other, &child);
+                }
+            }
+            (serde_json::Value::Array(e), serde_json::Value::Array(a)) => {
+                assert_eq!(e.len(), a.len(), "array length changed at {}", path);
+                for (i, (x, y)) in e.iter().zip(a).enumerate() {
+                    assert_subset(x, y, &format!("{}/{}", path, i));
+                }
+            }
+            _ => assert_eq!(expected, actual, "value changed at {}", path),
+        }
+    }
+    assert_subset(&expected, &actual, "");
+
+    // A second cycle must be a fixed point
+    let again = MzPeakMetadata::from_metadata_json(&written)
+        .unwrap()
+        .to_metadata_json("2.0")
+        .unwrap();
+    assert_eq!(without_header(&again), actual);
+}
+
+#[test]
+fn test_metadata_json_edit_keeps_unknown_fields() {
+    let mut metadata = MzPeakMetadata::from_metadata_json(FUTURE_METADATA_JSON).unwrap();
+
+    // Edit known fields at several levels
+    let instrument = metadata.instrument.as_mut().unwrap();
+    instrument.serial_number = Some("SN123".to_string());
+    instrument.mass_analyzers[0].resolution = Some(240000.0);
+    metadata.sdrf = Some(SdrfMetadata::new("EditedSample"));
+
+    let written = metadata.to_metadata_json("2.0").unwrap();
+    let value = without_header(&written);
+
+    assert_eq!(
+        value["future_section"],
+        serde_json::json!({"nested": [1, 2, {"deep": true}]})
+    );
+    assert_eq!(value["instrument"]["serial_number"], "SN123");
+    assert_eq!(value["instrument"]["future_instrument_field"], "kept");
+    assert_eq!(value["instrument"]["mass_analyzers"][0]["future_analyzer_field"], 42);
+    assert_eq!(value["instrument"]["mass_analyzers"][0]["resolution"], 240000.0);
+    assert!(value["lc_config"]["gradient"]["steps"][0]
+        .as_object()
+        .unwrap()
+        .contains_key("future_step_field"));
+    assert_eq!(value["processing_history"]["steps"][0]["future_step_field"], 1.5);
+    assert_eq!(value["sdrf"]["source_name"], "EditedSample");
+}
+
+#[test]
+fn test_footer_json_preserves_unknown_fields() {
+    let json = r#"{"model":"Exploris","future_field":{"a":1}}"#;
+    let instrument = InstrumentConfig::from_json(json).unwrap();
+    let restored: serde_json::Value =
+        serde_json::from_str(&instrument.to_json().unwrap()).unwrap();
+    assert_eq!(restored["future_field"], serde_json::json!({"a": 1}));
+}
+
+#[test]
+fn test_run_timestamp_normalization() -> Result<(), Box<dyn std::error::Error>> {
+    let parsed = parse_timestamp("01/15/2024 02:30:00 PM", TimestampZone::Utc).ok_or("US date")?;
+    assert_eq!(parsed.to_rfc3339(), "2024-01-15T14:30:00Z");
+    assert!(parsed.zone_assumed);
+    assert!(!parsed.day_month_ambiguous);
+
+    let cet = chrono::FixedOffset::east_opt(3600).ok_or("offset")?;
+    let parsed =
+        parse_timestamp("04.03.2024 10:30:00", TimestampZone::Fixed(cet)).ok_or("EU date")?;
+    assert_eq!(parsed.to_rfc3339(), "2024-03-04T10:30:00+01:00");
+    assert!(parsed.day_month_ambiguous);
+
+    let parsed =
+        parse_timestamp("2024-01-15T10:30:00.5+0200", TimestampZone::Utc).ok_or("offset")?;
+    assert!(!parsed.is_ambiguous());
+    assert!(parse_timestamp("yesterday", TimestampZone::Utc).is_none());
+
+    let mut run = RunParameters::new();
+    run.start_time = Some("2024-01-15 10:30:00".to_string());
+    run.end_time = Some("2024-01-15T12:30:00Z".to_string());
+    run.normalize_timestamps(TimestampZone::Utc);
+    assert_eq!(run.start_time.as_deref(), Some("2024-01-15T10:30:00Z"));
+    assert_eq!(run.end_time.as_deref(), Some("2024-01-15T12:30:00Z"));
+    assert_eq!(
+        run.vendor_params
+            .get("original_start_time")
+            .map(String::as_str),
+        Some("2024-01-15 10:30:00")
+    );
+    assert!(!run.vendor_params.contains_key("original_end_time"));
+
+    let duration = run.end_datetime().ok_or("end")? - run.start_datetime().ok_or("start")?;
+    assert_eq!(duration.num_hours(), 2);
+    Ok(())
+}
@@ -0,0 +1,183 @@
+//! Run timestamp parsing and normalization
+//!
+//! Vendors report run start and end times in their own formats, often in the
+//! instrument PC's local time without an offset (`01/15/2024 10:30:00`,
+//! `15.01.2024 10:30:00`). [`parse_timestamp`] accepts the common forms and
+//! resolves zone-less ones with a configurable [`TimestampZone`];
+//! [`RunParameters::normalize_timestamps`] rewrites the run times as RFC 3339.
+//!
+//! Timestamps that had to be resolved by assumption are flagged so the
+//! validator can warn about them:
+//!
+//! - no UTC offset: the zone was assumed
+//! - numeric dates with day and month both ≤ 12: `/` dates are read as
+//!   month/day (US), `.` dates as day.month
+
+use chrono::{
+    DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone, Utc,
+};
+
+use super::RunParameters;
+
+/// Zone assumed for timestamps without a UTC offset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimestampZone {
+    /// Coordinated Universal Time
+    #[default]
+    Utc,
+    /// Local time zone of the machine reading or converting
+    Local,
+    /// Fixed offset, e.g. the instrument site's zone
+    Fixed(FixedOffset),
+}
+
+impl TimestampZone {
+    /// Resolve a local date-time in this zone
+    ///
+    /// Times skipped by a daylight saving change resolve to `None`; repeated
+    /// times resolve to the earlier instant.
+    pub fn resolve(&self, naive: &NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+        match self {
+            TimestampZone::Utc => Some(Utc.from_utc_datetime(naive).fixed_offset()),
+            TimestampZone::Local => Local
+                .from_local_datetime(naive)
+                .earliest()
+                .map(|dt| dt.fixed_offset()),
+            TimestampZone::Fixed(offset) => offset.from_local_datetime(naive).single(),
+        }
+    }
+}
+
+/// A parsed timestamp and the assumptions needed to parse it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParsedTimestamp {
+    /// The instant, with the offset it was given in (or resolved to)
+    pub datetime: DateTime<FixedOffset>,
+    /// The text had no UTC offset; the configured zone was assumed
+    pub zone_assumed: bool,
+    /// Day and month of a numeric date could be swapped
+    pub day_month_ambiguous: bool,
+}
+
+impl ParsedTimestamp {
+    /// Whether any assumption was needed
+    pub fn is_ambiguous(&self) -> bool {
+        self.zone_assumed || self.day_month_ambiguous
+    }
+
+    /// RFC 3339 representation (`Z` for UTC)
+    pub fn to_rfc3339(&self) -> String {
+        self.datetime.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    }
+}
+
+/// Formats with an explicit offset
+const OFFSET_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f%z",
+    "%Y-%m-%d %H:%M:%S%.f%z",
+    "%Y-%m-%d %H:%M:%S%.f %z",
+];
+
+/// Formats without an offset, and whether their day and month can be confused
+const NAIVE_FORMATS: &[(&str, bool)] = &[
+    ("%Y-%m-%dT%H:%M:%S%.f", false),
+    ("%Y-%m-%d %H:%M:%S%.f", false),
+    ("%m/%d/%Y %I:%M:%S %p", true),
+    ("%m/%d/%Y %H:%M:%S", true),
+    ("%d.%m.%Y %H:%M:%S", true),
+    ("%a %b %e %H:%M:%S %Y", false),
+    ("%d-%b-%Y %H:%M:%S", false),
+];
+
+/// Parse a run timestamp, resolving zone-less text in `zone`
+///
+/// Returns `None` for text in none of the supported formats.
+pub fn parse_timestamp(text: &str, zone: TimestampZone) -> Option<ParsedTimestamp> {
+    let text = text.trim();
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(text) {
+        return Some(ParsedTimestamp {
+            datetime,
+            zone_assumed: false,
+            day_month_ambiguous: false,
+        });
+    }
+    for format in OFFSET_FORMATS {
+        if let Ok(datetime) = DateTime::parse_from_str(text, format) {
+            return Some(ParsedTimestamp {
+                datetime,
+                zone_assumed: false,
+                day_month_ambiguous: false,
+            });
+        }
+    }
+    for &(format, swappable) in NAIVE_FORMATS {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(text, format) {
+            return Some(ParsedTimestamp {
+                datetime: zone.resolve(&naive)?,
+                zone_assumed: true,
+                day_month_ambiguous: swappable && day_month_swappable(naive.date()),
+            });
+        }
+    }
+    None
+}
+
+/// Normalize a run timestamp to RFC 3339, `None` if it cannot be parsed
+pub fn normalize_timestamp(text: &str, zone: TimestampZone) -> Option<String> {
+    parse_timestamp(text, zone).map(|parsed| parsed.to_rfc3339())
+}
+
+/// Whether swapping day and month gives a different valid date
+fn day_month_swappable(date: NaiveDate) -> bool {
+    use chrono::Datelike;
+    date.day() != date.month() && date.day() <= 12
+}
+
+impl RunParameters {
+    /// Run start time, zone-less timestamps read as UTC
+    pub fn start_datetime(&self) -> Option<DateTime<FixedOffset>> {
+        self.start_datetime_in(TimestampZone::Utc)
+    }
+
+    /// Run end time, zone-less timestamps read as UTC
+    pub fn end_datetime(&self) -> Option<DateTime<FixedOffset>> {
+        self.end_datetime_in(TimestampZone::Utc)
+    }
+
+    /// Run start time, zone-less timestamps read in `zone`
+    pub fn start_datetime_in(&self, zone: TimestampZone) -> Option<DateTime<FixedOffset>> {
+        parse_timestamp(self.start_time.as_deref()?, zone).map(|parsed| parsed.datetime)
+    }
+
+    /// Run end time, zone-less timestamps read in `zone`
+    pub fn end_datetime_in(&self, zone: TimestampZone) -> Option<DateTime<FixedOffset>> {
+        parse_timestamp(self.end_time.as_deref()?, zone).map(|parsed| parsed.datetime)
+    }
+
+    /// Rewrite the run start and end times as RFC 3339
+    ///
+    /// Timestamps already in RFC 3339 are kept as written; zone-less ones are
+    /// resolved in `zone`. The original text of a rewritten timestamp is kept
+    /// in `vendor_params` (`original_start_time`, `original_end_time`);
+    /// unparseable timestamps are left unchanged.
+    pub fn normalize_timestamps(&mut self, zone: TimestampZone) {
+        for (time, original_key) in [
+            (&mut self.start_time, "original_start_time"),
+            (&mut self.end_time, "original_end_time"),
+        ] {
+            let Some(text) = time.as_deref() else {
+                continue;
+            };
+            if DateTime::parse_from_rfc3339(text).is_ok() {
+                continue;
+            }
+            let Some(normalized) = normalize_timestamp(text, zone) else {
+                continue;
+            };
+            self.vendor_params
+                .entry(original_key.to_string())
+                .or_insert_with(|| text.to_string());
+            *time = Some(normalized);
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use super::UnknownFields;
+
 /// Pressure trace over time (e.g., pump pressure during LC run)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PressureTrace {
@@ -14,6 +16,10 @@ pub struct PressureTrace {
 
     /// Pressure values
     pub values: Vec<f64>,
+
+    /// Fields not known to this version, preserved verbatim
+    #[serde(flatten)]
+    pub unknown_fields: UnknownFields,
 }
 
 /// Temperature trace over time
@@ -27,4 +33,8 @@ pub struct TemperatureTrace {
 
     /// Temperature values in Celsius
     pub values_celsius: Vec<f64>,
+
+    /// Fields not known to this version, preserved verbatim
+    #[serde(flatten)]
+    pub unknown_fields: UnknownFields,
 }
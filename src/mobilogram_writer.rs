@@ -29,8 +29,10 @@ use parquet::arrow::ArrowWriter;
 use parquet::basic::{Compression, ZstdLevel};
 use parquet::file::properties::{EnabledStatistics, WriterProperties};
 use parquet::format::KeyValue;
+use serde::{Deserialize, Serialize};
 
 use crate::metadata::MzPeakMetadata;
+use crate::schema::column_metadata::{append_column_key_values, field_with_cv};
 use crate::schema::{KEY_FORMAT_VERSION, MZPEAK_FORMAT_VERSION};
 
 /// Column names for mobilogram schema
@@ -45,13 +47,6 @@ pub mod mobilogram_columns {
     pub const INTENSITY_ARRAY: &str = "intensity_array";
 }
 
-/// Creates a Field with CV term metadata annotation
-fn field_with_cv(name: &str, data_type: DataType, nullable: bool, cv_accession: &str) -> Field {
-    let mut metadata = HashMap::new();
-    metadata.insert("cv_accession".to_string(), cv_accession.to_string());
-    Field::new(name, data_type, nullable).with_metadata(metadata)
-}
-
 /// Creates the mobilogram Arrow schema for the "Wide" format.
 ///
 /// # Example
@@ -316,7 +311,8 @@ impl<W: Write + Send> MobilogramWriter<W> {
         let parquet_metadata = metadata.to_parquet_metadata()?;
         let props = config.to_writer_properties(&parquet_metadata);
 
-        let arrow_writer = ArrowWriter::try_new(writer, schema.clone(), Some(props))?;
+        let mut arrow_writer = ArrowWriter::try_new(writer, schema.clone(), Some(props))?;
+        append_column_key_values(&mut arrow_writer, &schema);
 
         Ok(Self {
             writer: arrow_writer,
@@ -420,7 +416,8 @@ impl<W: Write + Send> MobilogramWriter<W> {
 }
 
 /// Statistics from a completed mobilogram write operation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MobilogramWriterStats {
     /// Number of mobilograms written
     pub mobilograms_written: usize,
@@ -0,0 +1,94 @@
+//! C declarations shared with plugin libraries
+//!
+//! These mirror `include/mzpeak_plugin.h`. Any change to a layout or to the
+//! calling contract needs a new [`MZPEAK_PLUGIN_ABI_VERSION`].
+
+use std::os::raw::c_char;
+
+use arrow::ffi::{FFI_ArrowArray, FFI_ArrowSchema};
+
+/// ABI version implemented by this build
+pub const MZPEAK_PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Symbol every plugin library exports (a [`MzPeakPluginRegisterFn`])
+pub const MZPEAK_PLUGIN_ENTRY: &str = "mzpeak_plugin_register";
+
+/// Status returned by a step that processed the spectrum
+pub const MZPEAK_PLUGIN_OK: i32 = 0;
+
+/// Spectrum metadata passed alongside the peak arrays
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct MzPeakSpectrumInfo {
+    /// Spectrum ID in the output file
+    pub spectrum_id: i64,
+    /// Retention time in seconds
+    pub retention_time: f32,
+    /// MS level (1, 2, ...)
+    pub ms_level: i16,
+    /// Polarity: 1 positive, -1 negative, 0 unknown
+    pub polarity: i8,
+    /// Precursor m/z, NaN for spectra without a precursor
+    pub precursor_mz: f64,
+}
+
+/// Processes the peaks of one spectrum
+///
+/// `input` is a struct array (schema `input_schema`) with a Float64 `mz`
+/// column, a Float32 `intensity` column and, for ion mobility data, a
+/// nullable Float64 `ion_mobility` column. The step may take ownership of
+/// `input` by moving it out and marking the original released, as the C Data
+/// Interface describes; otherwise the host releases it after the call.
+/// `input_schema` is borrowed.
+///
+/// On success the step returns [`MZPEAK_PLUGIN_OK`] and either exports a
+/// struct array with the same columns into `output`/`output_schema`, whose
+/// ownership passes to the host, or leaves them untouched to keep the peaks
+/// unchanged. Rows may be dropped, added or reordered, but `mz` must stay
+/// sorted.
+///
+/// Any other return value fails the conversion. The step may write a
+/// NUL-terminated message of at most `error_len` bytes into `error`.
+///
+/// Steps may be called from several threads at once and must be thread safe.
+pub type MzPeakProcessFn = unsafe extern "C" fn(
+    info: *const MzPeakSpectrumInfo,
+    input: *mut FFI_ArrowArray,
+    input_schema: *const FFI_ArrowSchema,
+    output: *mut FFI_ArrowArray,
+    output_schema: *mut FFI_ArrowSchema,
+    error: *mut c_char,
+    error_len: usize,
+) -> i32;
+
+/// One processing step offered by a plugin
+#[repr(C)]
+#[derive(Debug)]
+pub struct MzPeakPluginStep {
+    /// Step name used to select it, NUL-terminated UTF-8
+    pub name: *const c_char,
+    /// One-line description, NUL-terminated UTF-8; may be null
+    pub description: *const c_char,
+    /// Step function
+    pub process: Option<MzPeakProcessFn>,
+}
+
+/// Registration record returned by the plugin entry point
+///
+/// The record, its strings and its steps must stay valid while the library
+/// is loaded.
+#[repr(C)]
+#[derive(Debug)]
+pub struct MzPeakPlugin {
+    /// Must equal [`MZPEAK_PLUGIN_ABI_VERSION`]
+    pub abi_version: u32,
+    /// Plugin name, NUL-terminated UTF-8
+    pub name: *const c_char,
+    /// Number of entries in `steps`
+    pub step_count: usize,
+    /// Array of `step_count` steps
+    pub steps: *const MzPeakPluginStep,
+}
+
+/// Signature of the [`MZPEAK_PLUGIN_ENTRY`] symbol
+pub type MzPeakPluginRegisterFn = unsafe extern "C" fn() -> *const MzPeakPlugin;
@@ -0,0 +1,436 @@
+//! Processing step plugins
+//!
+//! Custom processing such as denoising or recalibration can live in a shared
+//! library and run during conversion without rebuilding mzpeak. A plugin
+//! exports one C function, `mzpeak_plugin_register`, returning an
+//! [`MzPeakPlugin`] record that lists its steps. Each step receives the peaks
+//! of one spectrum as an Arrow C Data Interface struct array and hands back
+//! the processed peaks the same way (see [`MzPeakProcessFn`]).
+//!
+//! C and C++ plugins include `include/mzpeak_plugin.h`;
+//! `examples/plugins/denoise_plugin.rs` is a plugin written in Rust. On the
+//! command line a step is selected with:
+//!
+//! ```text
+//! mzpeak convert run.mzML run.mzpeak --plugin ./libmyfilter.so --step denoise
+//! ```
+//!
+//! From Rust, loaded steps go into [`crate::ingest::SpectrumSteps`], which
+//! the mzML converter runs through `ConversionConfig::spectrum_steps`:
+//!
+//! ```rust,no_run
+//! use mzpeak::ingest::SpectrumSteps;
+//! use mzpeak::plugin::Plugin;
+//!
+//! let plugin = Plugin::load("./libmyfilter.so")?;
+//! let mut steps = SpectrumSteps::default();
+//! steps.push(plugin.step("denoise")?);
+//! # Ok::<(), mzpeak::plugin::PluginError>(())
+//! ```
+//!
+//! Plugins run in-process with full access to the host: only load libraries
+//! you trust.
+
+pub mod abi;
+
+#[cfg(test)]
+mod tests;
+
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::c_char;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::array::{make_array, Array, ArrayRef, AsArray, Float32Array, Float64Array, StructArray};
+use arrow::datatypes::{DataType, Field, Float32Type, Float64Type};
+use arrow::error::ArrowError;
+use arrow::ffi::{from_ffi, to_ffi, FFI_ArrowArray, FFI_ArrowSchema};
+use libloading::Library;
+
+pub use abi::{
+    MzPeakPlugin, MzPeakPluginRegisterFn, MzPeakPluginStep, MzPeakProcessFn, MzPeakSpectrumInfo,
+    MZPEAK_PLUGIN_ABI_VERSION, MZPEAK_PLUGIN_ENTRY, MZPEAK_PLUGIN_OK,
+};
+
+use crate::ingest::SpectrumStep;
+use crate::writer::{OptionalColumnBuf, PeakArrays, SpectrumArrays};
+
+/// Size of the buffer steps write error messages into
+const ERROR_BUFFER_LEN: usize = 1024;
+
+/// Errors from loading or running plugins
+#[derive(Debug, thiserror::Error)]
+pub enum PluginError {
+    /// The library could not be loaded or does not export the entry point
+    #[error("failed to load plugin {path}: {source}")]
+    Load {
+        /// Library path
+        path: String,
+        /// Loader error
+        #[source]
+        source: libloading::Error,
+    },
+
+    /// The plugin was built against another ABI version
+    #[error("plugin ABI version {found} is not supported (expected {expected})")]
+    AbiMismatch {
+        /// Version reported by the plugin
+        found: u32,
+        /// Version implemented by this build
+        expected: u32,
+    },
+
+    /// The registration record is malformed
+    #[error("invalid plugin registration: {0}")]
+    InvalidRegistration(String),
+
+    /// The plugin has no step with the requested name
+    #[error("plugin '{plugin}' has no step '{step}' (available: {available})")]
+    UnknownStep {
+        /// Plugin name
+        plugin: String,
+        /// Requested step
+        step: String,
+        /// Comma-separated names of the steps the plugin offers
+        available: String,
+    },
+
+    /// A step returned a non-zero status
+    #[error("plugin step returned status {status}: {message}")]
+    StepFailed {
+        /// Step name
+        step: String,
+        /// Status returned by the step
+        status: i32,
+        /// Message written by the step, if any
+        message: String,
+    },
+
+    /// A step returned peaks that do not follow the ABI
+    #[error("plugin step returned invalid peaks: {message}")]
+    InvalidOutput {
+        /// Step name
+        step: String,
+        /// What is wrong with the peaks
+        message: String,
+    },
+
+    /// Exporting the peaks failed
+    #[error("Arrow error: {0}")]
+    Arrow(#[from] ArrowError),
+}
+
+/// A loaded plugin and the steps it offers
+#[derive(Debug)]
+pub struct Plugin {
+    name: String,
+    steps: Vec<PluginStep>,
+}
+
+impl Plugin {
+    /// Load a plugin library and read its registration
+    ///
+    /// Loading runs the library's initialization code, so the library must
+    /// be trusted. It stays loaded while any of its steps is alive.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PluginError> {
+        let path = path.as_ref();
+        let load_error = |source| PluginError::Load {
+            path: path.display().to_string(),
+            source,
+        };
+        // SAFETY: plugins are trusted native code; the entry point has the
+        // documented signature
+        let (library, registration) = unsafe {
+            let library = Library::new(path).map_err(load_error)?;
+            let register = library
+                .get::<MzPeakPluginRegisterFn>(MZPEAK_PLUGIN_ENTRY.as_bytes())
+                .map_err(load_error)?;
+            let registration = register();
+            (library, registration)
+        };
+        // SAFETY: the record is valid while the library is loaded, and every
+        // step keeps the library alive
+        unsafe { Self::from_raw(registration, Some(Arc::new(library))) }
+    }
+
+    /// Use a plugin linked into the program instead of loaded at runtime
+    ///
+    /// # Safety
+    ///
+    /// `registration` must follow the ABI: valid NUL-terminated strings, a
+    /// `steps` array of `step_count` entries and step functions with the
+    /// documented contract.
+    pub unsafe fn from_registration(
+        registration: &'static MzPeakPlugin,
+    ) -> Result<Self, PluginError> {
+        Self::from_raw(registration, None)
+    }
+
+    unsafe fn from_raw(
+        registration: *const MzPeakPlugin,
+        library: Option<Arc<Library>>,
+    ) -> Result<Self, PluginError> {
+        let registration = registration.as_ref().ok_or_else(|| {
+            PluginError::InvalidRegistration(format!("{} returned null", MZPEAK_PLUGIN_ENTRY))
+        })?;
+        if registration.abi_version != MZPEAK_PLUGIN_ABI_VERSION {
+            return Err(PluginError::AbiMismatch {
+                found: registration.abi_version,
+                expected: MZPEAK_PLUGIN_ABI_VERSION,
+            });
+        }
+        let name = required_string(registration.name, "plugin name")?;
+        let records = match registration.step_count {
+            0 => &[][..],
+            _ if registration.steps.is_null() => {
+                return Err(PluginError::InvalidRegistration(format!(
+                    "plugin '{}' declares {} steps but no step array",
+                    name, registration.step_count
+                )))
+            }
+            count => std::slice::from_raw_parts(registration.steps, count),
+        };
+
+        let mut steps: Vec<PluginStep> = Vec::with_capacity(records.len());
+        for record in records {
+            let step_name = required_string(record.name, "step name")?;
+            if steps.iter().any(|step| step.name == step_name) {
+                return Err(PluginError::InvalidRegistration(format!(
+                    "plugin '{}' registers step '{}' twice",
+                    name, step_name
+                )));
+            }
+            let process = record.process.ok_or_else(|| {
+                PluginError::InvalidRegistration(format!(
+                    "step '{}' has no process function",
+                    step_name
+                ))
+            })?;
+            steps.push(PluginStep {
+                name: step_name,
+                description: optional_string(record.description, "step description")?,
+                process,
+                _library: library.clone(),
+            });
+        }
+        Ok(Self { name, steps })
+    }
+
+    /// Name the plugin registered under
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Steps offered by the plugin, in registration order
+    pub fn steps(&self) -> &[PluginStep] {
+        &self.steps
+    }
+
+    /// The step named `name`
+    pub fn step(&self, name: &str) -> Result<PluginStep, PluginError> {
+        self.steps
+            .iter()
+            .find(|step| step.name == name)
+            .cloned()
+            .ok_or_else(|| PluginError::UnknownStep {
+                plugin: self.name.clone(),
+                step: name.to_string(),
+                available: self
+                    .steps
+                    .iter()
+                    .map(|step| step.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            })
+    }
+}
+
+/// A processing step offered by a [`Plugin`]
+#[derive(Clone)]
+pub struct PluginStep {
+    name: String,
+    description: Option<String>,
+    process: MzPeakProcessFn,
+    // Keeps the library mapped while the step can be called
+    _library: Option<Arc<Library>>,
+}
+
+impl PluginStep {
+    /// Step name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Description registered by the plugin
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Run the step on the peaks of `spectrum`
+    pub fn process_spectrum(&self, spectrum: &mut SpectrumArrays) -> Result<(), PluginError> {
+        let input = peaks_to_array(&spectrum.peaks);
+        let (mut input, input_schema) = to_ffi(&input.to_data())?;
+        let info = MzPeakSpectrumInfo {
+            spectrum_id: spectrum.spectrum_id,
+            retention_time: spectrum.retention_time,
+            ms_level: spectrum.ms_level,
+            polarity: spectrum.polarity,
+            precursor_mz: spectrum.precursor_mz.unwrap_or(f64::NAN),
+        };
+        let mut output = FFI_ArrowArray::empty();
+        let mut output_schema = FFI_ArrowSchema::empty();
+        let mut error: [c_char; ERROR_BUFFER_LEN] = [0; ERROR_BUFFER_LEN];
+
+        // SAFETY: all pointers are valid for the duration of the call
+        let status = unsafe {
+            (self.process)(
+                &info,
+                &mut input,
+                &input_schema,
+                &mut output,
+                &mut output_schema,
+                error.as_mut_ptr(),
+                error.len() - 1,
+            )
+        };
+        if status != MZPEAK_PLUGIN_OK {
+            // The last byte is never handed out, so the message is terminated
+            // SAFETY: `error` is NUL-terminated
+            let message = unsafe { CStr::from_ptr(error.as_ptr()) };
+            return Err(PluginError::StepFailed {
+                step: self.name.clone(),
+                status,
+                message: message.to_string_lossy().into_owned(),
+            });
+        }
+        if output.is_released() {
+            return Ok(());
+        }
+
+        // SAFETY: the step exported `output` with `output_schema`
+        let data = unsafe { from_ffi(output, &output_schema) }
+            .map_err(|error| self.invalid_output(error.to_string()))?;
+        spectrum.peaks = self.peaks_from_array(make_array(data))?;
+        Ok(())
+    }
+
+    fn peaks_from_array(&self, array: ArrayRef) -> Result<PeakArrays, PluginError> {
+        let columns = array
+            .as_struct_opt()
+            .ok_or_else(|| self.invalid_output("expected a struct array"))?;
+        if columns.null_count() > 0 {
+            return Err(self.invalid_output("struct array has null rows"));
+        }
+        let mz = columns
+            .column_by_name("mz")
+            .and_then(|column| column.as_primitive_opt::<Float64Type>())
+            .filter(|column| column.null_count() == 0)
+            .ok_or_else(|| self.invalid_output("missing non-null Float64 'mz' column"))?;
+        let intensity = columns
+            .column_by_name("intensity")
+            .and_then(|column| column.as_primitive_opt::<Float32Type>())
+            .filter(|column| column.null_count() == 0)
+            .ok_or_else(|| self.invalid_output("missing non-null Float32 'intensity' column"))?;
+        if mz.values().windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err(self.invalid_output("mz is not sorted"));
+        }
+
+        let mut peaks = PeakArrays::new(mz.values().to_vec(), intensity.values().to_vec());
+        if let Some(column) = columns.column_by_name("ion_mobility") {
+            let ion_mobility = column
+                .as_primitive_opt::<Float64Type>()
+                .ok_or_else(|| self.invalid_output("'ion_mobility' is not Float64"))?;
+            let values = ion_mobility.values().to_vec();
+            peaks.ion_mobility = match ion_mobility.nulls() {
+                None => OptionalColumnBuf::AllPresent(values),
+                Some(nulls) if nulls.null_count() == nulls.len() => {
+                    OptionalColumnBuf::all_null(values.len())
+                }
+                Some(nulls) => OptionalColumnBuf::WithValidity {
+                    values,
+                    validity: nulls.iter().collect(),
+                },
+            };
+        }
+        Ok(peaks)
+    }
+
+    fn invalid_output(&self, message: impl Into<String>) -> PluginError {
+        PluginError::InvalidOutput {
+            step: self.name.clone(),
+            message: message.into(),
+        }
+    }
+}
+
+impl SpectrumStep for PluginStep {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn process(
+        &self,
+        spectrum: &mut SpectrumArrays,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.process_spectrum(spectrum)?)
+    }
+}
+
+impl fmt::Debug for PluginStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PluginStep")
+            .field("name", &self.name)
+            .field("description", &self.description)
+            .finish()
+    }
+}
+
+/// Peaks as the struct array handed to steps
+fn peaks_to_array(peaks: &PeakArrays) -> StructArray {
+    let mut columns: Vec<(Arc<Field>, ArrayRef)> = vec![
+        (
+            Arc::new(Field::new("mz", DataType::Float64, false)),
+            Arc::new(Float64Array::from(peaks.mz.clone())),
+        ),
+        (
+            Arc::new(Field::new("intensity", DataType::Float32, false)),
+            Arc::new(Float32Array::from(peaks.intensity.clone())),
+        ),
+    ];
+    let ion_mobility = match &peaks.ion_mobility {
+        OptionalColumnBuf::AllNull { .. } => None,
+        OptionalColumnBuf::AllPresent(values) => Some(Float64Array::from(values.clone())),
+        OptionalColumnBuf::WithValidity { values, validity } => Some(
+            values
+                .iter()
+                .zip(validity)
+                .map(|(value, valid)| valid.then_some(*value))
+                .collect(),
+        ),
+    };
+    if let Some(ion_mobility) = ion_mobility {
+        columns.push((
+            Arc::new(Field::new("ion_mobility", DataType::Float64, true)),
+            Arc::new(ion_mobility),
+        ));
+    }
+    StructArray::from(columns)
+}
+
+/// Copy of a NUL-terminated string from a registration record
+unsafe fn optional_string(ptr: *const c_char, what: &str) -> Result<Option<String>, PluginError> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map(|value| Some(value.to_string()))
+        .map_err(|_| PluginError::InvalidRegistration(format!("{} is not UTF-8", what)))
+}
+
+unsafe fn required_string(ptr: *const c_char, what: &str) -> Result<String, PluginError> {
+    optional_string(ptr, what)?
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| PluginError::InvalidRegistration(format!("{} is missing", what)))
+}
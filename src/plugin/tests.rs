@@ -0,0 +1,276 @@
+use super::*;
+use crate::ingest::{IngestSpectrum, IngestSpectrumConverter, SpectrumSteps};
+use std::ptr;
+
+/// Step name (NUL-terminated) and function
+type StepRecord = (&'static [u8], Option<MzPeakProcessFn>);
+
+/// Registration record leaked for the duration of the test binary
+fn registration(abi_version: u32, steps: &[StepRecord]) -> &'static MzPeakPlugin {
+    let steps: Vec<MzPeakPluginStep> = steps
+        .iter()
+        .map(|(name, process)| MzPeakPluginStep {
+            name: name.as_ptr().cast(),
+            description: b"test step\0".as_ptr().cast(),
+            process: *process,
+        })
+        .collect();
+    let steps = Box::leak(steps.into_boxed_slice());
+    Box::leak(Box::new(MzPeakPlugin {
+        abi_version,
+        name: b"test-plugin\0".as_ptr().cast(),
+        step_count: steps.len(),
+        steps: steps.as_ptr(),
+    }))
+}
+
+fn test_plugin() -> Result<Plugin, PluginError> {
+    let steps: [StepRecord; 4] = [
+        (b"denoise\0", Some(denoise)),
+        (b"passthrough\0", Some(passthrough)),
+        (b"fail\0", Some(fail)),
+        (b"reverse\0", Some(reverse)),
+    ];
+    unsafe { Plugin::from_registration(registration(MZPEAK_PLUGIN_ABI_VERSION, &steps)) }
+}
+
+/// Take ownership of the input peaks, as a Rust plugin would
+unsafe fn import_input(
+    input: *mut FFI_ArrowArray,
+    input_schema: *const FFI_ArrowSchema,
+) -> StructArray {
+    let data = from_ffi(FFI_ArrowArray::from_raw(input), &*input_schema).expect("import input");
+    StructArray::from(data)
+}
+
+unsafe fn export_output(
+    peaks: StructArray,
+    output: *mut FFI_ArrowArray,
+    output_schema: *mut FFI_ArrowSchema,
+) {
+    let (array, schema) = to_ffi(&peaks.to_data()).expect("export output");
+    ptr::write(output, array);
+    ptr::write(output_schema, schema);
+}
+
+/// Drop peaks below 100 counts, or 1000 for MS2 spectra
+unsafe extern "C" fn denoise(
+    info: *const MzPeakSpectrumInfo,
+    input: *mut FFI_ArrowArray,
+    input_schema: *const FFI_ArrowSchema,
+    output: *mut FFI_ArrowArray,
+    output_schema: *mut FFI_ArrowSchema,
+    _error: *mut c_char,
+    _error_len: usize,
+) -> i32 {
+    let threshold = if (*info).ms_level > 1 { 1000.0 } else { 100.0 };
+    let peaks = import_input(input, input_schema);
+    let keep = arrow::compute::kernels::cmp::gt_eq(
+        peaks.column(1).as_primitive::<Float32Type>(),
+        &Float32Array::new_scalar(threshold),
+    )
+    .expect("compare");
+    let kept = arrow::compute::filter_record_batch(&peaks.into(), &keep).expect("filter");
+    export_output(kept.into(), output, output_schema);
+    MZPEAK_PLUGIN_OK
+}
+
+unsafe extern "C" fn passthrough(
+    _info: *const MzPeakSpectrumInfo,
+    _input: *mut FFI_ArrowArray,
+    _input_schema: *const FFI_ArrowSchema,
+    _output: *mut FFI_ArrowArray,
+    _output_schema: *mut FFI_ArrowSchema,
+    _error: *mut c_char,
+    _error_len: usize,
+) -> i32 {
+    MZPEAK_PLUGIN_OK
+}
+
+unsafe extern "C" fn fail(
+    _info: *const MzPeakSpectrumInfo,
+    _input: *mut FFI_ArrowArray,
+    _input_schema: *const FFI_ArrowSchema,
+    _output: *mut FFI_ArrowArray,
+    _output_schema: *mut FFI_ArrowSchema,
+    error: *mut c_char,
+    error_len: usize,
+) -> i32 {
+    let message = b"no noise model\0";
+    ptr::copy_nonoverlapping(message.as_ptr().cast(), error, message.len().min(error_len));
+    3
+}
+
+unsafe extern "C" fn reverse(
+    _info: *const MzPeakSpectrumInfo,
+    input: *mut FFI_ArrowArray,
+    input_schema: *const FFI_ArrowSchema,
+    output: *mut FFI_ArrowArray,
+    output_schema: *mut FFI_ArrowSchema,
+    _error: *mut c_char,
+    _error_len: usize,
+) -> i32 {
+    let peaks = import_input(input, input_schema);
+    let indices = arrow::array::UInt32Array::from_iter_values((0..peaks.len() as u32).rev());
+    let reversed = arrow::compute::take(&peaks, &indices, None).expect("take");
+    export_output(reversed.as_struct().clone(), output, output_schema);
+    MZPEAK_PLUGIN_OK
+}
+
+fn spectrum() -> SpectrumArrays {
+    let mut peaks = PeakArrays::new(
+        vec![100.0, 200.0, 300.0, 400.0],
+        vec![50.0, 500.0, 5.0, 5000.0],
+    );
+    peaks.ion_mobility = OptionalColumnBuf::WithValidity {
+        values: vec![0.8, 0.9, 1.0, 1.1],
+        validity: vec![true, false, true, true],
+    };
+    SpectrumArrays::new_ms1(0, 1, 12.5, 1, peaks)
+}
+
+fn ms2_ingest() -> IngestSpectrum {
+    IngestSpectrum {
+        spectrum_id: 0,
+        scan_number: 1,
+        ms_level: 2,
+        retention_time: 30.0,
+        polarity: 1,
+        precursor_mz: Some(450.0),
+        precursor_charge: Some(2),
+        precursor_intensity: None,
+        isolation_window_lower: None,
+        isolation_window_upper: None,
+        collision_energy: None,
+        total_ion_current: Some(5555.0),
+        base_peak_mz: Some(400.0),
+        base_peak_intensity: Some(5000.0),
+        injection_time: None,
+        pixel_x: None,
+        pixel_y: None,
+        pixel_z: None,
+        peaks: PeakArrays::new(vec![100.0, 200.0, 400.0], vec![50.0, 500.0, 5000.0]),
+    }
+}
+
+#[test]
+fn test_registration() -> Result<(), PluginError> {
+    let plugin = test_plugin()?;
+    assert_eq!(plugin.name(), "test-plugin");
+    let names: Vec<&str> = plugin.steps().iter().map(PluginStep::name).collect();
+    assert_eq!(names, ["denoise", "passthrough", "fail", "reverse"]);
+
+    let step = plugin.step("denoise")?;
+    assert_eq!(step.description(), Some("test step"));
+
+    let error = plugin.step("smooth").expect_err("unknown step");
+    assert!(matches!(error, PluginError::UnknownStep { .. }));
+    assert!(error
+        .to_string()
+        .contains("denoise, passthrough, fail, reverse"));
+    Ok(())
+}
+
+#[test]
+fn test_invalid_registrations() {
+    let steps: [StepRecord; 1] = [(b"denoise\0", Some(denoise))];
+    let error =
+        unsafe { Plugin::from_registration(registration(MZPEAK_PLUGIN_ABI_VERSION + 1, &steps)) }
+            .expect_err("newer ABI");
+    assert!(matches!(
+        error,
+        PluginError::AbiMismatch {
+            found: 2,
+            expected: 1
+        }
+    ));
+
+    let twice: [StepRecord; 2] = [
+        (b"denoise\0", Some(denoise)),
+        (b"denoise\0", Some(passthrough)),
+    ];
+    let error =
+        unsafe { Plugin::from_registration(registration(MZPEAK_PLUGIN_ABI_VERSION, &twice)) }
+            .expect_err("duplicate step");
+    assert!(matches!(error, PluginError::InvalidRegistration(_)));
+
+    let missing: [StepRecord; 1] = [(b"denoise\0", None)];
+    let error =
+        unsafe { Plugin::from_registration(registration(MZPEAK_PLUGIN_ABI_VERSION, &missing)) }
+            .expect_err("no process function");
+    assert!(matches!(error, PluginError::InvalidRegistration(_)));
+
+    let error = Plugin::load("/nonexistent/libmissing.so").expect_err("missing library");
+    assert!(matches!(error, PluginError::Load { .. }));
+}
+
+#[test]
+fn test_step_replaces_peaks() -> Result<(), PluginError> {
+    let plugin = test_plugin()?;
+    let mut spectrum = spectrum();
+    plugin.step("denoise")?.process_spectrum(&mut spectrum)?;
+
+    assert_eq!(spectrum.peaks.mz, vec![200.0, 400.0]);
+    assert_eq!(spectrum.peaks.intensity, vec![500.0, 5000.0]);
+    match &spectrum.peaks.ion_mobility {
+        OptionalColumnBuf::WithValidity { values, validity } => {
+            assert_eq!(values[1], 1.1);
+            assert_eq!(validity, &vec![false, true]);
+        }
+        other => panic!("expected ion mobility with validity, got {:?}", other),
+    }
+
+    let mut unchanged = self::spectrum();
+    plugin
+        .step("passthrough")?
+        .process_spectrum(&mut unchanged)?;
+    assert_eq!(unchanged.peaks.mz, self::spectrum().peaks.mz);
+    Ok(())
+}
+
+#[test]
+fn test_step_errors() -> Result<(), PluginError> {
+    let plugin = test_plugin()?;
+    let mut spectrum = spectrum();
+
+    let error = plugin
+        .step("fail")?
+        .process_spectrum(&mut spectrum)
+        .expect_err("failing step");
+    assert_eq!(
+        error.to_string(),
+        "plugin step returned status 3: no noise model"
+    );
+
+    let error = plugin
+        .step("reverse")?
+        .process_spectrum(&mut spectrum)
+        .expect_err("unsorted output");
+    assert!(matches!(error, PluginError::InvalidOutput { .. }));
+    assert_eq!(spectrum.peaks.mz, vec![100.0, 200.0, 300.0, 400.0]);
+    Ok(())
+}
+
+#[test]
+fn test_steps_run_during_ingest() -> Result<(), Box<dyn std::error::Error>> {
+    let plugin = test_plugin()?;
+    let mut steps = SpectrumSteps::default();
+    steps.push(plugin.step("denoise")?);
+    assert_eq!(format!("{:?}", steps), r#"["denoise"]"#);
+
+    let mut converter = IngestSpectrumConverter::new().with_steps(steps);
+    let spectrum = converter.convert(ms2_ingest())?;
+
+    // The MS2 threshold applies and the source statistics are recomputed
+    assert_eq!(spectrum.peaks.mz, vec![400.0]);
+    assert_eq!(spectrum.total_ion_current, Some(5000.0));
+
+    let mut failing = SpectrumSteps::default();
+    failing.push(plugin.step("fail")?);
+    let mut converter = IngestSpectrumConverter::new().with_steps(failing);
+    let error = converter.convert(ms2_ingest()).expect_err("failing step");
+    assert!(error
+        .to_string()
+        .starts_with("processing step 'fail' failed"));
+    Ok(())
+}
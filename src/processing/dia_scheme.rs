@@ -0,0 +1,408 @@
+//! DIA isolation scheme detection and export
+//!
+//! Data-independent acquisition methods cycle through a fixed set of precursor
+//! isolation windows. The scheme is reconstructed from the MS2 spectra of a run
+//! (precursor m/z plus lower/upper isolation offsets) and summarized as:
+//!
+//! - **Window layout**: absolute m/z bounds of each window, its position in the
+//!   acquisition cycle and how often it was acquired
+//! - **Overlaps and gaps**: m/z ranges covered by more than one window, and
+//!   ranges inside the scheme that no window covers
+//! - **Cycle time**: statistics over the time between consecutive acquisitions
+//!   of the same window
+//!
+//! The scheme can be exported as JSON ([`DiaScheme::to_json`]) or as a
+//! standalone SVG drawing ([`DiaScheme::to_svg`]).
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use serde::Serialize;
+
+use super::ProcessingError;
+use crate::reader::MzPeakReader;
+
+/// Resolution (m/z) at which isolation windows are considered identical
+const WINDOW_KEY_RESOLUTION: f64 = 0.01;
+
+/// Minimum mean number of acquisitions per window for a run to be treated as DIA
+const MIN_MEAN_ACQUISITIONS: f64 = 2.0;
+
+/// One isolation window of a DIA scheme
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IsolationWindow {
+    /// Lower m/z bound (target minus lower offset)
+    pub lower_mz: f64,
+    /// Upper m/z bound (target plus upper offset)
+    pub upper_mz: f64,
+    /// Isolation target m/z
+    pub target_mz: f64,
+    /// Position of the window in the acquisition cycle (0-based)
+    pub cycle_position: usize,
+    /// Number of times the window was acquired
+    pub acquisitions: usize,
+}
+
+impl IsolationWindow {
+    /// Window width in m/z
+    pub fn width(&self) -> f64 {
+        self.upper_mz - self.lower_mz
+    }
+}
+
+/// m/z range covered by two windows at once
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WindowOverlap {
+    /// Index (into [`DiaScheme::windows`]) of the window with the lower bound
+    pub lower_window: usize,
+    /// Index (into [`DiaScheme::windows`]) of the window with the higher bound
+    pub upper_window: usize,
+    /// Start of the shared m/z range
+    pub start_mz: f64,
+    /// End of the shared m/z range
+    pub end_mz: f64,
+}
+
+impl WindowOverlap {
+    /// Width of the shared range in m/z
+    pub fn width(&self) -> f64 {
+        self.end_mz - self.start_mz
+    }
+}
+
+/// m/z range inside the scheme that is not covered by any window
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WindowGap {
+    /// Start of the uncovered range
+    pub start_mz: f64,
+    /// End of the uncovered range
+    pub end_mz: f64,
+}
+
+/// Cycle time statistics
+///
+/// Computed from the retention time differences between consecutive
+/// acquisitions of the same window. For staggered schemes this is the period
+/// after which the full pattern repeats.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CycleTimeStats {
+    /// Number of cycles (maximum acquisitions of any single window)
+    pub num_cycles: usize,
+    /// Mean cycle time in seconds
+    pub mean_seconds: Option<f64>,
+    /// Median cycle time in seconds
+    pub median_seconds: Option<f64>,
+    /// Shortest cycle time in seconds
+    pub min_seconds: Option<f64>,
+    /// Longest cycle time in seconds
+    pub max_seconds: Option<f64>,
+}
+
+/// Detected DIA isolation scheme
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiaScheme {
+    /// Isolation windows, sorted by lower m/z bound
+    pub windows: Vec<IsolationWindow>,
+    /// Pairwise window overlaps
+    pub overlaps: Vec<WindowOverlap>,
+    /// Uncovered ranges between the lowest and highest window bound
+    pub gaps: Vec<WindowGap>,
+    /// Cycle time statistics
+    pub cycle_time: CycleTimeStats,
+    /// Number of MS2+ spectra with a usable isolation window
+    pub num_ms2_spectra: usize,
+}
+
+impl DiaScheme {
+    /// Detect the DIA scheme of a run
+    ///
+    /// Returns `Ok(None)` if the run has no MS2 spectra with isolation windows
+    /// or if the windows do not repeat (i.e. the run looks like DDA).
+    pub fn detect(reader: &MzPeakReader) -> Result<Option<Self>, ProcessingError> {
+        let mut acquisitions = Vec::new();
+        for spectrum in reader.iter_spectra_arrays_streaming()? {
+            let spectrum = spectrum?;
+            if spectrum.ms_level < 2 {
+                continue;
+            }
+            if let (Some(target), Some(lower), Some(upper)) = (
+                spectrum.precursor_mz,
+                spectrum.isolation_window_lower,
+                spectrum.isolation_window_upper,
+            ) {
+                acquisitions.push((target, lower, upper, spectrum.retention_time));
+            }
+        }
+        Ok(Self::from_acquisitions(acquisitions))
+    }
+
+    /// Build a scheme from MS2 acquisitions in acquisition order
+    ///
+    /// Each item is `(target_mz, lower_offset, upper_offset, retention_time)`
+    /// with offsets as stored in the isolation window columns.
+    pub fn from_acquisitions<I>(acquisitions: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = (f64, f32, f32, f32)>,
+    {
+        struct Group {
+            window: IsolationWindow,
+            retention_times: Vec<f64>,
+        }
+
+        let mut groups: Vec<Group> = Vec::new();
+        let mut index: HashMap<(i64, i64), usize> = HashMap::new();
+        let mut num_ms2_spectra = 0;
+
+        for (target, lower_offset, upper_offset, rt) in acquisitions {
+            let lower_mz = target - lower_offset as f64;
+            let upper_mz = target + upper_offset as f64;
+            if !(upper_mz - lower_mz).is_finite() || upper_mz <= lower_mz {
+                continue;
+            }
+            num_ms2_spectra += 1;
+
+            let key = (
+                (lower_mz / WINDOW_KEY_RESOLUTION).round() as i64,
+                (upper_mz / WINDOW_KEY_RESOLUTION).round() as i64,
+            );
+            let position = *index.entry(key).or_insert_with(|| {
+                groups.push(Group {
+                    window: IsolationWindow {
+                        lower_mz,
+                        upper_mz,
+                        target_mz: target,
+                        cycle_position: groups.len(),
+                        acquisitions: 0,
+                    },
+                    retention_times: Vec::new(),
+                });
+                groups.len() - 1
+            });
+            let group = &mut groups[position];
+            group.window.acquisitions += 1;
+            group.retention_times.push(rt as f64);
+        }
+
+        if groups.is_empty()
+            || (num_ms2_spectra as f64 / groups.len() as f64) < MIN_MEAN_ACQUISITIONS
+        {
+            return None;
+        }
+
+        let mut cycle_times = Vec::new();
+        let mut num_cycles = 0;
+        for group in &mut groups {
+            group.retention_times.sort_by(|a, b| a.total_cmp(b));
+            cycle_times.extend(group.retention_times.windows(2).map(|w| w[1] - w[0]));
+            num_cycles = num_cycles.max(group.window.acquisitions);
+        }
+
+        let mut windows: Vec<IsolationWindow> = groups.into_iter().map(|g| g.window).collect();
+        windows.sort_by(|a, b| {
+            a.lower_mz
+                .total_cmp(&b.lower_mz)
+                .then(a.upper_mz.total_cmp(&b.upper_mz))
+        });
+
+        Some(Self {
+            overlaps: find_overlaps(&windows),
+            gaps: find_gaps(&windows),
+            cycle_time: cycle_time_stats(num_cycles, cycle_times),
+            windows,
+            num_ms2_spectra,
+        })
+    }
+
+    /// Lowest and highest m/z covered by the scheme
+    pub fn mz_range(&self) -> Option<(f64, f64)> {
+        let lower = self.windows.first()?.lower_mz;
+        let upper = self
+            .windows
+            .iter()
+            .map(|w| w.upper_mz)
+            .fold(f64::NEG_INFINITY, f64::max);
+        Some((lower, upper))
+    }
+
+    /// Serialize the scheme as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String, ProcessingError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Render the scheme as a standalone SVG document
+    ///
+    /// Windows are drawn as bars on an m/z axis, one row per cycle position, so
+    /// the acquisition order reads top to bottom. Overlapping ranges are shaded
+    /// across the full plot height.
+    pub fn to_svg(&self) -> String {
+        const WIDTH: f64 = 960.0;
+        const MARGIN_X: f64 = 50.0;
+        const MARGIN_TOP: f64 = 40.0;
+        const MARGIN_BOTTOM: f64 = 40.0;
+
+        let (min_mz, max_mz) = self.mz_range().unwrap_or((0.0, 1.0));
+        let span = (max_mz - min_mz).max(f64::EPSILON);
+        let plot_width = WIDTH - 2.0 * MARGIN_X;
+        let x = |mz: f64| MARGIN_X + (mz - min_mz) / span * plot_width;
+
+        let rows = self.windows.len().max(1);
+        let row_height = (600.0 / rows as f64).clamp(2.0, 14.0);
+        let plot_height = rows as f64 * row_height;
+        let height = MARGIN_TOP + plot_height + MARGIN_BOTTOM;
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{height:.0}" viewBox="0 0 {WIDTH} {height:.0}" font-family="sans-serif" font-size="11">"#
+        );
+        let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+        let median = self
+            .cycle_time
+            .median_seconds
+            .map(|s| format!("{:.3} s", s))
+            .unwrap_or_else(|| "n/a".to_string());
+        let _ = writeln!(
+            svg,
+            r#"<text x="{MARGIN_X}" y="20" font-size="13">DIA scheme: {} windows, {} overlaps, {} gaps, median cycle time {}</text>"#,
+            self.windows.len(),
+            self.overlaps.len(),
+            self.gaps.len(),
+            median
+        );
+
+        for overlap in &self.overlaps {
+            let _ = writeln!(
+                svg,
+                r##"<rect x="{:.2}" y="{MARGIN_TOP}" width="{:.2}" height="{plot_height:.2}" fill="#d62728" fill-opacity="0.2"/>"##,
+                x(overlap.start_mz),
+                (x(overlap.end_mz) - x(overlap.start_mz)).max(0.5)
+            );
+        }
+
+        for window in &self.windows {
+            let y = MARGIN_TOP + window.cycle_position as f64 * row_height;
+            let _ = writeln!(
+                svg,
+                r##"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" fill="#1f77b4" fill-opacity="0.7"><title>{:.4}-{:.4} m/z (position {}, {} acquisitions)</title></rect>"##,
+                x(window.lower_mz),
+                y,
+                (x(window.upper_mz) - x(window.lower_mz)).max(0.5),
+                (row_height - 1.0).max(1.0),
+                window.lower_mz,
+                window.upper_mz,
+                window.cycle_position,
+                window.acquisitions
+            );
+        }
+
+        let axis_y = MARGIN_TOP + plot_height + 5.0;
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{MARGIN_X}" y1="{axis_y:.2}" x2="{:.2}" y2="{axis_y:.2}" stroke="black"/>"#,
+            WIDTH - MARGIN_X
+        );
+        let step = tick_step(span);
+        let decimals = if step >= 1.0 { 0 } else { 2 };
+        let first_tick = (min_mz / step).ceil() as i64;
+        let last_tick = (max_mz / step).floor() as i64;
+        for k in first_tick..=last_tick {
+            let tick = k as f64 * step;
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{0:.2}" y1="{axis_y:.2}" x2="{0:.2}" y2="{1:.2}" stroke="black"/><text x="{0:.2}" y="{2:.2}" text-anchor="middle">{3:.4$}</text>"#,
+                x(tick),
+                axis_y + 4.0,
+                axis_y + 16.0,
+                tick,
+                decimals
+            );
+        }
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.2}" y="{:.2}" text-anchor="middle">m/z</text>"#,
+            WIDTH / 2.0,
+            axis_y + 30.0
+        );
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// Find all pairwise overlaps between windows sorted by lower bound
+fn find_overlaps(windows: &[IsolationWindow]) -> Vec<WindowOverlap> {
+    let mut overlaps = Vec::new();
+    for (i, a) in windows.iter().enumerate() {
+        for (j, b) in windows.iter().enumerate().skip(i + 1) {
+            if b.lower_mz >= a.upper_mz - WINDOW_KEY_RESOLUTION / 2.0 {
+                break;
+            }
+            let end_mz = a.upper_mz.min(b.upper_mz);
+            overlaps.push(WindowOverlap {
+                lower_window: i,
+                upper_window: j,
+                start_mz: b.lower_mz,
+                end_mz,
+            });
+        }
+    }
+    overlaps
+}
+
+/// Find uncovered ranges between windows sorted by lower bound
+fn find_gaps(windows: &[IsolationWindow]) -> Vec<WindowGap> {
+    let mut gaps = Vec::new();
+    let Some(first) = windows.first() else {
+        return gaps;
+    };
+    let mut covered_to = first.upper_mz;
+    for window in &windows[1..] {
+        if window.lower_mz > covered_to + WINDOW_KEY_RESOLUTION / 2.0 {
+            gaps.push(WindowGap {
+                start_mz: covered_to,
+                end_mz: window.lower_mz,
+            });
+        }
+        covered_to = covered_to.max(window.upper_mz);
+    }
+    gaps
+}
+
+fn cycle_time_stats(num_cycles: usize, mut cycle_times: Vec<f64>) -> CycleTimeStats {
+    if cycle_times.is_empty() {
+        return CycleTimeStats {
+            num_cycles,
+            ..Default::default()
+        };
+    }
+    cycle_times.sort_by(|a, b| a.total_cmp(b));
+    let n = cycle_times.len();
+    let median = if n % 2 == 0 {
+        (cycle_times[n / 2 - 1] + cycle_times[n / 2]) / 2.0
+    } else {
+        cycle_times[n / 2]
+    };
+    CycleTimeStats {
+        num_cycles,
+        mean_seconds: Some(cycle_times.iter().sum::<f64>() / n as f64),
+        median_seconds: Some(median),
+        min_seconds: Some(cycle_times[0]),
+        max_seconds: Some(cycle_times[n - 1]),
+    }
+}
+
+/// Pick a 1/2/5 x 10^k axis tick step giving roughly ten ticks
+fn tick_step(span: f64) -> f64 {
+    let raw = span / 10.0;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let normalized = raw / magnitude;
+    let nice = if normalized <= 1.0 {
+        1.0
+    } else if normalized <= 2.0 {
+        2.0
+    } else if normalized <= 5.0 {
+        5.0
+    } else {
+        10.0
+    };
+    nice * magnitude
+}
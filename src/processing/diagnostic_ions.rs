@@ -0,0 +1,321 @@
+//! Diagnostic ion and neutral loss scanning
+//!
+//! Flags MS2 spectra that contain characteristic fragment ions (e.g. glycan
+//! oxonium ions) or precursor neutral losses (e.g. the 98 Da phosphoric acid
+//! loss of phosphopeptides). The fraction of flagged spectra is a quick QC
+//! readout for PTM enrichment efficiency.
+//!
+//! Hits are stored as a long annotations table (`annotations.parquet`) with one
+//! row per spectrum and matched target.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use mzpeak::processing::{DiagnosticTarget, MassTolerance};
+//! use mzpeak::reader::MzPeakReader;
+//!
+//! let reader = MzPeakReader::open("data.mzpeak")?;
+//! let mut targets = vec![DiagnosticTarget::phospho_neutral_loss()];
+//! targets.extend(DiagnosticTarget::oxonium_ions());
+//!
+//! let scan = reader.scan_diagnostic_ions(&targets, MassTolerance::Ppm(20.0))?;
+//! for (name, spectra) in scan.flagged_spectra_per_target() {
+//!     println!("{}: {:.1}% of MS2", name, 100.0 * spectra as f64 / scan.num_ms2_spectra as f64);
+//! }
+//! scan.write_parquet("annotations.parquet")?;
+//! # Ok::<(), mzpeak::processing::ProcessingError>(())
+//! ```
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Float32Array, Float64Array, Int64Array, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+
+use super::ProcessingError;
+use crate::reader::MzPeakReader;
+
+/// Default file name for the annotations table
+pub const ANNOTATIONS_FILE_NAME: &str = "annotations.parquet";
+
+/// Charge states tried for neutral losses when the precursor charge is unknown
+const FALLBACK_CHARGES: [i16; 3] = [1, 2, 3];
+
+/// Mass tolerance for peak matching
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MassTolerance {
+    /// Relative tolerance in parts per million
+    Ppm(f64),
+    /// Absolute tolerance in Da (m/z)
+    Da(f64),
+}
+
+impl MassTolerance {
+    /// Half-width of the match window around `mz`
+    pub fn window(&self, mz: f64) -> f64 {
+        match *self {
+            MassTolerance::Ppm(ppm) => mz * ppm * 1e-6,
+            MassTolerance::Da(da) => da,
+        }
+    }
+}
+
+/// Fragment ion or neutral loss to look for
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiagnosticTarget {
+    /// Fragment ion at a fixed m/z
+    FragmentIon {
+        /// Target name stored in the annotations table
+        name: String,
+        /// Fragment m/z
+        mz: f64,
+    },
+    /// Loss of a neutral fragment from the precursor
+    NeutralLoss {
+        /// Target name stored in the annotations table
+        name: String,
+        /// Neutral mass lost (Da)
+        mass: f64,
+    },
+}
+
+impl DiagnosticTarget {
+    /// Fragment ion target
+    pub fn fragment(name: impl Into<String>, mz: f64) -> Self {
+        DiagnosticTarget::FragmentIon {
+            name: name.into(),
+            mz,
+        }
+    }
+
+    /// Neutral loss target
+    pub fn neutral_loss(name: impl Into<String>, mass: f64) -> Self {
+        DiagnosticTarget::NeutralLoss {
+            name: name.into(),
+            mass,
+        }
+    }
+
+    /// Loss of phosphoric acid (H3PO4, 97.9769 Da) from phosphopeptides
+    pub fn phospho_neutral_loss() -> Self {
+        Self::neutral_loss("H3PO4 loss", 97.976_896)
+    }
+
+    /// Common glycopeptide oxonium ions
+    pub fn oxonium_ions() -> Vec<Self> {
+        vec![
+            Self::fragment("HexNAc-C2H6O3", 126.055_039),
+            Self::fragment("HexNAc-CH6O3", 138.054_946),
+            Self::fragment("HexNAc-C2H4O2", 144.065_511),
+            Self::fragment("Hex", 163.060_101),
+            Self::fragment("HexNAc-2H2O", 168.065_511),
+            Self::fragment("HexNAc-H2O", 186.076_076),
+            Self::fragment("HexNAc", 204.086_640),
+            Self::fragment("NeuAc-H2O", 274.092_127),
+            Self::fragment("NeuAc", 292.102_692),
+            Self::fragment("HexHexNAc", 366.139_465),
+        ]
+    }
+
+    /// Target name
+    pub fn name(&self) -> &str {
+        match self {
+            DiagnosticTarget::FragmentIon { name, .. } => name,
+            DiagnosticTarget::NeutralLoss { name, .. } => name,
+        }
+    }
+
+    /// Target kind as stored in the annotations table
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DiagnosticTarget::FragmentIon { .. } => "fragment_ion",
+            DiagnosticTarget::NeutralLoss { .. } => "neutral_loss",
+        }
+    }
+
+    /// Expected m/z values of the target in a spectrum
+    fn expected_mzs(&self, precursor_mz: Option<f64>, precursor_charge: Option<i16>) -> Vec<f64> {
+        match self {
+            DiagnosticTarget::FragmentIon { mz, .. } => vec![*mz],
+            DiagnosticTarget::NeutralLoss { mass, .. } => {
+                let Some(precursor_mz) = precursor_mz else {
+                    return Vec::new();
+                };
+                let charges = match precursor_charge {
+                    Some(z) if z > 0 => vec![z],
+                    _ => FALLBACK_CHARGES.to_vec(),
+                };
+                charges
+                    .into_iter()
+                    .map(|z| precursor_mz - mass / z as f64)
+                    .collect()
+            }
+        }
+    }
+}
+
+/// One diagnostic ion match
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiagnosticHit {
+    /// Spectrum ID
+    pub spectrum_id: i64,
+    /// Retention time (seconds)
+    pub retention_time: f32,
+    /// Name of the matched target
+    pub target: String,
+    /// Target kind (`fragment_ion` or `neutral_loss`)
+    pub kind: &'static str,
+    /// Expected m/z
+    pub expected_mz: f64,
+    /// Observed m/z of the most intense matching peak
+    pub observed_mz: f64,
+    /// Intensity of the matching peak
+    pub intensity: f32,
+    /// Intensity relative to the spectrum's base peak
+    pub relative_intensity: f32,
+}
+
+/// Result of a diagnostic ion scan
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiagnosticIonScan {
+    /// Number of MS2 spectra scanned
+    pub num_ms2_spectra: usize,
+    /// Matches, in spectrum order
+    pub hits: Vec<DiagnosticHit>,
+}
+
+impl MzPeakReader {
+    /// Flag MS2 spectra containing diagnostic fragment ions or neutral losses
+    ///
+    /// Neutral losses are matched at `precursor_mz - mass / z`; when the
+    /// precursor charge is unknown, charges 1-3 are tried.
+    pub fn scan_diagnostic_ions(
+        &self,
+        targets: &[DiagnosticTarget],
+        tolerance: MassTolerance,
+    ) -> Result<DiagnosticIonScan, ProcessingError> {
+        let mut scan = DiagnosticIonScan::default();
+        for spectrum in self.iter_spectra_arrays_streaming()? {
+            let spectrum = spectrum?;
+            if spectrum.ms_level != 2 {
+                continue;
+            }
+            scan.num_ms2_spectra += 1;
+
+            let mz_arrays = spectrum.mz_arrays()?;
+            let intensity_arrays = spectrum.intensity_arrays()?;
+            let peaks = || {
+                mz_arrays.iter().zip(&intensity_arrays).flat_map(|(mzs, intensities)| {
+                    mzs.values()
+                        .iter()
+                        .copied()
+                        .zip(intensities.values().iter().copied())
+                })
+            };
+            let base_peak = peaks().map(|(_, i)| i).fold(0.0f32, f32::max);
+
+            for target in targets {
+                let expected = target.expected_mzs(spectrum.precursor_mz, spectrum.precursor_charge);
+                let best = expected
+                    .iter()
+                    .flat_map(|&expected_mz| {
+                        let window = tolerance.window(expected_mz);
+                        peaks()
+                            .filter(move |&(mz, _)| (mz - expected_mz).abs() <= window)
+                            .map(move |(mz, intensity)| (expected_mz, mz, intensity))
+                    })
+                    .max_by(|a, b| a.2.total_cmp(&b.2));
+                if let Some((expected_mz, observed_mz, intensity)) = best {
+                    scan.hits.push(DiagnosticHit {
+                        spectrum_id: spectrum.spectrum_id,
+                        retention_time: spectrum.retention_time,
+                        target: target.name().to_string(),
+                        kind: target.kind(),
+                        expected_mz,
+                        observed_mz,
+                        intensity,
+                        relative_intensity: if base_peak > 0.0 {
+                            intensity / base_peak
+                        } else {
+                            0.0
+                        },
+                    });
+                }
+            }
+        }
+        Ok(scan)
+    }
+}
+
+impl DiagnosticIonScan {
+    /// IDs of spectra with at least one hit
+    pub fn flagged_spectrum_ids(&self) -> Vec<i64> {
+        let mut seen = HashSet::new();
+        self.hits
+            .iter()
+            .filter(|hit| seen.insert(hit.spectrum_id))
+            .map(|hit| hit.spectrum_id)
+            .collect()
+    }
+
+    /// Number of flagged spectra per target name
+    pub fn flagged_spectra_per_target(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for hit in &self.hits {
+            *counts.entry(hit.target.as_str()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Arrow schema of the annotations table
+    pub fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("spectrum_id", DataType::Int64, false),
+            Field::new("retention_time", DataType::Float32, false),
+            Field::new("target", DataType::Utf8, false),
+            Field::new("kind", DataType::Utf8, false),
+            Field::new("expected_mz", DataType::Float64, false),
+            Field::new("observed_mz", DataType::Float64, false),
+            Field::new("intensity", DataType::Float32, false),
+            Field::new("relative_intensity", DataType::Float32, false),
+        ])
+    }
+
+    /// Convert the hits to an Arrow record batch
+    pub fn to_record_batch(&self) -> Result<RecordBatch, ProcessingError> {
+        let hits = &self.hits;
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from_iter_values(hits.iter().map(|h| h.spectrum_id))),
+            Arc::new(Float32Array::from_iter_values(
+                hits.iter().map(|h| h.retention_time),
+            )),
+            Arc::new(StringArray::from_iter_values(hits.iter().map(|h| &h.target))),
+            Arc::new(StringArray::from_iter_values(hits.iter().map(|h| h.kind))),
+            Arc::new(Float64Array::from_iter_values(hits.iter().map(|h| h.expected_mz))),
+            Arc::new(Float64Array::from_iter_values(hits.iter().map(|h| h.observed_mz))),
+            Arc::new(Float32Array::from_iter_values(hits.iter().map(|h| h.intensity))),
+            Arc::new(Float32Array::from_iter_values(
+                hits.iter().map(|h| h.relative_intensity),
+            )),
+        ];
+        Ok(RecordBatch::try_new(Arc::new(Self::schema()), columns)?)
+    }
+
+    /// Write the hits as a Parquet file (conventionally `annotations.parquet`)
+    pub fn write_parquet<P: AsRef<Path>>(&self, path: P) -> Result<(), ProcessingError> {
+        let batch = self.to_record_batch()?;
+        let props = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build();
+        let mut writer = ArrowWriter::try_new(File::create(path)?, batch.schema(), Some(props))?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+}
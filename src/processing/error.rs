@@ -0,0 +1,29 @@
+use crate::reader::ReaderError;
+
+/// Errors that can occur during data processing
+#[derive(Debug, thiserror::Error)]
+pub enum ProcessingError {
+    /// I/O error
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    /// Error from the Arrow library during array operations
+    #[error("Arrow error: {0}")]
+    ArrowError(#[from] arrow::error::ArrowError),
+
+    /// Error from the Parquet library during file writing
+    #[error("Parquet error: {0}")]
+    ParquetError(#[from] parquet::errors::ParquetError),
+
+    /// Error reading the source file
+    #[error("Reader error: {0}")]
+    ReaderError(#[from] ReaderError),
+
+    /// JSON serialization error
+    #[error("JSON error: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    /// Invalid configuration or input
+    #[error("Invalid data: {0}")]
+    InvalidData(String),
+}
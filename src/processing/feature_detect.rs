@@ -0,0 +1,621 @@
+//! MS1 feature detection
+//!
+//! A small centroid-tracing detector over MS1 spectra:
+//!
+//! 1. **Trace building**: centroids of consecutive MS1 scans are linked into
+//!    mass traces when they fall within a ppm tolerance of a trace's
+//!    intensity-weighted m/z; traces may skip a configurable number of scans.
+//!    Closed traces are split at chromatographic valleys so that co-eluting
+//!    isomers become separate traces.
+//! 2. **Isotope grouping**: traces are visited from most to least intense and
+//!    co-eluting traces at `n * 1.00336 / z` m/z above them are attached as
+//!    isotopes when their intensity ratio is plausible for an averagine
+//!    peptide of that mass. If a plausible lighter isotope exists, the
+//!    monoisotopic trace is moved down accordingly.
+//! 3. **Charge assignment**: the charge state explaining the longest isotope
+//!    series wins (ties prefer the higher charge).
+//!
+//! Input spectra are expected to be centroided. Results can be stored as a
+//! features table (`features.parquet`) via [`FeatureTable::write_parquet`].
+
+use std::fs::File;
+use std::ops::{Range, RangeInclusive};
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Float32Array, Float64Array, Int16Array, Int32Array, Int64Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+
+pub use super::isotopes::{C13_C12_MASS_DIFF, PROTON_MASS};
+
+use super::isotopes::averagine_isotope_ratio;
+use super::ProcessingError;
+use crate::reader::MzPeakReader;
+
+/// Default file name for the features table
+pub const FEATURES_FILE_NAME: &str = "features.parquet";
+
+/// Column names for the features table
+pub mod feature_columns {
+    /// Feature index
+    pub const FEATURE_ID: &str = "feature_id";
+    /// Monoisotopic m/z
+    pub const MZ: &str = "mz";
+    /// Assigned charge state (null if unassigned)
+    pub const CHARGE: &str = "charge";
+    /// Retention time of the monoisotopic apex (seconds)
+    pub const RT_APEX: &str = "rt_apex";
+    /// Start of the monoisotopic trace (seconds)
+    pub const RT_START: &str = "rt_start";
+    /// End of the monoisotopic trace (seconds)
+    pub const RT_END: &str = "rt_end";
+    /// Apex intensity of the monoisotopic trace
+    pub const APEX_INTENSITY: &str = "apex_intensity";
+    /// Summed area of all isotope traces
+    pub const AREA: &str = "area";
+    /// Number of isotope traces, including the monoisotopic one
+    pub const NUM_ISOTOPES: &str = "num_isotopes";
+    /// Number of scans in the monoisotopic trace
+    pub const NUM_SCANS: &str = "num_scans";
+}
+
+/// Configuration for MS1 feature detection
+#[derive(Debug, Clone)]
+pub struct FeatureDetectionConfig {
+    /// m/z tolerance for linking centroids into traces and matching isotopes (ppm)
+    pub mass_tolerance_ppm: f64,
+
+    /// Centroids below this intensity are ignored
+    pub min_intensity: f32,
+
+    /// Minimum number of scans for a trace to be kept
+    pub min_scans: usize,
+
+    /// Number of consecutive MS1 scans a trace may miss before it is closed
+    pub max_missing_scans: usize,
+
+    /// Charge states considered during isotope grouping
+    pub charges: RangeInclusive<i16>,
+
+    /// Maximum number of isotope traces attached to a monoisotopic trace
+    pub max_isotopes: usize,
+
+    /// Split traces at valleys below this fraction of the smaller neighboring
+    /// maximum (0 = never split)
+    pub valley_ratio: f32,
+
+    /// Maximum factor between observed and averagine-expected isotope
+    /// intensity ratios
+    pub isotope_ratio_tolerance: f64,
+}
+
+impl Default for FeatureDetectionConfig {
+    fn default() -> Self {
+        Self {
+            mass_tolerance_ppm: 10.0,
+            min_intensity: 0.0,
+            min_scans: 3,
+            max_missing_scans: 1,
+            charges: 1..=6,
+            max_isotopes: 4,
+            valley_ratio: 0.5,
+            isotope_ratio_tolerance: 3.0,
+        }
+    }
+}
+
+/// Chromatographic trace of a single m/z over consecutive MS1 scans
+#[derive(Debug, Clone, PartialEq)]
+pub struct MassTrace {
+    /// Intensity-weighted mean m/z
+    pub mz: f64,
+    /// Retention time of the first point (seconds)
+    pub rt_start: f32,
+    /// Retention time of the last point (seconds)
+    pub rt_end: f32,
+    /// Retention time of the most intense point (seconds)
+    pub rt_apex: f32,
+    /// Intensity of the most intense point
+    pub apex_intensity: f32,
+    /// Trapezoidal area under the trace (intensity x seconds)
+    pub area: f64,
+    /// Number of points in the trace
+    pub num_scans: usize,
+}
+
+/// Detected MS1 feature (monoisotopic trace plus its isotopes)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Feature {
+    /// Feature index (ordered by apex retention time, then m/z)
+    pub feature_id: usize,
+    /// Monoisotopic m/z
+    pub mz: f64,
+    /// Assigned charge state (None if no isotope was found)
+    pub charge: Option<i16>,
+    /// Retention time of the monoisotopic apex (seconds)
+    pub rt_apex: f32,
+    /// Start of the monoisotopic trace (seconds)
+    pub rt_start: f32,
+    /// End of the monoisotopic trace (seconds)
+    pub rt_end: f32,
+    /// Apex intensity of the monoisotopic trace
+    pub apex_intensity: f32,
+    /// Summed area of all isotope traces
+    pub area: f64,
+    /// Number of isotope traces, including the monoisotopic one
+    pub num_isotopes: usize,
+    /// Number of scans in the monoisotopic trace
+    pub num_scans: usize,
+}
+
+/// Detect MS1 features in a run
+pub fn detect_features(
+    reader: &MzPeakReader,
+    config: &FeatureDetectionConfig,
+) -> Result<Vec<Feature>, ProcessingError> {
+    let mut builder = TraceBuilder::new(config.clone());
+    for spectrum in reader.iter_spectra_arrays_streaming()? {
+        let spectrum = spectrum?;
+        if spectrum.ms_level != 1 {
+            continue;
+        }
+        let mz_arrays = spectrum.mz_arrays()?;
+        let intensity_arrays = spectrum.intensity_arrays()?;
+        let peaks = mz_arrays
+            .iter()
+            .zip(&intensity_arrays)
+            .flat_map(|(mzs, intensities)| {
+                mzs.values()
+                    .iter()
+                    .copied()
+                    .zip(intensities.values().iter().copied())
+            });
+        builder.push_scan(spectrum.retention_time, peaks);
+    }
+    Ok(group_isotopes(builder.finish(), config))
+}
+
+/// Trace that can still be extended
+struct OpenTrace {
+    points: Vec<(f32, f64, f32)>,
+    weighted_mz: f64,
+    total_intensity: f64,
+    last_scan: usize,
+}
+
+impl OpenTrace {
+    fn new(scan: usize, rt: f32, mz: f64, intensity: f32) -> Self {
+        let mut trace = Self {
+            points: Vec::new(),
+            weighted_mz: 0.0,
+            total_intensity: 0.0,
+            last_scan: scan,
+        };
+        trace.push(scan, rt, mz, intensity);
+        trace
+    }
+
+    fn push(&mut self, scan: usize, rt: f32, mz: f64, intensity: f32) {
+        self.points.push((rt, mz, intensity));
+        self.weighted_mz += mz * intensity as f64;
+        self.total_intensity += intensity as f64;
+        self.last_scan = scan;
+    }
+
+    fn mz(&self) -> f64 {
+        if self.total_intensity > 0.0 {
+            self.weighted_mz / self.total_intensity
+        } else {
+            self.points.iter().map(|p| p.1).sum::<f64>() / self.points.len() as f64
+        }
+    }
+}
+
+/// Summarize a run of trace points into a mass trace
+fn summarize_points(points: &[(f32, f64, f32)]) -> MassTrace {
+    let total_intensity: f64 = points.iter().map(|p| p.2 as f64).sum();
+    let mz = if total_intensity > 0.0 {
+        points.iter().map(|p| p.1 * p.2 as f64).sum::<f64>() / total_intensity
+    } else {
+        points.iter().map(|p| p.1).sum::<f64>() / points.len() as f64
+    };
+    let (rt_apex, apex_intensity) = points
+        .iter()
+        .map(|&(rt, _, intensity)| (rt, intensity))
+        .fold((0.0f32, f32::NEG_INFINITY), |best, point| {
+            if point.1 > best.1 {
+                point
+            } else {
+                best
+            }
+        });
+    let area = points
+        .windows(2)
+        .map(|w| (w[1].0 - w[0].0) as f64 * (w[0].2 + w[1].2) as f64 / 2.0)
+        .sum();
+    MassTrace {
+        mz,
+        rt_start: points[0].0,
+        rt_end: points[points.len() - 1].0,
+        rt_apex,
+        apex_intensity,
+        area,
+        num_scans: points.len(),
+    }
+}
+
+/// Split trace points at chromatographic valleys
+///
+/// Intensities are smoothed with a 3-point moving average; between two
+/// consecutive maxima the trace is cut at the lowest point if it falls below
+/// `valley_ratio` times the smaller of the two maxima.
+fn split_at_valleys(points: &[(f32, f64, f32)], valley_ratio: f32) -> Vec<Range<usize>> {
+    let n = points.len();
+    if valley_ratio <= 0.0 || n < 3 {
+        return std::iter::once(0..n).collect();
+    }
+
+    let smoothed: Vec<f32> = (0..n)
+        .map(|i| {
+            let window = &points[i.saturating_sub(1)..(i + 2).min(n)];
+            window.iter().map(|p| p.2).sum::<f32>() / window.len() as f32
+        })
+        .collect();
+    let maxima: Vec<usize> = (0..n)
+        .filter(|&i| {
+            (i == 0 || smoothed[i] > smoothed[i - 1])
+                && (i + 1 == n || smoothed[i] >= smoothed[i + 1])
+        })
+        .collect();
+
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut left_peak = maxima.first().map_or(0.0, |&i| smoothed[i]);
+    for pair in maxima.windows(2) {
+        let (left, right) = (pair[0], pair[1]);
+        let valley = (left..=right)
+            .min_by(|&a, &b| smoothed[a].total_cmp(&smoothed[b]))
+            .unwrap_or(left);
+        if smoothed[valley] < valley_ratio * left_peak.min(smoothed[right]) {
+            segments.push(start..valley + 1);
+            start = valley + 1;
+            left_peak = smoothed[right];
+        } else {
+            left_peak = left_peak.max(smoothed[right]);
+        }
+    }
+    segments.push(start..n);
+    segments
+}
+
+/// Incremental mass trace builder fed one MS1 scan at a time
+pub struct TraceBuilder {
+    config: FeatureDetectionConfig,
+    /// Open traces sorted by m/z
+    active: Vec<OpenTrace>,
+    finished: Vec<MassTrace>,
+    scan: usize,
+}
+
+impl TraceBuilder {
+    /// Create a builder
+    pub fn new(config: FeatureDetectionConfig) -> Self {
+        Self {
+            config,
+            active: Vec::new(),
+            finished: Vec::new(),
+            scan: 0,
+        }
+    }
+
+    /// Add the centroids of the next MS1 scan
+    pub fn push_scan<I>(&mut self, retention_time: f32, peaks: I)
+    where
+        I: IntoIterator<Item = (f64, f32)>,
+    {
+        self.scan += 1;
+        let scan = self.scan;
+
+        let mut peaks: Vec<(f64, f32)> = peaks
+            .into_iter()
+            .filter(|&(_, intensity)| intensity > self.config.min_intensity)
+            .collect();
+        // Most intense centroids claim traces first
+        peaks.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let centers: Vec<f64> = self.active.iter().map(OpenTrace::mz).collect();
+        let mut claimed = vec![false; self.active.len()];
+        let mut new_traces = Vec::new();
+
+        for (mz, intensity) in peaks {
+            let tolerance = mz * self.config.mass_tolerance_ppm * 1e-6;
+            let start = centers.partition_point(|&c| c < mz - tolerance);
+            let best = (start..centers.len())
+                .take_while(|&i| centers[i] <= mz + tolerance)
+                .filter(|&i| !claimed[i])
+                .min_by(|&a, &b| (centers[a] - mz).abs().total_cmp(&(centers[b] - mz).abs()));
+            match best {
+                Some(i) => {
+                    claimed[i] = true;
+                    self.active[i].push(scan, retention_time, mz, intensity);
+                }
+                None => new_traces.push(OpenTrace::new(scan, retention_time, mz, intensity)),
+            }
+        }
+
+        let max_missing = self.config.max_missing_scans;
+        let (keep, closed): (Vec<_>, Vec<_>) = std::mem::take(&mut self.active)
+            .into_iter()
+            .partition(|trace| scan - trace.last_scan <= max_missing);
+        self.close(closed);
+
+        self.active = keep;
+        self.active.extend(new_traces);
+        self.active.sort_by(|a, b| a.mz().total_cmp(&b.mz()));
+    }
+
+    /// Close all open traces and return the traces long enough to keep
+    pub fn finish(mut self) -> Vec<MassTrace> {
+        let active = std::mem::take(&mut self.active);
+        self.close(active);
+        self.finished
+    }
+
+    fn close(&mut self, traces: Vec<OpenTrace>) {
+        let min_scans = self.config.min_scans.max(1);
+        for trace in traces {
+            for segment in split_at_valleys(&trace.points, self.config.valley_ratio) {
+                if segment.len() >= min_scans {
+                    self.finished.push(summarize_points(&trace.points[segment]));
+                }
+            }
+        }
+    }
+}
+
+/// Group mass traces into isotope patterns and assign charge states
+pub fn group_isotopes(mut traces: Vec<MassTrace>, config: &FeatureDetectionConfig) -> Vec<Feature> {
+    traces.sort_by(|a, b| a.mz.total_cmp(&b.mz));
+
+    let mut order: Vec<usize> = (0..traces.len()).collect();
+    order.sort_by(|&a, &b| traces[b].apex_intensity.total_cmp(&traces[a].apex_intensity));
+
+    let mut assigned = vec![false; traces.len()];
+    let mut features = Vec::new();
+
+    for mono_index in order {
+        if assigned[mono_index] {
+            continue;
+        }
+        let mut best: Option<(i16, Vec<usize>)> = None;
+        for charge in config.charges.clone().filter(|&z| z > 0) {
+            let chain = isotope_chain(&traces, &assigned, mono_index, charge, config);
+            let better = match &best {
+                None => !chain.is_empty(),
+                Some((_, best_chain)) => chain.len() >= best_chain.len(),
+            };
+            if better {
+                best = Some((charge, chain));
+            }
+        }
+
+        let (mono_index, charge, isotopes) = match best {
+            Some((charge, chain)) => {
+                let (mono_index, chain) =
+                    extend_to_monoisotopic(&traces, &assigned, mono_index, chain, charge, config);
+                (mono_index, Some(charge), chain)
+            }
+            None => (mono_index, None, Vec::new()),
+        };
+        let mono = &traces[mono_index];
+        assigned[mono_index] = true;
+        let mut area = mono.area;
+        for &i in &isotopes {
+            assigned[i] = true;
+            area += traces[i].area;
+        }
+
+        features.push(Feature {
+            feature_id: 0,
+            mz: mono.mz,
+            charge,
+            rt_apex: mono.rt_apex,
+            rt_start: mono.rt_start,
+            rt_end: mono.rt_end,
+            apex_intensity: mono.apex_intensity,
+            area,
+            num_isotopes: isotopes.len() + 1,
+            num_scans: mono.num_scans,
+        });
+    }
+
+    features.sort_by(|a, b| a.rt_apex.total_cmp(&b.rt_apex).then(a.mz.total_cmp(&b.mz)));
+    for (id, feature) in features.iter_mut().enumerate() {
+        feature.feature_id = id;
+    }
+    features
+}
+
+/// Indices of the consecutive isotope traces of `mono_index` at charge `charge`
+fn isotope_chain(
+    traces: &[MassTrace],
+    assigned: &[bool],
+    mono_index: usize,
+    charge: i16,
+    config: &FeatureDetectionConfig,
+) -> Vec<usize> {
+    let mono = &traces[mono_index];
+    let mass = neutral_mass(mono.mz, charge);
+    let mut chain = Vec::new();
+    let mut previous = mono;
+    for n in 1..=config.max_isotopes {
+        let target = mono.mz + n as f64 * C13_C12_MASS_DIFF / charge as f64;
+        let candidate = find_isotope(traces, assigned, target, mono, config, |i| {
+            i != mono_index
+                && plausible_ratio(previous, &traces[i], mass, n, config.isotope_ratio_tolerance)
+        });
+        match candidate {
+            Some(i) => {
+                chain.push(i);
+                previous = &traces[i];
+            }
+            None => break,
+        }
+    }
+    chain
+}
+
+/// Move the monoisotopic trace down while a plausible lighter isotope exists
+///
+/// For heavier peptides the first isotope is more intense than the
+/// monoisotopic peak, so the most intense trace of a pattern is not
+/// necessarily the monoisotopic one.
+fn extend_to_monoisotopic(
+    traces: &[MassTrace],
+    assigned: &[bool],
+    mut mono_index: usize,
+    mut chain: Vec<usize>,
+    charge: i16,
+    config: &FeatureDetectionConfig,
+) -> (usize, Vec<usize>) {
+    while chain.len() < config.max_isotopes {
+        let mono = &traces[mono_index];
+        let target = mono.mz - C13_C12_MASS_DIFF / charge as f64;
+        let mass = neutral_mass(target, charge);
+        let candidate = find_isotope(traces, assigned, target, mono, config, |i| {
+            i != mono_index
+                && !chain.contains(&i)
+                && plausible_ratio(&traces[i], mono, mass, 1, config.isotope_ratio_tolerance)
+        });
+        match candidate {
+            Some(i) => {
+                chain.insert(0, mono_index);
+                mono_index = i;
+            }
+            None => break,
+        }
+    }
+    (mono_index, chain)
+}
+
+/// Most intense unassigned trace near `target` whose apex lies within `reference`
+fn find_isotope(
+    traces: &[MassTrace],
+    assigned: &[bool],
+    target: f64,
+    reference: &MassTrace,
+    config: &FeatureDetectionConfig,
+    accept: impl Fn(usize) -> bool,
+) -> Option<usize> {
+    let tolerance = target * config.mass_tolerance_ppm * 1e-6;
+    let start = traces.partition_point(|t| t.mz < target - tolerance);
+    (start..traces.len())
+        .take_while(|&i| traces[i].mz <= target + tolerance)
+        .filter(|&i| {
+            !assigned[i]
+                && traces[i].rt_apex >= reference.rt_start
+                && traces[i].rt_apex <= reference.rt_end
+                && accept(i)
+        })
+        .max_by(|&a, &b| traces[a].apex_intensity.total_cmp(&traces[b].apex_intensity))
+}
+
+/// Whether `heavier / lighter` is within tolerance of the averagine ratio for isotope `n`
+fn plausible_ratio(
+    lighter: &MassTrace,
+    heavier: &MassTrace,
+    mass: f64,
+    n: usize,
+    tolerance: f64,
+) -> bool {
+    if lighter.apex_intensity <= 0.0 {
+        return false;
+    }
+    let expected = averagine_isotope_ratio(mass, n).max(f64::MIN_POSITIVE);
+    let observed = heavier.apex_intensity as f64 / lighter.apex_intensity as f64;
+    let factor = observed / expected;
+    factor <= tolerance && factor >= 1.0 / tolerance
+}
+
+/// Neutral mass of an ion with the given m/z and charge
+fn neutral_mass(mz: f64, charge: i16) -> f64 {
+    (mz - PROTON_MASS) * charge as f64
+}
+
+/// Detected features as a table, written as `features.parquet`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FeatureTable {
+    /// Detected features ordered by apex retention time
+    pub features: Vec<Feature>,
+}
+
+impl FeatureTable {
+    /// Detect MS1 features in a run
+    pub fn detect(
+        reader: &MzPeakReader,
+        config: &FeatureDetectionConfig,
+    ) -> Result<Self, ProcessingError> {
+        Ok(Self {
+            features: detect_features(reader, config)?,
+        })
+    }
+
+    /// Arrow schema of the features table
+    pub fn schema() -> Schema {
+        use feature_columns::*;
+        Schema::new(vec![
+            Field::new(FEATURE_ID, DataType::Int64, false),
+            Field::new(MZ, DataType::Float64, false),
+            Field::new(CHARGE, DataType::Int16, true),
+            Field::new(RT_APEX, DataType::Float32, false),
+            Field::new(RT_START, DataType::Float32, false),
+            Field::new(RT_END, DataType::Float32, false),
+            Field::new(APEX_INTENSITY, DataType::Float32, false),
+            Field::new(AREA, DataType::Float64, false),
+            Field::new(NUM_ISOTOPES, DataType::Int32, false),
+            Field::new(NUM_SCANS, DataType::Int32, false),
+        ])
+    }
+
+    /// Convert the features to an Arrow record batch
+    pub fn to_record_batch(&self) -> Result<RecordBatch, ProcessingError> {
+        let features = &self.features;
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from_iter_values(
+                features.iter().map(|f| f.feature_id as i64),
+            )),
+            Arc::new(Float64Array::from_iter_values(features.iter().map(|f| f.mz))),
+            Arc::new(Int16Array::from_iter(features.iter().map(|f| f.charge))),
+            Arc::new(Float32Array::from_iter_values(features.iter().map(|f| f.rt_apex))),
+            Arc::new(Float32Array::from_iter_values(features.iter().map(|f| f.rt_start))),
+            Arc::new(Float32Array::from_iter_values(features.iter().map(|f| f.rt_end))),
+            Arc::new(Float32Array::from_iter_values(
+                features.iter().map(|f| f.apex_intensity),
+            )),
+            Arc::new(Float64Array::from_iter_values(features.iter().map(|f| f.area))),
+            Arc::new(Int32Array::from_iter_values(
+                features.iter().map(|f| f.num_isotopes as i32),
+            )),
+            Arc::new(Int32Array::from_iter_values(
+                features.iter().map(|f| f.num_scans as i32),
+            )),
+        ];
+        Ok(RecordBatch::try_new(Arc::new(Self::schema()), columns)?)
+    }
+
+    /// Write the features table as a Parquet file (conventionally `features.parquet`)
+    pub fn write_parquet<P: AsRef<Path>>(&self, path: P) -> Result<(), ProcessingError> {
+        let batch = self.to_record_batch()?;
+        let props = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build();
+        let mut writer = ArrowWriter::try_new(File::create(path)?, batch.schema(), Some(props))?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+}
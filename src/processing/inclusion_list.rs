@@ -0,0 +1,120 @@
+//! Targeted inclusion list generation
+//!
+//! Ranks detected MS1 features by apex intensity and exports the top entries
+//! as a vendor-neutral CSV that can be imported into the acquisition method of
+//! the next run:
+//!
+//! ```text
+//! mz,charge,rt_start_min,rt_end_min,collision_energy
+//! 524.2648,2,12.3500,13.1000,27
+//! ```
+//!
+//! Retention times are written in minutes, the unit used by instrument method
+//! editors. The collision energy column is left empty when not configured.
+
+use std::io::Write;
+use std::ops::RangeInclusive;
+
+use super::feature_detect::Feature;
+use super::ProcessingError;
+
+/// Configuration for inclusion list generation
+#[derive(Debug, Clone)]
+pub struct InclusionListConfig {
+    /// Maximum number of entries (most intense features first)
+    pub top_n: usize,
+
+    /// Charge states to include; features without a charge are skipped
+    pub charges: RangeInclusive<i16>,
+
+    /// Fixed RT window width around the feature apex in seconds
+    /// (None = use the detected feature start/end)
+    pub rt_window_seconds: Option<f32>,
+
+    /// Collision energy written to every entry
+    pub collision_energy: Option<f32>,
+}
+
+impl Default for InclusionListConfig {
+    fn default() -> Self {
+        Self {
+            top_n: 2000,
+            charges: 2..=4,
+            rt_window_seconds: None,
+            collision_energy: None,
+        }
+    }
+}
+
+/// One inclusion list entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct InclusionEntry {
+    /// Precursor m/z
+    pub mz: f64,
+    /// Precursor charge
+    pub charge: i16,
+    /// Start of the RT window (seconds)
+    pub rt_start: f32,
+    /// End of the RT window (seconds)
+    pub rt_end: f32,
+    /// Collision energy
+    pub collision_energy: Option<f32>,
+}
+
+/// Rank features and build the inclusion list
+pub fn build_inclusion_list(
+    features: &[Feature],
+    config: &InclusionListConfig,
+) -> Vec<InclusionEntry> {
+    let mut ranked: Vec<&Feature> = features
+        .iter()
+        .filter(|f| f.charge.is_some_and(|z| config.charges.contains(&z)))
+        .collect();
+    ranked.sort_by(|a, b| b.apex_intensity.total_cmp(&a.apex_intensity));
+
+    ranked
+        .into_iter()
+        .take(config.top_n)
+        .map(|feature| {
+            let (rt_start, rt_end) = match config.rt_window_seconds {
+                Some(width) => (
+                    (feature.rt_apex - width / 2.0).max(0.0),
+                    feature.rt_apex + width / 2.0,
+                ),
+                None => (feature.rt_start, feature.rt_end),
+            };
+            InclusionEntry {
+                mz: feature.mz,
+                charge: feature.charge.unwrap_or_default(),
+                rt_start,
+                rt_end,
+                collision_energy: config.collision_energy,
+            }
+        })
+        .collect()
+}
+
+/// Write inclusion list entries as CSV
+pub fn write_inclusion_list_csv<W: Write>(
+    entries: &[InclusionEntry],
+    mut writer: W,
+) -> Result<(), ProcessingError> {
+    writeln!(writer, "mz,charge,rt_start_min,rt_end_min,collision_energy")?;
+    for entry in entries {
+        let collision_energy = entry
+            .collision_energy
+            .map(|ce| ce.to_string())
+            .unwrap_or_default();
+        writeln!(
+            writer,
+            "{:.4},{},{:.4},{:.4},{}",
+            entry.mz,
+            entry.charge,
+            entry.rt_start / 60.0,
+            entry.rt_end / 60.0,
+            collision_energy
+        )?;
+    }
+    writer.flush()?;
+    Ok(())
+}
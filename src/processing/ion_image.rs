@@ -0,0 +1,173 @@
+//! Ion images of mass spectrometry imaging data
+//!
+//! An ion image holds the summed intensity in an m/z window for every pixel
+//! of the grid. It carries the grid calibration of the file
+//! ([`ImagingMetadata`](crate::metadata::ImagingMetadata)), so pixels map to physical positions and the image
+//! has a physical extent; without a calibration both are `None` and the image
+//! is dimensionless.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use mzpeak::processing::MassTolerance;
+//! use mzpeak::reader::MzPeakReader;
+//!
+//! let reader = MzPeakReader::open("tissue.mzpeak")?;
+//! let image = reader.extract_ion_image(885.5498, MassTolerance::Ppm(5.0))?;
+//! if let Some((width_um, height_um)) = image.extent_um() {
+//!     println!("{}x{} pixels, {:.0}x{:.0} µm", image.width, image.height, width_um, height_um);
+//! }
+//! # Ok::<(), mzpeak::processing::ProcessingError>(())
+//! ```
+
+use super::mz_kernels;
+use super::{MassTolerance, ProcessingError};
+use crate::metadata::AxisOrientation;
+use crate::reader::MzPeakReader;
+
+/// Summed intensity in an m/z window for every pixel of an imaging grid
+#[derive(Debug, Clone, PartialEq)]
+pub struct IonImage {
+    /// Target m/z
+    pub target_mz: f64,
+    /// Lower m/z bound of the extraction window (inclusive)
+    pub lower_mz: f64,
+    /// Upper m/z bound of the extraction window (inclusive)
+    pub upper_mz: f64,
+    /// Pixel x coordinate of the first column
+    pub first_x: i32,
+    /// Pixel y coordinate of the first row
+    pub first_y: i32,
+    /// Number of columns
+    pub width: usize,
+    /// Number of rows
+    pub height: usize,
+    /// Intensities, row-major (`width * height`); pixels without a spectrum
+    /// are zero
+    pub intensities: Vec<f64>,
+    /// Pixel size along X in micrometers
+    pub pixel_size_x_um: Option<f64>,
+    /// Pixel size along Y in micrometers
+    pub pixel_size_y_um: Option<f64>,
+    /// Position of pixel (1, 1) in micrometers
+    pub origin_um: Option<(f64, f64)>,
+    /// Direction of the Y axis when displayed
+    pub axis_orientation: Option<AxisOrientation>,
+}
+
+impl IonImage {
+    /// Intensity at pixel `(x, y)`, `None` outside the image
+    pub fn intensity(&self, x: i32, y: i32) -> Option<f64> {
+        let column = usize::try_from(x.checked_sub(self.first_x)?).ok()?;
+        let row = usize::try_from(y.checked_sub(self.first_y)?).ok()?;
+        if column >= self.width || row >= self.height {
+            return None;
+        }
+        self.intensities.get(row * self.width + column).copied()
+    }
+
+    /// Physical position of pixel `(x, y)` in micrometers
+    ///
+    /// `None` when the file has no pixel sizes.
+    pub fn position_um(&self, x: i32, y: i32) -> Option<(f64, f64)> {
+        let (origin_x, origin_y) = self.origin_um.unwrap_or((0.0, 0.0));
+        Some((
+            origin_x + f64::from(x - 1) * self.pixel_size_x_um?,
+            origin_y + f64::from(y - 1) * self.pixel_size_y_um?,
+        ))
+    }
+
+    /// Physical width and height of the image in micrometers
+    ///
+    /// `None` when the file has no pixel sizes.
+    pub fn extent_um(&self) -> Option<(f64, f64)> {
+        Some((
+            self.width as f64 * self.pixel_size_x_um?,
+            self.height as f64 * self.pixel_size_y_um?,
+        ))
+    }
+}
+
+impl MzPeakReader {
+    /// Extract the ion image of a target m/z from the MS1 spectra
+    ///
+    /// The image spans the bounding box of the pixels with spectra. MS1
+    /// spectra without pixel coordinates are ignored; data without any are
+    /// rejected.
+    pub fn extract_ion_image(
+        &self,
+        target_mz: f64,
+        tolerance: MassTolerance,
+    ) -> Result<IonImage, ProcessingError> {
+        let window = tolerance.window(target_mz);
+        let (lower_mz, upper_mz) = (target_mz - window, target_mz + window);
+
+        let mut pixels = Vec::new();
+        for spectrum in self.iter_spectra_arrays_streaming()? {
+            let spectrum = spectrum?;
+            let (Some(x), Some(y)) = (spectrum.pixel_x, spectrum.pixel_y) else {
+                continue;
+            };
+            if spectrum.ms_level != 1 {
+                continue;
+            }
+            let mz_arrays = spectrum.mz_arrays()?;
+            let intensity_arrays = spectrum.intensity_arrays()?;
+            let intensity: f64 = mz_arrays
+                .iter()
+                .zip(&intensity_arrays)
+                .map(|(mzs, intensities)| {
+                    mz_kernels::intensity_in_range(
+                        mzs.values(),
+                        intensities.values(),
+                        lower_mz,
+                        upper_mz,
+                    )
+                })
+                .sum();
+            pixels.push((x, y, intensity));
+        }
+
+        let no_pixels =
+            || ProcessingError::InvalidData("no MS1 spectra with pixel coordinates".to_string());
+        let (first_x, last_x) = min_max(pixels.iter().map(|p| p.0)).ok_or_else(no_pixels)?;
+        let (first_y, last_y) = min_max(pixels.iter().map(|p| p.1)).ok_or_else(no_pixels)?;
+        let width = (i64::from(last_x) - i64::from(first_x) + 1) as usize;
+        let height = (i64::from(last_y) - i64::from(first_y) + 1) as usize;
+
+        let mut intensities = vec![0.0; width * height];
+        for (x, y, intensity) in pixels {
+            let column = (i64::from(x) - i64::from(first_x)) as usize;
+            let row = (i64::from(y) - i64::from(first_y)) as usize;
+            intensities[row * width + column] += intensity;
+        }
+
+        let imaging = self.imaging_metadata()?.unwrap_or_default();
+        let origin_um = match (imaging.origin_x_um, imaging.origin_y_um) {
+            (Some(x), Some(y)) => Some((x, y)),
+            _ => None,
+        };
+        Ok(IonImage {
+            target_mz,
+            lower_mz,
+            upper_mz,
+            first_x,
+            first_y,
+            width,
+            height,
+            intensities,
+            pixel_size_x_um: imaging.pixel_size_x_um,
+            pixel_size_y_um: imaging.pixel_size_y_um,
+            origin_um,
+            axis_orientation: imaging.axis_orientation,
+        })
+    }
+}
+
+/// Smallest and largest value, `None` for an empty iterator
+fn min_max(values: impl Iterator<Item = i32>) -> Option<(i32, i32)> {
+    values.fold(None, |range, value| match range {
+        None => Some((value, value)),
+        Some((min, max)) => Some((min.min(value), max.max(value))),
+    })
+}
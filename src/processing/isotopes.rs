@@ -0,0 +1,402 @@
+//! Isotope pattern calculator
+//!
+//! Computes the isotope distribution of an elemental formula, or of an
+//! averagine peptide (Senko et al., 1995) when only the mass is known. Peaks
+//! are aggregated by nominal mass shift, so each peak of the distribution
+//! merges all isotopologues with the same number of extra neutrons; its mass
+//! is their abundance-weighted mean.
+//!
+//! Feature detection and precursor correction use the averagine distribution
+//! to judge whether neighbouring peaks can belong to one isotope envelope.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use mzpeak::processing::{Formula, IsotopeDistribution};
+//!
+//! let glucose: Formula = "C6H12O6".parse()?;
+//! let distribution = IsotopeDistribution::for_formula(&glucose, 3);
+//! assert!((distribution.peaks[0].mass - 180.0634).abs() < 1e-3);
+//!
+//! // Unknown peptide of 1500 Da, doubly protonated
+//! let averagine = IsotopeDistribution::averagine(1500.0, 4);
+//! println!("m/z {:?}, relative {:?}", averagine.mz(2), averagine.relative_abundances());
+//! # Ok::<(), mzpeak::processing::ProcessingError>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use super::ProcessingError;
+
+/// Mass difference between the 13C and 12C isotopes (Da)
+pub const C13_C12_MASS_DIFF: f64 = 1.003_354_835;
+
+/// Proton mass (Da)
+pub const PROTON_MASS: f64 = 1.007_276_467;
+
+/// Average mass of the averagine building block (Da)
+pub const AVERAGINE_MASS: f64 = 111.1254;
+
+/// Hydrogen atom mass (Da)
+const HYDROGEN_MASS: f64 = 1.007_825_032;
+
+/// Elemental composition of the averagine building block
+const AVERAGINE: &[(&str, f64)] = &[
+    ("C", 4.9384),
+    ("H", 7.7583),
+    ("N", 1.3577),
+    ("O", 1.4773),
+    ("S", 0.0417),
+];
+
+/// Stable isotope of an element: nominal mass shift, exact mass (Da) and
+/// natural abundance
+type Isotope = (usize, f64, f64);
+
+/// Supported elements and their stable isotopes (IUPAC 2009 abundances)
+const ELEMENTS: &[(&str, &[Isotope])] = &[
+    (
+        "H",
+        &[(0, HYDROGEN_MASS, 0.999_885), (1, 2.014_101_778, 0.000_115)],
+    ),
+    ("C", &[(0, 12.0, 0.9893), (1, 13.003_354_838, 0.0107)]),
+    (
+        "N",
+        &[(0, 14.003_074_005, 0.996_36), (1, 15.000_108_898, 0.003_64)],
+    ),
+    (
+        "O",
+        &[
+            (0, 15.994_914_620, 0.997_57),
+            (1, 16.999_131_70, 0.000_38),
+            (2, 17.999_161_0, 0.002_05),
+        ],
+    ),
+    ("F", &[(0, 18.998_403_22, 1.0)]),
+    ("Na", &[(0, 22.989_769_281, 1.0)]),
+    ("P", &[(0, 30.973_761_63, 1.0)]),
+    (
+        "S",
+        &[
+            (0, 31.972_071_00, 0.9499),
+            (1, 32.971_458_76, 0.0075),
+            (2, 33.967_866_90, 0.0425),
+            (4, 35.967_080_76, 0.0001),
+        ],
+    ),
+    (
+        "Cl",
+        &[(0, 34.968_852_68, 0.7576), (2, 36.965_902_59, 0.2424)],
+    ),
+    (
+        "K",
+        &[
+            (0, 38.963_706_68, 0.932_581),
+            (1, 39.963_998_48, 0.000_117),
+            (2, 40.961_825_76, 0.067_302),
+        ],
+    ),
+    (
+        "Br",
+        &[(0, 78.918_337_1, 0.5069), (2, 80.916_290_6, 0.4931)],
+    ),
+    ("I", &[(0, 126.904_473, 1.0)]),
+];
+
+/// Isotopes of a supported element
+fn isotopes(symbol: &str) -> Option<(&'static str, &'static [Isotope])> {
+    ELEMENTS
+        .iter()
+        .find(|(known, _)| *known == symbol)
+        .map(|&(known, isotopes)| (known, isotopes))
+}
+
+/// Elemental formula, e.g. `C6H12O6`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Formula {
+    counts: BTreeMap<&'static str, u32>,
+}
+
+impl Formula {
+    /// Empty formula
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `count` atoms of an element
+    pub fn add(&mut self, symbol: &str, count: u32) -> Result<(), ProcessingError> {
+        let (symbol, _) = isotopes(symbol)
+            .ok_or_else(|| ProcessingError::InvalidData(format!("unknown element {}", symbol)))?;
+        if count > 0 {
+            *self.counts.entry(symbol).or_insert(0) += count;
+        }
+        Ok(())
+    }
+
+    /// Number of atoms of an element
+    pub fn count(&self, symbol: &str) -> u32 {
+        self.counts.get(symbol).copied().unwrap_or(0)
+    }
+
+    /// Elements and their counts, ordered by symbol
+    pub fn elements(&self) -> impl Iterator<Item = (&'static str, u32)> + '_ {
+        self.counts.iter().map(|(&symbol, &count)| (symbol, count))
+    }
+
+    /// Monoisotopic mass (Da), using the lightest isotope of every element
+    pub fn monoisotopic_mass(&self) -> f64 {
+        self.elements()
+            .filter_map(|(symbol, count)| {
+                let (_, isotopes) = isotopes(symbol)?;
+                Some(isotopes.first()?.1 * f64::from(count))
+            })
+            .sum()
+    }
+
+    /// Averagine formula of a peptide with the given monoisotopic mass
+    ///
+    /// Carbon, nitrogen, oxygen and sulfur counts are the rounded averagine
+    /// proportions; hydrogens make up the remaining mass.
+    pub fn averagine(mass: f64) -> Self {
+        let units = (mass / AVERAGINE_MASS).max(0.0);
+        let mut formula = Self::new();
+        for &(symbol, per_unit) in AVERAGINE {
+            let count = (per_unit * units).round() as u32;
+            if symbol != "H" && count > 0 {
+                formula.counts.insert(symbol, count);
+            }
+        }
+        let hydrogens = ((mass - formula.monoisotopic_mass()) / HYDROGEN_MASS).round();
+        if hydrogens >= 1.0 {
+            formula.counts.insert("H", hydrogens as u32);
+        }
+        formula
+    }
+}
+
+impl FromStr for Formula {
+    type Err = ProcessingError;
+
+    /// Parse a formula such as `C6H12O6` or `C2H5Cl`
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || ProcessingError::InvalidData(format!("invalid formula {:?}", text));
+        let mut formula = Self::new();
+        let mut chars = text.trim().chars().peekable();
+        while let Some(first) = chars.next() {
+            if !first.is_ascii_uppercase() {
+                return Err(invalid());
+            }
+            let mut symbol = first.to_string();
+            while let Some(&next) = chars.peek() {
+                if !next.is_ascii_lowercase() {
+                    break;
+                }
+                symbol.push(next);
+                chars.next();
+            }
+            let mut digits = String::new();
+            while let Some(&next) = chars.peek() {
+                if !next.is_ascii_digit() {
+                    break;
+                }
+                digits.push(next);
+                chars.next();
+            }
+            let count = if digits.is_empty() {
+                1
+            } else {
+                digits.parse().map_err(|_| invalid())?
+            };
+            formula.add(&symbol, count)?;
+        }
+        if formula.counts.is_empty() {
+            return Err(invalid());
+        }
+        Ok(formula)
+    }
+}
+
+impl fmt::Display for Formula {
+    /// Hill notation: carbon, hydrogen, then the other elements alphabetically
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let carbon = self.counts.contains_key("C");
+        let mut write = |symbol: &str, count: u32| match count {
+            1 => write!(f, "{}", symbol),
+            _ => write!(f, "{}{}", symbol, count),
+        };
+        if carbon {
+            write("C", self.count("C"))?;
+            if self.count("H") > 0 {
+                write("H", self.count("H"))?;
+            }
+        }
+        for (symbol, count) in self.elements() {
+            if !(carbon && (symbol == "C" || symbol == "H")) {
+                write(symbol, count)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One peak of an isotope distribution
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IsotopePeak {
+    /// Abundance-weighted mean neutral mass (Da)
+    pub mass: f64,
+    /// Fraction of all molecules in this peak
+    pub abundance: f64,
+}
+
+/// Isotope distribution by nominal mass shift, monoisotopic peak first
+#[derive(Debug, Clone, PartialEq)]
+pub struct IsotopeDistribution {
+    /// Peaks at nominal shifts 0, 1, 2, ...
+    pub peaks: Vec<IsotopePeak>,
+}
+
+impl IsotopeDistribution {
+    /// Distribution of a formula, truncated to `max_peaks` peaks
+    pub fn for_formula(formula: &Formula, max_peaks: usize) -> Self {
+        let max_peaks = max_peaks.max(1);
+        let mut total = vec![IsotopePeak {
+            mass: 0.0,
+            abundance: 1.0,
+        }];
+        for (symbol, count) in formula.elements() {
+            if let Some((_, isotopes)) = isotopes(symbol) {
+                let element = element_distribution(isotopes, max_peaks);
+                total = convolve(&total, &power(&element, count, max_peaks), max_peaks);
+            }
+        }
+        Self { peaks: total }
+    }
+
+    /// Distribution of an averagine peptide with the given monoisotopic mass
+    pub fn averagine(mass: f64, max_peaks: usize) -> Self {
+        Self::for_formula(&Formula::averagine(mass), max_peaks)
+    }
+
+    /// Abundances relative to the most abundant peak
+    pub fn relative_abundances(&self) -> Vec<f64> {
+        let max = self
+            .peaks
+            .iter()
+            .map(|peak| peak.abundance)
+            .fold(0.0, f64::max);
+        self.peaks
+            .iter()
+            .map(|peak| if max > 0.0 { peak.abundance / max } else { 0.0 })
+            .collect()
+    }
+
+    /// Index of the most abundant peak
+    pub fn most_abundant(&self) -> usize {
+        self.peaks
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.abundance.total_cmp(&b.1.abundance))
+            .map_or(0, |(i, _)| i)
+    }
+
+    /// Peak m/z values of the `[M + zH]z+` ion
+    ///
+    /// A charge of 0 returns the neutral masses.
+    pub fn mz(&self, charge: i16) -> Vec<f64> {
+        let z = f64::from(charge.unsigned_abs());
+        self.peaks
+            .iter()
+            .map(|peak| {
+                if charge == 0 {
+                    peak.mass
+                } else {
+                    (peak.mass + f64::from(charge) * PROTON_MASS) / z
+                }
+            })
+            .collect()
+    }
+}
+
+/// Expected intensity ratio of isotope `n` to isotope `n - 1` of an averagine
+/// peptide with the given monoisotopic mass
+pub fn averagine_isotope_ratio(mass: f64, n: usize) -> f64 {
+    let Some(previous) = n.checked_sub(1) else {
+        return 0.0;
+    };
+    let distribution = IsotopeDistribution::averagine(mass, n + 1);
+    match (distribution.peaks.get(previous), distribution.peaks.get(n)) {
+        (Some(lighter), Some(heavier)) if lighter.abundance > 0.0 => {
+            heavier.abundance / lighter.abundance
+        }
+        _ => 0.0,
+    }
+}
+
+/// Single-atom distribution of an element by nominal shift
+fn element_distribution(isotopes: &[Isotope], max_peaks: usize) -> Vec<IsotopePeak> {
+    let mut peaks = Vec::new();
+    for &(shift, mass, abundance) in isotopes {
+        if shift >= max_peaks {
+            continue;
+        }
+        if peaks.len() <= shift {
+            peaks.resize(
+                shift + 1,
+                IsotopePeak {
+                    mass: 0.0,
+                    abundance: 0.0,
+                },
+            );
+        }
+        peaks[shift] = IsotopePeak { mass, abundance };
+    }
+    peaks
+}
+
+/// Distribution of `count` atoms, by repeated squaring
+fn power(element: &[IsotopePeak], count: u32, max_peaks: usize) -> Vec<IsotopePeak> {
+    let mut result = vec![IsotopePeak {
+        mass: 0.0,
+        abundance: 1.0,
+    }];
+    let mut base = element.to_vec();
+    let mut remaining = count;
+    while remaining > 0 {
+        if remaining & 1 == 1 {
+            result = convolve(&result, &base, max_peaks);
+        }
+        remaining >>= 1;
+        if remaining > 0 {
+            base = convolve(&base, &base, max_peaks);
+        }
+    }
+    result
+}
+
+/// Distribution of the combined molecule, truncated to `max_peaks` peaks
+fn convolve(a: &[IsotopePeak], b: &[IsotopePeak], max_peaks: usize) -> Vec<IsotopePeak> {
+    let len = (a.len() + b.len()).saturating_sub(1).min(max_peaks);
+    let mut abundance = vec![0.0; len];
+    let mut weighted_mass = vec![0.0; len];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate().take(len.saturating_sub(i)) {
+            let product = x.abundance * y.abundance;
+            abundance[i + j] += product;
+            weighted_mass[i + j] += product * (x.mass + y.mass);
+        }
+    }
+    abundance
+        .into_iter()
+        .zip(weighted_mass)
+        .map(|(abundance, weighted)| IsotopePeak {
+            mass: if abundance > 0.0 {
+                weighted / abundance
+            } else {
+                0.0
+            },
+            abundance,
+        })
+        .collect()
+}
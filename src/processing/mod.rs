@@ -0,0 +1,83 @@
+//! # mzPeak Processing Module
+//!
+//! Lightweight, reader-driven analyses that operate directly on converted mzPeak
+//! files without requiring external tools.
+//!
+//! ## Available Analyses
+//!
+//! - [`dia_scheme`]: Detect the DIA isolation scheme (window layout, overlaps,
+//!   cycle time) and export it as JSON or SVG for method review
+//! - [`feature_detect`]: MS1 feature detection (trace building, isotope
+//!   grouping, charge assignment) with a `features.parquet` output table
+//! - [`inclusion_list`]: Rank detected features into a targeted inclusion list
+//! - [`diagnostic_ions`]: Flag MS2 spectra with diagnostic fragment ions or
+//!   neutral losses (`annotations.parquet`)
+//! - [`reporter_ions`]: TMT/iTRAQ reporter ion intensities with isotope
+//!   impurity correction (`reporters.parquet`)
+//! - [`precursor_correction`]: Recalculate monoisotopic precursor m/z and
+//!   charge from the MS1 isotope envelope (`precursors.parquet`)
+//! - [`similarity`]: Spectral entropy plus cosine, modified dot-product and
+//!   entropy similarity between spectra with tolerant peak matching
+//! - [`xic`]: Extracted ion chromatograms and m/z-range peak queries
+//! - [`ion_image`]: Physically scaled ion images of imaging data
+//! - [`isotopes`]: Isotope distributions of formulas and averagine peptides
+//! - [`mz_kernels`]: SIMD kernels for m/z range masks, sorted search and
+//!   binning, with scalar fallbacks
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use mzpeak::processing::DiaScheme;
+//! use mzpeak::reader::MzPeakReader;
+//!
+//! let reader = MzPeakReader::open("data.mzpeak")?;
+//! if let Some(scheme) = DiaScheme::detect(&reader)? {
+//!     println!("{} windows, median cycle {:?} s", scheme.windows.len(), scheme.cycle_time.median_seconds);
+//! }
+//! # Ok::<(), mzpeak::processing::ProcessingError>(())
+//! ```
+
+pub mod dia_scheme;
+pub mod diagnostic_ions;
+mod error;
+pub mod feature_detect;
+pub mod inclusion_list;
+pub mod ion_image;
+pub mod isotopes;
+pub mod mz_kernels;
+pub mod precursor_correction;
+pub mod reporter_ions;
+pub mod similarity;
+pub mod xic;
+
+#[cfg(test)]
+mod tests;
+
+pub use dia_scheme::{CycleTimeStats, DiaScheme, IsolationWindow, WindowOverlap};
+pub use diagnostic_ions::{
+    DiagnosticHit, DiagnosticIonScan, DiagnosticTarget, MassTolerance, ANNOTATIONS_FILE_NAME,
+};
+pub use error::ProcessingError;
+pub use feature_detect::{
+    detect_features, feature_columns, group_isotopes, Feature, FeatureDetectionConfig,
+    FeatureTable, MassTrace, TraceBuilder, FEATURES_FILE_NAME,
+};
+pub use inclusion_list::{
+    build_inclusion_list, write_inclusion_list_csv, InclusionEntry, InclusionListConfig,
+};
+pub use ion_image::IonImage;
+pub use isotopes::{averagine_isotope_ratio, Formula, IsotopeDistribution, IsotopePeak};
+pub use precursor_correction::{
+    CorrectionStatus, PrecursorCorrection, PrecursorCorrectionConfig, PrecursorCorrectionTable,
+    PRECURSORS_FILE_NAME,
+};
+pub use reporter_ions::{
+    correct_impurities, reporter_ions, ReporterConfig, ReporterPlex, ReporterRow, ReporterTable,
+    REPORTERS_FILE_NAME,
+};
+pub use similarity::{
+    cosine_similarity, entropy_similarity, match_peaks, modified_dot_product,
+    normalized_spectral_entropy, spectral_entropy, weighted_entropy_similarity, DotProductWeights,
+    PeakList,
+};
+pub use xic::Xic;
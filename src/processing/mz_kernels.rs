@@ -0,0 +1,391 @@
+//! SIMD kernels for m/z filtering
+//!
+//! The hot loops of m/z-range queries and XIC extraction run over decoded
+//! `Float64` m/z arrays. This module provides those loops as kernels:
+//!
+//! - [`mz_range_mask`]: bit-packed mask of values inside `[lower, upper]`,
+//!   ready for [`arrow::compute::filter`]
+//! - [`intensity_in_range`]: summed intensity of peaks inside `[lower, upper]`
+//! - [`lower_bound`] / [`nearest_index`]: search in sorted m/z arrays
+//! - [`bin_intensities`]: accumulate intensities into fixed-width m/z bins
+//!
+//! On x86_64 the AVX2 implementation is selected at runtime when the CPU
+//! supports it; everything else uses the [`scalar`] implementations, which
+//! are also exported for testing and benchmarking. Both paths return
+//! identical results, except that [`intensity_in_range`] may differ in the
+//! last bits because the vector path sums in a different order.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use mzpeak::processing::mz_kernels;
+//!
+//! let mz = [100.0, 250.1, 250.2, 400.0];
+//! let mask = mz_kernels::mz_range_mask(&mz, 250.0, 300.0);
+//! assert_eq!(mask.count_set_bits(), 2);
+//! assert_eq!(mz_kernels::nearest_index(&mz, 251.0), Some(2));
+//! ```
+
+use arrow::buffer::{BooleanBuffer, Buffer};
+
+/// Remaining range size at which [`lower_bound`] switches from bisection to a
+/// linear count
+const LINEAR_SEARCH_BLOCK: usize = 64;
+
+/// Whether the AVX2 kernels can be used on this CPU
+#[cfg(target_arch = "x86_64")]
+#[inline]
+fn has_avx2() -> bool {
+    std::is_x86_feature_detected!("avx2")
+}
+
+/// Bit-packed mask of the values in `[lower, upper]`
+///
+/// NaN values are never inside the range.
+pub fn mz_range_mask(mz: &[f64], lower: f64, upper: f64) -> BooleanBuffer {
+    #[cfg(target_arch = "x86_64")]
+    if has_avx2() {
+        // SAFETY: AVX2 support was checked at runtime
+        return unsafe { avx2::mz_range_mask(mz, lower, upper) };
+    }
+    scalar::mz_range_mask(mz, lower, upper)
+}
+
+/// Sum of the intensities of peaks with m/z in `[lower, upper]`
+///
+/// `mz` does not need to be sorted. Extra elements of the longer slice are
+/// ignored.
+pub fn intensity_in_range(mz: &[f64], intensity: &[f32], lower: f64, upper: f64) -> f64 {
+    #[cfg(target_arch = "x86_64")]
+    if has_avx2() {
+        // SAFETY: AVX2 support was checked at runtime
+        return unsafe { avx2::intensity_in_range(mz, intensity, lower, upper) };
+    }
+    scalar::intensity_in_range(mz, intensity, lower, upper)
+}
+
+/// Index of the first value not less than `value` in an ascending array
+///
+/// Equivalent to `sorted_mz.partition_point(|&mz| mz < value)`.
+pub fn lower_bound(sorted_mz: &[f64], value: f64) -> usize {
+    #[cfg(target_arch = "x86_64")]
+    if has_avx2() {
+        let (start, end) = bisect(sorted_mz, value);
+        // SAFETY: AVX2 support was checked at runtime
+        return start + unsafe { avx2::count_less(&sorted_mz[start..end], value) };
+    }
+    scalar::lower_bound(sorted_mz, value)
+}
+
+/// Index of the value closest to `target` in an ascending array
+///
+/// A target exactly halfway between two values resolves to the lower one.
+/// Returns `None` for an empty array or a NaN target.
+pub fn nearest_index(sorted_mz: &[f64], target: f64) -> Option<usize> {
+    nearest_from_lower_bound(sorted_mz, target, lower_bound(sorted_mz, target))
+}
+
+/// Add each intensity to the bin of its m/z
+///
+/// Bin `i` covers `[lower + i * bin_width, lower + (i + 1) * bin_width)`.
+/// Peaks outside all bins are skipped.
+pub fn bin_intensities(
+    mz: &[f64],
+    intensity: &[f32],
+    lower: f64,
+    bin_width: f64,
+    bins: &mut [f64],
+) {
+    #[cfg(target_arch = "x86_64")]
+    if has_avx2() {
+        // SAFETY: AVX2 support was checked at runtime
+        return unsafe { avx2::bin_intensities(mz, intensity, lower, bin_width, bins) };
+    }
+    scalar::bin_intensities(mz, intensity, lower, bin_width, bins)
+}
+
+/// Narrow `[0, len)` by bisection until at most [`LINEAR_SEARCH_BLOCK`]
+/// candidates for the lower bound remain
+#[inline]
+fn bisect(sorted_mz: &[f64], value: f64) -> (usize, usize) {
+    let (mut start, mut end) = (0, sorted_mz.len());
+    while end - start > LINEAR_SEARCH_BLOCK {
+        let mid = start + (end - start) / 2;
+        if sorted_mz[mid] < value {
+            start = mid + 1;
+        } else {
+            end = mid;
+        }
+    }
+    (start, end)
+}
+
+#[inline]
+fn nearest_from_lower_bound(sorted_mz: &[f64], target: f64, index: usize) -> Option<usize> {
+    if sorted_mz.is_empty() || target.is_nan() {
+        return None;
+    }
+    if index == 0 {
+        return Some(0);
+    }
+    if index == sorted_mz.len() {
+        return Some(index - 1);
+    }
+    let below = target - sorted_mz[index - 1];
+    let above = sorted_mz[index] - target;
+    Some(if below <= above { index - 1 } else { index })
+}
+
+#[inline]
+fn bin_index(mz: f64, lower: f64, bin_width: f64, num_bins: usize) -> Option<usize> {
+    let position = ((mz - lower) / bin_width).floor();
+    (position >= 0.0 && position < num_bins as f64).then_some(position as usize)
+}
+
+/// Portable implementations of the kernels
+pub mod scalar {
+    use super::*;
+
+    /// Scalar [`mz_range_mask`](super::mz_range_mask)
+    pub fn mz_range_mask(mz: &[f64], lower: f64, upper: f64) -> BooleanBuffer {
+        let mut words = Vec::with_capacity((mz.len() + 63) / 64);
+        for chunk in mz.chunks(64) {
+            let mut word = 0u64;
+            for (bit, &value) in chunk.iter().enumerate() {
+                word |= ((value >= lower && value <= upper) as u64) << bit;
+            }
+            words.push(word.to_le());
+        }
+        BooleanBuffer::new(Buffer::from_vec(words), 0, mz.len())
+    }
+
+    /// Scalar [`intensity_in_range`](super::intensity_in_range)
+    pub fn intensity_in_range(mz: &[f64], intensity: &[f32], lower: f64, upper: f64) -> f64 {
+        mz.iter()
+            .zip(intensity)
+            .filter(|(&mz, _)| mz >= lower && mz <= upper)
+            .map(|(_, &intensity)| intensity as f64)
+            .sum()
+    }
+
+    /// Scalar [`lower_bound`](super::lower_bound)
+    pub fn lower_bound(sorted_mz: &[f64], value: f64) -> usize {
+        sorted_mz.partition_point(|&mz| mz < value)
+    }
+
+    /// Scalar [`nearest_index`](super::nearest_index)
+    pub fn nearest_index(sorted_mz: &[f64], target: f64) -> Option<usize> {
+        nearest_from_lower_bound(sorted_mz, target, lower_bound(sorted_mz, target))
+    }
+
+    /// Scalar [`bin_intensities`](super::bin_intensities)
+    pub fn bin_intensities(
+        mz: &[f64],
+        intensity: &[f32],
+        lower: f64,
+        bin_width: f64,
+        bins: &mut [f64],
+    ) {
+        let num_bins = bins.len();
+        for (&mz, &intensity) in mz.iter().zip(intensity) {
+            if let Some(bin) = bin_index(mz, lower, bin_width, num_bins) {
+                bins[bin] += intensity as f64;
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use std::arch::x86_64::*;
+
+    use super::*;
+
+    /// Lane mask of `lower <= v <= upper` (ordered, so NaN is outside)
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn in_range(values: __m256d, lower: __m256d, upper: __m256d) -> __m256d {
+        _mm256_and_pd(
+            _mm256_cmp_pd::<_CMP_GE_OQ>(values, lower),
+            _mm256_cmp_pd::<_CMP_LE_OQ>(values, upper),
+        )
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn mz_range_mask(mz: &[f64], lower: f64, upper: f64) -> BooleanBuffer {
+        let lower_v = _mm256_set1_pd(lower);
+        let upper_v = _mm256_set1_pd(upper);
+        let mut words = Vec::with_capacity((mz.len() + 63) / 64);
+        let mut chunks = mz.chunks_exact(64);
+        for chunk in &mut chunks {
+            let mut word = 0u64;
+            for (lane, quad) in chunk.chunks_exact(4).enumerate() {
+                let values = _mm256_loadu_pd(quad.as_ptr());
+                let bits = _mm256_movemask_pd(in_range(values, lower_v, upper_v)) as u64;
+                word |= bits << (lane * 4);
+            }
+            words.push(word.to_le());
+        }
+        let remainder = chunks.remainder();
+        if !remainder.is_empty() {
+            let mut word = 0u64;
+            for (bit, &value) in remainder.iter().enumerate() {
+                word |= ((value >= lower && value <= upper) as u64) << bit;
+            }
+            words.push(word.to_le());
+        }
+        BooleanBuffer::new(Buffer::from_vec(words), 0, mz.len())
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn intensity_in_range(
+        mz: &[f64],
+        intensity: &[f32],
+        lower: f64,
+        upper: f64,
+    ) -> f64 {
+        let len = mz.len().min(intensity.len());
+        let (mz, intensity) = (&mz[..len], &intensity[..len]);
+        let lower_v = _mm256_set1_pd(lower);
+        let upper_v = _mm256_set1_pd(upper);
+        let mut sum = _mm256_setzero_pd();
+
+        let vector_len = len - len % 4;
+        for start in (0..vector_len).step_by(4) {
+            let values = _mm256_loadu_pd(mz.as_ptr().add(start));
+            let weights = _mm256_cvtps_pd(_mm_loadu_ps(intensity.as_ptr().add(start)));
+            let mask = in_range(values, lower_v, upper_v);
+            sum = _mm256_add_pd(sum, _mm256_and_pd(mask, weights));
+        }
+
+        let mut lanes = [0.0f64; 4];
+        _mm256_storeu_pd(lanes.as_mut_ptr(), sum);
+        lanes.iter().sum::<f64>()
+            + scalar::intensity_in_range(&mz[vector_len..], &intensity[vector_len..], lower, upper)
+    }
+
+    /// Number of values less than `value`
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn count_less(values: &[f64], value: f64) -> usize {
+        let value_v = _mm256_set1_pd(value);
+        let mut quads = values.chunks_exact(4);
+        let mut count = 0;
+        for quad in &mut quads {
+            let less = _mm256_cmp_pd::<_CMP_LT_OQ>(_mm256_loadu_pd(quad.as_ptr()), value_v);
+            count += _mm256_movemask_pd(less).count_ones() as usize;
+        }
+        count + quads.remainder().iter().filter(|&&v| v < value).count()
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn bin_intensities(
+        mz: &[f64],
+        intensity: &[f32],
+        lower: f64,
+        bin_width: f64,
+        bins: &mut [f64],
+    ) {
+        let len = mz.len().min(intensity.len());
+        let (mz, intensity) = (&mz[..len], &intensity[..len]);
+        let num_bins = bins.len();
+        let lower_v = _mm256_set1_pd(lower);
+        let width_v = _mm256_set1_pd(bin_width);
+        let zero_v = _mm256_setzero_pd();
+        let count_v = _mm256_set1_pd(num_bins as f64);
+
+        let vector_len = len - len % 4;
+        let mut positions = [0.0f64; 4];
+        for start in (0..vector_len).step_by(4) {
+            // Same division and floor as the scalar path, so bin edges agree
+            let position = _mm256_floor_pd(_mm256_div_pd(
+                _mm256_sub_pd(_mm256_loadu_pd(mz.as_ptr().add(start)), lower_v),
+                width_v,
+            ));
+            let valid = _mm256_and_pd(
+                _mm256_cmp_pd::<_CMP_GE_OQ>(position, zero_v),
+                _mm256_cmp_pd::<_CMP_LT_OQ>(position, count_v),
+            );
+            let mask = _mm256_movemask_pd(valid);
+            if mask == 0 {
+                continue;
+            }
+            _mm256_storeu_pd(positions.as_mut_ptr(), position);
+            for (lane, &position) in positions.iter().enumerate() {
+                if mask & (1 << lane) != 0 {
+                    bins[position as usize] += intensity[start + lane] as f64;
+                }
+            }
+        }
+        scalar::bin_intensities(
+            &mz[vector_len..],
+            &intensity[vector_len..],
+            lower,
+            bin_width,
+            bins,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(len: usize) -> (Vec<f64>, Vec<f32>) {
+        // Ascending m/z with a few repeated values, plus matching intensities
+        let mz = (0..len).map(|i| 100.0 + (i / 2) as f64 * 0.37).collect();
+        let intensity = (0..len).map(|i| (i % 17) as f32 + 0.5).collect();
+        (mz, intensity)
+    }
+
+    #[test]
+    fn test_range_mask_matches_scalar() {
+        for len in [0, 3, 64, 65, 200] {
+            let (mut mz, _) = sample(len);
+            if len > 10 {
+                mz[7] = f64::NAN;
+            }
+            let fast = mz_range_mask(&mz, 110.0, 130.0);
+            let expected: Vec<bool> = mz.iter().map(|&v| (110.0..=130.0).contains(&v)).collect();
+            assert_eq!(fast.iter().collect::<Vec<_>>(), expected);
+            assert_eq!(fast, scalar::mz_range_mask(&mz, 110.0, 130.0));
+        }
+    }
+
+    #[test]
+    fn test_intensity_in_range_matches_scalar() {
+        let (mz, intensity) = sample(203);
+        let fast = intensity_in_range(&mz, &intensity, 120.0, 140.0);
+        let expected = scalar::intensity_in_range(&mz, &intensity, 120.0, 140.0);
+        assert!((fast - expected).abs() < 1e-9);
+        assert!(fast > 0.0);
+        assert_eq!(intensity_in_range(&mz, &intensity[..5], 0.0, 1000.0), 12.5);
+    }
+
+    #[test]
+    fn test_search_matches_scalar() {
+        let (mz, _) = sample(501);
+        for target in [0.0, 100.0, 100.1, 100.37, 150.0, 192.5, 500.0, f64::NAN] {
+            assert_eq!(lower_bound(&mz, target), scalar::lower_bound(&mz, target));
+            assert_eq!(
+                nearest_index(&mz, target),
+                scalar::nearest_index(&mz, target)
+            );
+        }
+        assert_eq!(nearest_index(&mz, 150.0), Some(271));
+        assert_eq!(nearest_index(&[], 150.0), None);
+        assert_eq!(nearest_index(&mz, 1e6), Some(500));
+    }
+
+    #[test]
+    fn test_binning_matches_scalar() {
+        let (mz, intensity) = sample(157);
+        let mut fast = vec![0.0; 10];
+        let mut expected = vec![0.0; 10];
+        bin_intensities(&mz, &intensity, 105.0, 2.5, &mut fast);
+        scalar::bin_intensities(&mz, &intensity, 105.0, 2.5, &mut expected);
+        assert_eq!(fast, expected);
+        // Peaks below 105 and above 130 fall outside every bin
+        let total: f64 = fast.iter().sum();
+        assert!(total < intensity.iter().map(|&i| i as f64).sum::<f64>());
+    }
+}
@@ -0,0 +1,409 @@
+//! Precursor m/z and charge correction from the MS1 isotope envelope
+//!
+//! Instruments frequently select the first or second 13C isotope instead of
+//! the monoisotopic peak, and report no or the wrong charge for weak
+//! precursors. For every MS2 spectrum this module inspects the preceding MS1
+//! scan around the selected precursor, determines the charge from the
+//! isotope spacing, and walks down the envelope while a lighter isotope with
+//! a plausible averagine intensity ratio exists.
+//!
+//! Results are stored as an overlay table (`precursors.parquet`) with one row
+//! per MS2 spectrum, holding the original and corrected values side by side.
+//! The source file's processing history, extended by this step and its
+//! parameters, is stored in the table footer for provenance.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use mzpeak::processing::{PrecursorCorrectionConfig, PrecursorCorrectionTable};
+//! use mzpeak::reader::MzPeakReader;
+//!
+//! let reader = MzPeakReader::open("data.mzpeak")?;
+//! let table = PrecursorCorrectionTable::compute(&reader, &PrecursorCorrectionConfig::default())?;
+//! println!("{} of {} precursors corrected", table.num_corrected(), table.rows.len());
+//! table.write_parquet("precursors.parquet")?;
+//! # Ok::<(), mzpeak::processing::ProcessingError>(())
+//! ```
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Float64Array, Int16Array, Int64Array, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::metadata::KeyValue;
+use parquet::file::properties::WriterProperties;
+
+use super::isotopes::{averagine_isotope_ratio, C13_C12_MASS_DIFF, PROTON_MASS};
+use super::{PeakList, ProcessingError};
+use crate::metadata::{ProcessingHistory, ProcessingStep};
+use crate::reader::MzPeakReader;
+use crate::schema::KEY_PROCESSING_HISTORY;
+
+/// Default file name for the precursor correction table
+pub const PRECURSORS_FILE_NAME: &str = "precursors.parquet";
+
+/// Most isotope peaks followed above the selected precursor
+const MAX_ENVELOPE_PEAKS: usize = 6;
+
+/// Configuration for precursor correction
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrecursorCorrectionConfig {
+    /// Match tolerance for isotope peaks in the MS1 scan (ppm)
+    pub tolerance_ppm: f64,
+    /// Lowest charge state considered
+    pub min_charge: i16,
+    /// Highest charge state considered
+    pub max_charge: i16,
+    /// Most isotope steps the monoisotopic peak may lie below the selected one
+    pub max_isotope_error: usize,
+    /// Allowed factor between observed and averagine isotope ratios
+    pub isotope_ratio_tolerance: f64,
+}
+
+impl Default for PrecursorCorrectionConfig {
+    fn default() -> Self {
+        Self {
+            tolerance_ppm: 10.0,
+            min_charge: 1,
+            max_charge: 6,
+            max_isotope_error: 2,
+            isotope_ratio_tolerance: 3.0,
+        }
+    }
+}
+
+/// Outcome of correcting one precursor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorrectionStatus {
+    /// Envelope found and the reported values already matched it
+    Unchanged,
+    /// Monoisotopic m/z and/or charge were corrected
+    Corrected,
+    /// No MS1 scan precedes the spectrum
+    NoMs1,
+    /// The selected precursor peak is absent from the MS1 scan
+    NoSignal,
+    /// The precursor peak has no isotope peaks at any considered charge
+    NoEnvelope,
+}
+
+impl CorrectionStatus {
+    /// Name stored in the `status` column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CorrectionStatus::Unchanged => "unchanged",
+            CorrectionStatus::Corrected => "corrected",
+            CorrectionStatus::NoMs1 => "no_ms1",
+            CorrectionStatus::NoSignal => "no_signal",
+            CorrectionStatus::NoEnvelope => "no_envelope",
+        }
+    }
+}
+
+/// Original and corrected precursor of one MS2 spectrum
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrecursorCorrection {
+    /// MS2 spectrum ID
+    pub spectrum_id: i64,
+    /// Native scan number of the MS2 spectrum
+    pub scan_number: i64,
+    /// MS1 spectrum the envelope was read from
+    pub ms1_spectrum_id: Option<i64>,
+    /// Precursor m/z as reported by the instrument
+    pub original_mz: f64,
+    /// Precursor charge as reported by the instrument
+    pub original_charge: Option<i16>,
+    /// Corrected monoisotopic m/z
+    ///
+    /// Shifted from the reported m/z by whole isotope spacings, so the
+    /// instrument's precursor calibration is kept.
+    pub corrected_mz: f64,
+    /// Corrected charge
+    pub corrected_charge: Option<i16>,
+    /// Isotope steps between the selected and the monoisotopic peak
+    pub isotope_error: i16,
+    /// Isotope peaks found in the envelope, including the monoisotopic peak
+    pub num_isotopes: i16,
+    /// Outcome of the correction
+    pub status: CorrectionStatus,
+}
+
+/// Precursor corrections for a run, written as `precursors.parquet`
+#[derive(Debug, Clone)]
+pub struct PrecursorCorrectionTable {
+    /// One row per MS2+ spectrum with a precursor m/z
+    pub rows: Vec<PrecursorCorrection>,
+    /// Processing history of the source file extended by this step
+    pub processing_history: ProcessingHistory,
+}
+
+impl PrecursorCorrectionTable {
+    /// Correct the precursor of every MS2+ spectrum against the preceding MS1 scan
+    pub fn compute(
+        reader: &MzPeakReader,
+        config: &PrecursorCorrectionConfig,
+    ) -> Result<Self, ProcessingError> {
+        if config.min_charge < 1 || config.max_charge < config.min_charge {
+            return Err(ProcessingError::InvalidData(format!(
+                "invalid charge range {}..={}",
+                config.min_charge, config.max_charge
+            )));
+        }
+
+        let mut ms1: Option<(i64, PeakList)> = None;
+        let mut rows = Vec::new();
+        for spectrum in reader.iter_spectra_arrays_streaming()? {
+            let spectrum = spectrum?;
+            if spectrum.ms_level == 1 {
+                ms1 = Some((spectrum.spectrum_id, PeakList::try_from(&spectrum)?));
+                continue;
+            }
+            let Some(precursor_mz) = spectrum.precursor_mz else {
+                continue;
+            };
+
+            let mut row = PrecursorCorrection {
+                spectrum_id: spectrum.spectrum_id,
+                scan_number: spectrum.scan_number,
+                ms1_spectrum_id: ms1.as_ref().map(|(id, _)| *id),
+                original_mz: precursor_mz,
+                original_charge: spectrum.precursor_charge,
+                corrected_mz: precursor_mz,
+                corrected_charge: spectrum.precursor_charge,
+                isotope_error: 0,
+                num_isotopes: 0,
+                status: CorrectionStatus::NoMs1,
+            };
+            if let Some((_, peaks)) = &ms1 {
+                correct_precursor(&mut row, peaks, config);
+            }
+            rows.push(row);
+        }
+
+        let mut processing_history = ProcessingHistory {
+            steps: reader.processing_history()?,
+            ..Default::default()
+        };
+        let corrected = rows
+            .iter()
+            .filter(|row| row.status == CorrectionStatus::Corrected)
+            .count();
+        processing_history.append(ProcessingStep::mzpeak(
+            "Precursor recalculation",
+            HashMap::from([
+                (
+                    "tolerance_ppm".to_string(),
+                    config.tolerance_ppm.to_string(),
+                ),
+                (
+                    "charge_range".to_string(),
+                    format!("{}-{}", config.min_charge, config.max_charge),
+                ),
+                (
+                    "max_isotope_error".to_string(),
+                    config.max_isotope_error.to_string(),
+                ),
+                (
+                    "isotope_ratio_tolerance".to_string(),
+                    config.isotope_ratio_tolerance.to_string(),
+                ),
+                ("precursors_corrected".to_string(), corrected.to_string()),
+            ]),
+        ));
+
+        Ok(Self {
+            rows,
+            processing_history,
+        })
+    }
+
+    /// Number of precursors whose m/z or charge was changed
+    pub fn num_corrected(&self) -> usize {
+        self.rows
+            .iter()
+            .filter(|row| row.status == CorrectionStatus::Corrected)
+            .count()
+    }
+
+    /// Arrow schema of the precursor correction table
+    pub fn schema() -> Schema {
+        Schema::new(vec![
+            Field::new("spectrum_id", DataType::Int64, false),
+            Field::new("scan_number", DataType::Int64, false),
+            Field::new("ms1_spectrum_id", DataType::Int64, true),
+            Field::new("original_mz", DataType::Float64, false),
+            Field::new("original_charge", DataType::Int16, true),
+            Field::new("corrected_mz", DataType::Float64, false),
+            Field::new("corrected_charge", DataType::Int16, true),
+            Field::new("isotope_error", DataType::Int16, false),
+            Field::new("num_isotopes", DataType::Int16, false),
+            Field::new("status", DataType::Utf8, false),
+        ])
+    }
+
+    /// Convert the table to an Arrow record batch
+    pub fn to_record_batch(&self) -> Result<RecordBatch, ProcessingError> {
+        let rows = &self.rows;
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from_iter_values(
+                rows.iter().map(|r| r.spectrum_id),
+            )),
+            Arc::new(Int64Array::from_iter_values(
+                rows.iter().map(|r| r.scan_number),
+            )),
+            Arc::new(Int64Array::from_iter(
+                rows.iter().map(|r| r.ms1_spectrum_id),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                rows.iter().map(|r| r.original_mz),
+            )),
+            Arc::new(Int16Array::from_iter(
+                rows.iter().map(|r| r.original_charge),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                rows.iter().map(|r| r.corrected_mz),
+            )),
+            Arc::new(Int16Array::from_iter(
+                rows.iter().map(|r| r.corrected_charge),
+            )),
+            Arc::new(Int16Array::from_iter_values(
+                rows.iter().map(|r| r.isotope_error),
+            )),
+            Arc::new(Int16Array::from_iter_values(
+                rows.iter().map(|r| r.num_isotopes),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|r| r.status.as_str()),
+            )),
+        ];
+        Ok(RecordBatch::try_new(Arc::new(Self::schema()), columns)?)
+    }
+
+    /// Write the table as a Parquet file (conventionally `precursors.parquet`)
+    ///
+    /// The processing history is stored under the `mzpeak:processing_history`
+    /// footer key, as in mzPeak files.
+    pub fn write_parquet<P: AsRef<Path>>(&self, path: P) -> Result<(), ProcessingError> {
+        let batch = self.to_record_batch()?;
+        let history = KeyValue::new(
+            KEY_PROCESSING_HISTORY.to_string(),
+            serde_json::to_string(&self.processing_history)?,
+        );
+        let props = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .set_key_value_metadata(Some(vec![history]))
+            .build();
+        let mut writer = ArrowWriter::try_new(File::create(path)?, batch.schema(), Some(props))?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+}
+
+/// Isotope envelope of the selected precursor at one charge
+struct Envelope {
+    charge: i16,
+    isotope_error: usize,
+    num_isotopes: usize,
+}
+
+/// Fill the corrected fields of `row` from the MS1 peaks
+fn correct_precursor(
+    row: &mut PrecursorCorrection,
+    peaks: &PeakList,
+    config: &PrecursorCorrectionConfig,
+) {
+    let Some(selected) = find_peak(peaks, row.original_mz, config.tolerance_ppm) else {
+        row.status = CorrectionStatus::NoSignal;
+        return;
+    };
+
+    // Most isotope peaks wins; ties go to the reported charge, then the
+    // higher charge, whose spacing also explains the lower charge's peaks
+    let best = (config.min_charge..=config.max_charge)
+        .map(|charge| envelope(peaks, selected, charge, config))
+        .filter(|envelope| envelope.num_isotopes > 1)
+        .max_by_key(|envelope| {
+            (
+                envelope.num_isotopes,
+                Some(envelope.charge) == row.original_charge,
+                envelope.charge,
+            )
+        });
+    let Some(best) = best else {
+        row.status = CorrectionStatus::NoEnvelope;
+        return;
+    };
+
+    row.corrected_charge = Some(best.charge);
+    row.isotope_error = best.isotope_error as i16;
+    row.num_isotopes = best.num_isotopes as i16;
+    row.corrected_mz =
+        row.original_mz - best.isotope_error as f64 * C13_C12_MASS_DIFF / best.charge as f64;
+    row.status = if best.isotope_error == 0 && row.original_charge == Some(best.charge) {
+        CorrectionStatus::Unchanged
+    } else {
+        CorrectionStatus::Corrected
+    };
+}
+
+/// Follow the isotope envelope of peak `selected` in both directions
+fn envelope(
+    peaks: &PeakList,
+    selected: usize,
+    charge: i16,
+    config: &PrecursorCorrectionConfig,
+) -> Envelope {
+    let spacing = C13_C12_MASS_DIFF / charge as f64;
+    let (mz, intensity) = (peaks.mz(), peaks.intensity());
+
+    let mut heavier = 0;
+    let mut current = selected;
+    while heavier < MAX_ENVELOPE_PEAKS {
+        match find_peak(peaks, mz[current] + spacing, config.tolerance_ppm) {
+            Some(next) => {
+                heavier += 1;
+                current = next;
+            }
+            None => break,
+        }
+    }
+
+    let mut isotope_error = 0;
+    let mut mono = selected;
+    while isotope_error < config.max_isotope_error {
+        let Some(lighter) = find_peak(peaks, mz[mono] - spacing, config.tolerance_ppm) else {
+            break;
+        };
+        let mass = (mz[lighter] - PROTON_MASS) * charge as f64;
+        let expected = averagine_isotope_ratio(mass, 1).max(f64::MIN_POSITIVE);
+        let factor = intensity[mono] / intensity[lighter] / expected;
+        if factor > config.isotope_ratio_tolerance || factor < 1.0 / config.isotope_ratio_tolerance
+        {
+            break;
+        }
+        isotope_error += 1;
+        mono = lighter;
+    }
+
+    Envelope {
+        charge,
+        isotope_error,
+        num_isotopes: 1 + heavier + isotope_error,
+    }
+}
+
+/// Most intense peak within `tolerance_ppm` of `target`
+fn find_peak(peaks: &PeakList, target: f64, tolerance_ppm: f64) -> Option<usize> {
+    let tolerance = target * tolerance_ppm * 1e-6;
+    let mz = peaks.mz();
+    let start = mz.partition_point(|&value| value < target - tolerance);
+    (start..mz.len())
+        .take_while(|&i| mz[i] <= target + tolerance)
+        .max_by(|&a, &b| peaks.intensity()[a].total_cmp(&peaks.intensity()[b]))
+}
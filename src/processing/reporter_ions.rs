@@ -0,0 +1,354 @@
+//! Reporter ion (TMT/iTRAQ) intensity extraction
+//!
+//! For every MS2/MS3 spectrum the most intense peak within a ppm tolerance of
+//! each reporter ion m/z is taken as the channel intensity. Intensities can be
+//! corrected for reagent isotope impurities with a lot-specific impurity
+//! matrix, so isobaric quantification does not require parsing search engine
+//! output.
+//!
+//! Results are stored as a wide table (`reporters.parquet`) with one row per
+//! spectrum and one `reporter_<channel>` column per channel.
+//!
+//! ## Impurity Correction
+//!
+//! The impurity matrix `M` is square with one row per channel (in channel
+//! order): `M[i][j]` is the fraction of reagent `i`'s signal that is observed in
+//! channel `j`, so each row sums to 1 and the diagonal holds the purity.
+//! Corrected intensities `x` solve `observed[j] = sum_i M[i][j] * x[i]`;
+//! negative solutions are clipped to zero.
+
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Float32Array, Float64Array, Int16Array, Int64Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+
+use super::ProcessingError;
+use crate::reader::MzPeakReader;
+
+/// Default file name for the reporter ion table
+pub const REPORTERS_FILE_NAME: &str = "reporters.parquet";
+
+/// Prefix of the per-channel intensity columns
+pub const REPORTER_COLUMN_PREFIX: &str = "reporter_";
+
+/// Isobaric labeling reagent set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReporterPlex {
+    /// iTRAQ 4-plex
+    Itraq4,
+    /// iTRAQ 8-plex
+    Itraq8,
+    /// TMT 6-plex
+    Tmt6,
+    /// TMT 10-plex
+    Tmt10,
+    /// TMT 11-plex
+    Tmt11,
+    /// TMTpro 16-plex
+    Tmt16,
+    /// TMTpro 18-plex
+    Tmt18,
+}
+
+/// TMT 11-plex channels (the 10-plex uses the first ten)
+const TMT11_CHANNELS: [(&str, f64); 11] = [
+    ("126", 126.127_726),
+    ("127N", 127.124_761),
+    ("127C", 127.131_081),
+    ("128N", 128.128_116),
+    ("128C", 128.134_436),
+    ("129N", 129.131_471),
+    ("129C", 129.137_790),
+    ("130N", 130.134_825),
+    ("130C", 130.141_145),
+    ("131N", 131.138_180),
+    ("131C", 131.144_500),
+];
+
+/// TMTpro 18-plex channels (the 16-plex uses the first sixteen)
+const TMTPRO18_CHANNELS: [(&str, f64); 18] = [
+    ("126", 126.127_726),
+    ("127N", 127.124_761),
+    ("127C", 127.131_081),
+    ("128N", 128.128_116),
+    ("128C", 128.134_436),
+    ("129N", 129.131_471),
+    ("129C", 129.137_790),
+    ("130N", 130.134_825),
+    ("130C", 130.141_145),
+    ("131N", 131.138_180),
+    ("131C", 131.144_500),
+    ("132N", 132.141_535),
+    ("132C", 132.147_855),
+    ("133N", 133.144_890),
+    ("133C", 133.151_210),
+    ("134N", 134.148_245),
+    ("134C", 134.154_565),
+    ("135N", 135.151_600),
+];
+
+const TMT6_CHANNELS: [(&str, f64); 6] = [
+    ("126", 126.127_726),
+    ("127", 127.124_761),
+    ("128", 128.134_436),
+    ("129", 129.131_471),
+    ("130", 130.141_145),
+    ("131", 131.138_180),
+];
+
+const ITRAQ4_CHANNELS: [(&str, f64); 4] = [
+    ("114", 114.111_2),
+    ("115", 115.108_3),
+    ("116", 116.111_6),
+    ("117", 117.115_0),
+];
+
+const ITRAQ8_CHANNELS: [(&str, f64); 8] = [
+    ("113", 113.107_9),
+    ("114", 114.111_2),
+    ("115", 115.108_3),
+    ("116", 116.111_6),
+    ("117", 117.115_0),
+    ("118", 118.112_0),
+    ("119", 119.115_3),
+    ("121", 121.122_0),
+];
+
+impl ReporterPlex {
+    /// Channel labels and reporter ion m/z values, in channel order
+    pub fn channels(&self) -> &'static [(&'static str, f64)] {
+        match self {
+            ReporterPlex::Itraq4 => &ITRAQ4_CHANNELS,
+            ReporterPlex::Itraq8 => &ITRAQ8_CHANNELS,
+            ReporterPlex::Tmt6 => &TMT6_CHANNELS,
+            ReporterPlex::Tmt10 => &TMT11_CHANNELS[..10],
+            ReporterPlex::Tmt11 => &TMT11_CHANNELS,
+            ReporterPlex::Tmt16 => &TMTPRO18_CHANNELS[..16],
+            ReporterPlex::Tmt18 => &TMTPRO18_CHANNELS,
+        }
+    }
+
+    /// Number of channels
+    pub fn num_channels(&self) -> usize {
+        self.channels().len()
+    }
+}
+
+/// Configuration for reporter ion extraction
+#[derive(Debug, Clone)]
+pub struct ReporterConfig {
+    /// Reagent set
+    pub plex: ReporterPlex,
+
+    /// Match tolerance around each reporter m/z (ppm)
+    pub tolerance_ppm: f64,
+
+    /// Isotope impurity matrix (None = no correction)
+    pub impurity_matrix: Option<Vec<Vec<f64>>>,
+}
+
+impl ReporterConfig {
+    /// Configuration with default tolerance and no impurity correction
+    pub fn new(plex: ReporterPlex) -> Self {
+        Self {
+            plex,
+            tolerance_ppm: 20.0,
+            impurity_matrix: None,
+        }
+    }
+}
+
+/// Reporter ion intensities of one spectrum
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReporterRow {
+    /// Spectrum ID
+    pub spectrum_id: i64,
+    /// Native scan number
+    pub scan_number: i64,
+    /// MS level (2 or 3)
+    pub ms_level: i16,
+    /// Retention time (seconds)
+    pub retention_time: f32,
+    /// Precursor m/z
+    pub precursor_mz: Option<f64>,
+    /// Channel intensities in channel order (impurity-corrected if configured)
+    pub intensities: Vec<f64>,
+}
+
+/// Reporter ion table for a run, written as `reporters.parquet`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReporterTable {
+    /// Reagent set
+    pub plex: ReporterPlex,
+    /// One row per MS2/MS3 spectrum
+    pub rows: Vec<ReporterRow>,
+}
+
+/// Extract reporter ion intensities with the default configuration for `plex`
+pub fn reporter_ions(
+    reader: &MzPeakReader,
+    plex: ReporterPlex,
+) -> Result<ReporterTable, ProcessingError> {
+    ReporterTable::extract(reader, &ReporterConfig::new(plex))
+}
+
+impl ReporterTable {
+    /// Extract reporter ion intensities from every MS2/MS3 spectrum
+    pub fn extract(
+        reader: &MzPeakReader,
+        config: &ReporterConfig,
+    ) -> Result<Self, ProcessingError> {
+        let channels = config.plex.channels();
+        if let Some(matrix) = &config.impurity_matrix {
+            if matrix.len() != channels.len() || matrix.iter().any(|r| r.len() != channels.len())
+            {
+                return Err(ProcessingError::InvalidData(format!(
+                    "impurity matrix must be {0}x{0} (one row per channel)",
+                    channels.len()
+                )));
+            }
+        }
+
+        let mut rows = Vec::new();
+        for spectrum in reader.iter_spectra_arrays_streaming()? {
+            let spectrum = spectrum?;
+            if !(2..=3).contains(&spectrum.ms_level) {
+                continue;
+            }
+
+            let mut observed = vec![0.0f64; channels.len()];
+            let mz_arrays = spectrum.mz_arrays()?;
+            let intensity_arrays = spectrum.intensity_arrays()?;
+            for (mzs, intensities) in mz_arrays.iter().zip(&intensity_arrays) {
+                for (&mz, &intensity) in mzs.values().iter().zip(intensities.values()) {
+                    for (channel, &(_, reporter_mz)) in channels.iter().enumerate() {
+                        if (mz - reporter_mz).abs() <= reporter_mz * config.tolerance_ppm * 1e-6 {
+                            observed[channel] = observed[channel].max(intensity as f64);
+                        }
+                    }
+                }
+            }
+
+            let intensities = match &config.impurity_matrix {
+                Some(matrix) => correct_impurities(&observed, matrix)?,
+                None => observed,
+            };
+            rows.push(ReporterRow {
+                spectrum_id: spectrum.spectrum_id,
+                scan_number: spectrum.scan_number,
+                ms_level: spectrum.ms_level,
+                retention_time: spectrum.retention_time,
+                precursor_mz: spectrum.precursor_mz,
+                intensities,
+            });
+        }
+
+        Ok(Self {
+            plex: config.plex,
+            rows,
+        })
+    }
+
+    /// Arrow schema of the reporter table
+    pub fn schema(&self) -> Schema {
+        let mut fields = vec![
+            Field::new("spectrum_id", DataType::Int64, false),
+            Field::new("scan_number", DataType::Int64, false),
+            Field::new("ms_level", DataType::Int16, false),
+            Field::new("retention_time", DataType::Float32, false),
+            Field::new("precursor_mz", DataType::Float64, true),
+        ];
+        fields.extend(self.plex.channels().iter().map(|(label, _)| {
+            Field::new(
+                format!("{}{}", REPORTER_COLUMN_PREFIX, label),
+                DataType::Float64,
+                false,
+            )
+        }));
+        Schema::new(fields)
+    }
+
+    /// Convert the table to an Arrow record batch
+    pub fn to_record_batch(&self) -> Result<RecordBatch, ProcessingError> {
+        let rows = &self.rows;
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.spectrum_id))),
+            Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.scan_number))),
+            Arc::new(Int16Array::from_iter_values(rows.iter().map(|r| r.ms_level))),
+            Arc::new(Float32Array::from_iter_values(
+                rows.iter().map(|r| r.retention_time),
+            )),
+            Arc::new(Float64Array::from_iter(rows.iter().map(|r| r.precursor_mz))),
+        ];
+        for channel in 0..self.plex.num_channels() {
+            columns.push(Arc::new(Float64Array::from_iter_values(
+                rows.iter().map(|r| r.intensities[channel]),
+            )));
+        }
+        Ok(RecordBatch::try_new(Arc::new(self.schema()), columns)?)
+    }
+
+    /// Write the table as a Parquet file (conventionally `reporters.parquet`)
+    pub fn write_parquet<P: AsRef<Path>>(&self, path: P) -> Result<(), ProcessingError> {
+        let batch = self.to_record_batch()?;
+        let props = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build();
+        let mut writer = ArrowWriter::try_new(File::create(path)?, batch.schema(), Some(props))?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+}
+
+/// Correct observed channel intensities for reagent isotope impurities
+///
+/// Solves `observed[j] = sum_i matrix[i][j] * x[i]` by Gaussian elimination
+/// with partial pivoting and clips negative intensities to zero.
+pub fn correct_impurities(
+    observed: &[f64],
+    matrix: &[Vec<f64>],
+) -> Result<Vec<f64>, ProcessingError> {
+    let n = observed.len();
+    // Augmented system A x = b with A = matrix^T
+    let mut a: Vec<Vec<f64>> = (0..n)
+        .map(|j| {
+            let mut row: Vec<f64> = (0..n).map(|i| matrix[i][j]).collect();
+            row.push(observed[j]);
+            row
+        })
+        .collect();
+
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&x, &y| a[x][col].abs().total_cmp(&a[y][col].abs()))
+            .unwrap_or(col);
+        if a[pivot][col].abs() < 1e-12 {
+            return Err(ProcessingError::InvalidData(
+                "impurity matrix is singular".to_string(),
+            ));
+        }
+        a.swap(col, pivot);
+        let (upper, lower) = a.split_at_mut(col + 1);
+        let pivot_row = &upper[col];
+        for row in lower.iter_mut() {
+            let factor = row[col] / pivot_row[col];
+            for (value, pivot_value) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                *value -= factor * pivot_value;
+            }
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (a[row][n] - sum) / a[row][row];
+    }
+    Ok(x.into_iter().map(|v| v.max(0.0)).collect())
+}
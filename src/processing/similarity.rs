@@ -0,0 +1,304 @@
+//! Spectral entropy and spectrum similarity metrics
+//!
+//! Compares two spectra with tolerant, one-to-one peak matching:
+//!
+//! - [`cosine_similarity`]: cosine of the raw intensity vectors
+//! - [`modified_dot_product`]: cosine of weighted intensities
+//!   (`intensity^a * mz^b`, see [`DotProductWeights`])
+//! - [`entropy_similarity`] / [`weighted_entropy_similarity`]: the spectral
+//!   entropy similarity of Li et al. (Nature Methods, 2021)
+//!
+//! All scores are in `0.0..=1.0`, with `1.0` for identical spectra. Inputs are
+//! [`PeakList`]s, which convert from [`SpectrumArrays`], [`PeakArrays`] and
+//! reader [`SpectrumArraysView`]s.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use mzpeak::processing::{entropy_similarity, MassTolerance, PeakList};
+//! use mzpeak::reader::MzPeakReader;
+//!
+//! let reader = MzPeakReader::open("data.mzpeak")?;
+//! let query = PeakList::try_from(&reader.get_spectrum_arrays(100)?.unwrap())?;
+//! let library = PeakList::try_from(&reader.get_spectrum_arrays(200)?.unwrap())?;
+//! println!("{:.3}", entropy_similarity(&query, &library, MassTolerance::Da(0.02)));
+//! # Ok::<(), mzpeak::processing::ProcessingError>(())
+//! ```
+
+use super::MassTolerance;
+use crate::reader::{ReaderError, SpectrumArraysView};
+use crate::writer::{PeakArrays, SpectrumArrays};
+
+/// Peaks sorted by m/z, with non-positive and non-finite peaks removed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PeakList {
+    mz: Vec<f64>,
+    intensity: Vec<f64>,
+}
+
+impl PeakList {
+    /// Build a peak list from parallel m/z and intensity slices
+    pub fn new(mz: &[f64], intensity: &[f32]) -> Self {
+        let mut peaks: Vec<(f64, f64)> = mz
+            .iter()
+            .zip(intensity)
+            .map(|(&mz, &intensity)| (mz, f64::from(intensity)))
+            .filter(|&(mz, intensity)| mz.is_finite() && intensity.is_finite() && intensity > 0.0)
+            .collect();
+        peaks.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let (mz, intensity) = peaks.into_iter().unzip();
+        Self { mz, intensity }
+    }
+
+    /// Number of peaks
+    pub fn len(&self) -> usize {
+        self.mz.len()
+    }
+
+    /// Whether the list has no peaks
+    pub fn is_empty(&self) -> bool {
+        self.mz.is_empty()
+    }
+
+    /// Peak m/z values, ascending
+    pub fn mz(&self) -> &[f64] {
+        &self.mz
+    }
+
+    /// Peak intensities, in m/z order
+    pub fn intensity(&self) -> &[f64] {
+        &self.intensity
+    }
+}
+
+impl From<&PeakArrays> for PeakList {
+    fn from(peaks: &PeakArrays) -> Self {
+        Self::new(&peaks.mz, &peaks.intensity)
+    }
+}
+
+impl From<&SpectrumArrays> for PeakList {
+    fn from(spectrum: &SpectrumArrays) -> Self {
+        Self::from(&spectrum.peaks)
+    }
+}
+
+impl TryFrom<&SpectrumArraysView> for PeakList {
+    type Error = ReaderError;
+
+    fn try_from(view: &SpectrumArraysView) -> Result<Self, Self::Error> {
+        let mut mz = Vec::with_capacity(view.peak_count());
+        let mut intensity = Vec::with_capacity(view.peak_count());
+        for array in view.mz_arrays()? {
+            mz.extend_from_slice(array.values());
+        }
+        for array in view.intensity_arrays()? {
+            intensity.extend_from_slice(array.values());
+        }
+        Ok(Self::new(&mz, &intensity))
+    }
+}
+
+/// Intensity and m/z exponents of the [`modified_dot_product`] peak weights
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DotProductWeights {
+    /// Exponent applied to the intensity
+    pub intensity_power: f64,
+    /// Exponent applied to the m/z
+    pub mz_power: f64,
+}
+
+impl DotProductWeights {
+    /// Raw intensities (plain cosine)
+    pub const COSINE: Self = Self {
+        intensity_power: 1.0,
+        mz_power: 0.0,
+    };
+
+    /// Square-root intensities, damping the dominance of a few large peaks
+    pub const SQRT_INTENSITY: Self = Self {
+        intensity_power: 0.5,
+        mz_power: 0.0,
+    };
+
+    /// Stein & Scott (1994) weights for EI library search
+    pub const STEIN_SCOTT: Self = Self {
+        intensity_power: 0.6,
+        mz_power: 3.0,
+    };
+}
+
+impl Default for DotProductWeights {
+    fn default() -> Self {
+        Self::SQRT_INTENSITY
+    }
+}
+
+/// Match peaks of `a` and `b` one-to-one within `tolerance`
+///
+/// Candidate pairs are taken greedily by descending intensity product, so
+/// each peak is used at most once. The tolerance window is centred on the
+/// peaks of `a`. Returns `(index_in_a, index_in_b)` pairs ordered by `a`.
+pub fn match_peaks(a: &PeakList, b: &PeakList, tolerance: MassTolerance) -> Vec<(usize, usize)> {
+    let mut candidates = Vec::new();
+    for (i, &mz) in a.mz.iter().enumerate() {
+        let window = tolerance.window(mz);
+        let first = b.mz.partition_point(|&other| other < mz - window);
+        for (j, &other) in b.mz.iter().enumerate().skip(first) {
+            if other > mz + window {
+                break;
+            }
+            candidates.push((a.intensity[i] * b.intensity[j], i, j));
+        }
+    }
+    candidates.sort_by(|x, y| y.0.total_cmp(&x.0));
+
+    let mut used_a = vec![false; a.len()];
+    let mut used_b = vec![false; b.len()];
+    let mut pairs = Vec::new();
+    for (_, i, j) in candidates {
+        if !used_a[i] && !used_b[j] {
+            used_a[i] = true;
+            used_b[j] = true;
+            pairs.push((i, j));
+        }
+    }
+    pairs.sort_unstable();
+    pairs
+}
+
+/// Cosine similarity of the matched raw intensities
+pub fn cosine_similarity(a: &PeakList, b: &PeakList, tolerance: MassTolerance) -> f64 {
+    modified_dot_product(a, b, tolerance, DotProductWeights::COSINE)
+}
+
+/// Cosine similarity of peak weights `intensity^intensity_power * mz^mz_power`
+pub fn modified_dot_product(
+    a: &PeakList,
+    b: &PeakList,
+    tolerance: MassTolerance,
+    weights: DotProductWeights,
+) -> f64 {
+    let weigh = |peaks: &PeakList| -> Vec<f64> {
+        peaks
+            .mz
+            .iter()
+            .zip(&peaks.intensity)
+            .map(|(&mz, &intensity)| {
+                intensity.powf(weights.intensity_power) * mz.powf(weights.mz_power)
+            })
+            .collect()
+    };
+    let (weights_a, weights_b) = (weigh(a), weigh(b));
+    let norm_a: f64 = weights_a.iter().map(|w| w * w).sum();
+    let norm_b: f64 = weights_b.iter().map(|w| w * w).sum();
+    if norm_a <= 0.0 || norm_b <= 0.0 {
+        return 0.0;
+    }
+
+    let dot: f64 = match_peaks(a, b, tolerance)
+        .into_iter()
+        .map(|(i, j)| weights_a[i] * weights_b[j])
+        .sum();
+    (dot / (norm_a * norm_b).sqrt()).clamp(0.0, 1.0)
+}
+
+/// Shannon entropy (in nats) of the intensity distribution
+pub fn spectral_entropy(peaks: &PeakList) -> f64 {
+    entropy(&probabilities(&peaks.intensity))
+}
+
+/// Spectral entropy divided by its maximum, `ln(n)`, giving `0.0..=1.0`
+///
+/// Spectra with fewer than two peaks have a normalized entropy of zero.
+pub fn normalized_spectral_entropy(peaks: &PeakList) -> f64 {
+    if peaks.len() < 2 {
+        return 0.0;
+    }
+    spectral_entropy(peaks) / (peaks.len() as f64).ln()
+}
+
+/// Unweighted spectral entropy similarity
+///
+/// `1 - (2 * S(AB) - S(A) - S(B)) / ln(4)`, where `S(AB)` is the entropy of
+/// the merged spectrum with both intensity distributions at half weight.
+pub fn entropy_similarity(a: &PeakList, b: &PeakList, tolerance: MassTolerance) -> f64 {
+    entropy_similarity_of(
+        a,
+        b,
+        tolerance,
+        probabilities(&a.intensity),
+        probabilities(&b.intensity),
+    )
+}
+
+/// Entropy similarity with low-entropy spectra reweighted
+///
+/// Spectra with an entropy below 3 nats have their intensities raised to
+/// `0.25 + 0.25 * S` before comparison, as in the reference implementation,
+/// which keeps a single dominant peak from deciding the score.
+pub fn weighted_entropy_similarity(a: &PeakList, b: &PeakList, tolerance: MassTolerance) -> f64 {
+    entropy_similarity_of(
+        a,
+        b,
+        tolerance,
+        entropy_weighted(&a.intensity),
+        entropy_weighted(&b.intensity),
+    )
+}
+
+fn entropy_similarity_of(
+    a: &PeakList,
+    b: &PeakList,
+    tolerance: MassTolerance,
+    pa: Vec<f64>,
+    pb: Vec<f64>,
+) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let mut merged: Vec<f64> = Vec::with_capacity(pa.len() + pb.len());
+    let mut matched_a = vec![false; pa.len()];
+    let mut matched_b = vec![false; pb.len()];
+    for (i, j) in match_peaks(a, b, tolerance) {
+        matched_a[i] = true;
+        matched_b[j] = true;
+        merged.push((pa[i] + pb[j]) / 2.0);
+    }
+    for (p, matched) in pa.iter().zip(matched_a).chain(pb.iter().zip(matched_b)) {
+        if !matched {
+            merged.push(p / 2.0);
+        }
+    }
+
+    let divergence = 2.0 * entropy(&merged) - entropy(&pa) - entropy(&pb);
+    (1.0 - divergence / 4f64.ln()).clamp(0.0, 1.0)
+}
+
+fn probabilities(intensity: &[f64]) -> Vec<f64> {
+    let total: f64 = intensity.iter().sum();
+    if total <= 0.0 {
+        return vec![0.0; intensity.len()];
+    }
+    intensity.iter().map(|i| i / total).collect()
+}
+
+fn entropy(probabilities: &[f64]) -> f64 {
+    -probabilities
+        .iter()
+        .filter(|&&p| p > 0.0)
+        .map(|&p| p * p.ln())
+        .sum::<f64>()
+}
+
+fn entropy_weighted(intensity: &[f64]) -> Vec<f64> {
+    let p = probabilities(intensity);
+    let s = entropy(&p);
+    if s >= 3.0 {
+        return p;
+    }
+    let power = 0.25 + 0.25 * s;
+    let weighted: Vec<f64> = p.iter().map(|p| p.powf(power)).collect();
+    probabilities(&weighted)
+}
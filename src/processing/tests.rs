@@ -0,0 +1,671 @@
+use super::*;
+use crate::metadata::MzPeakMetadata;
+use crate::reader::MzPeakReader;
+use crate::writer::{MzPeakWriter, PeakArrays, SpectrumArrays, WriterConfig};
+use tempfile::tempdir;
+
+/// Two cycles of a 3-window scheme: 400-425, 424-450 (1 m/z overlap), 455-480 (gap)
+fn dia_acquisitions() -> Vec<(f64, f32, f32, f32)> {
+    let mut acquisitions = Vec::new();
+    for cycle in 0..2 {
+        let start = cycle as f32 * 2.0;
+        acquisitions.push((412.5, 12.5, 12.5, start + 0.1));
+        acquisitions.push((437.0, 13.0, 13.0, start + 0.2));
+        acquisitions.push((467.5, 12.5, 12.5, start + 0.3));
+    }
+    acquisitions
+}
+
+#[test]
+fn test_dia_scheme_layout() {
+    let scheme = DiaScheme::from_acquisitions(dia_acquisitions()).expect("DIA detected");
+
+    assert_eq!(scheme.num_ms2_spectra, 6);
+    assert_eq!(scheme.windows.len(), 3);
+    assert_eq!(scheme.windows[0].lower_mz, 400.0);
+    assert_eq!(scheme.windows[1].width(), 26.0);
+    assert!(scheme.windows.iter().all(|w| w.acquisitions == 2));
+    assert_eq!(
+        scheme.windows.iter().map(|w| w.cycle_position).collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+
+    assert_eq!(scheme.overlaps.len(), 1);
+    assert_eq!((scheme.overlaps[0].lower_window, scheme.overlaps[0].upper_window), (0, 1));
+    assert!((scheme.overlaps[0].width() - 1.0).abs() < 1e-9);
+
+    assert_eq!(scheme.gaps.len(), 1);
+    assert_eq!((scheme.gaps[0].start_mz, scheme.gaps[0].end_mz), (450.0, 455.0));
+
+    assert_eq!(scheme.cycle_time.num_cycles, 2);
+    let median = scheme.cycle_time.median_seconds.unwrap();
+    assert!((median - 2.0).abs() < 1e-6);
+    assert_eq!(scheme.mz_range(), Some((400.0, 480.0)));
+}
+
+#[test]
+fn test_dia_scheme_rejects_dda() {
+    // Every precursor is isolated once: looks like DDA, not DIA
+    let dda = (0..10).map(|i| (400.0 + i as f64 * 7.3, 0.8, 0.8, i as f32));
+    assert!(DiaScheme::from_acquisitions(dda).is_none());
+    assert!(DiaScheme::from_acquisitions(Vec::new()).is_none());
+}
+
+#[test]
+fn test_dia_scheme_export() -> Result<(), Box<dyn std::error::Error>> {
+    let scheme = DiaScheme::from_acquisitions(dia_acquisitions()).expect("DIA detected");
+
+    let json: serde_json::Value = serde_json::from_str(&scheme.to_json()?)?;
+    assert_eq!(json["windows"].as_array().map(Vec::len), Some(3));
+    assert_eq!(json["cycle_time"]["num_cycles"], 2);
+
+    let svg = scheme.to_svg();
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert_eq!(svg.matches("fill=\"#1f77b4\"").count(), 3);
+    Ok(())
+}
+
+#[test]
+fn test_dia_scheme_detect_from_file() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().join("dia.parquet");
+
+    let mut writer = MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), WriterConfig::default())?;
+    for (i, (target, lower, upper, rt)) in dia_acquisitions().into_iter().enumerate() {
+        let mut ms2 = SpectrumArrays::new_ms2(
+            i as i64,
+            i as i64 + 1,
+            rt,
+            1,
+            target,
+            PeakArrays::new(vec![200.0], vec![10.0]),
+        );
+        ms2.isolation_window_lower = Some(lower);
+        ms2.isolation_window_upper = Some(upper);
+        writer.write_spectrum_arrays(&ms2)?;
+    }
+    writer.finish()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    let scheme = DiaScheme::detect(&reader)?.expect("DIA detected");
+    assert_eq!(scheme.windows.len(), 3);
+    assert_eq!(scheme.overlaps.len(), 1);
+    Ok(())
+}
+
+fn feature(feature_id: usize, mz: f64, charge: Option<i16>, apex_intensity: f32) -> Feature {
+    Feature {
+        feature_id,
+        mz,
+        charge,
+        rt_apex: 64.0,
+        rt_start: 60.0,
+        rt_end: 68.0,
+        apex_intensity,
+        area: apex_intensity as f64 * 4.0,
+        num_isotopes: if charge.is_some() { 3 } else { 1 },
+        num_scans: 9,
+    }
+}
+
+#[test]
+fn test_inclusion_list_csv() -> Result<(), Box<dyn std::error::Error>> {
+    let features = vec![
+        feature(0, 445.12, Some(1), 5000.0),
+        feature(1, 500.0, Some(2), 10000.0),
+        feature(2, 612.3, Some(3), 2000.0),
+        feature(3, 700.0, None, 50000.0),
+    ];
+    let config = InclusionListConfig {
+        top_n: 1,
+        charges: 2..=4,
+        rt_window_seconds: Some(30.0),
+        collision_energy: Some(27.0),
+    };
+    let entries = build_inclusion_list(&features, &config);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].charge, 2);
+    assert_eq!((entries[0].rt_start, entries[0].rt_end), (49.0, 79.0));
+
+    // Without a fixed window the feature bounds are used
+    let config = InclusionListConfig {
+        rt_window_seconds: None,
+        ..config
+    };
+    let entries = build_inclusion_list(&features, &config);
+    assert_eq!((entries[0].rt_start, entries[0].rt_end), (60.0, 68.0));
+
+    let mut csv = Vec::new();
+    write_inclusion_list_csv(&entries, &mut csv)?;
+    let csv = String::from_utf8(csv)?;
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "mz,charge,rt_start_min,rt_end_min,collision_energy");
+    assert_eq!(lines[1], "500.0000,2,1.0000,1.1333,27");
+    Ok(())
+}
+
+/// Gaussian elution profile of a doubly charged peptide at 500.0 m/z (3 isotopes)
+/// plus a singly charged contaminant at 445.12, over 9 MS1 scans
+fn synthetic_ms1_scans() -> Vec<(f32, Vec<(f64, f32)>)> {
+    let spacing = feature_detect::C13_C12_MASS_DIFF;
+    (0..9)
+        .map(|scan| {
+            let rt = 60.0 + scan as f32;
+            let profile = (-((scan as f32 - 4.0).powi(2)) / 4.0).exp();
+            let peaks = vec![
+                (445.12, 5000.0),
+                (445.12 + spacing, 1000.0),
+                (500.0, 10000.0 * profile),
+                (500.0 + spacing / 2.0, 6000.0 * profile),
+                (500.0 + spacing, 2000.0 * profile),
+            ];
+            (rt, peaks)
+        })
+        .collect()
+}
+
+fn synthetic_features() -> Vec<Feature> {
+    let config = FeatureDetectionConfig::default();
+    let mut builder = TraceBuilder::new(config.clone());
+    for (rt, peaks) in synthetic_ms1_scans() {
+        builder.push_scan(rt, peaks);
+    }
+    group_isotopes(builder.finish(), &config)
+}
+
+#[test]
+fn test_trace_builder_links_scans() {
+    let mut builder = TraceBuilder::new(FeatureDetectionConfig {
+        max_missing_scans: 1,
+        ..Default::default()
+    });
+    builder.push_scan(1.0, vec![(600.0, 100.0), (700.0, 50.0)]);
+    builder.push_scan(2.0, vec![(600.002, 300.0)]);
+    // 700.0 missed one scan and may continue; 800.0 only appears twice
+    builder.push_scan(3.0, vec![(600.001, 200.0), (700.0, 60.0), (800.0, 10.0)]);
+    builder.push_scan(4.0, vec![(800.0, 10.0)]);
+    let mut traces = builder.finish();
+    traces.sort_by(|a, b| a.mz.total_cmp(&b.mz));
+
+    assert_eq!(traces.len(), 1);
+    let trace = &traces[0];
+    assert_eq!(trace.num_scans, 3);
+    assert_eq!(trace.rt_apex, 2.0);
+    assert_eq!(trace.apex_intensity, 300.0);
+    assert!((trace.mz - 600.001_5).abs() < 1e-3);
+    assert!((trace.area - 450.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_feature_detection_assigns_charge() {
+    let features = synthetic_features();
+    assert_eq!(features.len(), 2);
+
+    let peptide = features.iter().find(|f| (f.mz - 500.0).abs() < 1e-3).unwrap();
+    assert_eq!(peptide.charge, Some(2));
+    assert_eq!(peptide.num_isotopes, 3);
+    assert_eq!(peptide.rt_apex, 64.0);
+
+    let contaminant = features.iter().find(|f| (f.mz - 445.12).abs() < 1e-3).unwrap();
+    assert_eq!(contaminant.charge, Some(1));
+    assert_eq!(contaminant.num_isotopes, 2);
+}
+
+#[test]
+fn test_inclusion_list_from_detected_features() {
+    let config = InclusionListConfig {
+        rt_window_seconds: Some(30.0),
+        ..Default::default()
+    };
+    let entries = build_inclusion_list(&synthetic_features(), &config);
+    assert_eq!(entries.len(), 1);
+    assert!((entries[0].mz - 500.0).abs() < 1e-3);
+    assert_eq!(entries[0].charge, 2);
+    assert_eq!((entries[0].rt_start, entries[0].rt_end), (49.0, 79.0));
+}
+
+#[test]
+fn test_trace_split_at_valley() {
+    let config = FeatureDetectionConfig::default();
+    let mut builder = TraceBuilder::new(config);
+    let profile = [10.0, 50.0, 100.0, 50.0, 10.0, 5.0, 10.0, 60.0, 120.0, 60.0, 10.0];
+    for (scan, &intensity) in profile.iter().enumerate() {
+        builder.push_scan(scan as f32, vec![(700.0, intensity)]);
+    }
+    let mut traces = builder.finish();
+    traces.sort_by(|a, b| a.rt_start.total_cmp(&b.rt_start));
+
+    assert_eq!(traces.len(), 2);
+    assert_eq!((traces[0].rt_apex, traces[0].apex_intensity), (2.0, 100.0));
+    assert_eq!((traces[1].rt_apex, traces[1].apex_intensity), (8.0, 120.0));
+    assert_eq!(traces[0].num_scans + traces[1].num_scans, profile.len());
+}
+
+#[test]
+fn test_feature_detection_corrects_monoisotopic_peak() {
+    // Heavy doubly charged peptide where the first isotope is the most intense
+    let config = FeatureDetectionConfig::default();
+    let spacing = feature_detect::C13_C12_MASS_DIFF / 2.0;
+    let mut builder = TraceBuilder::new(config.clone());
+    for scan in 0..5 {
+        let peaks = [7500.0, 10000.0, 6660.0, 2960.0]
+            .iter()
+            .enumerate()
+            .map(|(n, &intensity)| (1200.0 + n as f64 * spacing, intensity))
+            .collect::<Vec<_>>();
+        builder.push_scan(scan as f32, peaks);
+    }
+    let features = group_isotopes(builder.finish(), &config);
+
+    assert_eq!(features.len(), 1);
+    assert!((features[0].mz - 1200.0).abs() < 1e-6);
+    assert_eq!(features[0].charge, Some(2));
+    assert_eq!(features[0].num_isotopes, 4);
+}
+
+#[test]
+fn test_feature_table_parquet() -> Result<(), Box<dyn std::error::Error>> {
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let table = FeatureTable {
+        features: synthetic_features(),
+    };
+    let dir = tempdir()?;
+    let path = dir.path().join(FEATURES_FILE_NAME);
+    table.write_parquet(&path)?;
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path)?)?.build()?;
+    let batches: Vec<_> = reader.collect::<Result<_, _>>()?;
+    assert_eq!(batches[0].num_rows(), 2);
+    assert_eq!(batches[0].schema().as_ref(), &FeatureTable::schema());
+    let charges = batches[0]
+        .column_by_name(feature_columns::CHARGE)
+        .unwrap()
+        .as_any()
+        .downcast_ref::<arrow::array::Int16Array>()
+        .unwrap();
+    assert_eq!(arrow::array::Array::null_count(charges), 0);
+    Ok(())
+}
+
+#[test]
+fn test_reporter_plex_channels() {
+    assert_eq!(ReporterPlex::Tmt6.num_channels(), 6);
+    assert_eq!(ReporterPlex::Tmt10.num_channels(), 10);
+    assert_eq!(ReporterPlex::Tmt16.num_channels(), 16);
+    assert_eq!(ReporterPlex::Tmt18.channels()[17].0, "135N");
+    assert_eq!(ReporterPlex::Itraq8.channels()[7].0, "121");
+}
+
+#[test]
+fn test_correct_impurities() -> Result<(), Box<dyn std::error::Error>> {
+    // Channel 0 leaks 10% into channel 1
+    let matrix = vec![vec![0.9, 0.1], vec![0.0, 1.0]];
+    let corrected = correct_impurities(&[900.0, 600.0], &matrix)?;
+    assert!((corrected[0] - 1000.0).abs() < 1e-9);
+    assert!((corrected[1] - 500.0).abs() < 1e-9);
+
+    // Negative solutions are clipped
+    let corrected = correct_impurities(&[900.0, 50.0], &matrix)?;
+    assert_eq!(corrected[1], 0.0);
+
+    assert!(correct_impurities(&[1.0, 1.0], &[vec![1.0, 1.0], vec![1.0, 1.0]]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_reporter_ion_extraction() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().join("tmt.parquet");
+
+    let mut writer = MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), WriterConfig::default())?;
+    let ms1 = SpectrumArrays::new_ms1(
+        0,
+        1,
+        60.0,
+        1,
+        PeakArrays::new(vec![126.1277, 500.0], vec![1.0e6, 1.0e6]),
+    );
+    // 126 plus a weaker interfering peak within tolerance, 127N, nothing at 127C;
+    // 127.1280 is 10 ppm from 127N but 24 ppm from 127C
+    let ms2 = SpectrumArrays::new_ms2(
+        1,
+        2,
+        61.0,
+        1,
+        500.0,
+        PeakArrays::new(
+            vec![126.1277, 126.1290, 127.1248, 200.0],
+            vec![1000.0, 50.0, 2000.0, 9999.0],
+        ),
+    );
+    writer.write_spectrum_arrays(&ms1)?;
+    writer.write_spectrum_arrays(&ms2)?;
+    writer.finish()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    let table = reporter_ions(&reader, ReporterPlex::Tmt10)?;
+    assert_eq!(table.rows.len(), 1);
+    let row = &table.rows[0];
+    assert_eq!(row.spectrum_id, 1);
+    assert_eq!(row.precursor_mz, Some(500.0));
+    assert_eq!(&row.intensities[..3], &[1000.0, 2000.0, 0.0]);
+
+    let batch = table.to_record_batch()?;
+    assert_eq!(batch.num_columns(), 5 + 10);
+    assert!(batch.column_by_name("reporter_131N").is_some());
+    table.write_parquet(dir.path().join(REPORTERS_FILE_NAME))?;
+
+    let mut config = ReporterConfig::new(ReporterPlex::Tmt6);
+    config.impurity_matrix = Some(vec![vec![1.0]]);
+    assert!(ReporterTable::extract(&reader, &config).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_scan_diagnostic_ions() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().join("ptm.parquet");
+
+    let mut writer = MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), WriterConfig::default())?;
+    // Doubly charged phosphopeptide at 600.0: H3PO4 loss at 600.0 - 97.9769 / 2 = 551.0116
+    let mut phospho = SpectrumArrays::new_ms2(
+        0,
+        1,
+        60.0,
+        1,
+        600.0,
+        PeakArrays::new(vec![300.0, 551.0117], vec![100.0, 400.0]),
+    );
+    phospho.precursor_charge = Some(2);
+    // Glycopeptide with HexNAc oxonium ion, unknown precursor charge
+    let glyco = SpectrumArrays::new_ms2(
+        1,
+        2,
+        61.0,
+        1,
+        900.0,
+        PeakArrays::new(vec![204.0866, 500.0], vec![50.0, 200.0]),
+    );
+    let plain = SpectrumArrays::new_ms2(
+        2,
+        3,
+        62.0,
+        1,
+        700.0,
+        PeakArrays::new(vec![250.0], vec![10.0]),
+    );
+    writer.write_spectrum_arrays(&phospho)?;
+    writer.write_spectrum_arrays(&glyco)?;
+    writer.write_spectrum_arrays(&plain)?;
+    writer.finish()?;
+
+    let mut targets = vec![DiagnosticTarget::phospho_neutral_loss()];
+    targets.extend(DiagnosticTarget::oxonium_ions());
+
+    let reader = MzPeakReader::open(&path)?;
+    let scan = reader.scan_diagnostic_ions(&targets, MassTolerance::Ppm(20.0))?;
+
+    assert_eq!(scan.num_ms2_spectra, 3);
+    assert_eq!(scan.flagged_spectrum_ids(), vec![0, 1]);
+    assert_eq!(scan.hits[0].kind, "neutral_loss");
+    assert_eq!(scan.hits[0].relative_intensity, 1.0);
+    assert_eq!(scan.hits[1].target, "HexNAc");
+    assert!((scan.hits[1].relative_intensity - 0.25).abs() < 1e-6);
+
+    let counts = scan.flagged_spectra_per_target();
+    assert_eq!(counts.get("H3PO4 loss"), Some(&1));
+
+    let out = dir.path().join(ANNOTATIONS_FILE_NAME);
+    scan.write_parquet(&out)?;
+    assert!(out.exists());
+    Ok(())
+}
+
+#[test]
+fn test_xic_and_mz_range_query() -> Result<(), Box<dyn std::error::Error>> {
+    use arrow::array::AsArray;
+    use arrow::datatypes::Float64Type;
+
+    let dir = tempdir()?;
+    let path = dir.path().join("xic.parquet");
+
+    let mut writer = MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), WriterConfig::default())?;
+    let scans = [
+        (10.0, vec![300.0, 445.1201, 445.1205, 600.0], vec![1.0, 100.0, 50.0, 7.0]),
+        (11.0, vec![300.0, 600.0], vec![2.0, 8.0]),
+        (12.0, vec![445.1199, 524.2650], vec![300.0, 40.0]),
+    ];
+    for (i, (rt, mz, intensity)) in scans.into_iter().enumerate() {
+        let peaks = PeakArrays::new(mz, intensity);
+        writer.write_spectrum_arrays(&SpectrumArrays::new_ms1(i as i64, i as i64 + 1, rt, 1, peaks))?;
+    }
+    let fragment = PeakArrays::new(vec![445.1200], vec![1e6]);
+    writer.write_spectrum_arrays(&SpectrumArrays::new_ms2(3, 4, 12.5, 1, 500.0, fragment))?;
+    writer.finish()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    let xics = reader.extract_xics(&[445.1200, 524.2648], MassTolerance::Ppm(10.0))?;
+    assert_eq!(xics.len(), 2);
+    assert_eq!(xics[0].retention_times, vec![10.0, 11.0, 12.0]);
+    assert_eq!(xics[0].intensities, vec![150.0, 0.0, 300.0]);
+    assert_eq!(xics[1].intensities, vec![0.0, 0.0, 40.0]);
+
+    let batches = reader.peaks_in_mz_range(445.0, 446.0)?;
+    let mz: Vec<f64> = batches
+        .iter()
+        .flat_map(|b| b.column_by_name("mz").unwrap().as_primitive::<Float64Type>().values().to_vec())
+        .collect();
+    assert_eq!(mz, vec![445.1201, 445.1205, 445.1199, 445.1200]);
+    Ok(())
+}
+
+#[test]
+fn test_ion_image_physical_scale() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::metadata::{AxisOrientation, ImagingMetadata};
+
+    let dir = tempdir()?;
+    let path = dir.path().join("image.parquet");
+
+    let mut metadata = MzPeakMetadata::new();
+    metadata.imaging = ImagingMetadata::from_stage_positions(&[
+        (1, 1, 1000.0, 2000.0),
+        (3, 2, 1100.0, 2025.0),
+    ]);
+    let mut writer = MzPeakWriter::new_file(&path, &metadata, WriterConfig::default())?;
+    let pixels = [(1, 1, 10.0), (2, 1, 20.0), (3, 2, 30.0)];
+    for (i, (x, y, intensity)) in pixels.into_iter().enumerate() {
+        let peaks = PeakArrays::new(vec![500.0, 885.5498], vec![1.0, intensity]);
+        let mut spectrum = SpectrumArrays::new_ms1(i as i64, i as i64 + 1, i as f32, 1, peaks);
+        spectrum.pixel_x = Some(x);
+        spectrum.pixel_y = Some(y);
+        writer.write_spectrum_arrays(&spectrum)?;
+    }
+    writer.finish()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    let image = reader.extract_ion_image(885.5498, MassTolerance::Ppm(5.0))?;
+    assert_eq!((image.width, image.height), (3, 2));
+    assert_eq!(image.intensities, vec![10.0, 20.0, 0.0, 0.0, 0.0, 30.0]);
+    assert_eq!(image.intensity(3, 2), Some(30.0));
+    assert_eq!(image.intensity(4, 1), None);
+    assert_eq!(image.pixel_size_x_um, Some(50.0));
+    assert_eq!(image.pixel_size_y_um, Some(25.0));
+    assert_eq!(image.axis_orientation, Some(AxisOrientation::YUp));
+    assert_eq!(image.extent_um(), Some((150.0, 50.0)));
+    assert_eq!(image.position_um(2, 2), Some((1050.0, 2025.0)));
+    Ok(())
+}
+
+#[test]
+fn test_isotope_distributions() -> Result<(), Box<dyn std::error::Error>> {
+    let glucose: Formula = "C6H12O6".parse()?;
+    assert_eq!(glucose.count("C"), 6);
+    assert_eq!(glucose.to_string(), "C6H12O6");
+    assert!((glucose.monoisotopic_mass() - 180.063_388).abs() < 1e-5);
+    let pattern = IsotopeDistribution::for_formula(&glucose, 3);
+    let relative = pattern.relative_abundances();
+    assert_eq!(pattern.most_abundant(), 0);
+    assert!((relative[1] - 0.0686).abs() < 1e-3, "{:?}", relative);
+    assert!((pattern.peaks[1].mass - pattern.peaks[0].mass - 1.0030).abs() < 1e-3);
+
+    // Chlorine doublet, two nominal masses apart
+    let chloromethane = IsotopeDistribution::for_formula(&"CH3Cl".parse()?, 3);
+    let relative = chloromethane.relative_abundances();
+    assert!(relative[1] < 0.02 && (relative[2] - 0.3200).abs() < 2e-3, "{:?}", relative);
+
+    assert!("C6h12".parse::<Formula>().is_err());
+    assert!("Xx2".parse::<Formula>().is_err());
+    assert!("".parse::<Formula>().is_err());
+
+    // Averagine: monoisotopic peak dominates small peptides, not large ones
+    let averagine = Formula::averagine(1800.0);
+    assert!((averagine.monoisotopic_mass() - 1800.0).abs() < 0.6);
+    assert_eq!(IsotopeDistribution::averagine(800.0, 4).most_abundant(), 0);
+    assert_eq!(IsotopeDistribution::averagine(2500.0, 4).most_abundant(), 1);
+    let ratio = averagine_isotope_ratio(1800.0, 1);
+    assert!(ratio > 0.9 && ratio < 1.1, "{}", ratio);
+    assert_eq!(averagine_isotope_ratio(1800.0, 0), 0.0);
+
+    let mz = IsotopeDistribution::averagine(1800.0, 2).mz(2);
+    assert!((mz[1] - mz[0] - isotopes::C13_C12_MASS_DIFF / 2.0).abs() < 2e-3);
+    Ok(())
+}
+
+#[test]
+fn test_spectral_entropy() {
+    let uniform = PeakList::new(&[100.0, 200.0, 300.0, 400.0], &[5.0, 5.0, 5.0, 5.0]);
+    assert!((spectral_entropy(&uniform) - 4f64.ln()).abs() < 1e-12);
+    assert!((normalized_spectral_entropy(&uniform) - 1.0).abs() < 1e-12);
+
+    // Zero and non-finite peaks are dropped, the rest sorted by m/z
+    let peaks = PeakList::new(&[300.0, 100.0, 200.0, f64::NAN], &[1.0, 2.0, 0.0, 4.0]);
+    assert_eq!(peaks.mz(), &[100.0, 300.0]);
+    assert_eq!(peaks.intensity(), &[2.0, 1.0]);
+    assert_eq!(normalized_spectral_entropy(&PeakList::new(&[100.0], &[1.0])), 0.0);
+}
+
+#[test]
+fn test_similarity_metrics() {
+    let tolerance = MassTolerance::Da(0.01);
+    let a = PeakList::new(&[100.0, 200.0, 300.0], &[10.0, 20.0, 30.0]);
+    let shifted = PeakList::new(&[100.005, 200.005, 300.005], &[10.0, 20.0, 30.0]);
+    let disjoint = PeakList::new(&[150.0, 250.0], &[10.0, 20.0]);
+
+    for score in [
+        cosine_similarity(&a, &shifted, tolerance),
+        modified_dot_product(&a, &shifted, tolerance, DotProductWeights::STEIN_SCOTT),
+        entropy_similarity(&a, &shifted, tolerance),
+        weighted_entropy_similarity(&a, &shifted, tolerance),
+    ] {
+        assert!((score - 1.0).abs() < 1e-9, "{}", score);
+    }
+    assert_eq!(cosine_similarity(&a, &disjoint, tolerance), 0.0);
+    assert!(entropy_similarity(&a, &disjoint, tolerance).abs() < 1e-9);
+    assert_eq!(entropy_similarity(&a, &PeakList::default(), tolerance), 0.0);
+
+    // Half the intensity shared: S(AB) = 1.5 ln 2, so 1 - (3 ln 2 - 2 ln 2) / ln 4 = 0.5
+    let half = PeakList::new(&[100.0, 150.0], &[1.0, 1.0]);
+    let other = PeakList::new(&[100.0, 250.0], &[1.0, 1.0]);
+    assert!((cosine_similarity(&half, &other, tolerance) - 0.5).abs() < 1e-12);
+    assert!((entropy_similarity(&half, &other, tolerance) - 0.5).abs() < 1e-12);
+}
+
+#[test]
+fn test_match_peaks_is_one_to_one() {
+    let a = PeakList::new(&[500.0, 500.002], &[10.0, 1.0]);
+    let b = PeakList::new(&[500.001], &[5.0]);
+    assert_eq!(match_peaks(&a, &b, MassTolerance::Ppm(10.0)), vec![(0, 0)]);
+    assert!(match_peaks(&a, &b, MassTolerance::Ppm(0.1)).is_empty());
+}
+
+#[test]
+fn test_similarity_from_reader_view() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().join("similarity.parquet");
+
+    let mut writer = MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), WriterConfig::default())?;
+    let spectrum = SpectrumArrays::new_ms2(
+        0,
+        1,
+        10.0,
+        1,
+        500.0,
+        PeakArrays::new(vec![200.0, 300.0, 400.0], vec![1.0, 3.0, 2.0]),
+    );
+    writer.write_spectrum_arrays(&spectrum)?;
+    writer.finish()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    let view = reader.get_spectrum_arrays(0)?.unwrap();
+    let from_view = PeakList::try_from(&view)?;
+    assert_eq!(from_view, PeakList::from(&spectrum));
+    assert!((entropy_similarity(&from_view, &PeakList::from(&spectrum), MassTolerance::Ppm(5.0)) - 1.0).abs() < 1e-12);
+    Ok(())
+}
+
+#[test]
+fn test_precursor_correction() -> Result<(), Box<dyn std::error::Error>> {
+    use super::feature_detect::{C13_C12_MASS_DIFF, PROTON_MASS};
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    let dir = tempdir()?;
+    let path = dir.path().join("precursors_in.parquet");
+
+    // Doubly charged 1800 Da peptide: averagine ratios 1 : 1 : 0.5 : 0.17
+    let spacing = C13_C12_MASS_DIFF / 2.0;
+    let mono = 900.0 + PROTON_MASS;
+    let envelope: Vec<f64> = (0..4).map(|i| mono + i as f64 * spacing).collect();
+    let mut ms1_mz = vec![500.0];
+    ms1_mz.extend(&envelope);
+    let ms1 = PeakArrays::new(ms1_mz, vec![80.0, 100.0, 100.0, 50.0, 17.0]);
+
+    let mut writer = MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), WriterConfig::default())?;
+    let fragment = || PeakArrays::new(vec![200.0], vec![1.0]);
+    writer.write_spectrum_arrays(&SpectrumArrays::new_ms2(0, 1, 9.0, 1, mono, fragment()))?;
+    writer.write_spectrum_arrays(&SpectrumArrays::new_ms1(1, 2, 10.0, 1, ms1))?;
+    // First isotope selected, charge unknown
+    writer.write_spectrum_arrays(&SpectrumArrays::new_ms2(2, 3, 10.1, 1, envelope[1], fragment()))?;
+    let mut correct = SpectrumArrays::new_ms2(3, 4, 10.2, 1, mono, fragment());
+    correct.precursor_charge = Some(2);
+    writer.write_spectrum_arrays(&correct)?;
+    writer.write_spectrum_arrays(&SpectrumArrays::new_ms2(4, 5, 10.3, 1, 700.0, fragment()))?;
+    writer.write_spectrum_arrays(&SpectrumArrays::new_ms2(5, 6, 10.4, 1, 500.0, fragment()))?;
+    writer.finish()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    let table = PrecursorCorrectionTable::compute(&reader, &PrecursorCorrectionConfig::default())?;
+    let statuses: Vec<_> = table.rows.iter().map(|row| row.status).collect();
+    assert_eq!(
+        statuses,
+        vec![
+            CorrectionStatus::NoMs1,
+            CorrectionStatus::Corrected,
+            CorrectionStatus::Unchanged,
+            CorrectionStatus::NoSignal,
+            CorrectionStatus::NoEnvelope,
+        ]
+    );
+    let corrected = &table.rows[1];
+    assert_eq!(corrected.ms1_spectrum_id, Some(1));
+    assert_eq!(corrected.corrected_charge, Some(2));
+    assert_eq!(corrected.isotope_error, 1);
+    assert_eq!(corrected.num_isotopes, 4);
+    assert!((corrected.corrected_mz - mono).abs() < 1e-9);
+    assert_eq!(table.num_corrected(), 1);
+
+    let out = dir.path().join(PRECURSORS_FILE_NAME);
+    table.write_parquet(&out)?;
+    let footer = SerializedFileReader::new(std::fs::File::open(&out)?)?;
+    let history = footer
+        .metadata()
+        .file_metadata()
+        .key_value_metadata()
+        .and_then(|kv| kv.iter().find(|kv| kv.key == crate::schema::KEY_PROCESSING_HISTORY))
+        .and_then(|kv| kv.value.clone())
+        .unwrap();
+    assert!(history.contains("Precursor recalculation"));
+    assert_eq!(footer.metadata().file_metadata().num_rows(), 5);
+    Ok(())
+}
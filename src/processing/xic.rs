@@ -0,0 +1,131 @@
+//! Extracted ion chromatograms and m/z-range peak queries
+//!
+//! Both scans run the [`mz_kernels`](super::mz_kernels) over the decoded m/z
+//! columns, so the per-peak work is vectorized where the CPU allows it.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use mzpeak::processing::MassTolerance;
+//! use mzpeak::reader::MzPeakReader;
+//!
+//! let reader = MzPeakReader::open("data.mzpeak")?;
+//! for xic in reader.extract_xics(&[445.1200, 524.2648], MassTolerance::Ppm(10.0))? {
+//!     println!("{:.4}: {} points", xic.target_mz, xic.retention_times.len());
+//! }
+//!
+//! let peaks = reader.peaks_in_mz_range(445.0, 446.0)?;
+//! # Ok::<(), mzpeak::processing::ProcessingError>(())
+//! ```
+
+use arrow::array::{Array, AsArray, BooleanArray};
+use arrow::compute::filter_record_batch;
+use arrow::datatypes::Float64Type;
+use arrow::record_batch::RecordBatch;
+
+use super::mz_kernels;
+use super::{MassTolerance, ProcessingError};
+use crate::reader::MzPeakReader;
+use crate::schema::columns;
+
+/// Extracted ion chromatogram of one target m/z
+#[derive(Debug, Clone, PartialEq)]
+pub struct Xic {
+    /// Target m/z
+    pub target_mz: f64,
+    /// Lower m/z bound of the extraction window (inclusive)
+    pub lower_mz: f64,
+    /// Upper m/z bound of the extraction window (inclusive)
+    pub upper_mz: f64,
+    /// Retention time of each MS1 spectrum, in seconds
+    pub retention_times: Vec<f32>,
+    /// Summed intensity inside the window for each MS1 spectrum
+    pub intensities: Vec<f64>,
+}
+
+impl MzPeakReader {
+    /// Extract one chromatogram per target m/z from the MS1 spectra
+    ///
+    /// Every MS1 spectrum contributes a point to every chromatogram, with an
+    /// intensity of zero when no peak falls inside the window.
+    pub fn extract_xics(
+        &self,
+        targets: &[f64],
+        tolerance: MassTolerance,
+    ) -> Result<Vec<Xic>, ProcessingError> {
+        let mut xics: Vec<Xic> = targets
+            .iter()
+            .map(|&target_mz| {
+                let window = tolerance.window(target_mz);
+                Xic {
+                    target_mz,
+                    lower_mz: target_mz - window,
+                    upper_mz: target_mz + window,
+                    retention_times: Vec::new(),
+                    intensities: Vec::new(),
+                }
+            })
+            .collect();
+
+        for spectrum in self.iter_spectra_arrays_streaming()? {
+            let spectrum = spectrum?;
+            if spectrum.ms_level != 1 {
+                continue;
+            }
+
+            let mz_arrays = spectrum.mz_arrays()?;
+            let intensity_arrays = spectrum.intensity_arrays()?;
+            for xic in &mut xics {
+                let intensity: f64 = mz_arrays
+                    .iter()
+                    .zip(&intensity_arrays)
+                    .map(|(mzs, intensities)| {
+                        mz_kernels::intensity_in_range(
+                            mzs.values(),
+                            intensities.values(),
+                            xic.lower_mz,
+                            xic.upper_mz,
+                        )
+                    })
+                    .sum();
+                xic.retention_times.push(spectrum.retention_time);
+                xic.intensities.push(intensity);
+            }
+        }
+        Ok(xics)
+    }
+
+    /// All peaks with m/z in `[lower, upper]`, in file order
+    ///
+    /// The returned batches have the full peaks schema; batches without a
+    /// matching peak are skipped. Files with a page index only decode the
+    /// pages whose m/z range overlaps the query.
+    pub fn peaks_in_mz_range(
+        &self,
+        lower: f64,
+        upper: f64,
+    ) -> Result<Vec<RecordBatch>, ProcessingError> {
+        let mut matches = Vec::new();
+        for batch in self.iter_batches_in_range(columns::MZ, lower, upper)? {
+            let batch = batch?;
+            let mz = batch
+                .column_by_name(columns::MZ)
+                .and_then(|column| column.as_primitive_opt::<Float64Type>())
+                .ok_or_else(|| {
+                    ProcessingError::InvalidData("peaks table has no Float64 mz column".to_string())
+                })?;
+
+            let mask = mz_kernels::mz_range_mask(mz.values(), lower, upper);
+            match mask.count_set_bits() {
+                0 => continue,
+                n if n == batch.num_rows() && mz.null_count() == 0 => matches.push(batch),
+                _ => {
+                    let filtered =
+                        filter_record_batch(&batch, &BooleanArray::new(mask, mz.nulls().cloned()))?;
+                    matches.push(filtered);
+                }
+            }
+        }
+        Ok(matches)
+    }
+}
@@ -59,6 +59,7 @@ impl From<ReaderError> for PyErr {
             ReaderError::ZipError(_) => MzPeakIOError::new_err(msg),
             ReaderError::MetadataError(_) => MzPeakFormatError::new_err(msg),
             ReaderError::ColumnNotFound(_) => MzPeakFormatError::new_err(msg),
+            ReaderError::Security(_) => MzPeakFormatError::new_err(msg),
             ReaderError::JsonError(_) => MzPeakFormatError::new_err(msg),
         }
     }
@@ -88,11 +89,16 @@ impl From<DatasetError> for PyErr {
             DatasetError::WriterError(_) => MzPeakException::new_err(msg),
             DatasetError::MetadataError(_) => MzPeakFormatError::new_err(msg),
             DatasetError::SerdeJsonError(_) => MzPeakFormatError::new_err(msg),
+            DatasetError::ParquetError(_) => MzPeakIOError::new_err(msg),
+            DatasetError::ArrowError(_) => MzPeakException::new_err(msg),
             DatasetError::ZipError(_) => MzPeakIOError::new_err(msg),
             DatasetError::ChromatogramWriterError(_) => MzPeakException::new_err(msg),
             DatasetError::MobilogramWriterError(_) => MzPeakException::new_err(msg),
             DatasetError::InvalidPath(_) => PyValueError::new_err(msg),
             DatasetError::AlreadyExists(_) => MzPeakIOError::new_err(msg),
+            DatasetError::DestinationNotWritable { .. } => MzPeakIOError::new_err(msg),
+            DatasetError::VerificationFailed(_) => MzPeakValidationError::new_err(msg),
+            DatasetError::AttachmentError(_) => PyValueError::new_err(msg),
             DatasetError::NotInitialized => MzPeakException::new_err(msg),
         }
     }
@@ -59,7 +59,10 @@ impl PyMzPeakReader {
     #[new]
     #[pyo3(signature = (path, batch_size=None))]
     fn new(path: String, batch_size: Option<usize>) -> PyResult<Self> {
-        let config = batch_size.map(|bs| ReaderConfig { batch_size: bs });
+        let config = batch_size.map(|bs| ReaderConfig {
+            batch_size: bs,
+            ..Default::default()
+        });
 
         let reader = if let Some(cfg) = config {
             MzPeakReader::open_with_config(&path, cfg)
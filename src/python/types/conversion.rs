@@ -1,6 +1,8 @@
+use std::collections::HashMap;
+
 use pyo3::prelude::*;
 
-use crate::mzml::converter::{ConversionConfig, ConversionStats};
+use crate::mzml::converter::{ConversionConfig, ConversionStats, Stage};
 
 /// Configuration for mzML conversion
 #[pyclass(name = "ConversionConfig")]
@@ -137,6 +139,36 @@ impl PyConversionStats {
         self.inner.compression_ratio
     }
 
+    /// Wall-clock seconds per stage (parse, decode, transform, write, package, total)
+    #[getter]
+    fn stage_timings(&self) -> HashMap<&'static str, f64> {
+        let timings = &self.inner.timings;
+        let mut stages: HashMap<&'static str, f64> = Stage::ALL
+            .into_iter()
+            .map(|stage| (stage.name(), timings.get(stage)))
+            .collect();
+        stages.insert("total", timings.total);
+        stages
+    }
+
+    /// Average throughput in peaks per second
+    #[getter]
+    fn peaks_per_second(&self) -> f64 {
+        self.inner.peaks_per_second
+    }
+
+    /// Highest throughput over any one-second window, in peaks per second
+    #[getter]
+    fn max_peaks_per_second(&self) -> f64 {
+        self.inner.max_peaks_per_second
+    }
+
+    /// Peak resident memory of the process in bytes (None if not measurable)
+    #[getter]
+    fn peak_memory_bytes(&self) -> Option<u64> {
+        self.inner.peak_memory_bytes
+    }
+
     fn __repr__(&self) -> String {
         format!(
             "ConversionStats(spectra={}, peaks={}, compression_ratio={:.2}x)",
@@ -54,6 +54,16 @@ impl PyFileSummary {
         self.inner.format_version.clone()
     }
 
+    /// Number of spectra per polarity (1 positive, -1 negative, 0 unknown)
+    #[getter]
+    fn spectra_by_polarity(&self) -> HashMap<i8, i64> {
+        self.inner
+            .polarities
+            .iter()
+            .map(|summary| (summary.polarity, summary.num_spectra))
+            .collect()
+    }
+
     fn __repr__(&self) -> String {
         format!(
             "FileSummary(spectra={}, peaks={}, ms1={}, ms2={})",
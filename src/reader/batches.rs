@@ -1,9 +1,14 @@
-use std::fs::File;
 
 use arrow::record_batch::RecordBatch;
-use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::arrow_reader::{
+    ArrowReaderMetadata, ArrowReaderOptions, ParquetRecordBatchReaderBuilder,
+};
+use parquet::file::reader::ChunkReader;
+
+use crate::schema::mz_delta::MzDeltaDecoder;
 
 use super::config::ReaderSource;
+use super::prefetch::PrefetchingBatchIterator;
 use super::{MzPeakReader, ReaderError};
 
 /// Streaming iterator over record batches (Issue 003 fix)
@@ -12,6 +17,7 @@ use super::{MzPeakReader, ReaderError};
 /// rather than loading the entire file into memory.
 pub struct RecordBatchIterator {
     inner: Box<dyn Iterator<Item = Result<RecordBatch, arrow::error::ArrowError>> + Send>,
+    mz_delta: MzDeltaDecoder,
 }
 
 impl RecordBatchIterator {
@@ -21,6 +27,7 @@ impl RecordBatchIterator {
     {
         Self {
             inner: Box::new(iter),
+            mz_delta: MzDeltaDecoder::default(),
         }
     }
 }
@@ -29,7 +36,14 @@ impl Iterator for RecordBatchIterator {
     type Item = Result<RecordBatch, ReaderError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|r| r.map_err(ReaderError::from))
+        // Files written with sorted m/z enforced store `mz_delta`; every batch
+        // leaves here with the Float64 `mz` column restored
+        let batch = self.inner.next()?;
+        Some(
+            batch
+                .and_then(|batch| self.mz_delta.decode(batch))
+                .map_err(ReaderError::from),
+        )
     }
 }
 
@@ -37,7 +51,8 @@ impl MzPeakReader {
     /// Returns a streaming iterator over record batches
     ///
     /// This is the preferred API for large files as it avoids loading all data into memory.
-    /// Memory usage is bounded by `batch_size * row_size`.
+    /// Memory usage is bounded by `batch_size * row_size`, plus the row
+    /// groups decoded ahead when `ReaderConfig::prefetch_row_groups` is set.
     ///
     /// # Example
     /// ```rust,no_run
@@ -52,24 +67,39 @@ impl MzPeakReader {
     /// ```
     pub fn iter_batches(&self) -> Result<RecordBatchIterator, ReaderError> {
         match &self.source {
-            ReaderSource::FilePath(path) => {
-                let file = File::open(path)?;
-                let builder = ParquetRecordBatchReaderBuilder::try_new(file)?
-                    .with_batch_size(self.config.batch_size);
-                let reader = builder.build()?;
-                Ok(RecordBatchIterator::new(reader))
-            }
+            ReaderSource::FilePath(path) => self.scan_batches(self.open_positioned(path)?),
+            #[cfg(feature = "container")]
             ReaderSource::ZipContainer { chunk_reader, .. } => {
                 // Use the seekable chunk reader for streaming access (Issue 002 fix)
                 // This avoids loading the entire Parquet file into memory
-                let builder = ParquetRecordBatchReaderBuilder::try_new(chunk_reader.clone())?
-                    .with_batch_size(self.config.batch_size);
-                let reader = builder.build()?;
-                Ok(RecordBatchIterator::new(reader))
+                self.scan_batches(chunk_reader.clone())
             }
         }
     }
 
+    /// Full-file scan, prefetching row groups on a background thread when
+    /// `prefetch_row_groups` is set and there is more than one
+    fn scan_batches<T: ChunkReader + Clone + 'static>(
+        &self,
+        reader: T,
+    ) -> Result<RecordBatchIterator, ReaderError> {
+        let metadata = ArrowReaderMetadata::load(&reader, ArrowReaderOptions::default())?;
+
+        if self.config.prefetch_row_groups > 0 && metadata.metadata().num_row_groups() > 1 {
+            return Ok(RecordBatchIterator::new(PrefetchingBatchIterator::spawn(
+                reader,
+                metadata,
+                self.config.batch_size,
+                self.config.prefetch_row_groups,
+            )));
+        }
+
+        let reader = ParquetRecordBatchReaderBuilder::new_with_metadata(reader, metadata)
+            .with_batch_size(self.config.batch_size)
+            .build()?;
+        Ok(RecordBatchIterator::new(reader))
+    }
+
     /// Read all record batches from the file (eager, collects all batches)
     ///
     /// Returns the raw Arrow record batches for efficient data access.
@@ -1,3 +1,9 @@
+use crate::metadata::TimestampZone;
+
+use super::positioned::IoBackend;
+use super::safety::ContainerLimits;
+use super::transform::SpectrumTransforms;
+#[cfg(feature = "container")]
 use super::zip_chunk_reader::SharedZipEntryReader;
 
 /// Configuration for reading mzPeak files
@@ -5,11 +11,35 @@ use super::zip_chunk_reader::SharedZipEntryReader;
 pub struct ReaderConfig {
     /// Batch size for reading records
     pub batch_size: usize,
+    /// I/O backend for reading Parquet tables (io_uring when the `uring`
+    /// feature is enabled on Linux)
+    pub io_backend: IoBackend,
+    /// Row groups decoded ahead on a background thread during full-file
+    /// scans (`iter_batches`, `iter_spectra_arrays_streaming`); 0 (the
+    /// default) disables prefetching. Lookups and pruned range queries never
+    /// prefetch.
+    pub prefetch_row_groups: usize,
+    /// Transforms applied to every spectrum returned (see
+    /// [`ReaderConfig::with_transform`])
+    pub transforms: SpectrumTransforms,
+    /// Limits protecting against hostile containers (entry count, sizes of
+    /// sub-files read into memory)
+    pub container_limits: ContainerLimits,
+    /// Zone assumed for run start/end times stored without a UTC offset;
+    /// the run times in the returned metadata are normalized to RFC 3339
+    pub timestamp_zone: TimestampZone,
 }
 
 impl Default for ReaderConfig {
     fn default() -> Self {
-        Self { batch_size: 65536 }
+        Self {
+            batch_size: 65536,
+            io_backend: IoBackend::default(),
+            prefetch_row_groups: 0,
+            transforms: SpectrumTransforms::default(),
+            container_limits: ContainerLimits::default(),
+            timestamp_zone: TimestampZone::default(),
+        }
     }
 }
 
@@ -22,6 +52,7 @@ pub(super) enum ReaderSource {
     FilePath(std::path::PathBuf),
     /// Seekable reader for ZIP container format (.mzpeak files)
     /// Uses `SharedZipEntryReader` for bounded memory usage
+    #[cfg(feature = "container")]
     ZipContainer {
         /// Seekable reader for the peaks/peaks.parquet entry
         chunk_reader: SharedZipEntryReader,
@@ -14,6 +14,7 @@ pub enum ReaderError {
     ParquetError(#[from] parquet::errors::ParquetError),
 
     /// ZIP archive error
+    #[cfg(feature = "container")]
     #[error("ZIP error: {0}")]
     ZipError(#[from] zip::result::ZipError),
 
@@ -29,7 +30,16 @@ pub enum ReaderError {
     #[error("Column not found: {0}")]
     ColumnNotFound(String),
 
+    /// Container rejected as unsafe (see [`ContainerLimits`](super::ContainerLimits))
+    #[error("Unsafe container: {0}")]
+    Security(#[from] super::ContainerSecurityError),
+
     /// JSON parsing error
     #[error("JSON error: {0}")]
     JsonError(#[from] serde_json::Error),
+
+    /// Query planning or execution error
+    #[cfg(feature = "datafusion")]
+    #[error("Query error: {0}")]
+    QueryError(#[from] datafusion::error::DataFusionError),
 }
@@ -0,0 +1,315 @@
+//! Flat analysis table for v2.0 datasets
+//!
+//! Tools written against the v1 single-table layout expect one row per peak
+//! carrying the metadata of its spectrum. [`MzPeakReader::flat_table`]
+//! rebuilds that layout from a v2.0 dataset by joining the spectra table onto
+//! the peaks table one batch at a time: both tables are streamed once, side by
+//! side, and only the spectra batches referenced by the current peaks batch
+//! are held in memory. v1 files already have this layout and are streamed
+//! as stored.
+//!
+//! Like the other batch-level APIs, the table holds stored values; read-time
+//! transforms are not applied.
+
+use std::sync::Arc;
+
+use arrow::array::{new_null_array, Array, ArrayRef, AsArray, Int64Array, UInt32Array};
+use arrow::compute::{cast, interleave};
+use arrow::datatypes::{Field, Int64Type, SchemaRef, UInt32Type};
+use arrow::record_batch::{RecordBatch, RecordBatchOptions};
+use parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
+use parquet::arrow::ProjectionMask;
+
+use super::batches::RecordBatchIterator;
+use super::positioned::PositionedReader;
+use super::spectrum_index::{PEAKS_SUBPATH, SPECTRA_SUBPATH};
+use super::{MzPeakReader, ReaderError};
+use crate::schema::{columns, create_mzpeak_schema};
+
+/// Flat columns taken from the peaks table; all others come from the
+/// spectra table
+const PEAK_COLUMNS: [&str; 4] = [
+    columns::SPECTRUM_ID,
+    columns::MZ,
+    columns::INTENSITY,
+    columns::ION_MOBILITY,
+];
+
+/// Streaming iterator over the flat (v1 layout) analysis table
+///
+/// Every batch has the schema returned by [`FlatTableIterator::schema`]:
+/// the v1 columns selected by the projection, with v1 types.
+pub struct FlatTableIterator {
+    schema: SchemaRef,
+    source: FlatSource,
+}
+
+enum FlatSource {
+    /// v1 file, already flat
+    Flat(RecordBatchIterator),
+    /// v2.0 dataset, spectra joined onto peaks
+    Joined(Box<SpectraJoin>),
+}
+
+impl FlatTableIterator {
+    /// Schema of the batches
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+impl Iterator for FlatTableIterator {
+    type Item = Result<RecordBatch, ReaderError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.source {
+            FlatSource::Flat(batches) => {
+                let batch = batches.next()?;
+                Some(batch.and_then(|batch| {
+                    let columns = self
+                        .schema
+                        .fields()
+                        .iter()
+                        .map(|field| v1_column(field, batch.column_by_name(field.name()), &batch))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    flat_batch(&self.schema, columns, batch.num_rows())
+                }))
+            }
+            FlatSource::Joined(join) => join.next_batch(&self.schema).transpose(),
+        }
+    }
+}
+
+impl MzPeakReader {
+    /// Stream the dataset as the flat v1 analysis table
+    ///
+    /// For v2.0 datasets the spectra and peaks tables are joined on demand,
+    /// one peaks batch at a time, so tools written against the v1
+    /// single-table schema can read v2.0 files unchanged. `projection`
+    /// selects v1 column names (in output order); `None` returns all v1
+    /// columns. Columns the dataset does not store, such as `ion_mobility`
+    /// of 3D data, come back as nulls, and unknown scan numbers as 0.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use mzpeak::reader::MzPeakReader;
+    ///
+    /// let reader = MzPeakReader::open("data.mzpeak")?;
+    /// let table = reader.flat_table(Some(&["spectrum_id", "retention_time", "mz", "intensity"]))?;
+    /// for batch in table {
+    ///     println!("{} peaks", batch?.num_rows());
+    /// }
+    /// # Ok::<(), mzpeak::reader::ReaderError>(())
+    /// ```
+    pub fn flat_table(
+        &self,
+        projection: Option<&[&str]>,
+    ) -> Result<FlatTableIterator, ReaderError> {
+        let full = create_mzpeak_schema();
+        let schema = match projection {
+            None => Arc::new(full),
+            Some(names) => {
+                let indices = names
+                    .iter()
+                    .map(|name| {
+                        full.index_of(name)
+                            .map_err(|_| ReaderError::ColumnNotFound(name.to_string()))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Arc::new(full.project(&indices)?)
+            }
+        };
+
+        let Some(spectra) = self.open_table(SPECTRA_SUBPATH)? else {
+            return Ok(FlatTableIterator {
+                schema,
+                source: FlatSource::Flat(self.iter_batches()?),
+            });
+        };
+        let peaks = self.open_table(PEAKS_SUBPATH)?.ok_or_else(|| {
+            ReaderError::InvalidFormat(format!("Dataset has no {}", PEAKS_SUBPATH))
+        })?;
+
+        // Both sides need the join key whether or not it is projected
+        let (mut peak_columns, mut spectra_columns) =
+            (vec![columns::SPECTRUM_ID], vec![columns::SPECTRUM_ID]);
+        for field in schema.fields() {
+            let name = field.name().as_str();
+            if PEAK_COLUMNS.contains(&name) {
+                peak_columns.push(name);
+            } else {
+                spectra_columns.push(name);
+            }
+        }
+
+        let batch_size = self.config.batch_size;
+        let join = SpectraJoin {
+            peaks: table_reader(peaks, &peak_columns, batch_size)?,
+            spectra: table_reader(spectra, &spectra_columns, batch_size)?,
+            window: Vec::new(),
+            row: 0,
+        };
+        Ok(FlatTableIterator {
+            schema,
+            source: FlatSource::Joined(Box::new(join)),
+        })
+    }
+}
+
+/// Forward merge join of the spectra table onto the peaks table
+///
+/// Both tables store spectra in the same order, so a cursor over the spectra
+/// rows only ever moves forward.
+struct SpectraJoin {
+    peaks: ParquetRecordBatchReader,
+    spectra: ParquetRecordBatchReader,
+    /// Spectra batches referenced by the current peaks batch, oldest first
+    window: Vec<RecordBatch>,
+    /// Row of the current spectrum in the last window batch
+    row: usize,
+}
+
+impl SpectraJoin {
+    fn next_batch(&mut self, schema: &SchemaRef) -> Result<Option<RecordBatch>, ReaderError> {
+        let Some(peaks) = self.peaks.next().transpose()? else {
+            return Ok(None);
+        };
+        // Only the current spectrum can continue into this peaks batch
+        let done = self.window.len().saturating_sub(1);
+        self.window.drain(..done);
+
+        let mut rows = Vec::with_capacity(peaks.num_rows());
+        for &id in spectrum_ids(&peaks)?.values() {
+            if self.current_id()? != Some(id) {
+                self.seek(id)?;
+            }
+            rows.push((self.window.len() - 1, self.row));
+        }
+
+        let columns = schema
+            .fields()
+            .iter()
+            .map(|field| {
+                let name = field.name().as_str();
+                if PEAK_COLUMNS.contains(&name) {
+                    return v1_column(field, peaks.column_by_name(name), &peaks);
+                }
+                let arrays: Option<Vec<&dyn Array>> = self
+                    .window
+                    .iter()
+                    .map(|batch| batch.column_by_name(name).map(|column| column.as_ref()))
+                    .collect();
+                match arrays {
+                    Some(arrays) if !rows.is_empty() => {
+                        v1_column(field, Some(&interleave(&arrays, &rows)?), &peaks)
+                    }
+                    _ => v1_column(field, None, &peaks),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        flat_batch(schema, columns, peaks.num_rows()).map(Some)
+    }
+
+    /// Spectrum ID under the cursor
+    fn current_id(&self) -> Result<Option<u32>, ReaderError> {
+        match self.window.last() {
+            Some(batch) => Ok(Some(spectrum_ids(batch)?.value(self.row))),
+            None => Ok(None),
+        }
+    }
+
+    /// Move the cursor forward to spectrum `id`
+    fn seek(&mut self, id: u32) -> Result<(), ReaderError> {
+        loop {
+            match self.window.last() {
+                Some(batch) if self.row + 1 < batch.num_rows() => self.row += 1,
+                _ => {
+                    let batch = self.next_spectra_batch()?.ok_or_else(|| {
+                        ReaderError::InvalidFormat(format!(
+                            "Peaks of spectrum {} have no matching row in {} \
+                             (missing or out of order)",
+                            id, SPECTRA_SUBPATH
+                        ))
+                    })?;
+                    self.window.push(batch);
+                    self.row = 0;
+                }
+            }
+            if self.current_id()? == Some(id) {
+                return Ok(());
+            }
+        }
+    }
+
+    fn next_spectra_batch(&mut self) -> Result<Option<RecordBatch>, ReaderError> {
+        for batch in self.spectra.by_ref() {
+            let batch = batch?;
+            if batch.num_rows() > 0 {
+                return Ok(Some(batch));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Reader over the projected columns of one v2.0 table
+fn table_reader(
+    table: PositionedReader,
+    names: &[&str],
+    batch_size: usize,
+) -> Result<ParquetRecordBatchReader, ReaderError> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(table)?;
+    let projection = ProjectionMask::columns(builder.parquet_schema(), names.iter().copied());
+    Ok(builder
+        .with_projection(projection)
+        .with_batch_size(batch_size)
+        .build()?)
+}
+
+/// UInt32 `spectrum_id` column of a v2.0 table batch
+fn spectrum_ids(batch: &RecordBatch) -> Result<&UInt32Array, ReaderError> {
+    batch
+        .column_by_name(columns::SPECTRUM_ID)
+        .and_then(|column| column.as_primitive_opt::<UInt32Type>())
+        .ok_or_else(|| ReaderError::InvalidFormat("spectrum_id is not UInt32".to_string()))
+}
+
+/// Convert a stored column to the type of its v1 field
+///
+/// Absent optional columns become nulls; v2.0 leaves unknown scan numbers
+/// null where v1 stores 0.
+fn v1_column(
+    field: &Field,
+    column: Option<&ArrayRef>,
+    batch: &RecordBatch,
+) -> Result<ArrayRef, ReaderError> {
+    let Some(column) = column else {
+        if field.is_nullable() {
+            return Ok(new_null_array(field.data_type(), batch.num_rows()));
+        }
+        return Err(ReaderError::ColumnNotFound(field.name().to_string()));
+    };
+    let column = cast(column, field.data_type())?;
+    if field.name() == columns::SCAN_NUMBER && column.null_count() > 0 {
+        let scan_numbers: Int64Array = column
+            .as_primitive::<Int64Type>()
+            .iter()
+            .map(|value| Some(value.unwrap_or(0)))
+            .collect();
+        return Ok(Arc::new(scan_numbers));
+    }
+    Ok(column)
+}
+
+fn flat_batch(
+    schema: &SchemaRef,
+    columns: Vec<ArrayRef>,
+    rows: usize,
+) -> Result<RecordBatch, ReaderError> {
+    let options = RecordBatchOptions::new().with_row_count(Some(rows));
+    Ok(RecordBatch::try_new_with_options(
+        schema.clone(),
+        columns,
+        &options,
+    )?)
+}
@@ -0,0 +1,301 @@
+//! Foreign Parquet peak tables
+//!
+//! Peak lists exported into Parquet by other tools rarely use mzPeak column
+//! names or types. [`MzPeakReader::open_foreign_parquet`] maps such a table
+//! onto the v1 long layout (one row per peak, see
+//! [`create_mzpeak_schema`]) so it can be queried through the regular reader
+//! API and re-written as a proper mzPeak dataset.
+//!
+//! Each mzPeak column is taken from, in order of priority:
+//!
+//! 1. the foreign column named by [`ForeignColumnMapping::column`]
+//! 2. a constant set with [`ForeignColumnMapping::constant`]
+//! 3. a foreign column with the same name as the mzPeak column
+//! 4. a default:
+//!
+//! | Column | Default |
+//! |--------|---------|
+//! | spectrum_id, mz, intensity | none, must be mapped |
+//! | scan_number | spectrum_id |
+//! | ms_level | 1 |
+//! | retention_time | 0 |
+//! | polarity | 0 (unknown) |
+//! | all nullable columns | null |
+//!
+//! Values are cast to the mzPeak types; a value that does not fit (e.g. a
+//! non-numeric string or an out-of-range integer) fails the open rather than
+//! being dropped. The peaks of a spectrum must be stored as one contiguous
+//! run of rows.
+//!
+//! The mapped table is staged as a v1 Parquet file in the temp directory
+//! (`$MZPEAK_TMPDIR` if set) and removed when the reader is dropped.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arrow::array::{new_null_array, Array, ArrayRef, AsArray, Float64Array};
+use arrow::compute::{cast_with_options, CastOptions};
+use arrow::datatypes::{Field, Int64Type, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use parquet::format::KeyValue;
+
+use super::{MzPeakReader, ReaderConfig, ReaderError};
+use crate::schema::{columns, create_mzpeak_schema, KEY_FORMAT_VERSION, MZPEAK_FORMAT_VERSION};
+
+/// Footer key recording the path of the foreign table a reader was opened on
+pub const KEY_FOREIGN_SOURCE: &str = "mzpeak:foreign_source";
+
+/// Environment variable overriding the staging directory
+const TMPDIR_ENV: &str = "MZPEAK_TMPDIR";
+
+/// Mapping of a foreign Parquet peak table onto mzPeak columns
+///
+/// # Example
+/// ```rust,no_run
+/// use mzpeak::reader::{ForeignColumnMapping, MzPeakReader};
+///
+/// let mapping = ForeignColumnMapping::new()
+///     .column("spectrum_id", "ScanIndex")
+///     .column("retention_time", "RT")
+///     .column("mz", "m/z")
+///     .column("intensity", "Intensity")
+///     .constant("polarity", 1.0);
+/// let reader = MzPeakReader::open_foreign_parquet("export.parquet", &mapping)?;
+/// println!("{}", reader.summary()?);
+/// # Ok::<(), mzpeak::reader::ReaderError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ForeignColumnMapping {
+    /// mzPeak column -> foreign column
+    columns: BTreeMap<String, String>,
+    /// mzPeak column -> constant value
+    constants: BTreeMap<String, f64>,
+}
+
+impl ForeignColumnMapping {
+    /// Empty mapping: only same-named columns and defaults are used
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read mzPeak column `column` from foreign column `source`
+    pub fn column(mut self, column: &str, source: &str) -> Self {
+        self.columns.insert(column.to_string(), source.to_string());
+        self
+    }
+
+    /// Fill mzPeak column `column` with `value` (cast to the column type)
+    pub fn constant(mut self, column: &str, value: f64) -> Self {
+        self.constants.insert(column.to_string(), value);
+        self
+    }
+
+    /// Source of every column of `schema`, in schema order
+    fn resolve(&self, schema: &Schema, foreign: &Schema) -> Result<Vec<Source>, ReaderError> {
+        for name in self.columns.keys().chain(self.constants.keys()) {
+            if schema.field_with_name(name).is_err() {
+                return Err(ReaderError::ColumnNotFound(format!(
+                    "{} (not an mzPeak column)",
+                    name
+                )));
+            }
+        }
+
+        schema
+            .fields()
+            .iter()
+            .map(|field| {
+                let name = field.name().as_str();
+                if let Some(source) = self.columns.get(name) {
+                    return match foreign.field_with_name(source) {
+                        Ok(_) => Ok(Source::Column(source.clone())),
+                        Err(_) => Err(ReaderError::ColumnNotFound(format!(
+                            "{} (mapped to {})",
+                            source, name
+                        ))),
+                    };
+                }
+                if let Some(&value) = self.constants.get(name) {
+                    return Ok(Source::Constant(value));
+                }
+                if foreign.field_with_name(name).is_ok() {
+                    return Ok(Source::Column(name.to_string()));
+                }
+                match name {
+                    columns::SCAN_NUMBER => {
+                        Ok(Source::Copy(schema.index_of(columns::SPECTRUM_ID)?))
+                    }
+                    columns::MS_LEVEL => Ok(Source::Constant(1.0)),
+                    columns::RETENTION_TIME | columns::POLARITY => Ok(Source::Constant(0.0)),
+                    _ if field.is_nullable() => Ok(Source::Null),
+                    _ => Err(ReaderError::ColumnNotFound(format!(
+                        "{} (no foreign column of that name; map one with \
+                         ForeignColumnMapping::column)",
+                        name
+                    ))),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Where the values of one mzPeak column come from
+enum Source {
+    /// Foreign column of this name
+    Column(String),
+    /// The same value in every row
+    Constant(f64),
+    /// Values of an earlier mzPeak column
+    Copy(usize),
+    /// Nulls
+    Null,
+}
+
+impl Source {
+    /// Build the column for one foreign batch
+    ///
+    /// `mapped` holds the columns built so far for this batch.
+    fn build(
+        &self,
+        field: &Field,
+        batch: &RecordBatch,
+        mapped: &[ArrayRef],
+    ) -> Result<ArrayRef, ReaderError> {
+        let rows = batch.num_rows();
+        let column = match self {
+            Source::Column(name) => batch
+                .column_by_name(name)
+                .ok_or_else(|| ReaderError::ColumnNotFound(name.clone()))?
+                .clone(),
+            Source::Constant(value) => Arc::new(Float64Array::from(vec![*value; rows])),
+            Source::Copy(index) => mapped[*index].clone(),
+            Source::Null => return Ok(new_null_array(field.data_type(), rows)),
+        };
+
+        // Unsafe casts fail instead of silently turning bad values into nulls
+        let options = CastOptions {
+            safe: false,
+            ..Default::default()
+        };
+        let column = cast_with_options(&column, field.data_type(), &options).map_err(|e| {
+            ReaderError::InvalidFormat(format!("cannot convert to {}: {}", field.name(), e))
+        })?;
+        if !field.is_nullable() && column.null_count() > 0 {
+            return Err(ReaderError::InvalidFormat(format!(
+                "{} is required but the foreign table has {} null values",
+                field.name(),
+                column.null_count()
+            )));
+        }
+        Ok(column)
+    }
+}
+
+/// Rejects tables that interleave the peaks of different spectra
+#[derive(Default)]
+struct SpectrumRuns {
+    current: Option<i64>,
+    seen: HashSet<i64>,
+}
+
+impl SpectrumRuns {
+    fn check(&mut self, spectrum_ids: &ArrayRef) -> Result<(), ReaderError> {
+        for &id in spectrum_ids.as_primitive::<Int64Type>().values() {
+            if self.current == Some(id) {
+                continue;
+            }
+            if !self.seen.insert(id) {
+                return Err(ReaderError::InvalidFormat(format!(
+                    "peaks of spectrum {} are not contiguous; sort the foreign table by spectrum",
+                    id
+                )));
+            }
+            self.current = Some(id);
+        }
+        Ok(())
+    }
+}
+
+impl MzPeakReader {
+    /// Open a Parquet peak table written by another tool
+    ///
+    /// Maps the table onto the mzPeak v1 columns (see the
+    /// [module documentation](self) for defaults), so spectra can be read,
+    /// queried and summarized as for any mzPeak file, or re-written as a
+    /// dataset with the writers.
+    pub fn open_foreign_parquet<P: AsRef<Path>>(
+        path: P,
+        mapping: &ForeignColumnMapping,
+    ) -> Result<Self, ReaderError> {
+        Self::open_foreign_parquet_with_config(path, mapping, ReaderConfig::default())
+    }
+
+    /// Open a foreign Parquet peak table with custom configuration
+    pub fn open_foreign_parquet_with_config<P: AsRef<Path>>(
+        path: P,
+        mapping: &ForeignColumnMapping,
+        config: ReaderConfig,
+    ) -> Result<Self, ReaderError> {
+        let path = path.as_ref();
+        let schema = Arc::new(create_mzpeak_schema());
+        let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?;
+        let sources = mapping.resolve(&schema, builder.schema())?;
+        let batches = builder.with_batch_size(config.batch_size).build()?;
+
+        let staged = tempfile::Builder::new()
+            .prefix(".mzpeak-foreign")
+            .suffix(".parquet")
+            .tempfile_in(staging_dir())?;
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_key_value_metadata(Some(vec![
+                KeyValue::new(
+                    KEY_FORMAT_VERSION.to_string(),
+                    MZPEAK_FORMAT_VERSION.to_string(),
+                ),
+                KeyValue::new(KEY_FOREIGN_SOURCE.to_string(), path.display().to_string()),
+            ]))
+            .build();
+        let mut writer = ArrowWriter::try_new(
+            BufWriter::new(staged.as_file()),
+            schema.clone(),
+            Some(properties),
+        )?;
+
+        let mut runs = SpectrumRuns::default();
+        for batch in batches {
+            let batch = batch?;
+            let mut mapped = Vec::with_capacity(sources.len());
+            for (source, field) in sources.iter().zip(schema.fields()) {
+                let column = source.build(field, &batch, &mapped)?;
+                mapped.push(column);
+            }
+            runs.check(&mapped[0])?;
+            writer.write(&RecordBatch::try_new(schema.clone(), mapped)?)?;
+        }
+        writer
+            .into_inner()?
+            .into_inner()
+            .map_err(|e| e.into_error())?
+            .flush()?;
+
+        let mut reader = Self::open_parquet_file(staged.path(), config)?;
+        reader.staged = Some(staged.into_temp_path());
+        Ok(reader)
+    }
+}
+
+/// Directory for the staged v1 table
+fn staging_dir() -> PathBuf {
+    std::env::var_os(TMPDIR_ENV)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+}
@@ -4,9 +4,12 @@ use std::sync::Arc;
 use arrow::datatypes::Schema;
 use parquet::file::reader::{FileReader, SerializedFileReader};
 
-use crate::metadata::MzPeakMetadata;
+use crate::metadata::{MzPeakMetadata, TimestampZone};
+use crate::schema::units::{self, ColumnUnit, Unit};
+use crate::schema::mz_delta::mz_schema;
 use crate::schema::KEY_FORMAT_VERSION;
 
+#[cfg(feature = "container")]
 use super::zip_chunk_reader::SharedZipEntryReader;
 use super::{MzPeakReader, ReaderError};
 
@@ -19,7 +22,9 @@ pub struct FileMetadata {
     pub total_rows: i64,
     /// Number of row groups
     pub num_row_groups: usize,
-    /// Schema of the Parquet file
+    /// Schema of the batches the reader returns
+    ///
+    /// A stored `mz_delta` column appears as the decoded Float64 `mz` column.
     pub schema: Arc<Schema>,
     /// Raw key-value metadata from Parquet footer
     pub key_value_metadata: HashMap<String, String>,
@@ -27,6 +32,37 @@ pub struct FileMetadata {
     pub mzpeak_metadata: Option<MzPeakMetadata>,
 }
 
+impl FileMetadata {
+    /// Units of the measured columns, derived from the schema CV annotations
+    ///
+    /// Identifier and flag columns (spectrum_id, ms_level, polarity, ...) have
+    /// no unit and are not listed.
+    pub fn column_units(&self) -> Vec<ColumnUnit> {
+        units::column_units(&self.schema)
+    }
+
+    /// Rewrite the run start/end times as RFC 3339 (see [`ReaderConfig::timestamp_zone`])
+    ///
+    /// [`ReaderConfig::timestamp_zone`]: super::ReaderConfig::timestamp_zone
+    pub(super) fn normalize_timestamps(&mut self, zone: TimestampZone) {
+        if let Some(run) = self
+            .mzpeak_metadata
+            .as_mut()
+            .and_then(|metadata| metadata.run_parameters.as_mut())
+        {
+            run.normalize_timestamps(zone);
+        }
+    }
+
+    /// Unit of a single column, if it exists and is a measured quantity
+    pub fn column_unit(&self, column: &str) -> Option<Unit> {
+        self.schema
+            .field_with_name(column)
+            .ok()
+            .and_then(units::field_unit)
+    }
+}
+
 impl MzPeakReader {
     /// Extract metadata from a Parquet reader
     pub(super) fn extract_file_metadata<R: parquet::file::reader::ChunkReader + 'static>(
@@ -67,7 +103,7 @@ impl MzPeakReader {
             format_version,
             total_rows,
             num_row_groups: parquet_metadata.num_row_groups(),
-            schema: Arc::new(schema),
+            schema: Arc::new(mz_schema(&schema)),
             key_value_metadata: kv_metadata,
             mzpeak_metadata,
         })
@@ -77,6 +113,7 @@ impl MzPeakReader {
     ///
     /// This method enables metadata extraction from seekable ZIP entries
     /// without loading the entire file into memory (Issue 002 fix).
+    #[cfg(feature = "container")]
     pub(super) fn extract_file_metadata_from_chunk_reader(
         chunk_reader: &SharedZipEntryReader,
     ) -> Result<FileMetadata, ReaderError> {
@@ -95,7 +132,7 @@ impl MzPeakReader {
         self.file_metadata.total_rows
     }
 
-    /// Get the Arrow schema
+    /// Get the Arrow schema of the returned batches (m/z always as `mz`)
     pub fn schema(&self) -> Arc<Schema> {
         Arc::clone(&self.file_metadata.schema)
     }
@@ -8,12 +8,23 @@
 //! - **Random Access**: Query spectra by ID, retention time range, or m/z range
 //! - **Streaming Iteration**: Memory-efficient iteration over large files
 //! - **Container Support**: Read both ZIP container (`.mzpeak`) and directory formats
+//! - **Untrusted Input**: Entry paths and sub-file sizes of containers are checked (see [`ContainerLimits`])
+//! - **Multi-Container Runs**: Read sharded or segmented files as one run with virtual spectrum IDs
+//! - **Page Pruning**: m/z and retention time queries skip pages using the Parquet page index
+//! - **Positioned I/O**: Shared-handle `pread` reads, batched through io_uring on Linux (`uring` feature)
 //! - **Metadata Access**: Retrieve embedded metadata from Parquet footer
+//! - **Random Sampling**: Seeded uniform subsets of spectra without decoding the whole run
+//! - **Shared Memory**: Publish peak batches as Arrow IPC in shared memory for other processes
+//! - **Flat Analysis Table**: Join v2.0 spectra and peaks into the v1 long layout on demand
+//! - **Foreign Tables**: Map Parquet peak tables exported by other tools onto the mzPeak columns
+//! - **Read-Time Transforms**: Apply corrections such as recalibration to spectra as they are read
+//! - **Mockable Access**: [`SpectrumStore`] trait with an in-memory implementation for tests
+//! - **SQL Queries**: Run SQL or Substrait plans with embedded DataFusion (`datafusion` feature)
 //!
 //! ## Example
 //!
 //! ```rust,no_run
-//! use mzpeak::reader::MzPeakReader;
+//! use mzpeak::reader::{MzPeakReader, RtRange};
 //!
 //! // Open a file
 //! let reader = MzPeakReader::open("data.mzpeak")?;
@@ -22,7 +33,7 @@
 //! println!("Format version: {}", reader.metadata().format_version);
 //!
 //! // Query spectra by retention time range (SoA view)
-//! for spectrum in reader.spectra_by_rt_range_arrays(60.0, 120.0)? {
+//! for spectrum in reader.spectra_in_rt_range(RtRange::minutes(1.0, 2.0))? {
 //!     println!("Spectrum {}: {} peaks", spectrum.spectrum_id, spectrum.peak_count());
 //! }
 //!
@@ -36,23 +47,63 @@
 mod batches;
 mod config;
 mod error;
+mod flat;
+mod foreign;
 mod metadata;
+mod multi;
 mod open;
+mod page_index;
+mod prefetch;
+pub mod positioned;
+#[cfg(feature = "datafusion")]
+mod query;
+mod rt_range;
+mod safety;
+mod sample;
+mod shared_memory;
 mod spectra;
+mod spectrum_index;
+mod store;
 mod subfiles;
 mod summary;
+#[cfg(feature = "datafusion")]
+mod table_provider;
+mod transform;
 mod utils;
+#[cfg(feature = "container")]
 pub mod zip_chunk_reader;
 
 #[cfg(test)]
 mod tests;
+#[cfg(all(feature = "uring", target_os = "linux"))]
+mod uring;
 
 pub use batches::RecordBatchIterator;
 pub use config::ReaderConfig;
 pub use error::ReaderError;
+pub use flat::FlatTableIterator;
+pub use foreign::{ForeignColumnMapping, KEY_FOREIGN_SOURCE};
 pub use metadata::FileMetadata;
+pub use multi::{MultiContainerReader, SourceContainer, SpectrumProvenance};
+pub use positioned::{IoBackend, PositionedReader};
+#[cfg(feature = "datafusion")]
+pub use query::{PEAKS_TABLE, SPECTRA_TABLE};
+pub use rt_range::{parse_retention_time, ParseRtError, RtRange};
+pub use safety::{check_entry_path, ContainerLimits, ContainerSecurityError};
+pub use shared_memory::{
+    export_to_shared_memory, open_shared_memory, remove_shared_memory, shared_memory_dir,
+    shared_memory_path, SharedMemoryBatches,
+};
 pub use spectra::{SpectrumArraysView, StreamingSpectrumArraysViewIterator};
-pub use summary::FileSummary;
+pub use spectrum_index::SpectrumLocation;
+pub use store::{InMemorySpectrumStore, SpectrumIter, SpectrumStore};
+pub use summary::{FileSummary, PolaritySummary};
+#[cfg(feature = "datafusion")]
+pub use table_provider::{PeaksTableProvider, SpectraTableProvider};
+pub use transform::{SpectrumTransform, SpectrumTransforms};
+#[cfg(all(feature = "uring", target_os = "linux"))]
+pub use uring::is_available as uring_available;
+#[cfg(feature = "container")]
 pub use zip_chunk_reader::{SharedZipEntryReader, ZipEntryChunkReader};
 
 use config::ReaderSource;
@@ -64,4 +115,8 @@ pub struct MzPeakReader {
     source: ReaderSource,
     config: ReaderConfig,
     file_metadata: FileMetadata,
+    /// v2.0 spectrum lookup cache, built on first use
+    spectrum_index: std::sync::OnceLock<Option<std::sync::Arc<spectrum_index::SpectrumIndex>>>,
+    /// v1 file staged from a foreign table, deleted on drop
+    staged: Option<tempfile::TempPath>,
 }
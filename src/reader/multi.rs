@@ -0,0 +1,265 @@
+//! Several containers read as one run
+//!
+//! Upstream sharding (`max_peaks_per_file`) or instrument segmenting can leave
+//! one acquisition spread over several files. [`MzPeakReader::open_many`]
+//! presents them as a single logical run:
+//!
+//! - Spectra are numbered with contiguous virtual IDs: the spectra of the
+//!   first container get `0..n1` in storage order, those of the second
+//!   `n1..n1 + n2`, and so on.
+//! - [`MultiContainerReader::provenance`] maps a virtual ID back to its
+//!   container and original spectrum ID.
+//! - [`SpectrumStore`] gives lookup, iteration and range queries over the
+//!   whole run, with spectra renumbered to their virtual IDs.
+//!
+//! Retention times are passed through unchanged, so the containers are
+//! expected to share one time axis.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use super::store::{SpectrumIter, SpectrumStore};
+use super::summary::PolarityBreakdown;
+use super::{FileSummary, MzPeakReader, ReaderConfig, ReaderError, RtRange};
+use crate::writer::SpectrumArrays;
+
+/// One container of a [`MultiContainerReader`]
+pub struct SourceContainer {
+    path: PathBuf,
+    reader: MzPeakReader,
+    first_spectrum_id: i64,
+    spectrum_ids: Vec<i64>,
+    /// Storage position of each original spectrum ID
+    positions: HashMap<i64, i64>,
+}
+
+impl SourceContainer {
+    /// Path the container was opened from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reader for this container alone, with its original spectrum IDs
+    pub fn reader(&self) -> &MzPeakReader {
+        &self.reader
+    }
+
+    /// Virtual IDs of the spectra in this container
+    pub fn virtual_spectrum_ids(&self) -> Range<i64> {
+        self.first_spectrum_id..self.first_spectrum_id + self.spectrum_ids.len() as i64
+    }
+
+    /// Original spectrum IDs in storage order
+    pub fn spectrum_ids(&self) -> &[i64] {
+        &self.spectrum_ids
+    }
+
+    /// Virtual ID of an original spectrum ID of this container
+    fn virtual_id(&self, spectrum_id: i64) -> Option<i64> {
+        self.positions
+            .get(&spectrum_id)
+            .map(|position| self.first_spectrum_id + position)
+    }
+
+    /// Give an owned spectrum of this container its virtual ID
+    fn renumber(&self, mut spectrum: SpectrumArrays) -> Result<SpectrumArrays, ReaderError> {
+        spectrum.spectrum_id = self.virtual_id(spectrum.spectrum_id).ok_or_else(|| {
+            ReaderError::InvalidFormat(format!(
+                "spectrum {} of {} is missing from its spectrum list",
+                spectrum.spectrum_id,
+                self.path.display()
+            ))
+        })?;
+        Ok(spectrum)
+    }
+}
+
+/// Origin of a spectrum in a [`MultiContainerReader`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpectrumProvenance {
+    /// Index of the container in [`MultiContainerReader::sources`]
+    pub source: usize,
+    /// Spectrum ID inside that container
+    pub spectrum_id: i64,
+}
+
+/// Reader presenting several mzPeak containers as one run
+///
+/// Created by [`MzPeakReader::open_many`].
+pub struct MultiContainerReader {
+    sources: Vec<SourceContainer>,
+}
+
+impl MzPeakReader {
+    /// Open several containers as one logical run
+    ///
+    /// Spectra get contiguous virtual IDs in the order of `paths`; see
+    /// [`MultiContainerReader`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use mzpeak::reader::{MzPeakReader, SpectrumStore};
+    ///
+    /// let run = MzPeakReader::open_many(&["run_part1.mzpeak", "run_part2.mzpeak"])?;
+    /// for spectrum in run.iter_spectra()? {
+    ///     let spectrum = spectrum?;
+    ///     let origin = run.provenance(spectrum.spectrum_id).expect("virtual ID");
+    ///     println!(
+    ///         "{} <- {} #{}",
+    ///         spectrum.spectrum_id,
+    ///         run.sources()[origin.source].path().display(),
+    ///         origin.spectrum_id
+    ///     );
+    /// }
+    /// # Ok::<(), mzpeak::reader::ReaderError>(())
+    /// ```
+    pub fn open_many<P: AsRef<Path>>(paths: &[P]) -> Result<MultiContainerReader, ReaderError> {
+        Self::open_many_with_config(paths, ReaderConfig::default())
+    }
+
+    /// Open several containers as one logical run with custom configuration
+    pub fn open_many_with_config<P: AsRef<Path>>(
+        paths: &[P],
+        config: ReaderConfig,
+    ) -> Result<MultiContainerReader, ReaderError> {
+        if paths.is_empty() {
+            return Err(ReaderError::InvalidFormat(
+                "no containers given to open as one run".to_string(),
+            ));
+        }
+
+        let mut sources = Vec::with_capacity(paths.len());
+        let mut first_spectrum_id = 0;
+        for path in paths {
+            let path = path.as_ref();
+            let reader = Self::open_with_config(path, config.clone())?;
+            let spectrum_ids = reader.stored_spectrum_ids(None)?;
+            let positions = spectrum_ids
+                .iter()
+                .enumerate()
+                .map(|(position, &id)| (id, position as i64))
+                .collect();
+            let count = spectrum_ids.len() as i64;
+            sources.push(SourceContainer {
+                path: path.to_path_buf(),
+                reader,
+                first_spectrum_id,
+                spectrum_ids,
+                positions,
+            });
+            first_spectrum_id += count;
+        }
+        Ok(MultiContainerReader { sources })
+    }
+}
+
+impl MultiContainerReader {
+    /// The containers, in virtual ID order
+    pub fn sources(&self) -> &[SourceContainer] {
+        &self.sources
+    }
+
+    /// Number of spectra across all containers
+    pub fn num_spectra(&self) -> i64 {
+        self.sources
+            .last()
+            .map_or(0, |source| source.virtual_spectrum_ids().end)
+    }
+
+    /// Container and original spectrum ID of a virtual spectrum ID
+    pub fn provenance(&self, spectrum_id: i64) -> Option<SpectrumProvenance> {
+        let source = self
+            .sources
+            .partition_point(|source| source.virtual_spectrum_ids().end <= spectrum_id);
+        let container = self.sources.get(source)?;
+        let position = spectrum_id.checked_sub(container.first_spectrum_id)?;
+        let original = *container
+            .spectrum_ids
+            .get(usize::try_from(position).ok()?)?;
+        Some(SpectrumProvenance {
+            source,
+            spectrum_id: original,
+        })
+    }
+
+    /// Virtual ID of spectrum `spectrum_id` of container `source`
+    pub fn virtual_spectrum_id(&self, source: usize, spectrum_id: i64) -> Option<i64> {
+        self.sources.get(source)?.virtual_id(spectrum_id)
+    }
+}
+
+impl SpectrumStore for MultiContainerReader {
+    fn summary(&self) -> Result<FileSummary, ReaderError> {
+        let mut total: Option<FileSummary> = None;
+        for source in &self.sources {
+            let summary = source.reader.summary()?;
+            total = Some(match total {
+                None => summary,
+                Some(total) => {
+                    let mut polarities = PolarityBreakdown::default();
+                    polarities.merge(&total.polarities);
+                    polarities.merge(&summary.polarities);
+                    FileSummary {
+                        total_peaks: total.total_peaks + summary.total_peaks,
+                        num_spectra: total.num_spectra + summary.num_spectra,
+                        num_ms1_spectra: total.num_ms1_spectra + summary.num_ms1_spectra,
+                        num_ms2_spectra: total.num_ms2_spectra + summary.num_ms2_spectra,
+                        rt_range: merge_range(total.rt_range, summary.rt_range),
+                        mz_range: merge_range(total.mz_range, summary.mz_range),
+                        format_version: total.format_version,
+                        polarities: polarities.finish(),
+                    }
+                }
+            });
+        }
+        total.ok_or_else(|| ReaderError::InvalidFormat("no containers in run".to_string()))
+    }
+
+    fn get_spectrum(&self, spectrum_id: i64) -> Result<Option<SpectrumArrays>, ReaderError> {
+        let Some(origin) = self.provenance(spectrum_id) else {
+            return Ok(None);
+        };
+        let source = &self.sources[origin.source];
+        source
+            .reader
+            .get_spectrum(origin.spectrum_id)?
+            .map(|spectrum| source.renumber(spectrum))
+            .transpose()
+    }
+
+    fn iter_spectra(&self) -> Result<SpectrumIter<'_>, ReaderError> {
+        let mut parts = Vec::with_capacity(self.sources.len());
+        for source in &self.sources {
+            let spectra = source.reader.iter_spectra()?;
+            parts.push(spectra.map(move |spectrum| spectrum.and_then(|s| source.renumber(s))));
+        }
+        Ok(Box::new(parts.into_iter().flatten()))
+    }
+
+    fn spectrum_ids(&self) -> Result<Vec<i64>, ReaderError> {
+        Ok((0..self.num_spectra()).collect())
+    }
+
+    fn query_rt_range(&self, range: RtRange) -> Result<Vec<SpectrumArrays>, ReaderError> {
+        let mut spectra = Vec::new();
+        for source in &self.sources {
+            for spectrum in source.reader.query_rt_range(range)? {
+                spectra.push(source.renumber(spectrum)?);
+            }
+        }
+        Ok(spectra)
+    }
+}
+
+/// Smallest range covering both
+fn merge_range<T: PartialOrd>(a: Option<(T, T)>, b: Option<(T, T)>) -> Option<(T, T)> {
+    match (a, b) {
+        (Some((a_min, a_max)), Some((b_min, b_max))) => Some((
+            if b_min < a_min { b_min } else { a_min },
+            if b_max > a_max { b_max } else { a_max },
+        )),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
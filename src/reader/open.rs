@@ -1,9 +1,12 @@
-use std::fs::File;
 use std::path::Path;
 
 use parquet::file::reader::SerializedFileReader;
 
 use super::config::ReaderSource;
+use super::positioned::PositionedReader;
+#[cfg(feature = "container")]
+use super::safety;
+#[cfg(feature = "container")]
 use super::zip_chunk_reader::{SharedZipEntryReader, ZipEntryChunkReader};
 use super::{MzPeakReader, ReaderConfig, ReaderError};
 
@@ -48,16 +51,20 @@ impl MzPeakReader {
     ///
     /// Uses `SharedZipEntryReader` for streaming access without loading the
     /// entire Parquet file into memory (Issue 002 fix).
+    #[cfg(feature = "container")]
     fn open_container<P: AsRef<Path>>(path: P, config: ReaderConfig) -> Result<Self, ReaderError> {
         let zip_path = path.as_ref().to_path_buf();
+        safety::open_archive(&zip_path, &config.container_limits)?;
 
         // Create seekable chunk reader for the peaks parquet entry
         // This validates that the entry is Stored (uncompressed) and fails fast if not
-        let chunk_reader = ZipEntryChunkReader::new(&zip_path, "peaks/peaks.parquet")?;
+        let chunk_reader =
+            ZipEntryChunkReader::with_backend(&zip_path, "peaks/peaks.parquet", config.io_backend)?;
         let chunk_reader = SharedZipEntryReader::new(chunk_reader);
 
         // Extract metadata using the chunk reader
-        let file_metadata = Self::extract_file_metadata_from_chunk_reader(&chunk_reader)?;
+        let mut file_metadata = Self::extract_file_metadata_from_chunk_reader(&chunk_reader)?;
+        file_metadata.normalize_timestamps(config.timestamp_zone);
 
         Ok(Self {
             source: ReaderSource::ZipContainer {
@@ -66,24 +73,72 @@ impl MzPeakReader {
             },
             config,
             file_metadata,
+            spectrum_index: Default::default(),
+            staged: None,
         })
     }
 
+    /// ZIP containers need the `container` feature
+    #[cfg(not(feature = "container"))]
+    fn open_container<P: AsRef<Path>>(path: P, _config: ReaderConfig) -> Result<Self, ReaderError> {
+        Err(ReaderError::InvalidFormat(format!(
+            "{} is a ZIP container, but this build lacks the `container` feature",
+            path.as_ref().display()
+        )))
+    }
+
     /// Open a single Parquet file directly
-    fn open_parquet_file<P: AsRef<Path>>(
+    pub(super) fn open_parquet_file<P: AsRef<Path>>(
         path: P,
         config: ReaderConfig,
     ) -> Result<Self, ReaderError> {
         let path = path.as_ref().to_path_buf();
-        let file = File::open(&path)?;
+        let file = PositionedReader::open(&path, config.io_backend)?;
         let parquet_reader = SerializedFileReader::new(file)?;
 
-        let file_metadata = Self::extract_file_metadata(&parquet_reader)?;
+        let mut file_metadata = Self::extract_file_metadata(&parquet_reader)?;
+        file_metadata.normalize_timestamps(config.timestamp_zone);
 
         Ok(Self {
             source: ReaderSource::FilePath(path),
             config,
             file_metadata,
+            spectrum_index: Default::default(),
+            staged: None,
         })
     }
+
+    /// Positioned reader over a Parquet file of a directory bundle
+    pub(super) fn open_positioned<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<PositionedReader, ReaderError> {
+        Ok(PositionedReader::open(path, self.config.io_backend)?)
+    }
+
+    /// Open a Parquet table of the dataset, `None` if it does not exist
+    pub(super) fn open_table(&self, subpath: &str) -> Result<Option<PositionedReader>, ReaderError> {
+        match &self.source {
+            ReaderSource::FilePath(path) => match Self::dataset_subfile_path(path, subpath)? {
+                Some(table) if table.exists() => Ok(Some(self.open_positioned(table)?)),
+                _ => Ok(None),
+            },
+            #[cfg(feature = "container")]
+            ReaderSource::ZipContainer { zip_path, .. } => Ok(ZipEntryChunkReader::open_optional(
+                zip_path,
+                subpath,
+                self.config.io_backend,
+            )?
+            .map(ZipEntryChunkReader::into_positioned)),
+        }
+    }
+
+    /// Path the reader was opened on (peaks table or ZIP container)
+    pub(super) fn source_path(&self) -> &Path {
+        match &self.source {
+            ReaderSource::FilePath(path) => path,
+            #[cfg(feature = "container")]
+            ReaderSource::ZipContainer { zip_path, .. } => zip_path,
+        }
+    }
 }
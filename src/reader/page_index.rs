@@ -0,0 +1,258 @@
+//! Page-level pruning with the Parquet page index
+//!
+//! Row-group statistics are coarse: a row group of 100,000 peaks spans most
+//! of the m/z axis and many spectra, so a narrow XIC window or retention time
+//! range rarely excludes one. Writers record per-page minimum and maximum
+//! values of `mz` and `retention_time` (the column index) together with the
+//! first row of every page (the offset index). Range queries use both to
+//! decode only the pages that may hold a match.
+//!
+//! Files storing delta-encoded m/z (see [`crate::schema::mz_delta`]) have no
+//! m/z page statistics. Range queries on `mz` first decode only the spectrum
+//! ID and m/z columns, then read the other columns of the spectra with a peak
+//! in range.
+//!
+//! Files without a page index for the queried column are scanned in full.
+
+use arrow::array::{Array, AsArray};
+use arrow::datatypes::{Float64Type, Int64Type};
+use parquet::arrow::arrow_reader::{
+    ArrowReaderMetadata, ArrowReaderOptions, ParquetRecordBatchReaderBuilder, RowSelection,
+    RowSelector,
+};
+use parquet::arrow::ProjectionMask;
+use parquet::file::metadata::ParquetMetaData;
+use parquet::file::page_index::index::Index;
+use parquet::file::reader::ChunkReader;
+
+use super::config::ReaderSource;
+use super::{MzPeakReader, ReaderError, RecordBatchIterator};
+use crate::schema::columns;
+use crate::schema::mz_delta::MzDeltaDecoder;
+
+/// Row groups and rows whose pages may hold values in `[lower, upper]`
+///
+/// Returns `None` when the file has no page index for `column`.
+pub(super) fn prune_pages(
+    metadata: &ParquetMetaData,
+    column: &str,
+    lower: f64,
+    upper: f64,
+) -> Option<(Vec<usize>, RowSelection)> {
+    let column_index = metadata.column_index()?;
+    let offset_index = metadata.offset_index()?;
+    let column_idx = metadata
+        .file_metadata()
+        .schema_descr()
+        .columns()
+        .iter()
+        .position(|c| c.name() == column)?;
+
+    let mut row_groups = Vec::new();
+    let mut selectors = Vec::new();
+    let mut indexed = false;
+    for (rg, row_group) in metadata.row_groups().iter().enumerate() {
+        let num_rows = row_group.num_rows() as usize;
+        let pages = &offset_index.get(rg)?.get(column_idx)?.page_locations;
+        let bounds = page_bounds(column_index.get(rg)?.get(column_idx)?);
+
+        let mut group_selectors = Vec::with_capacity(pages.len());
+        for (page, location) in pages.iter().enumerate() {
+            let first_row = location.first_row_index as usize;
+            let end_row = pages
+                .get(page + 1)
+                .map_or(num_rows, |next| next.first_row_index as usize);
+            let keep = match &bounds {
+                Some(bounds) => bounds
+                    .get(page)
+                    .copied()
+                    .flatten()
+                    .is_some_and(|(min, max)| max >= lower && min <= upper),
+                None => true,
+            };
+            group_selectors.push(if keep {
+                RowSelector::select(end_row - first_row)
+            } else {
+                RowSelector::skip(end_row - first_row)
+            });
+        }
+        indexed |= bounds.is_some();
+
+        if group_selectors.iter().any(|selector| !selector.skip) {
+            row_groups.push(rg);
+            selectors.extend(group_selectors);
+        }
+    }
+
+    indexed.then(|| (row_groups, RowSelection::from(selectors)))
+}
+
+/// Row groups and rows of the spectra with an m/z in `[lower, upper]`, for
+/// files storing `mz_delta`
+///
+/// Selecting whole spectra keeps every selected row decodable from the first
+/// peak of its spectrum.
+fn select_mz_delta_spectra<T: ChunkReader + 'static>(
+    reader: T,
+    metadata: ArrowReaderMetadata,
+    batch_size: usize,
+    lower: f64,
+    upper: f64,
+) -> Result<(Vec<usize>, RowSelection), ReaderError> {
+    let schema = metadata.schema();
+    let roots = [
+        schema.index_of(columns::SPECTRUM_ID)?,
+        schema.index_of(columns::MZ_DELTA)?,
+    ];
+    let row_counts: Vec<usize> = metadata
+        .metadata()
+        .row_groups()
+        .iter()
+        .map(|row_group| row_group.num_rows() as usize)
+        .collect();
+    let builder = ParquetRecordBatchReaderBuilder::new_with_metadata(reader, metadata);
+    let mask = ProjectionMask::roots(builder.parquet_schema(), roots);
+    let batches = builder
+        .with_batch_size(batch_size)
+        .with_projection(mask)
+        .build()?;
+
+    // (rows, has a peak in range) of each spectrum in file order
+    let mut spectra: Vec<(usize, bool)> = Vec::new();
+    let mut current = None;
+    let mut decoder = MzDeltaDecoder::default();
+    for batch in batches {
+        let batch = decoder.decode(batch?)?;
+        let ids = batch.column(0).as_primitive::<Int64Type>();
+        let mz = batch.column(1).as_primitive::<Float64Type>();
+        for row in 0..batch.num_rows() {
+            let id = ids.value(row);
+            let hit = mz.is_valid(row) && (lower..=upper).contains(&mz.value(row));
+            match spectra.last_mut() {
+                Some((rows, any_hit)) if current == Some(id) => {
+                    *rows += 1;
+                    *any_hit |= hit;
+                }
+                _ => {
+                    spectra.push((1, hit));
+                    current = Some(id);
+                }
+            }
+        }
+    }
+
+    // Split the spectrum runs at row-group boundaries
+    let mut row_groups = Vec::new();
+    let mut selectors = Vec::new();
+    let mut spectra = spectra.into_iter();
+    let mut pending = None;
+    for (rg, num_rows) in row_counts.into_iter().enumerate() {
+        let mut group_selectors = Vec::new();
+        let mut remaining = num_rows;
+        while remaining > 0 {
+            let Some((rows, hit)) = pending.take().or_else(|| spectra.next()) else {
+                break;
+            };
+            let take = rows.min(remaining);
+            if rows > take {
+                pending = Some((rows - take, hit));
+            }
+            remaining -= take;
+            group_selectors.push(if hit {
+                RowSelector::select(take)
+            } else {
+                RowSelector::skip(take)
+            });
+        }
+        if group_selectors.iter().any(|selector| !selector.skip) {
+            row_groups.push(rg);
+            selectors.extend(group_selectors);
+        }
+    }
+
+    Ok((row_groups, RowSelection::from(selectors)))
+}
+
+/// Per-page `(min, max)` of a floating-point column index, `None` for
+/// all-null pages
+fn page_bounds(index: &Index) -> Option<Vec<Option<(f64, f64)>>> {
+    match index {
+        Index::DOUBLE(native) => Some(
+            native
+                .indexes
+                .iter()
+                .map(|page| page.min.zip(page.max))
+                .collect(),
+        ),
+        Index::FLOAT(native) => Some(
+            native
+                .indexes
+                .iter()
+                .map(|page| {
+                    page.min
+                        .zip(page.max)
+                        .map(|(min, max)| (f64::from(min), f64::from(max)))
+                })
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+impl MzPeakReader {
+    /// Batches of the rows whose `column` pages may hold values in
+    /// `[lower, upper]`
+    ///
+    /// Rows outside the range can still be returned (pages are the unit of
+    /// pruning), so callers filter the batches themselves.
+    pub(crate) fn iter_batches_in_range(
+        &self,
+        column: &str,
+        lower: f64,
+        upper: f64,
+    ) -> Result<RecordBatchIterator, ReaderError> {
+        match &self.source {
+            ReaderSource::FilePath(path) => {
+                self.scan_pruned_batches(self.open_positioned(path)?, column, lower, upper)
+            }
+            #[cfg(feature = "container")]
+            ReaderSource::ZipContainer { chunk_reader, .. } => {
+                self.scan_pruned_batches(chunk_reader.clone(), column, lower, upper)
+            }
+        }
+    }
+
+    fn scan_pruned_batches<T: ChunkReader + Clone + 'static>(
+        &self,
+        reader: T,
+        column: &str,
+        lower: f64,
+        upper: f64,
+    ) -> Result<RecordBatchIterator, ReaderError> {
+        let options = ArrowReaderOptions::new().with_page_index(true);
+        let metadata = ArrowReaderMetadata::load(&reader, options)?;
+        let stores_mz_delta = metadata.schema().index_of(columns::MZ_DELTA).is_ok();
+        let (row_groups, selection) = match prune_pages(metadata.metadata(), column, lower, upper) {
+            Some(pruned) => pruned,
+            None if column == columns::MZ && stores_mz_delta => select_mz_delta_spectra(
+                reader.clone(),
+                metadata.clone(),
+                self.config.batch_size,
+                lower,
+                upper,
+            )?,
+            None => return self.iter_batches(),
+        };
+        if row_groups.is_empty() {
+            let empty = std::iter::empty::<Result<_, arrow::error::ArrowError>>();
+            return Ok(RecordBatchIterator::new(empty));
+        }
+
+        let reader = ParquetRecordBatchReaderBuilder::new_with_metadata(reader, metadata)
+            .with_batch_size(self.config.batch_size)
+            .with_row_groups(row_groups)
+            .with_row_selection(selection)
+            .build()?;
+        Ok(RecordBatchIterator::new(reader))
+    }
+}
@@ -0,0 +1,264 @@
+//! Positioned-read I/O for Parquet tables
+//!
+//! [`PositionedReader`] exposes a byte range of a file (a whole `.parquet`
+//! file in a directory bundle, or a stored entry inside a ZIP container) as a
+//! parquet [`ChunkReader`]. The file is opened once and shared; every read is
+//! a positioned read (`pread`), so there is no open/seek per request and
+//! concurrent readers never contend on a file cursor.
+//!
+//! With the `uring` feature on Linux, `IoBackend::Uring` is the default:
+//! large requests are split into segments and submitted to io_uring as one
+//! batch, as are multi-range reads via [`PositionedReader::read_ranges`]. If
+//! the kernel refuses io_uring (old kernels, seccomp sandboxes), reads fall
+//! back to `pread` transparently.
+//!
+//! Targets without positioned reads (neither unix nor windows, e.g. wasm32)
+//! seek and read under a process-wide lock instead.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use parquet::file::reader::{ChunkReader, Length};
+
+/// Size of the segments large reads are split into for batched submission
+#[cfg(all(feature = "uring", target_os = "linux"))]
+const SEGMENT_SIZE: usize = 1024 * 1024;
+
+/// I/O backend used for reading Parquet tables
+///
+/// Defaults to `IoBackend::Uring` when it is available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IoBackend {
+    /// One positioned read (`pread`) per request
+    #[cfg_attr(not(all(feature = "uring", target_os = "linux")), default)]
+    Standard,
+    /// Batched positioned reads submitted through io_uring
+    #[cfg(all(feature = "uring", target_os = "linux"))]
+    #[default]
+    Uring,
+}
+
+/// Read up to `buf.len()` bytes at `offset`, returning the number read
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::FileExt::read_at(file, buf, offset)
+    }
+    #[cfg(windows)]
+    {
+        std::os::windows::fs::FileExt::seek_read(file, buf, offset)
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        use std::io::{Seek, SeekFrom};
+        use std::sync::Mutex;
+
+        // Clones and sub-ranges share one handle, so the seek and the read
+        // must not interleave with another reader's
+        static CURSOR: Mutex<()> = Mutex::new(());
+        let _guard = CURSOR.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut file = file;
+        file.seek(SeekFrom::Start(offset))?;
+        file.read(buf)
+    }
+}
+
+/// Read `buf.len()` bytes at `offset` without moving a file cursor
+fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::FileExt::read_exact_at(file, buf, offset)
+    }
+    #[cfg(not(unix))]
+    {
+        let mut filled = 0;
+        while filled < buf.len() {
+            let n = read_at(file, &mut buf[filled..], offset + filled as u64)?;
+            if n == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            filled += n;
+        }
+        Ok(())
+    }
+}
+
+/// Positioned reader over a byte range of a file
+///
+/// Cloning is cheap: clones share the open file handle.
+#[derive(Debug, Clone)]
+pub struct PositionedReader {
+    file: Arc<File>,
+    offset: u64,
+    len: u64,
+    backend: IoBackend,
+}
+
+impl PositionedReader {
+    /// Open a whole file
+    pub fn open<P: AsRef<Path>>(path: P, backend: IoBackend) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        Ok(Self::from_file(Arc::new(file), 0, len, backend))
+    }
+
+    /// Expose `len` bytes of an open file starting at `offset`
+    pub fn from_file(file: Arc<File>, offset: u64, len: u64, backend: IoBackend) -> Self {
+        Self {
+            file,
+            offset,
+            len,
+            backend,
+        }
+    }
+
+    /// Absolute file offset of the range
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// I/O backend used by this reader
+    pub fn backend(&self) -> IoBackend {
+        self.backend
+    }
+
+    /// Read several ranges in one batch
+    ///
+    /// Each range is `(start, length)` relative to the start of the reader
+    /// and is clamped to its end.
+    pub fn read_ranges(&self, ranges: &[(u64, usize)]) -> io::Result<Vec<Bytes>> {
+        let mut buffers: Vec<Vec<u8>> = ranges
+            .iter()
+            .map(|&(start, length)| vec![0u8; self.clamp(start, length)])
+            .collect();
+
+        #[cfg(all(feature = "uring", target_os = "linux"))]
+        if self.backend == IoBackend::Uring {
+            let mut requests: Vec<(u64, &mut [u8])> = Vec::new();
+            for (&(start, _), buffer) in ranges.iter().zip(buffers.iter_mut()) {
+                let mut offset = self.offset + start;
+                for segment in buffer.chunks_mut(SEGMENT_SIZE) {
+                    let segment_len = segment.len() as u64;
+                    requests.push((offset, segment));
+                    offset += segment_len;
+                }
+            }
+            match super::uring::read_batch(&self.file, &mut requests) {
+                Ok(()) => return Ok(buffers.into_iter().map(Bytes::from).collect()),
+                Err(e) if e.kind() == io::ErrorKind::Unsupported => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        for (&(start, _), buffer) in ranges.iter().zip(buffers.iter_mut()) {
+            read_exact_at(&self.file, buffer, self.offset + start)?;
+        }
+        Ok(buffers.into_iter().map(Bytes::from).collect())
+    }
+
+    /// Number of bytes readable at `start`, at most `length`
+    fn clamp(&self, start: u64, length: usize) -> usize {
+        std::cmp::min(length as u64, self.len.saturating_sub(start)) as usize
+    }
+}
+
+impl Length for PositionedReader {
+    fn len(&self) -> u64 {
+        self.len
+    }
+}
+
+impl ChunkReader for PositionedReader {
+    type T = PositionedSliceReader;
+
+    fn get_read(&self, start: u64) -> parquet::errors::Result<Self::T> {
+        Ok(PositionedSliceReader {
+            file: Arc::clone(&self.file),
+            position: self.offset + start,
+            end: self.offset + self.len,
+        })
+    }
+
+    fn get_bytes(&self, start: u64, length: usize) -> parquet::errors::Result<Bytes> {
+        let mut ranges = self.read_ranges(&[(start, length)]).map_err(|e| {
+            parquet::errors::ParquetError::General(format!("Failed to read bytes: {}", e))
+        })?;
+        Ok(ranges.pop().unwrap_or_default())
+    }
+}
+
+/// Sequential reader over the tail of a [`PositionedReader`] range
+pub struct PositionedSliceReader {
+    file: Arc<File>,
+    /// Absolute file offset of the next read
+    position: u64,
+    /// Absolute file offset where the range ends
+    end: u64,
+}
+
+impl Read for PositionedSliceReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.end.saturating_sub(self.position);
+        if remaining == 0 {
+            return Ok(0);
+        }
+
+        let to_read = std::cmp::min(buf.len() as u64, remaining) as usize;
+        let n = read_at(&self.file, &mut buf[..to_read], self.position)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn sample_file(len: usize) -> (tempfile::NamedTempFile, Vec<u8>) {
+        let data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&data).unwrap();
+        file.flush().unwrap();
+        (file, data)
+    }
+
+    #[test]
+    fn test_read_ranges_all_backends() {
+        let (file, data) = sample_file(3 * 1024 * 1024 + 17);
+        let backends = [
+            IoBackend::Standard,
+            #[cfg(all(feature = "uring", target_os = "linux"))]
+            IoBackend::Uring,
+        ];
+
+        for backend in backends {
+            let reader = PositionedReader::open(file.path(), backend).unwrap();
+            assert_eq!(Length::len(&reader), data.len() as u64);
+
+            let ranges = [(0, 4), (100, 3 * 1024 * 1024 - 100), (data.len() as u64 - 8, 100)];
+            let bytes = reader.read_ranges(&ranges).unwrap();
+            assert_eq!(&bytes[0][..], &data[..4]);
+            assert_eq!(&bytes[1][..], &data[100..3 * 1024 * 1024]);
+            // Clamped to the end of the file
+            assert_eq!(&bytes[2][..], &data[data.len() - 8..]);
+        }
+    }
+
+    #[test]
+    fn test_sub_range_reads() {
+        let (file, data) = sample_file(1000);
+        let shared = Arc::new(File::open(file.path()).unwrap());
+        let reader = PositionedReader::from_file(shared, 200, 300, IoBackend::default());
+
+        assert_eq!(&reader.get_bytes(0, 10).unwrap()[..], &data[200..210]);
+        assert_eq!(&reader.get_bytes(290, 50).unwrap()[..], &data[490..500]);
+
+        let mut content = Vec::new();
+        reader.get_read(250).unwrap().read_to_end(&mut content).unwrap();
+        assert_eq!(content, &data[450..500]);
+    }
+}
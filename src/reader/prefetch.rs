@@ -0,0 +1,82 @@
+//! Row-group prefetching for sequential full-file scans
+//!
+//! A full scan reads row groups strictly in order, so the next row group can
+//! be fetched and decoded on a background thread while the caller processes
+//! the current one. The worker stays at most
+//! [`ReaderConfig::prefetch_row_groups`](super::ReaderConfig::prefetch_row_groups)
+//! row groups ahead and stops as soon as the iterator is dropped.
+//!
+//! Prefetching is off by default: a caller that stops early would pay for
+//! row groups it never reads.
+
+use std::collections::VecDeque;
+
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+use crossbeam_channel::{bounded, Receiver};
+use parquet::arrow::arrow_reader::{ArrowReaderMetadata, ParquetRecordBatchReaderBuilder};
+use parquet::file::reader::ChunkReader;
+
+type BatchResult = Result<RecordBatch, ArrowError>;
+
+/// Iterator yielding the batches of row groups decoded by a background thread
+pub(super) struct PrefetchingBatchIterator {
+    receiver: Receiver<Vec<BatchResult>>,
+    current: VecDeque<BatchResult>,
+}
+
+impl PrefetchingBatchIterator {
+    /// Start decoding all row groups of `reader` in order
+    pub(super) fn spawn<T>(
+        reader: T,
+        metadata: ArrowReaderMetadata,
+        batch_size: usize,
+        prefetch_row_groups: usize,
+    ) -> Self
+    where
+        T: ChunkReader + Clone + 'static,
+    {
+        let num_row_groups = metadata.metadata().num_row_groups();
+        let (sender, receiver) = bounded(prefetch_row_groups.max(1));
+
+        std::thread::spawn(move || {
+            for row_group in 0..num_row_groups {
+                let batches: Vec<BatchResult> =
+                    match ParquetRecordBatchReaderBuilder::new_with_metadata(
+                        reader.clone(),
+                        metadata.clone(),
+                    )
+                    .with_row_groups(vec![row_group])
+                    .with_batch_size(batch_size)
+                    .build()
+                    {
+                        Ok(batch_reader) => batch_reader.collect(),
+                        Err(e) => vec![Err(e.into())],
+                    };
+                let failed = batches.iter().any(|b| b.is_err());
+                // A send error means the consumer dropped the iterator
+                if sender.send(batches).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            receiver,
+            current: VecDeque::new(),
+        }
+    }
+}
+
+impl Iterator for PrefetchingBatchIterator {
+    type Item = BatchResult;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(batch) = self.current.pop_front() {
+                return Some(batch);
+            }
+            self.current = self.receiver.recv().ok()?.into();
+        }
+    }
+}
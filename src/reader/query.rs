@@ -0,0 +1,88 @@
+//! SQL and Substrait query execution over mzPeak tables
+//!
+//! Available with the `datafusion` feature. The container's tables are registered
+//! with an embedded DataFusion session so that every `.mzpeak` file can be queried
+//! like a small database without an external engine:
+//!
+//! | Table | Source | Availability |
+//! |-------|--------|--------------|
+//! | `peaks` | `peaks/peaks.parquet` | Always |
+//! | `spectra` | `spectra/spectra.parquet` | v2.0 datasets only |
+//!
+//! Tables are backed by [`PeaksTableProvider`](super::PeaksTableProvider) and
+//! [`SpectraTableProvider`](super::SpectraTableProvider), which stream row groups
+//! lazily from both directory bundles and ZIP containers.
+
+use std::sync::Arc;
+
+use arrow::record_batch::RecordBatch;
+use datafusion::prelude::{SessionConfig, SessionContext};
+
+use super::{MzPeakReader, ReaderError};
+
+/// Table name under which the peak table is registered
+pub const PEAKS_TABLE: &str = "peaks";
+
+/// Table name under which the spectrum table is registered (v2.0 datasets)
+pub const SPECTRA_TABLE: &str = "spectra";
+
+impl MzPeakReader {
+    /// Execute a SQL query against the container's tables
+    ///
+    /// # Example
+    /// ```rust,ignore
+    /// use mzpeak::reader::MzPeakReader;
+    ///
+    /// let reader = MzPeakReader::open("data.mzpeak")?;
+    /// let batches = reader.sql(
+    ///     "SELECT spectrum_id, COUNT(*) AS n FROM peaks WHERE mz BETWEEN 500 AND 510 GROUP BY spectrum_id",
+    /// )?;
+    /// ```
+    pub fn sql(&self, query: &str) -> Result<Vec<RecordBatch>, ReaderError> {
+        query_runtime()?.block_on(async {
+            let ctx = self.session_context().await?;
+            let df = ctx.sql(query).await?;
+            Ok(df.collect().await?)
+        })
+    }
+
+    /// Execute a serialized Substrait plan against the container's tables
+    ///
+    /// `plan` is the protobuf encoding of a Substrait `Plan` whose named tables
+    /// refer to [`PEAKS_TABLE`] and/or [`SPECTRA_TABLE`].
+    pub fn execute_substrait(&self, plan: &[u8]) -> Result<Vec<RecordBatch>, ReaderError> {
+        query_runtime()?.block_on(async {
+            let ctx = self.session_context().await?;
+            let plan = datafusion_substrait::serializer::deserialize_bytes(plan.to_vec()).await?;
+            let logical_plan =
+                datafusion_substrait::logical_plan::consumer::from_substrait_plan(
+                    &ctx.state(),
+                    &plan,
+                )
+                .await?;
+            let df = ctx.execute_logical_plan(logical_plan).await?;
+            Ok(df.collect().await?)
+        })
+    }
+
+    /// Create a DataFusion session with the container's tables registered
+    ///
+    /// Useful for callers that already run an async runtime or want to register
+    /// additional tables (e.g. search engine results) next to the mzPeak data.
+    pub async fn session_context(&self) -> Result<SessionContext, ReaderError> {
+        let config = SessionConfig::new().with_batch_size(self.config.batch_size);
+        let ctx = SessionContext::new_with_config(config);
+
+        ctx.register_table(PEAKS_TABLE, Arc::new(self.peaks_table_provider()?))?;
+        if let Some(spectra) = self.spectra_table_provider()? {
+            ctx.register_table(SPECTRA_TABLE, Arc::new(spectra))?;
+        }
+
+        Ok(ctx)
+    }
+}
+
+/// Single-threaded runtime used to drive DataFusion from the synchronous reader API
+fn query_runtime() -> Result<tokio::runtime::Runtime, ReaderError> {
+    Ok(tokio::runtime::Builder::new_current_thread().build()?)
+}
@@ -0,0 +1,192 @@
+//! Unit-aware retention time ranges
+//!
+//! mzPeak stores retention times in seconds, while many mzML tools and
+//! instrument methods speak minutes. [`RtRange`] carries the unit in its
+//! constructor so a query reads as `RtRange::minutes(10.0, 20.0)` instead of
+//! a bare `(600.0, 1200.0)`.
+//!
+//! Ranges and single times also parse from strings with an explicit unit,
+//! which is what the CLI accepts:
+//!
+//! | Input | Meaning |
+//! |-------|---------|
+//! | `90`, `90s`, `90 sec` | 90 seconds |
+//! | `1.5min`, `1.5 minutes` | 90 seconds |
+//! | `0.5h` | 1800 seconds |
+//! | `10-20min` | 600 to 1200 seconds (the unit applies to both ends) |
+//! | `90s-2min` | 90 to 120 seconds |
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Error parsing a retention time or range
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid retention time '{input}': {reason}")]
+pub struct ParseRtError {
+    input: String,
+    reason: &'static str,
+}
+
+impl ParseRtError {
+    fn new(input: &str, reason: &'static str) -> Self {
+        Self {
+            input: input.to_string(),
+            reason,
+        }
+    }
+}
+
+/// Inclusive retention time range, stored in seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RtRange {
+    start: f32,
+    end: f32,
+}
+
+impl RtRange {
+    /// Range from `start` to `end` seconds (inclusive)
+    pub fn seconds(start: f32, end: f32) -> Self {
+        Self { start, end }
+    }
+
+    /// Range from `start` to `end` minutes (inclusive)
+    pub fn minutes(start: f32, end: f32) -> Self {
+        Self::seconds(start * 60.0, end * 60.0)
+    }
+
+    /// Range of `half_width` seconds either side of `center` seconds
+    pub fn around_seconds(center: f32, half_width: f32) -> Self {
+        Self::seconds(center - half_width, center + half_width)
+    }
+
+    /// Start of the range in seconds
+    pub fn start_seconds(&self) -> f32 {
+        self.start
+    }
+
+    /// End of the range in seconds
+    pub fn end_seconds(&self) -> f32 {
+        self.end
+    }
+
+    /// Start of the range in minutes
+    pub fn start_minutes(&self) -> f32 {
+        self.start / 60.0
+    }
+
+    /// End of the range in minutes
+    pub fn end_minutes(&self) -> f32 {
+        self.end / 60.0
+    }
+
+    /// Whether a retention time in seconds falls inside the range
+    pub fn contains(&self, retention_time_seconds: f32) -> bool {
+        retention_time_seconds >= self.start && retention_time_seconds <= self.end
+    }
+}
+
+impl fmt::Display for RtRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}s", self.start, self.end)
+    }
+}
+
+impl FromStr for RtRange {
+    type Err = ParseRtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        // Skip the first character so a leading minus sign is not taken as
+        // the range separator
+        let split = trimmed
+            .char_indices()
+            .skip(1)
+            .find(|&(_, c)| c == '-')
+            .map(|(index, _)| index)
+            .ok_or_else(|| ParseRtError::new(s, "expected a range like 10-20min"))?;
+        let (start, end) = (&trimmed[..split], &trimmed[split + 1..]);
+
+        let (end_value, end_scale) =
+            split_unit(end).map_err(|reason| ParseRtError::new(s, reason))?;
+        let (start_value, start_scale) =
+            split_unit(start).map_err(|reason| ParseRtError::new(s, reason))?;
+        // A unit on the end only ("10-20min") applies to both ends
+        let start_scale = start_scale.or(end_scale).unwrap_or(1.0);
+        let end_scale = end_scale.unwrap_or(1.0);
+
+        let range = Self::seconds(start_value * start_scale, end_value * end_scale);
+        if range.start > range.end {
+            return Err(ParseRtError::new(s, "start is after end"));
+        }
+        Ok(range)
+    }
+}
+
+/// Parse a single retention time with an optional unit into seconds.
+///
+/// A bare number is taken as seconds, the unit mzPeak stores.
+pub fn parse_retention_time(s: &str) -> Result<f32, ParseRtError> {
+    let (value, scale) = split_unit(s).map_err(|reason| ParseRtError::new(s, reason))?;
+    Ok(value * scale.unwrap_or(1.0))
+}
+
+/// Split `"1.5 min"` into the value and its scale to seconds, if a unit is given
+fn split_unit(s: &str) -> Result<(f32, Option<f32>), &'static str> {
+    let s = s.trim();
+    let unit_start = s.find(|c: char| c.is_ascii_alphabetic()).unwrap_or(s.len());
+    let (number, unit) = (s[..unit_start].trim(), s[unit_start..].trim());
+
+    let value: f32 = number.parse().map_err(|_| "not a number")?;
+    if !value.is_finite() {
+        return Err("not a finite number");
+    }
+    let scale = match unit.to_ascii_lowercase().as_str() {
+        "" => None,
+        "s" | "sec" | "secs" | "second" | "seconds" => Some(1.0),
+        "min" | "mins" | "minute" | "minutes" => Some(60.0),
+        "h" | "hr" | "hour" | "hours" => Some(3600.0),
+        _ => return Err("unknown unit (use s, min or h)"),
+    };
+    Ok((value, scale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constructors_convert_to_seconds() {
+        let range = RtRange::minutes(10.0, 20.0);
+        assert_eq!(range, RtRange::seconds(600.0, 1200.0));
+        assert_eq!(range.start_minutes(), 10.0);
+        assert!(range.contains(600.0) && range.contains(1200.0));
+        assert!(!range.contains(1200.5));
+        assert_eq!(
+            RtRange::around_seconds(60.0, 5.0),
+            RtRange::seconds(55.0, 65.0)
+        );
+    }
+
+    #[test]
+    fn test_parse_retention_time_units() {
+        assert_eq!(parse_retention_time("90"), Ok(90.0));
+        assert_eq!(parse_retention_time("90s"), Ok(90.0));
+        assert_eq!(parse_retention_time("1.5min"), Ok(90.0));
+        assert_eq!(parse_retention_time("1.5 Minutes"), Ok(90.0));
+        assert_eq!(parse_retention_time("0.5h"), Ok(1800.0));
+        assert!(parse_retention_time("90 parsecs").is_err());
+        assert!(parse_retention_time("min").is_err());
+        assert!(parse_retention_time("inf").is_err());
+    }
+
+    #[test]
+    fn test_parse_rt_range() {
+        assert_eq!("10-20min".parse(), Ok(RtRange::minutes(10.0, 20.0)));
+        assert_eq!("600-1200".parse(), Ok(RtRange::seconds(600.0, 1200.0)));
+        assert_eq!("90s-2min".parse(), Ok(RtRange::seconds(90.0, 120.0)));
+        assert_eq!(" 1min - 90s ".parse(), Ok(RtRange::seconds(60.0, 90.0)));
+        assert_eq!("-5-5s".parse(), Ok(RtRange::seconds(-5.0, 5.0)));
+        assert!("20-10min".parse::<RtRange>().is_err());
+        assert!("90min".parse::<RtRange>().is_err());
+    }
+}
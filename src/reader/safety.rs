@@ -0,0 +1,170 @@
+//! Defenses against hostile containers
+//!
+//! mzPeak containers are routinely exchanged between labs and downloaded from
+//! repositories, so the reader treats their structure as untrusted:
+//!
+//! - Every entry name of a ZIP container is checked when it is opened;
+//!   absolute paths, `..` components, backslashes and NUL bytes are rejected
+//!   (zip-slip), as are containers with more entries than
+//!   [`ContainerLimits::max_entries`].
+//! - Sub-files read into memory (`metadata.json`, `manifest.json`,
+//!   chromatograms, attachments) are bounded by
+//!   [`ContainerLimits::max_json_bytes`] and
+//!   [`ContainerLimits::max_entry_bytes`]. The bound is enforced on the bytes
+//!   actually decompressed, so a ZIP header understating the size of a
+//!   decompression bomb does not get around it.
+//! - Sub-file paths taken from the manifest (attachments) are checked the same
+//!   way for directory bundles, so they cannot point outside the bundle.
+//!
+//! Violations are reported as [`ReaderError::Security`].
+
+use std::fs::File;
+#[cfg(feature = "container")]
+use std::io::BufReader;
+use std::io::Read;
+use std::path::Path;
+
+#[cfg(feature = "container")]
+use zip::read::ZipFile;
+#[cfg(feature = "container")]
+use zip::ZipArchive;
+
+use super::ReaderError;
+
+/// A container violating the [`ContainerLimits`] or the entry path rules
+#[derive(Debug, thiserror::Error)]
+pub enum ContainerSecurityError {
+    /// Entry name that could escape the container when extracted
+    #[error("unsafe entry path '{0}'")]
+    UnsafeEntryPath(String),
+
+    /// More entries than [`ContainerLimits::max_entries`]
+    #[error("container has {count} entries, the limit is {limit}")]
+    TooManyEntries {
+        /// Entries in the container
+        count: usize,
+        /// Configured limit
+        limit: usize,
+    },
+
+    /// Entry larger than the limit for its kind
+    #[error("entry '{name}' exceeds the limit of {limit} bytes")]
+    EntryTooLarge {
+        /// Entry name
+        name: String,
+        /// Configured limit
+        limit: u64,
+    },
+}
+
+/// Resource limits applied when reading containers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContainerLimits {
+    /// Maximum number of entries in a ZIP container
+    pub max_entries: usize,
+    /// Maximum decompressed size of a JSON entry (`metadata.json`,
+    /// `manifest.json`)
+    pub max_json_bytes: u64,
+    /// Maximum decompressed size of any other entry read into memory
+    pub max_entry_bytes: u64,
+}
+
+impl Default for ContainerLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: 10_000,
+            max_json_bytes: 64 * 1024 * 1024,
+            max_entry_bytes: 4 * 1024 * 1024 * 1024,
+        }
+    }
+}
+
+impl ContainerLimits {
+    /// Size limit for the entry `name`
+    fn limit_for(&self, name: &str) -> u64 {
+        if name.ends_with(".json") {
+            self.max_json_bytes
+        } else {
+            self.max_entry_bytes
+        }
+    }
+}
+
+/// Check that an entry path stays inside the container
+///
+/// Paths are relative, `/`-separated and free of `..` components.
+pub fn check_entry_path(name: &str) -> Result<(), ContainerSecurityError> {
+    let unsafe_path = name.is_empty()
+        || name.starts_with('/')
+        || name.contains('\\')
+        || name.contains('\0')
+        || name.as_bytes().get(1) == Some(&b':')
+        || name.split('/').any(|component| component == "..");
+    if unsafe_path {
+        return Err(ContainerSecurityError::UnsafeEntryPath(name.to_string()));
+    }
+    Ok(())
+}
+
+/// Open a ZIP container and check its entry count and entry paths
+#[cfg(feature = "container")]
+pub(super) fn open_archive(
+    zip_path: &Path,
+    limits: &ContainerLimits,
+) -> Result<ZipArchive<BufReader<File>>, ReaderError> {
+    let archive = ZipArchive::new(BufReader::new(File::open(zip_path)?))?;
+    if archive.len() > limits.max_entries {
+        return Err(ContainerSecurityError::TooManyEntries {
+            count: archive.len(),
+            limit: limits.max_entries,
+        }
+        .into());
+    }
+    for name in archive.file_names() {
+        check_entry_path(name)?;
+    }
+    Ok(archive)
+}
+
+/// Read a ZIP entry into memory, bounded by the limit for its kind
+#[cfg(feature = "container")]
+pub(super) fn read_entry(
+    entry: ZipFile<'_>,
+    limits: &ContainerLimits,
+) -> Result<Vec<u8>, ReaderError> {
+    let name = entry.name().to_string();
+    let limit = limits.limit_for(&name);
+    let too_large = || ContainerSecurityError::EntryTooLarge {
+        name: name.clone(),
+        limit,
+    };
+    if entry.size() > limit {
+        return Err(too_large().into());
+    }
+    // The declared size can lie; the `take` bound holds regardless
+    let mut bytes = Vec::new();
+    entry.take(limit + 1).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > limit {
+        return Err(too_large().into());
+    }
+    Ok(bytes)
+}
+
+/// Read a sub-file of a directory bundle, bounded by the limit for its kind
+pub(super) fn read_file(
+    path: &Path,
+    name: &str,
+    limits: &ContainerLimits,
+) -> Result<Vec<u8>, ReaderError> {
+    let limit = limits.limit_for(name);
+    let mut bytes = Vec::new();
+    File::open(path)?.take(limit + 1).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > limit {
+        return Err(ContainerSecurityError::EntryTooLarge {
+            name: name.to_string(),
+            limit,
+        }
+        .into());
+    }
+    Ok(bytes)
+}
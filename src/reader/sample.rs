@@ -0,0 +1,346 @@
+//! Seeded random sampling of spectra
+//!
+//! [`MzPeakReader::sample_spectra`] draws a uniform random subset of the
+//! spectra in a run, for quick looks at a large file or for subsampling
+//! training data. Only the sampled spectra are decoded in full:
+//!
+//! - v2.0 datasets list the candidates from the cached spectrum index (plus
+//!   the `ms_level` column of the spectra table when filtering by MS level)
+//!   and read each sampled spectrum through its row pointers.
+//! - v1 files draw IDs uniformly from the range spanned by the
+//!   `spectrum_id` row-group statistics and keep the draws that hit a stored
+//!   spectrum (of the requested MS level). The `spectrum_id` and `ms_level`
+//!   columns are read only from the row groups a draw lands in, then only
+//!   the row groups holding a sampled spectrum are read in full. Files whose
+//!   statistics are missing or whose IDs do not ascend list every candidate
+//!   first.
+//!
+//! The same seed on the same file always gives the same sample.
+
+use std::collections::HashSet;
+
+use parquet::arrow::arrow_reader::{
+    ArrowReaderMetadata, ArrowReaderOptions, ParquetRecordBatchReaderBuilder,
+};
+use parquet::arrow::ProjectionMask;
+use parquet::file::metadata::ParquetMetaData;
+use parquet::file::reader::ChunkReader;
+use parquet::file::statistics::Statistics;
+
+use super::config::ReaderSource;
+use super::spectra::SpectrumArraysView;
+use super::utils::{get_int16_column, get_int64_column};
+use super::{MzPeakReader, ReaderError};
+use crate::schema::columns;
+
+impl MzPeakReader {
+    /// Uniform random sample of `n` spectra, in storage order
+    ///
+    /// With `ms_level` set, only spectra of that level are candidates. Returns
+    /// every candidate when there are at most `n`.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use mzpeak::reader::MzPeakReader;
+    ///
+    /// let reader = MzPeakReader::open("data.mzpeak")?;
+    /// let ms2 = reader.sample_spectra(500, 42, Some(2))?;
+    /// println!("Sampled {} MS2 spectra", ms2.len());
+    /// # Ok::<(), mzpeak::reader::ReaderError>(())
+    /// ```
+    pub fn sample_spectra(
+        &self,
+        n: usize,
+        seed: u64,
+        ms_level: Option<i16>,
+    ) -> Result<Vec<SpectrumArraysView>, ReaderError> {
+        if self.spectrum_index()?.is_none() {
+            if let Some(ids) = self.sample_v1_spectrum_ids(n, seed, ms_level)? {
+                return self.get_spectra_arrays(&ids);
+            }
+        }
+
+        let candidates = self.stored_spectrum_ids(ms_level)?;
+        let ids: Vec<i64> = sample_positions(candidates.len(), n, seed)
+            .into_iter()
+            .map(|position| candidates[position])
+            .collect();
+        self.get_spectra_arrays(&ids)
+    }
+
+    /// Spectrum IDs in storage order, optionally of one MS level, without
+    /// decoding any peaks
+    pub(super) fn stored_spectrum_ids(
+        &self,
+        ms_level: Option<i16>,
+    ) -> Result<Vec<i64>, ReaderError> {
+        match (self.spectrum_index()?, ms_level) {
+            (Some(index), None) => Ok(index.spectrum_ids()),
+            (Some(index), Some(level)) => {
+                index.spectrum_ids_at_level(level, self.config.batch_size)
+            }
+            (None, _) => self.spectrum_ids_by_level(ms_level),
+        }
+    }
+
+    /// Spectrum IDs of a v1 file, reading only the ID and MS level columns
+    fn spectrum_ids_by_level(&self, ms_level: Option<i16>) -> Result<Vec<i64>, ReaderError> {
+        match &self.source {
+            ReaderSource::FilePath(path) => {
+                self.scan_spectrum_ids(self.open_positioned(path)?, ms_level)
+            }
+            #[cfg(feature = "container")]
+            ReaderSource::ZipContainer { chunk_reader, .. } => {
+                self.scan_spectrum_ids(chunk_reader.clone(), ms_level)
+            }
+        }
+    }
+
+    /// Sampled spectrum IDs of a v1 file, ascending, or `None` when the
+    /// `spectrum_id` statistics cannot drive the sampling
+    fn sample_v1_spectrum_ids(
+        &self,
+        n: usize,
+        seed: u64,
+        ms_level: Option<i16>,
+    ) -> Result<Option<Vec<i64>>, ReaderError> {
+        match &self.source {
+            ReaderSource::FilePath(path) => {
+                self.sample_row_groups(self.open_positioned(path)?, n, seed, ms_level)
+            }
+            #[cfg(feature = "container")]
+            ReaderSource::ZipContainer { chunk_reader, .. } => {
+                self.sample_row_groups(chunk_reader.clone(), n, seed, ms_level)
+            }
+        }
+    }
+
+    /// Rejection sampling over the ID range of the row groups
+    ///
+    /// Every stored candidate is equally likely to be drawn first, so the
+    /// accepted draws are a uniform sample. If too few draws hit, the
+    /// remaining row groups are read and the rest of the sample is taken from
+    /// the candidates not drawn yet.
+    fn sample_row_groups<T: ChunkReader + Clone + 'static>(
+        &self,
+        reader: T,
+        n: usize,
+        seed: u64,
+        ms_level: Option<i16>,
+    ) -> Result<Option<Vec<i64>>, ReaderError> {
+        let metadata = ArrowReaderMetadata::load(&reader, ArrowReaderOptions::default())?;
+        let Some(ranges) = spectrum_id_ranges(metadata.metadata()) else {
+            return Ok(None);
+        };
+        let (Some(&(first, _)), Some(&(_, last))) = (ranges.first(), ranges.last()) else {
+            return Ok(Some(Vec::new()));
+        };
+        let universe = last.abs_diff(first).saturating_add(1);
+
+        let read_candidates = |row_groups: Vec<usize>, candidates: &mut HashSet<i64>| {
+            self.scan_spectrum_ids_in(
+                ParquetRecordBatchReaderBuilder::new_with_metadata(
+                    reader.clone(),
+                    metadata.clone(),
+                )
+                .with_row_groups(row_groups),
+                ms_level,
+            )
+            .map(|ids| candidates.extend(ids))
+        };
+
+        let mut rng = SplitMix64(seed);
+        let mut read = vec![false; ranges.len()];
+        let mut candidates = HashSet::new();
+        let mut drawn = HashSet::new();
+        let mut sample = Vec::with_capacity(n);
+        // Sparse IDs make most draws miss; give up on drawing well before
+        // that costs more than reading the remaining row groups
+        let max_draws = n.saturating_mul(8).saturating_add(ranges.len());
+        for _ in 0..max_draws {
+            if sample.len() == n || drawn.len() as u64 == universe {
+                break;
+            }
+            let id = first.wrapping_add(rng.below_u64(universe) as i64);
+            if !drawn.insert(id) {
+                continue;
+            }
+            // A spectrum straddling two row groups is found in the first
+            let group = ranges.partition_point(|&(_, max)| max < id);
+            if ranges[group].0 > id {
+                continue;
+            }
+            if !read[group] {
+                read_candidates(vec![group], &mut candidates)?;
+                read[group] = true;
+            }
+            if candidates.contains(&id) {
+                sample.push(id);
+            }
+        }
+
+        if sample.len() < n {
+            let unread: Vec<usize> = (0..ranges.len()).filter(|&group| !read[group]).collect();
+            if !unread.is_empty() {
+                read_candidates(unread, &mut candidates)?;
+            }
+            // Candidates not drawn yet are in uniformly random order
+            let mut remaining: Vec<i64> = candidates
+                .into_iter()
+                .filter(|id| !drawn.contains(id))
+                .collect();
+            remaining.sort_unstable();
+            let positions = sample_positions(remaining.len(), n - sample.len(), rng.next_u64());
+            sample.extend(positions.into_iter().map(|position| remaining[position]));
+        }
+
+        sample.sort_unstable();
+        Ok(Some(sample))
+    }
+
+    fn scan_spectrum_ids<T: ChunkReader + 'static>(
+        &self,
+        reader: T,
+        ms_level: Option<i16>,
+    ) -> Result<Vec<i64>, ReaderError> {
+        self.scan_spectrum_ids_in(ParquetRecordBatchReaderBuilder::try_new(reader)?, ms_level)
+    }
+
+    /// Spectrum IDs of the rows selected by `builder`, in storage order
+    fn scan_spectrum_ids_in<T: ChunkReader + 'static>(
+        &self,
+        builder: ParquetRecordBatchReaderBuilder<T>,
+        ms_level: Option<i16>,
+    ) -> Result<Vec<i64>, ReaderError> {
+        let projection = ProjectionMask::columns(
+            builder.parquet_schema(),
+            [columns::SPECTRUM_ID, columns::MS_LEVEL],
+        );
+        let batches = builder
+            .with_projection(projection)
+            .with_batch_size(self.config.batch_size)
+            .build()?;
+
+        // Peaks of a spectrum are contiguous, so a change of ID starts a spectrum
+        let mut ids = Vec::new();
+        let mut last_id = None;
+        for batch in batches {
+            let batch = batch?;
+            let spectrum_ids = get_int64_column(&batch, columns::SPECTRUM_ID)?;
+            let ms_levels = get_int16_column(&batch, columns::MS_LEVEL)?;
+            for i in 0..batch.num_rows() {
+                let id = spectrum_ids.value(i);
+                if last_id == Some(id) {
+                    continue;
+                }
+                last_id = Some(id);
+                if ms_level.is_some_and(|level| ms_levels.value(i) != level) {
+                    continue;
+                }
+                ids.push(id);
+            }
+        }
+        Ok(ids)
+    }
+}
+
+/// Exact `spectrum_id` range of every row group, if the ranges ascend
+/// through the file
+fn spectrum_id_ranges(metadata: &ParquetMetaData) -> Option<Vec<(i64, i64)>> {
+    let column = metadata
+        .file_metadata()
+        .schema_descr()
+        .columns()
+        .iter()
+        .position(|column| column.name() == columns::SPECTRUM_ID)?;
+    let ranges = metadata
+        .row_groups()
+        .iter()
+        .map(|row_group| match row_group.column(column).statistics() {
+            Some(Statistics::Int64(stats)) if stats.min_is_exact() && stats.max_is_exact() => {
+                Some((*stats.min_opt()?, *stats.max_opt()?))
+            }
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    // Consecutive row groups share at most the spectrum straddling them
+    ranges
+        .windows(2)
+        .all(|pair| pair[0].1 <= pair[1].0)
+        .then_some(ranges)
+}
+
+/// `n` distinct positions drawn uniformly from `0..len`, ascending
+///
+/// Floyd's algorithm: memory and time are proportional to `n`, not `len`.
+fn sample_positions(len: usize, n: usize, seed: u64) -> Vec<usize> {
+    if n >= len {
+        return (0..len).collect();
+    }
+    let mut rng = SplitMix64(seed);
+    let mut chosen = HashSet::with_capacity(n);
+    for upper in len - n..len {
+        let position = rng.below(upper + 1);
+        if !chosen.insert(position) {
+            chosen.insert(upper);
+        }
+    }
+    let mut positions: Vec<usize> = chosen.into_iter().collect();
+    positions.sort_unstable();
+    positions
+}
+
+/// Small seedable generator (SplitMix64), stable across platforms and releases
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Value in `0..bound`
+    fn below(&mut self, bound: usize) -> usize {
+        self.below_u64(bound as u64) as usize
+    }
+
+    /// Value in `0..bound`
+    fn below_u64(&mut self, bound: u64) -> u64 {
+        ((u128::from(self.next_u64()) * u128::from(bound)) >> 64) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_positions_distinct_and_reproducible() {
+        let positions = sample_positions(1000, 50, 7);
+        assert_eq!(positions.len(), 50);
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+        assert!(positions.iter().all(|&p| p < 1000));
+        assert_eq!(positions, sample_positions(1000, 50, 7));
+        assert_ne!(positions, sample_positions(1000, 50, 8));
+        assert_eq!(sample_positions(3, 10, 7), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_sample_positions_uniform() {
+        let mut counts = [0usize; 10];
+        for seed in 0..2000 {
+            for position in sample_positions(10, 3, seed) {
+                counts[position] += 1;
+            }
+        }
+        // 600 expected per position
+        assert!(
+            counts.iter().all(|&c| (500..700).contains(&c)),
+            "{:?}",
+            counts
+        );
+    }
+}
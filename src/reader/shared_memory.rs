@@ -0,0 +1,296 @@
+//! Shared-memory Arrow IPC export
+//!
+//! Multi-process GUIs decode a dataset in one process and plot it in another.
+//! Instead of serializing peaks through a pipe, a reader can publish its
+//! peak batches (or a single spectrum) as an Arrow IPC file in shared memory
+//! under a caller-chosen key. The consumer memory-maps the segment and reads
+//! the arrays in place:
+//!
+//! ```python
+//! import pyarrow as pa
+//! table = pa.ipc.open_file(pa.memory_map("/dev/shm/mzpeak-run42.arrow")).read_all()
+//! ```
+//!
+//! On Linux segments live in `/dev/shm`, the tmpfs backing POSIX shared
+//! memory; elsewhere they fall back to the system temp directory. A segment
+//! appears atomically once complete and is removed when its
+//! [`SharedMemoryBatches`] handle is dropped, unless it is
+//! [persisted](SharedMemoryBatches::persist).
+
+use std::fs;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arrow::datatypes::Schema;
+use arrow::ipc::reader::FileReader;
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+
+use super::spectra::SpectrumArraysView;
+use super::{MzPeakReader, ReaderError};
+
+/// File name prefix of shared-memory segments
+const SEGMENT_PREFIX: &str = "mzpeak-";
+
+/// Longest accepted segment key
+const MAX_KEY_LEN: usize = 200;
+
+/// Handle to peak batches published in shared memory
+#[derive(Debug)]
+pub struct SharedMemoryBatches {
+    key: String,
+    path: PathBuf,
+    num_batches: usize,
+    num_rows: usize,
+    size_bytes: u64,
+    persist: bool,
+}
+
+impl SharedMemoryBatches {
+    /// Key the segment was published under
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Path other processes memory-map to attach
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of record batches in the segment
+    pub fn num_batches(&self) -> usize {
+        self.num_batches
+    }
+
+    /// Number of rows (peaks) in the segment
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    /// Size of the segment in bytes
+    pub fn size_bytes(&self) -> u64 {
+        self.size_bytes
+    }
+
+    /// Keep the segment after this handle is dropped and return its path.
+    ///
+    /// The consumer becomes responsible for removing it, e.g. with
+    /// [`remove_shared_memory`].
+    pub fn persist(mut self) -> PathBuf {
+        self.persist = true;
+        self.path.clone()
+    }
+}
+
+impl Drop for SharedMemoryBatches {
+    fn drop(&mut self) {
+        if !self.persist {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Directory holding shared-memory segments
+pub fn shared_memory_dir() -> PathBuf {
+    let shm = Path::new("/dev/shm");
+    if cfg!(target_os = "linux") && shm.is_dir() {
+        shm.to_path_buf()
+    } else {
+        std::env::temp_dir()
+    }
+}
+
+/// Path of the segment for `key`.
+///
+/// Keys may contain ASCII letters, digits, `-`, `_` and `.`.
+pub fn shared_memory_path(key: &str) -> Result<PathBuf, ReaderError> {
+    let valid = !key.is_empty()
+        && key.len() <= MAX_KEY_LEN
+        && !key.starts_with('.')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(ReaderError::IoError(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid shared memory key: {:?}", key),
+        )));
+    }
+    Ok(shared_memory_dir().join(format!("{}{}.arrow", SEGMENT_PREFIX, key)))
+}
+
+/// Publish record batches in shared memory under `key`.
+///
+/// Batches are streamed into the segment, so memory use stays bounded by one
+/// batch. Fails if a segment with the same key already exists.
+pub fn export_to_shared_memory<I>(
+    key: &str,
+    schema: Arc<Schema>,
+    batches: I,
+) -> Result<SharedMemoryBatches, ReaderError>
+where
+    I: IntoIterator<Item = Result<RecordBatch, ReaderError>>,
+{
+    let path = shared_memory_path(key)?;
+    let dir = shared_memory_dir();
+
+    // Written under a hidden name and renamed, so attaching processes never
+    // observe a partial segment
+    let staging = tempfile::Builder::new()
+        .prefix(".mzpeak-shm")
+        .tempfile_in(&dir)?;
+    let mut writer = FileWriter::try_new(BufWriter::new(staging.as_file()), &schema)?;
+    let mut num_batches = 0;
+    let mut num_rows = 0;
+    for batch in batches {
+        let batch = batch?;
+        num_rows += batch.num_rows();
+        num_batches += 1;
+        writer.write(&batch)?;
+    }
+    writer.finish()?;
+    drop(writer);
+
+    staging.persist_noclobber(&path).map_err(|e| {
+        if e.error.kind() == io::ErrorKind::AlreadyExists {
+            ReaderError::IoError(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("shared memory key {:?} is already in use", key),
+            ))
+        } else {
+            ReaderError::IoError(e.error)
+        }
+    })?;
+    let size_bytes = fs::metadata(&path)?.len();
+
+    Ok(SharedMemoryBatches {
+        key: key.to_string(),
+        path,
+        num_batches,
+        num_rows,
+        size_bytes,
+        persist: false,
+    })
+}
+
+/// Attach to the segment published under `key` and read its batches
+pub fn open_shared_memory(key: &str) -> Result<Vec<RecordBatch>, ReaderError> {
+    let file = fs::File::open(shared_memory_path(key)?)?;
+    let reader = FileReader::try_new(io::BufReader::new(file), None)?;
+    reader
+        .map(|batch| batch.map_err(ReaderError::from))
+        .collect()
+}
+
+/// Remove a persisted segment
+pub fn remove_shared_memory(key: &str) -> Result<(), ReaderError> {
+    fs::remove_file(shared_memory_path(key)?)?;
+    Ok(())
+}
+
+impl MzPeakReader {
+    /// Publish all peak batches in shared memory under `key`.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use mzpeak::reader::MzPeakReader;
+    ///
+    /// let reader = MzPeakReader::open("data.mzpeak")?;
+    /// let segment = reader.to_shared_memory("run42")?;
+    /// println!("attach to {}", segment.path().display());
+    /// # Ok::<(), mzpeak::reader::ReaderError>(())
+    /// ```
+    pub fn to_shared_memory(&self, key: &str) -> Result<SharedMemoryBatches, ReaderError> {
+        export_to_shared_memory(key, self.schema(), self.iter_batches()?)
+    }
+}
+
+impl SpectrumArraysView {
+    /// Publish this spectrum's peaks in shared memory under `key`.
+    ///
+    /// Spectrum ID, MS level, retention time and precursor m/z are stored as
+    /// schema metadata. A spectrum without peaks is published with an empty
+    /// schema.
+    pub fn to_shared_memory(&self, key: &str) -> Result<SharedMemoryBatches, ReaderError> {
+        let batches = self.record_batches();
+        let fields = batches
+            .first()
+            .map(|batch| batch.schema().fields().clone())
+            .unwrap_or_default();
+
+        let mut metadata = std::collections::HashMap::new();
+        metadata.insert(
+            "mzpeak:spectrum_id".to_string(),
+            self.spectrum_id.to_string(),
+        );
+        metadata.insert("mzpeak:ms_level".to_string(), self.ms_level.to_string());
+        metadata.insert(
+            "mzpeak:retention_time".to_string(),
+            self.retention_time.to_string(),
+        );
+        if let Some(precursor_mz) = self.precursor_mz {
+            metadata.insert("mzpeak:precursor_mz".to_string(), precursor_mz.to_string());
+        }
+        let schema = Arc::new(Schema::new_with_metadata(fields, metadata));
+
+        let batches = batches.into_iter().map(|batch| {
+            Ok(RecordBatch::try_new(
+                schema.clone(),
+                batch.columns().to_vec(),
+            )?)
+        });
+        export_to_shared_memory(key, schema.clone(), batches)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_memory_key_validation() {
+        assert!(shared_memory_path("run42").is_ok());
+        assert!(shared_memory_path("run_42.ms1-v2").is_ok());
+        assert!(shared_memory_path("").is_err());
+        assert!(shared_memory_path("../etc/passwd").is_err());
+        assert!(shared_memory_path(".hidden").is_err());
+        assert!(shared_memory_path("a/b").is_err());
+        assert!(shared_memory_path(&"k".repeat(MAX_KEY_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_export_roundtrip_and_cleanup() {
+        use arrow::array::Float64Array;
+        use arrow::datatypes::{DataType, Field};
+
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "mz",
+            DataType::Float64,
+            false,
+        )]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Float64Array::from(vec![100.0, 200.0]))],
+        )
+        .unwrap();
+
+        let key = format!("test-{}", uuid::Uuid::new_v4());
+        let segment =
+            export_to_shared_memory(&key, schema.clone(), vec![Ok(batch.clone())]).unwrap();
+        assert_eq!(segment.num_rows(), 2);
+        assert_eq!(segment.num_batches(), 1);
+        assert!(segment.size_bytes() > 0);
+
+        // The key cannot be reused while the segment exists
+        assert!(export_to_shared_memory(&key, schema, vec![Ok(batch.clone())]).is_err());
+
+        let batches = open_shared_memory(&key).unwrap();
+        assert_eq!(batches, vec![batch]);
+
+        let path = segment.path().to_path_buf();
+        drop(segment);
+        assert!(!path.exists());
+        assert!(open_shared_memory(&key).is_err());
+    }
+}
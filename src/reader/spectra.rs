@@ -1,14 +1,15 @@
 use std::collections::HashSet;
-use std::fs::File;
+use std::sync::Arc;
 
-use arrow::array::{Array, Float32Array, Float64Array};
+use arrow::array::{Array, ArrayRef, Float32Array, Float64Array, Int64Array};
+use arrow::datatypes::{DataType, Field, Schema};
 use arrow::record_batch::RecordBatch;
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
 use parquet::file::metadata::ParquetMetaData;
 use parquet::file::statistics::Statistics;
 
 use crate::schema::columns;
-use crate::writer::{OptionalColumnBuf, PeakArrays, SpectrumArrays};
+use crate::writer::{OptionalColumnBuf, PeakArrays, SpectrumArrays, SpectrumMetadata};
 
 use super::config::ReaderSource;
 use super::utils::{
@@ -16,6 +17,8 @@ use super::utils::{
     get_optional_f32, get_optional_f64, get_optional_float32_column, get_optional_float64_column,
     get_optional_i16, get_optional_i32, get_optional_int16_column, get_optional_int32_column,
 };
+use super::rt_range::RtRange;
+use super::transform::SpectrumTransforms;
 use super::{MzPeakReader, ReaderError, RecordBatchIterator};
 
 fn spectrum_id_column_index(metadata: &ParquetMetaData) -> Option<usize> {
@@ -27,11 +30,11 @@ fn spectrum_id_column_index(metadata: &ParquetMetaData) -> Option<usize> {
         .position(|column| column.name() == columns::SPECTRUM_ID)
 }
 
-fn row_groups_for_spectrum_id_range(
+/// Row groups that may hold any of `spectrum_ids` (sorted ascending)
+fn row_groups_for_spectrum_ids(
     metadata: &ParquetMetaData,
     column_index: usize,
-    min_id: i64,
-    max_id: i64,
+    spectrum_ids: &[i64],
 ) -> Vec<usize> {
     let mut row_groups = Vec::new();
     let num_row_groups = metadata.num_row_groups();
@@ -44,7 +47,8 @@ fn row_groups_for_spectrum_id_range(
                 let max = stats.max_opt();
                 if stats.min_is_exact() && stats.max_is_exact() {
                     if let (Some(min), Some(max)) = (min, max) {
-                        if max_id >= *min && min_id <= *max {
+                        let first = spectrum_ids.partition_point(|id| id < min);
+                        if spectrum_ids.get(first).is_some_and(|id| id <= max) {
                             row_groups.push(i);
                         }
                     } else {
@@ -62,17 +66,14 @@ fn row_groups_for_spectrum_id_range(
 }
 
 impl MzPeakReader {
-    fn build_iter_for_spectrum_id_range<T: parquet::file::reader::ChunkReader + 'static>(
+    fn build_iter_for_spectrum_ids<T: parquet::file::reader::ChunkReader + 'static>(
         &self,
         builder: ParquetRecordBatchReaderBuilder<T>,
-        min_id: i64,
-        max_id: i64,
+        spectrum_ids: &[i64],
     ) -> Result<RecordBatchIterator, ReaderError> {
         let metadata = builder.metadata();
         let row_groups = spectrum_id_column_index(metadata)
-            .map(|column_index| {
-                row_groups_for_spectrum_id_range(metadata, column_index, min_id, max_id)
-            })
+            .map(|column_index| row_groups_for_spectrum_ids(metadata, column_index, spectrum_ids))
             .unwrap_or_else(|| (0..metadata.num_row_groups()).collect());
 
         if row_groups.is_empty() {
@@ -87,24 +88,23 @@ impl MzPeakReader {
         Ok(RecordBatchIterator::new(reader))
     }
 
-    fn iter_batches_for_spectrum_id_range(
+    /// Batches of the row groups that may hold any of `spectrum_ids` (sorted ascending)
+    fn iter_batches_for_spectrum_ids(
         &self,
-        min_id: i64,
-        max_id: i64,
+        spectrum_ids: &[i64],
     ) -> Result<RecordBatchIterator, ReaderError> {
         match &self.source {
             ReaderSource::FilePath(path) => {
-                let file = File::open(path)?;
-                self.build_iter_for_spectrum_id_range(
+                let file = self.open_positioned(path)?;
+                self.build_iter_for_spectrum_ids(
                     ParquetRecordBatchReaderBuilder::try_new(file)?,
-                    min_id,
-                    max_id,
+                    spectrum_ids,
                 )
             }
-            ReaderSource::ZipContainer { chunk_reader, .. } => self.build_iter_for_spectrum_id_range(
+            #[cfg(feature = "container")]
+            ReaderSource::ZipContainer { chunk_reader, .. } => self.build_iter_for_spectrum_ids(
                 ParquetRecordBatchReaderBuilder::try_new(chunk_reader.clone())?,
-                min_id,
-                max_id,
+                spectrum_ids,
             ),
         }
     }
@@ -126,20 +126,49 @@ impl MzPeakReader {
         &self,
     ) -> Result<StreamingSpectrumArraysViewIterator, ReaderError> {
         let batch_iter = self.iter_batches()?;
-        Ok(StreamingSpectrumArraysViewIterator::new(batch_iter))
+        Ok(StreamingSpectrumArraysViewIterator::new(batch_iter)
+            .with_transforms(self.config.transforms.clone()))
     }
 
-    /// Query spectra by retention time range (inclusive), SoA layout
+    /// Query spectra by retention time range in seconds (inclusive), SoA layout
+    ///
+    /// Prefer [`spectra_in_rt_range`](Self::spectra_in_rt_range), which makes
+    /// the unit explicit.
     pub fn spectra_by_rt_range_arrays(
         &self,
         start_rt: f32,
         end_rt: f32,
     ) -> Result<Vec<SpectrumArraysView>, ReaderError> {
-        let all_spectra = self.iter_spectra_arrays()?;
-        Ok(all_spectra
-            .into_iter()
-            .filter(|s| s.retention_time >= start_rt && s.retention_time <= end_rt)
-            .collect())
+        self.spectra_in_rt_range(RtRange::seconds(start_rt, end_rt))
+    }
+
+    /// Query spectra inside a retention time range, SoA layout
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use mzpeak::reader::{MzPeakReader, RtRange};
+    ///
+    /// let reader = MzPeakReader::open("data.mzpeak")?;
+    /// let spectra = reader.spectra_in_rt_range(RtRange::minutes(10.0, 12.5))?;
+    /// # Ok::<(), mzpeak::reader::ReaderError>(())
+    /// ```
+    pub fn spectra_in_rt_range(
+        &self,
+        range: RtRange,
+    ) -> Result<Vec<SpectrumArraysView>, ReaderError> {
+        let batches = self.iter_batches_in_range(
+            columns::RETENTION_TIME,
+            f64::from(range.start_seconds()),
+            f64::from(range.end_seconds()),
+        )?;
+        let mut matches = Vec::new();
+        for spectrum in StreamingSpectrumArraysViewIterator::new(batches) {
+            let spectrum = spectrum?;
+            if range.contains(spectrum.retention_time) {
+                matches.push(self.config.transforms.apply_view(spectrum)?);
+            }
+        }
+        Ok(matches)
     }
 
     /// Query spectra by MS level, SoA layout
@@ -154,17 +183,49 @@ impl MzPeakReader {
             .collect())
     }
 
+    /// Query spectra by polarity (1 positive, -1 negative, 0 unknown), SoA layout
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use mzpeak::reader::MzPeakReader;
+    ///
+    /// let reader = MzPeakReader::open("data.mzpeak")?;
+    /// let negative = reader.spectra_by_polarity_arrays(-1)?;
+    /// # Ok::<(), mzpeak::reader::ReaderError>(())
+    /// ```
+    pub fn spectra_by_polarity_arrays(
+        &self,
+        polarity: i8,
+    ) -> Result<Vec<SpectrumArraysView>, ReaderError> {
+        let all_spectra = self.iter_spectra_arrays()?;
+        Ok(all_spectra
+            .into_iter()
+            .filter(|s| s.polarity == polarity)
+            .collect())
+    }
+
     /// Get a specific spectrum by ID, SoA layout
+    ///
+    /// For v2.0 datasets the spectrum is resolved through a cached
+    /// `spectrum_id → location` map that is built on the first lookup (see
+    /// [`spectrum_location`](Self::spectrum_location)).
     pub fn get_spectrum_arrays(
         &self,
         spectrum_id: i64,
     ) -> Result<Option<SpectrumArraysView>, ReaderError> {
-        let batch_iter = self.iter_batches_for_spectrum_id_range(spectrum_id, spectrum_id)?;
+        if let Some(index) = self.spectrum_index()? {
+            return index
+                .read_spectrum(spectrum_id)?
+                .map(|view| self.config.transforms.apply_view(view))
+                .transpose();
+        }
+
+        let batch_iter = self.iter_batches_for_spectrum_ids(&[spectrum_id])?;
         let iter = StreamingSpectrumArraysViewIterator::new(batch_iter);
         for spectrum in iter {
             let spectrum = spectrum?;
             if spectrum.spectrum_id == spectrum_id {
-                return Ok(Some(spectrum));
+                return self.config.transforms.apply_view(spectrum).map(Some);
             }
         }
         Ok(None)
@@ -180,15 +241,27 @@ impl MzPeakReader {
             return Ok(Vec::new());
         }
 
-        let min_id = **id_set.iter().min().unwrap();
-        let max_id = **id_set.iter().max().unwrap();
-        let batch_iter = self.iter_batches_for_spectrum_id_range(min_id, max_id)?;
+        if let Some(index) = self.spectrum_index()? {
+            let mut ids: Vec<i64> = id_set.into_iter().copied().collect();
+            ids.sort_unstable();
+            let mut matches = Vec::with_capacity(ids.len());
+            for id in ids {
+                if let Some(view) = index.read_spectrum(id)? {
+                    matches.push(self.config.transforms.apply_view(view)?);
+                }
+            }
+            return Ok(matches);
+        }
+
+        let mut ids: Vec<i64> = id_set.iter().map(|&&id| id).collect();
+        ids.sort_unstable();
+        let batch_iter = self.iter_batches_for_spectrum_ids(&ids)?;
         let iter = StreamingSpectrumArraysViewIterator::new(batch_iter);
         let mut matches = Vec::new();
         for spectrum in iter {
             let spectrum = spectrum?;
             if id_set.contains(&spectrum.spectrum_id) {
-                matches.push(spectrum);
+                matches.push(self.config.transforms.apply_view(spectrum)?);
             }
         }
         Ok(matches)
@@ -310,11 +383,123 @@ impl SpectrumArraysView {
         })
     }
 
+    /// Build a view from v2.0 spectrum metadata and the peak-table batches
+    /// holding exactly this spectrum's peaks
+    pub(super) fn from_v2_parts(metadata: &SpectrumMetadata, peak_batches: Vec<RecordBatch>) -> Self {
+        let segments: Vec<_> = peak_batches
+            .into_iter()
+            .filter(|batch| batch.num_rows() > 0)
+            .map(|batch| SpectrumArraysViewSegment {
+                start: 0,
+                len: batch.num_rows(),
+                batch,
+            })
+            .collect();
+        let num_peaks = segments.iter().map(|s| s.len).sum();
+
+        Self {
+            segments,
+            spectrum_id: metadata.spectrum_id as i64,
+            scan_number: metadata.scan_number.unwrap_or_default() as i64,
+            ms_level: metadata.ms_level as i16,
+            retention_time: metadata.retention_time,
+            polarity: metadata.polarity,
+            precursor_mz: metadata.precursor_mz,
+            precursor_charge: metadata.precursor_charge.map(i16::from),
+            precursor_intensity: metadata.precursor_intensity,
+            isolation_window_lower: metadata.isolation_window_lower,
+            isolation_window_upper: metadata.isolation_window_upper,
+            collision_energy: metadata.collision_energy,
+            total_ion_current: metadata.total_ion_current,
+            base_peak_mz: metadata.base_peak_mz,
+            base_peak_intensity: metadata.base_peak_intensity,
+            injection_time: metadata.injection_time,
+            pixel_x: metadata.pixel_x.map(i32::from),
+            pixel_y: metadata.pixel_y.map(i32::from),
+            pixel_z: metadata.pixel_z.map(i32::from),
+            num_peaks,
+        }
+    }
+
+    /// View over an owned spectrum, its peaks held in a single batch of
+    /// `spectrum_id`, `mz`, `intensity` and (when present) `ion_mobility`
+    pub(super) fn from_owned(spectrum: &SpectrumArrays) -> Result<Self, ReaderError> {
+        let peaks = &spectrum.peaks;
+        let num_peaks = peaks.mz.len();
+        let mut fields = vec![
+            Field::new(columns::SPECTRUM_ID, DataType::Int64, false),
+            Field::new(columns::MZ, DataType::Float64, false),
+            Field::new(columns::INTENSITY, DataType::Float32, false),
+        ];
+        let mut arrays: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from(vec![spectrum.spectrum_id; num_peaks])),
+            Arc::new(Float64Array::from(peaks.mz.clone())),
+            Arc::new(Float32Array::from(peaks.intensity.clone())),
+        ];
+        let ion_mobility = match &peaks.ion_mobility {
+            OptionalColumnBuf::AllPresent(values) => Some(Float64Array::from(values.clone())),
+            OptionalColumnBuf::AllNull { .. } => None,
+            OptionalColumnBuf::WithValidity { values, validity } => Some(
+                values
+                    .iter()
+                    .zip(validity)
+                    .map(|(&value, &valid)| valid.then_some(value))
+                    .collect(),
+            ),
+        };
+        if let Some(ion_mobility) = ion_mobility {
+            fields.push(Field::new(columns::ION_MOBILITY, DataType::Float64, true));
+            arrays.push(Arc::new(ion_mobility));
+        }
+        let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?;
+
+        let segments = if num_peaks > 0 {
+            vec![SpectrumArraysViewSegment {
+                batch,
+                start: 0,
+                len: num_peaks,
+            }]
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            segments,
+            spectrum_id: spectrum.spectrum_id,
+            scan_number: spectrum.scan_number,
+            ms_level: spectrum.ms_level,
+            retention_time: spectrum.retention_time,
+            polarity: spectrum.polarity,
+            precursor_mz: spectrum.precursor_mz,
+            precursor_charge: spectrum.precursor_charge,
+            precursor_intensity: spectrum.precursor_intensity,
+            isolation_window_lower: spectrum.isolation_window_lower,
+            isolation_window_upper: spectrum.isolation_window_upper,
+            collision_energy: spectrum.collision_energy,
+            total_ion_current: spectrum.total_ion_current,
+            base_peak_mz: spectrum.base_peak_mz,
+            base_peak_intensity: spectrum.base_peak_intensity,
+            injection_time: spectrum.injection_time,
+            pixel_x: spectrum.pixel_x,
+            pixel_y: spectrum.pixel_y,
+            pixel_z: spectrum.pixel_z,
+            num_peaks,
+        })
+    }
+
     /// Number of peaks in this spectrum.
     pub fn peak_count(&self) -> usize {
         self.num_peaks
     }
 
+    /// This spectrum's peak rows as record batches (zero-copy slices).
+    pub(super) fn record_batches(&self) -> Vec<RecordBatch> {
+        self.segments
+            .iter()
+            .map(|seg| seg.batch.slice(seg.start, seg.len))
+            .collect()
+    }
+
     /// Return m/z arrays for each segment (zero-copy slices).
     pub fn mz_arrays(&self) -> Result<Vec<Float64Array>, ReaderError> {
         self.segments
@@ -449,6 +634,7 @@ pub struct StreamingSpectrumArraysViewIterator {
     pending: Option<SpectrumArraysViewBuilder>,
     ready: std::collections::VecDeque<SpectrumArraysView>,
     exhausted: bool,
+    transforms: SpectrumTransforms,
 }
 
 impl StreamingSpectrumArraysViewIterator {
@@ -460,9 +646,16 @@ impl StreamingSpectrumArraysViewIterator {
             pending: None,
             ready: std::collections::VecDeque::new(),
             exhausted: false,
+            transforms: SpectrumTransforms::default(),
         }
     }
 
+    /// Apply `transforms` to every spectrum yielded
+    pub(super) fn with_transforms(mut self, transforms: SpectrumTransforms) -> Self {
+        self.transforms = transforms;
+        self
+    }
+
     fn load_next_batch(&mut self) -> Option<RecordBatch> {
         match self.batch_iter.next() {
             Some(Ok(batch)) => {
@@ -486,6 +679,14 @@ impl Iterator for StreamingSpectrumArraysViewIterator {
     type Item = Result<SpectrumArraysView, ReaderError>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        let view = self.next_stored()?;
+        Some(view.and_then(|view| self.transforms.apply_view(view)))
+    }
+}
+
+impl StreamingSpectrumArraysViewIterator {
+    /// Next spectrum as stored, before transforms
+    fn next_stored(&mut self) -> Option<Result<SpectrumArraysView, ReaderError>> {
         loop {
             if let Some(view) = self.ready.pop_front() {
                 return Some(Ok(view));
@@ -0,0 +1,454 @@
+//! Spectrum lookups for v2.0 datasets
+//!
+//! v2.0 datasets keep spectrum metadata in `spectra/spectra.parquet` and peaks
+//! in `peaks/peaks.parquet`, joined through the `peak_offset` / `peak_count`
+//! row pointers of the spectra table. Resolving a spectrum ID therefore needs
+//! a scan of the spectra table; doing that on every `get_spectrum_arrays`
+//! call dominates repeated lookups.
+//!
+//! The first lookup reads only the pointer columns once and caches a
+//! `spectrum_id → SpectrumLocation` map (plus the footers of both tables) on
+//! the reader. Later lookups decode exactly one spectra row and the peak rows
+//! it points to. [`MzPeakReader::refresh`] drops the cache.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow::array::{
+    Array, ArrowPrimitiveType, AsArray, Float32Array, Float64Array, Int8Array, UInt32Array,
+    UInt64Array, UInt8Array,
+};
+use arrow::datatypes::{Float32Type, Float64Type, Int32Type, Int8Type, UInt16Type, UInt64Type};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::arrow_reader::{
+    ArrowReaderMetadata, ArrowReaderOptions, ParquetRecordBatchReaderBuilder, RowSelection,
+    RowSelector,
+};
+use parquet::arrow::ProjectionMask;
+
+use super::positioned::PositionedReader;
+use super::spectra::SpectrumArraysView;
+use super::{MzPeakReader, ReaderError};
+use crate::schema::{columns, spectra_columns as cols};
+use crate::writer::{spectrum_checksum, SpectrumMetadata};
+
+/// Path of the spectra table inside a v2.0 dataset
+pub(super) const SPECTRA_SUBPATH: &str = "spectra/spectra.parquet";
+
+/// Path of the peaks table inside a v2.0 dataset
+pub(super) const PEAKS_SUBPATH: &str = "peaks/peaks.parquet";
+
+/// Where a spectrum lives in a v2.0 dataset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpectrumLocation {
+    /// Row group of the spectra table holding the spectrum's metadata row
+    pub spectra_row_group: usize,
+    /// Row within that row group
+    pub spectra_row: usize,
+    /// First row of the spectrum's peaks in the peaks table
+    pub peak_offset: u64,
+    /// Number of peak rows
+    pub peak_count: u32,
+}
+
+/// Cached join between the spectra and peaks tables
+pub(super) struct SpectrumIndex {
+    spectra: PositionedReader,
+    spectra_metadata: ArrowReaderMetadata,
+    peaks: PositionedReader,
+    peaks_metadata: ArrowReaderMetadata,
+    locations: HashMap<i64, SpectrumLocation>,
+}
+
+impl SpectrumIndex {
+    /// Build the index, or `None` if the dataset has no spectra table
+    pub(super) fn load(reader: &MzPeakReader) -> Result<Option<Self>, ReaderError> {
+        let Some(spectra) = reader.open_table(SPECTRA_SUBPATH)? else {
+            return Ok(None);
+        };
+        let peaks = reader.open_table(PEAKS_SUBPATH)?.ok_or_else(|| {
+            ReaderError::InvalidFormat(format!("Dataset has no {}", PEAKS_SUBPATH))
+        })?;
+
+        let spectra_metadata = ArrowReaderMetadata::load(&spectra, ArrowReaderOptions::default())?;
+        let peaks_metadata = ArrowReaderMetadata::load(&peaks, ArrowReaderOptions::default())?;
+
+        let schema = spectra_metadata.metadata().file_metadata().schema_descr();
+        let pointer_columns = [cols::SPECTRUM_ID, cols::PEAK_OFFSET, cols::PEAK_COUNT];
+        let projection = ProjectionMask::columns(schema, pointer_columns);
+
+        let mut locations = HashMap::new();
+        for row_group in 0..spectra_metadata.metadata().num_row_groups() {
+            let batches = ParquetRecordBatchReaderBuilder::new_with_metadata(
+                spectra.clone(),
+                spectra_metadata.clone(),
+            )
+            .with_projection(projection.clone())
+            .with_row_groups(vec![row_group])
+            .with_batch_size(reader.config.batch_size)
+            .build()?;
+
+            let mut row = 0;
+            for batch in batches {
+                let batch = batch?;
+                let ids = column::<UInt32Array>(&batch, cols::SPECTRUM_ID)?;
+                let offsets = column::<UInt64Array>(&batch, cols::PEAK_OFFSET)?;
+                let counts = column::<UInt32Array>(&batch, cols::PEAK_COUNT)?;
+                for i in 0..batch.num_rows() {
+                    locations.insert(
+                        ids.value(i) as i64,
+                        SpectrumLocation {
+                            spectra_row_group: row_group,
+                            spectra_row: row,
+                            peak_offset: offsets.value(i),
+                            peak_count: counts.value(i),
+                        },
+                    );
+                    row += 1;
+                }
+            }
+        }
+
+        Ok(Some(Self {
+            spectra,
+            spectra_metadata,
+            peaks,
+            peaks_metadata,
+            locations,
+        }))
+    }
+
+    /// Location of a spectrum
+    pub(super) fn location(&self, spectrum_id: i64) -> Option<SpectrumLocation> {
+        self.locations.get(&spectrum_id).copied()
+    }
+
+    /// IDs of all spectra in spectra table order
+    pub(super) fn spectrum_ids(&self) -> Vec<i64> {
+        let mut ids: Vec<(usize, usize, i64)> = self
+            .locations
+            .iter()
+            .map(|(&id, location)| (location.spectra_row_group, location.spectra_row, id))
+            .collect();
+        ids.sort_unstable();
+        ids.into_iter().map(|(_, _, id)| id).collect()
+    }
+
+    /// IDs of the spectra of one MS level in spectra table order
+    ///
+    /// Reads only the `spectrum_id` and `ms_level` columns.
+    pub(super) fn spectrum_ids_at_level(
+        &self,
+        ms_level: i16,
+        batch_size: usize,
+    ) -> Result<Vec<i64>, ReaderError> {
+        let schema = self.spectra_metadata.metadata().file_metadata().schema_descr();
+        let projection = ProjectionMask::columns(schema, [cols::SPECTRUM_ID, cols::MS_LEVEL]);
+        let batches = ParquetRecordBatchReaderBuilder::new_with_metadata(
+            self.spectra.clone(),
+            self.spectra_metadata.clone(),
+        )
+        .with_projection(projection)
+        .with_batch_size(batch_size)
+        .build()?;
+
+        let mut ids = Vec::new();
+        for batch in batches {
+            let batch = batch?;
+            let spectrum_ids = column::<UInt32Array>(&batch, cols::SPECTRUM_ID)?;
+            let ms_levels = column::<UInt8Array>(&batch, cols::MS_LEVEL)?;
+            for i in 0..batch.num_rows() {
+                if i16::from(ms_levels.value(i)) == ms_level {
+                    ids.push(spectrum_ids.value(i) as i64);
+                }
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Read one spectrum
+    pub(super) fn read_spectrum(
+        &self,
+        spectrum_id: i64,
+    ) -> Result<Option<SpectrumArraysView>, ReaderError> {
+        let Some(location) = self.location(spectrum_id) else {
+            return Ok(None);
+        };
+        let metadata = self.read_metadata_row(&location)?;
+        let peaks = self.read_peak_rows(location.peak_offset, location.peak_count as u64)?;
+        Ok(Some(SpectrumArraysView::from_v2_parts(&metadata, peaks)))
+    }
+
+    /// Recompute a spectrum's content checksum and compare it to the stored one
+    ///
+    /// `None` if the spectrum does not exist or has no stored checksum.
+    pub(super) fn verify_checksum(&self, spectrum_id: i64) -> Result<Option<bool>, ReaderError> {
+        let Some(location) = self.location(spectrum_id) else {
+            return Ok(None);
+        };
+        let row = self.read_metadata_batch(&location)?;
+        let Some(stored) = optional::<UInt64Type>(&row, cols::SPECTRUM_CHECKSUM, 0) else {
+            return Ok(None);
+        };
+        let metadata = spectrum_metadata_from_row(&row, 0)?;
+
+        let count = location.peak_count as usize;
+        let (mut mz, mut intensity) = (Vec::with_capacity(count), Vec::with_capacity(count));
+        for batch in self.read_peak_rows(location.peak_offset, count as u64)? {
+            mz.extend_from_slice(column::<Float64Array>(&batch, columns::MZ)?.values());
+            intensity
+                .extend_from_slice(column::<Float32Array>(&batch, columns::INTENSITY)?.values());
+        }
+        let checksum = spectrum_checksum(&metadata, &mz, &intensity);
+        Ok(Some(checksum == stored))
+    }
+
+    /// Stored content checksums in spectra table order
+    ///
+    /// `None` if the spectra table has no `spectrum_checksum` column; spectra
+    /// without a checksum are skipped.
+    pub(super) fn checksums(
+        &self,
+        batch_size: usize,
+    ) -> Result<Option<Vec<(i64, u64)>>, ReaderError> {
+        if self.spectra_metadata.schema().field_with_name(cols::SPECTRUM_CHECKSUM).is_err() {
+            return Ok(None);
+        }
+        let schema = self.spectra_metadata.metadata().file_metadata().schema_descr();
+        let projection =
+            ProjectionMask::columns(schema, [cols::SPECTRUM_ID, cols::SPECTRUM_CHECKSUM]);
+        let batches = ParquetRecordBatchReaderBuilder::new_with_metadata(
+            self.spectra.clone(),
+            self.spectra_metadata.clone(),
+        )
+        .with_projection(projection)
+        .with_batch_size(batch_size)
+        .build()?;
+
+        let mut checksums = Vec::new();
+        for batch in batches {
+            let batch = batch?;
+            let spectrum_ids = column::<UInt32Array>(&batch, cols::SPECTRUM_ID)?;
+            let values = column::<UInt64Array>(&batch, cols::SPECTRUM_CHECKSUM)?;
+            for i in 0..batch.num_rows() {
+                if !values.is_null(i) {
+                    checksums.push((spectrum_ids.value(i) as i64, values.value(i)));
+                }
+            }
+        }
+        Ok(Some(checksums))
+    }
+
+    fn read_metadata_row(
+        &self,
+        location: &SpectrumLocation,
+    ) -> Result<SpectrumMetadata, ReaderError> {
+        spectrum_metadata_from_row(&self.read_metadata_batch(location)?, 0)
+    }
+
+    /// Read the spectra table row of a spectrum, all columns
+    fn read_metadata_batch(&self, location: &SpectrumLocation) -> Result<RecordBatch, ReaderError> {
+        let num_rows = self
+            .spectra_metadata
+            .metadata()
+            .row_group(location.spectra_row_group)
+            .num_rows() as usize;
+        let selection = RowSelection::from(vec![
+            RowSelector::skip(location.spectra_row),
+            RowSelector::select(1),
+            RowSelector::skip(num_rows - location.spectra_row - 1),
+        ]);
+        let batches = ParquetRecordBatchReaderBuilder::new_with_metadata(
+            self.spectra.clone(),
+            self.spectra_metadata.clone(),
+        )
+        .with_row_groups(vec![location.spectra_row_group])
+        .with_row_selection(selection)
+        .build()?;
+
+        for batch in batches {
+            let batch = batch?;
+            if batch.num_rows() > 0 {
+                return Ok(batch);
+            }
+        }
+        Err(ReaderError::InvalidFormat(
+            "spectra row selection returned no rows".to_string(),
+        ))
+    }
+
+    /// Read `count` rows of the peaks table starting at `offset`
+    fn read_peak_rows(&self, offset: u64, count: u64) -> Result<Vec<RecordBatch>, ReaderError> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let end = offset + count;
+
+        // Row groups overlapping [offset, end) and the selection within them
+        let mut row_groups = Vec::new();
+        let mut selectors = Vec::new();
+        let mut group_start = 0u64;
+        for (index, row_group) in self
+            .peaks_metadata
+            .metadata()
+            .row_groups()
+            .iter()
+            .enumerate()
+        {
+            let group_end = group_start + row_group.num_rows() as u64;
+            if group_end > offset && group_start < end {
+                let first = offset.max(group_start);
+                let last = end.min(group_end);
+                row_groups.push(index);
+                selectors.push(RowSelector::skip((first - group_start) as usize));
+                selectors.push(RowSelector::select((last - first) as usize));
+                selectors.push(RowSelector::skip((group_end - last) as usize));
+            }
+            group_start = group_end;
+        }
+        if group_start < end {
+            return Err(ReaderError::InvalidFormat(format!(
+                "peak rows {}..{} exceed the peaks table ({} rows)",
+                offset, end, group_start
+            )));
+        }
+
+        let batches = ParquetRecordBatchReaderBuilder::new_with_metadata(
+            self.peaks.clone(),
+            self.peaks_metadata.clone(),
+        )
+        .with_row_groups(row_groups)
+        .with_row_selection(RowSelection::from(selectors))
+        .with_batch_size(count as usize)
+        .build()?;
+        Ok(batches.collect::<Result<_, _>>()?)
+    }
+}
+
+impl MzPeakReader {
+    /// Cached spectra/peaks join of a v2.0 dataset, built on first use
+    ///
+    /// Returns `None` for datasets without a spectra table.
+    pub(super) fn spectrum_index(&self) -> Result<Option<Arc<SpectrumIndex>>, ReaderError> {
+        if let Some(index) = self.spectrum_index.get() {
+            return Ok(index.clone());
+        }
+        let index = SpectrumIndex::load(self)?.map(Arc::new);
+        // Another thread may have won the race; either index is equivalent
+        let _ = self.spectrum_index.set(index);
+        Ok(self.spectrum_index.get().cloned().flatten())
+    }
+
+    /// Location of a spectrum in a v2.0 dataset
+    ///
+    /// Returns `None` if the spectrum does not exist or the dataset has no
+    /// spectra table. The first call builds the lookup cache.
+    pub fn spectrum_location(
+        &self,
+        spectrum_id: i64,
+    ) -> Result<Option<SpectrumLocation>, ReaderError> {
+        Ok(self
+            .spectrum_index()?
+            .and_then(|index| index.location(spectrum_id)))
+    }
+
+    /// Check one spectrum against its stored content checksum
+    ///
+    /// Re-reads the spectrum's metadata row and peaks and recomputes
+    /// [`spectrum_checksum`]. Returns `None` if the spectrum does not exist
+    /// or the dataset was written without checksums (see
+    /// [`SpectraWriterConfig::spectrum_checksums`](crate::writer::SpectraWriterConfig::spectrum_checksums)).
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use mzpeak::reader::MzPeakReader;
+    ///
+    /// let reader = MzPeakReader::open("data.mzpeak")?;
+    /// if reader.verify_spectrum_checksum(0)? == Some(false) {
+    ///     eprintln!("spectrum 0 does not match its checksum");
+    /// }
+    /// # Ok::<(), mzpeak::reader::ReaderError>(())
+    /// ```
+    pub fn verify_spectrum_checksum(&self, spectrum_id: i64) -> Result<Option<bool>, ReaderError> {
+        match self.spectrum_index()? {
+            Some(index) => index.verify_checksum(spectrum_id),
+            None => Ok(None),
+        }
+    }
+
+    /// Stored content checksums as `(spectrum_id, checksum)` pairs
+    ///
+    /// Reads only two columns of the spectra table, for deduplication or
+    /// comparing datasets spectrum by spectrum. Returns `None` if the dataset
+    /// has no `spectrum_checksum` column.
+    pub fn spectrum_checksums(&self) -> Result<Option<Vec<(i64, u64)>>, ReaderError> {
+        match self.spectrum_index()? {
+            Some(index) => index.checksums(self.config.batch_size),
+            None => Ok(None),
+        }
+    }
+
+    /// Re-open the dataset and drop cached lookups
+    ///
+    /// Call after the file was rewritten in place (e.g. by compaction or
+    /// attaching files) so that footers and spectrum locations are re-read.
+    pub fn refresh(&mut self) -> Result<(), ReaderError> {
+        let path = self.source_path().to_path_buf();
+        let mut reopened = Self::open_with_config(path, self.config.clone())?;
+        // Keep the staged copy of a foreign table alive
+        reopened.staged = self.staged.take();
+        *self = reopened;
+        Ok(())
+    }
+}
+
+/// Downcast a required column
+fn column<'a, T: Array + 'static>(
+    batch: &'a RecordBatch,
+    name: &str,
+) -> Result<&'a T, ReaderError> {
+    batch
+        .column_by_name(name)
+        .ok_or_else(|| ReaderError::ColumnNotFound(name.to_string()))?
+        .as_any()
+        .downcast_ref::<T>()
+        .ok_or_else(|| ReaderError::InvalidFormat(format!("{} has an unexpected type", name)))
+}
+
+/// Value of an optional column at `row`, `None` if absent or null
+fn optional<T: ArrowPrimitiveType>(
+    batch: &RecordBatch,
+    name: &str,
+    row: usize,
+) -> Option<T::Native> {
+    let array = batch.column_by_name(name)?.as_primitive_opt::<T>()?;
+    (!array.is_null(row)).then(|| array.value(row))
+}
+
+/// Decode one row of the spectra table
+fn spectrum_metadata_from_row(
+    batch: &RecordBatch,
+    row: usize,
+) -> Result<SpectrumMetadata, ReaderError> {
+    Ok(SpectrumMetadata {
+        spectrum_id: column::<UInt32Array>(batch, cols::SPECTRUM_ID)?.value(row),
+        scan_number: optional::<Int32Type>(batch, cols::SCAN_NUMBER, row),
+        ms_level: column::<UInt8Array>(batch, cols::MS_LEVEL)?.value(row),
+        retention_time: column::<Float32Array>(batch, cols::RETENTION_TIME)?.value(row),
+        polarity: column::<Int8Array>(batch, cols::POLARITY)?.value(row),
+        peak_count: column::<UInt32Array>(batch, cols::PEAK_COUNT)?.value(row),
+        precursor_mz: optional::<Float64Type>(batch, cols::PRECURSOR_MZ, row),
+        precursor_charge: optional::<Int8Type>(batch, cols::PRECURSOR_CHARGE, row),
+        precursor_intensity: optional::<Float32Type>(batch, cols::PRECURSOR_INTENSITY, row),
+        isolation_window_lower: optional::<Float32Type>(batch, cols::ISOLATION_WINDOW_LOWER, row),
+        isolation_window_upper: optional::<Float32Type>(batch, cols::ISOLATION_WINDOW_UPPER, row),
+        collision_energy: optional::<Float32Type>(batch, cols::COLLISION_ENERGY, row),
+        total_ion_current: optional::<Float64Type>(batch, cols::TOTAL_ION_CURRENT, row),
+        base_peak_mz: optional::<Float64Type>(batch, cols::BASE_PEAK_MZ, row),
+        base_peak_intensity: optional::<Float32Type>(batch, cols::BASE_PEAK_INTENSITY, row),
+        injection_time: optional::<Float32Type>(batch, cols::INJECTION_TIME, row),
+        pixel_x: optional::<UInt16Type>(batch, cols::PIXEL_X, row),
+        pixel_y: optional::<UInt16Type>(batch, cols::PIXEL_Y, row),
+        pixel_z: optional::<UInt16Type>(batch, cols::PIXEL_Z, row),
+    })
+}
@@ -0,0 +1,199 @@
+//! Spectrum access behind a trait
+//!
+//! [`SpectrumStore`] covers the read operations most applications need
+//! (summary, lookup by ID, iteration, range queries) on owned
+//! [`SpectrumArrays`]. Code written against the trait runs on an
+//! [`MzPeakReader`] in production and on an [`InMemorySpectrumStore`] in unit
+//! tests, without fixture files.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use mzpeak::prelude::*;
+//!
+//! fn count_ms2(store: &dyn SpectrumStore) -> Result<usize, ReaderError> {
+//!     Ok(store.query_ms_level(2)?.len())
+//! }
+//!
+//! let store = InMemorySpectrumStore::new(vec![
+//!     SpectrumArrays::new_ms1(0, 1, 60.0, 1, PeakArrays::new(vec![400.0], vec![1000.0])),
+//!     SpectrumArrays::new_ms2(1, 2, 61.0, 1, 400.0, PeakArrays::new(vec![200.0], vec![50.0])),
+//! ]);
+//! assert_eq!(count_ms2(&store)?, 1);
+//! # Ok::<(), mzpeak::reader::ReaderError>(())
+//! ```
+
+use super::summary::PolarityBreakdown;
+use super::{FileSummary, MzPeakReader, ReaderError, RtRange};
+use crate::schema::MZPEAK_FORMAT_VERSION;
+use crate::writer::SpectrumArrays;
+
+/// Boxed iterator over owned spectra
+pub type SpectrumIter<'a> = Box<dyn Iterator<Item = Result<SpectrumArrays, ReaderError>> + 'a>;
+
+/// Read access to the spectra of a run
+pub trait SpectrumStore {
+    /// Summary statistics of the run
+    fn summary(&self) -> Result<FileSummary, ReaderError>;
+
+    /// Spectrum with the given ID, if present
+    fn get_spectrum(&self, spectrum_id: i64) -> Result<Option<SpectrumArrays>, ReaderError>;
+
+    /// All spectra in storage order
+    fn iter_spectra(&self) -> Result<SpectrumIter<'_>, ReaderError>;
+
+    /// IDs of all spectra in storage order
+    fn spectrum_ids(&self) -> Result<Vec<i64>, ReaderError> {
+        self.iter_spectra()?
+            .map(|spectrum| spectrum.map(|s| s.spectrum_id))
+            .collect()
+    }
+
+    /// Spectra whose retention time falls inside `range`
+    fn query_rt_range(&self, range: RtRange) -> Result<Vec<SpectrumArrays>, ReaderError> {
+        self.query(&|s| range.contains(s.retention_time))
+    }
+
+    /// Spectra of one MS level
+    fn query_ms_level(&self, ms_level: i16) -> Result<Vec<SpectrumArrays>, ReaderError> {
+        self.query(&|s| s.ms_level == ms_level)
+    }
+
+    /// MS2+ spectra whose precursor m/z lies in `min_mz..=max_mz`
+    fn query_precursor_mz(
+        &self,
+        min_mz: f64,
+        max_mz: f64,
+    ) -> Result<Vec<SpectrumArrays>, ReaderError> {
+        self.query(&|s| {
+            s.precursor_mz
+                .is_some_and(|mz| mz >= min_mz && mz <= max_mz)
+        })
+    }
+
+    /// Spectra matching `predicate`
+    fn query(
+        &self,
+        predicate: &dyn Fn(&SpectrumArrays) -> bool,
+    ) -> Result<Vec<SpectrumArrays>, ReaderError> {
+        let mut matches = Vec::new();
+        for spectrum in self.iter_spectra()? {
+            let spectrum = spectrum?;
+            if predicate(&spectrum) {
+                matches.push(spectrum);
+            }
+        }
+        Ok(matches)
+    }
+}
+
+impl SpectrumStore for MzPeakReader {
+    fn summary(&self) -> Result<FileSummary, ReaderError> {
+        MzPeakReader::summary(self)
+    }
+
+    fn get_spectrum(&self, spectrum_id: i64) -> Result<Option<SpectrumArrays>, ReaderError> {
+        self.get_spectrum_arrays(spectrum_id)?
+            .map(|view| view.to_owned())
+            .transpose()
+    }
+
+    fn iter_spectra(&self) -> Result<SpectrumIter<'_>, ReaderError> {
+        let spectra = self.iter_spectra_arrays_streaming()?;
+        Ok(Box::new(
+            spectra.map(|spectrum| spectrum.and_then(|view| view.to_owned())),
+        ))
+    }
+
+    fn spectrum_ids(&self) -> Result<Vec<i64>, ReaderError> {
+        MzPeakReader::spectrum_ids(self)
+    }
+
+    fn query_rt_range(&self, range: RtRange) -> Result<Vec<SpectrumArrays>, ReaderError> {
+        self.spectra_in_rt_range(range)?
+            .iter()
+            .map(|view| view.to_owned())
+            .collect()
+    }
+}
+
+/// [`SpectrumStore`] over spectra held in memory, for tests and small datasets
+#[derive(Debug, Clone, Default)]
+pub struct InMemorySpectrumStore {
+    spectra: Vec<SpectrumArrays>,
+}
+
+impl InMemorySpectrumStore {
+    /// Store holding `spectra` in the given order
+    pub fn new(spectra: Vec<SpectrumArrays>) -> Self {
+        Self { spectra }
+    }
+
+    /// Append a spectrum
+    pub fn push(&mut self, spectrum: SpectrumArrays) {
+        self.spectra.push(spectrum);
+    }
+
+    /// The stored spectra
+    pub fn spectra(&self) -> &[SpectrumArrays] {
+        &self.spectra
+    }
+}
+
+impl From<Vec<SpectrumArrays>> for InMemorySpectrumStore {
+    fn from(spectra: Vec<SpectrumArrays>) -> Self {
+        Self::new(spectra)
+    }
+}
+
+impl SpectrumStore for InMemorySpectrumStore {
+    fn summary(&self) -> Result<FileSummary, ReaderError> {
+        let spectra = &self.spectra;
+        let rt_range = spectra
+            .iter()
+            .map(|s| s.retention_time)
+            .fold(None, |range, rt| {
+                Some(match range {
+                    Some((min, max)) => (f32::min(min, rt), f32::max(max, rt)),
+                    None => (rt, rt),
+                })
+            });
+        let mz_range = spectra
+            .iter()
+            .flat_map(|s| s.peaks.mz.iter().copied())
+            .fold(None, |range, mz| {
+                Some(match range {
+                    Some((min, max)) => (f64::min(min, mz), f64::max(max, mz)),
+                    None => (mz, mz),
+                })
+            });
+
+        let mut polarities = PolarityBreakdown::default();
+        for spectrum in spectra {
+            polarities.add(spectrum.polarity, spectrum.ms_level, spectrum.peaks.len());
+        }
+
+        Ok(FileSummary {
+            total_peaks: spectra.iter().map(|s| s.peaks.len() as i64).sum(),
+            num_spectra: spectra.len() as i64,
+            num_ms1_spectra: spectra.iter().filter(|s| s.ms_level == 1).count() as i64,
+            num_ms2_spectra: spectra.iter().filter(|s| s.ms_level == 2).count() as i64,
+            rt_range,
+            mz_range,
+            format_version: MZPEAK_FORMAT_VERSION.to_string(),
+            polarities: polarities.finish(),
+        })
+    }
+
+    fn get_spectrum(&self, spectrum_id: i64) -> Result<Option<SpectrumArrays>, ReaderError> {
+        Ok(self
+            .spectra
+            .iter()
+            .find(|s| s.spectrum_id == spectrum_id)
+            .cloned())
+    }
+
+    fn iter_spectra(&self) -> Result<SpectrumIter<'_>, ReaderError> {
+        Ok(Box::new(self.spectra.iter().cloned().map(Ok)))
+    }
+}
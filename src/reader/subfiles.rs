@@ -1,50 +1,258 @@
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
 
 use arrow::record_batch::RecordBatch;
+#[cfg(feature = "container")]
 use bytes::Bytes;
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
-use zip::ZipArchive;
 
 use super::config::ReaderSource;
-use super::utils::{extract_f32_list, extract_f64_list, get_list_column, get_string_column};
+use super::safety::{self, check_entry_path};
+use crate::metadata::{ImagingMetadata, MzPeakMetadata, ProcessingStep};
+use crate::schema::manifest::{Attachment, LayoutStats, Manifest};
+use super::utils::{
+    extract_f32_list, extract_f64_list, get_int64_column, get_list_column, get_string_column,
+};
 use super::{MzPeakReader, ReaderError};
 
 impl MzPeakReader {
+    /// Resolve the on-disk location of a dataset sub-file for file-based sources
+    ///
+    /// Returns `None` when the reader was opened on a standalone Parquet file
+    /// that is not part of a dataset bundle.
+    pub(super) fn dataset_subfile_path(
+        path: &Path,
+        subpath: &str,
+    ) -> Result<Option<PathBuf>, ReaderError> {
+        if path.is_dir() {
+            // Directory bundle
+            Ok(Some(path.join(subpath)))
+        } else if path
+            .extension()
+            .map(|e| e == "parquet")
+            .unwrap_or(false)
+        {
+            // Single parquet file - could be peaks/peaks.parquet from a directory dataset
+            // Check if parent is peaks/ directory
+            match path.parent() {
+                Some(parent) if parent.file_name().and_then(|n| n.to_str()) == Some("peaks") => {
+                    // This is a directory dataset, go up to dataset root
+                    Ok(parent.parent().map(|dataset_root| dataset_root.join(subpath)))
+                }
+                // Single file mode - no sub-files
+                _ => Ok(None),
+            }
+        } else {
+            Err(ReaderError::InvalidFormat(format!(
+                "Cannot determine sub-file location for {:?}",
+                path
+            )))
+        }
+    }
+
+    /// Read the raw bytes of a dataset sub-file
+    ///
+    /// Returns `None` when the sub-file does not exist or the reader was opened
+    /// on a standalone Parquet file.
+    pub(super) fn read_subfile_bytes(&self, subpath: &str) -> Result<Option<Vec<u8>>, ReaderError> {
+        check_entry_path(subpath)?;
+        let limits = &self.config.container_limits;
+        match &self.source {
+            ReaderSource::FilePath(path) => {
+                let sub_file_path = match Self::dataset_subfile_path(path, subpath)? {
+                    Some(p) if p.exists() => p,
+                    _ => return Ok(None),
+                };
+                Ok(Some(safety::read_file(&sub_file_path, subpath, limits)?))
+            }
+            #[cfg(feature = "container")]
+            ReaderSource::ZipContainer { zip_path, .. } => {
+                let mut archive = safety::open_archive(zip_path, limits)?;
+                let sub_file = match archive.by_name(subpath) {
+                    Ok(f) => f,
+                    Err(_) => return Ok(None),
+                };
+                Ok(Some(safety::read_entry(sub_file, limits)?))
+            }
+        }
+    }
+
+    /// Read the dataset's `metadata.json`
+    ///
+    /// Fields written by newer versions are preserved in the `unknown_fields`
+    /// maps of the returned metadata, so it can be edited and written back
+    /// without loss. Run start/end times are normalized to RFC 3339 (see
+    /// [`ReaderConfig::timestamp_zone`](super::ReaderConfig::timestamp_zone)).
+    /// Returns `None` if the dataset has no `metadata.json`.
+    pub fn read_metadata_json(&self) -> Result<Option<MzPeakMetadata>, ReaderError> {
+        let bytes = match self.read_subfile_bytes("metadata.json")? {
+            Some(b) => b,
+            None => return Ok(None),
+        };
+        let json = String::from_utf8(bytes)
+            .map_err(|e| ReaderError::MetadataError(format!("metadata.json is not UTF-8: {}", e)))?;
+        let mut metadata = MzPeakMetadata::from_metadata_json(&json)
+            .map_err(|e| ReaderError::MetadataError(e.to_string()))?;
+        if let Some(run) = metadata.run_parameters.as_mut() {
+            run.normalize_timestamps(self.config.timestamp_zone);
+        }
+        Ok(Some(metadata))
+    }
+
+    /// Processing steps recorded in the file, oldest first
+    ///
+    /// Single-file outputs carry the history in the Parquet footer, dataset
+    /// bundles in `metadata.json`. Returns an empty list when neither has one.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use mzpeak::reader::MzPeakReader;
+    ///
+    /// let reader = MzPeakReader::open("data.mzpeak")?;
+    /// for step in reader.processing_history()? {
+    ///     println!("{}. {} ({})", step.order, step.processing_type, step.software);
+    /// }
+    /// # Ok::<(), mzpeak::reader::ReaderError>(())
+    /// ```
+    pub fn processing_history(&self) -> Result<Vec<ProcessingStep>, ReaderError> {
+        let footer = self
+            .file_metadata
+            .mzpeak_metadata
+            .as_ref()
+            .and_then(|metadata| metadata.processing_history.as_ref());
+        if let Some(history) = footer {
+            return Ok(history.steps.clone());
+        }
+        Ok(self
+            .read_metadata_json()?
+            .and_then(|metadata| metadata.processing_history)
+            .map(|history| history.steps)
+            .unwrap_or_default())
+    }
+
+    /// Pixel grid calibration of imaging data (pixel size, origin, axis
+    /// orientation)
+    ///
+    /// Taken from the Parquet footer, the v2.0 manifest or `metadata.json`,
+    /// whichever has it first. Returns `None` for non-imaging data and for
+    /// imaging data converted without calibration.
+    pub fn imaging_metadata(&self) -> Result<Option<ImagingMetadata>, ReaderError> {
+        let footer = self
+            .file_metadata
+            .mzpeak_metadata
+            .as_ref()
+            .and_then(|metadata| metadata.imaging.clone());
+        if footer.is_some() {
+            return Ok(footer);
+        }
+        if let Some(imaging) = self.manifest()?.and_then(|manifest| manifest.imaging) {
+            return Ok(Some(imaging));
+        }
+        Ok(self
+            .read_metadata_json()?
+            .and_then(|metadata| metadata.imaging))
+    }
+
+    /// Peak density summaries of the peaks table row groups
+    ///
+    /// Read from the v2.0 manifest, so no peak data is scanned. Returns `None`
+    /// for datasets written without summaries and when the summaries no
+    /// longer match the peaks table (e.g. after compaction regrouped it).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use mzpeak::reader::MzPeakReader;
+    ///
+    /// let reader = MzPeakReader::open("data.mzpeak")?;
+    /// if let Some(layout) = reader.layout_stats()? {
+    ///     for (index, row_group) in layout.row_groups.iter().enumerate() {
+    ///         println!(
+    ///             "row group {}: {} spectra, {:.1} peaks/spectrum, max intensity {:?}",
+    ///             index,
+    ///             row_group.spectrum_count,
+    ///             row_group.mean_peaks_per_spectrum(),
+    ///             row_group.max_intensity()
+    ///         );
+    ///     }
+    /// }
+    /// # Ok::<(), mzpeak::reader::ReaderError>(())
+    /// ```
+    pub fn layout_stats(&self) -> Result<Option<LayoutStats>, ReaderError> {
+        let layout = match self.manifest()?.and_then(|manifest| manifest.layout_stats) {
+            Some(layout) => layout,
+            None => return Ok(None),
+        };
+        let matches_table = layout.row_groups.len() == self.file_metadata.num_row_groups
+            && layout.peak_count() == self.file_metadata.total_rows as u64;
+        Ok(matches_table.then_some(layout))
+    }
+
+    /// List the files attached to the dataset
+    ///
+    /// Returns the attachment registry from `manifest.json`; datasets without
+    /// a manifest have no attachments.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use mzpeak::reader::MzPeakReader;
+    ///
+    /// let reader = MzPeakReader::open("data.mzpeak")?;
+    /// for attachment in reader.attachments()? {
+    ///     let bytes = reader.read_attachment(&attachment.name)?;
+    ///     println!("{} ({:?}): {:?} bytes", attachment.name, attachment.kind, bytes.map(|b| b.len()));
+    /// }
+    /// # Ok::<(), mzpeak::reader::ReaderError>(())
+    /// ```
+    pub fn attachments(&self) -> Result<Vec<Attachment>, ReaderError> {
+        Ok(self
+            .manifest()?
+            .map(|manifest| manifest.attachments)
+            .unwrap_or_default())
+    }
+
+    /// Read the v2.0 container manifest (`manifest.json`)
+    ///
+    /// Returns `None` for v1 datasets and single files without a manifest.
+    pub fn manifest(&self) -> Result<Option<Manifest>, ReaderError> {
+        match self.read_subfile_bytes("manifest.json")? {
+            Some(bytes) => Ok(Some(serde_json::from_slice::<Manifest>(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Read the content of an attached file
+    ///
+    /// Returns `None` if no attachment with this name is registered.
+    pub fn read_attachment(&self, name: &str) -> Result<Option<Vec<u8>>, ReaderError> {
+        let attachment = match self.attachments()?.into_iter().find(|a| a.name == name) {
+            Some(a) => a,
+            None => return Ok(None),
+        };
+        let bytes = self.read_subfile_bytes(&attachment.entry_path())?.ok_or_else(|| {
+            ReaderError::InvalidFormat(format!(
+                "Attachment '{}' is registered but missing from the dataset",
+                name
+            ))
+        })?;
+        if crc32fast::hash(&bytes) != attachment.crc32 {
+            return Err(ReaderError::InvalidFormat(format!(
+                "Attachment '{}' failed its CRC-32 check",
+                name
+            )));
+        }
+        Ok(Some(bytes))
+    }
+
     /// Open a sub-parquet file (chromatograms or mobilograms) from the dataset
     fn open_sub_parquet(&self, subpath: &str) -> Result<Option<Vec<RecordBatch>>, ReaderError> {
         match &self.source {
             ReaderSource::FilePath(path) => {
-                let sub_file_path = if path.is_dir() {
-                    // Directory bundle
-                    path.join(subpath)
-                } else if path
-                    .extension()
-                    .map(|e| e == "parquet")
-                    .unwrap_or(false)
-                {
-                    // Single parquet file - could be peaks/peaks.parquet from a directory dataset
-                    // Check if parent is peaks/ directory
-                    if let Some(parent) = path.parent() {
-                        if parent.file_name().and_then(|n| n.to_str()) == Some("peaks") {
-                            // This is a directory dataset, go up to dataset root
-                            if let Some(dataset_root) = parent.parent() {
-                                dataset_root.join(subpath)
-                            } else {
-                                return Ok(None);
-                            }
-                        } else {
-                            // Single file mode - no chromatograms/mobilograms
-                            return Ok(None);
-                        }
-                    } else {
-                        return Ok(None);
-                    }
-                } else {
-                    return Err(ReaderError::InvalidFormat(format!(
-                        "Cannot determine sub-file location for {:?}",
-                        path
-                    )));
+                let sub_file_path = match Self::dataset_subfile_path(path, subpath)? {
+                    Some(p) => p,
+                    None => return Ok(None),
                 };
 
                 // If the file doesn't exist, return None (chromatograms/mobilograms are optional)
@@ -63,20 +271,20 @@ impl MzPeakReader {
                 }
                 Ok(Some(batches))
             }
+            #[cfg(feature = "container")]
             ReaderSource::ZipContainer { zip_path, .. } => {
                 // ZIP container - re-open and extract the sub-file
-                let file = File::open(zip_path)?;
-                let mut archive = ZipArchive::new(BufReader::new(file))?;
+                let limits = &self.config.container_limits;
+                let mut archive = safety::open_archive(zip_path, limits)?;
 
                 // Try to find the sub-file in the ZIP
-                let mut sub_file = match archive.by_name(subpath) {
+                let sub_file = match archive.by_name(subpath) {
                     Ok(f) => f,
                     Err(_) => return Ok(None), // File doesn't exist in ZIP, return None
                 };
 
                 // Read the parquet file into memory
-                let mut parquet_bytes = Vec::new();
-                sub_file.read_to_end(&mut parquet_bytes)?;
+                let parquet_bytes = safety::read_entry(sub_file, limits)?;
 
                 // Parse as Parquet
                 let bytes = Bytes::from(parquet_bytes);
@@ -187,4 +395,56 @@ impl MzPeakReader {
 
         Ok(mobilograms)
     }
+
+    /// Read per-scan instrument diagnostics from the dataset
+    ///
+    /// Rows are grouped into one [`ScanDiagnostics`](crate::scan_diagnostics_writer::ScanDiagnostics)
+    /// per spectrum, in file order. Returns an empty vector if the dataset has
+    /// no diagnostics table.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use mzpeak::reader::MzPeakReader;
+    ///
+    /// let reader = MzPeakReader::open("data.mzpeak")?;
+    /// for scan in reader.read_scan_diagnostics()? {
+    ///     if let Some(it) = scan.get_numeric("Ion Injection Time (ms)") {
+    ///         println!("Spectrum {}: {} ms", scan.spectrum_id, it);
+    ///     }
+    /// }
+    /// # Ok::<(), mzpeak::reader::ReaderError>(())
+    /// ```
+    pub fn read_scan_diagnostics(
+        &self,
+    ) -> Result<Vec<crate::scan_diagnostics_writer::ScanDiagnostics>, ReaderError> {
+        use crate::scan_diagnostics_writer::{
+            scan_diagnostics_columns, ScanDiagnostics, SCAN_DIAGNOSTICS_PATH,
+        };
+
+        let batches = match self.open_sub_parquet(SCAN_DIAGNOSTICS_PATH)? {
+            Some(b) => b,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut scans: Vec<ScanDiagnostics> = Vec::new();
+
+        for batch in &batches {
+            let spectrum_ids = get_int64_column(batch, scan_diagnostics_columns::SPECTRUM_ID)?;
+            let keys = get_string_column(batch, scan_diagnostics_columns::KEY)?;
+            let values = get_string_column(batch, scan_diagnostics_columns::VALUE)?;
+
+            for i in 0..batch.num_rows() {
+                let spectrum_id = spectrum_ids.value(i);
+                if scans.last().map(|s| s.spectrum_id) != Some(spectrum_id) {
+                    scans.push(ScanDiagnostics::new(spectrum_id));
+                }
+                if let Some(scan) = scans.last_mut() {
+                    scan.push(keys.value(i), values.value(i));
+                }
+            }
+        }
+
+        Ok(scans)
+    }
 }
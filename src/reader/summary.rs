@@ -1,9 +1,12 @@
 use std::fmt;
 
+use serde::{Deserialize, Serialize};
+
 use super::{MzPeakReader, ReaderError};
 
 /// Summary statistics about an mzPeak file
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct FileSummary {
     /// Total number of peaks in the file
     pub total_peaks: i64,
@@ -19,6 +22,95 @@ pub struct FileSummary {
     pub mz_range: Option<(f64, f64)>,
     /// Format version
     pub format_version: String,
+    /// Spectrum counts per polarity, positive first; only polarities present
+    /// in the file are listed
+    pub polarities: Vec<PolaritySummary>,
+}
+
+/// Spectrum counts for one polarity
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PolaritySummary {
+    /// Polarity: 1 positive, -1 negative, 0 unknown
+    pub polarity: i8,
+    /// Number of spectra
+    pub num_spectra: i64,
+    /// Number of MS1 spectra
+    pub num_ms1_spectra: i64,
+    /// Number of MS2 spectra
+    pub num_ms2_spectra: i64,
+    /// Number of peaks
+    pub total_peaks: i64,
+}
+
+impl FileSummary {
+    /// Counts for one polarity, `None` if the file has no such spectra
+    pub fn polarity(&self, polarity: i8) -> Option<&PolaritySummary> {
+        self.polarities
+            .iter()
+            .find(|summary| summary.polarity == polarity)
+    }
+
+    /// Whether the file holds spectra of more than one polarity
+    pub fn is_mixed_polarity(&self) -> bool {
+        self.polarities.len() > 1
+    }
+}
+
+/// Accumulates [`PolaritySummary`] counts spectrum by spectrum
+#[derive(Debug, Default)]
+pub(super) struct PolarityBreakdown(Vec<PolaritySummary>);
+
+impl PolarityBreakdown {
+    pub(super) fn add(&mut self, polarity: i8, ms_level: i16, peaks: usize) {
+        let summary = self.entry(polarity);
+        summary.num_spectra += 1;
+        match ms_level {
+            1 => summary.num_ms1_spectra += 1,
+            2 => summary.num_ms2_spectra += 1,
+            _ => {}
+        }
+        summary.total_peaks += peaks as i64;
+    }
+
+    /// Add the counts of another file
+    pub(super) fn merge(&mut self, polarities: &[PolaritySummary]) {
+        for other in polarities {
+            let summary = self.entry(other.polarity);
+            summary.num_spectra += other.num_spectra;
+            summary.num_ms1_spectra += other.num_ms1_spectra;
+            summary.num_ms2_spectra += other.num_ms2_spectra;
+            summary.total_peaks += other.total_peaks;
+        }
+    }
+
+    fn entry(&mut self, polarity: i8) -> &mut PolaritySummary {
+        let index = match self.0.iter().position(|s| s.polarity == polarity) {
+            Some(index) => index,
+            None => {
+                self.0.push(PolaritySummary {
+                    polarity,
+                    ..Default::default()
+                });
+                self.0.len() - 1
+            }
+        };
+        &mut self.0[index]
+    }
+
+    pub(super) fn finish(mut self) -> Vec<PolaritySummary> {
+        self.0.sort_by_key(|s| std::cmp::Reverse(s.polarity));
+        self.0
+    }
+}
+
+/// Display name of a polarity value
+fn polarity_name(polarity: i8) -> &'static str {
+    match polarity {
+        1 => "positive",
+        -1 => "negative",
+        _ => "unknown",
+    }
 }
 
 impl MzPeakReader {
@@ -29,6 +121,10 @@ impl MzPeakReader {
         let num_spectra = spectra.len() as i64;
         let num_ms1 = spectra.iter().filter(|s| s.ms_level == 1).count() as i64;
         let num_ms2 = spectra.iter().filter(|s| s.ms_level == 2).count() as i64;
+        let mut polarities = PolarityBreakdown::default();
+        for spectrum in &spectra {
+            polarities.add(spectrum.polarity, spectrum.ms_level, spectrum.peak_count());
+        }
 
         let rt_range = if !spectra.is_empty() {
             let min_rt = spectra
@@ -72,6 +168,7 @@ impl MzPeakReader {
             rt_range,
             mz_range,
             format_version: self.file_metadata.format_version.clone(),
+            polarities: polarities.finish(),
         })
     }
 }
@@ -85,6 +182,21 @@ impl fmt::Display for FileSummary {
         writeln!(f, "Total spectra: {}", self.num_spectra)?;
         writeln!(f, "  MS1 spectra: {}", self.num_ms1_spectra)?;
         writeln!(f, "  MS2 spectra: {}", self.num_ms2_spectra)?;
+        if self.is_mixed_polarity() {
+            writeln!(f, "Polarity:")?;
+            for summary in &self.polarities {
+                writeln!(
+                    f,
+                    "  {}: {} spectra (MS1 {}, MS2 {})",
+                    polarity_name(summary.polarity),
+                    summary.num_spectra,
+                    summary.num_ms1_spectra,
+                    summary.num_ms2_spectra
+                )?;
+            }
+        } else if let Some(summary) = self.polarities.first() {
+            writeln!(f, "Polarity: {}", polarity_name(summary.polarity))?;
+        }
         if let Some((min_rt, max_rt)) = self.rt_range {
             writeln!(f, "RT range: {:.2} - {:.2} sec", min_rt, max_rt)?;
         }
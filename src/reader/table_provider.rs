@@ -0,0 +1,596 @@
+//! DataFusion `TableProvider` implementations for mzPeak tables
+//!
+//! Available with the `datafusion` feature. [`PeaksTableProvider`] and
+//! [`SpectraTableProvider`] let applications that embed DataFusion register
+//! mzPeak containers directly:
+//!
+//! ```rust,ignore
+//! use std::sync::Arc;
+//! use datafusion::prelude::SessionContext;
+//! use mzpeak::reader::MzPeakReader;
+//!
+//! let reader = MzPeakReader::open("data.mzpeak")?;
+//! let ctx = SessionContext::new();
+//! ctx.register_table("peaks", Arc::new(reader.peaks_table_provider()?))?;
+//! ```
+//!
+//! Both providers read through the same seekable sources as [`MzPeakReader`]
+//! (plain files or stored ZIP entries), so nothing is loaded eagerly:
+//!
+//! - **Projection pushdown**: only the requested columns are decoded.
+//! - **Filter pushdown**: comparisons and `BETWEEN` on numeric columns prune
+//!   row groups using column chunk statistics. Pushdown is inexact; DataFusion
+//!   re-applies the filters to the surviving rows.
+//! - **Delta-encoded m/z**: files storing `mz_delta` (see
+//!   [`crate::schema::mz_delta`]) expose the decoded Float64 `mz` column.
+//!   Their row groups are scanned in order in one partition, and only
+//!   filters on columns that are constant within a spectrum prune them.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use arrow::datatypes::{DataType, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use datafusion::catalog::{Session, TableProvider};
+use datafusion::common::{Column, DataFusionError, Result as DFResult, ScalarValue};
+use datafusion::execution::{SendableRecordBatchStream, TaskContext};
+use datafusion::logical_expr::{
+    Between, BinaryExpr, Expr, Operator, TableProviderFilterPushDown, TableType,
+};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::streaming::{PartitionStream, StreamingTableExec};
+use datafusion::physical_plan::ExecutionPlan;
+use parquet::arrow::arrow_reader::{
+    ArrowReaderMetadata, ArrowReaderOptions, ParquetRecordBatchReaderBuilder,
+};
+use parquet::arrow::ProjectionMask;
+use parquet::file::metadata::ParquetMetaData;
+use parquet::file::statistics::Statistics;
+use zip::ZipArchive;
+
+use crate::schema::columns;
+use crate::schema::mz_delta::{mz_schema, MzDeltaDecoder};
+
+use super::config::ReaderSource;
+use super::zip_chunk_reader::{SharedZipEntryReader, ZipEntryChunkReader};
+use super::{MzPeakReader, ReaderError};
+
+const SPECTRA_SUBPATH: &str = "spectra/spectra.parquet";
+
+// =============================================================================
+// Reader entry points
+// =============================================================================
+
+impl MzPeakReader {
+    /// Create a DataFusion table provider for the peak table
+    pub fn peaks_table_provider(&self) -> Result<PeaksTableProvider, ReaderError> {
+        let source = match &self.source {
+            ReaderSource::FilePath(path) => TableSource::File(path.clone()),
+            ReaderSource::ZipContainer { chunk_reader, .. } => {
+                TableSource::Zip(chunk_reader.clone())
+            }
+        };
+        Ok(PeaksTableProvider(ParquetTable::try_new(
+            source,
+            self.config.batch_size,
+        )?))
+    }
+
+    /// Create a DataFusion table provider for the spectrum table
+    ///
+    /// Returns `None` for v1.0 files, which have no separate spectrum table.
+    pub fn spectra_table_provider(&self) -> Result<Option<SpectraTableProvider>, ReaderError> {
+        let source = match &self.source {
+            ReaderSource::FilePath(path) => {
+                match Self::dataset_subfile_path(path, SPECTRA_SUBPATH)? {
+                    Some(spectra_path) if spectra_path.exists() => TableSource::File(spectra_path),
+                    _ => return Ok(None),
+                }
+            }
+            ReaderSource::ZipContainer { zip_path, .. } => {
+                let archive = ZipArchive::new(BufReader::new(File::open(zip_path)?))?;
+                if archive.index_for_name(SPECTRA_SUBPATH).is_none() {
+                    return Ok(None);
+                }
+                TableSource::Zip(SharedZipEntryReader::new(ZipEntryChunkReader::new(
+                    zip_path,
+                    SPECTRA_SUBPATH,
+                )?))
+            }
+        };
+        Ok(Some(SpectraTableProvider(ParquetTable::try_new(
+            source,
+            self.config.batch_size,
+        )?)))
+    }
+}
+
+// =============================================================================
+// Providers
+// =============================================================================
+
+/// DataFusion table provider over `peaks/peaks.parquet`
+#[derive(Debug, Clone)]
+pub struct PeaksTableProvider(ParquetTable);
+
+/// DataFusion table provider over `spectra/spectra.parquet` (v2.0 datasets)
+#[derive(Debug, Clone)]
+pub struct SpectraTableProvider(ParquetTable);
+
+macro_rules! impl_table_provider {
+    ($provider:ty) => {
+        #[async_trait]
+        impl TableProvider for $provider {
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
+
+            fn schema(&self) -> SchemaRef {
+                Arc::clone(&self.0.schema)
+            }
+
+            fn table_type(&self) -> TableType {
+                TableType::Base
+            }
+
+            fn supports_filters_pushdown(
+                &self,
+                filters: &[&Expr],
+            ) -> DFResult<Vec<TableProviderFilterPushDown>> {
+                Ok(filters
+                    .iter()
+                    .map(|filter| {
+                        if collect_bounds(filter, &self.0.schema, &mut HashMap::new()) {
+                            TableProviderFilterPushDown::Inexact
+                        } else {
+                            TableProviderFilterPushDown::Unsupported
+                        }
+                    })
+                    .collect())
+            }
+
+            async fn scan(
+                &self,
+                state: &dyn Session,
+                projection: Option<&Vec<usize>>,
+                filters: &[Expr],
+                limit: Option<usize>,
+            ) -> DFResult<Arc<dyn ExecutionPlan>> {
+                self.0
+                    .scan(state.config().target_partitions(), projection, filters, limit)
+            }
+        }
+    };
+}
+
+impl_table_provider!(PeaksTableProvider);
+impl_table_provider!(SpectraTableProvider);
+
+// =============================================================================
+// Shared scan implementation
+// =============================================================================
+
+/// Seekable location of a Parquet table
+#[derive(Debug, Clone)]
+enum TableSource {
+    File(PathBuf),
+    Zip(SharedZipEntryReader),
+}
+
+/// Parquet table with its footer parsed once and shared by every scan
+#[derive(Clone)]
+struct ParquetTable {
+    source: TableSource,
+    reader_metadata: ArrowReaderMetadata,
+    /// Table schema, with `mz` in place of a stored `mz_delta`
+    schema: SchemaRef,
+    /// Index of the stored `mz_delta` column and of `spectrum_id`
+    mz_delta: Option<(usize, usize)>,
+    batch_size: usize,
+}
+
+impl fmt::Debug for ParquetTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParquetTable")
+            .field("source", &self.source)
+            .field("num_row_groups", &self.reader_metadata.metadata().num_row_groups())
+            .field("batch_size", &self.batch_size)
+            .finish()
+    }
+}
+
+impl ParquetTable {
+    fn try_new(source: TableSource, batch_size: usize) -> Result<Self, ReaderError> {
+        let options = ArrowReaderOptions::new();
+        let reader_metadata = match &source {
+            TableSource::File(path) => ArrowReaderMetadata::load(&File::open(path)?, options)?,
+            TableSource::Zip(reader) => ArrowReaderMetadata::load(reader, options)?,
+        };
+        let stored = reader_metadata.schema();
+        let mz_delta = match stored.index_of(columns::MZ_DELTA) {
+            Ok(index) => Some((index, stored.index_of(columns::SPECTRUM_ID)?)),
+            Err(_) => None,
+        };
+        let schema = Arc::new(mz_schema(stored));
+        Ok(Self {
+            source,
+            reader_metadata,
+            schema,
+            mz_delta,
+            batch_size,
+        })
+    }
+
+    fn scan(
+        &self,
+        target_partitions: usize,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> DFResult<Arc<dyn ExecutionPlan>> {
+        let mut bounds = HashMap::new();
+        for filter in filters {
+            collect_bounds(filter, &self.schema, &mut bounds);
+        }
+        // Unsigned columns are stored with signed physical statistics.
+        bounds.retain(|name, _| {
+            self.schema
+                .field_with_name(name)
+                .map(|field| !field.data_type().is_unsigned_integer())
+                .unwrap_or(false)
+        });
+        // Pruning on a peak column could cut off the first rows of a matching
+        // spectrum, whose later `mz_delta` values then decode wrongly.
+        if self.mz_delta.is_some() {
+            bounds.retain(|name, _| {
+                ![columns::MZ, columns::INTENSITY, columns::ION_MOBILITY].contains(&name.as_str())
+            });
+        }
+        let row_groups = prune_row_groups(self.reader_metadata.metadata(), &bounds);
+
+        // Parquet decodes columns in file order; remember how to restore the
+        // order DataFusion asked for.
+        let (file_columns, reorder) = match projection {
+            Some(projection) => {
+                let mut sorted = projection.clone();
+                // Decoding `mz` needs the spectrum IDs
+                if let Some((mz, spectrum_id)) = self.mz_delta {
+                    if projection.contains(&mz) {
+                        sorted.push(spectrum_id);
+                    }
+                }
+                sorted.sort_unstable();
+                sorted.dedup();
+                let reorder = projection
+                    .iter()
+                    .map(|i| sorted.binary_search(i).unwrap_or_default())
+                    .collect();
+                (Some(sorted), Some(reorder))
+            }
+            None => (None, None),
+        };
+        let schema = match projection {
+            Some(projection) => Arc::new(self.schema.project(projection)?),
+            None => Arc::clone(&self.schema),
+        };
+
+        // Only bound the decoder when every row it yields is a result row.
+        let limit = if filters.is_empty() { limit } else { None };
+
+        // Spectra straddle row groups, so `mz_delta` decodes in file order
+        let num_partitions = match self.mz_delta {
+            Some(_) => 1,
+            None => target_partitions.clamp(1, row_groups.len().max(1)),
+        };
+        let mut partition_row_groups = vec![Vec::new(); num_partitions];
+        for (i, row_group) in row_groups.into_iter().enumerate() {
+            partition_row_groups[i % num_partitions].push(row_group);
+        }
+
+        let partitions = partition_row_groups
+            .into_iter()
+            .map(|row_groups| {
+                Arc::new(RowGroupPartition {
+                    table: self.clone(),
+                    schema: Arc::clone(&schema),
+                    row_groups,
+                    file_columns: file_columns.clone(),
+                    reorder: reorder.clone(),
+                    limit,
+                }) as Arc<dyn PartitionStream>
+            })
+            .collect();
+
+        Ok(Arc::new(StreamingTableExec::try_new(
+            schema,
+            partitions,
+            None,
+            Vec::new(),
+            false,
+            limit,
+        )?))
+    }
+
+    fn open(
+        &self,
+        row_groups: Vec<usize>,
+        file_columns: Option<&[usize]>,
+        limit: Option<usize>,
+    ) -> Result<Box<dyn Iterator<Item = DFResult<RecordBatch>> + Send>, ReaderError> {
+        fn build<T: parquet::file::reader::ChunkReader + 'static>(
+            builder: ParquetRecordBatchReaderBuilder<T>,
+            batch_size: usize,
+            row_groups: Vec<usize>,
+            file_columns: Option<&[usize]>,
+            limit: Option<usize>,
+        ) -> Result<Box<dyn Iterator<Item = DFResult<RecordBatch>> + Send>, ReaderError> {
+            let mut builder = builder
+                .with_batch_size(batch_size)
+                .with_row_groups(row_groups);
+            if let Some(columns) = file_columns {
+                let mask = ProjectionMask::roots(builder.parquet_schema(), columns.iter().copied());
+                builder = builder.with_projection(mask);
+            }
+            if let Some(limit) = limit {
+                builder = builder.with_limit(limit);
+            }
+            let reader = builder.build()?;
+            // Files storing `mz_delta` get `mz` back; others pass through
+            let mut decoder = MzDeltaDecoder::default();
+            Ok(Box::new(reader.map(move |batch| {
+                batch
+                    .and_then(|batch| decoder.decode(batch))
+                    .map_err(DataFusionError::from)
+            })))
+        }
+
+        let metadata = self.reader_metadata.clone();
+        match &self.source {
+            TableSource::File(path) => build(
+                ParquetRecordBatchReaderBuilder::new_with_metadata(File::open(path)?, metadata),
+                self.batch_size,
+                row_groups,
+                file_columns,
+                limit,
+            ),
+            TableSource::Zip(reader) => build(
+                ParquetRecordBatchReaderBuilder::new_with_metadata(reader.clone(), metadata),
+                self.batch_size,
+                row_groups,
+                file_columns,
+                limit,
+            ),
+        }
+    }
+}
+
+/// One output partition: a subset of the table's surviving row groups
+struct RowGroupPartition {
+    table: ParquetTable,
+    schema: SchemaRef,
+    row_groups: Vec<usize>,
+    file_columns: Option<Vec<usize>>,
+    reorder: Option<Vec<usize>>,
+    limit: Option<usize>,
+}
+
+impl fmt::Debug for RowGroupPartition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RowGroupPartition")
+            .field("row_groups", &self.row_groups)
+            .field("file_columns", &self.file_columns)
+            .finish()
+    }
+}
+
+impl PartitionStream for RowGroupPartition {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        let batches: Box<dyn Iterator<Item = DFResult<RecordBatch>> + Send> = match self
+            .table
+            .open(
+                self.row_groups.clone(),
+                self.file_columns.as_deref(),
+                self.limit,
+            ) {
+            Ok(batches) => match self.reorder.clone() {
+                Some(reorder) => Box::new(batches.map(move |batch| {
+                    batch.and_then(|batch| Ok(batch.project(&reorder)?))
+                })),
+                None => batches,
+            },
+            Err(e) => Box::new(std::iter::once(Err(DataFusionError::External(Box::new(e))))),
+        };
+
+        Box::pin(RecordBatchStreamAdapter::new(
+            Arc::clone(&self.schema),
+            futures::stream::iter(batches),
+        ))
+    }
+}
+
+// =============================================================================
+// Filter analysis and row-group pruning
+// =============================================================================
+
+/// Intersect the value range implied by `expr` into `bounds`
+///
+/// Returns `false` if the expression is not a supported range predicate, in
+/// which case `bounds` may be partially updated but remains conservative.
+/// Casts of a column are only looked through when they are lossless
+/// widenings: `CAST(mz AS INT) = 500` also matches mz 500.5, which a bound of
+/// [500, 500] on mz would prune.
+fn collect_bounds(
+    expr: &Expr,
+    schema: &Schema,
+    bounds: &mut HashMap<String, (f64, f64)>,
+) -> bool {
+    match expr {
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => {
+            if *op == Operator::And {
+                let left_ok = collect_bounds(left, schema, bounds);
+                let right_ok = collect_bounds(right, schema, bounds);
+                return left_ok && right_ok;
+            }
+            let (column, value, op) = match (as_column(left, schema), as_number(right)) {
+                (Some(column), Some(value)) => (column, value, *op),
+                _ => match (as_number(left), as_column(right, schema)) {
+                    (Some(value), Some(column)) => match op.swap() {
+                        Some(op) => (column, value, op),
+                        None => return false,
+                    },
+                    _ => return false,
+                },
+            };
+            // Strict comparisons are widened to inclusive ones; pruning only
+            // needs to be conservative.
+            let range = match op {
+                Operator::Eq => (value, value),
+                Operator::Lt | Operator::LtEq => (f64::NEG_INFINITY, value),
+                Operator::Gt | Operator::GtEq => (value, f64::INFINITY),
+                _ => return false,
+            };
+            intersect(bounds, column, range);
+            true
+        }
+        Expr::Between(Between {
+            expr,
+            negated: false,
+            low,
+            high,
+        }) => match (as_column(expr, schema), as_number(low), as_number(high)) {
+            (Some(column), Some(low), Some(high)) => {
+                intersect(bounds, column, (low, high));
+                true
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn intersect(bounds: &mut HashMap<String, (f64, f64)>, column: &Column, range: (f64, f64)) {
+    let entry = bounds
+        .entry(column.name.clone())
+        .or_insert((f64::NEG_INFINITY, f64::INFINITY));
+    entry.0 = entry.0.max(range.0);
+    entry.1 = entry.1.min(range.1);
+}
+
+/// Column compared by `expr`, directly or through a lossless cast
+fn as_column<'a>(expr: &'a Expr, schema: &Schema) -> Option<&'a Column> {
+    let (inner, data_type) = match expr {
+        Expr::Column(column) => return Some(column),
+        Expr::Cast(cast) => (&cast.expr, &cast.data_type),
+        Expr::TryCast(cast) => (&cast.expr, &cast.data_type),
+        _ => return None,
+    };
+    let Expr::Column(column) = inner.as_ref() else {
+        return None;
+    };
+    let field = schema.field_with_name(&column.name).ok()?;
+    is_lossless_widening(field.data_type(), data_type).then_some(column)
+}
+
+/// Whether every value of `from` converts to `to` without changing its order
+/// or merging with its neighbours
+fn is_lossless_widening(from: &DataType, to: &DataType) -> bool {
+    use DataType::*;
+    from == to
+        || matches!(
+            (from, to),
+            (Int8, Int16 | Int32 | Int64 | Float32 | Float64)
+                | (Int16, Int32 | Int64 | Float32 | Float64)
+                | (Int32, Int64 | Float64)
+                | (UInt8, Int16 | Int32 | Int64 | UInt16 | UInt32 | UInt64 | Float32 | Float64)
+                | (UInt16, Int32 | Int64 | UInt32 | UInt64 | Float32 | Float64)
+                | (UInt32, Int64 | UInt64 | Float64)
+                | (Float32, Float64)
+        )
+}
+
+fn as_number(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::Literal(value) => match value {
+            ScalarValue::Int8(Some(v)) => Some(*v as f64),
+            ScalarValue::Int16(Some(v)) => Some(*v as f64),
+            ScalarValue::Int32(Some(v)) => Some(*v as f64),
+            ScalarValue::Int64(Some(v)) => Some(*v as f64),
+            ScalarValue::UInt8(Some(v)) => Some(*v as f64),
+            ScalarValue::UInt16(Some(v)) => Some(*v as f64),
+            ScalarValue::UInt32(Some(v)) => Some(*v as f64),
+            ScalarValue::UInt64(Some(v)) => Some(*v as f64),
+            ScalarValue::Float32(Some(v)) => Some(*v as f64),
+            ScalarValue::Float64(Some(v)) => Some(*v),
+            _ => None,
+        },
+        // The comparison sees the cast value, e.g. CAST(500.7 AS INT) is 500
+        Expr::Cast(cast) => match cast.expr.as_ref() {
+            Expr::Literal(value) => {
+                as_number(&Expr::Literal(value.cast_to(&cast.data_type).ok()?))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Select the row groups whose column statistics overlap every bound
+fn prune_row_groups(metadata: &ParquetMetaData, bounds: &HashMap<String, (f64, f64)>) -> Vec<usize> {
+    let columns = metadata.file_metadata().schema_descr().columns();
+    let bounded: Vec<(usize, (f64, f64))> = bounds
+        .iter()
+        .filter_map(|(name, range)| {
+            columns
+                .iter()
+                .position(|column| column.name() == name)
+                .map(|index| (index, *range))
+        })
+        .collect();
+
+    (0..metadata.num_row_groups())
+        .filter(|&i| {
+            let row_group = metadata.row_group(i);
+            bounded.iter().all(|(index, (low, high))| {
+                match statistics_range(row_group.column(*index).statistics()) {
+                    Some((min, max)) => *high >= min && *low <= max,
+                    None => true,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Exact numeric min/max of a column chunk, if available
+fn statistics_range(statistics: Option<&Statistics>) -> Option<(f64, f64)> {
+    fn range<T: Copy + Into<f64>>(
+        stats: &parquet::file::statistics::ValueStatistics<T>,
+    ) -> Option<(f64, f64)> {
+        if !(stats.min_is_exact() && stats.max_is_exact()) {
+            return None;
+        }
+        Some(((*stats.min_opt()?).into(), (*stats.max_opt()?).into()))
+    }
+
+    match statistics? {
+        Statistics::Int32(stats) => range(stats),
+        Statistics::Float(stats) => range(stats),
+        Statistics::Double(stats) => range(stats),
+        Statistics::Int64(stats) => {
+            if !(stats.min_is_exact() && stats.max_is_exact()) {
+                return None;
+            }
+            Some((*stats.min_opt()? as f64, *stats.max_opt()? as f64))
+        }
+        _ => None,
+    }
+}
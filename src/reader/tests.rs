@@ -1,6 +1,7 @@
 use super::*;
 use crate::metadata::MzPeakMetadata;
 use crate::writer::{MzPeakWriter, PeakArrays, SpectrumArrays, WriterConfig};
+use std::sync::Arc;
 use tempfile::tempdir;
 
 #[test]
@@ -42,6 +43,18 @@ fn test_read_write_roundtrip() -> Result<(), Box<dyn std::error::Error>> {
     assert_eq!(spectra[1].ms_level, 2);
     assert_eq!(spectra[1].precursor_mz, Some(450.0));
 
+    // Field CV annotations survive the Parquet round trip
+    let metadata = reader.metadata();
+    assert_eq!(
+        metadata.column_unit(crate::schema::columns::RETENTION_TIME),
+        Some(crate::schema::Unit::Second)
+    );
+    assert_eq!(
+        metadata.column_unit(crate::schema::columns::MZ),
+        Some(crate::schema::Unit::MassToCharge)
+    );
+    assert!(metadata.column_units().len() >= 3);
+
     Ok(())
 }
 
@@ -111,6 +124,56 @@ fn test_file_summary() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn test_mixed_polarity_summary_and_filter() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().join("test.parquet");
+    let mut writer = MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), WriterConfig::default())?;
+
+    // Polarity switching: positive MS1 + MS2, then negative MS1 + MS2
+    let mut spectra = Vec::new();
+    for i in 0..8 {
+        let polarity = if (i / 2) % 2 == 0 { 1 } else { -1 };
+        let peaks = PeakArrays::new(vec![300.0, 400.0 + i as f64], vec![10.0, 1000.0]);
+        spectra.push(if i % 2 == 0 {
+            SpectrumArrays::new_ms1(i, i + 1, i as f32, polarity, peaks)
+        } else {
+            SpectrumArrays::new_ms2(i, i + 1, i as f32, polarity, 450.0, peaks)
+        });
+    }
+    writer.write_spectra_arrays(&spectra)?;
+    writer.finish()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    let summary = reader.summary()?;
+    assert!(summary.is_mixed_polarity());
+    let polarities: Vec<i8> = summary.polarities.iter().map(|s| s.polarity).collect();
+    assert_eq!(polarities, vec![1, -1]);
+    let negative = summary.polarity(-1).unwrap();
+    assert_eq!(negative.num_spectra, 4);
+    assert_eq!(negative.num_ms1_spectra, 2);
+    assert_eq!(negative.num_ms2_spectra, 2);
+    assert_eq!(negative.total_peaks, 8);
+    assert!(summary.polarity(0).is_none());
+    assert!(summary.to_string().contains("negative: 4 spectra (MS1 2, MS2 2)"));
+
+    let ids: Vec<i64> = reader
+        .spectra_by_polarity_arrays(-1)?
+        .iter()
+        .map(|s| s.spectrum_id)
+        .collect();
+    assert_eq!(ids, vec![2, 3, 6, 7]);
+    assert!(reader
+        .spectra_by_polarity_arrays(-1)?
+        .iter()
+        .all(|s| s.polarity == -1));
+
+    // The in-memory store reports the same breakdown
+    let store = InMemorySpectrumStore::new(spectra);
+    assert_eq!(store.summary()?.polarities, summary.polarities);
+    Ok(())
+}
+
 #[test]
 fn test_spectra_by_rt_range() -> Result<(), Box<dyn std::error::Error>> {
     let dir = tempdir()?;
@@ -136,6 +199,11 @@ fn test_spectra_by_rt_range() -> Result<(), Box<dyn std::error::Error>> {
     assert_eq!(spectra[1].retention_time, 40.0);
     assert_eq!(spectra[2].retention_time, 50.0);
 
+    // The same window expressed in minutes
+    let spectra = reader.spectra_in_rt_range(RtRange::minutes(0.5, 0.9))?;
+    assert_eq!(spectra.len(), 3);
+    assert_eq!(spectra[0].retention_time, 30.0);
+
     Ok(())
 }
 
@@ -154,7 +222,11 @@ fn test_spectrum_arrays_view_segments() -> Result<(), Box<dyn std::error::Error>
     writer.write_spectrum_arrays(&spectrum)?;
     writer.finish()?;
 
-    let reader = MzPeakReader::open_with_config(&path, ReaderConfig { batch_size: 2 })?;
+    let config = ReaderConfig {
+        batch_size: 2,
+        ..Default::default()
+    };
+    let reader = MzPeakReader::open_with_config(&path, config)?;
     let mut iter = reader.iter_spectra_arrays_streaming()?;
     let view = iter.next().unwrap()?;
 
@@ -175,3 +247,1288 @@ fn test_spectrum_arrays_view_segments() -> Result<(), Box<dyn std::error::Error>
 
     Ok(())
 }
+
+#[cfg(feature = "datafusion")]
+#[test]
+fn test_sql_query_on_peaks_table() -> Result<(), Box<dyn std::error::Error>> {
+    use arrow::array::Int64Array;
+
+    let dir = tempdir()?;
+    let path = dir.path().join("test.parquet");
+
+    let metadata = MzPeakMetadata::new();
+    let mut writer = MzPeakWriter::new_file(&path, &metadata, WriterConfig::default())?;
+
+    for i in 0..5 {
+        let peaks = PeakArrays::new(vec![400.0, 500.0 + i as f64], vec![1000.0, 2000.0]);
+        let spectrum = SpectrumArrays::new_ms1(i, i + 1, i as f32 * 10.0, 1, peaks);
+        writer.write_spectrum_arrays(&spectrum)?;
+    }
+    writer.finish()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    let batches = reader.sql("SELECT COUNT(*) AS n FROM peaks WHERE mz >= 502.0")?;
+
+    let counts = batches[0]
+        .column(0)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .expect("COUNT(*) is Int64");
+    assert_eq!(counts.value(0), 3);
+
+    Ok(())
+}
+
+#[cfg(feature = "datafusion")]
+#[test]
+fn test_substrait_plan_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    use arrow::array::AsArray;
+    use arrow::datatypes::Int64Type;
+    use arrow::record_batch::RecordBatch;
+
+    let dir = tempdir()?;
+    let path = dir.path().join("test.parquet");
+
+    let metadata = MzPeakMetadata::new();
+    let mut writer = MzPeakWriter::new_file(&path, &metadata, WriterConfig::default())?;
+    for i in 0..5 {
+        let peaks = PeakArrays::new(vec![400.0, 500.0 + i as f64], vec![1000.0, 2000.0]);
+        let spectrum = SpectrumArrays::new_ms1(i, i + 1, i as f32 * 10.0, 1, peaks);
+        writer.write_spectrum_arrays(&spectrum)?;
+    }
+    writer.finish()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    let sql = "SELECT spectrum_id, COUNT(*) AS n FROM peaks WHERE mz >= 502.0 \
+               GROUP BY spectrum_id ORDER BY spectrum_id";
+
+    // Serialize the plan against the reader's own tables, as an external
+    // planner would before handing it over
+    let plan = tokio::runtime::Builder::new_current_thread()
+        .build()?
+        .block_on(async {
+            let ctx = reader.session_context().await?;
+            let plan = datafusion_substrait::serializer::serialize_bytes(sql, &ctx).await?;
+            Ok::<_, ReaderError>(plan)
+        })?;
+
+    let rows = |batches: &[RecordBatch]| -> Vec<(i64, i64)> {
+        batches
+            .iter()
+            .flat_map(|batch| {
+                let ids = batch.column(0).as_primitive::<Int64Type>();
+                let counts = batch.column(1).as_primitive::<Int64Type>();
+                ids.values()
+                    .iter()
+                    .copied()
+                    .zip(counts.values().iter().copied())
+            })
+            .collect()
+    };
+    let substrait = reader.execute_substrait(&plan)?;
+    assert_eq!(rows(&substrait), vec![(2, 1), (3, 1), (4, 1)]);
+    assert_eq!(rows(&substrait), rows(&reader.sql(sql)?));
+    assert_eq!(substrait[0].schema().field(1).name(), "n");
+
+    Ok(())
+}
+
+#[cfg(feature = "datafusion")]
+#[test]
+fn test_sql_truncating_cast_does_not_prune_row_groups() -> Result<(), Box<dyn std::error::Error>>
+{
+    use arrow::array::Int64Array;
+
+    let dir = tempdir()?;
+    let path = dir.path().join("test.parquet");
+
+    // One spectrum per row group, with mz 500.0 and 500.5 in different groups
+    let config = WriterConfig {
+        row_group_size: 2,
+        ..Default::default()
+    };
+    let metadata = MzPeakMetadata::new();
+    let mut writer = MzPeakWriter::new_file(&path, &metadata, config)?;
+    for (i, mz) in [400.0, 500.0, 500.5, 600.0].into_iter().enumerate() {
+        let peaks = PeakArrays::new(vec![mz, mz + 0.25], vec![1000.0, 2000.0]);
+        let spectrum = SpectrumArrays::new_ms1(i as i64, i as i64 + 1, i as f32, 1, peaks);
+        writer.write_spectrum_arrays(&spectrum)?;
+    }
+    writer.finish()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    for (sql, expected) in [
+        ("SELECT COUNT(*) FROM peaks WHERE CAST(mz AS INT) = 500", 4),
+        ("SELECT COUNT(*) FROM peaks WHERE CAST(mz AS BIGINT) BETWEEN 500 AND 500", 4),
+        ("SELECT COUNT(*) FROM peaks WHERE mz = CAST(500.7 AS INT)", 1),
+    ] {
+        let batches = reader.sql(sql)?;
+        let counts = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .expect("COUNT(*) is Int64");
+        assert_eq!(counts.value(0), expected, "{}", sql);
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "datafusion")]
+#[test]
+fn test_sql_query_on_sorted_mz() -> Result<(), Box<dyn std::error::Error>> {
+    use arrow::array::{AsArray, Float64Array, Int64Array};
+    use arrow::datatypes::Float64Type;
+
+    let dir = tempdir()?;
+    let path = dir.path().join("test.parquet");
+
+    let config = WriterConfig {
+        // Spectra straddle row groups
+        row_group_size: 4,
+        enforce_sorted_mz: true,
+        ..Default::default()
+    };
+    let mut writer = MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), config)?;
+    for i in 0..5 {
+        let mz = vec![400.0, 500.0 + i as f64, 600.25];
+        let peaks = PeakArrays::new(mz, vec![1000.0, 2000.0, 3000.0 + i as f32]);
+        writer.write_spectrum_arrays(&SpectrumArrays::new_ms1(i, i + 1, i as f32, 1, peaks))?;
+    }
+    writer.finish()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    let batches = reader.sql("SELECT COUNT(*) FROM peaks WHERE mz BETWEEN 502.0 AND 600.0")?;
+    let counts = batches[0]
+        .column(0)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .expect("COUNT(*) is Int64");
+    assert_eq!(counts.value(0), 3);
+
+    // Spectrum 2 starts in the second row group, whose intensity statistics
+    // exclude 3002; the row in the third must still decode
+    let batches = reader.sql("SELECT mz FROM peaks WHERE intensity = 3002 ORDER BY mz")?;
+    let mz: Vec<f64> = batches
+        .iter()
+        .flat_map(|batch| batch.column(0).as_primitive::<Float64Type>().values().to_vec())
+        .collect();
+    assert_eq!(mz, vec![600.25]);
+
+    let batches = reader.sql("SELECT mz FROM peaks WHERE spectrum_id = 3 ORDER BY mz")?;
+    let mz = batches[0]
+        .column(0)
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .expect("mz is Float64");
+    assert_eq!(mz.values().to_vec(), vec![400.0, 503.0, 600.25]);
+
+    Ok(())
+}
+
+#[cfg(feature = "datafusion")]
+#[test]
+fn test_table_provider_on_container() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::dataset::MzPeakDatasetWriter;
+    use arrow::array::Int64Array;
+
+    let dir = tempdir()?;
+    let path = dir.path().join("test.mzpeak");
+
+    let metadata = MzPeakMetadata::new();
+    let mut dataset = MzPeakDatasetWriter::new(&path, &metadata, WriterConfig::default())?;
+    for i in 0..10 {
+        let peaks = PeakArrays::new(vec![400.0, 500.0], vec![1000.0, 2000.0]);
+        dataset.write_spectrum_arrays(&SpectrumArrays::new_ms1(i, i + 1, i as f32, 1, peaks))?;
+    }
+    dataset.close()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    assert!(reader.spectra_table_provider()?.is_none());
+
+    let batches = reader.sql("SELECT spectrum_id FROM peaks WHERE spectrum_id BETWEEN 3 AND 4")?;
+    let mut ids: Vec<i64> = batches
+        .iter()
+        .flat_map(|batch| {
+            let ids = batch.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+            ids.values().to_vec()
+        })
+        .collect();
+    ids.sort_unstable();
+    assert_eq!(ids, vec![3, 3, 4, 4]);
+
+    Ok(())
+}
+
+#[test]
+fn test_metadata_json_edit_roundtrip_keeps_unknown_fields(
+) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::dataset::MzPeakDatasetWriter;
+    use crate::metadata::SdrfMetadata;
+
+    let dir = tempdir()?;
+    let path = dir.path().join("future.mzpeak");
+
+    // Simulate metadata carrying fields from a newer writer
+    let mut metadata = MzPeakMetadata::new();
+    let mut sdrf = SdrfMetadata::new("sample");
+    sdrf.unknown_fields
+        .insert("future_sdrf_field".to_string(), serde_json::json!([1, 2, 3]));
+    metadata.sdrf = Some(sdrf);
+    metadata
+        .unknown_fields
+        .insert("future_section".to_string(), serde_json::json!({"key": "value"}));
+
+    let mut dataset = MzPeakDatasetWriter::new(&path, &metadata, WriterConfig::default())?;
+    let peaks = PeakArrays::new(vec![400.0], vec![1000.0]);
+    dataset.write_spectrum_arrays(&SpectrumArrays::new_ms1(0, 1, 0.0, 1, peaks))?;
+    dataset.close()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    let restored = reader.read_metadata_json()?.expect("metadata.json present");
+
+    assert_eq!(
+        restored.unknown_fields.get("future_section"),
+        Some(&serde_json::json!({"key": "value"}))
+    );
+    assert!(!restored.unknown_fields.contains_key("format_version"));
+    let sdrf = restored.sdrf.expect("sdrf present");
+    assert_eq!(sdrf.source_name, "sample");
+    assert_eq!(
+        sdrf.unknown_fields.get("future_sdrf_field"),
+        Some(&serde_json::json!([1, 2, 3]))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_read_metadata_json_missing_for_single_file() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().join("single.parquet");
+
+    let mut writer = MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), WriterConfig::default())?;
+    let peaks = PeakArrays::new(vec![400.0], vec![1000.0]);
+    writer.write_spectrum_arrays(&SpectrumArrays::new_ms1(0, 1, 0.0, 1, peaks))?;
+    writer.finish()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    assert!(reader.read_metadata_json()?.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn test_processing_history_from_footer() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::metadata::{ProcessingHistory, ProcessingStep};
+    use std::collections::HashMap;
+
+    let dir = tempdir()?;
+    let peaks = PeakArrays::new(vec![400.0], vec![1000.0]);
+    let spectrum = SpectrumArrays::new_ms1(0, 1, 0.0, 1, peaks);
+
+    let plain = dir.path().join("plain.parquet");
+    let mut writer =
+        MzPeakWriter::new_file(&plain, &MzPeakMetadata::new(), WriterConfig::default())?;
+    writer.write_spectrum_arrays(&spectrum)?;
+    writer.finish()?;
+    assert!(MzPeakReader::open(&plain)?.processing_history()?.is_empty());
+
+    let mut history = ProcessingHistory::new();
+    history.append(ProcessingStep::mzpeak("Conversion", HashMap::new()));
+    history.append(ProcessingStep::mzpeak("Centroiding", HashMap::new()));
+    let mut metadata = MzPeakMetadata::new();
+    metadata.processing_history = Some(history);
+
+    let path = dir.path().join("history.parquet");
+    let mut writer = MzPeakWriter::new_file(&path, &metadata, WriterConfig::default())?;
+    writer.write_spectrum_arrays(&spectrum)?;
+    writer.finish()?;
+
+    let steps = MzPeakReader::open(&path)?.processing_history()?;
+    let orders: Vec<_> = steps.iter().map(|s| (s.order, s.processing_type.as_str())).collect();
+    assert_eq!(orders, vec![(1, "Conversion"), (2, "Centroiding")]);
+    assert_eq!(steps[0].software, "mzpeak-rs");
+
+    Ok(())
+}
+
+#[test]
+fn test_prefetching_scan_matches_sequential() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().join("test.parquet");
+
+    let metadata = MzPeakMetadata::new();
+    let config = WriterConfig {
+        row_group_size: 7,
+        ..Default::default()
+    };
+    let mut writer = MzPeakWriter::new_file(&path, &metadata, config)?;
+    for i in 0..40 {
+        let peaks = PeakArrays::new(vec![400.0 + i as f64, 500.0], vec![1000.0, i as f32]);
+        writer.write_spectrum_arrays(&SpectrumArrays::new_ms1(i, i + 1, i as f32, 1, peaks))?;
+    }
+    writer.finish()?;
+
+    let open = |prefetch_row_groups| {
+        let config = ReaderConfig {
+            batch_size: 5,
+            prefetch_row_groups,
+            ..Default::default()
+        };
+        MzPeakReader::open_with_config(&path, config)
+    };
+    // Prefetching is opt-in
+    assert_eq!(ReaderConfig::default().prefetch_row_groups, 0);
+    let sequential = open(0)?;
+    let prefetching = open(2)?;
+    assert!(prefetching.metadata().num_row_groups > 1);
+
+    // Batches may be cut differently at row-group boundaries
+    let concat = |reader: &MzPeakReader| -> Result<_, Box<dyn std::error::Error>> {
+        let batches = reader.read_all_batches()?;
+        Ok(arrow::compute::concat_batches(&batches[0].schema(), &batches)?)
+    };
+    assert_eq!(concat(&sequential)?, concat(&prefetching)?);
+
+    let spectra = prefetching.iter_spectra_arrays()?;
+    assert_eq!(spectra.len(), 40);
+    assert!(spectra.iter().enumerate().all(|(i, s)| s.spectrum_id == i as i64));
+
+    // Dropping a partially consumed scan stops the background thread
+    let mut batches = prefetching.iter_batches()?;
+    assert!(batches.next().is_some());
+    drop(batches);
+
+    Ok(())
+}
+
+fn write_v2_dataset(path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+    use crate::dataset::{DatasetWriterV2Config, MzPeakDatasetWriterV2};
+    use crate::schema::manifest::Modality;
+    use crate::writer::{PeakArraysV2, SpectrumMetadata};
+
+    let mut config = DatasetWriterV2Config::default();
+    config.spectra_config.row_group_size = 4;
+    config.peaks_config.row_group_size = 5;
+    let mut writer = MzPeakDatasetWriterV2::with_config(path, Modality::LcMs, None, config)?;
+    for i in 0..12u32 {
+        // Spectrum i has i + 1 peaks, so spectra straddle peak row groups
+        let count = i as usize + 1;
+        let peaks = PeakArraysV2::new(
+            (0..count).map(|j| 100.0 * i as f64 + j as f64).collect(),
+            (0..count).map(|j| j as f32).collect(),
+        );
+        let mut metadata = if i % 3 == 0 {
+            SpectrumMetadata::new_ms1(i, Some(i as i32 + 1), i as f32, 1, count as u32)
+        } else {
+            SpectrumMetadata::new_ms2(i, Some(i as i32 + 1), i as f32, 1, count as u32, 500.0)
+        };
+        metadata.precursor_charge = (i % 3 != 0).then_some(2);
+        writer.write_spectrum_v2(&metadata, &peaks)?;
+    }
+    writer.close()?;
+    Ok(())
+}
+
+#[test]
+fn test_spectrum_checksums() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::dataset::{DatasetWriterV2Config, MzPeakDatasetWriterV2};
+    use crate::schema::manifest::Modality;
+    use crate::writer::{PeakArraysV2, SpectrumMetadata};
+
+    let dir = tempdir()?;
+    let path = dir.path().join("checksums.mzpeak");
+    let mut config = DatasetWriterV2Config::default();
+    config.spectra_config.spectrum_checksums = true;
+    let mut writer = MzPeakDatasetWriterV2::with_config(&path, Modality::LcMs, None, config)?;
+    for i in 0..4u32 {
+        // Spectrum 3 repeats the content of spectrum 1 under a new ID
+        let content = if i == 3 { 1 } else { i };
+        let peaks = PeakArraysV2::new(
+            vec![100.0 + content as f64, 200.0],
+            vec![10.0 * content as f32, 5.0],
+        );
+        let metadata = SpectrumMetadata::new_ms1(i, Some(i as i32 + 1), content as f32, 1, 2);
+        writer.write_spectrum_v2(&metadata, &peaks)?;
+    }
+    writer.close()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    for id in 0..4 {
+        assert_eq!(reader.verify_spectrum_checksum(id)?, Some(true));
+    }
+    assert_eq!(reader.verify_spectrum_checksum(99)?, None);
+
+    let checksums = reader.spectrum_checksums()?.unwrap();
+    let ids: Vec<i64> = checksums.iter().map(|&(id, _)| id).collect();
+    assert_eq!(ids, [0, 1, 2, 3]);
+    assert_eq!(checksums[1].1, checksums[3].1);
+    assert_ne!(checksums[0].1, checksums[1].1);
+
+    // Datasets written without checksums have nothing to verify
+    let plain = dir.path().join("plain.mzpeak");
+    write_v2_dataset(&plain)?;
+    let reader = MzPeakReader::open(&plain)?;
+    assert_eq!(reader.verify_spectrum_checksum(0)?, None);
+    assert!(reader.spectrum_checksums()?.is_none());
+    Ok(())
+}
+
+#[test]
+fn test_open_foreign_parquet() -> Result<(), Box<dyn std::error::Error>> {
+    use arrow::array::{Float32Array, Float64Array, Int8Array, UInt32Array};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::record_batch::RecordBatch;
+    use parquet::arrow::ArrowWriter;
+
+    let dir = tempdir()?;
+    let path = dir.path().join("export.parquet");
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("ScanNum", DataType::UInt32, false),
+        Field::new("RT", DataType::Float64, false),
+        Field::new("m/z", DataType::Float32, false),
+        Field::new("Intensity", DataType::Float64, false),
+        Field::new("precursor_charge", DataType::Int8, true),
+    ]));
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(UInt32Array::from(vec![5, 5, 6, 6, 6])),
+            Arc::new(Float64Array::from(vec![60.0, 60.0, 61.5, 61.5, 61.5])),
+            Arc::new(Float32Array::from(vec![100.5, 200.0, 150.0, 250.0, 350.0])),
+            Arc::new(Float64Array::from(vec![10.0, 20.0, 1.0, 2.0, 3.0])),
+            Arc::new(Int8Array::from(vec![None, None, Some(2), Some(2), Some(2)])),
+        ],
+    )?;
+    let mut writer = ArrowWriter::try_new(std::fs::File::create(&path)?, schema, None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+
+    let mapping = ForeignColumnMapping::new()
+        .column("spectrum_id", "ScanNum")
+        .column("retention_time", "RT")
+        .column("mz", "m/z")
+        .column("intensity", "Intensity")
+        .constant("polarity", 1.0);
+    let reader = MzPeakReader::open_foreign_parquet(&path, &mapping)?;
+    assert_eq!(reader.total_peaks(), 5);
+    assert_eq!(
+        reader.metadata().key_value_metadata.get(KEY_FOREIGN_SOURCE),
+        Some(&path.display().to_string())
+    );
+
+    let spectrum = reader.get_spectrum_arrays(6)?.expect("spectrum 6");
+    assert_eq!(spectrum.scan_number, 6);
+    assert_eq!(spectrum.ms_level, 1);
+    assert_eq!(spectrum.polarity, 1);
+    assert_eq!(spectrum.retention_time, 61.5);
+    assert_eq!(spectrum.precursor_charge, Some(2));
+    assert_eq!(spectrum.precursor_mz, None);
+    assert_eq!(spectrum.peak_count(), 3);
+    let spectrum = reader.get_spectrum_arrays(5)?.expect("spectrum 5");
+    assert_eq!(spectrum.precursor_charge, None);
+
+    // Re-written as a proper dataset
+    let output = dir.path().join("converted.mzpeak");
+    let mut writer = crate::dataset::MzPeakDatasetWriter::new(
+        &output,
+        &MzPeakMetadata::new(),
+        WriterConfig::default(),
+    )?;
+    for spectrum in reader.iter_spectra_arrays()? {
+        writer.write_spectrum_arrays(&spectrum.to_owned()?)?;
+    }
+    writer.close()?;
+    let converted = MzPeakReader::open(&output)?;
+    assert_eq!(converted.total_peaks(), 5);
+    let spectrum = converted.get_spectrum_arrays(5)?.expect("spectrum 5");
+    assert_eq!(spectrum.peak_count(), 2);
+
+    // Required columns must be mapped, and mapped columns must exist
+    let unmapped = ForeignColumnMapping::new().column("spectrum_id", "ScanNum");
+    assert!(matches!(
+        MzPeakReader::open_foreign_parquet(&path, &unmapped),
+        Err(ReaderError::ColumnNotFound(_))
+    ));
+    let missing = mapping.clone().column("ion_mobility", "1/K0");
+    assert!(matches!(
+        MzPeakReader::open_foreign_parquet(&path, &missing),
+        Err(ReaderError::ColumnNotFound(_))
+    ));
+    let unknown = mapping.clone().constant("charge", 2.0);
+    assert!(matches!(
+        MzPeakReader::open_foreign_parquet(&path, &unknown),
+        Err(ReaderError::ColumnNotFound(_))
+    ));
+    // Float IDs are cast, constants override the scan number default
+    let float_ids = mapping
+        .clone()
+        .column("spectrum_id", "Intensity")
+        .constant("scan_number", 0.0);
+    let reader = MzPeakReader::open_foreign_parquet(&path, &float_ids)?;
+    let spectrum = reader.get_spectrum_arrays(20)?.expect("spectrum 20");
+    assert_eq!(spectrum.scan_number, 0);
+
+    // Nulls in required columns and interleaved spectra are rejected
+    let nulls = mapping.clone().column("spectrum_id", "precursor_charge");
+    assert!(matches!(
+        MzPeakReader::open_foreign_parquet(&path, &nulls),
+        Err(ReaderError::InvalidFormat(_))
+    ));
+    let unsorted = dir.path().join("unsorted.parquet");
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::UInt32, false),
+        Field::new("mz", DataType::Float64, false),
+        Field::new("intensity", DataType::Float32, false),
+    ]));
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(UInt32Array::from(vec![1, 2, 1])),
+            Arc::new(Float64Array::from(vec![100.0, 100.0, 200.0])),
+            Arc::new(Float32Array::from(vec![1.0, 1.0, 1.0])),
+        ],
+    )?;
+    let mut writer = ArrowWriter::try_new(std::fs::File::create(&unsorted)?, schema, None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+    let by_name = ForeignColumnMapping::new().column("spectrum_id", "id");
+    assert!(matches!(
+        MzPeakReader::open_foreign_parquet(&unsorted, &by_name),
+        Err(ReaderError::InvalidFormat(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_v2_spectrum_lookup_cache() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().join("test.mzpeak");
+    write_v2_dataset(&path)?;
+
+    let mut reader = MzPeakReader::open(&path)?;
+    let location = reader.spectrum_location(7)?.unwrap();
+    assert_eq!(location.spectra_row_group, 1);
+    assert_eq!(location.spectra_row, 3);
+    assert_eq!(location.peak_offset, (1..=7).sum::<u64>());
+    assert_eq!(location.peak_count, 8);
+    assert!(reader.spectrum_location(99)?.is_none());
+
+    // Repeated lookups reuse the cached index
+    let index = reader.spectrum_index()?.unwrap();
+    for _ in 0..3 {
+        let spectrum = reader.get_spectrum_arrays(7)?.unwrap();
+        assert_eq!(spectrum.spectrum_id, 7);
+        assert_eq!(spectrum.ms_level, 2);
+        assert_eq!(spectrum.scan_number, 8);
+        assert_eq!(spectrum.precursor_mz, Some(500.0));
+        assert_eq!(spectrum.precursor_charge, Some(2));
+        let owned = spectrum.to_owned()?;
+        assert_eq!(owned.peaks.mz, (0..8).map(|j| 700.0 + j as f64).collect::<Vec<_>>());
+        assert_eq!(owned.peaks.intensity, (0..8).map(|j| j as f32).collect::<Vec<_>>());
+    }
+    assert!(Arc::ptr_eq(&index, &reader.spectrum_index()?.unwrap()));
+    assert!(reader.get_spectrum_arrays(99)?.is_none());
+
+    let spectra = reader.get_spectra_arrays(&[11, 0, 5])?;
+    let ids: Vec<i64> = spectra.iter().map(|s| s.spectrum_id).collect();
+    assert_eq!(ids, vec![0, 5, 11]);
+    assert_eq!(spectra[2].peak_count(), 12);
+
+    reader.refresh()?;
+    assert!(reader.spectrum_index.get().is_none());
+    assert_eq!(reader.get_spectrum_arrays(0)?.unwrap().peak_count(), 1);
+    assert!(!Arc::ptr_eq(&index, &reader.spectrum_index()?.unwrap()));
+
+    Ok(())
+}
+
+#[test]
+fn test_v2_spectrum_lookup_directory() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let container = dir.path().join("test.mzpeak");
+    write_v2_dataset(&container)?;
+
+    // The v2 writer only produces containers; unpack one into a bundle
+    let path = dir.path().join("bundle");
+    zip::ZipArchive::new(std::fs::File::open(&container)?)?.extract(&path)?;
+
+    let reader = MzPeakReader::open(&path)?;
+    let spectrum = reader.get_spectrum_arrays(3)?.unwrap();
+    assert_eq!(spectrum.ms_level, 1);
+    assert_eq!(spectrum.peak_count(), 4);
+    Ok(())
+}
+
+#[test]
+fn test_flat_table_joins_v2_dataset() -> Result<(), Box<dyn std::error::Error>> {
+    use arrow::array::{Array, AsArray};
+    use arrow::datatypes::{Float64Type, Int16Type, Int64Type};
+    use arrow::record_batch::RecordBatch;
+
+    let dir = tempdir()?;
+    let path = dir.path().join("test.mzpeak");
+    write_v2_dataset(&path)?;
+
+    // Small batches so peaks batches span several spectra batches
+    let config = ReaderConfig {
+        batch_size: 7,
+        ..Default::default()
+    };
+    let reader = MzPeakReader::open_with_config(&path, config)?;
+    let table = reader.flat_table(None)?;
+    assert_eq!(table.schema(), crate::schema::create_mzpeak_schema_arc());
+    let batches: Vec<RecordBatch> = table.collect::<Result<_, _>>()?;
+    let flat = arrow::compute::concat_batches(&batches[0].schema(), &batches)?;
+    assert_eq!(flat.num_rows(), (1..=12).sum::<usize>());
+
+    let expected_ids: Vec<i64> = (0..12).flat_map(|i| vec![i; i as usize + 1]).collect();
+    let ids = flat
+        .column_by_name("spectrum_id")
+        .unwrap()
+        .as_primitive::<Int64Type>();
+    assert_eq!(ids.values().to_vec(), expected_ids);
+    let scans = flat
+        .column_by_name("scan_number")
+        .unwrap()
+        .as_primitive::<Int64Type>();
+    assert!(scans
+        .iter()
+        .zip(&expected_ids)
+        .all(|(scan, id)| scan == Some(id + 1)));
+    let mz = flat
+        .column_by_name("mz")
+        .unwrap()
+        .as_primitive::<Float64Type>();
+    assert_eq!(mz.value(3), 200.0);
+    let charge = flat
+        .column_by_name("precursor_charge")
+        .unwrap()
+        .as_primitive::<Int16Type>();
+    assert!(charge.is_null(0));
+    assert_eq!(charge.value(1), 2);
+    assert_eq!(
+        flat.column_by_name("ion_mobility").unwrap().null_count(),
+        flat.num_rows()
+    );
+
+    let projected: Vec<RecordBatch> = reader
+        .flat_table(Some(&["retention_time", "mz"]))?
+        .collect::<Result<_, _>>()?;
+    let names: Vec<&str> = projected[0]
+        .schema_ref()
+        .fields()
+        .iter()
+        .map(|field| field.name().as_str())
+        .collect();
+    assert_eq!(names, ["retention_time", "mz"]);
+    assert_eq!(
+        projected.iter().map(RecordBatch::num_rows).sum::<usize>(),
+        78
+    );
+
+    assert!(matches!(
+        reader.flat_table(Some(&["peak_offset"])),
+        Err(ReaderError::ColumnNotFound(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_flat_table_on_v1_file() -> Result<(), Box<dyn std::error::Error>> {
+    use arrow::record_batch::RecordBatch;
+
+    let dir = tempdir()?;
+    let path = dir.path().join("test.parquet");
+    let mut writer =
+        MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), WriterConfig::default())?;
+    let peaks = PeakArrays::new(vec![400.0, 500.0], vec![1000.0, 10.0]);
+    writer.write_spectrum_arrays(&SpectrumArrays::new_ms1(0, 1, 1.0, 1, peaks))?;
+    writer.finish()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    let batches: Vec<RecordBatch> = reader
+        .flat_table(Some(&["mz", "spectrum_id"]))?
+        .collect::<Result<_, _>>()?;
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batches[0].num_columns(), 2);
+    assert_eq!(batches[0].schema_ref().field(0).name(), "mz");
+    assert_eq!(batches[0].num_rows(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_v1_file_has_no_spectrum_index() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().join("test.parquet");
+    let mut writer = MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), WriterConfig::default())?;
+    let peaks = PeakArrays::new(vec![400.0], vec![1000.0]);
+    writer.write_spectrum_arrays(&SpectrumArrays::new_ms1(0, 1, 1.0, 1, peaks))?;
+    writer.finish()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    assert!(reader.spectrum_location(0)?.is_none());
+    assert_eq!(reader.get_spectrum_arrays(0)?.unwrap().peak_count(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_page_index_prunes_range_queries() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::schema::columns;
+    use parquet::arrow::arrow_reader::{ArrowReaderMetadata, ArrowReaderOptions};
+
+    let dir = tempdir()?;
+    let path = dir.path().join("paged.parquet");
+    // Small pages so each row group holds several
+    let config = WriterConfig {
+        data_page_size: 1024,
+        ..Default::default()
+    };
+    let mut writer = MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), config)?;
+    for i in 0..50 {
+        let mz = (0..100).map(|j| 100.0 + i as f64 * 10.0 + j as f64 * 0.01).collect();
+        let peaks = PeakArrays::new(mz, vec![1.0; 100]);
+        writer.write_spectrum_arrays(&SpectrumArrays::new_ms1(i, i + 1, i as f32, 1, peaks))?;
+    }
+    writer.finish()?;
+
+    let metadata = ArrowReaderMetadata::load(
+        &std::fs::File::open(&path)?,
+        ArrowReaderOptions::new().with_page_index(true),
+    )?;
+    let (_, rt_selection) =
+        page_index::prune_pages(metadata.metadata(), columns::RETENTION_TIME, 10.0, 12.0)
+            .expect("retention time page index");
+    assert!(rt_selection.row_count() < 5000);
+    let (_, mz_selection) = page_index::prune_pages(metadata.metadata(), columns::MZ, 300.0, 300.5)
+        .expect("m/z page index");
+    assert!(mz_selection.row_count() < 5000);
+    let (row_groups, _) =
+        page_index::prune_pages(metadata.metadata(), columns::RETENTION_TIME, 900.0, 1000.0)
+            .expect("retention time page index");
+    assert!(row_groups.is_empty());
+
+    let reader = MzPeakReader::open(&path)?;
+    let spectra = reader.spectra_in_rt_range(RtRange::seconds(10.0, 12.0))?;
+    let ids: Vec<i64> = spectra.iter().map(|s| s.spectrum_id).collect();
+    assert_eq!(ids, vec![10, 11, 12]);
+    assert!(spectra.iter().all(|s| s.peak_count() == 100));
+    assert!(reader
+        .spectra_in_rt_range(RtRange::seconds(900.0, 1000.0))?
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_sample_spectra_v1() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().join("test.parquet");
+    let config = WriterConfig {
+        row_group_size: 8,
+        ..Default::default()
+    };
+    let mut writer = MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), config)?;
+    for i in 0..40 {
+        let peaks = PeakArrays::new(vec![400.0, 500.0, 600.0], vec![1.0, 2.0, 3.0]);
+        let spectrum = if i % 4 == 0 {
+            SpectrumArrays::new_ms1(i, i + 1, i as f32, 1, peaks)
+        } else {
+            SpectrumArrays::new_ms2(i, i + 1, i as f32, 1, 500.0, peaks)
+        };
+        writer.write_spectrum_arrays(&spectrum)?;
+    }
+    writer.finish()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    let sample = reader.sample_spectra(6, 42, None)?;
+    let ids: Vec<i64> = sample.iter().map(|s| s.spectrum_id).collect();
+    assert_eq!(ids.len(), 6);
+    assert!(ids.windows(2).all(|w| w[0] < w[1]));
+    assert!(sample.iter().all(|s| s.peak_count() == 3));
+    let again: Vec<i64> = reader
+        .sample_spectra(6, 42, None)?
+        .iter()
+        .map(|s| s.spectrum_id)
+        .collect();
+    assert_eq!(ids, again);
+
+    let ms1 = reader.sample_spectra(4, 1, Some(1))?;
+    assert_eq!(ms1.len(), 4);
+    assert!(ms1.iter().all(|s| s.ms_level == 1 && s.spectrum_id % 4 == 0));
+    assert_eq!(reader.sample_spectra(100, 1, Some(1))?.len(), 10);
+    assert!(reader.sample_spectra(5, 1, Some(3))?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_sample_spectra_v1_sparse_and_unsorted_ids() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let write = |name: &str, ids: Vec<i64>| -> Result<_, Box<dyn std::error::Error>> {
+        let path = dir.path().join(name);
+        let config = WriterConfig {
+            // Spectra straddle row groups
+            row_group_size: 5,
+            ..Default::default()
+        };
+        let mut writer = MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), config)?;
+        for (i, id) in ids.into_iter().enumerate() {
+            let peaks = PeakArrays::new(vec![400.0, 500.0, 600.0], vec![1.0, 2.0, 3.0]);
+            let spectrum = if i % 2 == 0 {
+                SpectrumArrays::new_ms1(id, id + 1, i as f32, 1, peaks)
+            } else {
+                SpectrumArrays::new_ms2(id, id + 1, i as f32, 1, 500.0, peaks)
+            };
+            writer.write_spectrum_arrays(&spectrum)?;
+        }
+        writer.finish()?;
+        Ok(MzPeakReader::open(&path)?)
+    };
+    let ids = |spectra: Vec<SpectrumArraysView>| -> Vec<i64> {
+        spectra.iter().map(|s| s.spectrum_id).collect()
+    };
+
+    // Every 100th ID: most draws from the ID range miss
+    let sparse = write("sparse.parquet", (0..20).map(|i| i * 100).collect())?;
+    let mut counts = [0usize; 20];
+    for seed in 0..300 {
+        let sample = ids(sparse.sample_spectra(4, seed, None)?);
+        assert_eq!(sample.len(), 4);
+        assert!(sample.windows(2).all(|w| w[0] < w[1]));
+        for id in sample {
+            counts[id as usize / 100] += 1;
+        }
+    }
+    // 60 expected per spectrum
+    assert!(counts.iter().all(|&c| (35..85).contains(&c)), "{:?}", counts);
+    let ms2 = ids(sparse.sample_spectra(3, 5, Some(2))?);
+    assert_eq!(ms2.len(), 3);
+    assert!(ms2.iter().all(|id| id % 200 == 100));
+    assert_eq!(sparse.sample_spectra(50, 5, Some(1))?.len(), 10);
+
+    // Descending IDs list every candidate first
+    let unsorted = write("unsorted.parquet", (0..20).rev().collect())?;
+    let sample = ids(unsorted.sample_spectra(5, 3, None)?);
+    assert_eq!(sample.len(), 5);
+    assert!(sample.windows(2).all(|w| w[0] > w[1]));
+    Ok(())
+}
+
+#[test]
+fn test_sample_spectra_v2() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().join("test.mzpeak");
+    write_v2_dataset(&path)?;
+
+    let reader = MzPeakReader::open(&path)?;
+    let sample = reader.sample_spectra(5, 7, None)?;
+    assert_eq!(sample.len(), 5);
+    for spectrum in &sample {
+        assert_eq!(spectrum.peak_count(), spectrum.spectrum_id as usize + 1);
+    }
+
+    let ms2 = reader.sample_spectra(3, 7, Some(2))?;
+    assert_eq!(ms2.len(), 3);
+    assert!(ms2.iter().all(|s| s.ms_level == 2 && s.spectrum_id % 3 != 0));
+    assert_eq!(reader.sample_spectra(20, 7, Some(1))?.len(), 4);
+    Ok(())
+}
+
+#[test]
+fn test_reader_and_spectrum_to_shared_memory() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().join("test.parquet");
+    let mut writer = MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), WriterConfig::default())?;
+    writer.write_spectrum_arrays(&SpectrumArrays::new_ms1(
+        0,
+        1,
+        60.0,
+        1,
+        PeakArrays::new(vec![400.0, 500.0], vec![1000.0, 2000.0]),
+    ))?;
+    writer.write_spectrum_arrays(&SpectrumArrays::new_ms2(
+        1,
+        2,
+        65.0,
+        1,
+        450.0,
+        PeakArrays::new(vec![200.0, 250.0, 300.0], vec![500.0, 1500.0, 750.0]),
+    ))?;
+    writer.finish()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    let key = format!("reader-{}", uuid::Uuid::new_v4());
+    let segment = reader.to_shared_memory(&key)?;
+    assert_eq!(segment.num_rows(), 5);
+    let rows: usize = open_shared_memory(&key)?.iter().map(|b| b.num_rows()).sum();
+    assert_eq!(rows, 5);
+    drop(segment);
+
+    let spectrum = reader.get_spectrum_arrays(1)?.unwrap();
+    let segment = spectrum.to_shared_memory(&key)?;
+    let batches = open_shared_memory(&key)?;
+    assert_eq!(segment.num_rows(), 3);
+    let schema = batches[0].schema();
+    assert_eq!(schema.metadata()["mzpeak:spectrum_id"], "1");
+    assert_eq!(schema.metadata()["mzpeak:precursor_mz"], "450");
+
+    let persisted = segment.persist();
+    assert!(persisted.exists());
+    remove_shared_memory(&key)?;
+    assert!(!persisted.exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_spectrum_store_reader_matches_in_memory() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().join("store.parquet");
+    let spectra = vec![
+        SpectrumArrays::new_ms1(0, 1, 60.0, 1, PeakArrays::new(vec![400.0, 500.0], vec![10.0, 20.0])),
+        SpectrumArrays::new_ms2(1, 2, 65.0, 1, 450.0, PeakArrays::new(vec![200.0], vec![5.0])),
+        SpectrumArrays::new_ms1(2, 3, 130.0, 1, PeakArrays::new(vec![410.0], vec![30.0])),
+    ];
+    let mut writer = MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), WriterConfig::default())?;
+    for spectrum in &spectra {
+        writer.write_spectrum_arrays(spectrum)?;
+    }
+    writer.finish()?;
+
+    let reader = MzPeakReader::open(&path)?;
+    let memory = InMemorySpectrumStore::new(spectra);
+    let stores: [&dyn SpectrumStore; 2] = [&reader, &memory];
+    for store in stores {
+        let summary = store.summary()?;
+        assert_eq!((summary.num_spectra, summary.num_ms1_spectra, summary.total_peaks), (3, 2, 4));
+        assert_eq!(summary.rt_range, Some((60.0, 130.0)));
+        assert_eq!(store.spectrum_ids()?, vec![0, 1, 2]);
+        assert_eq!(store.get_spectrum(1)?.unwrap().precursor_mz, Some(450.0));
+        assert!(store.get_spectrum(9)?.is_none());
+        assert_eq!(store.iter_spectra()?.count(), 3);
+
+        let ids = |spectra: Vec<SpectrumArrays>| spectra.iter().map(|s| s.spectrum_id).collect::<Vec<_>>();
+        assert_eq!(ids(store.query_rt_range(RtRange::minutes(0.9, 1.5))?), vec![0, 1]);
+        assert_eq!(ids(store.query_ms_level(1)?), vec![0, 2]);
+        assert_eq!(ids(store.query_precursor_mz(449.0, 451.0)?), vec![1]);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_sorted_mz_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::schema::columns;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    // Centroid-like spectra: sorted m/z with irregular spacing, recorded at
+    // single precision
+    let mut state = 1u64;
+    let spectra: Vec<SpectrumArrays> = (0..200)
+        .map(|i| {
+            let mut mz = 100.0;
+            let values = (0..300)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    mz += (state >> 11) as f64 / (1u64 << 53) as f64 * 5.0;
+                    mz as f32 as f64
+                })
+                .collect();
+            let peaks = PeakArrays::new(values, vec![1.0; 300]);
+            SpectrumArrays::new_ms1(i, i + 1, i as f32, 1, peaks)
+        })
+        .collect();
+
+    let dir = tempdir()?;
+    let write = |name: &str, enforce_sorted_mz: bool| -> Result<_, Box<dyn std::error::Error>> {
+        let path = dir.path().join(name);
+        let config = WriterConfig {
+            // Spectra straddle row groups
+            row_group_size: 7_000,
+            enforce_sorted_mz,
+            ..Default::default()
+        };
+        let mut writer = MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), config)?;
+        writer.write_spectra_arrays(&spectra)?;
+        writer.finish()?;
+        Ok(path)
+    };
+    let plain = write("plain.parquet", false)?;
+    let sorted = write("sorted.parquet", true)?;
+    assert!(std::fs::metadata(&sorted)?.len() < std::fs::metadata(&plain)?.len());
+
+    let file = SerializedFileReader::new(std::fs::File::open(&sorted)?)?;
+    let stored: Vec<_> = file
+        .metadata()
+        .file_metadata()
+        .schema_descr()
+        .columns()
+        .iter()
+        .map(|c| c.name().to_string())
+        .collect();
+    assert!(stored.iter().any(|name| name == columns::MZ_DELTA));
+    assert!(!stored.iter().any(|name| name == columns::MZ));
+
+    let reader = MzPeakReader::open(&sorted)?;
+    let batch = reader.iter_batches()?.next().ok_or("no batches")??;
+    assert_eq!(batch.schema().fields(), reader.schema().fields());
+
+    // Exported segments carry the decoded m/z
+    let key = format!("mz-delta-{}", uuid::Uuid::new_v4());
+    let segment = reader.to_shared_memory(&key)?;
+    let exported = open_shared_memory(&key)?;
+    let mz = exported[0]
+        .column_by_name(columns::MZ)
+        .and_then(|c| c.as_any().downcast_ref::<arrow::array::Float64Array>())
+        .ok_or("mz column")?;
+    assert_eq!(mz.values()[..300], spectra[0].peaks.mz[..]);
+    drop(segment);
+
+    let read = reader.iter_spectra_arrays()?;
+    assert_eq!(read.len(), spectra.len());
+    for (read, written) in read.iter().zip(&spectra) {
+        let bits = |mz: &[f64]| mz.iter().map(|v| v.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits(&read.to_owned()?.peaks.mz), bits(&written.peaks.mz));
+    }
+
+    // Pruned reads only cut off spectra outside the query
+    let spectrum = reader.get_spectrum_arrays(150)?.ok_or("spectrum 150")?;
+    assert_eq!(spectrum.peak_count(), 300);
+    let in_range = reader.spectra_in_rt_range(RtRange::seconds(20.0, 22.0))?;
+    assert_eq!(in_range.len(), 3);
+    assert_eq!(in_range[1].to_owned()?.peaks.mz, spectra[21].peaks.mz);
+
+    let report = crate::validator::validate_mzpeak_file(&sorted)?;
+    assert!(!report.has_failures(), "{}", report);
+
+    Ok(())
+}
+
+#[test]
+fn test_mz_range_query_on_sorted_mz() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::schema::columns;
+    use arrow::array::AsArray;
+    use arrow::datatypes::{Float64Type, Int64Type};
+
+    let dir = tempdir()?;
+    let write = |name: &str, enforce_sorted_mz: bool| -> Result<_, Box<dyn std::error::Error>> {
+        let path = dir.path().join(name);
+        let config = WriterConfig {
+            // Spectra straddle row groups
+            row_group_size: 250,
+            data_page_size: 1024,
+            enforce_sorted_mz,
+            ..Default::default()
+        };
+        let mut writer = MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), config)?;
+        for i in 0..50 {
+            let mz = (0..100).map(|j| 100.0 + i as f64 * 10.0 + j as f64 * 0.01).collect();
+            let peaks = PeakArrays::new(mz, vec![1.0; 100]);
+            writer.write_spectrum_arrays(&SpectrumArrays::new_ms1(i, i + 1, i as f32, 1, peaks))?;
+        }
+        writer.finish()?;
+        Ok(MzPeakReader::open(&path)?)
+    };
+    let plain = write("plain.parquet", false)?;
+    let sorted = write("sorted.parquet", true)?;
+
+    // Only spectrum 20 is read, in full
+    let mut ids = Vec::new();
+    for batch in sorted.iter_batches_in_range(columns::MZ, 300.0, 300.5)? {
+        let batch = batch?;
+        let column = batch.column_by_name(columns::SPECTRUM_ID).ok_or("spectrum_id")?;
+        ids.extend_from_slice(column.as_primitive::<Int64Type>().values());
+    }
+    assert_eq!(ids, vec![20; 100]);
+
+    let peaks = |reader: &MzPeakReader| -> Result<Vec<u64>, Box<dyn std::error::Error>> {
+        let mut bits = Vec::new();
+        for batch in reader.peaks_in_mz_range(300.0, 300.5)? {
+            let column = batch.column_by_name(columns::MZ).ok_or("mz")?;
+            let mz = column.as_primitive::<Float64Type>();
+            bits.extend(mz.values().iter().map(|v| v.to_bits()));
+        }
+        Ok(bits)
+    };
+    let expected = peaks(&plain)?;
+    assert_eq!(expected.len(), 51);
+    assert_eq!(peaks(&sorted)?, expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_open_many_virtual_spectrum_ids() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let write = |name: &str, ids: &[i64], rt0: f32| -> Result<_, Box<dyn std::error::Error>> {
+        let path = dir.path().join(name);
+        let mut writer =
+            MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), WriterConfig::default())?;
+        for (i, &id) in ids.iter().enumerate() {
+            let peaks = PeakArrays::new(vec![400.0 + id as f64], vec![1.0]);
+            let rt = rt0 + i as f32;
+            writer.write_spectrum_arrays(&SpectrumArrays::new_ms1(id, id + 1, rt, 1, peaks))?;
+        }
+        writer.finish()?;
+        Ok(path)
+    };
+    // Each segment numbers its spectra independently
+    let first = write("part1.parquet", &[10, 11, 12], 0.0)?;
+    let second = write("part2.parquet", &[0, 1], 100.0)?;
+
+    let run = MzPeakReader::open_many(&[&first, &second])?;
+    assert_eq!(run.num_spectra(), 5);
+    assert_eq!(run.sources()[1].path(), second.as_path());
+    assert_eq!(run.sources()[1].virtual_spectrum_ids(), 3..5);
+    assert_eq!(
+        run.provenance(3),
+        Some(SpectrumProvenance {
+            source: 1,
+            spectrum_id: 0
+        })
+    );
+    assert_eq!(run.provenance(5), None);
+    assert_eq!(run.virtual_spectrum_id(0, 11), Some(1));
+
+    let ids: Vec<i64> = run
+        .iter_spectra()?
+        .map(|s| s.map(|s| s.spectrum_id))
+        .collect::<Result<_, _>>()?;
+    assert_eq!(ids, vec![0, 1, 2, 3, 4]);
+    let spectrum = run.get_spectrum(4)?.ok_or("virtual spectrum 4")?;
+    assert_eq!(spectrum.peaks.mz, vec![401.0]);
+    assert!(run.get_spectrum(5)?.is_none());
+
+    let late = run.query_rt_range(RtRange::seconds(50.0, 200.0))?;
+    assert_eq!(
+        late.iter().map(|s| s.spectrum_id).collect::<Vec<_>>(),
+        vec![3, 4]
+    );
+    let summary = run.summary()?;
+    assert_eq!(summary.num_spectra, 5);
+    assert_eq!(summary.rt_range, Some((0.0, 101.0)));
+
+    let no_paths: [&std::path::Path; 0] = [];
+    assert!(MzPeakReader::open_many(&no_paths).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_reader_transforms() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempdir()?;
+    let path = dir.path().join("test.parquet");
+    let mut writer =
+        MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), WriterConfig::default())?;
+    for i in 0..3 {
+        let peaks = PeakArrays::new(vec![100.0, 200.0], vec![10.0, 20.0]);
+        writer.write_spectrum_arrays(&SpectrumArrays::new_ms1(i, i + 1, i as f32, 1, peaks))?;
+    }
+    writer.finish()?;
+
+    // Registration order: shift first, then scale
+    let config = ReaderConfig::default()
+        .with_transform(|spectrum: &mut SpectrumArrays| {
+            spectrum.peaks.mz.iter_mut().for_each(|mz| *mz += 1.0);
+        })
+        .with_transform(|spectrum: &mut SpectrumArrays| {
+            spectrum.peaks.mz.iter_mut().for_each(|mz| *mz *= 2.0);
+            spectrum.peaks.intensity.iter_mut().for_each(|i| *i *= 0.5);
+        });
+    let reader = MzPeakReader::open_with_config(&path, config)?;
+
+    for view in reader.iter_spectra_arrays()? {
+        assert_eq!(view.mz_arrays()?[0].values().to_vec(), vec![202.0, 402.0]);
+        assert_eq!(
+            view.intensity_arrays()?[0].values().to_vec(),
+            vec![5.0, 10.0]
+        );
+    }
+    let spectrum = reader
+        .get_spectrum_arrays(1)?
+        .ok_or("spectrum 1")?
+        .to_owned()?;
+    assert_eq!(spectrum.spectrum_id, 1);
+    assert_eq!(spectrum.peaks.mz, vec![202.0, 402.0]);
+    let in_range = reader.spectra_in_rt_range(RtRange::seconds(2.0, 2.0))?;
+    assert_eq!(in_range.len(), 1);
+    assert_eq!(in_range[0].to_owned()?.peaks.intensity, vec![5.0, 10.0]);
+    let stored = MzPeakReader::open(&path)?
+        .get_spectrum_arrays(1)?
+        .ok_or("spectrum 1")?;
+    assert_eq!(stored.to_owned()?.peaks.mz, vec![100.0, 200.0]);
+
+    Ok(())
+}
+
+#[test]
+fn test_container_safety_checks() -> Result<(), Box<dyn std::error::Error>> {
+    use crate::dataset::MzPeakDatasetWriter;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    let dir = tempdir()?;
+    let path = dir.path().join("test.mzpeak");
+    let mut dataset =
+        MzPeakDatasetWriter::new(&path, &MzPeakMetadata::new(), WriterConfig::default())?;
+    let peaks = PeakArrays::new(vec![400.0], vec![1000.0]);
+    dataset.write_spectrum_arrays(&SpectrumArrays::new_ms1(0, 1, 0.0, 1, peaks))?;
+    dataset.close()?;
+    assert!(MzPeakReader::open(&path)?.read_metadata_json()?.is_some());
+
+    // Sub-files read into memory are bounded
+    let config = ReaderConfig {
+        container_limits: ContainerLimits {
+            max_json_bytes: 16,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let reader = MzPeakReader::open_with_config(&path, config)?;
+    assert!(matches!(
+        reader.read_metadata_json(),
+        Err(ReaderError::Security(
+            ContainerSecurityError::EntryTooLarge { .. }
+        ))
+    ));
+
+    let config = ReaderConfig {
+        container_limits: ContainerLimits {
+            max_entries: 2,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert!(matches!(
+        MzPeakReader::open_with_config(&path, config),
+        Err(ReaderError::Security(
+            ContainerSecurityError::TooManyEntries { .. }
+        ))
+    ));
+
+    // A copy of the container with a zip-slip entry is rejected
+    let hostile = dir.path().join("hostile.mzpeak");
+    let mut source = zip::ZipArchive::new(std::fs::File::open(&path)?)?;
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(&hostile)?);
+    for i in 0..source.len() {
+        zip.raw_copy_file(source.by_index_raw(i)?)?;
+    }
+    zip.start_file("../../evil.sh", SimpleFileOptions::default())?;
+    zip.write_all(b"#!/bin/sh\n")?;
+    zip.finish()?;
+    assert!(matches!(
+        MzPeakReader::open(&hostile),
+        Err(ReaderError::Security(
+            ContainerSecurityError::UnsafeEntryPath(_)
+        ))
+    ));
+
+    for name in ["/etc/passwd", "C:/evil", "a\\..\\b", "peaks/../../x", ""] {
+        assert!(check_entry_path(name).is_err(), "{}", name);
+    }
+    assert!(check_entry_path("attachments/method.pdf").is_ok());
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+//! Read-time spectrum transforms
+//!
+//! Corrections such as mass recalibration or intensity normalization can be
+//! applied while reading instead of rewriting the container. Transforms
+//! registered with [`ReaderConfig::with_transform`] run, in registration
+//! order, on every spectrum the reader returns:
+//!
+//! - Views ([`SpectrumArraysView`]) are rebuilt from the transformed spectrum,
+//!   so `mz_arrays()`, `to_owned()` and the metadata fields agree.
+//! - Lookups and range queries select spectra by their stored values; only
+//!   the spectra returned are transformed.
+//! - Batch-level APIs (`iter_batches`, SQL queries, shared memory export of
+//!   the whole file) return stored values.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use mzpeak::reader::{MzPeakReader, ReaderConfig};
+//! use mzpeak::writer::SpectrumArrays;
+//!
+//! // Shift every m/z by +2.5 ppm
+//! let config = ReaderConfig::default().with_transform(|spectrum: &mut SpectrumArrays| {
+//!     for mz in &mut spectrum.peaks.mz {
+//!         *mz *= 1.0 + 2.5e-6;
+//!     }
+//! });
+//! let reader = MzPeakReader::open_with_config("data.mzpeak", config)?;
+//! # Ok::<(), mzpeak::reader::ReaderError>(())
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+
+use super::spectra::SpectrumArraysView;
+use super::{ReaderConfig, ReaderError};
+use crate::writer::SpectrumArrays;
+
+/// Correction applied to each spectrum as it is read
+///
+/// Implemented for any `Fn(&mut SpectrumArrays) + Send + Sync` closure.
+pub trait SpectrumTransform: Send + Sync {
+    /// Modify `spectrum` in place
+    fn apply(&self, spectrum: &mut SpectrumArrays);
+}
+
+impl<F> SpectrumTransform for F
+where
+    F: Fn(&mut SpectrumArrays) + Send + Sync,
+{
+    fn apply(&self, spectrum: &mut SpectrumArrays) {
+        self(spectrum)
+    }
+}
+
+/// Ordered list of transforms held by a [`ReaderConfig`]
+#[derive(Clone, Default)]
+pub struct SpectrumTransforms(Vec<Arc<dyn SpectrumTransform>>);
+
+impl SpectrumTransforms {
+    /// Append a transform
+    pub fn push(&mut self, transform: impl SpectrumTransform + 'static) {
+        self.0.push(Arc::new(transform));
+    }
+
+    /// Number of transforms
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether no transforms are registered
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Apply every transform, in registration order
+    pub fn apply(&self, spectrum: &mut SpectrumArrays) {
+        for transform in &self.0 {
+            transform.apply(spectrum);
+        }
+    }
+
+    /// Transformed copy of a view; the view itself when there is nothing to apply
+    pub(crate) fn apply_view(
+        &self,
+        view: SpectrumArraysView,
+    ) -> Result<SpectrumArraysView, ReaderError> {
+        if self.is_empty() {
+            return Ok(view);
+        }
+        let mut spectrum = view.to_owned()?;
+        self.apply(&mut spectrum);
+        SpectrumArraysView::from_owned(&spectrum)
+    }
+}
+
+impl fmt::Debug for SpectrumTransforms {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpectrumTransforms")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+impl ReaderConfig {
+    /// Register a transform applied to every spectrum the reader returns
+    ///
+    /// Transforms run in the order they are registered.
+    pub fn with_transform(mut self, transform: impl SpectrumTransform + 'static) -> Self {
+        self.transforms.push(transform);
+        self
+    }
+}
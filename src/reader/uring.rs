@@ -0,0 +1,280 @@
+//! io_uring batch reads (Linux, `uring` feature)
+//!
+//! Each thread lazily creates one small ring and reuses it for every batch.
+//! Kernels or sandboxes that refuse `io_uring_setup` are detected once, after
+//! which callers fall back to plain positioned reads.
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use io_uring::{opcode, types, IoUring};
+
+/// Submission queue depth of the per-thread ring
+const RING_ENTRIES: u32 = 64;
+
+/// Consecutive failed waits tolerated while reads are in flight
+const MAX_FAILED_WAITS: usize = 1000;
+
+/// Set once ring creation has failed on this system
+static UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static RING: RefCell<Option<Ring>> = const { RefCell::new(None) };
+}
+
+/// Per-thread ring and the tag of its last batch
+///
+/// The tag is stored in the upper half of every `user_data`, so a completion
+/// can only ever be credited to the batch that submitted it.
+struct Ring {
+    uring: IoUring,
+    tag: u32,
+}
+
+impl Ring {
+    fn next_tag(&mut self) -> u32 {
+        self.tag = self.tag.wrapping_add(1);
+        self.tag
+    }
+}
+
+fn user_data(tag: u32, index: usize) -> u64 {
+    (u64::from(tag) << 32) | index as u64
+}
+
+/// Whether rings can be created on this system
+///
+/// Creates and drops a probe ring once; readers fall back to positioned reads
+/// when this is `false`.
+pub fn is_available() -> bool {
+    if UNAVAILABLE.load(Ordering::Relaxed) {
+        return false;
+    }
+    let available = IoUring::new(RING_ENTRIES).is_ok();
+    if !available {
+        UNAVAILABLE.store(true, Ordering::Relaxed);
+    }
+    available
+}
+
+/// One pending read: file offset and destination buffer
+struct Pending<'a> {
+    offset: u64,
+    buf: &'a mut [u8],
+}
+
+/// Fill every buffer from its file offset, submitting up to
+/// [`RING_ENTRIES`] reads per `io_uring_enter`
+///
+/// Returns `ErrorKind::Unsupported` if io_uring is not available, in which
+/// case nothing has been read. On any other error every submitted read has
+/// completed before this returns, so the kernel no longer writes into the
+/// buffers.
+pub(super) fn read_batch(file: &File, requests: &mut [(u64, &mut [u8])]) -> io::Result<()> {
+    if UNAVAILABLE.load(Ordering::Relaxed) {
+        return Err(io::ErrorKind::Unsupported.into());
+    }
+
+    RING.with(|cell| {
+        let mut cell = cell.borrow_mut();
+        if cell.is_none() {
+            match IoUring::new(RING_ENTRIES) {
+                Ok(uring) => *cell = Some(Ring { uring, tag: 0 }),
+                Err(_) => {
+                    UNAVAILABLE.store(true, Ordering::Relaxed);
+                    return Err(io::ErrorKind::Unsupported.into());
+                }
+            }
+        }
+
+        let mut pending: Vec<Pending<'_>> = requests
+            .iter_mut()
+            .filter(|(_, buf)| !buf.is_empty())
+            .map(|(offset, buf)| Pending {
+                offset: *offset,
+                buf: &mut buf[..],
+            })
+            .collect();
+        let fd = types::Fd(file.as_raw_fd());
+
+        while !pending.is_empty() {
+            let Some(ring) = cell.as_mut() else {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "io_uring ring was dropped",
+                ));
+            };
+            let tag = ring.next_tag();
+            let batch = pending.len().min(RING_ENTRIES as usize);
+            let mut pushed = 0;
+            let mut push_error = None;
+            for (index, request) in pending[..batch].iter_mut().enumerate() {
+                let len = request.buf.len().min(u32::MAX as usize) as u32;
+                let entry = opcode::Read::new(fd, request.buf.as_mut_ptr(), len)
+                    .offset(request.offset)
+                    .build()
+                    .user_data(user_data(tag, index));
+                // SAFETY: the buffer outlives the read because `complete`
+                // waits for every pushed entry before the buffers are touched
+                // again or released.
+                if unsafe { ring.uring.submission().push(&entry) }.is_err() {
+                    push_error = Some(io::Error::new(
+                        io::ErrorKind::Other,
+                        "io_uring submission queue full",
+                    ));
+                    break;
+                }
+                pushed += 1;
+            }
+
+            let mut results = vec![0i32; pushed];
+            match complete(&mut ring.uring, tag, &mut results) {
+                Completion::Done => {}
+                Completion::Failed(error) => return Err(error),
+                Completion::NeverSubmitted(error) => {
+                    // Dropping the ring discards the entries it still holds
+                    *cell = None;
+                    return Err(error);
+                }
+            }
+            if let Some(error) = push_error {
+                return Err(error);
+            }
+            if let Some(&result) = results.iter().find(|&&result| result < 0) {
+                return Err(io::Error::from_raw_os_error(-result));
+            }
+
+            // Short reads are resubmitted for their remainder
+            let mut remaining = Vec::with_capacity(pending.len());
+            for (index, request) in pending.drain(..).enumerate() {
+                if index >= pushed {
+                    remaining.push(request);
+                    continue;
+                }
+                let read = results[index] as usize;
+                if read == request.buf.len() {
+                    continue;
+                }
+                if read == 0 {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+                let Pending { offset, buf } = request;
+                remaining.push(Pending {
+                    offset: offset + read as u64,
+                    buf: &mut buf[read..],
+                });
+            }
+            pending = remaining;
+        }
+        Ok(())
+    })
+}
+
+/// Outcome of waiting for a batch
+enum Completion {
+    /// Every read completed; results are in place
+    Done,
+    /// `io_uring_enter` failed after the kernel took some entries, all of
+    /// which have completed since
+    Failed(io::Error),
+    /// `io_uring_enter` failed before the kernel took any entry; they are
+    /// still queued and the ring must be dropped
+    NeverSubmitted(io::Error),
+}
+
+/// Submit the queued entries of batch `tag` and reap one completion per
+/// entry into `results`
+///
+/// Completions carrying another tag are left-overs and are skipped. This
+/// only returns once no read of the batch can still write into its buffer:
+/// if waiting keeps failing while reads are in flight, the process is
+/// aborted rather than releasing memory the kernel may write to.
+fn complete(uring: &mut IoUring, tag: u32, results: &mut [i32]) -> Completion {
+    let mut outstanding = results.len();
+    let mut error = None;
+    let mut failed_waits = 0;
+    while outstanding > 0 {
+        if let Some(cqe) = uring.completion().next() {
+            let data = cqe.user_data();
+            let index = (data & u64::from(u32::MAX)) as usize;
+            if data >> 32 == u64::from(tag) && index < results.len() {
+                results[index] = cqe.result();
+                outstanding -= 1;
+            }
+            continue;
+        }
+        match submit_and_wait(uring, outstanding) {
+            Ok(()) => failed_waits = 0,
+            Err(e) => {
+                if uring.submission().len() == outstanding {
+                    // Nothing reached the kernel, so nothing is in flight
+                    return Completion::NeverSubmitted(e);
+                }
+                failed_waits += 1;
+                if failed_waits > MAX_FAILED_WAITS {
+                    log::error!("io_uring reads still in flight after {}: aborting", e);
+                    std::process::abort();
+                }
+                error.get_or_insert(e);
+                std::thread::yield_now();
+            }
+        }
+    }
+    match error {
+        Some(error) => Completion::Failed(error),
+        None => Completion::Done,
+    }
+}
+
+/// `submit_and_wait`, retried when interrupted by a signal
+fn submit_and_wait(ring: &IoUring, want: usize) -> io::Result<()> {
+    loop {
+        match ring.submit_and_wait(want) {
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            result => return result.map(|_| ()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_batches_ignore_foreign_completions() -> io::Result<()> {
+        if !is_available() {
+            return Ok(());
+        }
+        let mut file = tempfile::NamedTempFile::new()?;
+        file.write_all(&[7u8; 4096])?;
+        file.flush()?;
+
+        // A failed batch must leave nothing behind for the next one
+        let write_only = std::fs::OpenOptions::new().write(true).open(file.path())?;
+        let mut buf = [0u8; 16];
+        assert!(read_batch(&write_only, &mut [(0, &mut buf[..])]).is_err());
+
+        // Completion of an unrelated entry still sitting in the queue
+        RING.with(|cell| {
+            let mut cell = cell.borrow_mut();
+            let ring = cell.as_mut().expect("ring created by the first batch");
+            let nop = opcode::Nop::new().build().user_data(user_data(0, 5));
+            // SAFETY: a no-op references no memory
+            unsafe { ring.uring.submission().push(&nop) }
+                .map_err(|_| io::Error::new(io::ErrorKind::Other, "queue full"))?;
+            submit_and_wait(&ring.uring, 1)
+        })?;
+
+        let (mut first, mut second) = ([0u8; 100], [0u8; 10]);
+        read_batch(
+            file.as_file(),
+            &mut [(0, &mut first[..]), (4000, &mut second[..])],
+        )?;
+        assert!(first.iter().chain(second.iter()).all(|&byte| byte == 7));
+        Ok(())
+    }
+}
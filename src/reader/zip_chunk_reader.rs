@@ -10,13 +10,15 @@
 //! This is required by the mzPeak format specification to enable random access.
 
 use std::fs::File;
-use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::io::BufReader;
 use std::path::Path;
+use std::sync::Arc;
 
 use bytes::Bytes;
 use parquet::file::reader::{ChunkReader, Length};
 use zip::ZipArchive;
 
+use super::positioned::{IoBackend, PositionedReader, PositionedSliceReader};
 use super::ReaderError;
 
 /// Zero-copy reader for stored ZIP entries
@@ -26,16 +28,14 @@ use super::ReaderError;
 ///
 /// # Thread Safety
 ///
-/// This struct uses interior mutability via `Mutex` to allow concurrent reads
-/// from different file handles (via `try_clone`), while the logical offset/size
-/// remain immutable.
+/// The container is opened once and shared; all reads are positioned reads
+/// (see [`PositionedReader`]), so concurrent readers never share a file
+/// cursor.
 pub struct ZipEntryChunkReader {
-    /// Path to the ZIP file (used for try_clone)
+    /// Path to the ZIP file (for error messages)
     zip_path: std::path::PathBuf,
-    /// Byte offset of entry data within ZIP
-    entry_offset: u64,
-    /// Size of uncompressed entry
-    entry_size: u64,
+    /// Positioned reader over the entry data
+    inner: PositionedReader,
 }
 
 impl ZipEntryChunkReader {
@@ -59,13 +59,35 @@ impl ZipEntryChunkReader {
     /// # Ok::<(), mzpeak::reader::ReaderError>(())
     /// ```
     pub fn new<P: AsRef<Path>>(zip_path: P, entry_name: &str) -> Result<Self, ReaderError> {
+        Self::with_backend(zip_path, entry_name, IoBackend::default())
+    }
+
+    /// Create a chunk reader for a stored ZIP entry using a specific I/O backend
+    pub fn with_backend<P: AsRef<Path>>(
+        zip_path: P,
+        entry_name: &str,
+        backend: IoBackend,
+    ) -> Result<Self, ReaderError> {
+        Self::open_optional(zip_path, entry_name, backend)?.ok_or_else(|| {
+            ReaderError::InvalidFormat(format!("ZIP container missing {}", entry_name))
+        })
+    }
+
+    /// Like [`with_backend`](Self::with_backend), but returns `None` if the
+    /// container has no such entry
+    pub(super) fn open_optional<P: AsRef<Path>>(
+        zip_path: P,
+        entry_name: &str,
+        backend: IoBackend,
+    ) -> Result<Option<Self>, ReaderError> {
         let zip_path = zip_path.as_ref();
         let file = File::open(zip_path)?;
-        let mut archive = ZipArchive::new(BufReader::new(file))?;
+        let mut archive = ZipArchive::new(BufReader::new(file.try_clone()?))?;
 
-        let entry = archive.by_name(entry_name).map_err(|_| {
-            ReaderError::InvalidFormat(format!("ZIP container missing {}", entry_name))
-        })?;
+        let entry = match archive.by_name(entry_name) {
+            Ok(entry) => entry,
+            Err(_) => return Ok(None),
+        };
 
         // Verify entry is Stored (uncompressed) for direct seeking
         if entry.compression() != zip::CompressionMethod::Stored {
@@ -80,21 +102,32 @@ impl ZipEntryChunkReader {
         let entry_offset = entry.data_start();
         let entry_size = entry.size();
 
-        Ok(Self {
+        Ok(Some(Self {
             zip_path: zip_path.to_path_buf(),
-            entry_offset,
-            entry_size,
-        })
+            inner: PositionedReader::from_file(Arc::new(file), entry_offset, entry_size, backend),
+        }))
+    }
+
+    /// Positioned reader over the entry data
+    pub(super) fn into_positioned(self) -> PositionedReader {
+        self.inner
     }
 
     /// Returns the size of the entry in bytes
     pub fn entry_size(&self) -> u64 {
-        self.entry_size
+        Length::len(&self.inner)
     }
 
     /// Returns the byte offset of the entry within the ZIP file
     pub fn entry_offset(&self) -> u64 {
-        self.entry_offset
+        self.inner.offset()
+    }
+
+    /// Read several ranges of the entry in one batch
+    ///
+    /// See [`PositionedReader::read_ranges`].
+    pub fn read_ranges(&self, ranges: &[(u64, usize)]) -> std::io::Result<Vec<Bytes>> {
+        self.inner.read_ranges(ranges)
     }
 }
 
@@ -102,99 +135,36 @@ impl std::fmt::Debug for ZipEntryChunkReader {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ZipEntryChunkReader")
             .field("zip_path", &self.zip_path)
-            .field("entry_offset", &self.entry_offset)
-            .field("entry_size", &self.entry_size)
+            .field("entry_offset", &self.entry_offset())
+            .field("entry_size", &self.entry_size())
+            .field("backend", &self.inner.backend())
             .finish()
     }
 }
 
 impl Length for ZipEntryChunkReader {
     fn len(&self) -> u64 {
-        self.entry_size
+        self.entry_size()
     }
 }
 
 /// A reader for a slice of a ZIP entry
 ///
-/// This wraps a file handle positioned at the correct offset within the ZIP
-/// and limits reads to not exceed the entry boundary.
-pub struct ZipEntrySliceReader {
-    /// The underlying file handle
-    file: File,
-    /// Current position within the logical slice
-    position: u64,
-    /// Maximum position (entry_size - start offset)
-    max_len: u64,
-}
-
-impl Read for ZipEntrySliceReader {
-    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        let remaining = self.max_len.saturating_sub(self.position);
-        if remaining == 0 {
-            return Ok(0);
-        }
-
-        let to_read = std::cmp::min(buf.len() as u64, remaining) as usize;
-        let n = self.file.read(&mut buf[..to_read])?;
-        self.position += n as u64;
-        Ok(n)
-    }
-}
+/// Reads are limited to the entry boundary.
+pub type ZipEntrySliceReader = PositionedSliceReader;
 
 impl ChunkReader for ZipEntryChunkReader {
     type T = ZipEntrySliceReader;
 
     fn get_read(&self, start: u64) -> parquet::errors::Result<Self::T> {
-        let mut file = File::open(&self.zip_path).map_err(|e| {
-            parquet::errors::ParquetError::General(format!("Failed to open ZIP file: {}", e))
-        })?;
-
-        // Seek to the correct position within the entry
-        file.seek(SeekFrom::Start(self.entry_offset + start))
-            .map_err(|e| {
-                parquet::errors::ParquetError::General(format!("Failed to seek in ZIP: {}", e))
-            })?;
-
-        let max_len = self.entry_size.saturating_sub(start);
-
-        Ok(ZipEntrySliceReader {
-            file,
-            position: 0,
-            max_len,
-        })
+        self.inner.get_read(start)
     }
 
     fn get_bytes(&self, start: u64, length: usize) -> parquet::errors::Result<Bytes> {
-        let mut file = File::open(&self.zip_path).map_err(|e| {
-            parquet::errors::ParquetError::General(format!("Failed to open ZIP file: {}", e))
-        })?;
-
-        // Seek to the correct position within the entry
-        file.seek(SeekFrom::Start(self.entry_offset + start))
-            .map_err(|e| {
-                parquet::errors::ParquetError::General(format!("Failed to seek in ZIP: {}", e))
-            })?;
-
-        // Clamp length to not exceed entry boundary
-        let remaining = self.entry_size.saturating_sub(start) as usize;
-        let actual_length = std::cmp::min(length, remaining);
-
-        let mut buf = vec![0u8; actual_length];
-        file.read_exact(&mut buf).map_err(|e| {
-            parquet::errors::ParquetError::General(format!("Failed to read from ZIP: {}", e))
-        })?;
-
-        Ok(Bytes::from(buf))
+        self.inner.get_bytes(start, length)
     }
 }
 
-// SAFETY: ZipEntryChunkReader is Send + Sync because:
-// - zip_path is PathBuf (Send + Sync)
-// - entry_offset and entry_size are u64 (Send + Sync)
-// - Each method call opens a new file handle, so there's no shared mutable state
-unsafe impl Send for ZipEntryChunkReader {}
-unsafe impl Sync for ZipEntryChunkReader {}
-
 /// Arc-wrapped ZipEntryChunkReader for sharing across threads
 ///
 /// This is a newtype wrapper that implements ChunkReader, working around
@@ -216,7 +186,7 @@ impl SharedZipEntryReader {
 
 impl Length for SharedZipEntryReader {
     fn len(&self) -> u64 {
-        self.0.entry_size
+        self.0.entry_size()
     }
 }
 
@@ -235,7 +205,7 @@ impl ChunkReader for SharedZipEntryReader {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Write;
+    use std::io::{Read, Write};
     use tempfile::NamedTempFile;
     use zip::write::SimpleFileOptions;
     use zip::ZipWriter;
@@ -0,0 +1,428 @@
+//! # Scan Diagnostics Writer Module
+//!
+//! This module provides functionality for writing per-scan instrument
+//! diagnostics (e.g. Thermo trailer extras such as "Ion Injection Time (ms)",
+//! "FT Resolution" or "Elapsed Scan Time (sec)") to a "Long" key-value table.
+//!
+//! Vendors report dozens of such values per scan, and the set differs between
+//! instrument models and firmware versions. Rather than growing a dedicated
+//! column for each one, every value is stored as one row keyed by spectrum,
+//! so nothing reported by the instrument is lost. The original text is kept
+//! verbatim; values that parse as numbers are also stored in `numeric_value`
+//! for direct filtering and plotting.
+//!
+//! In v2 containers the table is stored as `diagnostics/scan_diagnostics.parquet`.
+//!
+//! ## Schema Columns
+//!
+//! | Column | Type | Description |
+//! |--------|------|-------------|
+//! | spectrum_id | Int64 | Spectrum the value belongs to |
+//! | key | Utf8 | Vendor label, e.g. `Ion Injection Time (ms)` |
+//! | value | Utf8 | Value as reported by the vendor |
+//! | numeric_value | Float64 (nullable) | `value` parsed as a number, if it is one |
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Float64Builder, Int64Builder, StringBuilder};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::{EnabledStatistics, WriterProperties};
+use parquet::format::KeyValue;
+
+use crate::metadata::MzPeakMetadata;
+use crate::schema::column_metadata::{append_column_key_values, field_with_cv};
+use crate::schema::{KEY_FORMAT_VERSION, MZPEAK_FORMAT_VERSION};
+
+/// Path of the scan diagnostics table inside a dataset
+pub const SCAN_DIAGNOSTICS_PATH: &str = "diagnostics/scan_diagnostics.parquet";
+
+/// Column names for scan diagnostics schema
+pub mod scan_diagnostics_columns {
+    /// Spectrum the value belongs to
+    pub const SPECTRUM_ID: &str = "spectrum_id";
+    /// Vendor label of the value
+    pub const KEY: &str = "key";
+    /// Value text as reported by the vendor
+    pub const VALUE: &str = "value";
+    /// Value parsed as a number, null if it is not numeric
+    pub const NUMERIC_VALUE: &str = "numeric_value";
+}
+
+/// Creates the scan diagnostics Arrow schema for the "Long" key-value format.
+///
+/// # Example
+///
+/// ```
+/// use mzpeak::scan_diagnostics_writer::create_scan_diagnostics_schema;
+///
+/// let schema = create_scan_diagnostics_schema();
+/// assert_eq!(schema.fields().len(), 4);
+/// ```
+pub fn create_scan_diagnostics_schema() -> Schema {
+    let fields = vec![
+        field_with_cv(
+            scan_diagnostics_columns::SPECTRUM_ID,
+            DataType::Int64,
+            false,
+            "MS:1000796",
+        ),
+        Field::new(scan_diagnostics_columns::KEY, DataType::Utf8, false),
+        Field::new(scan_diagnostics_columns::VALUE, DataType::Utf8, false),
+        Field::new(scan_diagnostics_columns::NUMERIC_VALUE, DataType::Float64, true),
+    ];
+
+    let mut metadata = HashMap::new();
+    metadata.insert(KEY_FORMAT_VERSION.to_string(), MZPEAK_FORMAT_VERSION.to_string());
+    metadata.insert(
+        "mzpeak:schema_description".to_string(),
+        "Long-format per-scan instrument diagnostics as key-value pairs".to_string(),
+    );
+
+    Schema::new(fields).with_metadata(metadata)
+}
+
+/// Returns an Arc-wrapped scan diagnostics schema for shared ownership
+pub fn create_scan_diagnostics_schema_arc() -> Arc<Schema> {
+    Arc::new(create_scan_diagnostics_schema())
+}
+
+/// Errors that can occur during scan diagnostics writing
+#[derive(Debug, thiserror::Error)]
+pub enum ScanDiagnosticsWriterError {
+    /// I/O error
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    /// Arrow error
+    #[error("Arrow error: {0}")]
+    ArrowError(#[from] arrow::error::ArrowError),
+
+    /// Parquet error
+    #[error("Parquet error: {0}")]
+    ParquetError(#[from] parquet::errors::ParquetError),
+
+    /// Metadata error
+    #[error("Metadata error: {0}")]
+    MetadataError(#[from] crate::metadata::MetadataError),
+}
+
+/// Configuration for the scan diagnostics writer
+#[derive(Debug, Clone)]
+pub struct ScanDiagnosticsWriterConfig {
+    /// Compression level (ZSTD, 1-22, default 3)
+    pub compression_level: i32,
+
+    /// Target row group size (rows are single key-value pairs)
+    pub row_group_size: usize,
+
+    /// Data page size in bytes
+    pub data_page_size: usize,
+
+    /// Whether to write statistics for columns
+    pub write_statistics: bool,
+}
+
+impl Default for ScanDiagnosticsWriterConfig {
+    fn default() -> Self {
+        Self {
+            compression_level: 3,
+            row_group_size: 1024 * 1024,
+            data_page_size: 1024 * 1024,
+            write_statistics: true,
+        }
+    }
+}
+
+impl ScanDiagnosticsWriterConfig {
+    /// Create writer properties from this configuration
+    fn to_writer_properties(&self, metadata: &HashMap<String, String>) -> WriterProperties {
+        let compression = Compression::ZSTD(
+            ZstdLevel::try_new(self.compression_level).unwrap_or_default(),
+        );
+
+        let statistics = if self.write_statistics {
+            EnabledStatistics::Chunk
+        } else {
+            EnabledStatistics::None
+        };
+
+        // Keys repeat for every scan, so the default dictionary encoding
+        // reduces the key column to a few bytes per row group.
+        let kv_metadata: Vec<KeyValue> = metadata
+            .iter()
+            .map(|(k, v)| KeyValue {
+                key: k.clone(),
+                value: Some(v.clone()),
+            })
+            .collect();
+
+        WriterProperties::builder()
+            .set_compression(compression)
+            .set_data_page_size_limit(self.data_page_size)
+            .set_statistics_enabled(statistics)
+            .set_max_row_group_size(self.row_group_size)
+            .set_key_value_metadata(Some(kv_metadata))
+            .build()
+    }
+}
+
+/// Diagnostic values reported by the instrument for one scan
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScanDiagnostics {
+    /// Spectrum the values belong to
+    pub spectrum_id: i64,
+
+    /// `(key, value)` pairs in vendor order
+    pub entries: Vec<(String, String)>,
+}
+
+impl ScanDiagnostics {
+    /// Create an empty set of diagnostics for a spectrum
+    pub fn new(spectrum_id: i64) -> Self {
+        Self {
+            spectrum_id,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Append a key-value pair
+    pub fn push(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.entries.push((key.into(), value.into()));
+    }
+
+    /// Value of the first entry with `key`
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Value of the first entry with `key`, parsed as a number
+    pub fn get_numeric(&self, key: &str) -> Option<f64> {
+        self.get(key).and_then(parse_numeric)
+    }
+
+    /// Number of key-value pairs
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no key-value pairs
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Parse a diagnostic value as a finite number
+pub(crate) fn parse_numeric(value: &str) -> Option<f64> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+}
+
+/// Streaming writer for scan diagnostics Parquet files
+pub struct ScanDiagnosticsWriter<W: Write + Send> {
+    writer: ArrowWriter<W>,
+    schema: Arc<Schema>,
+    scans_written: usize,
+    entries_written: usize,
+}
+
+impl ScanDiagnosticsWriter<File> {
+    /// Create a new writer to a file path
+    pub fn new_file<P: AsRef<Path>>(
+        path: P,
+        metadata: &MzPeakMetadata,
+        config: ScanDiagnosticsWriterConfig,
+    ) -> Result<Self, ScanDiagnosticsWriterError> {
+        let file = File::create(path)?;
+        Self::new(file, metadata, config)
+    }
+}
+
+impl<W: Write + Send> ScanDiagnosticsWriter<W> {
+    /// Create a new writer to any Write implementation
+    pub fn new(
+        writer: W,
+        metadata: &MzPeakMetadata,
+        config: ScanDiagnosticsWriterConfig,
+    ) -> Result<Self, ScanDiagnosticsWriterError> {
+        let schema = create_scan_diagnostics_schema_arc();
+        let parquet_metadata = metadata.to_parquet_metadata()?;
+        let props = config.to_writer_properties(&parquet_metadata);
+
+        let mut arrow_writer = ArrowWriter::try_new(writer, schema.clone(), Some(props))?;
+        append_column_key_values(&mut arrow_writer, &schema);
+
+        Ok(Self {
+            writer: arrow_writer,
+            schema,
+            scans_written: 0,
+            entries_written: 0,
+        })
+    }
+
+    /// Write diagnostics for a batch of scans
+    pub fn write_scans(&mut self, scans: &[ScanDiagnostics]) -> Result<(), ScanDiagnosticsWriterError> {
+        let rows: usize = scans.iter().map(ScanDiagnostics::len).sum();
+        if rows == 0 {
+            self.scans_written += scans.len();
+            return Ok(());
+        }
+
+        let mut spectrum_id_builder = Int64Builder::with_capacity(rows);
+        let mut key_builder = StringBuilder::new();
+        let mut value_builder = StringBuilder::new();
+        let mut numeric_builder = Float64Builder::with_capacity(rows);
+
+        for scan in scans {
+            for (key, value) in &scan.entries {
+                spectrum_id_builder.append_value(scan.spectrum_id);
+                key_builder.append_value(key);
+                value_builder.append_value(value);
+                numeric_builder.append_option(parse_numeric(value));
+            }
+        }
+
+        let arrays: Vec<ArrayRef> = vec![
+            Arc::new(spectrum_id_builder.finish()),
+            Arc::new(key_builder.finish()),
+            Arc::new(value_builder.finish()),
+            Arc::new(numeric_builder.finish()),
+        ];
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays)?;
+        self.writer.write(&batch)?;
+
+        self.scans_written += scans.len();
+        self.entries_written += rows;
+
+        Ok(())
+    }
+
+    /// Write diagnostics for a single scan
+    pub fn write_scan(&mut self, scan: &ScanDiagnostics) -> Result<(), ScanDiagnosticsWriterError> {
+        self.write_scans(std::slice::from_ref(scan))
+    }
+
+    /// Flush any buffered data and finalize the file
+    pub fn finish(self) -> Result<ScanDiagnosticsWriterStats, ScanDiagnosticsWriterError> {
+        let file_metadata = self.writer.close()?;
+
+        Ok(ScanDiagnosticsWriterStats {
+            scans_written: self.scans_written,
+            entries_written: self.entries_written,
+            row_groups_written: file_metadata.row_groups.len(),
+            file_size_bytes: file_metadata
+                .row_groups
+                .iter()
+                .map(|rg| rg.total_byte_size as u64)
+                .sum(),
+        })
+    }
+
+    /// Flush any buffered data, finalize the file, and return the underlying writer
+    pub fn finish_into_inner(self) -> Result<W, ScanDiagnosticsWriterError> {
+        let inner = self.writer.into_inner()?;
+        Ok(inner)
+    }
+
+    /// Get current statistics
+    pub fn stats(&self) -> ScanDiagnosticsWriterStats {
+        ScanDiagnosticsWriterStats {
+            scans_written: self.scans_written,
+            entries_written: self.entries_written,
+            row_groups_written: 0,
+            file_size_bytes: 0,
+        }
+    }
+}
+
+/// Statistics from a completed scan diagnostics write operation
+#[derive(Debug, Clone)]
+pub struct ScanDiagnosticsWriterStats {
+    /// Number of scans written
+    pub scans_written: usize,
+    /// Total number of key-value pairs written
+    pub entries_written: usize,
+    /// Number of row groups written
+    pub row_groups_written: usize,
+    /// Total file size in bytes
+    pub file_size_bytes: u64,
+}
+
+impl std::fmt::Display for ScanDiagnosticsWriterStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Wrote {} diagnostic values for {} scans in {} row groups",
+            self.entries_written, self.scans_written, self.row_groups_written
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Array;
+    use bytes::Bytes;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::io::Cursor;
+
+    fn thermo_trailer(spectrum_id: i64) -> ScanDiagnostics {
+        let mut scan = ScanDiagnostics::new(spectrum_id);
+        scan.push("Ion Injection Time (ms)", "22.5");
+        scan.push("FT Resolution", "120000");
+        scan.push("Scan Description", "");
+        scan
+    }
+
+    #[test]
+    fn test_scan_diagnostics_lookup() {
+        let scan = thermo_trailer(0);
+        assert_eq!(scan.len(), 3);
+        assert_eq!(scan.get("FT Resolution"), Some("120000"));
+        assert_eq!(scan.get_numeric("Ion Injection Time (ms)"), Some(22.5));
+        assert_eq!(scan.get_numeric("Scan Description"), None);
+        assert_eq!(scan.get("Missing"), None);
+    }
+
+    #[test]
+    fn test_write_scan_diagnostics() -> Result<(), ScanDiagnosticsWriterError> {
+        let mut writer = ScanDiagnosticsWriter::new(
+            Cursor::new(Vec::new()),
+            &MzPeakMetadata::new(),
+            ScanDiagnosticsWriterConfig::default(),
+        )?;
+        writer.write_scans(&[thermo_trailer(0), ScanDiagnostics::new(1)])?;
+        writer.write_scan(&thermo_trailer(2))?;
+        assert_eq!(writer.stats().scans_written, 3);
+        assert_eq!(writer.stats().entries_written, 6);
+
+        let bytes = Bytes::from(writer.finish_into_inner()?.into_inner());
+        let batches: Vec<RecordBatch> = ParquetRecordBatchReaderBuilder::try_new(bytes)?
+            .build()?
+            .collect::<Result<_, _>>()?;
+        let batch = arrow::compute::concat_batches(&batches[0].schema(), &batches)?;
+        assert_eq!(batch.num_rows(), 6);
+
+        let numeric = batch
+            .column_by_name(scan_diagnostics_columns::NUMERIC_VALUE)
+            .unwrap()
+            .as_any()
+            .downcast_ref::<arrow::array::Float64Array>()
+            .unwrap();
+        assert_eq!(numeric.value(1), 120000.0);
+        assert!(numeric.is_null(2));
+        Ok(())
+    }
+}
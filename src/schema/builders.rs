@@ -4,16 +4,10 @@ use std::sync::Arc;
 use arrow::datatypes::{DataType, Field, Schema, SchemaBuilder};
 
 use super::chromatogram_columns;
+use super::column_metadata::field_with_cv;
 use super::columns;
 use super::constants::KEY_FORMAT_VERSION;
-use super::constants::MZPEAK_FORMAT_VERSION;
-
-/// Creates a Field with CV term metadata annotation
-fn field_with_cv(name: &str, data_type: DataType, nullable: bool, cv_accession: &str) -> Field {
-    let mut metadata = HashMap::new();
-    metadata.insert("cv_accession".to_string(), cv_accession.to_string());
-    Field::new(name, data_type, nullable).with_metadata(metadata)
-}
+use super::constants::{MZPEAK_FORMAT_VERSION, MZPEAK_V2_SCHEMA_VERSION};
 
 /// Creates the core mzPeak Arrow schema for LC-MS data.
 ///
@@ -373,7 +367,7 @@ pub fn create_peaks_schema_v2(has_ion_mobility: bool) -> Schema {
 
     // Add schema-level metadata
     let mut metadata = HashMap::new();
-    metadata.insert(KEY_FORMAT_VERSION.to_string(), "2.0".to_string());
+    metadata.insert(KEY_FORMAT_VERSION.to_string(), MZPEAK_V2_SCHEMA_VERSION.to_string());
     metadata.insert(
         "mzpeak:schema_description".to_string(),
         "v2.0 minimal peaks schema with optimized encodings".to_string(),
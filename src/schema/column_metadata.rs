@@ -0,0 +1,154 @@
+//! Self-describing column metadata
+//!
+//! Every column annotated with a CV accession also carries, in its Arrow field
+//! metadata, the name of the CV term ([`DESCRIPTION_KEY`]) and the unit its
+//! values are stored in ([`UNIT_KEY`], [`UNIT_ACCESSION_KEY`]). pyarrow and
+//! pandas expose field metadata directly, e.g.
+//! `pq.read_schema(path).field("retention_time").metadata`.
+//!
+//! Tools that ignore the embedded Arrow schema (Spark, DuckDB,
+//! `parquet-tools`) see the same annotations as one plain Parquet footer entry
+//! per column: the key is [`KEY_COLUMN_PREFIX`] followed by the column name
+//! and the value is a JSON object of the field's annotations:
+//!
+//! ```text
+//! mzpeak:column:retention_time = {"cv_accession":"MS:1000016","description":"scan start time","unit":"second","unit_accession":"UO:0000010"}
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::sync::OnceLock;
+
+use arrow::datatypes::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use parquet::format::KeyValue;
+
+use super::constants::KEY_COLUMN_PREFIX;
+use super::units::{field_unit, CV_ACCESSION_KEY, UNIT_ACCESSION_KEY};
+
+/// Field metadata key holding the name of the column's CV term
+pub const DESCRIPTION_KEY: &str = "description";
+
+/// Field metadata key holding the name of the column's unit
+pub const UNIT_KEY: &str = "unit";
+
+/// Field metadata keys copied into the per-column footer entries
+const ANNOTATION_KEYS: [&str; 4] = [
+    CV_ACCESSION_KEY,
+    DESCRIPTION_KEY,
+    UNIT_KEY,
+    UNIT_ACCESSION_KEY,
+];
+
+/// Name of a CV term used by the mzPeak schemas
+///
+/// Returns `None` for accessions the schemas do not use.
+pub fn cv_term_name(cv_accession: &str) -> Option<&'static str> {
+    let name = match cv_accession {
+        "MS:1000016" => "scan start time",
+        "MS:1000040" => "m/z",
+        "MS:1000041" => "charge state",
+        "MS:1000042" => "peak intensity",
+        "MS:1000045" => "collision energy",
+        "MS:1000235" => "total ion current chromatogram",
+        "MS:1000285" => "total ion current",
+        "MS:1000465" => "scan polarity",
+        "MS:1000504" => "base peak m/z",
+        "MS:1000505" => "base peak intensity",
+        "MS:1000511" => "ms level",
+        "MS:1000515" => "intensity array",
+        "MS:1000595" => "time array",
+        "MS:1000744" => "selected ion m/z",
+        "MS:1000796" => "spectrum title",
+        "MS:1000797" => "peak list scans",
+        "MS:1000828" => "isolation window lower offset",
+        "MS:1000829" => "isolation window upper offset",
+        "MS:1000927" => "ion injection time",
+        "MS:1002476" => "ion mobility drift time",
+        "MS:1002815" => "inverse reduced ion mobility",
+        "IMS:1000050" => "position x",
+        "IMS:1000051" => "position y",
+        "IMS:1000052" => "position z",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Empty metadata map sharing one hasher with every other map it is cloned for
+///
+/// The embedded Arrow schema stores field metadata in map iteration order.
+/// Maps with the same hasher and insertion order iterate alike, so all writers
+/// of a process produce the same bytes for the same schema.
+fn metadata_map() -> HashMap<String, String> {
+    static EMPTY: OnceLock<HashMap<String, String>> = OnceLock::new();
+    EMPTY.get_or_init(HashMap::new).clone()
+}
+
+/// Creates a Field annotated with a CV term, its name and its unit
+pub(crate) fn field_with_cv(
+    name: &str,
+    data_type: DataType,
+    nullable: bool,
+    cv_accession: &str,
+) -> Field {
+    let mut metadata = metadata_map();
+    metadata.insert(CV_ACCESSION_KEY.to_string(), cv_accession.to_string());
+    annotate_field(Field::new(name, data_type, nullable).with_metadata(metadata))
+}
+
+/// Fill in the description and unit of a field annotated with a CV accession
+///
+/// Annotations already present are kept, so an explicit [`UNIT_ACCESSION_KEY`]
+/// still overrides the default unit of the quantity.
+pub fn annotate_field(field: Field) -> Field {
+    let mut metadata = field.metadata().clone();
+    let Some(accession) = metadata.get(CV_ACCESSION_KEY) else {
+        return field;
+    };
+    if let Some(name) = cv_term_name(accession) {
+        metadata
+            .entry(DESCRIPTION_KEY.to_string())
+            .or_insert_with(|| name.to_string());
+    }
+    if let Some(unit) = field_unit(&field) {
+        metadata
+            .entry(UNIT_KEY.to_string())
+            .or_insert_with(|| unit.name().to_string());
+        metadata
+            .entry(UNIT_ACCESSION_KEY.to_string())
+            .or_insert_with(|| unit.accession().to_string());
+    }
+    field.with_metadata(metadata)
+}
+
+/// Per-column Parquet footer entries for the annotated fields of `schema`
+pub fn column_key_values(schema: &Schema) -> Vec<KeyValue> {
+    schema
+        .fields()
+        .iter()
+        .filter_map(|field| {
+            let annotations: BTreeMap<&str, &String> = ANNOTATION_KEYS
+                .iter()
+                .filter_map(|&key| Some((key, field.metadata().get(key)?)))
+                .collect();
+            if annotations.is_empty() {
+                return None;
+            }
+            let value = serde_json::to_string(&annotations).ok()?;
+            Some(KeyValue::new(
+                format!("{}{}", KEY_COLUMN_PREFIX, field.name()),
+                value,
+            ))
+        })
+        .collect()
+}
+
+/// Append the per-column footer entries of `schema` to a writer
+pub(crate) fn append_column_key_values<W: Write + Send>(
+    writer: &mut ArrowWriter<W>,
+    schema: &Schema,
+) {
+    for kv in column_key_values(schema) {
+        writer.append_key_value_metadata(kv);
+    }
+}
@@ -11,6 +11,9 @@ pub const RETENTION_TIME: &str = "retention_time";
 pub const POLARITY: &str = "polarity";
 /// Mass-to-charge ratio (MS:1000040)
 pub const MZ: &str = "mz";
+/// Per-spectrum deltas of m/z bit patterns (Int64), stored in place of `mz`
+/// by writers that enforce sorted m/z
+pub const MZ_DELTA: &str = "mz_delta";
 /// Peak intensity (MS:1000042)
 pub const INTENSITY: &str = "intensity";
 /// Ion mobility drift time in milliseconds (MS:1002476)
@@ -2,7 +2,13 @@
 pub const MS_CV_PREFIX: &str = "MS";
 
 /// mzPeak format version - follows semantic versioning
-pub const MZPEAK_FORMAT_VERSION: &str = "1.0.0";
+pub const MZPEAK_FORMAT_VERSION: &str = "1.2.0";
+
+/// Schema version of the v2 container tables (`spectra.parquet`, `peaks.parquet`)
+///
+/// Bump together with an entry in `docs/SCHEMA_CHANGES.md` whenever a v2 table
+/// schema changes.
+pub const MZPEAK_V2_SCHEMA_VERSION: &str = "2.2";
 
 /// File extension for mzPeak files (legacy single-file format)
 pub const MZPEAK_EXTENSION: &str = ".mzpeak.parquet";
@@ -10,6 +16,9 @@ pub const MZPEAK_EXTENSION: &str = ".mzpeak.parquet";
 /// MIME type for mzPeak container files (public for use in validator and dataset modules)
 pub const MZPEAK_MIMETYPE: &str = "application/vnd.mzpeak";
 
+/// MIME type for mzPeak v2.0 container files
+pub const MZPEAK_V2_MIMETYPE: &str = "application/vnd.mzpeak+v2";
+
 /// Metadata key for format version in Parquet footer
 pub const KEY_FORMAT_VERSION: &str = "mzpeak:format_version";
 
@@ -45,3 +54,6 @@ pub const KEY_IMAGING_METADATA: &str = "mzpeak:imaging_metadata";
 
 /// Metadata key for vendor hints (files converted via intermediate formats)
 pub const KEY_VENDOR_HINTS: &str = "mzpeak:vendor_hints";
+
+/// Prefix of the per-column footer keys (`mzpeak:column:<column name>`)
+pub const KEY_COLUMN_PREFIX: &str = "mzpeak:column:";
@@ -5,9 +5,13 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::constants::MZPEAK_V2_SCHEMA_VERSION;
+
 // Re-export VendorHints from metadata module to avoid duplication
 pub use crate::metadata::VendorHints;
 
+use crate::metadata::ImagingMetadata;
+
 /// Data modality determining which optional columns are present
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -82,9 +86,170 @@ pub struct Manifest {
     /// Vendor hints for files converted via intermediate formats
     #[serde(skip_serializing_if = "Option::is_none")]
     pub vendor_hints: Option<VendorHints>,
+    /// Pixel grid calibration (pixel size, origin, axis orientation) of
+    /// imaging data
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imaging: Option<ImagingMetadata>,
     /// Optional hash of the schema for validation
     #[serde(skip_serializing_if = "Option::is_none")]
     pub schema_hash: Option<String>,
+    /// Registry of files stored under `attachments/`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+    /// Peak density summaries of the peaks table row groups
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout_stats: Option<LayoutStats>,
+}
+
+/// Category of a container attachment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AttachmentKind {
+    /// Acquisition or LC method export
+    Method,
+    /// Screenshot or other image
+    Image,
+    /// Report, notes or other document
+    Document,
+    /// Processing or acquisition script
+    Script,
+    /// Anything else
+    Other,
+}
+
+impl AttachmentKind {
+    /// Guess the kind from a file extension
+    pub fn from_extension(extension: &str) -> Self {
+        match extension.to_ascii_lowercase().as_str() {
+            "meth" | "method" | "m" | "dam" | "lcm" => AttachmentKind::Method,
+            "png" | "jpg" | "jpeg" | "gif" | "bmp" | "tif" | "tiff" | "svg" | "webp" => {
+                AttachmentKind::Image
+            }
+            "pdf" | "txt" | "md" | "html" | "htm" | "doc" | "docx" | "rtf" | "csv" | "tsv"
+            | "xlsx" | "json" | "xml" => AttachmentKind::Document,
+            "py" | "r" | "sh" | "ps1" | "bat" | "js" | "lua" | "ipynb" => AttachmentKind::Script,
+            _ => AttachmentKind::Other,
+        }
+    }
+
+    /// Guess the media type from a file extension
+    pub fn media_type(extension: &str) -> &'static str {
+        match extension.to_ascii_lowercase().as_str() {
+            "pdf" => "application/pdf",
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "gif" => "image/gif",
+            "bmp" => "image/bmp",
+            "tif" | "tiff" => "image/tiff",
+            "svg" => "image/svg+xml",
+            "webp" => "image/webp",
+            "txt" | "py" | "r" | "sh" | "ps1" | "bat" | "lua" => "text/plain",
+            "md" => "text/markdown",
+            "html" | "htm" => "text/html",
+            "csv" => "text/csv",
+            "tsv" => "text/tab-separated-values",
+            "json" | "ipynb" => "application/json",
+            "xml" => "application/xml",
+            "js" => "text/javascript",
+            _ => "application/octet-stream",
+        }
+    }
+}
+
+/// Registry entry for a file stored under `attachments/` in the container
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Attachment {
+    /// File name, unique within the container
+    pub name: String,
+    /// Attachment category
+    pub kind: AttachmentKind,
+    /// MIME type of the content
+    pub media_type: String,
+    /// Size in bytes
+    pub size_bytes: u64,
+    /// CRC-32 of the content
+    pub crc32: u32,
+    /// ISO 8601 timestamp of when the file was attached
+    pub added: String,
+    /// Optional free-text description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl Attachment {
+    /// Path of the attachment inside the container
+    pub fn entry_path(&self) -> String {
+        format!("{}{}", ATTACHMENTS_DIR, self.name)
+    }
+}
+
+/// Directory holding attachments inside a container
+pub const ATTACHMENTS_DIR: &str = "attachments/";
+
+/// Number of bins of [`RowGroupStats::peaks_per_spectrum`]
+pub const PEAKS_PER_SPECTRUM_BINS: usize = 24;
+
+/// Peak density summaries of the peaks table, one per row group
+///
+/// Written at conversion time so query planners and QC tools can judge how
+/// dense each row group is without scanning it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutStats {
+    /// Target number of peaks per row group
+    pub row_group_size: u64,
+    /// Summaries in row group order
+    pub row_groups: Vec<RowGroupStats>,
+}
+
+impl LayoutStats {
+    /// Total number of peaks across all row groups
+    pub fn peak_count(&self) -> u64 {
+        self.row_groups.iter().map(|row_group| row_group.peak_count).sum()
+    }
+}
+
+/// Peak density summary of one peaks table row group
+///
+/// A spectrum whose peaks straddle a row group boundary is counted in both
+/// row groups, with the peaks it has in each.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RowGroupStats {
+    /// Number of peaks (rows)
+    pub peak_count: u64,
+    /// Number of spectra with peaks in the row group
+    pub spectrum_count: u64,
+    /// First spectrum_id in the row group
+    pub first_spectrum_id: u32,
+    /// Last spectrum_id in the row group
+    pub last_spectrum_id: u32,
+    /// Histogram of peaks per spectrum: bin `k` counts spectra with
+    /// `2^k <= peaks < 2^(k+1)`. Trailing empty bins are omitted.
+    pub peaks_per_spectrum: Vec<u64>,
+    /// Intensity minimum, deciles (10% to 90%) and maximum, 11 values in
+    /// ascending order; empty for an empty row group
+    pub intensity_deciles: Vec<f32>,
+}
+
+impl RowGroupStats {
+    /// Histogram bin of a spectrum with `peaks` peaks (at least one)
+    pub fn peaks_per_spectrum_bin(peaks: u64) -> usize {
+        (peaks.max(1).ilog2() as usize).min(PEAKS_PER_SPECTRUM_BINS - 1)
+    }
+
+    /// Mean number of peaks per spectrum
+    pub fn mean_peaks_per_spectrum(&self) -> f64 {
+        if self.spectrum_count == 0 {
+            return 0.0;
+        }
+        self.peak_count as f64 / self.spectrum_count as f64
+    }
+
+    /// Largest intensity in the row group
+    pub fn max_intensity(&self) -> Option<f32> {
+        self.intensity_deciles.last().copied()
+    }
 }
 
 impl Manifest {
@@ -110,7 +275,7 @@ impl Manifest {
     ) -> Self {
         Self {
             format_version: "2.0".to_string(),
-            schema_version: "2.0".to_string(),
+            schema_version: MZPEAK_V2_SCHEMA_VERSION.to_string(),
             modality,
             has_ion_mobility: modality.has_ion_mobility(),
             has_imaging: modality.has_imaging(),
@@ -120,7 +285,10 @@ impl Manifest {
             created,
             converter,
             vendor_hints: None,
+            imaging: None,
             schema_hash: None,
+            attachments: Vec::new(),
+            layout_stats: None,
         }
     }
 }
@@ -165,7 +333,7 @@ mod tests {
         );
 
         assert_eq!(manifest.format_version, "2.0");
-        assert_eq!(manifest.schema_version, "2.0");
+        assert_eq!(manifest.schema_version, MZPEAK_V2_SCHEMA_VERSION);
         assert_eq!(manifest.modality, Modality::LcImsMs);
         assert!(manifest.has_ion_mobility);
         assert!(!manifest.has_imaging);
@@ -174,6 +342,7 @@ mod tests {
         assert_eq!(manifest.peak_count, 500000);
         assert!(manifest.vendor_hints.is_none());
         assert!(manifest.schema_hash.is_none());
+        assert!(manifest.attachments.is_empty());
     }
 
     #[test]
@@ -194,6 +363,37 @@ mod tests {
         assert_eq!(deserialized.spectrum_count, 100);
     }
 
+    #[test]
+    fn test_manifest_without_attachments_field() {
+        let manifest = Manifest::new(
+            Modality::LcMs,
+            false,
+            1,
+            1,
+            "2024-01-01T00:00:00Z".to_string(),
+            "mzpeak-rs".to_string(),
+        );
+        let json = serde_json::to_string(&manifest).unwrap();
+        assert!(!json.contains("attachments"));
+        assert!(!json.contains("\"imaging\""));
+
+        let deserialized: Manifest = serde_json::from_str(&json).unwrap();
+        assert!(deserialized.attachments.is_empty());
+    }
+
+    #[test]
+    fn test_attachment_kind_from_extension() {
+        assert_eq!(AttachmentKind::from_extension("PDF"), AttachmentKind::Document);
+        assert_eq!(AttachmentKind::from_extension("png"), AttachmentKind::Image);
+        assert_eq!(AttachmentKind::from_extension("py"), AttachmentKind::Script);
+        assert_eq!(AttachmentKind::from_extension("meth"), AttachmentKind::Method);
+        assert_eq!(AttachmentKind::from_extension("bin"), AttachmentKind::Other);
+        assert_eq!(
+            serde_json::to_string(&AttachmentKind::Method).unwrap(),
+            "\"method\""
+        );
+    }
+
     #[test]
     fn test_modality_kebab_case_serialization() {
         assert_eq!(
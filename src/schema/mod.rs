@@ -40,17 +40,30 @@
 //! By sorting data by spectrum_id, all peaks from the same spectrum are grouped together.
 //! Metadata columns (ms_level, retention_time, precursor_mz, etc.) will have identical
 //! values within each spectrum group, allowing RLE to achieve excellent compression ratios.
+//!
+//! Writers that enforce sorted m/z store `mz` as per-spectrum deltas in an Int64
+//! `mz_delta` column instead; see [`mz_delta`].
+//!
+//! Every annotated column also carries its CV term name and unit, in the Arrow
+//! field metadata and in a per-column Parquet footer entry; see
+//! [`column_metadata`].
 
 mod builders;
 /// Chromatogram column name constants.
 pub mod chromatogram_columns;
+/// Self-describing column metadata.
+pub mod column_metadata;
 /// Peak table column name constants.
 pub mod columns;
 mod constants;
 /// Manifest schema for mzPeak v2.0 container format.
 pub mod manifest;
+/// Per-spectrum delta storage of sorted m/z.
+pub mod mz_delta;
 /// Spectra table schema for mzPeak v2.0.
 pub mod spectra_columns;
+/// Physical units of schema columns.
+pub mod units;
 mod validation;
 
 #[cfg(test)]
@@ -63,6 +76,9 @@ pub use builders::{
 pub use chromatogram_columns::*;
 pub use columns::*;
 pub use constants::*;
-pub use manifest::{Manifest, Modality, VendorHints};
+pub use manifest::{
+    Attachment, AttachmentKind, Manifest, Modality, VendorHints, ATTACHMENTS_DIR,
+};
 pub use spectra_columns::{create_spectra_schema, create_spectra_schema_arc};
+pub use units::{column_units, ColumnUnit, Unit};
 pub use validation::{validate_schema, SchemaValidationError};
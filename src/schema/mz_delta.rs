@@ -0,0 +1,170 @@
+//! Per-spectrum delta storage of sorted m/z
+//!
+//! Within a centroided spectrum, consecutive m/z values share their sign,
+//! exponent and leading mantissa bits. Writers with
+//! [`WriterConfig::enforce_sorted_mz`](crate::writer::WriterConfig::enforce_sorted_mz)
+//! store an Int64 `mz_delta` column in place of the Float64 `mz` column, at
+//! the same position:
+//!
+//! - the first peak of each spectrum holds the IEEE 754 bit pattern of its m/z
+//! - every later peak holds the difference to the previous peak's bit pattern
+//!
+//! The bit pattern of a non-negative double orders like the value, so sorted
+//! m/z gives small non-negative differences whose high bytes are zero, which
+//! BYTE_STREAM_SPLIT + ZSTD compresses much better than the raw doubles. The
+//! differences wrap, so decoding restores every value bit for bit.
+//!
+//! Decoding needs the rows of a spectrum in order from its first peak.
+//! [`crate::reader`] restores `mz` on every batch it returns, including the
+//! DataFusion tables, which expose the schema of [`mz_schema`]. Readers only
+//! prune on columns that are constant within a spectrum, so a spectrum in the
+//! query is never cut off, and range queries on `mz` select whole spectra.
+//! Consumers reading the Parquet file directly see `mz_delta` and can use
+//! [`MzDeltaDecoder`].
+
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, Float64Array, Int64Array};
+use arrow::buffer::ScalarBuffer;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+
+use super::columns;
+
+/// Replace the `mz` field of a peak schema with an Int64 `mz_delta` field
+///
+/// Field metadata (the CV annotation) is kept, so [`MzDeltaDecoder`] restores
+/// the original field exactly.
+pub fn mz_delta_schema(schema: &Schema) -> Schema {
+    let fields: Vec<Field> = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            if field.name() == columns::MZ {
+                Field::new(columns::MZ_DELTA, DataType::Int64, field.is_nullable())
+                    .with_metadata(field.metadata().clone())
+            } else {
+                field.as_ref().clone()
+            }
+        })
+        .collect();
+    Schema::new_with_metadata(fields, schema.metadata().clone())
+}
+
+/// Replace the `mz_delta` field of a stored peak schema with the Float64 `mz`
+/// field, the inverse of [`mz_delta_schema`]
+///
+/// Schemas without `mz_delta` are returned unchanged.
+pub fn mz_schema(schema: &Schema) -> Schema {
+    let fields: Vec<Field> = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            if field.name() == columns::MZ_DELTA {
+                Field::new(columns::MZ, DataType::Float64, field.is_nullable())
+                    .with_metadata(field.metadata().clone())
+            } else {
+                field.as_ref().clone()
+            }
+        })
+        .collect();
+    Schema::new_with_metadata(fields, schema.metadata().clone())
+}
+
+/// Spectrum ID and m/z bit pattern of the last peak seen
+type LastPeak = Option<(i64, u64)>;
+
+/// Int64 `spectrum_id` column of a batch
+fn spectrum_ids(batch: &RecordBatch) -> Result<&Int64Array, ArrowError> {
+    batch
+        .column_by_name(columns::SPECTRUM_ID)
+        .and_then(|column| column.as_any().downcast_ref::<Int64Array>())
+        .ok_or_else(|| ArrowError::SchemaError("spectrum_id column is not Int64".to_string()))
+}
+
+/// Converts `mz` to `mz_delta`, continuing spectra across batches
+#[derive(Debug)]
+pub struct MzDeltaEncoder {
+    schema: SchemaRef,
+    last: LastPeak,
+}
+
+impl MzDeltaEncoder {
+    /// Encoder producing batches in `schema` (the output of [`mz_delta_schema`])
+    pub fn new(schema: SchemaRef) -> Self {
+        Self { schema, last: None }
+    }
+
+    /// Encode a batch in the peaks schema
+    pub fn encode(&mut self, batch: &RecordBatch) -> Result<RecordBatch, ArrowError> {
+        let index = batch.schema().index_of(columns::MZ)?;
+        let mz = batch
+            .column(index)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .ok_or_else(|| ArrowError::SchemaError("mz column is not Float64".to_string()))?;
+
+        let mut deltas = Vec::with_capacity(mz.len());
+        for (&id, &value) in spectrum_ids(batch)?.values().iter().zip(mz.values().iter()) {
+            let bits = value.to_bits();
+            deltas.push(match self.last {
+                Some((last_id, last_bits)) if last_id == id => bits.wrapping_sub(last_bits) as i64,
+                _ => bits as i64,
+            });
+            self.last = Some((id, bits));
+        }
+
+        let mut arrays = batch.columns().to_vec();
+        arrays[index] = Arc::new(Int64Array::new(
+            ScalarBuffer::from(deltas),
+            mz.nulls().cloned(),
+        ));
+        RecordBatch::try_new(self.schema.clone(), arrays)
+    }
+}
+
+/// Restores `mz` from `mz_delta`, continuing spectra across batches
+#[derive(Debug, Default)]
+pub struct MzDeltaDecoder {
+    last: LastPeak,
+}
+
+impl MzDeltaDecoder {
+    /// Decode a batch read from storage
+    ///
+    /// Batches without an `mz_delta` column are returned unchanged.
+    pub fn decode(&mut self, batch: RecordBatch) -> Result<RecordBatch, ArrowError> {
+        let schema = batch.schema();
+        let Ok(index) = schema.index_of(columns::MZ_DELTA) else {
+            return Ok(batch);
+        };
+        let deltas = batch
+            .column(index)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .ok_or_else(|| ArrowError::SchemaError("mz_delta column is not Int64".to_string()))?;
+
+        let mut values = Vec::with_capacity(deltas.len());
+        for (&id, &delta) in spectrum_ids(&batch)?
+            .values()
+            .iter()
+            .zip(deltas.values().iter())
+        {
+            let bits = match self.last {
+                Some((last_id, last_bits)) if last_id == id => last_bits.wrapping_add(delta as u64),
+                _ => delta as u64,
+            };
+            values.push(f64::from_bits(bits));
+            self.last = Some((id, bits));
+        }
+
+        let mut arrays = batch.columns().to_vec();
+        arrays[index] = Arc::new(Float64Array::new(
+            ScalarBuffer::from(values),
+            deltas.nulls().cloned(),
+        )) as ArrayRef;
+
+        RecordBatch::try_new(Arc::new(mz_schema(&schema)), arrays)
+    }
+}
@@ -34,12 +34,14 @@
 //! | pixel_x | UInt16 | Yes | IMS:1000050 | Imaging only |
 //! | pixel_y | UInt16 | Yes | IMS:1000051 | Imaging only |
 //! | pixel_z | UInt16 | Yes | IMS:1000052 | 3D imaging |
+//! | spectrum_checksum | UInt64 | Yes | - | Optional, see [`create_spectra_schema_with_checksum`] |
 
 use std::collections::HashMap;
 use std::sync::Arc;
 
 use arrow::datatypes::{DataType, Field, Schema, SchemaBuilder};
 
+use super::column_metadata::field_with_cv;
 use super::constants::{KEY_FORMAT_VERSION, MZPEAK_FORMAT_VERSION};
 
 // =============================================================================
@@ -124,17 +126,14 @@ pub const PIXEL_Y: &str = "pixel_y";
 /// CV: IMS:1000052 - position z
 pub const PIXEL_Z: &str = "pixel_z";
 
+/// XXH3-64 content checksum of the spectrum (optional column)
+/// See [`crate::writer::spectrum_checksum`] for the hashed bytes
+pub const SPECTRUM_CHECKSUM: &str = "spectrum_checksum";
+
 // =============================================================================
 // Schema Builder Functions
 // =============================================================================
 
-/// Creates a Field with CV term metadata annotation
-fn field_with_cv(name: &str, data_type: DataType, nullable: bool, cv_accession: &str) -> Field {
-    let mut metadata = HashMap::new();
-    metadata.insert("cv_accession".to_string(), cv_accession.to_string());
-    Field::new(name, data_type, nullable).with_metadata(metadata)
-}
-
 /// Creates a Field without CV term metadata (for internal columns)
 fn field_without_cv(name: &str, data_type: DataType, nullable: bool) -> Field {
     Field::new(name, data_type, nullable)
@@ -369,6 +368,28 @@ pub fn create_spectra_schema_arc() -> Arc<Schema> {
     Arc::new(create_spectra_schema())
 }
 
+/// Creates the spectra table schema with the optional `spectrum_checksum`
+/// column appended.
+///
+/// Written when [`SpectraWriterConfig::spectrum_checksums`](crate::writer::SpectraWriterConfig::spectrum_checksums)
+/// is set. Readers treat the column as optional.
+///
+/// # Example
+///
+/// ```
+/// use mzpeak::schema::spectra_columns::{create_spectra_schema_with_checksum, SPECTRUM_CHECKSUM};
+///
+/// let schema = create_spectra_schema_with_checksum();
+/// assert_eq!(schema.fields().len(), 21);
+/// assert!(schema.field_with_name(SPECTRUM_CHECKSUM).is_ok());
+/// ```
+pub fn create_spectra_schema_with_checksum() -> Schema {
+    let schema = create_spectra_schema();
+    let mut builder = SchemaBuilder::from(schema.fields());
+    builder.push(field_without_cv(SPECTRUM_CHECKSUM, DataType::UInt64, true));
+    builder.finish().with_metadata(schema.metadata().clone())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -463,4 +484,18 @@ mod tests {
         let schema_arc = create_spectra_schema_arc();
         assert_eq!(schema_arc.fields().len(), 20);
     }
+
+    #[test]
+    fn test_spectra_schema_with_checksum() {
+        let schema = create_spectra_schema_with_checksum();
+        let base = create_spectra_schema();
+
+        // Same columns in the same order, checksum last
+        assert_eq!(&schema.fields()[..20], &base.fields()[..]);
+        let checksum = schema.field(20);
+        assert_eq!(checksum.name(), SPECTRUM_CHECKSUM);
+        assert!(checksum.is_nullable());
+        assert_eq!(checksum.data_type(), &DataType::UInt64);
+        assert_eq!(schema.metadata(), base.metadata());
+    }
 }
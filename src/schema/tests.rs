@@ -24,6 +24,16 @@ fn test_schema_validation() {
     assert!(validate_schema(&schema).is_ok());
 }
 
+#[test]
+fn test_schema_versions() {
+    let v1 = create_mzpeak_schema();
+    assert_eq!(v1.metadata()[KEY_FORMAT_VERSION], MZPEAK_FORMAT_VERSION);
+    for has_ion_mobility in [false, true] {
+        let v2 = create_peaks_schema_v2(has_ion_mobility);
+        assert_eq!(v2.metadata()[KEY_FORMAT_VERSION], MZPEAK_V2_SCHEMA_VERSION);
+    }
+}
+
 #[test]
 fn test_cv_metadata() {
     let schema = create_mzpeak_schema();
@@ -32,6 +42,93 @@ fn test_cv_metadata() {
     assert_eq!(cv, "MS:1000040");
 }
 
+#[test]
+fn test_column_units_from_cv_annotations() {
+    let schema = create_mzpeak_schema();
+    let units = column_units(&schema);
+    let unit_of = |column: &str| units.iter().find(|u| u.column == column).map(|u| u.unit);
+
+    assert_eq!(unit_of(columns::RETENTION_TIME), Some(Unit::Second));
+    assert_eq!(unit_of(columns::MZ), Some(Unit::MassToCharge));
+    assert_eq!(unit_of(columns::INTENSITY), Some(Unit::DetectorCounts));
+    assert_eq!(unit_of(columns::ION_MOBILITY), Some(Unit::Millisecond));
+    assert_eq!(unit_of(columns::COLLISION_ENERGY), Some(Unit::Electronvolt));
+    assert_eq!(unit_of(columns::SPECTRUM_ID), None);
+    assert_eq!(unit_of(columns::MS_LEVEL), None);
+
+    let chromatogram_units = column_units(&create_chromatogram_schema());
+    assert_eq!(chromatogram_units.len(), 2);
+    assert_eq!(chromatogram_units[0].unit, Unit::Second);
+
+    // An explicit unit annotation overrides the quantity default
+    let mut metadata = std::collections::HashMap::new();
+    metadata.insert(units::CV_ACCESSION_KEY.to_string(), "MS:1002476".to_string());
+    metadata.insert(units::UNIT_ACCESSION_KEY.to_string(), "MS:1002814".to_string());
+    let field = arrow::datatypes::Field::new(columns::ION_MOBILITY, DataType::Float64, true)
+        .with_metadata(metadata);
+    assert_eq!(
+        units::field_unit(&field),
+        Some(Unit::VoltSecondPerSquareCentimeter)
+    );
+    assert_eq!(Unit::VoltSecondPerSquareCentimeter.symbol(), "V·s/cm²");
+}
+
+#[test]
+fn test_column_metadata_annotations() -> Result<(), Box<dyn std::error::Error>> {
+    use column_metadata::{annotate_field, column_key_values, DESCRIPTION_KEY, UNIT_KEY};
+
+    let schema = create_mzpeak_schema();
+    let rt = schema.field_with_name(columns::RETENTION_TIME)?.metadata();
+    assert_eq!(
+        rt.get(DESCRIPTION_KEY).map(String::as_str),
+        Some("scan start time")
+    );
+    assert_eq!(rt.get(UNIT_KEY).map(String::as_str), Some("second"));
+    assert_eq!(
+        rt.get(units::UNIT_ACCESSION_KEY).map(String::as_str),
+        Some("UO:0000010")
+    );
+    let ms_level = schema.field_with_name(columns::MS_LEVEL)?.metadata();
+    assert_eq!(
+        ms_level.get(DESCRIPTION_KEY).map(String::as_str),
+        Some("ms level")
+    );
+    assert!(!ms_level.contains_key(UNIT_KEY));
+
+    // An explicit unit annotation is kept
+    let mut metadata = std::collections::HashMap::new();
+    metadata.insert(
+        units::CV_ACCESSION_KEY.to_string(),
+        "MS:1002815".to_string(),
+    );
+    metadata.insert(
+        units::UNIT_ACCESSION_KEY.to_string(),
+        "MS:1002814".to_string(),
+    );
+    let field = annotate_field(
+        arrow::datatypes::Field::new(columns::ION_MOBILITY, DataType::Float64, true)
+            .with_metadata(metadata),
+    );
+    assert_eq!(
+        field.metadata().get(UNIT_KEY).map(String::as_str),
+        Some("volt-second per square centimeter")
+    );
+
+    // One footer entry per annotated column, holding the annotations as JSON
+    let schema = arrow::datatypes::Schema::new(vec![
+        column_metadata::field_with_cv(columns::SPECTRUM_ID, DataType::Int64, false, "MS:1000796"),
+        arrow::datatypes::Field::new("key", DataType::Utf8, false),
+    ]);
+    let key_values = column_key_values(&schema);
+    assert_eq!(key_values.len(), 1);
+    assert_eq!(key_values[0].key, "mzpeak:column:spectrum_id");
+    let value: serde_json::Value =
+        serde_json::from_str(key_values[0].value.as_deref().unwrap_or_default())?;
+    assert_eq!(value["cv_accession"], "MS:1000796");
+    assert_eq!(value["description"], "spectrum title");
+    Ok(())
+}
+
 #[test]
 fn test_chromatogram_schema_creation() {
     let schema = create_chromatogram_schema();
@@ -0,0 +1,167 @@
+//! Physical units of schema columns
+//!
+//! Every measured column carries a `cv_accession` annotation naming the
+//! quantity it stores (e.g. `MS:1000016` scan start time). This module maps
+//! those quantities to the unit mzPeak stores them in, so readers can label
+//! axes without hard-coding column names. A field may override the default
+//! with an explicit [`UNIT_ACCESSION_KEY`] annotation, which is how
+//! ion mobility stored as 1/K0 is distinguished from drift time.
+
+use arrow::datatypes::{Field, Schema};
+
+/// Field metadata key holding the CV accession of the column's quantity
+pub const CV_ACCESSION_KEY: &str = "cv_accession";
+
+/// Field metadata key holding an explicit unit CV accession
+pub const UNIT_ACCESSION_KEY: &str = "unit_accession";
+
+/// Unit of a measured column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unit {
+    /// Seconds (UO:0000010)
+    Second,
+    /// Minutes (UO:0000031)
+    Minute,
+    /// Milliseconds (UO:0000028)
+    Millisecond,
+    /// Mass-to-charge ratio in Thomson (MS:1000040)
+    MassToCharge,
+    /// Detector counts (MS:1000131)
+    DetectorCounts,
+    /// Electronvolts (UO:0000266)
+    Electronvolt,
+    /// Inverse reduced ion mobility, 1/K0 in V·s/cm² (MS:1002814)
+    VoltSecondPerSquareCentimeter,
+}
+
+impl Unit {
+    /// CV accession of the unit term
+    pub fn accession(&self) -> &'static str {
+        match self {
+            Unit::Second => "UO:0000010",
+            Unit::Minute => "UO:0000031",
+            Unit::Millisecond => "UO:0000028",
+            Unit::MassToCharge => "MS:1000040",
+            Unit::DetectorCounts => "MS:1000131",
+            Unit::Electronvolt => "UO:0000266",
+            Unit::VoltSecondPerSquareCentimeter => "MS:1002814",
+        }
+    }
+
+    /// CV name of the unit term
+    pub fn name(&self) -> &'static str {
+        match self {
+            Unit::Second => "second",
+            Unit::Minute => "minute",
+            Unit::Millisecond => "millisecond",
+            Unit::MassToCharge => "m/z",
+            Unit::DetectorCounts => "number of detector counts",
+            Unit::Electronvolt => "electronvolt",
+            Unit::VoltSecondPerSquareCentimeter => "volt-second per square centimeter",
+        }
+    }
+
+    /// Short symbol for axis labels
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Unit::Second => "s",
+            Unit::Minute => "min",
+            Unit::Millisecond => "ms",
+            Unit::MassToCharge => "Th",
+            Unit::DetectorCounts => "counts",
+            Unit::Electronvolt => "eV",
+            Unit::VoltSecondPerSquareCentimeter => "V·s/cm²",
+        }
+    }
+
+    /// Look up a unit by its CV accession
+    pub fn from_accession(accession: &str) -> Option<Self> {
+        [
+            Unit::Second,
+            Unit::Minute,
+            Unit::Millisecond,
+            Unit::MassToCharge,
+            Unit::DetectorCounts,
+            Unit::Electronvolt,
+            Unit::VoltSecondPerSquareCentimeter,
+        ]
+        .into_iter()
+        .find(|unit| unit.accession() == accession)
+    }
+
+    /// Unit mzPeak stores a quantity in, by the quantity's CV accession
+    ///
+    /// Returns `None` for identifiers, flags and other unitless quantities.
+    pub fn for_quantity(cv_accession: &str) -> Option<Self> {
+        match cv_accession {
+            // scan start time, time array
+            "MS:1000016" | "MS:1000595" => Some(Unit::Second),
+            // m/z, selected ion m/z, isolation window offsets, base peak m/z
+            "MS:1000040" | "MS:1000744" | "MS:1000828" | "MS:1000829" | "MS:1000504" => {
+                Some(Unit::MassToCharge)
+            }
+            // peak intensity, intensity array, total ion current, base peak intensity
+            "MS:1000042" | "MS:1000515" | "MS:1000285" | "MS:1000505" => {
+                Some(Unit::DetectorCounts)
+            }
+            // ion mobility drift time, ion injection time
+            "MS:1002476" | "MS:1000927" => Some(Unit::Millisecond),
+            // inverse reduced ion mobility
+            "MS:1002815" => Some(Unit::VoltSecondPerSquareCentimeter),
+            // collision energy
+            "MS:1000045" => Some(Unit::Electronvolt),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+/// Unit descriptor of one schema column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnUnit {
+    /// Column name
+    pub column: String,
+    /// CV accession of the stored quantity
+    pub cv_accession: String,
+    /// Unit of the stored values
+    pub unit: Unit,
+}
+
+/// Unit of a single field, if it is annotated with a measured quantity
+pub fn field_unit(field: &Field) -> Option<Unit> {
+    let metadata = field.metadata();
+    if let Some(unit) = metadata
+        .get(UNIT_ACCESSION_KEY)
+        .and_then(|accession| Unit::from_accession(accession))
+    {
+        return Some(unit);
+    }
+    metadata
+        .get(CV_ACCESSION_KEY)
+        .and_then(|accession| Unit::for_quantity(accession))
+}
+
+/// Units of all measured columns of `schema`, in schema order
+pub fn column_units(schema: &Schema) -> Vec<ColumnUnit> {
+    schema
+        .fields()
+        .iter()
+        .filter_map(|field| {
+            let unit = field_unit(field)?;
+            Some(ColumnUnit {
+                column: field.name().clone(),
+                cv_accession: field
+                    .metadata()
+                    .get(CV_ACCESSION_KEY)
+                    .cloned()
+                    .unwrap_or_default(),
+                unit,
+            })
+        })
+        .collect()
+}
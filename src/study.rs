@@ -0,0 +1,628 @@
+//! # Study-Level QC Module
+//!
+//! This module aggregates per-run quality metrics across a study and applies
+//! simple Shewhart control-chart rules so that core facilities can monitor
+//! instrument drift directly from converted mzPeak files.
+//!
+//! For every run the following metrics are computed:
+//!
+//! - Spectrum and peak counts (total, MS1, MS2, median peaks per spectrum)
+//! - Total ion current (stored TIC if present, otherwise summed intensities)
+//! - Median mass error in ppm against user-supplied reference m/z values
+//!   (e.g. lock masses or known contaminants) observed in MS1 spectra
+//!
+//! The drift-relevant metrics (TIC, peak count, mass error) are then compared
+//! against a baseline: each run gets the delta to the previous run, a z-score
+//! against the baseline mean/standard deviation, and a [`ControlFlag`].
+//!
+//! The result is written as `study_qc.parquet` with one row per run.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use mzpeak::study::{StudyQc, StudyQcConfig};
+//!
+//! let config = StudyQcConfig {
+//!     reference_mzs: vec![445.120025], // polysiloxane lock mass
+//!     ..Default::default()
+//! };
+//! let qc = StudyQc::from_paths(&["run01.mzpeak", "run02.mzpeak", "run03.mzpeak"], &config)?;
+//! qc.write_parquet("study_qc.parquet")?;
+//! # Ok::<(), mzpeak::study::StudyError>(())
+//! ```
+
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Float64Array, Int32Array, Int64Array, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+
+use crate::reader::{MzPeakReader, ReaderError};
+
+/// Default file name for study QC output
+pub const STUDY_QC_FILE_NAME: &str = "study_qc.parquet";
+
+/// Column names for the study QC table
+pub mod study_qc_columns {
+    /// Position of the run in the study (0-based, input order)
+    pub const RUN_INDEX: &str = "run_index";
+    /// Run name (file stem of the source path)
+    pub const RUN_NAME: &str = "run_name";
+    /// Number of spectra in the run
+    pub const NUM_SPECTRA: &str = "num_spectra";
+    /// Number of MS1 spectra in the run
+    pub const NUM_MS1_SPECTRA: &str = "num_ms1_spectra";
+    /// Number of MS2 spectra in the run
+    pub const NUM_MS2_SPECTRA: &str = "num_ms2_spectra";
+    /// Number of positive-mode spectra in the run
+    pub const NUM_POSITIVE_SPECTRA: &str = "num_positive_spectra";
+    /// Number of negative-mode spectra in the run
+    pub const NUM_NEGATIVE_SPECTRA: &str = "num_negative_spectra";
+    /// Total number of peaks in the run
+    pub const TOTAL_PEAKS: &str = "total_peaks";
+    /// Median number of peaks per spectrum
+    pub const MEDIAN_PEAKS_PER_SPECTRUM: &str = "median_peaks_per_spectrum";
+    /// Total ion current summed over all spectra
+    pub const TOTAL_ION_CURRENT: &str = "total_ion_current";
+    /// Median mass error against the reference m/z values (ppm)
+    pub const MEDIAN_MASS_ERROR_PPM: &str = "median_mass_error_ppm";
+}
+
+/// Errors that can occur during study QC aggregation
+#[derive(Debug, thiserror::Error)]
+pub enum StudyError {
+    /// I/O error during file operations
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    /// Error from the Arrow library during array operations
+    #[error("Arrow error: {0}")]
+    ArrowError(#[from] arrow::error::ArrowError),
+
+    /// Error from the Parquet library during file writing
+    #[error("Parquet error: {0}")]
+    ParquetError(#[from] parquet::errors::ParquetError),
+
+    /// Error reading a run
+    #[error("Reader error: {0}")]
+    ReaderError(#[from] ReaderError),
+
+    /// Invalid configuration or input
+    #[error("Invalid data: {0}")]
+    InvalidData(String),
+}
+
+/// Configuration for study QC aggregation
+#[derive(Debug, Clone)]
+pub struct StudyQcConfig {
+    /// Reference m/z values used to compute mass error (empty = no mass error)
+    pub reference_mzs: Vec<f64>,
+
+    /// Search tolerance around each reference m/z in ppm
+    pub mass_tolerance_ppm: f64,
+
+    /// Number of leading runs used to establish the control limits
+    /// (None = all runs)
+    pub baseline_runs: Option<usize>,
+
+    /// |z| above which a metric is flagged as a warning
+    pub warning_limit_sigma: f64,
+
+    /// |z| above which a metric is flagged as out of control
+    pub control_limit_sigma: f64,
+}
+
+impl Default for StudyQcConfig {
+    fn default() -> Self {
+        Self {
+            reference_mzs: Vec::new(),
+            mass_tolerance_ppm: 10.0,
+            baseline_runs: None,
+            warning_limit_sigma: 2.0,
+            control_limit_sigma: 3.0,
+        }
+    }
+}
+
+/// QC metrics for a single run
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunQcMetrics {
+    /// Run name
+    pub run_name: String,
+    /// Number of spectra
+    pub num_spectra: i64,
+    /// Number of MS1 spectra
+    pub num_ms1_spectra: i64,
+    /// Number of MS2 spectra
+    pub num_ms2_spectra: i64,
+    /// Number of positive-mode spectra
+    pub num_positive_spectra: i64,
+    /// Number of negative-mode spectra
+    pub num_negative_spectra: i64,
+    /// Total number of peaks
+    pub total_peaks: i64,
+    /// Median number of peaks per spectrum
+    pub median_peaks_per_spectrum: f64,
+    /// Total ion current summed over all spectra
+    pub total_ion_current: f64,
+    /// Median mass error in ppm (None if no reference m/z was observed)
+    pub median_mass_error_ppm: Option<f64>,
+}
+
+impl RunQcMetrics {
+    /// Compute QC metrics for one run
+    pub fn from_reader(
+        run_name: impl Into<String>,
+        reader: &MzPeakReader,
+        config: &StudyQcConfig,
+    ) -> Result<Self, StudyError> {
+        let mut num_spectra = 0i64;
+        let mut num_ms1_spectra = 0i64;
+        let mut num_ms2_spectra = 0i64;
+        let mut num_positive_spectra = 0i64;
+        let mut num_negative_spectra = 0i64;
+        let mut total_peaks = 0i64;
+        let mut total_ion_current = 0.0f64;
+        let mut peak_counts = Vec::new();
+        let mut mass_errors = Vec::new();
+
+        for spectrum in reader.iter_spectra_arrays_streaming()? {
+            let spectrum = spectrum?;
+            num_spectra += 1;
+            match spectrum.ms_level {
+                1 => num_ms1_spectra += 1,
+                2 => num_ms2_spectra += 1,
+                _ => {}
+            }
+            match spectrum.polarity {
+                1 => num_positive_spectra += 1,
+                -1 => num_negative_spectra += 1,
+                _ => {}
+            }
+            total_peaks += spectrum.peak_count() as i64;
+            peak_counts.push(spectrum.peak_count() as f64);
+
+            let intensity_arrays = spectrum.intensity_arrays()?;
+            total_ion_current += match spectrum.total_ion_current {
+                Some(tic) => tic,
+                None => intensity_arrays
+                    .iter()
+                    .flat_map(|array| array.values().iter())
+                    .map(|&i| i as f64)
+                    .sum(),
+            };
+
+            if spectrum.ms_level == 1 && !config.reference_mzs.is_empty() {
+                let mz_arrays = spectrum.mz_arrays()?;
+                for &reference in &config.reference_mzs {
+                    let tolerance = reference * config.mass_tolerance_ppm * 1e-6;
+                    // Most intense peak within tolerance of the reference
+                    let mut best: Option<(f64, f32)> = None;
+                    for (mzs, intensities) in mz_arrays.iter().zip(&intensity_arrays) {
+                        for (&mz, &intensity) in mzs.values().iter().zip(intensities.values()) {
+                            if (mz - reference).abs() <= tolerance
+                                && best.map_or(true, |(_, i)| intensity > i)
+                            {
+                                best = Some((mz, intensity));
+                            }
+                        }
+                    }
+                    if let Some((mz, _)) = best {
+                        mass_errors.push((mz - reference) / reference * 1e6);
+                    }
+                }
+            }
+        }
+
+        Ok(Self {
+            run_name: run_name.into(),
+            num_spectra,
+            num_ms1_spectra,
+            num_ms2_spectra,
+            num_positive_spectra,
+            num_negative_spectra,
+            total_peaks,
+            median_peaks_per_spectrum: median(&mut peak_counts).unwrap_or(0.0),
+            total_ion_current,
+            median_mass_error_ppm: median(&mut mass_errors),
+        })
+    }
+}
+
+/// Control-chart classification of a metric value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlFlag {
+    /// Within the warning limits (or no baseline variance to compare against)
+    InControl,
+    /// Beyond the warning limit but within the control limit
+    Warning,
+    /// Beyond the control limit
+    OutOfControl,
+}
+
+impl ControlFlag {
+    /// String representation stored in the QC table
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::InControl => "in_control",
+            Self::Warning => "warning",
+            Self::OutOfControl => "out_of_control",
+        }
+    }
+}
+
+impl std::fmt::Display for ControlFlag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Control-chart evaluation of one metric for one run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MetricControl {
+    /// Difference to the previous run (None for the first run)
+    pub delta: Option<f64>,
+    /// Deviation from the baseline mean in baseline standard deviations
+    pub z_score: Option<f64>,
+    /// Control-chart classification
+    pub flag: ControlFlag,
+}
+
+/// QC row for one run in a study
+#[derive(Debug, Clone)]
+pub struct StudyQcRow {
+    /// Raw metrics for the run
+    pub metrics: RunQcMetrics,
+    /// Control evaluation of the total ion current
+    pub total_ion_current: MetricControl,
+    /// Control evaluation of the peak count
+    pub total_peaks: MetricControl,
+    /// Control evaluation of the median mass error
+    pub median_mass_error_ppm: MetricControl,
+}
+
+/// Study-level QC table
+#[derive(Debug, Clone)]
+pub struct StudyQc {
+    /// One row per run, in input order
+    pub rows: Vec<StudyQcRow>,
+}
+
+impl StudyQc {
+    /// Compute study QC from a list of mzPeak files (in acquisition order)
+    pub fn from_paths<P: AsRef<Path>>(
+        paths: &[P],
+        config: &StudyQcConfig,
+    ) -> Result<Self, StudyError> {
+        let mut runs = Vec::with_capacity(paths.len());
+        for path in paths {
+            let path = path.as_ref();
+            let reader = MzPeakReader::open(path)?;
+            let run_name = run_name_from_path(path);
+            runs.push(RunQcMetrics::from_reader(run_name, &reader, config)?);
+        }
+        Self::from_metrics(runs, config)
+    }
+
+    /// Evaluate control-chart rules over precomputed run metrics
+    pub fn from_metrics(
+        runs: Vec<RunQcMetrics>,
+        config: &StudyQcConfig,
+    ) -> Result<Self, StudyError> {
+        if config.warning_limit_sigma > config.control_limit_sigma {
+            return Err(StudyError::InvalidData(format!(
+                "warning limit ({}) must not exceed control limit ({})",
+                config.warning_limit_sigma, config.control_limit_sigma
+            )));
+        }
+
+        let tic: Vec<Option<f64>> = runs.iter().map(|r| Some(r.total_ion_current)).collect();
+        let peaks: Vec<Option<f64>> = runs.iter().map(|r| Some(r.total_peaks as f64)).collect();
+        let mass_error: Vec<Option<f64>> = runs.iter().map(|r| r.median_mass_error_ppm).collect();
+
+        let tic = evaluate_metric(&tic, config);
+        let peaks = evaluate_metric(&peaks, config);
+        let mass_error = evaluate_metric(&mass_error, config);
+
+        let rows = runs
+            .into_iter()
+            .enumerate()
+            .map(|(i, metrics)| StudyQcRow {
+                metrics,
+                total_ion_current: tic[i],
+                total_peaks: peaks[i],
+                median_mass_error_ppm: mass_error[i],
+            })
+            .collect();
+
+        Ok(Self { rows })
+    }
+
+    /// Runs with at least one metric out of control
+    pub fn out_of_control_runs(&self) -> impl Iterator<Item = &StudyQcRow> {
+        self.rows.iter().filter(|row| {
+            [
+                row.total_ion_current.flag,
+                row.total_peaks.flag,
+                row.median_mass_error_ppm.flag,
+            ]
+            .contains(&ControlFlag::OutOfControl)
+        })
+    }
+
+    /// Arrow schema of the study QC table
+    pub fn schema() -> Schema {
+        use study_qc_columns::*;
+
+        let mut fields = vec![
+            Field::new(RUN_INDEX, DataType::Int32, false),
+            Field::new(RUN_NAME, DataType::Utf8, false),
+            Field::new(NUM_SPECTRA, DataType::Int64, false),
+            Field::new(NUM_MS1_SPECTRA, DataType::Int64, false),
+            Field::new(NUM_MS2_SPECTRA, DataType::Int64, false),
+            Field::new(NUM_POSITIVE_SPECTRA, DataType::Int64, false),
+            Field::new(NUM_NEGATIVE_SPECTRA, DataType::Int64, false),
+            Field::new(TOTAL_PEAKS, DataType::Int64, false),
+            Field::new(MEDIAN_PEAKS_PER_SPECTRUM, DataType::Float64, false),
+            Field::new(TOTAL_ION_CURRENT, DataType::Float64, false),
+            Field::new(MEDIAN_MASS_ERROR_PPM, DataType::Float64, true),
+        ];
+        for metric in [TOTAL_ION_CURRENT, TOTAL_PEAKS, MEDIAN_MASS_ERROR_PPM] {
+            fields.push(Field::new(format!("{}_delta", metric), DataType::Float64, true));
+            fields.push(Field::new(format!("{}_z_score", metric), DataType::Float64, true));
+            fields.push(Field::new(format!("{}_flag", metric), DataType::Utf8, false));
+        }
+        Schema::new(fields)
+    }
+
+    /// Convert the QC table to an Arrow record batch
+    pub fn to_record_batch(&self) -> Result<RecordBatch, StudyError> {
+        let rows = &self.rows;
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(Int32Array::from_iter_values(0..rows.len() as i32)),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|r| r.metrics.run_name.as_str()),
+            )),
+            Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.metrics.num_spectra))),
+            Arc::new(Int64Array::from_iter_values(
+                rows.iter().map(|r| r.metrics.num_ms1_spectra),
+            )),
+            Arc::new(Int64Array::from_iter_values(
+                rows.iter().map(|r| r.metrics.num_ms2_spectra),
+            )),
+            Arc::new(Int64Array::from_iter_values(
+                rows.iter().map(|r| r.metrics.num_positive_spectra),
+            )),
+            Arc::new(Int64Array::from_iter_values(
+                rows.iter().map(|r| r.metrics.num_negative_spectra),
+            )),
+            Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.metrics.total_peaks))),
+            Arc::new(Float64Array::from_iter_values(
+                rows.iter().map(|r| r.metrics.median_peaks_per_spectrum),
+            )),
+            Arc::new(Float64Array::from_iter_values(
+                rows.iter().map(|r| r.metrics.total_ion_current),
+            )),
+            Arc::new(Float64Array::from_iter(
+                rows.iter().map(|r| r.metrics.median_mass_error_ppm),
+            )),
+        ];
+
+        let controls: [fn(&StudyQcRow) -> &MetricControl; 3] = [
+            |r| &r.total_ion_current,
+            |r| &r.total_peaks,
+            |r| &r.median_mass_error_ppm,
+        ];
+        for control in controls {
+            columns.push(Arc::new(Float64Array::from_iter(
+                rows.iter().map(|r| control(r).delta),
+            )));
+            columns.push(Arc::new(Float64Array::from_iter(
+                rows.iter().map(|r| control(r).z_score),
+            )));
+            columns.push(Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|r| control(r).flag.as_str()),
+            )));
+        }
+
+        Ok(RecordBatch::try_new(Arc::new(Self::schema()), columns)?)
+    }
+
+    /// Write the QC table as a Parquet file (conventionally `study_qc.parquet`)
+    pub fn write_parquet<P: AsRef<Path>>(&self, path: P) -> Result<(), StudyError> {
+        let batch = self.to_record_batch()?;
+        let props = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build();
+        let mut writer = ArrowWriter::try_new(File::create(path)?, batch.schema(), Some(props))?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+}
+
+/// Apply delta and control-chart rules to one metric series
+fn evaluate_metric(values: &[Option<f64>], config: &StudyQcConfig) -> Vec<MetricControl> {
+    let baseline_len = config.baseline_runs.unwrap_or(values.len()).min(values.len());
+    let baseline: Vec<f64> = values[..baseline_len].iter().flatten().copied().collect();
+
+    let limits = if baseline.len() >= 2 {
+        let mean = baseline.iter().sum::<f64>() / baseline.len() as f64;
+        let variance = baseline.iter().map(|v| (v - mean).powi(2)).sum::<f64>()
+            / (baseline.len() - 1) as f64;
+        let std_dev = variance.sqrt();
+        (std_dev > 0.0).then_some((mean, std_dev))
+    } else {
+        None
+    };
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let previous = if i > 0 { values[i - 1] } else { None };
+            let delta = value.zip(previous).map(|(v, p)| v - p);
+            let z_score = value
+                .zip(limits)
+                .map(|(v, (mean, std_dev))| (v - mean) / std_dev);
+            let flag = match z_score.map(f64::abs) {
+                Some(z) if z > config.control_limit_sigma => ControlFlag::OutOfControl,
+                Some(z) if z > config.warning_limit_sigma => ControlFlag::Warning,
+                _ => ControlFlag::InControl,
+            };
+            MetricControl {
+                delta,
+                z_score,
+                flag,
+            }
+        })
+        .collect()
+}
+
+fn median(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    Some(if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    })
+}
+
+/// Run name from a path: file name without `.mzpeak`/`.parquet` extension
+fn run_name_from_path(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    name.strip_suffix(".mzpeak")
+        .or_else(|| name.strip_suffix(".parquet"))
+        .unwrap_or(&name)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::MzPeakMetadata;
+    use crate::writer::{MzPeakWriter, PeakArrays, SpectrumArrays, WriterConfig};
+    use tempfile::tempdir;
+
+    fn metrics(name: &str, tic: f64, peaks: i64, mass_error: Option<f64>) -> RunQcMetrics {
+        RunQcMetrics {
+            run_name: name.to_string(),
+            num_spectra: 10,
+            num_ms1_spectra: 5,
+            num_ms2_spectra: 5,
+            num_positive_spectra: 10,
+            num_negative_spectra: 0,
+            total_peaks: peaks,
+            median_peaks_per_spectrum: peaks as f64 / 10.0,
+            total_ion_current: tic,
+            median_mass_error_ppm: mass_error,
+        }
+    }
+
+    #[test]
+    fn test_run_metrics_with_reference_mass() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let path = dir.path().join("run01.parquet");
+
+        let mut writer =
+            MzPeakWriter::new_file(&path, &MzPeakMetadata::new(), WriterConfig::default())?;
+        // Reference at 445.12; observed at +2.247 ppm (445.121) and a weaker peak further away
+        let ms1 = SpectrumArrays::new_ms1(
+            0,
+            1,
+            60.0,
+            1,
+            PeakArrays::new(vec![445.121, 445.1228, 500.0], vec![1000.0, 10.0, 50.0]),
+        );
+        // Polarity switching: the MS2 scan is acquired in negative mode
+        let ms2 = SpectrumArrays::new_ms2(
+            1,
+            2,
+            61.0,
+            -1,
+            445.12,
+            PeakArrays::new(vec![200.0], vec![40.0]),
+        );
+        writer.write_spectrum_arrays(&ms1)?;
+        writer.write_spectrum_arrays(&ms2)?;
+        writer.finish()?;
+
+        let config = StudyQcConfig {
+            reference_mzs: vec![445.12],
+            ..Default::default()
+        };
+        let reader = MzPeakReader::open(&path)?;
+        let run = RunQcMetrics::from_reader("run01", &reader, &config)?;
+
+        assert_eq!(run.num_spectra, 2);
+        assert_eq!(run.num_ms1_spectra, 1);
+        assert_eq!(run.num_ms2_spectra, 1);
+        assert_eq!(run.num_positive_spectra, 1);
+        assert_eq!(run.num_negative_spectra, 1);
+        assert_eq!(run.total_peaks, 4);
+        assert_eq!(run.median_peaks_per_spectrum, 2.0);
+        let ppm = run.median_mass_error_ppm.expect("reference observed");
+        assert!((ppm - 2.2466).abs() < 1e-3, "ppm = {}", ppm);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_control_flags_against_baseline() -> Result<(), StudyError> {
+        let runs = vec![
+            metrics("a", 100.0, 1000, Some(1.0)),
+            metrics("b", 102.0, 1010, Some(1.2)),
+            metrics("c", 98.0, 990, Some(0.8)),
+            metrics("d", 101.0, 1005, None),
+            metrics("e", 150.0, 1000, Some(5.0)),
+        ];
+        let config = StudyQcConfig {
+            baseline_runs: Some(4),
+            ..Default::default()
+        };
+        let qc = StudyQc::from_metrics(runs, &config)?;
+
+        assert_eq!(qc.rows[0].total_ion_current.delta, None);
+        assert_eq!(qc.rows[1].total_ion_current.delta, Some(2.0));
+        assert_eq!(qc.rows[1].total_ion_current.flag, ControlFlag::InControl);
+        assert_eq!(qc.rows[4].total_ion_current.flag, ControlFlag::OutOfControl);
+        assert_eq!(qc.rows[4].median_mass_error_ppm.flag, ControlFlag::OutOfControl);
+        assert_eq!(qc.rows[3].median_mass_error_ppm.z_score, None);
+
+        let flagged: Vec<_> = qc.out_of_control_runs().map(|r| r.metrics.run_name.as_str()).collect();
+        assert_eq!(flagged, vec!["e"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_study_qc_parquet() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempdir()?;
+        let path = dir.path().join(STUDY_QC_FILE_NAME);
+
+        let runs = vec![metrics("a", 100.0, 1000, None), metrics("b", 110.0, 900, None)];
+        let qc = StudyQc::from_metrics(runs, &StudyQcConfig::default())?;
+        qc.write_parquet(&path)?;
+
+        let file = File::open(&path)?;
+        let reader =
+            parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(file)?.build()?;
+        let batches: Vec<RecordBatch> = reader.collect::<Result<_, _>>()?;
+        assert_eq!(batches[0].num_rows(), 2);
+        assert_eq!(*batches[0].schema(), StudyQc::schema());
+
+        Ok(())
+    }
+}
@@ -4,7 +4,9 @@ use anyhow::Result;
 use parquet::file::reader::{FileReader, SerializedFileReader};
 use parquet::record::RowAccessor;
 
+use crate::ingest::{RtOrderTracker, DEFAULT_RT_JITTER_TOLERANCE};
 use crate::schema::columns;
+#[cfg(feature = "container")]
 use crate::reader::ZipEntryChunkReader;
 use crate::schema::spectra_columns;
 
@@ -22,6 +24,7 @@ pub(crate) fn check_data_sanity(
                     let reader = SerializedFileReader::new(File::open(path)?)?;
                     perform_data_sanity_checks(reader, report)
                 }
+                #[cfg(feature = "container")]
                 ParquetSource::ZipEntry { zip_path, entry_name } => {
                     let reader = ZipEntryChunkReader::new(zip_path, entry_name)?;
                     let reader = SerializedFileReader::new(reader)?;
@@ -39,6 +42,7 @@ pub(crate) fn check_data_sanity(
                     let reader = SerializedFileReader::new(File::open(path)?)?;
                     perform_v2_peaks_sanity_checks(reader, report)?;
                 }
+                #[cfg(feature = "container")]
                 ParquetSource::ZipEntry { zip_path, entry_name } => {
                     let reader = ZipEntryChunkReader::new(zip_path, entry_name)?;
                     let reader = SerializedFileReader::new(reader)?;
@@ -56,6 +60,7 @@ pub(crate) fn check_data_sanity(
                         let reader = SerializedFileReader::new(File::open(path)?)?;
                         perform_v2_spectra_sanity_checks(reader, report)?;
                     }
+                    #[cfg(feature = "container")]
                     ParquetSource::ZipEntry { zip_path, entry_name } => {
                         let reader = ZipEntryChunkReader::new(zip_path, entry_name)?;
                         let reader = SerializedFileReader::new(reader)?;
@@ -102,6 +107,7 @@ fn perform_data_sanity_checks<R: parquet::file::reader::ChunkReader + 'static>(
     let mut ms_level_idx = None;
     let mut retention_time_idx = None;
     let mut mz_idx = None;
+    let mut mz_as_delta = false;
     let mut intensity_idx = None;
 
     for i in 0..schema_descriptor.num_columns() {
@@ -111,6 +117,10 @@ fn perform_data_sanity_checks<R: parquet::file::reader::ChunkReader + 'static>(
             columns::MS_LEVEL => ms_level_idx = Some(i),
             columns::RETENTION_TIME => retention_time_idx = Some(i),
             columns::MZ => mz_idx = Some(i),
+            columns::MZ_DELTA => {
+                mz_idx = Some(i);
+                mz_as_delta = true;
+            }
             columns::INTENSITY => intensity_idx = Some(i),
             _ => {}
         }
@@ -123,9 +133,10 @@ fn perform_data_sanity_checks<R: parquet::file::reader::ChunkReader + 'static>(
     let mut mz_positive_count = 0;
     let mut intensity_non_negative_count = 0;
     let mut ms_level_valid_count = 0;
-    let mut last_rt: Option<f32> = None;
-    let mut rt_non_decreasing = true;
+    let mut rt_order = RtOrderTracker::new(DEFAULT_RT_JITTER_TOLERANCE);
     let mut prev_spectrum_id: Option<i64> = None;
+    // Spectrum ID and m/z bits of the previous row, for `mz_delta`
+    let mut prev_mz: Option<(i64, u64)> = None;
 
     for _i in 0..sample_size {
         if let Some(row_result) = row_iter.next() {
@@ -133,7 +144,20 @@ fn perform_data_sanity_checks<R: parquet::file::reader::ChunkReader + 'static>(
 
             // Check mz > 0
             if let Some(idx) = mz_idx {
-                if let Ok(mz) = row.get_double(idx) {
+                let mz = match spectrum_id_idx.map(|i| row.get_long(i)) {
+                    Some(Ok(id)) if mz_as_delta => row.get_long(idx).map(|delta| {
+                        let bits = match prev_mz {
+                            Some((prev_id, prev_bits)) if prev_id == id => {
+                                prev_bits.wrapping_add(delta as u64)
+                            }
+                            _ => delta as u64,
+                        };
+                        prev_mz = Some((id, bits));
+                        f64::from_bits(bits)
+                    }),
+                    _ => row.get_double(idx),
+                };
+                if let Ok(mz) = mz {
                     if mz > 0.0 {
                         mz_positive_count += 1;
                     }
@@ -176,12 +200,7 @@ fn perform_data_sanity_checks<R: parquet::file::reader::ChunkReader + 'static>(
                         if let Ok(rt) = row.get_float(rt_idx) {
                             // New spectrum
                             if prev_spectrum_id != Some(spectrum_id) {
-                                if let Some(prev_rt) = last_rt {
-                                    if rt < prev_rt {
-                                        rt_non_decreasing = false;
-                                    }
-                                }
-                                last_rt = Some(rt);
+                                rt_order.observe(rt);
                                 prev_spectrum_id = Some(spectrum_id);
                             }
                         }
@@ -242,14 +261,7 @@ fn perform_data_sanity_checks<R: parquet::file::reader::ChunkReader + 'static>(
         ));
     }
 
-    if rt_non_decreasing {
-        report.add_check(ValidationCheck::ok("Retention time non-decreasing"));
-    } else {
-        report.add_check(ValidationCheck::warning(
-            "Retention time non-decreasing",
-            "Retention time decreases between spectra (may be intentional)",
-        ));
-    }
+    report_rt_order(report, "Retention time non-decreasing", &rt_order);
 
     Ok(())
 }
@@ -317,7 +329,9 @@ fn perform_v2_peaks_sanity_checks<R: parquet::file::reader::ChunkReader + 'stati
             }
 
             if let Some(idx) = spectrum_id_idx {
-                if let Ok(spectrum_id) = row.get_int(idx) {
+                if row.get_uint(idx).is_ok() {
+                    spectrum_id_valid_count += 1;
+                } else if let Ok(spectrum_id) = row.get_int(idx) {
                     if spectrum_id >= 0 {
                         spectrum_id_valid_count += 1;
                     }
@@ -426,8 +440,7 @@ fn perform_v2_spectra_sanity_checks<R: parquet::file::reader::ChunkReader + 'sta
     let mut ms_level_valid_count = 0;
     let mut polarity_valid_count = 0;
     let mut rt_valid_count = 0;
-    let mut last_rt: Option<f32> = None;
-    let mut rt_non_decreasing = true;
+    let mut rt_order = RtOrderTracker::new(DEFAULT_RT_JITTER_TOLERANCE);
     let mut last_spectrum_id: Option<i64> = None;
     let mut spectrum_id_non_decreasing = true;
 
@@ -436,7 +449,11 @@ fn perform_v2_spectra_sanity_checks<R: parquet::file::reader::ChunkReader + 'sta
             let row = row_result?;
 
             if let Some(idx) = ms_level_idx {
-                if let Ok(ms_level) = row.get_byte(idx) {
+                if let Ok(ms_level) = row.get_ubyte(idx) {
+                    if ms_level >= 1 {
+                        ms_level_valid_count += 1;
+                    }
+                } else if let Ok(ms_level) = row.get_byte(idx) {
                     if ms_level >= 1 {
                         ms_level_valid_count += 1;
                     }
@@ -465,17 +482,14 @@ fn perform_v2_spectra_sanity_checks<R: parquet::file::reader::ChunkReader + 'sta
                         rt_valid_count += 1;
                     }
 
-                    if let Some(prev_rt) = last_rt {
-                        if rt < prev_rt {
-                            rt_non_decreasing = false;
-                        }
-                    }
-                    last_rt = Some(rt);
+                    rt_order.observe(rt);
                 }
             }
 
             if let Some(idx) = spectrum_id_idx {
-                let spectrum_id = if let Ok(value) = row.get_int(idx) {
+                let spectrum_id = if let Ok(value) = row.get_uint(idx) {
+                    Some(i64::from(value))
+                } else if let Ok(value) = row.get_int(idx) {
                     Some(value as i64)
                 } else if let Ok(value) = row.get_long(idx) {
                     Some(value)
@@ -545,14 +559,7 @@ fn perform_v2_spectra_sanity_checks<R: parquet::file::reader::ChunkReader + 'sta
         ));
     }
 
-    if rt_non_decreasing {
-        report.add_check(ValidationCheck::ok("V2 retention time non-decreasing"));
-    } else {
-        report.add_check(ValidationCheck::warning(
-            "V2 retention time non-decreasing",
-            "Retention time decreases between spectra (may be intentional)",
-        ));
-    }
+    report_rt_order(report, "V2 retention time non-decreasing", &rt_order);
 
     if spectrum_id_non_decreasing {
         report.add_check(ValidationCheck::ok("V2 spectrum_id non-decreasing"));
@@ -565,3 +572,32 @@ fn perform_v2_spectra_sanity_checks<R: parquet::file::reader::ChunkReader + 'sta
 
     Ok(())
 }
+
+/// Report the retention time order of the sampled spectra
+///
+/// Decreases within the jitter tolerance are benign vendor jitter; larger
+/// decreases mean the spectra are out of order.
+pub(super) fn report_rt_order(report: &mut ValidationReport, name: &str, rt_order: &RtOrderTracker) {
+    if rt_order.break_count() > 0 {
+        report.add_check(ValidationCheck::failed(
+            name,
+            format!(
+                "Retention time decreases by more than {} s {} times (spectra out of order)",
+                rt_order.tolerance(),
+                rt_order.break_count()
+            ),
+        ));
+    } else if rt_order.jitter_count() > 0 {
+        report.add_check(ValidationCheck::warning(
+            name,
+            format!(
+                "{} spectra up to {:.3} s before an earlier retention time (benign jitter within {} s)",
+                rt_order.jitter_count(),
+                rt_order.max_jitter(),
+                rt_order.tolerance()
+            ),
+        ));
+    } else {
+        report.add_check(ValidationCheck::ok(name));
+    }
+}
@@ -1,17 +1,21 @@
 use std::collections::HashMap;
 use std::fs::File;
+#[cfg(feature = "container")]
 use std::io::{BufReader, Read};
 use std::path::Path;
 
 use anyhow::Result;
 use parquet::file::reader::{FileReader, SerializedFileReader};
+#[cfg(feature = "container")]
 use zip::ZipArchive;
 
-use crate::metadata::MzPeakMetadata;
+use crate::metadata::{parse_timestamp, MzPeakMetadata, TimestampZone};
+#[cfg(feature = "container")]
 use crate::reader::ZipEntryChunkReader;
-use crate::schema::{KEY_FORMAT_VERSION, MZPEAK_FORMAT_VERSION};
+use crate::schema::{KEY_FORMAT_VERSION, MZPEAK_FORMAT_VERSION, MZPEAK_V2_SCHEMA_VERSION};
 use crate::schema::manifest::Manifest;
 
+#[cfg(feature = "container")]
 use super::structure::is_zip_file;
 use super::{ParquetSource, SchemaVersion, ValidationCheck, ValidationReport, ValidationTarget};
 
@@ -27,7 +31,10 @@ pub(crate) fn check_metadata_integrity(
         if metadata_json_path.exists() {
             validate_metadata_json_file(&metadata_json_path, report)?;
         }
-    } else if base_path.is_file() && is_zip_file(base_path) {
+    }
+    // Without the `container` feature the structure check already rejected ZIPs
+    #[cfg(feature = "container")]
+    if base_path.is_file() && is_zip_file(base_path) {
         let json_content = {
             let file = File::open(base_path)?;
             let mut archive = ZipArchive::new(BufReader::new(file))?;
@@ -59,12 +66,18 @@ pub(crate) fn check_metadata_integrity(
                             format!("Expected 2.0, found {}", manifest.format_version),
                         ));
                     }
-                    if manifest.schema_version == "2.0" {
-                        report.add_check(ValidationCheck::ok("Manifest schema version = 2.0"));
+                    if same_major(&manifest.schema_version, MZPEAK_V2_SCHEMA_VERSION) {
+                        report.add_check(ValidationCheck::ok(format!(
+                            "Manifest schema version = {}",
+                            manifest.schema_version
+                        )));
                     } else {
                         report.add_check(ValidationCheck::warning(
                             "Manifest schema version",
-                            format!("Expected 2.0, found {}", manifest.schema_version),
+                            format!(
+                                "Expected {}, found {}",
+                                MZPEAK_V2_SCHEMA_VERSION, manifest.schema_version
+                            ),
                         ));
                     }
                 }
@@ -103,14 +116,14 @@ pub(crate) fn check_metadata_integrity(
     if let Some(kv_map) = kv_map {
         if let Some(version) = kv_map.get(KEY_FORMAT_VERSION) {
             let expected = if validation_target.schema_version == SchemaVersion::V2 {
-                "2.0"
+                MZPEAK_V2_SCHEMA_VERSION
             } else {
                 MZPEAK_FORMAT_VERSION
             };
-            if version == expected {
+            if same_major(version, expected) {
                 report.add_check(ValidationCheck::ok(format!(
-                    "Format version matches ({})",
-                    expected
+                    "Format version compatible ({})",
+                    version
                 )));
             } else {
                 report.add_check(ValidationCheck::warning(
@@ -126,8 +139,9 @@ pub(crate) fn check_metadata_integrity(
         }
 
         match MzPeakMetadata::from_parquet_metadata(&kv_map) {
-            Ok(_) => {
+            Ok(metadata) => {
                 report.add_check(ValidationCheck::ok("Parquet metadata deserializes"));
+                check_run_timestamps(&metadata, report);
             }
             Err(e) => {
                 report.add_check(ValidationCheck::warning(
@@ -146,6 +160,11 @@ pub(crate) fn check_metadata_integrity(
     Ok(())
 }
 
+/// Whether two versions share their major version, which readers treat as compatible
+fn same_major(version: &str, expected: &str) -> bool {
+    version.split('.').next() == expected.split('.').next()
+}
+
 /// Validate metadata.json from file path
 fn validate_metadata_json_file(path: &Path, report: &mut ValidationReport) -> Result<()> {
     match std::fs::read_to_string(path) {
@@ -163,8 +182,9 @@ fn validate_metadata_json_file(path: &Path, report: &mut ValidationReport) -> Re
 /// Validate metadata.json content
 fn validate_metadata_json_content(json_content: &str, report: &mut ValidationReport) -> Result<()> {
     match serde_json::from_str::<MzPeakMetadata>(json_content) {
-        Ok(_metadata) => {
+        Ok(metadata) => {
             report.add_check(ValidationCheck::ok("metadata.json valid JSON"));
+            check_run_timestamps(&metadata, report);
         }
         Err(e) => {
             report.add_check(ValidationCheck::failed(
@@ -176,6 +196,61 @@ fn validate_metadata_json_content(json_content: &str, report: &mut ValidationRep
     Ok(())
 }
 
+/// Warn about run start/end times that are unparseable, zone-less or whose
+/// day and month could be swapped
+pub(super) fn check_run_timestamps(metadata: &MzPeakMetadata, report: &mut ValidationReport) {
+    let Some(run) = &metadata.run_parameters else {
+        return;
+    };
+    let mut all_unambiguous = true;
+    for (label, time, original_key) in [
+        ("Run start time", &run.start_time, "original_start_time"),
+        ("Run end time", &run.end_time, "original_end_time"),
+    ] {
+        let Some(text) = time else {
+            continue;
+        };
+        // Normalized timestamps inherit the ambiguity of the text they came from
+        let original = run.vendor_params.get(original_key).unwrap_or(text);
+        let Some(parsed) = parse_timestamp(original, TimestampZone::Utc) else {
+            all_unambiguous = false;
+            report.add_check(ValidationCheck::warning(
+                label,
+                format!("Unrecognized timestamp '{}'", original),
+            ));
+            continue;
+        };
+        if parsed.is_ambiguous() {
+            all_unambiguous = false;
+            let mut reasons = Vec::new();
+            if parsed.zone_assumed {
+                reasons.push("has no UTC offset");
+            }
+            if parsed.day_month_ambiguous {
+                reasons.push("could have day and month swapped");
+            }
+            report.add_check(ValidationCheck::warning(
+                label,
+                format!("Ambiguous timestamp '{}': {}", original, reasons.join(" and ")),
+            ));
+        }
+    }
+
+    if let (Some(start), Some(end)) = (run.start_datetime(), run.end_datetime()) {
+        if end < start {
+            all_unambiguous = false;
+            report.add_check(ValidationCheck::warning(
+                "Run time order",
+                format!("Run ends ({}) before it starts ({})", end, start),
+            ));
+        }
+    }
+
+    if all_unambiguous && (run.start_time.is_some() || run.end_time.is_some()) {
+        report.add_check(ValidationCheck::ok("Run timestamps unambiguous"));
+    }
+}
+
 fn read_parquet_kv_metadata(
     source: &ParquetSource,
 ) -> Result<Option<HashMap<String, String>>> {
@@ -185,6 +260,7 @@ fn read_parquet_kv_metadata(
             let reader = SerializedFileReader::new(file)?;
             reader.metadata().clone()
         }
+        #[cfg(feature = "container")]
         ParquetSource::ZipEntry { zip_path, entry_name } => {
             let reader = ZipEntryChunkReader::new(zip_path, entry_name)?;
             let reader = SerializedFileReader::new(reader)?;
@@ -84,6 +84,7 @@ pub(crate) enum ParquetSource {
     /// Direct Parquet file path
     FilePath(std::path::PathBuf),
     /// ZIP container entry (uncompressed)
+    #[cfg(feature = "container")]
     ZipEntry {
         zip_path: std::path::PathBuf,
         entry_name: String,
@@ -137,4 +138,99 @@ mod tests {
         assert!(output.contains("✗"));
         assert!(output.contains("1 passed, 1 warnings, 1 failed"));
     }
+
+    #[test]
+    fn test_validation_report_json() -> Result<(), serde_json::Error> {
+        let mut report = ValidationReport::new("test.mzpeak");
+        report.add_check(ValidationCheck::ok("Test check 1"));
+        report.add_check(ValidationCheck::failed("Test check 2", "This failed"));
+
+        let json = serde_json::to_value(&report)?;
+        assert_eq!(json["file_path"], "test.mzpeak");
+        assert_eq!(json["checks"][0]["status"], "ok");
+        assert!(json["checks"][0].get("message").is_none());
+        assert_eq!(json["checks"][1]["name"], "Test check 2");
+        assert_eq!(json["checks"][1]["status"], "failed");
+        assert_eq!(json["checks"][1]["message"], "This failed");
+
+        let parsed: ValidationReport = serde_json::from_value(json)?;
+        assert_eq!(parsed, report);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rt_order_checks() {
+        use crate::ingest::RtOrderTracker;
+
+        let check = |retention_times: &[f32]| {
+            let mut rt_order = RtOrderTracker::new(0.5);
+            for rt in retention_times {
+                rt_order.observe(*rt);
+            }
+            let mut report = ValidationReport::new("test.mzpeak");
+            data::report_rt_order(&mut report, "Retention time order", &rt_order);
+            report.checks.remove(0).status
+        };
+
+        assert_eq!(check(&[1.0, 2.0, 2.0, 3.0]), CheckStatus::Ok);
+        assert!(matches!(
+            check(&[1.0, 2.0, 1.8, 3.0]),
+            CheckStatus::Warning(message) if message.contains("benign jitter")
+        ));
+        assert!(matches!(
+            check(&[1.0, 2.0, 1.8, 3.0, 0.5]),
+            CheckStatus::Failed(message) if message.contains("out of order")
+        ));
+    }
+
+    #[cfg(feature = "writer")]
+    #[test]
+    fn test_v2_container_passes_data_checks() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::dataset::MzPeakDatasetWriterV2;
+        use crate::schema::manifest::Modality;
+        use crate::writer::{PeakArraysV2, SpectrumMetadata, SpectrumV2};
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("v2.mzpeak");
+        let mut writer = MzPeakDatasetWriterV2::new(&path, Modality::LcMs, None)?;
+        for id in 0..4u32 {
+            let metadata = if id % 2 == 0 {
+                SpectrumMetadata::new_ms1(id, Some(id as i32 + 1), id as f32, 1, 2)
+            } else {
+                SpectrumMetadata::new_ms2(id, Some(id as i32 + 1), id as f32, 1, 2, 500.0)
+            };
+            let peaks = PeakArraysV2::new(vec![100.0, 200.0], vec![10.0, 20.0]);
+            writer.write_spectrum(&SpectrumV2::new(metadata, peaks))?;
+        }
+        writer.close()?;
+
+        // UInt8 ms_level and UInt32 spectrum_id columns are read as valid
+        let report = validate_mzpeak_file(&path)?;
+        assert!(!report.has_failures(), "{}", report);
+        let output = report.to_string();
+        assert!(output.contains("V2 MS level values >= 1 (sampled 4 rows)"));
+        assert!(output.contains("V2 spectrum_id values non-negative (sampled 8 rows)"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_timestamp_warnings() {
+        let mut run = crate::metadata::RunParameters::new();
+        run.start_time = Some("03/04/2024 10:30:00".to_string());
+        run.end_time = Some("2024-03-04T09:00:00Z".to_string());
+        let mut metadata = crate::metadata::MzPeakMetadata::new();
+        metadata.run_parameters = Some(run);
+
+        let mut report = ValidationReport::new("test.mzpeak");
+        metadata::check_run_timestamps(&metadata, &mut report);
+        let output = report.to_string();
+        assert!(output.contains("has no UTC offset and could have day and month swapped"));
+        assert!(output.contains("Run ends"));
+
+        let run = metadata.run_parameters.as_mut().expect("run parameters");
+        run.start_time = Some("2024-03-04T08:00:00+01:00".to_string());
+        let mut report = ValidationReport::new("test.mzpeak");
+        metadata::check_run_timestamps(&metadata, &mut report);
+        assert!(report.to_string().contains("Run timestamps unambiguous"));
+    }
 }
@@ -2,9 +2,14 @@ use std::fmt;
 
 #[cfg(feature = "colorized_output")]
 use console::style;
+use serde::{Deserialize, Serialize};
 
 /// Validation check result status
-#[derive(Debug, Clone)]
+///
+/// Serialized as `{"status": "ok"}`, `{"status": "warning", "message": ...}`
+/// or `{"status": "failed", "message": ...}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", content = "message", rename_all = "lowercase")]
 pub enum CheckStatus {
     /// Check passed
     Ok,
@@ -25,11 +30,12 @@ impl CheckStatus {
 }
 
 /// Individual validation check result
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ValidationCheck {
     /// Name of the validation check
     pub name: String,
-    /// Result status of the check
+    /// Result status of the check, flattened into the check object
+    #[serde(flatten)]
     pub status: CheckStatus,
 }
 
@@ -57,7 +63,7 @@ impl ValidationCheck {
 }
 
 /// Complete validation report for an mzPeak file
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ValidationReport {
     /// List of individual validation check results
     pub checks: Vec<ValidationCheck>,
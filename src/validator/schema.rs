@@ -4,6 +4,7 @@ use anyhow::Result;
 use arrow::datatypes::DataType;
 use parquet::file::reader::{FileReader, SerializedFileReader};
 
+#[cfg(feature = "container")]
 use crate::reader::ZipEntryChunkReader;
 use crate::schema::{columns, create_mzpeak_schema, create_peaks_schema_v2, spectra_columns};
 
@@ -48,6 +49,17 @@ fn perform_schema_validation(
     // Create expected schema
     let expected_schema = create_mzpeak_schema();
 
+    // Writers enforcing sorted m/z store it as Int64 deltas in `mz_delta`
+    let mz_column = if schema_descriptor
+        .columns()
+        .iter()
+        .any(|col| col.name() == columns::MZ_DELTA)
+    {
+        (columns::MZ_DELTA, DataType::Int64)
+    } else {
+        (columns::MZ, DataType::Float64)
+    };
+
     // Check that all REQUIRED columns exist with correct types
     let required_columns = vec![
         (columns::SPECTRUM_ID, DataType::Int64),
@@ -55,7 +67,7 @@ fn perform_schema_validation(
         (columns::MS_LEVEL, DataType::Int16),
         (columns::RETENTION_TIME, DataType::Float32),
         (columns::POLARITY, DataType::Int8),
-        (columns::MZ, DataType::Float64),
+        mz_column,
         (columns::INTENSITY, DataType::Float32),
     ];
 
@@ -366,6 +378,7 @@ fn read_parquet_metadata(
             let reader = SerializedFileReader::new(file)?;
             Ok(reader.metadata().clone())
         }
+        #[cfg(feature = "container")]
         ParquetSource::ZipEntry { zip_path, entry_name } => {
             let reader = ZipEntryChunkReader::new(zip_path, entry_name)?;
             let reader = SerializedFileReader::new(reader)?;
@@ -1,14 +1,20 @@
 use std::fs::File;
-use std::io::{BufReader, Read};
+#[cfg(feature = "container")]
+use std::io::BufReader;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
+#[cfg(feature = "container")]
+use anyhow::Context;
+use anyhow::Result;
 use parquet::file::reader::SerializedFileReader;
+#[cfg(feature = "container")]
 use zip::ZipArchive;
 
-use crate::dataset::MZPEAK_V2_MIMETYPE;
+#[cfg(feature = "container")]
 use crate::reader::ZipEntryChunkReader;
-use crate::schema::MZPEAK_MIMETYPE;
+#[cfg(feature = "container")]
+use crate::schema::{MZPEAK_MIMETYPE, MZPEAK_V2_MIMETYPE};
 
 use super::{ParquetSource, SchemaVersion, ValidationCheck, ValidationError, ValidationReport, ValidationTarget};
 
@@ -49,6 +55,7 @@ pub(crate) fn check_structure(path: &Path, report: &mut ValidationReport) -> Res
 }
 
 /// Check if a file is a ZIP archive
+#[cfg(feature = "container")]
 pub(crate) fn is_zip_file(path: &Path) -> bool {
     if let Ok(file) = File::open(path) {
         if ZipArchive::new(file).is_ok() {
@@ -58,6 +65,16 @@ pub(crate) fn is_zip_file(path: &Path) -> bool {
     false
 }
 
+/// Check if a file starts with a ZIP local file header
+#[cfg(not(feature = "container"))]
+pub(crate) fn is_zip_file(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut magic))
+        .is_ok()
+        && magic == *b"PK\x03\x04"
+}
+
 /// Validate directory bundle structure
 fn validate_directory_bundle(path: &Path, report: &mut ValidationReport) -> Result<ValidationTarget> {
     // Detect schema version by checking for manifest.json (v2.0) or metadata.json only (v1.0)
@@ -181,7 +198,20 @@ fn validate_directory_bundle(path: &Path, report: &mut ValidationReport) -> Resu
     })
 }
 
+/// ZIP containers need the `container` feature
+#[cfg(not(feature = "container"))]
+fn validate_zip_container(_path: &Path, report: &mut ValidationReport) -> Result<ValidationTarget> {
+    report.add_check(ValidationCheck::failed(
+        "ZIP structure",
+        "This build lacks the `container` feature",
+    ));
+    anyhow::bail!(ValidationError::StructureError(
+        "ZIP containers need the `container` feature".to_string()
+    ));
+}
+
 /// Validate ZIP container structure with zero-extraction
+#[cfg(feature = "container")]
 fn validate_zip_container(path: &Path, report: &mut ValidationReport) -> Result<ValidationTarget> {
     let file = File::open(path)?;
     let mut archive = ZipArchive::new(BufReader::new(file))?;
@@ -0,0 +1,153 @@
+//! Column buffer reuse for merged peak batches
+//!
+//! [`MzPeakWriter::write_spectra_owned`](super::MzPeakWriter::write_spectra_owned)
+//! merges every batch of spectra into one set of column vectors, which are
+//! handed to Arrow without copying. Once the batch has been encoded the
+//! Parquet writer no longer references those buffers, so they are taken back
+//! out of the arrays, cleared, and reused for the next merge instead of being
+//! freed and allocated again. Buffers that are still shared (e.g. the zero
+//! buffer backing all-null columns) are simply dropped.
+
+use arrow::array::{Array, ArrayRef, ArrowPrimitiveType, AsArray};
+use arrow::datatypes::{
+    DataType, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type,
+};
+use arrow::record_batch::RecordBatch;
+
+/// Free list of cleared vectors of one element type
+#[derive(Debug)]
+pub(super) struct BufferStack<T> {
+    free: Vec<Vec<T>>,
+}
+
+impl<T> Default for BufferStack<T> {
+    fn default() -> Self {
+        Self { free: Vec::new() }
+    }
+}
+
+impl<T> BufferStack<T> {
+    /// Take an empty vector with room for at least `capacity` elements
+    pub(super) fn take(&mut self, capacity: usize) -> Vec<T> {
+        match self.free.pop() {
+            Some(mut buf) => {
+                buf.reserve(capacity);
+                buf
+            }
+            None => Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Return a vector for reuse
+    pub(super) fn give(&mut self, mut buf: Vec<T>) {
+        if buf.capacity() > 0 {
+            buf.clear();
+            self.free.push(buf);
+        }
+    }
+
+    /// Number of vectors ready for reuse
+    #[cfg(test)]
+    pub(super) fn available(&self) -> usize {
+        self.free.len()
+    }
+}
+
+/// Reusable column buffers, one free list per native type
+#[derive(Debug, Default)]
+pub(super) struct ColumnBufferPool {
+    pub(super) f64s: BufferStack<f64>,
+    pub(super) f32s: BufferStack<f32>,
+    pub(super) i64s: BufferStack<i64>,
+    pub(super) i32s: BufferStack<i32>,
+    pub(super) i16s: BufferStack<i16>,
+    pub(super) i8s: BufferStack<i8>,
+    /// Validity vectors, reused when a column ends up all-valid or all-null
+    pub(super) bools: BufferStack<bool>,
+}
+
+impl ColumnBufferPool {
+    /// Take back the value buffers of a written batch
+    pub(super) fn reclaim(&mut self, batch: RecordBatch) {
+        let columns: Vec<ArrayRef> = batch.columns().to_vec();
+        drop(batch);
+
+        for column in columns {
+            match column.data_type() {
+                DataType::Float64 => reclaim_into::<Float64Type>(column, &mut self.f64s),
+                DataType::Float32 => reclaim_into::<Float32Type>(column, &mut self.f32s),
+                DataType::Int64 => reclaim_into::<Int64Type>(column, &mut self.i64s),
+                DataType::Int32 => reclaim_into::<Int32Type>(column, &mut self.i32s),
+                DataType::Int16 => reclaim_into::<Int16Type>(column, &mut self.i16s),
+                DataType::Int8 => reclaim_into::<Int8Type>(column, &mut self.i8s),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Move the values of `column` into `stack` if this is their only owner
+fn reclaim_into<T: ArrowPrimitiveType>(column: ArrayRef, stack: &mut BufferStack<T::Native>) {
+    let Some(array) = column.as_primitive_opt::<T>().cloned() else {
+        return;
+    };
+    drop(column);
+    let (_, values, _) = array.into_parts();
+    if let Ok(buf) = values.into_inner().into_vec::<T::Native>() {
+        stack.give(buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::{Float64Array, Int16Array};
+    use arrow::buffer::ScalarBuffer;
+    use arrow::datatypes::{Field, Schema};
+    use parquet::arrow::ArrowWriter;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_reclaim_after_parquet_write() {
+        let mz = vec![100.0f64, 200.0, 300.0];
+        let mz_ptr = mz.as_ptr();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("mz", DataType::Float64, false),
+            Field::new("ms_level", DataType::Int16, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Float64Array::new(ScalarBuffer::from(mz), None)),
+                Arc::new(Int16Array::new(ScalarBuffer::from(vec![1i16, 1, 2]), None)),
+            ],
+        )
+        .unwrap();
+
+        let mut writer = ArrowWriter::try_new(Vec::new(), schema, None).unwrap();
+        writer.write(&batch).unwrap();
+
+        let mut pool = ColumnBufferPool::default();
+        pool.reclaim(batch);
+        assert_eq!(pool.f64s.available(), 1);
+        assert_eq!(pool.i16s.available(), 1);
+
+        // The reclaimed allocation comes back cleared
+        let reused = pool.f64s.take(3);
+        assert!(reused.is_empty());
+        assert_eq!(reused.as_ptr(), mz_ptr);
+        writer.close().unwrap();
+    }
+
+    #[test]
+    fn test_shared_buffers_are_not_reclaimed() {
+        let values: ArrayRef = Arc::new(Float64Array::from(vec![1.0, 2.0]));
+        let schema = Arc::new(Schema::new(vec![Field::new("x", DataType::Float64, false)]));
+        let batch = RecordBatch::try_new(schema, vec![values.clone()]).unwrap();
+
+        let mut pool = ColumnBufferPool::default();
+        pool.reclaim(batch);
+        assert_eq!(pool.f64s.available(), 0);
+        assert_eq!(values.len(), 2);
+    }
+}
@@ -0,0 +1,141 @@
+//! Per-spectrum content checksums
+//!
+//! With [`SpectraWriterConfig::spectrum_checksums`](super::SpectraWriterConfig::spectrum_checksums)
+//! the spectra table of a v2.0 dataset stores an XXH3-64 hash of each
+//! spectrum's content in the `spectrum_checksum` column. Identical spectra
+//! hash identically regardless of their position in the run, so the column
+//! supports deduplication across merged datasets, spot-checks of stored
+//! peaks (see `MzPeakReader::verify_spectrum_checksum`) and diffing the
+//! output of two converter versions.
+//!
+//! ## Hashed Bytes
+//!
+//! XXH3-64 with seed 0 over, in order (all little-endian):
+//!
+//! | Field | Encoding |
+//! |-------|----------|
+//! | ms_level | u8 |
+//! | polarity | i8 |
+//! | retention_time | f32 bits |
+//! | precursor_mz | u8 presence flag, then f64 bits if present |
+//! | precursor_charge | u8 presence flag, then i8 if present |
+//! | peak count | u64 |
+//! | mz | f64 bits per peak |
+//! | intensity | f32 bits per peak |
+//!
+//! The spectrum ID and scan number are left out on purpose: they change when
+//! runs are merged or renumbered while the spectrum itself does not.
+
+use std::hash::Hasher;
+
+use twox_hash::XxHash3_64;
+
+use super::types::SpectrumMetadata;
+
+/// XXH3-64 content checksum of a spectrum
+///
+/// `mz` and `intensity` are the stored peak arrays. See the
+/// [module documentation](self) for the exact bytes hashed.
+///
+/// # Example
+///
+/// ```
+/// use mzpeak::writer::{spectrum_checksum, SpectrumMetadata};
+///
+/// let mz = [100.0, 200.0];
+/// let intensity = [10.0, 20.0];
+/// let first = SpectrumMetadata::new_ms1(0, Some(1), 60.0, 1, 2);
+/// let renumbered = SpectrumMetadata::new_ms1(7, Some(8), 60.0, 1, 2);
+/// assert_eq!(
+///     spectrum_checksum(&first, &mz, &intensity),
+///     spectrum_checksum(&renumbered, &mz, &intensity)
+/// );
+/// ```
+pub fn spectrum_checksum(metadata: &SpectrumMetadata, mz: &[f64], intensity: &[f32]) -> u64 {
+    // Fields are fed to the streaming hasher as they are encoded, so no copy
+    // of the peaks is made
+    let mut hasher = XxHash3_64::with_seed(0);
+    hasher.write(&[metadata.ms_level]);
+    hasher.write(&metadata.polarity.to_le_bytes());
+    hasher.write(&metadata.retention_time.to_bits().to_le_bytes());
+    match metadata.precursor_mz {
+        Some(precursor_mz) => {
+            hasher.write(&[1]);
+            hasher.write(&precursor_mz.to_bits().to_le_bytes());
+        }
+        None => hasher.write(&[0]),
+    }
+    match metadata.precursor_charge {
+        Some(charge) => {
+            hasher.write(&[1]);
+            hasher.write(&charge.to_le_bytes());
+        }
+        None => hasher.write(&[0]),
+    }
+    hasher.write(&(mz.len() as u64).to_le_bytes());
+    for value in mz {
+        hasher.write(&value.to_bits().to_le_bytes());
+    }
+    for value in intensity {
+        hasher.write(&value.to_bits().to_le_bytes());
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksum_covers_content() {
+        let mz = [100.0, 200.0, 300.0];
+        let intensity = [1.0, 2.0, 3.0];
+        let metadata = SpectrumMetadata::new_ms2(0, Some(1), 60.0, 1, 3, 450.5);
+        let checksum = spectrum_checksum(&metadata, &mz, &intensity);
+
+        // Stable across calls
+        assert_eq!(checksum, spectrum_checksum(&metadata, &mz, &intensity));
+
+        // Peaks
+        assert_ne!(
+            checksum,
+            spectrum_checksum(&metadata, &[100.0, 200.0, 300.5], &intensity)
+        );
+        assert_ne!(
+            checksum,
+            spectrum_checksum(&metadata, &mz, &[1.0, 2.0, 4.0])
+        );
+
+        // Key metadata
+        let mut changed = metadata.clone();
+        changed.retention_time = 60.5;
+        assert_ne!(checksum, spectrum_checksum(&changed, &mz, &intensity));
+        let mut changed = metadata.clone();
+        changed.polarity = -1;
+        assert_ne!(checksum, spectrum_checksum(&changed, &mz, &intensity));
+        let mut changed = metadata.clone();
+        changed.precursor_mz = None;
+        assert_ne!(checksum, spectrum_checksum(&changed, &mz, &intensity));
+
+        // Exactly the documented bytes
+        let mut bytes = vec![2u8, 1];
+        bytes.extend_from_slice(&60.0f32.to_bits().to_le_bytes());
+        bytes.push(1);
+        bytes.extend_from_slice(&450.5f64.to_bits().to_le_bytes());
+        bytes.push(0);
+        bytes.extend_from_slice(&3u64.to_le_bytes());
+        for value in mz {
+            bytes.extend_from_slice(&value.to_bits().to_le_bytes());
+        }
+        for value in intensity {
+            bytes.extend_from_slice(&value.to_bits().to_le_bytes());
+        }
+        assert_eq!(checksum, XxHash3_64::oneshot(&bytes));
+
+        // Not the identifiers
+        let mut renumbered = metadata.clone();
+        renumbered.spectrum_id = 42;
+        renumbered.scan_number = Some(43);
+        assert_eq!(checksum, spectrum_checksum(&renumbered, &mz, &intensity));
+    }
+}
@@ -1,4 +1,6 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 use parquet::basic::{Compression, Encoding, ZstdLevel};
 use parquet::file::properties::{EnabledStatistics, WriterProperties};
@@ -7,6 +9,8 @@ use parquet::schema::types::ColumnPath;
 
 use crate::schema::columns;
 
+use super::progress::WriteProgress;
+
 /// Compression options for mzPeak files
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CompressionType {
@@ -75,6 +79,32 @@ pub struct WriterConfig {
     /// Higher values reduce backpressure but use more memory.
     /// Default: 8
     pub async_buffer_capacity: usize,
+
+    /// Directory for temp files staged before packaging into a container.
+    /// `None` uses `$MZPEAK_TMPDIR` or the system temp directory
+    /// (see [`crate::dataset::default_temp_dir`]).
+    pub temp_dir: Option<PathBuf>,
+
+    /// Write datasets to `temp_dir` and copy them to the destination on
+    /// close. Recommended for network shares. Default: false
+    pub stage_locally: bool,
+
+    /// Require m/z to be non-negative and non-decreasing within each spectrum,
+    /// and store it as per-spectrum deltas of its bit patterns in an Int64
+    /// `mz_delta` column (see [`crate::schema::mz_delta`]). Centroided m/z
+    /// compresses markedly better this way; [`crate::reader`] restores the
+    /// Float64 `mz` column bit for bit. Applies to v1 peak files.
+    /// Default: false
+    pub enforce_sorted_mz: bool,
+
+    /// Store an XXH3 content checksum per spectrum in the spectra table of
+    /// v2.0 datasets (see [`crate::writer::spectrum_checksum`]).
+    /// Default: false
+    pub spectrum_checksums: bool,
+
+    /// Shared progress counters the writer reports into. `None` gives each
+    /// writer its own, available from `MzPeakWriter::progress`
+    pub progress: Option<Arc<WriteProgress>>,
 }
 
 impl Default for WriterConfig {
@@ -97,6 +127,11 @@ impl Default for WriterConfig {
             use_byte_stream_split: true,
             // Buffer 8 batches for async writer pipeline
             async_buffer_capacity: 8,
+            temp_dir: None,
+            stage_locally: false,
+            enforce_sorted_mz: false,
+            spectrum_checksums: false,
+            progress: None,
         }
     }
 }
@@ -113,6 +148,11 @@ impl WriterConfig {
             max_peaks_per_file: Some(100_000_000),
             use_byte_stream_split: true,
             async_buffer_capacity: 8,
+            temp_dir: None,
+            stage_locally: false,
+            enforce_sorted_mz: false,
+            spectrum_checksums: false,
+            progress: None,
         }
     }
 
@@ -127,6 +167,11 @@ impl WriterConfig {
             max_peaks_per_file: Some(50_000_000),
             use_byte_stream_split: true,
             async_buffer_capacity: 16, // Larger buffer for fast writes
+            temp_dir: None,
+            stage_locally: false,
+            enforce_sorted_mz: false,
+            spectrum_checksums: false,
+            progress: None,
         }
     }
 
@@ -161,6 +206,17 @@ impl WriterConfig {
             .set_statistics_enabled(statistics)
             .set_max_row_group_size(self.row_group_size);
 
+        // Page-level statistics write a column index, which lets readers skip
+        // pages inside a row group on narrow m/z and retention time queries
+        if self.write_statistics {
+            for col in [columns::MZ, columns::RETENTION_TIME] {
+                builder = builder.set_column_statistics_enabled(
+                    ColumnPath::new(vec![col.to_string()]),
+                    EnabledStatistics::Page,
+                );
+            }
+        }
+
         // Enable dictionary encoding for columns that benefit from it (repeated metadata)
         // These columns have the same value for all peaks in a spectrum, so dictionary
         // encoding + RLE will achieve excellent compression.
@@ -215,6 +271,16 @@ impl WriterConfig {
             }
         }
 
+        // Sorted m/z is stored as small non-negative deltas: their high bytes
+        // are zero, so BYTE_STREAM_SPLIT hands ZSTD long runs to compress
+        if self.enforce_sorted_mz {
+            let path = ColumnPath::new(vec![columns::MZ_DELTA.to_string()]);
+            builder = builder.set_column_dictionary_enabled(path.clone(), false);
+            if self.use_byte_stream_split {
+                builder = builder.set_column_encoding(path, Encoding::BYTE_STREAM_SPLIT);
+            }
+        }
+
         // Add key-value metadata
         let kv_metadata: Vec<KeyValue> = metadata
             .iter()
@@ -260,6 +326,14 @@ impl WriterConfig {
             .set_statistics_enabled(statistics)
             .set_max_row_group_size(self.row_group_size);
 
+        // Page-level m/z statistics for pruning pages on narrow m/z queries
+        if self.write_statistics {
+            builder = builder.set_column_statistics_enabled(
+                ColumnPath::new(vec!["mz".to_string()]),
+                EnabledStatistics::Page,
+            );
+        }
+
         // Disable dictionary encoding for all columns (high-cardinality data)
         // spectrum_id: unique per peak group, mz/intensity/ion_mobility: unique per peak
         builder = builder.set_dictionary_enabled(false);
@@ -330,6 +404,14 @@ impl WriterConfig {
             .set_statistics_enabled(statistics)
             .set_max_row_group_size(self.row_group_size);
 
+        // Page-level retention time statistics for pruning pages on RT queries
+        if self.write_statistics {
+            builder = builder.set_column_statistics_enabled(
+                ColumnPath::new(vec!["retention_time".to_string()]),
+                EnabledStatistics::Page,
+            );
+        }
+
         // Disable dictionary encoding by default (most columns are unique per spectrum)
         builder = builder.set_dictionary_enabled(false);
 
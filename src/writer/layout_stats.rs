@@ -0,0 +1,152 @@
+//! Row group summaries of the v2 peaks table
+//!
+//! The peaks writer hands every spectrum it buffers to a
+//! [`LayoutStatsCollector`], which follows the row group boundaries the
+//! Parquet writer will cut (every `row_group_size` rows) and summarizes each
+//! row group as it fills.
+
+use crate::schema::manifest::{LayoutStats, RowGroupStats};
+
+/// Number of intensity quantiles kept per row group (minimum, deciles, maximum)
+const INTENSITY_QUANTILES: usize = 11;
+
+/// Builds [`LayoutStats`] while peaks are written
+#[derive(Debug)]
+pub(crate) struct LayoutStatsCollector {
+    row_group_size: usize,
+    completed: Vec<RowGroupStats>,
+    current: RowGroupAccumulator,
+}
+
+/// Contents of the row group being filled
+#[derive(Debug, Default)]
+struct RowGroupAccumulator {
+    first_spectrum_id: u32,
+    last_spectrum_id: u32,
+    spectrum_count: u64,
+    peaks_per_spectrum: Vec<u64>,
+    intensities: Vec<f32>,
+}
+
+impl LayoutStatsCollector {
+    pub(crate) fn new(row_group_size: usize) -> Self {
+        Self {
+            row_group_size: row_group_size.max(1),
+            completed: Vec::new(),
+            current: RowGroupAccumulator::default(),
+        }
+    }
+
+    /// Record the peaks of one spectrum, in write order
+    pub(crate) fn push_spectrum(&mut self, spectrum_id: u32, intensities: &[f32]) {
+        let mut remaining = intensities;
+        while !remaining.is_empty() {
+            let room = self.row_group_size - self.current.intensities.len();
+            let (segment, rest) = remaining.split_at(room.min(remaining.len()));
+            self.current.push_segment(spectrum_id, segment);
+            if self.current.intensities.len() == self.row_group_size {
+                let full = std::mem::take(&mut self.current);
+                self.completed.push(full.summarize());
+            }
+            remaining = rest;
+        }
+    }
+
+    /// Summaries of all row groups written so far, including a partial last one
+    pub(crate) fn layout_stats(&self) -> LayoutStats {
+        let mut row_groups = self.completed.clone();
+        if !self.current.intensities.is_empty() {
+            row_groups.push(self.current.summarize());
+        }
+        LayoutStats {
+            row_group_size: self.row_group_size as u64,
+            row_groups,
+        }
+    }
+}
+
+impl RowGroupAccumulator {
+    fn push_segment(&mut self, spectrum_id: u32, intensities: &[f32]) {
+        if self.spectrum_count == 0 {
+            self.first_spectrum_id = spectrum_id;
+        }
+        self.last_spectrum_id = spectrum_id;
+        self.spectrum_count += 1;
+
+        let bin = RowGroupStats::peaks_per_spectrum_bin(intensities.len() as u64);
+        if self.peaks_per_spectrum.len() <= bin {
+            self.peaks_per_spectrum.resize(bin + 1, 0);
+        }
+        self.peaks_per_spectrum[bin] += 1;
+        self.intensities.extend_from_slice(intensities);
+    }
+
+    fn summarize(&self) -> RowGroupStats {
+        RowGroupStats {
+            peak_count: self.intensities.len() as u64,
+            spectrum_count: self.spectrum_count,
+            first_spectrum_id: self.first_spectrum_id,
+            last_spectrum_id: self.last_spectrum_id,
+            peaks_per_spectrum: self.peaks_per_spectrum.clone(),
+            intensity_deciles: quantiles(&self.intensities),
+        }
+    }
+}
+
+/// Minimum, deciles and maximum of `values` (nearest rank)
+fn quantiles(values: &[f32]) -> Vec<f32> {
+    if values.is_empty() {
+        return Vec::new();
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable_by(f32::total_cmp);
+    let last = sorted.len() - 1;
+    (0..INTENSITY_QUANTILES)
+        .map(|i| sorted[(last * i + (INTENSITY_QUANTILES - 1) / 2) / (INTENSITY_QUANTILES - 1)])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_groups_follow_writer_boundaries() {
+        let mut collector = LayoutStatsCollector::new(10);
+        collector.push_spectrum(0, &[1.0; 4]);
+        collector.push_spectrum(1, &[2.0; 8]);
+        collector.push_spectrum(2, &[3.0; 1]);
+
+        let stats = collector.layout_stats();
+        assert_eq!(stats.row_group_size, 10);
+        assert_eq!(stats.peak_count(), 13);
+        assert_eq!(stats.row_groups.len(), 2);
+
+        let first = &stats.row_groups[0];
+        assert_eq!(first.peak_count, 10);
+        assert_eq!(first.spectrum_count, 2);
+        assert_eq!((first.first_spectrum_id, first.last_spectrum_id), (0, 1));
+        // 4 peaks (bin 2) and the first 6 peaks of spectrum 1 (bin 2)
+        assert_eq!(first.peaks_per_spectrum, vec![0, 0, 2]);
+        assert_eq!(first.intensity_deciles.len(), INTENSITY_QUANTILES);
+        assert_eq!(first.intensity_deciles[0], 1.0);
+        assert_eq!(first.max_intensity(), Some(2.0));
+
+        let last = &stats.row_groups[1];
+        assert_eq!(last.peak_count, 3);
+        assert_eq!((last.first_spectrum_id, last.last_spectrum_id), (1, 2));
+        assert_eq!(last.peaks_per_spectrum, vec![1, 1]);
+        assert_eq!(last.mean_peaks_per_spectrum(), 1.5);
+    }
+
+    #[test]
+    fn test_intensity_deciles() {
+        let values: Vec<f32> = (0..=100).rev().map(|v| v as f32).collect();
+        let deciles = quantiles(&values);
+        assert_eq!(
+            deciles,
+            vec![0.0, 10.0, 20.0, 30.0, 40.0, 50.0, 60.0, 70.0, 80.0, 90.0, 100.0]
+        );
+        assert!(quantiles(&[]).is_empty());
+    }
+}
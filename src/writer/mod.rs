@@ -15,30 +15,58 @@
 //!    embedded in the Parquet footer's key_value_metadata.
 //!
 //! 4. **Configurable Compression**: Supports ZSTD (default), Snappy, and uncompressed.
+//!
+//! The writers require the `writer` feature. Without it only the record types
+//! shared with the reader ([`SpectrumArrays`], [`PeakArrays`],
+//! [`SpectrumMetadata`], ...) and [`WriterError`] are compiled.
 
+#[cfg(feature = "writer")]
 mod async_writer;
+#[cfg(feature = "writer")]
+mod buffer_pool;
+mod checksum;
+#[cfg(feature = "writer")]
 mod config;
 mod error;
+#[cfg(feature = "writer")]
+mod layout_stats;
+#[cfg(feature = "writer")]
 mod peaks_writer_v2;
+#[cfg(feature = "writer")]
+mod progress;
+#[cfg(feature = "writer")]
 mod rolling;
+#[cfg(feature = "writer")]
 mod spectra_writer;
+#[cfg(feature = "writer")]
 mod stats;
 mod types;
+#[cfg(feature = "writer")]
 mod writer_impl;
 
 #[cfg(test)]
 mod tests;
 
+#[cfg(feature = "writer")]
 pub use async_writer::AsyncMzPeakWriter;
+pub use checksum::spectrum_checksum;
+#[cfg(feature = "writer")]
 pub use config::{CompressionType, WriterConfig};
 pub use error::WriterError;
+#[cfg(feature = "writer")]
 pub use peaks_writer_v2::{PeaksWriterV2, PeaksWriterV2Config, PeaksWriterV2Stats};
+#[cfg(feature = "writer")]
+pub use progress::{StatsSnapshot, WriteProgress};
+#[cfg(feature = "writer")]
 pub use rolling::{RollingWriter, RollingWriterStats};
+#[cfg(feature = "writer")]
 pub use spectra_writer::{SpectraWriter, SpectraWriterConfig, SpectraWriterStats};
+#[cfg(feature = "writer")]
 pub use stats::WriterStats;
 pub use types::{
     ColumnarBatch, OptionalColumn, OptionalColumnBuf, OwnedColumnarBatch, PeakArrays,
     PeakArraysV2, SpectrumArrays, SpectrumMetadata, SpectrumV2,
 };
+#[cfg(feature = "writer")]
 pub use writer_impl::MzPeakWriter;
 
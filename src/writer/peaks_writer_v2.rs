@@ -30,7 +30,7 @@
 //! ```
 
 use std::collections::HashMap;
-use std::io::{Seek, Write};
+use std::io::Write;
 use std::sync::Arc;
 
 use arrow::array::{ArrayRef, Float32Builder, Float64Builder, UInt32Builder};
@@ -40,11 +40,16 @@ use parquet::basic::{Compression, Encoding, ZstdLevel};
 use parquet::file::properties::{EnabledStatistics, WriterProperties};
 use parquet::format::KeyValue;
 use parquet::schema::types::ColumnPath;
+use serde::{Deserialize, Serialize};
 
+use crate::schema::column_metadata::append_column_key_values;
 use crate::schema::create_peaks_schema_v2_arc;
+use crate::schema::manifest::LayoutStats;
 
 use super::config::CompressionType;
 use super::error::WriterError;
+use super::layout_stats::LayoutStatsCollector;
+use super::progress::{StatsSnapshot, WriteProgress};
 use super::types::PeakArraysV2;
 
 // =============================================================================
@@ -72,6 +77,12 @@ pub struct PeaksWriterV2Config {
 
     /// Optional key-value metadata to include in the file
     pub metadata: HashMap<String, String>,
+
+    /// Shared progress counters to report into (see `WriterConfig::progress`)
+    pub progress: Option<Arc<WriteProgress>>,
+
+    /// Summarize peak density per row group (see [`PeaksWriterV2::layout_stats`])
+    pub layout_stats: bool,
 }
 
 impl Default for PeaksWriterV2Config {
@@ -87,6 +98,8 @@ impl Default for PeaksWriterV2Config {
             // BYTE_STREAM_SPLIT improves compression for floating-point data
             use_byte_stream_split: true,
             metadata: HashMap::new(),
+            progress: None,
+            layout_stats: true,
         }
     }
 }
@@ -114,6 +127,14 @@ impl PeaksWriterV2Config {
             .set_statistics_enabled(statistics)
             .set_max_row_group_size(self.row_group_size);
 
+        // Page-level m/z statistics for pruning pages on narrow m/z queries
+        if self.write_statistics {
+            builder = builder.set_column_statistics_enabled(
+                ColumnPath::new(vec!["mz".to_string()]),
+                EnabledStatistics::Page,
+            );
+        }
+
         // Disable dictionary encoding for all columns (high-cardinality data)
         builder = builder.set_dictionary_enabled(false);
 
@@ -161,7 +182,8 @@ impl PeaksWriterV2Config {
 // =============================================================================
 
 /// Statistics from a completed peaks write operation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PeaksWriterV2Stats {
     /// Number of peaks written
     pub peaks_written: u64,
@@ -282,7 +304,7 @@ impl ColumnBuffers {
 /// let stats = writer.finish()?;
 /// println!("Written: {}", stats);
 /// ```
-pub struct PeaksWriterV2<W: Write + Seek> {
+pub struct PeaksWriterV2<W: Write> {
     writer: ArrowWriter<W>,
     schema: Arc<arrow::datatypes::Schema>,
     row_group_size: usize,
@@ -290,9 +312,14 @@ pub struct PeaksWriterV2<W: Write + Seek> {
     peaks_written: u64,
     spectra_written: u64,
     buffers: ColumnBuffers,
+    progress: Arc<WriteProgress>,
+    /// Output bytes already added to `progress`
+    published_bytes: usize,
+    /// Row group summaries, when enabled
+    layout: Option<LayoutStatsCollector>,
 }
 
-impl<W: Write + Seek + Send> PeaksWriterV2<W> {
+impl<W: Write + Send> PeaksWriterV2<W> {
     fn validate_ion_mobility(&self, peaks: &PeakArraysV2) -> Result<(), WriterError> {
         match (self.has_ion_mobility, peaks.ion_mobility.as_ref()) {
             (true, Some(_)) => Ok(()),
@@ -325,7 +352,8 @@ impl<W: Write + Seek + Send> PeaksWriterV2<W> {
         let schema = create_peaks_schema_v2_arc(has_ion_mobility);
         let props = config.to_writer_properties(has_ion_mobility);
 
-        let arrow_writer = ArrowWriter::try_new(writer, schema.clone(), Some(props))?;
+        let mut arrow_writer = ArrowWriter::try_new(writer, schema.clone(), Some(props))?;
+        append_column_key_values(&mut arrow_writer, &schema);
 
         Ok(Self {
             writer: arrow_writer,
@@ -335,6 +363,11 @@ impl<W: Write + Seek + Send> PeaksWriterV2<W> {
             peaks_written: 0,
             spectra_written: 0,
             buffers: ColumnBuffers::new(has_ion_mobility, config.row_group_size),
+            progress: config.progress.clone().unwrap_or_default(),
+            published_bytes: 0,
+            layout: config
+                .layout_stats
+                .then(|| LayoutStatsCollector::new(config.row_group_size)),
         })
     }
 
@@ -355,8 +388,12 @@ impl<W: Write + Seek + Send> PeaksWriterV2<W> {
 
         self.validate_ion_mobility(peaks)?;
         self.buffers.push_spectrum(spectrum_id, peaks);
+        if let Some(layout) = &mut self.layout {
+            layout.push_spectrum(spectrum_id, &peaks.intensity);
+        }
         self.peaks_written += peaks.len() as u64;
         self.spectra_written += 1;
+        self.progress.record(1, peaks.len() as u64, 0);
 
         // Flush if buffer is full
         if self.buffers.len() >= self.row_group_size {
@@ -382,8 +419,12 @@ impl<W: Write + Seek + Send> PeaksWriterV2<W> {
 
             self.validate_ion_mobility(peaks)?;
             self.buffers.push_spectrum(spectrum_id, peaks);
+            if let Some(layout) = &mut self.layout {
+                layout.push_spectrum(spectrum_id, &peaks.intensity);
+            }
             self.peaks_written += peaks.len() as u64;
             self.spectra_written += 1;
+            self.progress.record(1, peaks.len() as u64, 0);
 
             // Flush if buffer is full
             if self.buffers.len() >= self.row_group_size {
@@ -405,6 +446,11 @@ impl<W: Write + Seek + Send> PeaksWriterV2<W> {
         self.writer.write(&record_batch)?;
         self.buffers.clear();
 
+        let bytes = self.writer.bytes_written();
+        self.progress
+            .record(0, 0, bytes.saturating_sub(self.published_bytes) as u64);
+        self.published_bytes = bytes;
+
         Ok(())
     }
 
@@ -503,6 +549,24 @@ impl<W: Write + Seek + Send> PeaksWriterV2<W> {
         }
     }
 
+    /// Peak density summaries of the row groups written so far
+    ///
+    /// Row groups are cut every `row_group_size` peaks; the last, partial row
+    /// group is included. `None` when disabled in the configuration.
+    pub fn layout_stats(&self) -> Option<LayoutStats> {
+        self.layout.as_ref().map(LayoutStatsCollector::layout_stats)
+    }
+
+    /// Live progress counters, readable from other threads while writing
+    pub fn progress(&self) -> Arc<WriteProgress> {
+        self.progress.clone()
+    }
+
+    /// Running statistics (see [`StatsSnapshot`])
+    pub fn stats_snapshot(&self) -> StatsSnapshot {
+        self.progress.snapshot()
+    }
+
     /// Get the number of peaks written so far.
     pub fn peaks_written(&self) -> u64 {
         self.peaks_written
@@ -0,0 +1,192 @@
+//! Live progress of an open writer
+//!
+//! Writers publish their counters into a [`WriteProgress`] after every
+//! flushed batch. The counters are atomics behind an `Arc`, so a CLI progress
+//! display or a service status endpoint can take [`StatsSnapshot`]s from
+//! another thread while the writer keeps running.
+
+use std::fmt;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Bit pattern marking an unset retention time
+const UNSET_RT: u32 = u32::MAX;
+
+/// Thread-safe running counters of one or more writers
+///
+/// Writers that share a handle (via [`WriterConfig::progress`](super::WriterConfig::progress))
+/// add up into the same counters.
+#[derive(Debug)]
+pub struct WriteProgress {
+    started: Instant,
+    spectra: AtomicU64,
+    peaks: AtomicU64,
+    bytes: AtomicU64,
+    current_rt: AtomicU32,
+    expected_spectra: AtomicU64,
+    expected_rt: AtomicU32,
+}
+
+impl Default for WriteProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WriteProgress {
+    /// Create counters; throughput is measured from this point
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            spectra: AtomicU64::new(0),
+            peaks: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            current_rt: AtomicU32::new(UNSET_RT),
+            expected_spectra: AtomicU64::new(0),
+            expected_rt: AtomicU32::new(UNSET_RT),
+        }
+    }
+
+    /// Number of spectra the run is expected to contain, for completion estimates
+    pub fn set_expected_spectra(&self, count: u64) {
+        self.expected_spectra.store(count, Ordering::Relaxed);
+    }
+
+    /// Retention time (seconds) at which the run ends, for completion
+    /// estimates when the spectrum count is unknown
+    pub fn set_expected_rt(&self, retention_time: f32) {
+        self.expected_rt
+            .store(retention_time.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Add newly written spectra, peaks and output bytes
+    pub(crate) fn record(&self, spectra: u64, peaks: u64, bytes: u64) {
+        self.spectra.fetch_add(spectra, Ordering::Relaxed);
+        self.peaks.fetch_add(peaks, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Retention time (seconds) of the most recently written spectrum
+    pub(crate) fn set_current_rt(&self, retention_time: f32) {
+        if retention_time.is_finite() {
+            self.current_rt
+                .store(retention_time.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// Point-in-time copy of the counters
+    pub fn snapshot(&self) -> StatsSnapshot {
+        let spectra_written = self.spectra.load(Ordering::Relaxed);
+        let current_rt = load_rt(&self.current_rt);
+        let expected_spectra =
+            Some(self.expected_spectra.load(Ordering::Relaxed)).filter(|&count| count > 0);
+
+        let fraction_complete = match (expected_spectra, load_rt(&self.expected_rt), current_rt) {
+            (Some(expected), _, _) => Some(spectra_written as f64 / expected as f64),
+            (None, Some(end), Some(rt)) if end > 0.0 => Some(f64::from(rt / end)),
+            _ => None,
+        }
+        .map(|fraction| fraction.clamp(0.0, 1.0));
+
+        StatsSnapshot {
+            elapsed: self.started.elapsed(),
+            spectra_written,
+            peaks_written: self.peaks.load(Ordering::Relaxed),
+            bytes_written: self.bytes.load(Ordering::Relaxed),
+            current_rt,
+            expected_spectra,
+            fraction_complete,
+        }
+    }
+}
+
+fn load_rt(value: &AtomicU32) -> Option<f32> {
+    match value.load(Ordering::Relaxed) {
+        UNSET_RT => None,
+        bits => Some(f32::from_bits(bits)),
+    }
+}
+
+/// Running statistics of an open writer
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsSnapshot {
+    /// Time since the progress counters were created
+    pub elapsed: Duration,
+    /// Spectra written so far
+    pub spectra_written: u64,
+    /// Peaks written so far
+    pub peaks_written: u64,
+    /// Bytes flushed to the output so far
+    pub bytes_written: u64,
+    /// Retention time (seconds) of the most recent spectrum, if known
+    pub current_rt: Option<f32>,
+    /// Expected number of spectra, if known
+    pub expected_spectra: Option<u64>,
+    /// Completed fraction (0.0 - 1.0), from the expected spectrum count or run length
+    pub fraction_complete: Option<f64>,
+}
+
+impl StatsSnapshot {
+    /// Peaks written per second
+    pub fn peaks_per_second(&self) -> f64 {
+        per_second(self.peaks_written as f64, self.elapsed)
+    }
+
+    /// Spectra written per second
+    pub fn spectra_per_second(&self) -> f64 {
+        per_second(self.spectra_written as f64, self.elapsed)
+    }
+
+    /// Output throughput in MB/s
+    pub fn megabytes_per_second(&self) -> f64 {
+        per_second(self.bytes_written as f64 / 1_000_000.0, self.elapsed)
+    }
+
+    /// Estimated time until completion, extrapolated from the progress so far
+    pub fn estimated_remaining(&self) -> Option<Duration> {
+        let fraction = self.fraction_complete?;
+        if fraction <= 0.0 {
+            return None;
+        }
+        let total = self.elapsed.as_secs_f64() / fraction;
+        Some(Duration::from_secs_f64(
+            (total - self.elapsed.as_secs_f64()).max(0.0),
+        ))
+    }
+}
+
+fn per_second(value: f64, elapsed: Duration) -> f64 {
+    let seconds = elapsed.as_secs_f64();
+    if seconds > 0.0 {
+        value / seconds
+    } else {
+        0.0
+    }
+}
+
+impl fmt::Display for StatsSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.expected_spectra {
+            Some(expected) => write!(f, "{}/{} spectra", self.spectra_written, expected)?,
+            None => write!(f, "{} spectra", self.spectra_written)?,
+        }
+        write!(
+            f,
+            ", {} peaks ({:.0} peaks/s, {:.1} MB/s)",
+            self.peaks_written,
+            self.peaks_per_second(),
+            self.megabytes_per_second()
+        )?;
+        if let Some(rt) = self.current_rt {
+            write!(f, ", RT {:.1} s", rt)?;
+        }
+        if let Some(fraction) = self.fraction_complete {
+            write!(f, ", {:.1}%", fraction * 100.0)?;
+        }
+        if let Some(remaining) = self.estimated_remaining() {
+            let seconds = remaining.as_secs();
+            write!(f, ", ~{}m {:02}s left", seconds / 60, seconds % 60)?;
+        }
+        Ok(())
+    }
+}
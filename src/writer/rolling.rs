@@ -117,7 +117,17 @@ impl RollingWriter {
     /// Write owned spectra, transferring peak buffers into the writer.
     pub fn write_spectra_owned(
         &mut self,
-        spectra: Vec<SpectrumArrays>,
+        mut spectra: Vec<SpectrumArrays>,
+    ) -> Result<(), WriterError> {
+        self.write_spectra_drain(&mut spectra)
+    }
+
+    /// Write and remove all spectra from `spectra`, keeping its capacity.
+    ///
+    /// See [`MzPeakWriter::write_spectra_drain`].
+    pub fn write_spectra_drain(
+        &mut self,
+        spectra: &mut Vec<SpectrumArrays>,
     ) -> Result<(), WriterError> {
         if spectra.is_empty() {
             return Ok(());
@@ -137,12 +147,12 @@ impl RollingWriter {
             if writer.peaks_written() > 0 && writer.peaks_written() + peaks_in_batch > max_peaks {
                 self.rotate_file()?;
                 let writer = self.current_writer.as_mut().unwrap();
-                writer.write_spectra_owned(spectra)?;
+                writer.write_spectra_drain(spectra)?;
             } else {
-                writer.write_spectra_owned(spectra)?;
+                writer.write_spectra_drain(spectra)?;
             }
         } else {
-            writer.write_spectra_owned(spectra)?;
+            writer.write_spectra_drain(spectra)?;
         }
 
         self.total_spectra_written += spectra_len;
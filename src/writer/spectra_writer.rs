@@ -13,6 +13,8 @@
 //! - Fragmentation: collision_energy
 //! - Summary stats: total_ion_current, base_peak_mz, base_peak_intensity, injection_time
 //! - Imaging coords: pixel_x, pixel_y, pixel_z (MSI data only)
+//! - Content checksum: spectrum_checksum (only with
+//!   [`SpectraWriterConfig::spectrum_checksums`])
 //!
 //! ## Usage
 //!
@@ -46,12 +48,14 @@ use parquet::basic::{Compression, Encoding, ZstdLevel};
 use parquet::file::properties::{EnabledStatistics, WriterProperties};
 use parquet::format::KeyValue;
 use parquet::schema::types::ColumnPath;
+use serde::{Deserialize, Serialize};
 
+use crate::schema::column_metadata::append_column_key_values;
 use crate::schema::spectra_columns::{
-    create_spectra_schema_arc, BASE_PEAK_INTENSITY, BASE_PEAK_MZ, COLLISION_ENERGY,
-    INJECTION_TIME, ISOLATION_WINDOW_LOWER, ISOLATION_WINDOW_UPPER, MS_LEVEL, PEAK_OFFSET,
-    POLARITY, PRECURSOR_CHARGE, PRECURSOR_INTENSITY, PRECURSOR_MZ, RETENTION_TIME,
-    SPECTRUM_ID, TOTAL_ION_CURRENT,
+    create_spectra_schema_arc, create_spectra_schema_with_checksum, BASE_PEAK_INTENSITY,
+    BASE_PEAK_MZ, COLLISION_ENERGY, INJECTION_TIME, ISOLATION_WINDOW_LOWER, ISOLATION_WINDOW_UPPER,
+    MS_LEVEL, PEAK_OFFSET, POLARITY, PRECURSOR_CHARGE, PRECURSOR_INTENSITY, PRECURSOR_MZ,
+    RETENTION_TIME, SPECTRUM_CHECKSUM, SPECTRUM_ID, TOTAL_ION_CURRENT,
 };
 
 use super::config::CompressionType;
@@ -83,6 +87,12 @@ pub struct SpectraWriterConfig {
 
     /// Optional key-value metadata to include in the file
     pub metadata: HashMap<String, String>,
+
+    /// Add the `spectrum_checksum` column (see [`super::spectrum_checksum`]).
+    /// Checksums are passed to
+    /// [`SpectraWriter::write_spectrum_metadata_with_checksum`]; rows written
+    /// without one are null. Default: false
+    pub spectrum_checksums: bool,
 }
 
 impl Default for SpectraWriterConfig {
@@ -98,6 +108,7 @@ impl Default for SpectraWriterConfig {
             // 1MB dictionary page limit
             dictionary_page_size_limit: 1024 * 1024,
             metadata: HashMap::new(),
+            spectrum_checksums: false,
         }
     }
 }
@@ -126,6 +137,14 @@ impl SpectraWriterConfig {
             .set_statistics_enabled(statistics)
             .set_max_row_group_size(self.row_group_size);
 
+        // Page-level retention time statistics for pruning pages on RT queries
+        if self.write_statistics {
+            builder = builder.set_column_statistics_enabled(
+                ColumnPath::new(vec![RETENTION_TIME.to_string()]),
+                EnabledStatistics::Page,
+            );
+        }
+
         // Enable dictionary encoding for columns that benefit from it
         // These columns often have repeated values across many spectra
         let dict_columns = [
@@ -149,6 +168,7 @@ impl SpectraWriterConfig {
             PRECURSOR_MZ,
             TOTAL_ION_CURRENT,
             BASE_PEAK_MZ,
+            SPECTRUM_CHECKSUM,
         ];
 
         for col in no_dict_columns {
@@ -202,7 +222,8 @@ impl SpectraWriterConfig {
 // =============================================================================
 
 /// Statistics from a completed spectra write operation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SpectraWriterStats {
     /// Number of spectra written
     pub spectra_written: u64,
@@ -260,6 +281,9 @@ struct ColumnBuffers {
     pixel_x: Vec<Option<u16>>,
     pixel_y: Vec<Option<u16>>,
     pixel_z: Vec<Option<u16>>,
+
+    // Content checksum (nullable, optional column)
+    spectrum_checksum: Vec<Option<u64>>,
 }
 
 impl ColumnBuffers {
@@ -285,6 +309,7 @@ impl ColumnBuffers {
             pixel_x: Vec::with_capacity(capacity),
             pixel_y: Vec::with_capacity(capacity),
             pixel_z: Vec::with_capacity(capacity),
+            spectrum_checksum: Vec::with_capacity(capacity),
         }
     }
 
@@ -317,10 +342,11 @@ impl ColumnBuffers {
         self.pixel_x.clear();
         self.pixel_y.clear();
         self.pixel_z.clear();
+        self.spectrum_checksum.clear();
     }
 
     /// Push a spectrum's metadata into the buffers
-    fn push(&mut self, metadata: &SpectrumMetadata, peak_offset: u64) {
+    fn push(&mut self, metadata: &SpectrumMetadata, peak_offset: u64, checksum: Option<u64>) {
         self.spectrum_id.push(metadata.spectrum_id);
         self.scan_number.push(metadata.scan_number);
         self.ms_level.push(metadata.ms_level);
@@ -341,6 +367,7 @@ impl ColumnBuffers {
         self.pixel_x.push(metadata.pixel_x);
         self.pixel_y.push(metadata.pixel_y);
         self.pixel_z.push(metadata.pixel_z);
+        self.spectrum_checksum.push(checksum);
     }
 }
 
@@ -395,10 +422,15 @@ impl<W: Write + Seek + Send> SpectraWriter<W> {
     ///
     /// A new SpectraWriter ready to write spectrum metadata.
     pub fn new(writer: W, config: &SpectraWriterConfig) -> Result<Self, WriterError> {
-        let schema = create_spectra_schema_arc();
+        let schema = if config.spectrum_checksums {
+            Arc::new(create_spectra_schema_with_checksum())
+        } else {
+            create_spectra_schema_arc()
+        };
         let props = config.to_writer_properties();
 
-        let arrow_writer = ArrowWriter::try_new(writer, schema.clone(), Some(props))?;
+        let mut arrow_writer = ArrowWriter::try_new(writer, schema.clone(), Some(props))?;
+        append_column_key_values(&mut arrow_writer, &schema);
 
         Ok(Self {
             writer: arrow_writer,
@@ -436,7 +468,7 @@ impl<W: Write + Seek + Send> SpectraWriter<W> {
     ///
     /// `Ok(())` on success, or an error if writing fails.
     pub fn write_spectrum_metadata(&mut self, metadata: &SpectrumMetadata) -> Result<(), WriterError> {
-        self.buffers.push(metadata, self.current_peak_offset);
+        self.buffers.push(metadata, self.current_peak_offset, None);
         self.spectra_written += 1;
 
         // Flush if buffer is full
@@ -462,7 +494,30 @@ impl<W: Write + Seek + Send> SpectraWriter<W> {
         metadata: &SpectrumMetadata,
         peak_offset: u64,
     ) -> Result<(), WriterError> {
-        self.buffers.push(metadata, peak_offset);
+        self.buffers.push(metadata, peak_offset, None);
+        self.spectra_written += 1;
+
+        // Flush if buffer is full
+        if self.buffers.len() >= self.row_group_size {
+            self.flush_buffers()?;
+        }
+
+        Ok(())
+    }
+
+    /// Write a single spectrum's metadata with an explicit peak offset and
+    /// its content checksum.
+    ///
+    /// The checksum is only stored if the writer was created with
+    /// [`SpectraWriterConfig::spectrum_checksums`]; compute it with
+    /// [`super::spectrum_checksum`].
+    pub fn write_spectrum_metadata_with_checksum(
+        &mut self,
+        metadata: &SpectrumMetadata,
+        peak_offset: u64,
+        checksum: u64,
+    ) -> Result<(), WriterError> {
+        self.buffers.push(metadata, peak_offset, Some(checksum));
         self.spectra_written += 1;
 
         // Flush if buffer is full
@@ -489,7 +544,7 @@ impl<W: Write + Seek + Send> SpectraWriter<W> {
         I: IntoIterator<Item = (&'a SpectrumMetadata, u64)>,
     {
         for (metadata, peak_offset) in metadata_batch {
-            self.buffers.push(metadata, peak_offset);
+            self.buffers.push(metadata, peak_offset, None);
             self.spectra_written += 1;
 
             // Flush if buffer is full
@@ -519,8 +574,8 @@ impl<W: Write + Seek + Send> SpectraWriter<W> {
     fn build_arrays(&self) -> Result<Vec<ArrayRef>, WriterError> {
         let len = self.buffers.len();
 
-        // Build arrays in schema order (20 columns)
-        let arrays: Vec<ArrayRef> = vec![
+        // Build arrays in schema order (20 columns, plus the checksum if enabled)
+        let mut arrays: Vec<ArrayRef> = vec![
             // 1. spectrum_id (UInt32, required)
             Self::build_u32_array(&self.buffers.spectrum_id),
             // 2. scan_number (Int32, nullable)
@@ -562,6 +617,13 @@ impl<W: Write + Seek + Send> SpectraWriter<W> {
             // 20. pixel_z (UInt16, nullable)
             Self::build_optional_u16_array(&self.buffers.pixel_z, len),
         ];
+        // 21. spectrum_checksum (UInt64, nullable, optional)
+        if self.schema.column_with_name(SPECTRUM_CHECKSUM).is_some() {
+            arrays.push(Self::build_optional_u64_array(
+                &self.buffers.spectrum_checksum,
+                len,
+            ));
+        }
 
         Ok(arrays)
     }
@@ -660,6 +722,16 @@ impl<W: Write + Seek + Send> SpectraWriter<W> {
         Arc::new(builder.finish())
     }
 
+    /// Build an optional UInt64 array
+    #[inline]
+    fn build_optional_u64_array(data: &[Option<u64>], len: usize) -> ArrayRef {
+        let mut builder = UInt64Builder::with_capacity(len);
+        for val in data {
+            builder.append_option(*val);
+        }
+        Arc::new(builder.finish())
+    }
+
     /// Finish writing and close the file.
     ///
     /// This method:
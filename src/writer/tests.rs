@@ -106,6 +106,230 @@ fn test_write_owned_batch_with_optional_columns() -> Result<(), WriterError> {
     Ok(())
 }
 
+fn required_peaks_record_batch(spectrum_ids: Vec<i64>) -> arrow::record_batch::RecordBatch {
+    use arrow::array::{ArrayRef, Float32Array, Float64Array, Int16Array, Int64Array, Int8Array};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use std::sync::Arc;
+
+    let n = spectrum_ids.len();
+    let schema = Schema::new(vec![
+        Field::new("mz", DataType::Float64, false),
+        Field::new("intensity", DataType::Float32, false),
+        Field::new("spectrum_id", DataType::Int64, false),
+        Field::new("scan_number", DataType::Int64, false),
+        Field::new("ms_level", DataType::Int16, false),
+        Field::new("retention_time", DataType::Float32, false),
+        Field::new("polarity", DataType::Int8, false),
+    ]);
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Float64Array::from_iter_values((0..n).map(|i| 100.0 + i as f64))),
+        Arc::new(Float32Array::from(vec![1000.0; n])),
+        Arc::new(Int64Array::from(spectrum_ids.clone())),
+        Arc::new(Int64Array::from(spectrum_ids)),
+        Arc::new(Int16Array::from(vec![1; n])),
+        Arc::new(Float32Array::from(vec![60.0; n])),
+        Arc::new(Int8Array::from(vec![1; n])),
+    ];
+    arrow::record_batch::RecordBatch::try_new(Arc::new(schema), columns).unwrap()
+}
+
+#[test]
+fn test_write_record_batch() -> Result<(), WriterError> {
+    let metadata = MzPeakMetadata::new();
+    let buffer = Cursor::new(Vec::new());
+    let mut writer = MzPeakWriter::new(buffer, &metadata, WriterConfig::default())?;
+
+    // Spectrum 1 continues across the batch boundary and is counted once
+    writer.write_record_batch(required_peaks_record_batch(vec![0, 0, 1]))?;
+    writer.write_record_batch(required_peaks_record_batch(vec![1, 2, 2]))?;
+
+    let stats = writer.finish()?;
+    assert_eq!(stats.peaks_written, 6);
+    assert_eq!(stats.spectra_written, 3);
+
+    Ok(())
+}
+
+#[test]
+fn test_write_record_batch_rejects_interleaved_spectra() -> Result<(), WriterError> {
+    let metadata = MzPeakMetadata::new();
+    let buffer = Cursor::new(Vec::new());
+    let mut writer = MzPeakWriter::new(buffer, &metadata, WriterConfig::default())?;
+
+    // Interleaved or descending within one batch
+    for ids in [vec![0, 1, 0], vec![0, 2, 1]] {
+        assert!(matches!(
+            writer.write_record_batch(required_peaks_record_batch(ids)),
+            Err(WriterError::InvalidData(_))
+        ));
+    }
+
+    // Interleaved across batches
+    writer.write_record_batch(required_peaks_record_batch(vec![0, 0, 1]))?;
+    assert!(matches!(
+        writer.write_record_batch(required_peaks_record_batch(vec![0])),
+        Err(WriterError::InvalidData(_))
+    ));
+
+    // Rejected batches are not written; spectrum 1 can still continue
+    writer.write_record_batch(required_peaks_record_batch(vec![1, 2]))?;
+
+    let stats = writer.finish()?;
+    assert_eq!(stats.peaks_written, 5);
+    assert_eq!(stats.spectra_written, 3);
+
+    Ok(())
+}
+
+#[test]
+fn test_write_record_batch_continues_other_write_paths() -> Result<(), WriterError> {
+    let metadata = MzPeakMetadata::new();
+    let buffer = Cursor::new(Vec::new());
+    let mut writer = MzPeakWriter::new(buffer, &metadata, WriterConfig::default())?;
+
+    let peaks = PeakArrays::new(vec![400.0, 500.0], vec![1.0, 2.0]);
+    writer.write_spectrum_arrays(&SpectrumArrays::new_ms1(3, 4, 60.0, 1, peaks))?;
+
+    // Spectrum 3 continues and is not counted again; IDs below it are rejected
+    assert!(matches!(
+        writer.write_record_batch(required_peaks_record_batch(vec![2])),
+        Err(WriterError::InvalidData(_))
+    ));
+    writer.write_record_batch(required_peaks_record_batch(vec![3, 4]))?;
+
+    let stats = writer.finish()?;
+    assert_eq!(stats.peaks_written, 4);
+    assert_eq!(stats.spectra_written, 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_write_record_batch_rejects_invalid_schema() -> Result<(), WriterError> {
+    use arrow::array::{ArrayRef, Float32Array};
+    use arrow::datatypes::{DataType, Field};
+    use std::sync::Arc;
+
+    let metadata = MzPeakMetadata::new();
+    let buffer = Cursor::new(Vec::new());
+    let mut writer = MzPeakWriter::new(buffer, &metadata, WriterConfig::default())?;
+
+    // Missing required column
+    let batch = required_peaks_record_batch(vec![0, 0]);
+    let truncated = batch.project(&[0, 1, 2, 3, 4, 5])?;
+    assert!(matches!(
+        writer.write_record_batch(truncated),
+        Err(WriterError::InvalidData(_))
+    ));
+
+    // Optional column with the wrong type
+    let mut fields = batch.schema().fields().to_vec();
+    fields.push(Arc::new(Field::new("precursor_mz", DataType::Float32, true)));
+    let mut columns = batch.columns().to_vec();
+    columns.push(Arc::new(Float32Array::from(vec![500.0, 500.0])) as ArrayRef);
+    let wrong_type = arrow::record_batch::RecordBatch::try_new(
+        Arc::new(arrow::datatypes::Schema::new(fields)),
+        columns,
+    )?;
+    assert!(matches!(
+        writer.write_record_batch(wrong_type),
+        Err(WriterError::InvalidData(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_enforce_sorted_mz_rejects_unsorted() -> Result<(), WriterError> {
+    let config = WriterConfig {
+        enforce_sorted_mz: true,
+        ..Default::default()
+    };
+    let buffer = Cursor::new(Vec::new());
+    let mut writer = MzPeakWriter::new(buffer, &MzPeakMetadata::new(), config)?;
+
+    // Order is only required within a spectrum; ties are allowed
+    let peaks = PeakArrays::new(vec![400.0, 500.0], vec![1.0, 1.0]);
+    writer.write_spectrum_arrays(&SpectrumArrays::new_ms1(0, 1, 1.0, 1, peaks))?;
+    let peaks = PeakArrays::new(vec![100.0, 100.0, 200.0], vec![1.0; 3]);
+    writer.write_spectrum_arrays(&SpectrumArrays::new_ms1(1, 2, 2.0, 1, peaks))?;
+
+    for (id, mz) in [
+        (2, vec![300.0, 200.0]),
+        (3, vec![-1.0, 200.0]),
+        (4, vec![f64::NAN]),
+    ] {
+        let peaks = PeakArrays::new(mz.clone(), vec![1.0; mz.len()]);
+        let spectrum = SpectrumArrays::new_ms1(id, id + 1, id as f32, 1, peaks);
+        assert!(matches!(
+            writer.write_spectrum_arrays(&spectrum),
+            Err(WriterError::InvalidData(_))
+        ));
+    }
+
+    // Spectrum 6 continues across the batch boundary with lower m/z
+    writer.write_record_batch(required_peaks_record_batch(vec![5, 5, 6]))?;
+    assert!(matches!(
+        writer.write_record_batch(required_peaks_record_batch(vec![6, 7, 7])),
+        Err(WriterError::InvalidData(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_write_spectra_drain_reuses_batch() -> Result<(), WriterError> {
+    use arrow::array::{Array, AsArray};
+    use arrow::datatypes::Float64Type;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let metadata = MzPeakMetadata::new();
+    let buffer = Cursor::new(Vec::new());
+    let mut writer = MzPeakWriter::new(buffer, &metadata, WriterConfig::default())?;
+
+    let ms1 = |id: i64| {
+        let peaks = PeakArrays::new(vec![100.0 + id as f64; 2], vec![1.0; 2]);
+        SpectrumArrays::new_ms1(id, id, 1.0, 1, peaks)
+    };
+    let ms2 = |id: i64| {
+        let peaks = PeakArrays::new(vec![200.0 + id as f64; 3], vec![1.0; 3]);
+        SpectrumArrays::new_ms2(id, id, 1.0, 1, 500.0, peaks)
+    };
+
+    // The second round has no precursors, so pooled buffers from the first
+    // round must not leak values into it
+    let mut batch = Vec::with_capacity(4);
+    let capacity = batch.capacity();
+    for round in [vec![ms1(0), ms2(1)], vec![ms1(2), ms1(3)], vec![ms2(4)]] {
+        batch.extend(round);
+        writer.write_spectra_drain(&mut batch)?;
+        assert!(batch.is_empty());
+        assert_eq!(batch.capacity(), capacity);
+    }
+    assert_eq!(writer.stats().spectra_written, 5);
+
+    let bytes = bytes::Bytes::from(writer.finish_into_inner()?.into_inner());
+    let batches: Vec<_> = ParquetRecordBatchReaderBuilder::try_new(bytes)?
+        .build()?
+        .collect::<Result<_, _>>()?;
+    let peaks = arrow::compute::concat_batches(&batches[0].schema(), &batches)?;
+
+    let mz = peaks.column_by_name("mz").unwrap().as_primitive::<Float64Type>();
+    let expected_mz = [
+        100.0, 100.0, 201.0, 201.0, 201.0, 102.0, 102.0, 103.0, 103.0, 204.0, 204.0, 204.0,
+    ];
+    assert_eq!(mz.values().to_vec(), expected_mz.to_vec());
+
+    let precursor_mz = peaks.column_by_name("precursor_mz").unwrap();
+    let valid: Vec<bool> = (0..precursor_mz.len()).map(|i| precursor_mz.is_valid(i)).collect();
+    let mut expected_valid = vec![false, false, true, true, true];
+    expected_valid.extend([false; 4]);
+    expected_valid.extend([true; 3]);
+    assert_eq!(valid, expected_valid);
+
+    Ok(())
+}
+
 #[test]
 fn test_owned_columnar_batch_as_columnar_batch() {
     // Test that we can borrow an OwnedColumnarBatch as a ColumnarBatch view
@@ -147,3 +371,65 @@ fn test_spectrum_v2_try_from_range_checks() {
     let result = SpectrumV2::try_from_spectrum_arrays(spectrum);
     assert!(result.is_err());
 }
+
+#[test]
+fn test_stats_snapshot_while_writing() {
+    use std::sync::Arc;
+
+    let progress = Arc::new(WriteProgress::new());
+    progress.set_expected_spectra(4);
+    let config = WriterConfig {
+        progress: Some(progress.clone()),
+        ..Default::default()
+    };
+    let mut writer = MzPeakWriter::new(Vec::new(), &MzPeakMetadata::new(), config).unwrap();
+    assert!(Arc::ptr_eq(&writer.progress(), &progress));
+
+    let spectra: Vec<SpectrumArrays> = (0..2)
+        .map(|i| {
+            SpectrumArrays::new_ms1(
+                i,
+                i + 1,
+                60.0 + i as f32,
+                1,
+                PeakArrays::new(vec![400.0, 500.0, 600.0], vec![1.0, 2.0, 3.0]),
+            )
+        })
+        .collect();
+    writer.write_spectra_arrays(&spectra).unwrap();
+
+    // Readable from another thread while the writer is open
+    let snapshot = std::thread::spawn(move || progress.snapshot())
+        .join()
+        .unwrap();
+    assert_eq!(snapshot.spectra_written, 2);
+    assert_eq!(snapshot.peaks_written, 6);
+    assert_eq!(snapshot.current_rt, Some(61.0));
+    assert_eq!(snapshot.fraction_complete, Some(0.5));
+    assert!(snapshot.estimated_remaining().is_some());
+    assert!(snapshot.to_string().starts_with("2/4 spectra, 6 peaks"));
+
+    writer
+        .write_spectrum_owned(SpectrumArrays::new_ms1(
+            2,
+            3,
+            62.0,
+            1,
+            PeakArrays::new(vec![400.0], vec![1.0]),
+        ))
+        .unwrap();
+    assert_eq!(writer.stats_snapshot().spectra_written, 3);
+    assert_eq!(writer.stats_snapshot().peaks_written, 7);
+}
+
+#[test]
+fn test_progress_estimates_from_rt() {
+    let progress = WriteProgress::new();
+    assert_eq!(progress.snapshot().fraction_complete, None);
+    progress.set_expected_rt(120.0);
+    progress.set_current_rt(30.0);
+    progress.record(10, 100, 0);
+    let snapshot = progress.snapshot();
+    assert_eq!(snapshot.fraction_complete, Some(0.25));
+    assert_eq!(snapshot.expected_spectra, None);
+}
@@ -5,7 +5,7 @@ use std::sync::Arc;
 
 use arrow::buffer::Buffer;
 use arrow::array::{
-    ArrayRef, Float32Array, Float64Array, Int16Array, Int32Array, Int64Array, Int8Array,
+    new_null_array, Array, ArrayRef, Float32Array, Float64Array, Int16Array, Int32Array, Int64Array, Int8Array,
     Float32Builder, Float64Builder, Int16Builder, Int32Builder, Int64Builder,
     Int8Builder,
 };
@@ -19,10 +19,14 @@ use rayon::prelude::*;
 use parquet::arrow::ArrowWriter;
 
 use crate::metadata::MzPeakMetadata;
-use crate::schema::create_mzpeak_schema_arc;
+use crate::schema::column_metadata::append_column_key_values;
+use crate::schema::mz_delta::{mz_delta_schema, MzDeltaEncoder};
+use crate::schema::{columns, create_mzpeak_schema_arc, validate_schema};
 
+use super::buffer_pool::ColumnBufferPool;
 use super::config::WriterConfig;
 use super::error::WriterError;
+use super::progress::{StatsSnapshot, WriteProgress};
 use super::stats::WriterStats;
 use super::types::{
     ColumnarBatch, OptionalColumn, OptionalColumnBuf, OwnedColumnarBatch, SpectrumArrays,
@@ -34,6 +38,20 @@ pub struct MzPeakWriter<W: Write + Send + Sync> {
     schema: Arc<arrow::datatypes::Schema>,
     spectra_written: usize,
     peaks_written: usize,
+    /// Spectrum ID of the last peak written by any write path, so
+    /// `write_record_batch` counts a spectrum continuing from an earlier
+    /// write only once and rejects IDs that go backwards
+    last_spectrum_id: Option<i64>,
+    /// Column buffers reclaimed from written batches
+    buffers: ColumnBufferPool,
+    /// Live counters shared with other threads
+    progress: Arc<WriteProgress>,
+    /// Spectra, peaks and bytes already added to `progress`
+    published: (usize, usize, usize),
+    /// Converts `mz` to `mz_delta` when sorted m/z is enforced
+    mz_delta: Option<MzDeltaEncoder>,
+    /// Spectrum ID and m/z of the last peak checked for sort order
+    last_mz: Option<(i64, f64)>,
 }
 
 impl MzPeakWriter<File> {
@@ -58,14 +76,31 @@ impl<W: Write + Send + Sync> MzPeakWriter<W> {
         let schema = create_mzpeak_schema_arc();
         let parquet_metadata = metadata.to_parquet_metadata()?;
         let props = config.to_writer_properties(&parquet_metadata);
+        let progress = config.progress.clone().unwrap_or_default();
 
-        let arrow_writer = ArrowWriter::try_new(writer, schema.clone(), Some(props))?;
+        let stored_schema = if config.enforce_sorted_mz {
+            Arc::new(mz_delta_schema(&schema))
+        } else {
+            schema.clone()
+        };
+        let mz_delta = config
+            .enforce_sorted_mz
+            .then(|| MzDeltaEncoder::new(stored_schema.clone()));
+
+        let mut arrow_writer = ArrowWriter::try_new(writer, stored_schema.clone(), Some(props))?;
+        append_column_key_values(&mut arrow_writer, &stored_schema);
 
         Ok(Self {
             writer: arrow_writer,
             schema,
             spectra_written: 0,
             peaks_written: 0,
+            last_spectrum_id: None,
+            buffers: ColumnBufferPool::default(),
+            progress,
+            published: (0, 0, 0),
+            mz_delta,
+            last_mz: None,
         })
     }
 
@@ -73,6 +108,82 @@ impl<W: Write + Send + Sync> MzPeakWriter<W> {
         self.peaks_written
     }
 
+    /// Live progress counters, readable from other threads while writing
+    pub fn progress(&self) -> Arc<WriteProgress> {
+        self.progress.clone()
+    }
+
+    /// Running statistics: throughput, current retention time and, when the
+    /// expected size is known, estimated completion
+    pub fn stats_snapshot(&self) -> StatsSnapshot {
+        self.progress.snapshot()
+    }
+
+    /// Hand a batch in the peaks schema to the Parquet writer, storing m/z as
+    /// per-spectrum deltas when sorted m/z is enforced
+    fn write_batch(&mut self, batch: &RecordBatch) -> Result<(), WriterError> {
+        if self.mz_delta.is_some() {
+            self.check_sorted_mz(batch)?;
+        }
+        match self.mz_delta.as_mut() {
+            Some(encoder) => self.writer.write(&encoder.encode(batch)?)?,
+            None => self.writer.write(batch)?,
+        }
+        let spectrum_ids = spectrum_ids(batch)?;
+        if !spectrum_ids.is_empty() {
+            self.last_spectrum_id = Some(spectrum_ids.value(spectrum_ids.len() - 1));
+        }
+        Ok(())
+    }
+
+    /// Check that m/z is non-negative and non-decreasing within each spectrum,
+    /// including spectra that continue from the previous batch
+    fn check_sorted_mz(&mut self, batch: &RecordBatch) -> Result<(), WriterError> {
+        let spectrum_ids = spectrum_ids(batch)?;
+        let mz_values = batch
+            .column_by_name(columns::MZ)
+            .and_then(|column| column.as_any().downcast_ref::<Float64Array>())
+            .ok_or_else(|| WriterError::InvalidData("mz is not Float64".to_string()))?;
+
+        for (&id, &mz) in spectrum_ids.values().iter().zip(mz_values.values().iter()) {
+            if !(mz.is_finite() && mz >= 0.0) {
+                return Err(WriterError::InvalidData(format!(
+                    "spectrum {} has m/z {}; sorted m/z must be finite and non-negative",
+                    id, mz
+                )));
+            }
+            if let Some((last_id, last_mz)) = self.last_mz {
+                if last_id == id && mz < last_mz {
+                    return Err(WriterError::InvalidData(format!(
+                        "spectrum {} is not sorted by m/z: {} follows {}",
+                        id, mz, last_mz
+                    )));
+                }
+            }
+            self.last_mz = Some((id, mz));
+        }
+        Ok(())
+    }
+
+    /// Add everything written since the last call to the progress counters
+    fn publish_progress(&mut self, batch: &RecordBatch) {
+        let bytes = self.writer.bytes_written();
+        let (spectra, peaks, published_bytes) = self.published;
+        self.progress.record(
+            self.spectra_written.saturating_sub(spectra) as u64,
+            self.peaks_written.saturating_sub(peaks) as u64,
+            bytes.saturating_sub(published_bytes) as u64,
+        );
+        self.published = (self.spectra_written, self.peaks_written, bytes);
+
+        let retention_times = batch
+            .column_by_name(columns::RETENTION_TIME)
+            .and_then(|column| column.as_any().downcast_ref::<Float32Array>());
+        if let Some(rt) = retention_times.filter(|rts| !rts.is_empty()) {
+            self.progress.set_current_rt(rt.value(rt.len() - 1));
+        }
+    }
+
     // ========================================================================
     // Vectorized Array Builder Helpers
     // ========================================================================
@@ -477,8 +588,10 @@ impl<W: Write + Send + Sync> MzPeakWriter<W> {
         ];
 
         let record_batch = RecordBatch::try_new(self.schema.clone(), arrays)?;
-        self.writer.write(&record_batch)?;
+        self.write_batch(&record_batch)?;
         self.peaks_written += num_peaks;
+        self.publish_progress(&record_batch);
+        self.buffers.reclaim(record_batch);
 
         Ok(())
     }
@@ -627,8 +740,114 @@ impl<W: Write + Send + Sync> MzPeakWriter<W> {
         ];
 
         let record_batch = RecordBatch::try_new(self.schema.clone(), arrays)?;
-        self.writer.write(&record_batch)?;
+        self.write_batch(&record_batch)?;
         self.peaks_written += num_peaks;
+        self.publish_progress(&record_batch);
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // Arrow-Native Record Batch Writing
+    // ========================================================================
+
+    /// Align a record batch that is already in the peaks schema with the writer schema.
+    ///
+    /// Required columns must be present with the exact mzPeak types. Optional
+    /// columns may be omitted (they are written as nulls), but if present their
+    /// types must match. Columns that are not part of the schema are rejected.
+    fn conform_record_batch(&self, batch: &RecordBatch) -> Result<RecordBatch, WriterError> {
+        let input_schema = batch.schema();
+        validate_schema(&input_schema).map_err(|e| WriterError::InvalidData(e.to_string()))?;
+
+        if let Some(field) = input_schema
+            .fields()
+            .iter()
+            .find(|field| self.schema.field_with_name(field.name()).is_err())
+        {
+            return Err(WriterError::InvalidData(format!(
+                "Column '{}' is not part of the mzPeak peaks schema",
+                field.name()
+            )));
+        }
+
+        let arrays = self
+            .schema
+            .fields()
+            .iter()
+            .map(|field| match batch.column_by_name(field.name()) {
+                Some(column) if column.data_type() == field.data_type() => Ok(column.clone()),
+                Some(column) => Err(WriterError::InvalidData(format!(
+                    "Column '{}' has type {:?}, expected {:?}",
+                    field.name(),
+                    column.data_type(),
+                    field.data_type()
+                ))),
+                None => Ok(new_null_array(field.data_type(), batch.num_rows())),
+            })
+            .collect::<Result<Vec<ArrayRef>, WriterError>>()?;
+
+        Ok(RecordBatch::try_new(self.schema.clone(), arrays)?)
+    }
+
+    /// Write a record batch that is already in the mzPeak peaks schema.
+    ///
+    /// This is the zero-conversion path for Arrow-native producers (e.g. search
+    /// engines or IPC streams). Column buffers are passed to the Parquet writer
+    /// as-is; only missing optional columns are materialized as null arrays.
+    /// Batches larger than the configured row group size are split into
+    /// multiple row groups by the underlying Parquet writer.
+    ///
+    /// Rows must be sorted by ascending `spectrum_id`, within and across
+    /// batches, so all peaks of a spectrum are adjacent. A spectrum may
+    /// continue across batch boundaries, and from the last spectrum written
+    /// by any other write path; it is counted once. Only the last spectrum ID
+    /// is kept, so the check needs constant memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns `WriterError::InvalidData` if a required column is missing, a
+    /// column has the wrong type, the batch contains columns outside the
+    /// peaks schema, or a spectrum ID is lower than the one before it. A
+    /// rejected batch is not written.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// let reader = arrow::ipc::reader::StreamReader::try_new(stdin, None)?;
+    /// for batch in reader {
+    ///     writer.write_record_batch(batch?)?;
+    /// }
+    /// ```
+    pub fn write_record_batch(&mut self, batch: RecordBatch) -> Result<(), WriterError> {
+        let num_peaks = batch.num_rows();
+        if num_peaks == 0 {
+            return Ok(());
+        }
+
+        let batch = self.conform_record_batch(&batch)?;
+
+        let mut last = self.last_spectrum_id;
+        let mut started = 0;
+        for &id in spectrum_ids(&batch)?.values().iter() {
+            match last {
+                Some(previous) if previous == id => continue,
+                Some(previous) if previous > id => {
+                    return Err(WriterError::InvalidData(format!(
+                        "spectrum {} follows spectrum {}; sort rows by ascending spectrum_id",
+                        id, previous
+                    )));
+                }
+                _ => {}
+            }
+            started += 1;
+            last = Some(id);
+        }
+
+        self.write_batch(&batch)?;
+        self.peaks_written += num_peaks;
+        self.spectra_written += started;
+        self.publish_progress(&batch);
 
         Ok(())
     }
@@ -638,7 +857,19 @@ impl<W: Write + Send + Sync> MzPeakWriter<W> {
     /// This creates ONE RecordBatch for all spectra instead of one per spectrum.
     pub fn write_spectra_owned(
         &mut self,
-        spectra: Vec<SpectrumArrays>,
+        mut spectra: Vec<SpectrumArrays>,
+    ) -> Result<(), WriterError> {
+        self.write_spectra_drain(&mut spectra)
+    }
+
+    /// Write and remove all spectra from `spectra`, keeping its capacity.
+    ///
+    /// Behaves like [`write_spectra_owned`](Self::write_spectra_owned), but
+    /// lets a converter refill the same batch vector. The merged column
+    /// buffers are reclaimed after each write and reused for the next batch.
+    pub fn write_spectra_drain(
+        &mut self,
+        spectra: &mut Vec<SpectrumArrays>,
     ) -> Result<(), WriterError> {
         if spectra.is_empty() {
             return Ok(());
@@ -646,95 +877,96 @@ impl<W: Write + Send + Sync> MzPeakWriter<W> {
 
         let total_peaks: usize = spectra.iter().map(|s| s.peak_count()).sum();
         if total_peaks == 0 {
+            spectra.clear();
             return Ok(());
         }
 
-        // Pre-allocate all buffers for the merged batch
-        let mut mz_buf: Vec<f64> = Vec::with_capacity(total_peaks);
-        let mut intensity_buf: Vec<f32> = Vec::with_capacity(total_peaks);
-        let mut spectrum_id_buf: Vec<i64> = Vec::with_capacity(total_peaks);
-        let mut scan_number_buf: Vec<i64> = Vec::with_capacity(total_peaks);
-        let mut ms_level_buf: Vec<i16> = Vec::with_capacity(total_peaks);
-        let mut retention_time_buf: Vec<f32> = Vec::with_capacity(total_peaks);
-        let mut polarity_buf: Vec<i8> = Vec::with_capacity(total_peaks);
+        // Take all buffers for the merged batch from the pool
+        let mut mz_buf = self.buffers.f64s.take(total_peaks);
+        let mut intensity_buf = self.buffers.f32s.take(total_peaks);
+        let mut spectrum_id_buf = self.buffers.i64s.take(total_peaks);
+        let mut scan_number_buf = self.buffers.i64s.take(total_peaks);
+        let mut ms_level_buf = self.buffers.i16s.take(total_peaks);
+        let mut retention_time_buf = self.buffers.f32s.take(total_peaks);
+        let mut polarity_buf = self.buffers.i8s.take(total_peaks);
 
         // Ion mobility (per-peak optional) - track has_any AND all_valid to avoid O(n) scans
-        let mut ion_mobility_buf: Vec<f64> = Vec::with_capacity(total_peaks);
-        let mut ion_mobility_valid: Vec<bool> = Vec::with_capacity(total_peaks);
+        let mut ion_mobility_buf = self.buffers.f64s.take(total_peaks);
+        let mut ion_mobility_valid = self.buffers.bools.take(total_peaks);
         let mut has_any_ion_mobility = false;
         let mut all_valid_ion_mobility = true;
 
         // Optional spectrum-level columns - track has_any (Some seen) and all_valid (no None seen)
         // This avoids O(n) validity bitmap scans on 12M+ element arrays
-        let mut precursor_mz_buf: Vec<f64> = Vec::with_capacity(total_peaks);
-        let mut precursor_mz_valid: Vec<bool> = Vec::with_capacity(total_peaks);
+        let mut precursor_mz_buf = self.buffers.f64s.take(total_peaks);
+        let mut precursor_mz_valid = self.buffers.bools.take(total_peaks);
         let mut has_any_precursor_mz = false;
         let mut all_valid_precursor_mz = true;
 
-        let mut precursor_charge_buf: Vec<i16> = Vec::with_capacity(total_peaks);
-        let mut precursor_charge_valid: Vec<bool> = Vec::with_capacity(total_peaks);
+        let mut precursor_charge_buf = self.buffers.i16s.take(total_peaks);
+        let mut precursor_charge_valid = self.buffers.bools.take(total_peaks);
         let mut has_any_precursor_charge = false;
         let mut all_valid_precursor_charge = true;
 
-        let mut precursor_intensity_buf: Vec<f32> = Vec::with_capacity(total_peaks);
-        let mut precursor_intensity_valid: Vec<bool> = Vec::with_capacity(total_peaks);
+        let mut precursor_intensity_buf = self.buffers.f32s.take(total_peaks);
+        let mut precursor_intensity_valid = self.buffers.bools.take(total_peaks);
         let mut has_any_precursor_intensity = false;
         let mut all_valid_precursor_intensity = true;
 
-        let mut isolation_lower_buf: Vec<f32> = Vec::with_capacity(total_peaks);
-        let mut isolation_lower_valid: Vec<bool> = Vec::with_capacity(total_peaks);
+        let mut isolation_lower_buf = self.buffers.f32s.take(total_peaks);
+        let mut isolation_lower_valid = self.buffers.bools.take(total_peaks);
         let mut has_any_isolation_lower = false;
         let mut all_valid_isolation_lower = true;
 
-        let mut isolation_upper_buf: Vec<f32> = Vec::with_capacity(total_peaks);
-        let mut isolation_upper_valid: Vec<bool> = Vec::with_capacity(total_peaks);
+        let mut isolation_upper_buf = self.buffers.f32s.take(total_peaks);
+        let mut isolation_upper_valid = self.buffers.bools.take(total_peaks);
         let mut has_any_isolation_upper = false;
         let mut all_valid_isolation_upper = true;
 
-        let mut collision_energy_buf: Vec<f32> = Vec::with_capacity(total_peaks);
-        let mut collision_energy_valid: Vec<bool> = Vec::with_capacity(total_peaks);
+        let mut collision_energy_buf = self.buffers.f32s.take(total_peaks);
+        let mut collision_energy_valid = self.buffers.bools.take(total_peaks);
         let mut has_any_collision_energy = false;
         let mut all_valid_collision_energy = true;
 
-        let mut tic_buf: Vec<f64> = Vec::with_capacity(total_peaks);
-        let mut tic_valid: Vec<bool> = Vec::with_capacity(total_peaks);
+        let mut tic_buf = self.buffers.f64s.take(total_peaks);
+        let mut tic_valid = self.buffers.bools.take(total_peaks);
         let mut has_any_tic = false;
         let mut all_valid_tic = true;
 
-        let mut base_peak_mz_buf: Vec<f64> = Vec::with_capacity(total_peaks);
-        let mut base_peak_mz_valid: Vec<bool> = Vec::with_capacity(total_peaks);
+        let mut base_peak_mz_buf = self.buffers.f64s.take(total_peaks);
+        let mut base_peak_mz_valid = self.buffers.bools.take(total_peaks);
         let mut has_any_base_peak_mz = false;
         let mut all_valid_base_peak_mz = true;
 
-        let mut base_peak_intensity_buf: Vec<f32> = Vec::with_capacity(total_peaks);
-        let mut base_peak_intensity_valid: Vec<bool> = Vec::with_capacity(total_peaks);
+        let mut base_peak_intensity_buf = self.buffers.f32s.take(total_peaks);
+        let mut base_peak_intensity_valid = self.buffers.bools.take(total_peaks);
         let mut has_any_base_peak_intensity = false;
         let mut all_valid_base_peak_intensity = true;
 
-        let mut injection_time_buf: Vec<f32> = Vec::with_capacity(total_peaks);
-        let mut injection_time_valid: Vec<bool> = Vec::with_capacity(total_peaks);
+        let mut injection_time_buf = self.buffers.f32s.take(total_peaks);
+        let mut injection_time_valid = self.buffers.bools.take(total_peaks);
         let mut has_any_injection_time = false;
         let mut all_valid_injection_time = true;
 
-        let mut pixel_x_buf: Vec<i32> = Vec::with_capacity(total_peaks);
-        let mut pixel_x_valid: Vec<bool> = Vec::with_capacity(total_peaks);
+        let mut pixel_x_buf = self.buffers.i32s.take(total_peaks);
+        let mut pixel_x_valid = self.buffers.bools.take(total_peaks);
         let mut has_any_pixel_x = false;
         let mut all_valid_pixel_x = true;
 
-        let mut pixel_y_buf: Vec<i32> = Vec::with_capacity(total_peaks);
-        let mut pixel_y_valid: Vec<bool> = Vec::with_capacity(total_peaks);
+        let mut pixel_y_buf = self.buffers.i32s.take(total_peaks);
+        let mut pixel_y_valid = self.buffers.bools.take(total_peaks);
         let mut has_any_pixel_y = false;
         let mut all_valid_pixel_y = true;
 
-        let mut pixel_z_buf: Vec<i32> = Vec::with_capacity(total_peaks);
-        let mut pixel_z_valid: Vec<bool> = Vec::with_capacity(total_peaks);
+        let mut pixel_z_buf = self.buffers.i32s.take(total_peaks);
+        let mut pixel_z_valid = self.buffers.bools.take(total_peaks);
         let mut has_any_pixel_z = false;
         let mut all_valid_pixel_z = true;
 
         let spectra_len = spectra.len();
 
         // Merge all spectra into one batch - consuming ownership
-        for spectrum in spectra {
+        for spectrum in spectra.drain(..) {
             let num_peaks = spectrum.peak_count();
             if num_peaks == 0 {
                 continue;
@@ -855,11 +1087,16 @@ impl<W: Write + Send + Sync> MzPeakWriter<W> {
         // Helper to create OptionalColumnBuf from owned buffers
         // CRITICAL: Uses pre-computed all_valid flag instead of O(n) .iter().all() scan
         // This eliminates ~4 billion boolean comparisons on large batches
+        // Unused value and validity buffers go straight back to the pool
         macro_rules! make_optional_owned {
-            ($buf:ident, $valid:ident, $has_any:ident, $all_valid:ident) => {
+            ($buf:ident, $valid:ident, $has_any:ident, $all_valid:ident, $stack:ident) => {
                 if !$has_any {
-                    OptionalColumnBuf::AllNull { len: $buf.len() }
+                    let len = $buf.len();
+                    self.buffers.$stack.give($buf);
+                    self.buffers.bools.give($valid);
+                    OptionalColumnBuf::AllNull { len }
                 } else if $all_valid {
+                    self.buffers.bools.give($valid);
                     OptionalColumnBuf::AllPresent($buf)
                 } else {
                     OptionalColumnBuf::WithValidity {
@@ -879,26 +1116,25 @@ impl<W: Write + Send + Sync> MzPeakWriter<W> {
             ms_level: ms_level_buf,
             retention_time: retention_time_buf,
             polarity: polarity_buf,
-            ion_mobility: make_optional_owned!(ion_mobility_buf, ion_mobility_valid, has_any_ion_mobility, all_valid_ion_mobility),
-            precursor_mz: make_optional_owned!(precursor_mz_buf, precursor_mz_valid, has_any_precursor_mz, all_valid_precursor_mz),
-            precursor_charge: make_optional_owned!(precursor_charge_buf, precursor_charge_valid, has_any_precursor_charge, all_valid_precursor_charge),
-            precursor_intensity: make_optional_owned!(precursor_intensity_buf, precursor_intensity_valid, has_any_precursor_intensity, all_valid_precursor_intensity),
-            isolation_window_lower: make_optional_owned!(isolation_lower_buf, isolation_lower_valid, has_any_isolation_lower, all_valid_isolation_lower),
-            isolation_window_upper: make_optional_owned!(isolation_upper_buf, isolation_upper_valid, has_any_isolation_upper, all_valid_isolation_upper),
-            collision_energy: make_optional_owned!(collision_energy_buf, collision_energy_valid, has_any_collision_energy, all_valid_collision_energy),
-            total_ion_current: make_optional_owned!(tic_buf, tic_valid, has_any_tic, all_valid_tic),
-            base_peak_mz: make_optional_owned!(base_peak_mz_buf, base_peak_mz_valid, has_any_base_peak_mz, all_valid_base_peak_mz),
-            base_peak_intensity: make_optional_owned!(base_peak_intensity_buf, base_peak_intensity_valid, has_any_base_peak_intensity, all_valid_base_peak_intensity),
-            injection_time: make_optional_owned!(injection_time_buf, injection_time_valid, has_any_injection_time, all_valid_injection_time),
-            pixel_x: make_optional_owned!(pixel_x_buf, pixel_x_valid, has_any_pixel_x, all_valid_pixel_x),
-            pixel_y: make_optional_owned!(pixel_y_buf, pixel_y_valid, has_any_pixel_y, all_valid_pixel_y),
-            pixel_z: make_optional_owned!(pixel_z_buf, pixel_z_valid, has_any_pixel_z, all_valid_pixel_z),
+            ion_mobility: make_optional_owned!(ion_mobility_buf, ion_mobility_valid, has_any_ion_mobility, all_valid_ion_mobility, f64s),
+            precursor_mz: make_optional_owned!(precursor_mz_buf, precursor_mz_valid, has_any_precursor_mz, all_valid_precursor_mz, f64s),
+            precursor_charge: make_optional_owned!(precursor_charge_buf, precursor_charge_valid, has_any_precursor_charge, all_valid_precursor_charge, i16s),
+            precursor_intensity: make_optional_owned!(precursor_intensity_buf, precursor_intensity_valid, has_any_precursor_intensity, all_valid_precursor_intensity, f32s),
+            isolation_window_lower: make_optional_owned!(isolation_lower_buf, isolation_lower_valid, has_any_isolation_lower, all_valid_isolation_lower, f32s),
+            isolation_window_upper: make_optional_owned!(isolation_upper_buf, isolation_upper_valid, has_any_isolation_upper, all_valid_isolation_upper, f32s),
+            collision_energy: make_optional_owned!(collision_energy_buf, collision_energy_valid, has_any_collision_energy, all_valid_collision_energy, f32s),
+            total_ion_current: make_optional_owned!(tic_buf, tic_valid, has_any_tic, all_valid_tic, f64s),
+            base_peak_mz: make_optional_owned!(base_peak_mz_buf, base_peak_mz_valid, has_any_base_peak_mz, all_valid_base_peak_mz, f64s),
+            base_peak_intensity: make_optional_owned!(base_peak_intensity_buf, base_peak_intensity_valid, has_any_base_peak_intensity, all_valid_base_peak_intensity, f32s),
+            injection_time: make_optional_owned!(injection_time_buf, injection_time_valid, has_any_injection_time, all_valid_injection_time, f32s),
+            pixel_x: make_optional_owned!(pixel_x_buf, pixel_x_valid, has_any_pixel_x, all_valid_pixel_x, i32s),
+            pixel_y: make_optional_owned!(pixel_y_buf, pixel_y_valid, has_any_pixel_y, all_valid_pixel_y, i32s),
+            pixel_z: make_optional_owned!(pixel_z_buf, pixel_z_valid, has_any_pixel_z, all_valid_pixel_z, i32s),
         };
 
         // Write the single merged batch
-        self.write_owned_batch(batch)?;
         self.spectra_written += spectra_len;
-        Ok(())
+        self.write_owned_batch(batch)
     }
 
     /// Write a single spectrum by transferring ownership of its peak arrays.
@@ -907,11 +1143,10 @@ impl<W: Write + Send + Sync> MzPeakWriter<W> {
     pub fn write_spectrum_owned(&mut self, spectrum: SpectrumArrays) -> Result<(), WriterError> {
         let peak_count = spectrum.peak_count();
         let batch = OwnedColumnarBatch::from_spectrum_arrays(spectrum);
-        self.write_owned_batch(batch)?;
         if peak_count > 0 {
             self.spectra_written += 1;
         }
-        Ok(())
+        self.write_owned_batch(batch)
     }
 
     /// Write a batch of spectra with SoA peak layout (Sequential Implementation)
@@ -1607,3 +1842,11 @@ impl<W: Write + Send + Sync> MzPeakWriter<W> {
         }
     }
 }
+
+/// Int64 `spectrum_id` column of a batch in the peaks schema
+fn spectrum_ids(batch: &RecordBatch) -> Result<&Int64Array, WriterError> {
+    batch
+        .column_by_name(columns::SPECTRUM_ID)
+        .and_then(|column| column.as_any().downcast_ref::<Int64Array>())
+        .ok_or_else(|| WriterError::InvalidData("spectrum_id is not Int64".to_string()))
+}
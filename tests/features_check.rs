@@ -0,0 +1,106 @@
+//! Feature matrix checks
+//!
+//! Every supported feature combination must compile on its own, in
+//! particular the reader-only build without default features. Checking runs
+//! `cargo check` once per combination, so it is ignored by default:
+//!
+//! ```text
+//! cargo test --test features_check -- --ignored
+//! ```
+//!
+//! See docs/FEATURES.md for the matrix.
+
+use std::path::Path;
+use std::process::Command;
+
+/// Combinations checked with `--no-default-features --features <combination>`
+const COMBINATIONS: &[&str] = &[
+    "",
+    "container",
+    "writer",
+    "colorized_output",
+    "mzml",
+    "mzml-parallel",
+    "cli",
+    "uring",
+    "plugins",
+    "cli,mzml-parallel,uring",
+];
+
+/// Combinations needing a vendor toolchain or large dependencies, checked
+/// with `MZPEAK_FEATURES_CHECK_ALL=1`
+const HEAVY_COMBINATIONS: &[&str] = &["tdf", "thermo", "datafusion"];
+
+fn manifest_dir() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Feature names declared in the `[features]` table of Cargo.toml
+fn declared_features() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let manifest = std::fs::read_to_string(manifest_dir().join("Cargo.toml"))?;
+    let mut in_features = false;
+    let mut features = Vec::new();
+    for line in manifest.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_features = line == "[features]";
+            continue;
+        }
+        if !in_features || line.starts_with('#') {
+            continue;
+        }
+        if let Some((name, _)) = line.split_once('=') {
+            let name = name.trim();
+            if !name.is_empty() && !name.starts_with('"') {
+                features.push(name.to_string());
+            }
+        }
+    }
+    Ok(features)
+}
+
+#[test]
+fn test_every_feature_is_documented() -> Result<(), Box<dyn std::error::Error>> {
+    let documented = std::fs::read_to_string(manifest_dir().join("docs/FEATURES.md"))?;
+    let features = declared_features()?;
+    assert!(features.iter().any(|feature| feature == "writer"));
+    for feature in features.iter().filter(|feature| *feature != "default") {
+        assert!(
+            documented.contains(&format!("| `{}` |", feature)),
+            "feature `{}` is missing from docs/FEATURES.md",
+            feature
+        );
+    }
+    Ok(())
+}
+
+#[test]
+#[ignore = "runs cargo check once per feature combination"]
+fn test_feature_combinations_compile() -> Result<(), Box<dyn std::error::Error>> {
+    let mut combinations = COMBINATIONS.to_vec();
+    if std::env::var_os("MZPEAK_FEATURES_CHECK_ALL").is_some() {
+        combinations.extend(HEAVY_COMBINATIONS);
+    }
+
+    let mut failed = Vec::new();
+    for combination in combinations {
+        let status = Command::new(env!("CARGO"))
+            .current_dir(manifest_dir())
+            .args(["check", "--lib", "--quiet", "--no-default-features"])
+            .args(["--features", combination])
+            .status()?;
+        if !status.success() {
+            failed.push(if combination.is_empty() {
+                "(none)"
+            } else {
+                combination
+            });
+        }
+    }
+    assert!(
+        failed.is_empty(),
+        "failed to compile with features: {:?}",
+        failed
+    );
+    Ok(())
+}
@@ -1,3 +1,4 @@
+#![cfg(feature = "writer")]
 //! Integration tests for mzPeak
 //!
 //! These tests verify the full pipeline from data creation to reading.
@@ -1139,6 +1140,7 @@ fn test_all_footer_metadata_keys() {
         timestamp: Some("2024-01-15T10:30:00Z".to_string()),
         parameters: params,
         cv_params: Default::default(),
+        unknown_fields: Default::default(),
     });
     metadata.processing_history = Some(history);
 
@@ -1167,7 +1169,7 @@ fn test_all_footer_metadata_keys() {
     // 1. mzpeak:format_version (always present)
     let format_version = find_key("mzpeak:format_version");
     assert!(format_version.is_some(), "mzpeak:format_version missing");
-    assert_eq!(format_version.unwrap(), "1.0.0");
+    assert_eq!(format_version.unwrap(), "1.2.0");
 
     // 2. mzpeak:conversion_timestamp (always present)
     let timestamp = find_key("mzpeak:conversion_timestamp");
@@ -1265,6 +1267,7 @@ fn test_metadata_from_parquet_roundtrip() {
         timestamp: None,
         parameters: HashMap::new(),
         cv_params: Default::default(),
+        unknown_fields: Default::default(),
     });
     original.processing_history = Some(history);
 
@@ -1333,6 +1336,7 @@ fn test_processing_history_footer_serialization() {
         timestamp: Some("2024-01-15T10:00:00Z".to_string()),
         parameters: params1,
         cv_params: Default::default(),
+        unknown_fields: Default::default(),
     });
 
     // Second step: peak picking
@@ -1347,6 +1351,7 @@ fn test_processing_history_footer_serialization() {
         timestamp: Some("2024-01-15T09:00:00Z".to_string()),
         parameters: params2,
         cv_params: Default::default(),
+        unknown_fields: Default::default(),
     });
 
     metadata.processing_history = Some(history);
@@ -571,6 +571,51 @@ mod parallel_conversion_tests {
         // and compression, but the data content should be equivalent
     }
 
+    /// Test that scan filters skip the same spectra in both conversion modes
+    #[test]
+    fn test_scan_filters_sequential_and_parallel() {
+        use mzpeak::mzml::ScanFilters;
+        use mzpeak::reader::MzPeakReader;
+
+        let dir = tempdir().unwrap();
+        let input_path = dir.path().join("test.mzML");
+        fs::write(&input_path, create_test_mzml(50)).unwrap();
+
+        // MS1 spectra are every fifth scan, 0.5 s apart
+        let scan_filters = ScanFilters {
+            skip_ms_levels: [2].into_iter().collect(),
+            skip_native_id: Some(regex::Regex::new(r"^scan=46$").unwrap()),
+            min_retention_time: Some(5.0),
+            ..ScanFilters::default()
+        };
+        let config = ConversionConfig {
+            parallel_batch_size: 7,
+            scan_filters,
+            ..ConversionConfig::default()
+        };
+        let converter = MzMLConverter::with_config(config);
+
+        let seq_output = dir.path().join("sequential.mzpeak");
+        let par_output = dir.path().join("parallel.mzpeak");
+        let seq_stats = converter.convert(&input_path, &seq_output).unwrap();
+        let par_stats = converter.convert_parallel(&input_path, &par_output).unwrap();
+
+        for (stats, output) in [(seq_stats, seq_output), (par_stats, par_output)] {
+            assert_eq!(stats.spectra_count, 7);
+            assert_eq!(stats.skipped_spectra.ms_level, 40);
+            assert_eq!(stats.skipped_spectra.retention_time, 2);
+            assert_eq!(stats.skipped_spectra.native_id, 1);
+
+            // Kept spectra are renumbered contiguously and keep their scan numbers
+            let reader = MzPeakReader::open(&output).unwrap();
+            let scans: Vec<i64> = (0..7)
+                .map(|id| reader.get_spectrum_arrays(id).unwrap().unwrap().scan_number)
+                .collect();
+            assert_eq!(scans, vec![11, 16, 21, 26, 31, 36, 41]);
+            assert!(reader.get_spectrum_arrays(7).unwrap().is_none());
+        }
+    }
+
     /// Test parallel conversion with various batch sizes
     #[test]
     fn test_parallel_batch_sizes() {
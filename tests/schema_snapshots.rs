@@ -0,0 +1,259 @@
+#![cfg(feature = "writer")]
+//! Golden schema snapshots of every write path
+//!
+//! Each writer's Parquet schema, Arrow schema (with field metadata) and footer
+//! keys are rendered to text and compared against `tests/snapshots/schema/`.
+//! Any difference fails the test, so schema changes cannot slip in unnoticed.
+//!
+//! Intentional changes follow the procedure in `docs/SCHEMA_CHANGES.md`:
+//! bump the version that governs the table (`MZPEAK_FORMAT_VERSION` for v1
+//! tables, `MZPEAK_V2_SCHEMA_VERSION` for v2 container tables), add a
+//! `## <version>` migration entry to that document, then regenerate with
+//!
+//! ```text
+//! MZPEAK_UPDATE_SCHEMA_SNAPSHOTS=1 cargo test --test schema_snapshots
+//! ```
+//!
+//! Regeneration is refused while the version or the migration entry is
+//! missing.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use bytes::Bytes;
+use parquet::arrow::parquet_to_arrow_schema;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::schema::printer::print_schema;
+
+use mzpeak::chromatogram_writer::{ChromatogramWriter, ChromatogramWriterConfig};
+use mzpeak::metadata::MzPeakMetadata;
+use mzpeak::mobilogram_writer::{MobilogramWriter, MobilogramWriterConfig};
+use mzpeak::scan_diagnostics_writer::{ScanDiagnosticsWriter, ScanDiagnosticsWriterConfig};
+use mzpeak::schema::{MZPEAK_FORMAT_VERSION, MZPEAK_V2_SCHEMA_VERSION};
+use mzpeak::writer::{
+    MzPeakWriter, PeaksWriterV2, PeaksWriterV2Config, SpectraWriter, SpectraWriterConfig,
+    WriterConfig,
+};
+
+/// Environment variable that allows regenerating snapshots
+const UPDATE_ENV: &str = "MZPEAK_UPDATE_SCHEMA_SNAPSHOTS";
+
+/// Migration log that must describe every version bump
+const CHANGES_DOC: &str = "docs/SCHEMA_CHANGES.md";
+
+/// Footer keys left out of the key list (the Arrow schema is rendered field by field)
+const IGNORED_FOOTER_KEYS: [&str; 1] = ["ARROW:schema"];
+
+fn snapshot_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots/schema")
+}
+
+/// Render the schema-relevant parts of a Parquet file as stable text
+fn render_schema(bytes: Vec<u8>) -> Result<String, Box<dyn Error>> {
+    let reader = SerializedFileReader::new(Bytes::from(bytes))?;
+    let file_metadata = reader.metadata().file_metadata();
+
+    let mut parquet_schema = Vec::new();
+    print_schema(&mut parquet_schema, file_metadata.schema());
+    let mut text = String::from("## Parquet schema\n");
+    text.push_str(&String::from_utf8(parquet_schema)?);
+
+    text.push_str("\n## Arrow fields\n");
+    let arrow = parquet_to_arrow_schema(
+        file_metadata.schema_descr(),
+        file_metadata.key_value_metadata(),
+    )?;
+    for field in arrow.fields() {
+        text.push_str(&format!(
+            "{}: {:?}{}\n",
+            field.name(),
+            field.data_type(),
+            if field.is_nullable() {
+                " (nullable)"
+            } else {
+                ""
+            }
+        ));
+        let metadata: BTreeMap<_, _> = field.metadata().iter().collect();
+        for (key, value) in metadata {
+            text.push_str(&format!("    {} = {}\n", key, value));
+        }
+    }
+
+    text.push_str("\n## Footer keys\n");
+    let mut keys: Vec<&str> = file_metadata
+        .key_value_metadata()
+        .map(|kv| kv.iter().map(|kv| kv.key.as_str()).collect())
+        .unwrap_or_default();
+    keys.retain(|key| !IGNORED_FOOTER_KEYS.contains(key));
+    keys.sort_unstable();
+    keys.dedup();
+    for key in keys {
+        text.push_str(key);
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+/// Compare `rendered` against the golden snapshot of `table`
+fn check_snapshot(table: &str, version: &str, rendered: &str) -> Result<(), Box<dyn Error>> {
+    let path = snapshot_dir().join(format!("{}.txt", table));
+    let header = format!("# {} (schema version {})\n", table, version);
+    let expected = std::fs::read_to_string(&path).ok();
+    let golden_version = expected.as_deref().and_then(|golden| {
+        golden
+            .lines()
+            .next()?
+            .rsplit_once("(schema version ")?
+            .1
+            .strip_suffix(')')
+            .map(str::to_string)
+    });
+    let golden_body = expected
+        .as_deref()
+        .map(|golden| golden.split_once('\n').map_or("", |(_, body)| body));
+
+    if golden_body == Some(rendered) {
+        return Ok(());
+    }
+
+    let update = std::env::var_os(UPDATE_ENV).is_some();
+    if update {
+        if let Some(golden_version) = &golden_version {
+            assert_ne!(
+                golden_version, version,
+                "the {} schema changed but its schema version is still {}; bump it before \
+                 regenerating the snapshot (see {})",
+                table, version, CHANGES_DOC
+            );
+            let changes =
+                std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join(CHANGES_DOC))
+                    .unwrap_or_default();
+            assert!(
+                changes
+                    .lines()
+                    .any(|line| line.trim() == format!("## {}", version)),
+                "{} has no `## {}` migration entry; describe the {} schema change before \
+                 regenerating the snapshot",
+                CHANGES_DOC,
+                version,
+                table
+            );
+        }
+        std::fs::create_dir_all(snapshot_dir())?;
+        std::fs::write(&path, format!("{}{}", header, rendered))?;
+        return Ok(());
+    }
+
+    match golden_body {
+        None => panic!(
+            "no schema snapshot for {} at {}; run with {}=1 to create it",
+            table,
+            path.display(),
+            UPDATE_ENV
+        ),
+        Some(golden) => panic!(
+            "the {} schema differs from its golden snapshot {}.\n\
+             If the change is intentional: bump the schema version, add a migration entry to {} \
+             and regenerate with {}=1.\n\n--- expected\n{}\n+++ actual\n{}",
+            table,
+            path.display(),
+            CHANGES_DOC,
+            UPDATE_ENV,
+            golden,
+            rendered
+        ),
+    }
+}
+
+#[test]
+fn snapshot_v1_peaks_schema() -> Result<(), Box<dyn Error>> {
+    // Both peak layouts share the table version: plain `mz` and the
+    // `mz_delta` layout written with `enforce_sorted_mz`
+    let mut rendered = String::new();
+    for enforce_sorted_mz in [false, true] {
+        let config = WriterConfig {
+            enforce_sorted_mz,
+            ..Default::default()
+        };
+        let writer = MzPeakWriter::new(Vec::new(), &MzPeakMetadata::new(), config)?;
+        let bytes = writer.finish_into_inner()?;
+        rendered.push_str(&format!("\n# enforce_sorted_mz = {}\n", enforce_sorted_mz));
+        rendered.push_str(&render_schema(bytes)?);
+    }
+    check_snapshot("peaks_v1", MZPEAK_FORMAT_VERSION, &rendered)
+}
+
+#[test]
+fn snapshot_chromatograms_schema() -> Result<(), Box<dyn Error>> {
+    let writer = ChromatogramWriter::new(
+        Vec::new(),
+        &MzPeakMetadata::new(),
+        ChromatogramWriterConfig::default(),
+    )?;
+    let bytes = writer.finish_into_inner()?;
+    check_snapshot(
+        "chromatograms",
+        MZPEAK_FORMAT_VERSION,
+        &render_schema(bytes)?,
+    )
+}
+
+#[test]
+fn snapshot_mobilograms_schema() -> Result<(), Box<dyn Error>> {
+    let writer = MobilogramWriter::new(
+        Vec::new(),
+        &MzPeakMetadata::new(),
+        MobilogramWriterConfig::default(),
+    )?;
+    let bytes = writer.finish_into_inner()?;
+    check_snapshot("mobilograms", MZPEAK_FORMAT_VERSION, &render_schema(bytes)?)
+}
+
+#[test]
+fn snapshot_scan_diagnostics_schema() -> Result<(), Box<dyn Error>> {
+    let writer = ScanDiagnosticsWriter::new(
+        Vec::new(),
+        &MzPeakMetadata::new(),
+        ScanDiagnosticsWriterConfig::default(),
+    )?;
+    let bytes = writer.finish_into_inner()?;
+    check_snapshot(
+        "scan_diagnostics",
+        MZPEAK_FORMAT_VERSION,
+        &render_schema(bytes)?,
+    )
+}
+
+#[test]
+fn snapshot_v2_spectra_schema() -> Result<(), Box<dyn Error>> {
+    // With and without the optional `spectrum_checksum` column
+    let mut rendered = String::new();
+    for spectrum_checksums in [false, true] {
+        let config = SpectraWriterConfig {
+            spectrum_checksums,
+            ..Default::default()
+        };
+        let writer = SpectraWriter::new(Cursor::new(Vec::new()), &config)?;
+        let bytes = writer.finish_into_inner()?.into_inner();
+        rendered.push_str(&format!("\n# spectrum_checksums = {}\n", spectrum_checksums));
+        rendered.push_str(&render_schema(bytes)?);
+    }
+    check_snapshot("spectra_v2", MZPEAK_V2_SCHEMA_VERSION, &rendered)
+}
+
+#[test]
+fn snapshot_v2_peaks_schema() -> Result<(), Box<dyn Error>> {
+    for (table, has_ion_mobility) in [("peaks_v2", false), ("peaks_v2_ion_mobility", true)] {
+        let writer = PeaksWriterV2::new(
+            Vec::new(),
+            &PeaksWriterV2Config::default(),
+            has_ion_mobility,
+        )?;
+        let bytes = writer.finish_into_inner()?;
+        check_snapshot(table, MZPEAK_V2_SCHEMA_VERSION, &render_schema(bytes)?)?;
+    }
+    Ok(())
+}
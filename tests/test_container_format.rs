@@ -1,3 +1,4 @@
+#![cfg(feature = "writer")]
 //! Integration tests for .mzpeak ZIP container format
 //!
 //! These tests verify: